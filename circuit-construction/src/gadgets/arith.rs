@@ -0,0 +1,128 @@
+//! Native field arithmetic gadgets, expressed with generic gates.
+
+use crate::writer::{Cs, Var};
+use ark_ff::PrimeField;
+use kimchi::circuits::polynomials::generic::{GENERIC_COEFFS, GENERIC_REGISTERS};
+
+/// Index of the multiplication coefficient in a generic gate.
+const MUL_COEFF: usize = GENERIC_REGISTERS;
+
+/// Index of the constant coefficient in a generic gate.
+const CONST_COEFF: usize = GENERIC_REGISTERS + 1;
+
+/// Arithmetic gadgets over the native field.
+pub trait Arith<F: PrimeField>: Cs<F> {
+    /// Returns `a * b`.
+    fn mul(&mut self, a: Var<F>, b: Var<F>) -> Var<F> {
+        let res = self.var(|| a.val() * b.val());
+
+        let mut coeffs = [F::zero(); GENERIC_COEFFS];
+        coeffs[2] = -F::one();
+        coeffs[MUL_COEFF] = F::one();
+        self.generic(coeffs, [Some(a), Some(b), Some(res)]);
+
+        res
+    }
+
+    /// Returns the inner product `sum_i a_i * b_i` of two vectors of the same length.
+    fn inner_product(&mut self, a: &[Var<F>], b: &[Var<F>]) -> Var<F> {
+        assert_eq!(a.len(), b.len());
+
+        let mut terms: Vec<_> = a
+            .iter()
+            .zip(b)
+            .map(|(a, b)| (F::one(), self.mul(*a, *b)))
+            .collect();
+
+        match terms.len() {
+            0 => self.constant(F::zero()),
+            1 => terms.pop().unwrap().1,
+            _ => self.linear_combination(&terms, F::zero()),
+        }
+    }
+
+    /// Returns `x^exp`, for a non-zero constant `exp`, by square-and-multiply.
+    fn pow(&mut self, x: Var<F>, exp: u64) -> Var<F> {
+        assert!(exp > 0);
+
+        let mut res = x;
+        for i in (0..63 - exp.leading_zeros()).rev() {
+            res = self.mul(res, res);
+            if (exp >> i) & 1 == 1 {
+                res = self.mul(res, x);
+            }
+        }
+        res
+    }
+
+    /// Constrains `b` to be either `0` or `1`, using `b * b - b = 0`.
+    fn assert_boolean(&mut self, b: Var<F>) {
+        let mut coeffs = [F::zero(); GENERIC_COEFFS];
+        coeffs[0] = -F::one();
+        coeffs[MUL_COEFF] = F::one();
+        self.generic(coeffs, [Some(b), Some(b), None]);
+    }
+
+    /// Returns the boolean `a | b = a + b - a * b`, for booleans `a` and `b`.
+    fn or(&mut self, a: Var<F>, b: Var<F>) -> Var<F> {
+        let res = self.var(|| a.val() + b.val() - a.val() * b.val());
+
+        let mut coeffs = [F::zero(); GENERIC_COEFFS];
+        coeffs[0] = F::one();
+        coeffs[1] = F::one();
+        coeffs[2] = -F::one();
+        coeffs[MUL_COEFF] = -F::one();
+        self.generic(coeffs, [Some(a), Some(b), Some(res)]);
+
+        res
+    }
+
+    /// Returns the linear combination `sum_i c_i * v_i + constant`.
+    fn linear_combination(&mut self, terms: &[(F, Var<F>)], constant: F) -> Var<F> {
+        let res = self.var(|| evaluate(terms, constant));
+
+        let mut terms = terms.to_vec();
+        terms.push((-F::one(), res));
+        self.assert_linear(&terms, constant);
+
+        res
+    }
+
+    /// Constrains the linear combination `sum_i c_i * v_i + constant` to be zero.
+    ///
+    /// Terms are accumulated two at a time, so `n` terms use `n - 2` generic gates.
+    fn assert_linear(&mut self, terms: &[(F, Var<F>)], constant: F) {
+        let mut coeffs = [F::zero(); GENERIC_COEFFS];
+        coeffs[CONST_COEFF] = constant;
+
+        if terms.len() <= GENERIC_REGISTERS {
+            let mut vars = [None; GENERIC_REGISTERS];
+            for (i, (c, v)) in terms.iter().enumerate() {
+                coeffs[i] = *c;
+                vars[i] = Some(*v);
+            }
+            self.generic(coeffs, vars);
+            return;
+        }
+
+        // acc = c_0 * v_0 + c_1 * v_1 + constant
+        let acc = self.var(|| evaluate(&terms[..2], constant));
+        coeffs[0] = terms[0].0;
+        coeffs[1] = terms[1].0;
+        coeffs[2] = -F::one();
+        self.generic(coeffs, [Some(terms[0].1), Some(terms[1].1), Some(acc)]);
+
+        let mut rest = vec![(F::one(), acc)];
+        rest.extend_from_slice(&terms[2..]);
+        self.assert_linear(&rest, F::zero());
+    }
+}
+
+impl<F: PrimeField, C: Cs<F>> Arith<F> for C {}
+
+/// Evaluates a linear combination on the values of its variables.
+pub(crate) fn evaluate<F: PrimeField>(terms: &[(F, Var<F>)], constant: F) -> F {
+    terms
+        .iter()
+        .fold(constant, |acc, (c, v)| acc + *c * v.val())
+}
@@ -0,0 +1,343 @@
+//! Arbitrary-size integer arithmetic.
+//!
+//! A [`BigIntVar`] is a non-negative integer given by any number of 88-bit limbs
+//! (in little-endian order), each of them range-checked, as for foreign field elements.
+//! Integer operations produce results with enough limbs to hold any possible value
+//! (e.g. `a * b` has as many limbs as `a` and `b` together), and modular operations
+//! produce canonical results with the limb count of the modulus.
+//!
+//! Relations are checked limb by limb with [`ForeignField::foreign_assert_zero`],
+//! with carries sized from the number of terms of each limb position:
+//! this is sound as long as these sums stay far below the native modulus,
+//! which is asserted when the circuit is built.
+
+use super::foreign_field::{ForeignField, ForeignVar};
+use crate::writer::Var;
+use ark_ff::{One, PrimeField, Zero};
+use num_bigint::BigUint;
+use o1_utils::{field_helpers::FieldFromBig, foreign_field::LIMB_BITS};
+
+/// Bit length of the carries of an addition or a comparison (shifted to be positive).
+const ADD_CARRY_BITS: usize = 3;
+
+/// The modulus of a modular operation, with the number of limbs of its canonical elements.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigModulus {
+    modulus: BigUint,
+    limbs: usize,
+}
+
+impl BigModulus {
+    /// Creates a modulus, whose elements have as few limbs as possible.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the modulus is smaller than 2.
+    pub fn new(modulus: BigUint) -> Self {
+        assert!(modulus > BigUint::one());
+        let limbs = (modulus.bits() as usize + LIMB_BITS - 1) / LIMB_BITS;
+        Self { modulus, limbs }
+    }
+
+    /// Creates a modulus from its big-endian bytes.
+    pub fn from_be(bytes: &[u8]) -> Self {
+        Self::new(BigUint::from_bytes_be(bytes))
+    }
+
+    /// Returns the modulus.
+    pub fn modulus(&self) -> &BigUint {
+        &self.modulus
+    }
+
+    /// Returns the number of limbs of the elements.
+    pub fn limbs(&self) -> usize {
+        self.limbs
+    }
+}
+
+/// A variable containing a non-negative integer, as 88-bit limbs (little-endian).
+#[derive(Debug, Clone)]
+pub struct BigIntVar<F> {
+    /// The limbs, in little-endian order
+    pub limbs: Vec<Var<F>>,
+}
+
+impl<F: PrimeField> BigIntVar<F> {
+    /// Returns the number of bits that the integer can hold.
+    pub fn bits(&self) -> usize {
+        LIMB_BITS * self.limbs.len()
+    }
+
+    /// Returns the value of the integer.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the variable has no value (i.e. in circuit mode).
+    pub fn val(&self) -> BigUint {
+        self.limbs.iter().rev().fold(BigUint::zero(), |acc, l| {
+            (acc << LIMB_BITS) + Into::<BigUint>::into(l.val())
+        })
+    }
+}
+
+impl<F: PrimeField> From<ForeignVar<F>> for BigIntVar<F> {
+    fn from(x: ForeignVar<F>) -> Self {
+        Self {
+            limbs: x.limbs.to_vec(),
+        }
+    }
+}
+
+/// Returns the `count` limbs of `x` as native field elements.
+///
+/// # Panics
+///
+/// Will panic if `x` does not fit in `count` limbs.
+fn limbs<F: PrimeField>(x: &BigUint, count: usize) -> Vec<F> {
+    assert!(x.bits() as usize <= LIMB_BITS * count, "too many bits");
+    let mask = (BigUint::one() << LIMB_BITS) - 1u32;
+    (0..count)
+        .map(|k| F::from_biguint((x >> (LIMB_BITS * k)) & &mask).unwrap())
+        .collect()
+}
+
+/// Returns the number of limbs of the quotient of an integer of `bits` bits by `m`.
+fn quotient_limbs(bits: usize, m: &BigModulus) -> usize {
+    // q < 2^bits / m <= 2^(bits - m.bits + 1)
+    let q_bits = (bits + 1).saturating_sub(m.modulus.bits() as usize).max(1);
+    (q_bits + LIMB_BITS - 1) / LIMB_BITS
+}
+
+/// Returns the bit length of the (shifted) carries of a relation with at most `terms`
+/// products of two limbs in each limb position.
+fn mul_carry_bits<F: PrimeField>(terms: usize) -> usize {
+    // |carry| < (terms + 1) 2^88
+    let bits = LIMB_BITS + (usize::BITS - terms.leading_zeros()) as usize + 2;
+    assert!(
+        LIMB_BITS + bits < F::size_in_bits() - 2,
+        "too many limbs for the native field"
+    );
+    bits
+}
+
+/// Big integer gadgets.
+///
+/// The `zero` variable taken by its functions is described
+/// in [the module documentation](super#the-zero-variable).
+pub trait BigInt<F: PrimeField>: ForeignField<F> {
+    /// Creates an integer of `count` limbs from the value returned by `g`,
+    /// and range-checks its limbs.
+    fn bigint_var<G>(&mut self, zero: Var<F>, count: usize, g: G) -> BigIntVar<F>
+    where
+        G: FnOnce() -> BigUint,
+    {
+        let x = self.bigint_limbs(count, g);
+        self.range_check_limbs(zero, &x.limbs);
+        x
+    }
+
+    /// Creates an integer of `count` limbs holding the constant `x`.
+    fn bigint_constant(&mut self, x: &BigUint, count: usize) -> BigIntVar<F> {
+        BigIntVar {
+            limbs: limbs::<F>(x, count)
+                .into_iter()
+                .map(|l| self.constant(l))
+                .collect(),
+        }
+    }
+
+    /// Returns `a + b`, with one more limb than the largest operand.
+    fn bigint_add(&mut self, zero: Var<F>, a: &BigIntVar<F>, b: &BigIntVar<F>) -> BigIntVar<F> {
+        let count = a.limbs.len().max(b.limbs.len()) + 1;
+        let r = self.bigint_var(zero, count, || a.val() + b.val());
+
+        // a + b - r = 0
+        let mut columns = vec![vec![]; count];
+        for (k, l) in a.limbs.iter().enumerate() {
+            columns[k].push((F::one(), *l));
+        }
+        for (k, l) in b.limbs.iter().enumerate() {
+            columns[k].push((F::one(), *l));
+        }
+        for (k, l) in r.limbs.iter().enumerate() {
+            columns[k].push((-F::one(), *l));
+        }
+        self.foreign_assert_zero(zero, columns, ADD_CARRY_BITS);
+
+        r
+    }
+
+    /// Returns `a * b`, with as many limbs as both operands together.
+    fn bigint_mul(&mut self, zero: Var<F>, a: &BigIntVar<F>, b: &BigIntVar<F>) -> BigIntVar<F> {
+        let count = a.limbs.len() + b.limbs.len();
+        let r = self.bigint_var(zero, count, || a.val() * b.val());
+
+        // a * b - r = 0
+        let mut columns = self.bigint_products(a, b, count);
+        for (k, l) in r.limbs.iter().enumerate() {
+            columns[k].push((-F::one(), *l));
+        }
+        let terms = a.limbs.len().min(b.limbs.len());
+        self.foreign_assert_zero(zero, columns, mul_carry_bits::<F>(terms));
+
+        r
+    }
+
+    /// Returns `a mod m`.
+    fn bigint_reduce(&mut self, zero: Var<F>, m: &BigModulus, a: &BigIntVar<F>) -> BigIntVar<F> {
+        let columns = a.limbs.iter().map(|l| vec![(F::one(), *l)]).collect();
+        self.bigint_assert_mod(zero, m, columns, a.bits(), || a.val())
+    }
+
+    /// Returns `a * b mod m`.
+    fn bigint_mul_mod(
+        &mut self,
+        zero: Var<F>,
+        m: &BigModulus,
+        a: &BigIntVar<F>,
+        b: &BigIntVar<F>,
+    ) -> BigIntVar<F> {
+        let columns = self.bigint_products(a, b, a.limbs.len() + b.limbs.len());
+        self.bigint_assert_mod(zero, m, columns, a.bits() + b.bits(), || a.val() * b.val())
+    }
+
+    /// Returns the boolean `a < b`.
+    fn bigint_less_than(&mut self, zero: Var<F>, a: &BigIntVar<F>, b: &BigIntVar<F>) -> Var<F> {
+        let count = a.limbs.len().max(b.limbs.len());
+        let lt = self.var(|| F::from(u64::from(a.val() < b.val())));
+        self.assert_boolean(lt);
+
+        // b - a - 1 + (1 - lt) 2^(88 count) - d = 0, with d of count limbs
+        let d = self.bigint_var(zero, count, || {
+            if a.val() < b.val() {
+                b.val() - a.val() - 1u32
+            } else {
+                (BigUint::one() << (LIMB_BITS * count)) + b.val() - a.val() - 1u32
+            }
+        });
+        let one = self.constant(F::one());
+        let mut columns = self.bigint_difference(a, b, &d);
+        columns.push(vec![(F::one(), one), (-F::one(), lt)]);
+        self.foreign_assert_zero(zero, columns, ADD_CARRY_BITS);
+
+        lt
+    }
+
+    /// Constrains `a < b`.
+    fn bigint_assert_lt(&mut self, zero: Var<F>, a: &BigIntVar<F>, b: &BigIntVar<F>) {
+        // b - a - 1 - d = 0, with d of as many limbs as the operands
+        let count = a.limbs.len().max(b.limbs.len());
+        let d = self.bigint_var(zero, count, || b.val() - a.val() - 1u32);
+        let columns = self.bigint_difference(a, b, &d);
+        self.foreign_assert_zero(zero, columns, ADD_CARRY_BITS);
+    }
+
+    /// Constrains `a = b`. The operands may have different limb counts.
+    fn bigint_assert_eq(&mut self, zero: Var<F>, a: &BigIntVar<F>, b: &BigIntVar<F>) {
+        let count = a.limbs.len().max(b.limbs.len());
+        for k in 0..count {
+            let x = a.limbs.get(k).copied().unwrap_or(zero);
+            let y = b.limbs.get(k).copied().unwrap_or(zero);
+            self.assert_eq(x, y);
+        }
+    }
+
+    /// Creates the limbs of an integer from the value returned by `g`,
+    /// without constraining them.
+    fn bigint_limbs<G>(&mut self, count: usize, g: G) -> BigIntVar<F>
+    where
+        G: FnOnce() -> BigUint,
+    {
+        assert!(count > 0);
+
+        let mut value = None;
+        let mut limbs = vec![self.var(|| {
+            let x = limbs::<F>(&g(), count);
+            let l0 = x[0];
+            value = Some(x);
+            l0
+        })];
+        for k in 1..count {
+            limbs.push(self.var(|| value.as_ref().unwrap()[k]));
+        }
+        BigIntVar { limbs }
+    }
+
+    /// Returns the `count` limb positions of the products of the limbs of `a` and `b`.
+    fn bigint_products(
+        &mut self,
+        a: &BigIntVar<F>,
+        b: &BigIntVar<F>,
+        count: usize,
+    ) -> Vec<Vec<(F, Var<F>)>> {
+        let mut columns = vec![vec![]; count];
+        for (i, x) in a.limbs.iter().enumerate() {
+            for (j, y) in b.limbs.iter().enumerate() {
+                let xy = self.mul(*x, *y);
+                columns[i + j].push((F::one(), xy));
+            }
+        }
+        columns
+    }
+
+    /// Returns the limb positions of `b - a - 1 - d`.
+    fn bigint_difference(
+        &mut self,
+        a: &BigIntVar<F>,
+        b: &BigIntVar<F>,
+        d: &BigIntVar<F>,
+    ) -> Vec<Vec<(F, Var<F>)>> {
+        let one = self.constant(F::one());
+        let mut columns = vec![vec![(-F::one(), one)]];
+        columns.resize(d.limbs.len(), vec![]);
+        for (k, l) in b.limbs.iter().enumerate() {
+            columns[k].push((F::one(), *l));
+        }
+        for (k, l) in a.limbs.iter().enumerate() {
+            columns[k].push((-F::one(), *l));
+        }
+        for (k, l) in d.limbs.iter().enumerate() {
+            columns[k].push((-F::one(), *l));
+        }
+        columns
+    }
+
+    /// Returns `r = x mod m`, where the integer `x` of at most `bits` bits is given
+    /// by its limb positions in `columns` and its value by `g`,
+    /// by constraining `x - q * m - r = 0` and `r < m`.
+    fn bigint_assert_mod<G>(
+        &mut self,
+        zero: Var<F>,
+        m: &BigModulus,
+        mut columns: Vec<Vec<(F, Var<F>)>>,
+        bits: usize,
+        g: G,
+    ) -> BigIntVar<F>
+    where
+        G: Fn() -> BigUint,
+    {
+        let q = self.bigint_var(zero, quotient_limbs(bits, m), || g() / m.modulus());
+        let r = self.bigint_var(zero, m.limbs, || g() % m.modulus());
+
+        let count = columns.len().max(q.limbs.len() + m.limbs);
+        columns.resize(count, vec![]);
+        let p = limbs::<F>(m.modulus(), m.limbs);
+        for (i, l) in q.limbs.iter().enumerate() {
+            for (j, c) in p.iter().enumerate() {
+                columns[i + j].push((-*c, *l));
+            }
+        }
+        for (k, l) in r.limbs.iter().enumerate() {
+            columns[k].push((-F::one(), *l));
+        }
+        let terms = columns.iter().map(Vec::len).max().unwrap();
+        self.foreign_assert_zero(zero, columns, mul_carry_bits::<F>(terms));
+
+        let modulus = self.bigint_constant(m.modulus(), m.limbs);
+        self.bigint_assert_lt(zero, &r, &modulus);
+
+        r
+    }
+}
+
+impl<F: PrimeField, C: ForeignField<F>> BigInt<F> for C {}
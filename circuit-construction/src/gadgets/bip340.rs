@@ -0,0 +1,243 @@
+//! BIP-340 (Taproot) Schnorr signature verification over secp256k1.
+//!
+//! A signature `(r, s)` of the message `m` under the public key `P` (given by its `x` coordinate,
+//! the `y` coordinate being the even one) is valid if the point `R = s G - e P`
+//! has an even `y` coordinate and the `x` coordinate `r`,
+//! where `e = SHA-256(SHA-256(tag) || SHA-256(tag) || r || P.x || m) mod n`
+//! with the tag `BIP0340/challenge`.
+//!
+//! Public keys, messages and signatures are given as big-endian 32-bit words,
+//! as they are hashed with the SHA-256 gadget. The first block of the tagged hash
+//! is a constant, so its compression is computed outside of the circuit.
+//! See <https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki>.
+
+use super::foreign_curve::{ForeignCurve, ForeignCurveGadgets, ForeignPoint};
+use super::foreign_field::ForeignVar;
+use super::sha256::{compress_native, sha256_native, to_words, Sha256, BLOCK_WORDS, IV};
+use super::uint32::U32;
+use crate::writer::Var;
+use ark_ff::PrimeField;
+use num_bigint::BigUint;
+
+/// The tag of the challenge hash
+pub const CHALLENGE_TAG: &[u8] = b"BIP0340/challenge";
+
+/// The tag of the hash masking the secret key with auxiliary randomness
+const AUX_TAG: &[u8] = b"BIP0340/aux";
+
+/// The tag of the nonce hash
+const NONCE_TAG: &[u8] = b"BIP0340/nonce";
+
+/// Computes `SHA-256(SHA-256(tag) || SHA-256(tag) || message)`, outside of the circuit.
+pub fn tagged_hash(tag: &[u8], message: &[u8]) -> [u8; 32] {
+    let tag_hash = sha256_native(tag);
+    let mut bytes = tag_hash.to_vec();
+    bytes.extend(tag_hash);
+    bytes.extend(message);
+    sha256_native(&bytes)
+}
+
+/// Serializes an integer of at most 256 bits in 32 big-endian bytes.
+fn to_bytes32(x: &BigUint) -> [u8; 32] {
+    let bytes = x.to_bytes_be();
+    assert!(bytes.len() <= 32);
+    let mut res = [0u8; 32];
+    res[32 - bytes.len()..].copy_from_slice(&bytes);
+    res
+}
+
+/// Returns the point with the `x` coordinate `x` and an even `y` coordinate, if any.
+///
+/// # Panics
+///
+/// Will panic if the base field modulus is not `3 mod 4`.
+pub fn lift_x(curve: &ForeignCurve, x: &BigUint) -> Option<ForeignPoint> {
+    let m = curve.base.modulus();
+    assert_eq!(m % 4u32, BigUint::from(3u32));
+    if x >= m {
+        return None;
+    }
+
+    let rhs = (x * x * x + &curve.a * x + &curve.b) % m;
+    let y = rhs.modpow(&((m + 1u32) >> 2), m);
+    if &y * &y % m != rhs {
+        return None;
+    }
+    let y = if y.bit(0) { m - y } else { y };
+    Some(ForeignPoint { x: x.clone(), y })
+}
+
+/// Signs `message` with the secret key `sk` and the auxiliary randomness `aux`,
+/// outside of the circuit. Returns the signature as `r || s`.
+/// Returns `None` if the secret key or the derived nonce is invalid.
+pub fn bip340_sign(
+    curve: &ForeignCurve,
+    sk: &BigUint,
+    message: &[u8],
+    aux: &[u8; 32],
+) -> Option<[u8; 64]> {
+    let n = curve.scalar.modulus();
+    let zero = BigUint::from(0u32);
+    if *sk == zero || sk >= n {
+        return None;
+    }
+
+    let public_key = curve.scale(&curve.generator, sk)?;
+    let d = if public_key.y.bit(0) {
+        n - sk
+    } else {
+        sk.clone()
+    };
+    let px = to_bytes32(&public_key.x);
+
+    let mask = tagged_hash(AUX_TAG, aux);
+    let masked: Vec<u8> = to_bytes32(&d)
+        .iter()
+        .zip(mask)
+        .map(|(a, b)| a ^ b)
+        .collect();
+    let nonce = tagged_hash(NONCE_TAG, &[&masked[..], &px, message].concat());
+    let k = BigUint::from_bytes_be(&nonce) % n;
+    if k == zero {
+        return None;
+    }
+
+    let nonce_point = curve.scale(&curve.generator, &k)?;
+    let k = if nonce_point.y.bit(0) { n - k } else { k };
+    let rx = to_bytes32(&nonce_point.x);
+    let e = BigUint::from_bytes_be(&tagged_hash(CHALLENGE_TAG, &[&rx, &px, message].concat())) % n;
+
+    let mut signature = [0u8; 64];
+    signature[..32].copy_from_slice(&rx);
+    signature[32..].copy_from_slice(&to_bytes32(&((k + e * d) % n)));
+    Some(signature)
+}
+
+/// Verifies a signature (`r || s`) of `message` under the `x`-only public key `public_key`,
+/// outside of the circuit.
+pub fn bip340_verify_native(
+    curve: &ForeignCurve,
+    public_key: &[u8; 32],
+    message: &[u8],
+    signature: &[u8; 64],
+) -> bool {
+    let n = curve.scalar.modulus();
+    let p = match lift_x(curve, &BigUint::from_bytes_be(public_key)) {
+        Some(p) => p,
+        None => return false,
+    };
+    let r = BigUint::from_bytes_be(&signature[..32]);
+    let s = BigUint::from_bytes_be(&signature[32..]);
+    if &r >= curve.base.modulus() || &s >= n {
+        return false;
+    }
+
+    let e = tagged_hash(
+        CHALLENGE_TAG,
+        &[&signature[..32], &public_key[..], message].concat(),
+    );
+    let e = BigUint::from_bytes_be(&e) % n;
+    let point = curve.add(
+        &curve.scale(&curve.generator, &s),
+        &curve.scale(&curve.neg(&p), &e),
+    );
+    match point {
+        None => false,
+        Some(point) => !point.y.bit(0) && point.x == r,
+    }
+}
+
+/// BIP-340 gadgets.
+///
+/// The `zero` variable taken by its functions is described
+/// in [the module documentation](super#the-zero-variable).
+pub trait Bip340<F: PrimeField>: ForeignCurveGadgets<F> + Sha256<F> {
+    /// Converts a 256-bit integer given as eight big-endian 32-bit words into a foreign element
+    /// (which is not reduced).
+    ///
+    /// The words straddling two limbs are split with [`super::uint32::Uint32::u32_shr`].
+    fn foreign_from_words(&mut self, zero: Var<F>, words: &[U32<F>]) -> ForeignVar<F> {
+        assert_eq!(words.len(), 8);
+        let w: Vec<_> = words.iter().rev().map(U32::var).collect();
+        let hi2 = self.u32_shr(zero, words[5], 24).var();
+        let hi5 = self.u32_shr(zero, words[2], 16).var();
+
+        let pow = |e: u64| F::from(2u64).pow([e]);
+        let l0 = self.linear_combination(
+            &[
+                (F::one(), w[0]),
+                (pow(32), w[1]),
+                (pow(64), w[2]),
+                (-pow(88), hi2),
+            ],
+            F::zero(),
+        );
+        let l1 = self.linear_combination(
+            &[
+                (F::one(), hi2),
+                (pow(8), w[3]),
+                (pow(40), w[4]),
+                (pow(72), w[5]),
+                (-pow(88), hi5),
+            ],
+            F::zero(),
+        );
+        let l2 = self.linear_combination(
+            &[(F::one(), hi5), (pow(16), w[6]), (pow(48), w[7])],
+            F::zero(),
+        );
+        ForeignVar {
+            limbs: [l0, l1, l2],
+        }
+    }
+
+    /// Constrains `signature` (`r || s`, 16 words) to be a valid signature of `message`
+    /// under the `x`-only public key `public_key` (8 words).
+    fn bip340_verify(
+        &mut self,
+        zero: Var<F>,
+        curve: &ForeignCurve,
+        public_key: &[U32<F>],
+        message: &[U32<F>],
+        signature: &[U32<F>],
+    ) {
+        assert_eq!(public_key.len(), 8);
+        assert_eq!(signature.len(), 16);
+        let (r_words, s_words) = signature.split_at(8);
+
+        // P = lift_x(public key), the canonical check of P.x also applies to px
+        let px = self.foreign_from_words(zero, public_key);
+        let p = self.point_var(zero, curve, || {
+            lift_x(curve, &px.val()).expect("public key is not on the curve")
+        });
+        self.foreign_assert_eq(p.x, px);
+        self.foreign_assert_even(zero, p.y);
+
+        // r is checked against x(R), s must be canonical
+        let r = self.foreign_from_words(zero, r_words);
+        let s = self.foreign_from_words(zero, s_words);
+        self.foreign_assert_canonical(zero, &curve.scalar, s);
+
+        // e = tagged hash of r || P.x || m, the first block being constant
+        let tag_hash = to_words(&sha256_native(CHALLENGE_TAG));
+        let prefix = compress_native(IV, &[tag_hash.clone(), tag_hash].concat());
+        let state = prefix.map(|w| self.u32_constant(w));
+        let input = [r_words, public_key, message].concat();
+        let digest = self.sha256_resume(zero, state, BLOCK_WORDS, &input);
+        let e = self.foreign_from_words(zero, &digest);
+        let e = self.foreign_reduce(zero, &curve.scalar, e);
+
+        // R = s G - e P
+        let bits = curve.scalar_bits();
+        let s_bits = self.foreign_to_bits(zero, s, bits);
+        let e_bits = self.foreign_to_bits(zero, e, bits);
+        let generator = self.point_constant(curve, &curve.generator);
+        let neg_p = self.point_neg(zero, curve, p);
+        let point = self.point_multi_scale(zero, curve, &[(generator, s_bits), (neg_p, e_bits)]);
+
+        self.foreign_assert_even(zero, point.y);
+        self.foreign_assert_eq(point.x, r);
+    }
+}
+
+impl<F: PrimeField, C: ForeignCurveGadgets<F> + Sha256<F>> Bip340<F> for C {}
@@ -0,0 +1,111 @@
+//! 8-bit bitwise operations, looked up in the preset tables of kimchi
+//! (see [`kimchi::circuits::lookup::tables::bitwise`]).
+//!
+//! Each operation adds its table to the circuit, and looks up its results with `Lookup` gates
+//! (three per row), the index of a binary operation on `x` and `y` being `256 x + y`.
+//! The operands of AND and OR must be range-checked to 8 bits
+//! (e.g. with [`super::bytes::Bytes::assert_bytes`]),
+//! as otherwise `256 x + y` could be the index of other operands.
+//! The operand of NOT is range-checked by its lookup.
+//!
+//! The AND and OR tables have `2^16` entries each,
+//! so circuits using them must have at least as many rows.
+//! In witness mode, the operations panic if their operands are not bytes.
+
+use super::arith::Arith;
+use crate::writer::{Cs, GateSpec, Var};
+use ark_ff::{BigInteger, PrimeField};
+use kimchi::circuits::{
+    gate::GateType,
+    lookup::tables::{get_table, GateLookupTable},
+};
+use o1_utils::field_helpers::i64_to_field;
+
+/// Number of lookups of a `Lookup` gate.
+const LOOKUPS_PER_ROW: usize = 3;
+
+/// Returns the value of a byte.
+fn byte<F: PrimeField>(x: F) -> u8 {
+    let repr = x.into_repr();
+    assert!(repr.num_bits() <= 8, "not a byte");
+    repr.as_ref()[0] as u8
+}
+
+/// Looks up the pairs `(index, value)` in the preset table `table`, three per row.
+fn bitwise_lookups<F: PrimeField, Sys: Cs<F> + ?Sized>(
+    sys: &mut Sys,
+    table: GateLookupTable,
+    lookups: &[(Var<F>, Var<F>)],
+) {
+    if lookups.is_empty() {
+        return;
+    }
+    let table = get_table(table);
+    let table_id = sys.constant(i64_to_field(table.id));
+    sys.lookup_table(table);
+
+    for chunk in lookups.chunks(LOOKUPS_PER_ROW) {
+        let mut row = vec![Some(table_id)];
+        for i in 0..LOOKUPS_PER_ROW {
+            // unused lookups repeat the last one
+            let (index, value) = chunk[i.min(chunk.len() - 1)];
+            row.extend([Some(index), Some(value)]);
+        }
+        sys.gate(GateSpec {
+            typ: GateType::Lookup,
+            row,
+            coeffs: vec![],
+        });
+    }
+}
+
+/// Applies the binary operation `op` of the preset table `table` to each pair of `operands`.
+fn binary<F: PrimeField, Sys: Arith<F> + ?Sized>(
+    sys: &mut Sys,
+    table: GateLookupTable,
+    operands: &[(Var<F>, Var<F>)],
+    op: fn(u8, u8) -> u8,
+) -> Vec<Var<F>> {
+    let (results, pairs): (Vec<_>, Vec<_>) = operands
+        .iter()
+        .map(|(x, y)| {
+            // index = 256 x + y
+            let index = sys.linear_combination(&[(F::from(256u64), *x), (F::one(), *y)], F::zero());
+            let result = sys.var(|| F::from(op(byte(x.val()), byte(y.val()))));
+            (result, (index, result))
+        })
+        .unzip();
+    bitwise_lookups(sys, table, &pairs);
+    results
+}
+
+/// Bitwise gadgets.
+pub trait Bitwise<F: PrimeField>: Arith<F> {
+    /// Returns the bitwise AND of each pair of bytes of `operands`.
+    /// The bytes should be constrained (e.g. with [`super::bytes::Bytes::assert_bytes`]).
+    fn and8(&mut self, operands: &[(Var<F>, Var<F>)]) -> Vec<Var<F>> {
+        binary(self, GateLookupTable::And, operands, |x, y| x & y)
+    }
+
+    /// Returns the bitwise OR of each pair of bytes of `operands`.
+    /// The bytes should be constrained (e.g. with [`super::bytes::Bytes::assert_bytes`]).
+    fn or8(&mut self, operands: &[(Var<F>, Var<F>)]) -> Vec<Var<F>> {
+        binary(self, GateLookupTable::Or, operands, |x, y| x | y)
+    }
+
+    /// Returns the bitwise NOT of each byte of `operands`,
+    /// constraining the operands to be bytes.
+    fn not8(&mut self, operands: &[Var<F>]) -> Vec<Var<F>> {
+        let (results, pairs): (Vec<_>, Vec<_>) = operands
+            .iter()
+            .map(|x| {
+                let result = self.var(|| F::from(!byte(x.val())));
+                (result, (*x, result))
+            })
+            .unzip();
+        bitwise_lookups(self, GateLookupTable::Not, &pairs);
+        results
+    }
+}
+
+impl<F: PrimeField, C: Cs<F>> Bitwise<F> for C {}
@@ -0,0 +1,216 @@
+//! Byte decomposition and packing.
+//!
+//! Bytes are variables range-checked to 8 bits (with [`super::range_check`]).
+//! A byte string encodes the integer `sum_i b_i 256^i` when read in little-endian order,
+//! or `sum_i b_i 256^(n - 1 - i)` in big-endian order (see [`Endianness`]).
+//!
+//! The canonical encoding of a field element is the encoding of its representative
+//! in `[0, p)`, on [`FieldHelpers::size_in_bytes`] bytes (32 bytes for the Pasta fields).
+//! Decomposing an element into that many bytes constrains the encoding to be canonical,
+//! as otherwise `x` and `x + p` would both be valid decompositions.
+//! Shorter decompositions (at most [`packed_bytes`] bytes) are unique without this check,
+//! as they can not wrap around the modulus.
+
+use super::arith::{evaluate, Arith};
+use super::range_check::{RangeCheck, RANGE_CHECK_BITS};
+use crate::writer::Var;
+use ark_ff::{BigInteger, One, PrimeField};
+use num_bigint::BigUint;
+use o1_utils::field_helpers::{FieldFromBig, FieldHelpers};
+
+/// The order of the bytes of an encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    /// The first byte is the least significant one
+    Little,
+    /// The first byte is the most significant one
+    Big,
+}
+
+impl Endianness {
+    /// Reorders `bytes` (given with this endianness) in little-endian order, or back.
+    pub fn to_le<T: Clone>(self, bytes: &[T]) -> Vec<T> {
+        match self {
+            Endianness::Little => bytes.to_vec(),
+            Endianness::Big => bytes.iter().rev().cloned().collect(),
+        }
+    }
+}
+
+/// Returns the number of bytes that can be packed into a field element
+/// without wrapping around the modulus (31 for the Pasta fields).
+pub fn packed_bytes<F: PrimeField>() -> usize {
+    (F::size_in_bits() - 1) / 8
+}
+
+/// Encodes a field element on `len` bytes, outside of the circuit.
+///
+/// # Panics
+///
+/// Will panic if the element does not fit in `len` bytes.
+pub fn field_to_bytes<F: PrimeField>(x: F, len: usize, endianness: Endianness) -> Vec<u8> {
+    let mut bytes = x.into_repr().to_bytes_le();
+    assert!(bytes[len.min(bytes.len())..].iter().all(|b| *b == 0));
+    bytes.resize(len, 0);
+    endianness.to_le(&bytes)
+}
+
+/// Decodes bytes into a field element (modulo `p`), outside of the circuit.
+pub fn field_from_bytes<F: PrimeField>(bytes: &[u8], endianness: Endianness) -> F {
+    F::from_le_bytes_mod_order(&endianness.to_le(bytes))
+}
+
+/// Byte gadgets.
+///
+/// The `zero` variable taken by its functions is described
+/// in [the module documentation](super#the-zero-variable).
+pub trait Bytes<F: PrimeField>: Arith<F> + RangeCheck<F> {
+    /// Creates `len` bytes from the value returned by `g`, and range-checks them.
+    fn byte_vars<G>(&mut self, zero: Var<F>, len: usize, g: G) -> Vec<Var<F>>
+    where
+        G: FnOnce() -> Vec<u8>,
+    {
+        let mut value = None;
+        let mut bytes = vec![];
+        if len > 0 {
+            bytes.push(self.var(|| {
+                let v = g();
+                assert_eq!(v.len(), len);
+                let b0 = F::from(v[0]);
+                value = Some(v);
+                b0
+            }));
+        }
+        for i in 1..len {
+            bytes.push(self.var(|| F::from(value.as_ref().unwrap()[i])));
+        }
+        self.assert_bytes(zero, &bytes);
+        bytes
+    }
+
+    /// Constrains each variable to be a byte.
+    fn assert_bytes(&mut self, zero: Var<F>, bytes: &[Var<F>]) {
+        let checks: Vec<_> = bytes.iter().map(|b| (*b, 8)).collect();
+        self.range_check_bits(zero, &checks);
+    }
+
+    /// Decomposes `x` into `len` bytes.
+    /// With [`FieldHelpers::size_in_bytes`] bytes, the encoding is constrained to be canonical.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `len` is neither at most [`packed_bytes`] nor the size of the field,
+    /// or, in witness mode, if `x` does not fit in `len` bytes (which can not be proven).
+    fn unpack_bytes(
+        &mut self,
+        zero: Var<F>,
+        x: Var<F>,
+        len: usize,
+        endianness: Endianness,
+    ) -> Vec<Var<F>> {
+        let canonical = len > packed_bytes::<F>();
+        assert!(!canonical || len == F::size_in_bytes());
+
+        let bytes = self.byte_vars(zero, len, || {
+            field_to_bytes(x.val(), len, Endianness::Little)
+        });
+
+        // x = sum_i 256^i b_i
+        let mut terms = vec![(-F::one(), x)];
+        terms.extend(weighted(&bytes));
+        self.assert_linear(&terms, F::zero());
+
+        if canonical {
+            self.assert_canonical_bytes(zero, &bytes);
+        }
+        endianness.to_le(&bytes)
+    }
+
+    /// Packs bytes into a field element.
+    /// The bytes should be constrained (e.g. with [`Bytes::assert_bytes`]).
+    ///
+    /// # Panics
+    ///
+    /// Will panic if there are more than [`packed_bytes`] bytes.
+    fn pack_bytes(&mut self, bytes: &[Var<F>], endianness: Endianness) -> Var<F> {
+        assert!(bytes.len() <= packed_bytes::<F>());
+
+        let terms = weighted(&endianness.to_le(bytes));
+        self.linear_combination(&terms, F::zero())
+    }
+
+    /// Packs bytes into field elements of [`packed_bytes`] bytes each
+    /// (the last one possibly being shorter), each of them with the given endianness.
+    /// The bytes should be constrained (e.g. with [`Bytes::assert_bytes`]).
+    fn pack_bytes_chunked(&mut self, bytes: &[Var<F>], endianness: Endianness) -> Vec<Var<F>> {
+        bytes
+            .chunks(packed_bytes::<F>())
+            .map(|chunk| self.pack_bytes(chunk, endianness))
+            .collect()
+    }
+
+    /// Constrains the integer given by the little-endian `bytes` (as many as the size of the field)
+    /// to be smaller than the modulus.
+    ///
+    /// This is done by checking that `B + 2^(8 len) - p` does not overflow `8 len` bits,
+    /// with both halves of the sum computed separately so that they do not wrap around.
+    fn assert_canonical_bytes(&mut self, zero: Var<F>, bytes: &[Var<F>]) {
+        let len = bytes.len();
+        let half = len / 2;
+        let half_bits = 8 * half;
+        // the halves are range-checked as two pieces each
+        assert_eq!(len % 2, 0);
+        assert!(half_bits <= 2 * RANGE_CHECK_BITS);
+
+        // n = 2^(8 len) - p, split into two halves
+        let neg = (BigUint::one() << (8 * len)) - F::modulus_biguint();
+        let split = BigUint::one() << half_bits;
+        let neg_lo = &neg % &split;
+        let neg_hi = &neg >> half_bits;
+
+        let lo = weighted(&bytes[..half]);
+        let hi = weighted(&bytes[half..]);
+
+        // c is the carry out of the low half
+        let c = self.var(|| {
+            let sum = Into::<BigUint>::into(evaluate(&lo, F::zero())) + &neg_lo;
+            F::from(u64::from(sum >= split))
+        });
+        self.assert_boolean(c);
+
+        // lo + n_lo - 2^half c and hi + n_hi + c must both fit in half_bits bits
+        let mut checks = vec![];
+        for (mut terms, constant, carry) in [
+            (lo, &neg_lo, -F::from_biguint(split.clone()).unwrap()),
+            (hi, &neg_hi, F::one()),
+        ] {
+            let constant = F::from_biguint(constant.clone()).unwrap();
+            terms.push((carry, c));
+            let y = self.var(|| evaluate(&terms, constant));
+            for start in [0, half_bits / 2] {
+                let piece = self.limb(y, start, start + half_bits / 2);
+                terms.push((-F::from(2u64).pow([start as u64]), piece));
+                checks.push((piece, half_bits / 2));
+            }
+            self.assert_linear(&terms, constant);
+        }
+        self.range_check_bits(zero, &checks);
+    }
+}
+
+impl<F: PrimeField, C: Arith<F> + RangeCheck<F>> Bytes<F> for C {}
+
+/// Returns `1, 256, ..., 256^(len - 1)`.
+fn powers<F: PrimeField>(len: usize) -> Vec<F> {
+    (0..len)
+        .map(|i| F::from(2u64).pow([8 * i as u64]))
+        .collect()
+}
+
+/// Returns the terms `256^i b_i` of the integer given by the little-endian `bytes`.
+fn weighted<F: PrimeField>(bytes: &[Var<F>]) -> Vec<(F, Var<F>)> {
+    powers::<F>(bytes.len())
+        .into_iter()
+        .zip(bytes.iter().copied())
+        .collect()
+}
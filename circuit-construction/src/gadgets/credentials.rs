@@ -0,0 +1,182 @@
+//! Anonymous credential gadgets, for identity use cases.
+//!
+//! A credential is a list of attributes (e.g. a birth year, a country code, a document number)
+//! committed to with a Poseidon hash, along with a random blinding factor:
+//! `C = H(blinding || attributes)`, with a sponge initialized with [`CREDENTIAL_COMMITMENT_DOMAIN`].
+//! The issuer of the credential signs its commitment with a Mina Schnorr signature,
+//! the message being the commitment alone (see [`CredentialCommitment`]).
+//!
+//! As with BBS+ signatures, the holder of a credential can then present it selectively:
+//! the circuit recomputes the commitment from the attributes, checks the signature of the issuer,
+//! and only exposes the attributes the holder chooses to disclose,
+//! or some facts about them, such as an attribute lying in a range.
+//! Neither the commitment nor the signature are revealed by the proof,
+//! so that presentations of the same credential cannot be linked by them.
+
+use super::schnorr::{Schnorr, SchnorrSignatureVar, SchnorrWitness};
+use super::sponge::{domain_state, CircuitSponge};
+use crate::constants::Constants;
+use crate::writer::Var;
+use ark_ec::AffineCurve;
+use ark_ff::PrimeField;
+use mina_curves::pasta::Fp;
+use mina_hasher::{Hashable, ROInput};
+use oracle::{
+    constants::PlonkSpongeConstantsKimchi,
+    poseidon::{ArithmeticSponge, Sponge},
+};
+
+/// The domain string of the hash of credential commitments.
+pub const CREDENTIAL_COMMITMENT_DOMAIN: &str = "KimchiCredentialCom";
+
+/// The domain string of the signatures of credential commitments.
+pub const CREDENTIAL_SIGNATURE_DOMAIN: &str = "KimchiCredentialSig";
+
+/// The number of bits of the differences checked by the range proofs over attributes,
+/// which thus accept any bounds that fit in 64 bits.
+pub const ATTRIBUTE_RANGE_BITS: usize = 64;
+
+/// A credential, as known to its holder.
+#[derive(Clone, Debug)]
+pub struct Credential<F> {
+    /// The attributes of the credential
+    pub attributes: Vec<F>,
+    /// The blinding factor of the commitment
+    pub blinding: F,
+}
+
+impl<F: PrimeField> Credential<F> {
+    /// Returns the commitment to the credential, as computed by [`Credentials::credential_commitment`].
+    pub fn commitment(&self, constants: &Constants<F>) -> F {
+        let mut sponge = ArithmeticSponge::<F, PlonkSpongeConstantsKimchi>::new(constants.poseidon);
+        sponge.state = domain_state(constants, Some(CREDENTIAL_COMMITMENT_DOMAIN.to_string()));
+        sponge.absorb(&[self.blinding]);
+        sponge.absorb(&self.attributes);
+        sponge.squeeze()
+    }
+}
+
+/// The message signed by the issuer of a credential: its commitment.
+#[derive(Clone, Debug)]
+pub struct CredentialCommitment(pub Fp);
+
+impl Hashable for CredentialCommitment {
+    type D = ();
+
+    fn to_roinput(&self) -> ROInput {
+        ROInput::new().append_field(self.0)
+    }
+
+    fn domain_string(_: Self::D) -> Option<String> {
+        Some(CREDENTIAL_SIGNATURE_DOMAIN.to_string())
+    }
+}
+
+/// A credential in the circuit.
+pub struct CredentialVar<F> {
+    /// The attributes of the credential
+    pub attributes: Vec<Var<F>>,
+    /// The blinding factor of the commitment
+    pub blinding: Var<F>,
+}
+
+/// Anonymous credential gadgets.
+///
+/// The `zero` variable taken by its functions is described
+/// in [the module documentation](super#the-zero-variable).
+pub trait Credentials<F: PrimeField>: Schnorr<F> {
+    /// Creates the variables of a credential of `attributes` attributes.
+    fn credential_witness(
+        &mut self,
+        witness: Option<&Credential<F>>,
+        attributes: usize,
+    ) -> CredentialVar<F> {
+        CredentialVar {
+            attributes: (0..attributes)
+                .map(|i| self.var(|| witness.unwrap().attributes[i]))
+                .collect(),
+            blinding: self.var(|| witness.unwrap().blinding),
+        }
+    }
+
+    /// Creates the variables of the signature of a credential by its issuer,
+    /// the witness being created with [`SchnorrWitness::new`] from a [`CredentialCommitment`].
+    fn credential_signature<G: AffineCurve<BaseField = F>>(
+        &mut self,
+        witness: Option<&SchnorrWitness<G>>,
+    ) -> SchnorrSignatureVar<F> {
+        // the message is the commitment, which is computed from the credential instead
+        let (_, signature) = self.schnorr_witness(witness, 0);
+        signature
+    }
+
+    /// Returns the commitment `H(blinding || attributes)` to `credential`.
+    fn credential_commitment(
+        &mut self,
+        constants: &Constants<F>,
+        credential: &CredentialVar<F>,
+    ) -> Var<F> {
+        let domain = Some(CREDENTIAL_COMMITMENT_DOMAIN.to_string());
+        let mut sponge = CircuitSponge::with_domain(self, constants, domain);
+        sponge.absorb(self, constants, &[credential.blinding]);
+        sponge.absorb(self, constants, &credential.attributes);
+        sponge.squeeze(self, constants)
+    }
+
+    /// Constrains `signature` to be a signature of the commitment to `credential`
+    /// under the public key `issuer`, and returns the commitment.
+    fn credential_verify(
+        &mut self,
+        zero: Var<F>,
+        constants: &Constants<F>,
+        issuer: (Var<F>, Var<F>),
+        credential: &CredentialVar<F>,
+        signature: SchnorrSignatureVar<F>,
+    ) -> Var<F> {
+        let commitment = self.credential_commitment(constants, credential);
+        self.schnorr_verify(
+            zero,
+            constants,
+            Some(CREDENTIAL_SIGNATURE_DOMAIN.to_string()),
+            issuer,
+            &[commitment],
+            signature,
+        );
+        commitment
+    }
+
+    /// Discloses some attributes of `credential`,
+    /// constraining the attribute of each index to be equal to the associated variable
+    /// (typically a public input).
+    fn credential_disclose(
+        &mut self,
+        credential: &CredentialVar<F>,
+        disclosed: &[(usize, Var<F>)],
+    ) {
+        for (i, value) in disclosed {
+            self.assert_eq(credential.attributes[*i], *value);
+        }
+    }
+
+    /// Constrains the attribute of index `i` of `credential` to lie in `lower..=upper`.
+    fn credential_range(
+        &mut self,
+        zero: Var<F>,
+        credential: &CredentialVar<F>,
+        i: usize,
+        (lower, upper): (u64, u64),
+    ) {
+        assert!(lower <= upper);
+        // attribute - lower and upper - attribute both fit in 64 bits,
+        // which only holds if neither wraps around the field
+        let attribute = credential.attributes[i];
+        let above = self.linear_combination(&[(F::one(), attribute)], -F::from(lower));
+        let below = self.linear_combination(&[(-F::one(), attribute)], F::from(upper));
+        self.range_check_bits(
+            zero,
+            &[(above, ATTRIBUTE_RANGE_BITS), (below, ATTRIBUTE_RANGE_BITS)],
+        );
+    }
+}
+
+impl<F: PrimeField, C: Schnorr<F>> Credentials<F> for C {}
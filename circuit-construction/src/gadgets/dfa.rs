@@ -0,0 +1,204 @@
+//! Execution of deterministic finite automata (e.g. compiled regular expressions) on bytes.
+//!
+//! The transition function of a [`Dfa`] is a fixed lookup table, with one entry
+//! `(256 s + b, t)` for each transition from the state `s` to the state `t` on the byte `b`.
+//! Running the automaton on `n` bytes computes the `n` indices `256 s_i + b_i` with
+//! generic gates, and looks up the `n` pairs `(256 s_i + b_i, s_(i+1))` with `Lookup` gates
+//! (three per row), so that the number of rows is linear in the length of the input.
+//!
+//! The bytes must be range-checked (e.g. with [`super::bytes::Bytes::assert_bytes`]),
+//! as otherwise `256 s + b` could be the index of a transition from another state.
+//! A missing transition rejects the input, as no run can then be proven.
+
+use super::arith::Arith;
+use crate::writer::{Cs, GateSpec, Var};
+use ark_ff::{BigInteger, PrimeField};
+use kimchi::circuits::{
+    gate::GateType,
+    lookup::tables::{
+        table_id_namespace, LookupTable, PRESET_TABLE_NAMESPACE, RANGE_CHECK_TABLE_ID, XOR_TABLE_ID,
+    },
+};
+use o1_utils::field_helpers::i64_to_field;
+use std::collections::BTreeMap;
+
+/// Number of lookups of a `Lookup` gate.
+const LOOKUPS_PER_ROW: usize = 3;
+
+/// A deterministic finite automaton over bytes.
+#[derive(Debug, Clone)]
+pub struct Dfa {
+    /// The ID of the lookup table of the transitions, unique in the circuit
+    /// (see [`kimchi::circuits::lookup::tables::namespaced_table_id`])
+    pub table_id: i64,
+    /// The initial state
+    pub start: usize,
+    /// The accepting states
+    pub accepting: Vec<usize>,
+    transitions: BTreeMap<(usize, u8), usize>,
+}
+
+impl Dfa {
+    /// Creates an automaton without transitions.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `table_id` is the ID of a table used by the gates of kimchi,
+    /// or of one of its preset tables.
+    pub fn new(table_id: i64, start: usize, accepting: Vec<usize>) -> Self {
+        assert!(
+            table_id != XOR_TABLE_ID
+                && table_id != RANGE_CHECK_TABLE_ID
+                && table_id_namespace(table_id).0 != PRESET_TABLE_NAMESPACE,
+            "reserved lookup table ID"
+        );
+        Self {
+            table_id,
+            start,
+            accepting,
+            transitions: BTreeMap::new(),
+        }
+    }
+
+    /// Creates the automaton accepting the inputs that contain `pattern`,
+    /// whose state `i` means that the last `i` bytes are a prefix of the pattern (as in KMP).
+    pub fn substring(table_id: i64, pattern: &[u8]) -> Self {
+        let len = pattern.len();
+        let mut dfa = Self::new(table_id, 0, vec![len]);
+        for state in 0..len {
+            for byte in 0..=255 {
+                // the longest suffix of pattern[..state] || byte that is a prefix of the pattern
+                let mut next = pattern[..state].to_vec();
+                next.push(byte);
+                let to = (0..=state + 1)
+                    .rev()
+                    .find(|k| next.ends_with(&pattern[..*k]))
+                    .unwrap();
+                dfa.add_transition(state, byte, to);
+            }
+        }
+        for byte in 0..=255 {
+            dfa.add_transition(len, byte, len);
+        }
+        dfa
+    }
+
+    /// Adds the transition from `from` to `to` on `byte`.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if there is already a transition from `from` on `byte`.
+    pub fn add_transition(&mut self, from: usize, byte: u8, to: usize) {
+        let previous = self.transitions.insert((from, byte), to);
+        assert!(previous.is_none(), "the automaton must be deterministic");
+    }
+
+    /// Returns the state reached from `state` on `byte`, if any.
+    pub fn transition(&self, state: usize, byte: u8) -> Option<usize> {
+        self.transitions.get(&(state, byte)).copied()
+    }
+
+    /// Returns the state reached from the initial state on `input`, if any.
+    pub fn run(&self, input: &[u8]) -> Option<usize> {
+        input
+            .iter()
+            .try_fold(self.start, |state, byte| self.transition(state, *byte))
+    }
+
+    /// Returns whether the automaton accepts `input`.
+    pub fn accepts(&self, input: &[u8]) -> bool {
+        matches!(self.run(input), Some(state) if self.accepting.contains(&state))
+    }
+
+    /// Returns the lookup table of the transitions.
+    pub fn table<F: PrimeField>(&self) -> LookupTable<F> {
+        let (indices, values) = self
+            .transitions
+            .iter()
+            .map(|((from, byte), to)| (index::<F>(*from, *byte), F::from(*to as u64)))
+            .unzip();
+        LookupTable {
+            id: self.table_id,
+            data: vec![indices, values],
+        }
+    }
+}
+
+/// Returns the index of the transition from `state` on `byte` in the lookup table.
+fn index<F: PrimeField>(state: usize, byte: u8) -> F {
+    F::from(256 * state as u64 + u64::from(byte))
+}
+
+/// Returns the value of a small field element.
+fn small<F: PrimeField>(x: F) -> u64 {
+    let repr = x.into_repr();
+    assert!(repr.num_bits() <= 64);
+    repr.as_ref()[0]
+}
+
+/// Automata gadgets.
+pub trait DfaGadgets<F: PrimeField>: Arith<F> {
+    /// Runs `dfa` on the bytes of `input`, and returns the final state.
+    /// The bytes should be constrained (e.g. with [`super::bytes::Bytes::assert_bytes`]).
+    ///
+    /// # Panics
+    ///
+    /// In witness mode, will panic if the automaton has no transition for the input
+    /// (which can not be proven).
+    fn dfa_run(&mut self, dfa: &Dfa, input: &[Var<F>]) -> Var<F> {
+        self.lookup_table(dfa.table());
+        let table_id = self.constant(i64_to_field(dfa.table_id));
+
+        let mut state = self.constant(F::from(dfa.start as u64));
+        let mut lookups = vec![];
+        for byte in input {
+            // index = 256 state + byte
+            let index =
+                self.linear_combination(&[(F::from(256u64), state), (F::one(), *byte)], F::zero());
+            let next = self.var(|| {
+                let from = small(state.val()) as usize;
+                let to = dfa.transition(from, small(byte.val()) as u8);
+                F::from(to.expect("no transition") as u64)
+            });
+            lookups.push((index, next));
+            state = next;
+        }
+
+        for chunk in lookups.chunks(LOOKUPS_PER_ROW) {
+            let mut row = vec![Some(table_id)];
+            for i in 0..LOOKUPS_PER_ROW {
+                // unused lookups repeat the last one
+                let (index, value) = chunk[i.min(chunk.len() - 1)];
+                row.extend([Some(index), Some(value)]);
+            }
+            self.gate(GateSpec {
+                typ: GateType::Lookup,
+                row,
+                coeffs: vec![],
+            });
+        }
+
+        state
+    }
+
+    /// Constrains `dfa` to accept the bytes of `input`.
+    /// The bytes should be constrained (e.g. with [`super::bytes::Bytes::assert_bytes`]).
+    fn dfa_assert_accepts(&mut self, dfa: &Dfa, input: &[Var<F>]) {
+        assert!(!dfa.accepting.is_empty());
+
+        // prod_a (state - a) = 0
+        let state = self.dfa_run(dfa, input);
+        let mut product = None;
+        for a in &dfa.accepting {
+            let factor = self.linear_combination(&[(F::one(), state)], -F::from(*a as u64));
+            product = Some(match product {
+                None => factor,
+                Some(p) => self.mul(p, factor),
+            });
+        }
+        let zero = self.constant(F::zero());
+        self.assert_eq(product.unwrap(), zero);
+    }
+}
+
+impl<F: PrimeField, C: Cs<F>> DfaGadgets<F> for C {}
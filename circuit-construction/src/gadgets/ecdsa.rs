@@ -0,0 +1,118 @@
+//! ECDSA signature verification over a foreign curve (e.g. P-256, used by WebAuthn and passkeys).
+//!
+//! Given a public key `Q`, a message hash `z` and a signature `(r, s)`,
+//! the verifier computes `R = (z / s) G + (r / s) Q` and checks that `x(R) = r (mod n)`.
+//! In the circuit, the scalars `z / s` and `r / s` are witnessed and checked with
+//! multiplications modulo `n`, and the two scalar multiplications share their doublings.
+
+use super::foreign_curve::{ForeignCurve, ForeignCurveGadgets, ForeignPoint, ForeignPointVar};
+use super::foreign_field::{inverse_mod, ForeignVar};
+use crate::writer::Var;
+use ark_ff::PrimeField;
+use num_bigint::BigUint;
+
+/// An ECDSA signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EcdsaSignature {
+    /// The `x` coordinate of the nonce point, reduced modulo the group order
+    pub r: BigUint,
+    /// The signature scalar
+    pub s: BigUint,
+}
+
+/// Converts a message hash (in big endian) into a scalar, as specified by ECDSA:
+/// the hash is truncated to the bit length of the group order, then reduced.
+pub fn hash_to_scalar(curve: &ForeignCurve, hash: &[u8]) -> BigUint {
+    let n = curve.scalar.modulus();
+    let z = BigUint::from_bytes_be(hash);
+    let excess = (8 * hash.len()).saturating_sub(curve.scalar_bits());
+    (z >> excess) % n
+}
+
+/// Signs the message hash `z` (already converted to a scalar) with the secret key `sk`
+/// and the nonce `k`, outside of the circuit.
+/// Returns `None` if the nonce leads to an invalid signature.
+pub fn ecdsa_sign(
+    curve: &ForeignCurve,
+    sk: &BigUint,
+    k: &BigUint,
+    z: &BigUint,
+) -> Option<EcdsaSignature> {
+    let n = curve.scalar.modulus();
+    let nonce = curve.scale(&curve.generator, k)?;
+    let r = nonce.x % n;
+    let s = inverse_mod(k, n) * (z + &r * sk) % n;
+    if r == BigUint::from(0u32) || s == BigUint::from(0u32) {
+        return None;
+    }
+    Some(EcdsaSignature { r, s })
+}
+
+/// Verifies a signature on the message hash `z` (already converted to a scalar),
+/// outside of the circuit.
+pub fn ecdsa_verify_native(
+    curve: &ForeignCurve,
+    public_key: &ForeignPoint,
+    z: &BigUint,
+    signature: &EcdsaSignature,
+) -> bool {
+    let n = curve.scalar.modulus();
+    let (r, s) = (&signature.r, &signature.s);
+    let zero = BigUint::from(0u32);
+    if *r == zero || r >= n || *s == zero || s >= n || !curve.is_on_curve(public_key) {
+        return false;
+    }
+
+    let w = inverse_mod(s, n);
+    let u1 = z * &w % n;
+    let u2 = r * &w % n;
+    let point = curve.add(
+        &curve.scale(&curve.generator, &u1),
+        &curve.scale(public_key, &u2),
+    );
+    match point {
+        None => false,
+        Some(point) => point.x % n == *r,
+    }
+}
+
+/// ECDSA gadgets.
+///
+/// The `zero` variable taken by its functions is described
+/// in [the module documentation](super#the-zero-variable).
+pub trait Ecdsa<F: PrimeField>: ForeignCurveGadgets<F> {
+    /// Constrains `(r, s)` to be a valid signature of the message hash `z` under `public_key`.
+    ///
+    /// The scalars `z`, `r` and `s` are elements modulo the group order,
+    /// and the public key is assumed to be on the curve (see [`ForeignCurveGadgets::point_var`]).
+    fn ecdsa_verify(
+        &mut self,
+        zero: Var<F>,
+        curve: &ForeignCurve,
+        public_key: ForeignPointVar<F>,
+        z: ForeignVar<F>,
+        r: ForeignVar<F>,
+        s: ForeignVar<F>,
+    ) {
+        let n = &curve.scalar;
+
+        // r is non-zero, which also prevents s from being zero (as s u2 = r)
+        self.foreign_assert_nonzero(zero, n, r);
+        let u1 = self.foreign_div(zero, n, z, s);
+        let u2 = self.foreign_div(zero, n, r, s);
+
+        // R = u1 G + u2 Q
+        let bits = curve.scalar_bits();
+        let u1_bits = self.foreign_to_bits(zero, u1, bits);
+        let u2_bits = self.foreign_to_bits(zero, u2, bits);
+        let generator = self.point_constant(curve, &curve.generator);
+        let point =
+            self.point_multi_scale(zero, curve, &[(generator, u1_bits), (public_key, u2_bits)]);
+
+        // x(R) mod n = r
+        let x = self.foreign_reduce(zero, n, point.x);
+        self.foreign_assert_eq(x, r);
+    }
+}
+
+impl<F: PrimeField, C: ForeignCurveGadgets<F>> Ecdsa<F> for C {}
@@ -0,0 +1,412 @@
+//! Arithmetic on short Weierstrass curves `y^2 = x^3 + a x + b` defined over a foreign field.
+//!
+//! Points are represented in affine coordinates, with incomplete addition formulas:
+//! adding two points with the same `x` coordinate can not be proven
+//! (the gadget checks that their difference is invertible).
+//! Scalar multiplication avoids those cases by starting from an offset point
+//! of unknown discrete logarithm, which is removed at the end.
+
+use super::foreign_field::{inverse_mod, ForeignField, ForeignModulus, ForeignVar};
+use crate::writer::Var;
+use ark_ff::PrimeField;
+use blake2::{Blake2b512, Digest};
+use num_bigint::BigUint;
+use o1_utils::foreign_field::{P256_MOD, P256_ORDER, SECP256K1_MOD, SECP256K1_ORDER};
+
+/// The seed of the offset point used by scalar multiplications
+const OFFSET_SEED: &[u8] = b"kimchi foreign curve offset";
+
+/// The coefficient `b` of P-256 (in big endian)
+const P256_B: &[u8] = &[
+    0x5A, 0xC6, 0x35, 0xD8, 0xAA, 0x3A, 0x93, 0xE7, 0xB3, 0xEB, 0xBD, 0x55, 0x76, 0x98, 0x86, 0xBC,
+    0x65, 0x1D, 0x06, 0xB0, 0xCC, 0x53, 0xB0, 0xF6, 0x3B, 0xCE, 0x3C, 0x3E, 0x27, 0xD2, 0x60, 0x4B,
+];
+
+/// The `x` coordinate of the generator of P-256 (in big endian)
+const P256_GX: &[u8] = &[
+    0x6B, 0x17, 0xD1, 0xF2, 0xE1, 0x2C, 0x42, 0x47, 0xF8, 0xBC, 0xE6, 0xE5, 0x63, 0xA4, 0x40, 0xF2,
+    0x77, 0x03, 0x7D, 0x81, 0x2D, 0xEB, 0x33, 0xA0, 0xF4, 0xA1, 0x39, 0x45, 0xD8, 0x98, 0xC2, 0x96,
+];
+
+/// The `y` coordinate of the generator of P-256 (in big endian)
+const P256_GY: &[u8] = &[
+    0x4F, 0xE3, 0x42, 0xE2, 0xFE, 0x1A, 0x7F, 0x9B, 0x8E, 0xE7, 0xEB, 0x4A, 0x7C, 0x0F, 0x9E, 0x16,
+    0x2B, 0xCE, 0x33, 0x57, 0x6B, 0x31, 0x5E, 0xCE, 0xCB, 0xB6, 0x40, 0x68, 0x37, 0xBF, 0x51, 0xF5,
+];
+
+/// The `x` coordinate of the generator of secp256k1 (in big endian)
+const SECP256K1_GX: &[u8] = &[
+    0x79, 0xBE, 0x66, 0x7E, 0xF9, 0xDC, 0xBB, 0xAC, 0x55, 0xA0, 0x62, 0x95, 0xCE, 0x87, 0x0B, 0x07,
+    0x02, 0x9B, 0xFC, 0xDB, 0x2D, 0xCE, 0x28, 0xD9, 0x59, 0xF2, 0x81, 0x5B, 0x16, 0xF8, 0x17, 0x98,
+];
+
+/// The `y` coordinate of the generator of secp256k1 (in big endian)
+const SECP256K1_GY: &[u8] = &[
+    0x48, 0x3A, 0xDA, 0x77, 0x26, 0xA3, 0xC4, 0x65, 0x5D, 0xA4, 0xFB, 0xFC, 0x0E, 0x11, 0x08, 0xA8,
+    0xFD, 0x17, 0xB4, 0x48, 0xA6, 0x85, 0x54, 0x19, 0x9C, 0x47, 0xD0, 0x8F, 0xFB, 0x10, 0xD4, 0xB8,
+];
+
+/// A point of a foreign curve, in affine coordinates.
+/// The point at infinity is represented by `None` where needed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeignPoint {
+    /// The `x` coordinate
+    pub x: BigUint,
+    /// The `y` coordinate
+    pub y: BigUint,
+}
+
+/// The parameters of a prime-order short Weierstrass curve over a foreign field.
+#[derive(Debug, Clone)]
+pub struct ForeignCurve {
+    /// The modulus of the base field
+    pub base: ForeignModulus,
+    /// The order of the group, i.e. the modulus of the scalar field
+    pub scalar: ForeignModulus,
+    /// The coefficient `a`
+    pub a: BigUint,
+    /// The coefficient `b`
+    pub b: BigUint,
+    /// The generator
+    pub generator: ForeignPoint,
+}
+
+impl ForeignCurve {
+    /// The NIST P-256 curve (a.k.a. secp256r1).
+    pub fn p256() -> Self {
+        let base = ForeignModulus::from_be(P256_MOD);
+        Self {
+            a: base.modulus() - 3u32,
+            base,
+            scalar: ForeignModulus::from_be(P256_ORDER),
+            b: BigUint::from_bytes_be(P256_B),
+            generator: ForeignPoint {
+                x: BigUint::from_bytes_be(P256_GX),
+                y: BigUint::from_bytes_be(P256_GY),
+            },
+        }
+    }
+
+    /// The secp256k1 curve (used by Bitcoin and Ethereum).
+    pub fn secp256k1() -> Self {
+        Self {
+            base: ForeignModulus::from_be(SECP256K1_MOD),
+            scalar: ForeignModulus::from_be(SECP256K1_ORDER),
+            a: BigUint::from(0u32),
+            b: BigUint::from(7u32),
+            generator: ForeignPoint {
+                x: BigUint::from_bytes_be(SECP256K1_GX),
+                y: BigUint::from_bytes_be(SECP256K1_GY),
+            },
+        }
+    }
+
+    /// Returns the bit length of the scalars.
+    pub fn scalar_bits(&self) -> usize {
+        self.scalar.modulus().bits() as usize
+    }
+
+    /// Checks that a point satisfies the curve equation.
+    pub fn is_on_curve(&self, p: &ForeignPoint) -> bool {
+        let m = self.base.modulus();
+        let lhs = &p.y * &p.y % m;
+        let rhs = (&p.x * &p.x * &p.x + &self.a * &p.x + &self.b) % m;
+        p.x < *m && p.y < *m && lhs == rhs
+    }
+
+    /// Returns `-p`.
+    pub fn neg(&self, p: &ForeignPoint) -> ForeignPoint {
+        let m = self.base.modulus();
+        ForeignPoint {
+            x: p.x.clone(),
+            y: (m - &p.y) % m,
+        }
+    }
+
+    /// Adds two points (`None` being the point at infinity).
+    pub fn add(&self, p: &Option<ForeignPoint>, q: &Option<ForeignPoint>) -> Option<ForeignPoint> {
+        let m = self.base.modulus();
+        let (p, q) = match (p, q) {
+            (None, q) => return q.clone(),
+            (p, None) => return p.clone(),
+            (Some(p), Some(q)) => (p, q),
+        };
+
+        let lambda = if p.x == q.x {
+            if (&p.y + &q.y) % m == BigUint::from(0u32) {
+                return None;
+            }
+            // tangent: (3 x^2 + a) / 2 y
+            (BigUint::from(3u32) * &p.x * &p.x + &self.a) * inverse_mod(&(&p.y << 1), m) % m
+        } else {
+            // chord: (y_q - y_p) / (x_q - x_p)
+            (&q.y + m - &p.y) * inverse_mod(&((&q.x + m - &p.x) % m), m) % m
+        };
+
+        let x = (&lambda * &lambda + m + m - &p.x - &q.x) % m;
+        let y = (lambda * ((&p.x + m - &x) % m) + m - &p.y) % m;
+        Some(ForeignPoint { x, y })
+    }
+
+    /// Computes `k * p` with double-and-add.
+    pub fn scale(&self, p: &ForeignPoint, k: &BigUint) -> Option<ForeignPoint> {
+        let mut acc = None;
+        for i in (0..k.bits()).rev() {
+            acc = self.add(&acc, &acc);
+            if k.bit(i) {
+                acc = self.add(&acc, &Some(p.clone()));
+            }
+        }
+        acc
+    }
+
+    /// Returns a point of unknown discrete logarithm, derived from `seed`
+    /// by hashing to the `x` coordinate until it is on the curve.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the base field modulus is not `3 mod 4`.
+    pub fn hash_to_point(&self, seed: &[u8]) -> ForeignPoint {
+        let m = self.base.modulus();
+        assert_eq!(m % 4u32, BigUint::from(3u32));
+        let sqrt_exp = (m + 1u32) >> 2;
+
+        for counter in 0u32.. {
+            let mut hasher = Blake2b512::new();
+            hasher.update(seed);
+            hasher.update(counter.to_le_bytes());
+            let x = BigUint::from_bytes_le(&hasher.finalize()) % m;
+
+            let rhs = (&x * &x * &x + &self.a * &x + &self.b) % m;
+            let y = rhs.modpow(&sqrt_exp, m);
+            let p = ForeignPoint { x, y };
+            if self.is_on_curve(&p) {
+                return p;
+            }
+        }
+        unreachable!()
+    }
+}
+
+/// A variable containing a point of a foreign curve, in affine coordinates.
+#[derive(Debug, Clone, Copy)]
+pub struct ForeignPointVar<F> {
+    /// The `x` coordinate
+    pub x: ForeignVar<F>,
+    /// The `y` coordinate
+    pub y: ForeignVar<F>,
+}
+
+impl<F: PrimeField> ForeignPointVar<F> {
+    /// Returns the value of the point.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the variable has no value (i.e. in circuit mode).
+    pub fn val(&self) -> ForeignPoint {
+        ForeignPoint {
+            x: self.x.val(),
+            y: self.y.val(),
+        }
+    }
+}
+
+/// Gadgets for foreign curve arithmetic.
+///
+/// The `zero` variable taken by its functions is described
+/// in [the module documentation](super#the-zero-variable).
+pub trait ForeignCurveGadgets<F: PrimeField>: ForeignField<F> {
+    /// Creates a point from the value returned by `g`,
+    /// and constrains it to be on the curve.
+    fn point_var<G>(&mut self, zero: Var<F>, curve: &ForeignCurve, g: G) -> ForeignPointVar<F>
+    where
+        G: FnOnce() -> ForeignPoint,
+    {
+        let mut value = None;
+        let x = self.foreign_var(zero, &curve.base, || {
+            let p = g();
+            let x = p.x.clone();
+            value = Some(p);
+            x
+        });
+        let y = self.foreign_var(zero, &curve.base, || value.unwrap().y);
+        let p = ForeignPointVar { x, y };
+        self.point_assert_on_curve(zero, curve, p);
+        p
+    }
+
+    /// Creates a point holding the constant `p`.
+    fn point_constant(&mut self, curve: &ForeignCurve, p: &ForeignPoint) -> ForeignPointVar<F> {
+        assert!(curve.is_on_curve(p));
+        ForeignPointVar {
+            x: self.foreign_constant(&curve.base, &p.x),
+            y: self.foreign_constant(&curve.base, &p.y),
+        }
+    }
+
+    /// Constrains `y^2 = x^3 + a x + b`.
+    fn point_assert_on_curve(&mut self, zero: Var<F>, curve: &ForeignCurve, p: ForeignPointVar<F>) {
+        let m = &curve.base;
+        let a = self.foreign_constant(m, &curve.a);
+        let b = self.foreign_constant(m, &curve.b);
+
+        // (x^2 + a) x + b = y^2
+        let x2 = self.foreign_mul(zero, m, p.x, p.x);
+        let x2_a = self.foreign_add(zero, m, x2, a);
+        let x3_ax = self.foreign_mul(zero, m, x2_a, p.x);
+        let rhs = self.foreign_add(zero, m, x3_ax, b);
+        self.foreign_assert_mul(zero, m, p.y, p.y, rhs);
+    }
+
+    /// Returns `p + q`, for points with distinct `x` coordinates.
+    fn point_add(
+        &mut self,
+        zero: Var<F>,
+        curve: &ForeignCurve,
+        p: ForeignPointVar<F>,
+        q: ForeignPointVar<F>,
+    ) -> ForeignPointVar<F> {
+        let m = &curve.base;
+
+        // lambda = (y_q - y_p) / (x_q - x_p)
+        let dx = self.foreign_sub(zero, m, q.x, p.x);
+        self.foreign_assert_nonzero(zero, m, dx);
+        let dy = self.foreign_sub(zero, m, q.y, p.y);
+        let lambda = self.foreign_div(zero, m, dy, dx);
+
+        self.point_from_slope(zero, curve, lambda, p, q.x)
+    }
+
+    /// Returns `2 p`.
+    /// As the curve has prime order, `y` is never zero.
+    fn point_double(
+        &mut self,
+        zero: Var<F>,
+        curve: &ForeignCurve,
+        p: ForeignPointVar<F>,
+    ) -> ForeignPointVar<F> {
+        let m = &curve.base;
+        let a = self.foreign_constant(m, &curve.a);
+
+        // lambda = (3 x^2 + a) / 2 y
+        let x2 = self.foreign_mul(zero, m, p.x, p.x);
+        let x2_2 = self.foreign_add(zero, m, x2, x2);
+        let x2_3 = self.foreign_add(zero, m, x2_2, x2);
+        let num = self.foreign_add(zero, m, x2_3, a);
+        let den = self.foreign_add(zero, m, p.y, p.y);
+        let lambda = self.foreign_div(zero, m, num, den);
+
+        self.point_from_slope(zero, curve, lambda, p, p.x)
+    }
+
+    /// Returns the third intersection of the line of slope `lambda` going through `p`
+    /// (and a point of abscissa `x_q`) with the curve, negated.
+    fn point_from_slope(
+        &mut self,
+        zero: Var<F>,
+        curve: &ForeignCurve,
+        lambda: ForeignVar<F>,
+        p: ForeignPointVar<F>,
+        x_q: ForeignVar<F>,
+    ) -> ForeignPointVar<F> {
+        let m = &curve.base;
+
+        // x = lambda^2 - x_p - x_q
+        let lambda2 = self.foreign_mul(zero, m, lambda, lambda);
+        let t = self.foreign_sub(zero, m, lambda2, p.x);
+        let x = self.foreign_sub(zero, m, t, x_q);
+
+        // y = lambda (x_p - x) - y_p
+        let dx = self.foreign_sub(zero, m, p.x, x);
+        let t = self.foreign_mul(zero, m, lambda, dx);
+        let y = self.foreign_sub(zero, m, t, p.y);
+
+        ForeignPointVar { x, y }
+    }
+
+    /// Returns `-p`.
+    fn point_neg(
+        &mut self,
+        zero: Var<F>,
+        curve: &ForeignCurve,
+        p: ForeignPointVar<F>,
+    ) -> ForeignPointVar<F> {
+        ForeignPointVar {
+            x: p.x,
+            y: self.foreign_neg(zero, &curve.base, p.y),
+        }
+    }
+
+    /// Returns `t` if `b` is one, `f` if it is zero.
+    /// The boolean `b` is not constrained by this function.
+    fn point_cond_select(
+        &mut self,
+        b: Var<F>,
+        t: ForeignPointVar<F>,
+        f: ForeignPointVar<F>,
+    ) -> ForeignPointVar<F> {
+        ForeignPointVar {
+            x: self.foreign_cond_select(b, t.x, f.x),
+            y: self.foreign_cond_select(b, t.y, f.y),
+        }
+    }
+
+    /// Computes `sum_i k_i p_i` where the scalars `k_i` are given as
+    /// constrained booleans (in little-endian order, all of the same length),
+    /// sharing the doublings between all the scalars.
+    ///
+    /// The result must not be the point at infinity.
+    fn point_multi_scale(
+        &mut self,
+        zero: Var<F>,
+        curve: &ForeignCurve,
+        terms: &[(ForeignPointVar<F>, Vec<Var<F>>)],
+    ) -> ForeignPointVar<F> {
+        let bits = terms[0].1.len();
+        assert!(terms.iter().all(|(_, k)| k.len() == bits));
+
+        // the sums of all the subsets of points, indexed by the bits of the subset
+        let mut table = vec![None];
+        for (p, _) in terms {
+            for i in 0..table.len() {
+                let sum = match table[i] {
+                    None => *p,
+                    Some(q) => self.point_add(zero, curve, q, *p),
+                };
+                table.push(Some(sum));
+            }
+        }
+        // the empty subset is never added, so any point can stand for it
+        table[0] = table[1];
+        let table: Vec<_> = table.into_iter().map(Option::unwrap).collect();
+
+        let offset = curve.hash_to_point(OFFSET_SEED);
+        let mut acc = self.point_constant(curve, &offset);
+
+        for i in (0..bits).rev() {
+            acc = self.point_double(zero, curve, acc);
+
+            // select the subset of points given by the i-th bits of the scalars
+            let mut candidates = table.clone();
+            let mut any = zero;
+            for (_, k) in terms {
+                any = self.or(any, k[i]);
+                candidates = candidates
+                    .chunks(2)
+                    .map(|pair| self.point_cond_select(k[i], pair[1], pair[0]))
+                    .collect();
+            }
+
+            let sum = self.point_add(zero, curve, acc, candidates[0]);
+            acc = self.point_cond_select(any, sum, acc);
+        }
+
+        // remove the offset, which has been doubled `bits` times
+        let shift = BigUint::from(1u32) << bits;
+        let shifted = curve.scale(&offset, &(shift % curve.scalar.modulus()));
+        let correction = self.point_constant(curve, &curve.neg(&shifted.unwrap()));
+        self.point_add(zero, curve, acc, correction)
+    }
+}
+
+impl<F: PrimeField, C: ForeignField<F>> ForeignCurveGadgets<F> for C {}
@@ -0,0 +1,438 @@
+//! Foreign field arithmetic.
+//!
+//! Elements of a foreign field (of modulus `p` of at most 264 bits) are represented
+//! by three 88-bit limbs, in little-endian order, each of them range-checked.
+//! All the gadgets of this module output canonical elements (i.e. `x < p`),
+//! and assume that their inputs are canonical.
+//!
+//! A relation such as `a * b = q * p + r` is checked over the integers, limb by limb:
+//! for each limb position `k`, the sum `D_k` of the terms of weight `2^(88 k)`
+//! plus the carry coming from the previous position must be `2^88` times the next carry.
+//! Carries are signed, so they are range-checked after being shifted by a constant.
+//! As limbs, carries, and sums all stay far below the native modulus,
+//! these relations cannot wrap around the native field.
+
+use super::arith::{evaluate, Arith};
+use super::range_check::{RangeCheck, RANGE_CHECK_BITS};
+use crate::writer::Var;
+use ark_ff::{One, PrimeField, Zero};
+use num_bigint::BigUint;
+use o1_utils::foreign_field::{ForeignElement, LIMB_BITS, LIMB_COUNT};
+
+/// Bit length of the carries of a multiplication (shifted to be positive).
+/// With 88-bit limbs the limb sums `D_k` have at most 179 bits (in absolute value).
+const MUL_CARRY_BITS: usize = 93;
+
+/// Bit length of the carries of an addition (shifted to be positive).
+const ADD_CARRY_BITS: usize = 3;
+
+/// The modulus of a foreign field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeignModulus {
+    modulus: BigUint,
+}
+
+impl ForeignModulus {
+    /// Creates a foreign modulus.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the modulus does not fit in three limbs.
+    pub fn new(modulus: BigUint) -> Self {
+        assert!(modulus.bits() as usize <= LIMB_BITS * LIMB_COUNT);
+        assert!(modulus > BigUint::one());
+        Self { modulus }
+    }
+
+    /// Creates a foreign modulus from its big-endian bytes.
+    pub fn from_be(bytes: &[u8]) -> Self {
+        Self::new(BigUint::from_bytes_be(bytes))
+    }
+
+    /// Returns the modulus.
+    pub fn modulus(&self) -> &BigUint {
+        &self.modulus
+    }
+
+    /// Returns `2^264 - p`, which is used to check that elements are canonical.
+    fn negated(&self) -> BigUint {
+        (BigUint::one() << (LIMB_BITS * LIMB_COUNT)) - &self.modulus
+    }
+}
+
+/// A variable containing a foreign field element, as three 88-bit limbs (little-endian).
+#[derive(Debug, Clone, Copy)]
+pub struct ForeignVar<F> {
+    /// The limbs, in little-endian order
+    pub limbs: [Var<F>; LIMB_COUNT],
+}
+
+impl<F: PrimeField> ForeignVar<F> {
+    /// Returns the value of the element.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the variable has no value (i.e. in circuit mode).
+    pub fn val(&self) -> BigUint {
+        ForeignElement::<F, LIMB_COUNT>::new(self.limbs.map(|l| l.val())).to_big()
+    }
+}
+
+/// Returns the limbs of a foreign element as native field elements.
+fn limbs<F: PrimeField>(x: &BigUint) -> [F; LIMB_COUNT] {
+    let fe = ForeignElement::<F, LIMB_COUNT>::from_biguint(x.clone());
+    [fe[0], fe[1], fe[2]]
+}
+
+/// Computes `x^-1 mod p`, for a prime `p`.
+pub fn inverse_mod(x: &BigUint, p: &BigUint) -> BigUint {
+    assert!(!x.is_zero(), "zero has no inverse");
+    x.modpow(&(p - 2u32), p)
+}
+
+/// Foreign field arithmetic gadgets.
+///
+/// The `zero` variable taken by its functions is described
+/// in [the module documentation](super#the-zero-variable).
+pub trait ForeignField<F: PrimeField>: Arith<F> + RangeCheck<F> {
+    /// Creates a foreign element from the value returned by `g`,
+    /// and constrains it to be canonical.
+    fn foreign_var<G>(&mut self, zero: Var<F>, m: &ForeignModulus, g: G) -> ForeignVar<F>
+    where
+        G: FnOnce() -> BigUint,
+    {
+        let x = self.foreign_limbs(g);
+        self.foreign_assert_canonical(zero, m, x);
+        x
+    }
+
+    /// Creates a foreign element holding the constant `x`.
+    fn foreign_constant(&mut self, m: &ForeignModulus, x: &BigUint) -> ForeignVar<F> {
+        assert!(x < m.modulus());
+        ForeignVar {
+            limbs: limbs::<F>(x).map(|l| self.constant(l)),
+        }
+    }
+
+    /// Returns `a + b`.
+    fn foreign_add(
+        &mut self,
+        zero: Var<F>,
+        m: &ForeignModulus,
+        a: ForeignVar<F>,
+        b: ForeignVar<F>,
+    ) -> ForeignVar<F> {
+        let r = self.foreign_var(zero, m, || (a.val() + b.val()) % m.modulus());
+
+        // a + b - q * p - r = 0, with q a boolean
+        let q = self.var(|| F::from(u64::from(a.val() + b.val() >= *m.modulus())));
+        self.assert_boolean(q);
+
+        let p = limbs::<F>(m.modulus());
+        let columns = (0..LIMB_COUNT)
+            .map(|k| {
+                vec![
+                    (F::one(), a.limbs[k]),
+                    (F::one(), b.limbs[k]),
+                    (-p[k], q),
+                    (-F::one(), r.limbs[k]),
+                ]
+            })
+            .collect();
+        self.foreign_assert_zero(zero, columns, ADD_CARRY_BITS);
+
+        r
+    }
+
+    /// Returns `a - b`.
+    fn foreign_sub(
+        &mut self,
+        zero: Var<F>,
+        m: &ForeignModulus,
+        a: ForeignVar<F>,
+        b: ForeignVar<F>,
+    ) -> ForeignVar<F> {
+        let r = self.foreign_var(zero, m, || (a.val() + m.modulus() - b.val()) % m.modulus());
+
+        // a - b + q * p - r = 0, with q a boolean
+        let q = self.var(|| F::from(u64::from(a.val() < b.val())));
+        self.assert_boolean(q);
+
+        let p = limbs::<F>(m.modulus());
+        let columns = (0..LIMB_COUNT)
+            .map(|k| {
+                vec![
+                    (F::one(), a.limbs[k]),
+                    (-F::one(), b.limbs[k]),
+                    (p[k], q),
+                    (-F::one(), r.limbs[k]),
+                ]
+            })
+            .collect();
+        self.foreign_assert_zero(zero, columns, ADD_CARRY_BITS);
+
+        r
+    }
+
+    /// Returns `-a`.
+    fn foreign_neg(&mut self, zero: Var<F>, m: &ForeignModulus, a: ForeignVar<F>) -> ForeignVar<F> {
+        let z = ForeignVar {
+            limbs: [zero; LIMB_COUNT],
+        };
+        self.foreign_sub(zero, m, z, a)
+    }
+
+    /// Returns `a * b`.
+    fn foreign_mul(
+        &mut self,
+        zero: Var<F>,
+        m: &ForeignModulus,
+        a: ForeignVar<F>,
+        b: ForeignVar<F>,
+    ) -> ForeignVar<F> {
+        let r = self.foreign_var(zero, m, || (a.val() * b.val()) % m.modulus());
+        self.foreign_assert_mul(zero, m, a, b, r);
+        r
+    }
+
+    /// Constrains `a * b = c`.
+    fn foreign_assert_mul(
+        &mut self,
+        zero: Var<F>,
+        m: &ForeignModulus,
+        a: ForeignVar<F>,
+        b: ForeignVar<F>,
+        c: ForeignVar<F>,
+    ) {
+        // a * b - q * p - c = 0, where q only needs to fit in three limbs
+        let q = self.foreign_limbs(|| (a.val() * b.val()) / m.modulus());
+        self.range_check_limbs(zero, &q.limbs);
+
+        let p = limbs::<F>(m.modulus());
+        let mut columns = vec![vec![]; 2 * LIMB_COUNT - 1];
+        for i in 0..LIMB_COUNT {
+            for j in 0..LIMB_COUNT {
+                let ab = self.mul(a.limbs[i], b.limbs[j]);
+                columns[i + j].push((F::one(), ab));
+                columns[i + j].push((-p[j], q.limbs[i]));
+            }
+            columns[i].push((-F::one(), c.limbs[i]));
+        }
+        self.foreign_assert_zero(zero, columns, MUL_CARRY_BITS);
+    }
+
+    /// Returns `a^-1`.
+    ///
+    /// # Panics
+    ///
+    /// In witness mode, will panic if `a` is zero (which can not be proven).
+    fn foreign_inv(&mut self, zero: Var<F>, m: &ForeignModulus, a: ForeignVar<F>) -> ForeignVar<F> {
+        let inv = self.foreign_var(zero, m, || inverse_mod(&a.val(), m.modulus()));
+        let one = self.foreign_constant(m, &BigUint::one());
+        self.foreign_assert_mul(zero, m, a, inv, one);
+        inv
+    }
+
+    /// Returns `a / b`.
+    ///
+    /// # Panics
+    ///
+    /// In witness mode, will panic if `b` is zero (which can not be proven).
+    fn foreign_div(
+        &mut self,
+        zero: Var<F>,
+        m: &ForeignModulus,
+        a: ForeignVar<F>,
+        b: ForeignVar<F>,
+    ) -> ForeignVar<F> {
+        let res = self.foreign_var(zero, m, || {
+            a.val() * inverse_mod(&b.val(), m.modulus()) % m.modulus()
+        });
+        self.foreign_assert_mul(zero, m, b, res, a);
+        res
+    }
+
+    /// Constrains `a` to be non-zero, by exhibiting its inverse.
+    fn foreign_assert_nonzero(&mut self, zero: Var<F>, m: &ForeignModulus, a: ForeignVar<F>) {
+        self.foreign_inv(zero, m, a);
+    }
+
+    /// Reduces an element of another foreign field (or any three-limb integer
+    /// of at most 264 bits) modulo `m`.
+    fn foreign_reduce(
+        &mut self,
+        zero: Var<F>,
+        m: &ForeignModulus,
+        a: ForeignVar<F>,
+    ) -> ForeignVar<F> {
+        let one = self.foreign_constant(m, &BigUint::one());
+        self.foreign_mul(zero, m, a, one)
+    }
+
+    /// Constrains `a = b`. As elements are canonical, this is done limb by limb.
+    fn foreign_assert_eq(&mut self, a: ForeignVar<F>, b: ForeignVar<F>) {
+        for (x, y) in a.limbs.into_iter().zip(b.limbs) {
+            self.assert_eq(x, y);
+        }
+    }
+
+    /// Constrains `a` to be even, by writing its lowest limb as `2 t` with `t` of 87 bits.
+    fn foreign_assert_even(&mut self, zero: Var<F>, a: ForeignVar<F>) {
+        let half = self.var(|| a.limbs[0].val() / F::from(2u64));
+        self.assert_linear(
+            &[(F::from(2u64), half), (-F::one(), a.limbs[0])],
+            F::zero(),
+        );
+        self.range_check_bits(zero, &[(half, LIMB_BITS - 1)]);
+    }
+
+    /// Returns `t` if `b` is one, `f` if it is zero.
+    /// The boolean `b` is not constrained by this function.
+    fn foreign_cond_select(
+        &mut self,
+        b: Var<F>,
+        t: ForeignVar<F>,
+        f: ForeignVar<F>,
+    ) -> ForeignVar<F> {
+        let mut limbs = t.limbs;
+        for (k, limb) in limbs.iter_mut().enumerate() {
+            *limb = self.cond_select(b, t.limbs[k], f.limbs[k]);
+        }
+        ForeignVar { limbs }
+    }
+
+    /// Decomposes `a` into `bits` constrained booleans, in little-endian order.
+    fn foreign_to_bits(&mut self, zero: Var<F>, a: ForeignVar<F>, bits: usize) -> Vec<Var<F>> {
+        assert!(bits <= LIMB_BITS * LIMB_COUNT);
+
+        let mut res = vec![];
+        for (k, limb) in a.limbs.into_iter().enumerate() {
+            let len = bits.saturating_sub(k * LIMB_BITS).min(LIMB_BITS);
+            let limb_bits: Vec<_> = (0..len).map(|i| self.limb(limb, i, i + 1)).collect();
+
+            let mut terms = vec![(-F::one(), limb)];
+            let mut power = F::one();
+            for b in &limb_bits {
+                self.assert_boolean(*b);
+                terms.push((power, *b));
+                power.double_in_place();
+            }
+            if len == 0 {
+                self.assert_eq(limb, zero);
+            } else {
+                self.assert_linear(&terms, F::zero());
+            }
+            res.extend(limb_bits);
+        }
+        res
+    }
+
+    /// Creates the limbs of a foreign element from the value returned by `g`,
+    /// without constraining them.
+    fn foreign_limbs<G>(&mut self, g: G) -> ForeignVar<F>
+    where
+        G: FnOnce() -> BigUint,
+    {
+        let mut value = None;
+        let l0 = self.var(|| {
+            let x = limbs::<F>(&g());
+            value = Some(x);
+            x[0]
+        });
+        let l1 = self.var(|| value.unwrap()[1]);
+        let l2 = self.var(|| value.unwrap()[2]);
+        ForeignVar {
+            limbs: [l0, l1, l2],
+        }
+    }
+
+    /// Constrains the limbs of `x` to be 88 bits, and `x` to be smaller than the modulus.
+    ///
+    /// The latter is done by checking that `x + 2^264 - p` does not overflow 264 bits.
+    fn foreign_assert_canonical(&mut self, zero: Var<F>, m: &ForeignModulus, x: ForeignVar<F>) {
+        let neg = m.negated();
+        let neg_limbs = limbs::<F>(&neg);
+        let two_to_limb = F::from(2u64).pow([LIMB_BITS as u64]);
+
+        // y = x + 2^264 - p
+        let y = self.foreign_limbs(|| x.val() + &neg);
+        // c_k is the carry out of the limb position k
+        let carries: Vec<_> = (1..LIMB_COUNT)
+            .map(|k| {
+                self.var(|| {
+                    let bound = BigUint::one() << (LIMB_BITS * k);
+                    let low_sum = x.val() % &bound + &neg % &bound;
+                    F::from(u64::from(low_sum >= bound))
+                })
+            })
+            .collect();
+
+        // y_k = x_k + neg_k + c_{k-1} - 2^88 c_k
+        for k in 0..LIMB_COUNT {
+            let mut terms = vec![(F::one(), x.limbs[k]), (-F::one(), y.limbs[k])];
+            if k > 0 {
+                terms.push((F::one(), carries[k - 1]));
+            }
+            if k < LIMB_COUNT - 1 {
+                self.assert_boolean(carries[k]);
+                terms.push((-two_to_limb, carries[k]));
+            }
+            self.assert_linear(&terms, neg_limbs[k]);
+        }
+
+        let mut values = x.limbs.to_vec();
+        values.extend(y.limbs);
+        self.range_check_limbs(zero, &values);
+    }
+
+    /// Constrains the integer `sum_k 2^(88 k) D_k` to be zero,
+    /// where `D_k` is the linear combination given in `columns[k]`.
+    /// The carries are constrained to `carry_bits` bits (once shifted to be positive).
+    fn foreign_assert_zero(
+        &mut self,
+        zero: Var<F>,
+        columns: Vec<Vec<(F, Var<F>)>>,
+        carry_bits: usize,
+    ) {
+        let two_to_limb = F::from(2u64).pow([LIMB_BITS as u64]);
+        let two_to_limb_inv = two_to_limb.inverse().unwrap();
+        let offset = F::from(2u64).pow([(carry_bits - 1) as u64]);
+
+        let mut checks = vec![];
+        // the carry from the previous position, as a linear combination
+        let mut carry: Vec<(F, Var<F>)> = vec![];
+        let mut carry_constant = F::zero();
+
+        let last = columns.len() - 1;
+        for (k, mut terms) in columns.into_iter().enumerate() {
+            terms.extend_from_slice(&carry);
+            let constant = carry_constant;
+
+            if k == last {
+                self.assert_linear(&terms, constant);
+                break;
+            }
+
+            // the shifted carry, split into an 88-bit limb and a high part
+            let shifted = self.var(|| evaluate(&terms, constant) * two_to_limb_inv + offset);
+            let low_bits = carry_bits.min(RANGE_CHECK_BITS);
+            let low = self.limb(shifted, 0, low_bits);
+            carry = vec![(F::one(), low)];
+            checks.push((low, low_bits));
+            if carry_bits > RANGE_CHECK_BITS {
+                let high = self.limb(shifted, RANGE_CHECK_BITS, carry_bits);
+                carry.push((two_to_limb, high));
+                checks.push((high, carry_bits - RANGE_CHECK_BITS));
+            }
+            carry_constant = -offset;
+
+            // D_k + c_{k-1} - 2^88 c_k = 0
+            terms.extend(carry.iter().map(|(c, v)| (-two_to_limb * c, *v)));
+            self.assert_linear(&terms, constant + two_to_limb * offset);
+        }
+
+        self.range_check_bits(zero, &checks);
+    }
+}
+
+impl<F: PrimeField, C: Arith<F> + RangeCheck<F>> ForeignField<F> for C {}
@@ -0,0 +1,214 @@
+//! Multiplication in `GF(2^128)`, as used by GHASH (the authenticator of AES-GCM).
+//!
+//! Elements are polynomials over `GF(2)` modulo `x^128 + x^7 + x^2 + x + 1`, with the bit order
+//! of GCM: the coefficient of `x^i` is the bit `127 - i` of the block read as a big-endian integer.
+//!
+//! In the circuit, an element is given by its 128 coefficient bits. The carry-less product is
+//! computed over the integers: `a` and `b` are split into 16-bit chunks, which are spread
+//! to one byte per bit, so that the integer product of two spread chunks counts, in each byte,
+//! the pairs of set bits of the corresponding degree. These counts are at most 128,
+//! hence they fit in a byte and the sums of products do not wrap around the native field.
+//! The bytes are extracted and range-checked with [`super::range_check`] (i.e. with lookups).
+//!
+//! The reduction modulo the polynomial is linear, so the coefficient of `x^i` of the result
+//! is the parity of the sum of the counts of all the degrees reducing to `x^i`:
+//! this parity is the only bit extracted for each output bit.
+
+use super::range_check::RangeCheck;
+use super::uint32::{Uint32, U32};
+use crate::writer::Var;
+use ark_ff::{BigInteger, PrimeField};
+
+/// Number of coefficients of an element
+pub const GF128_BITS: usize = 128;
+
+/// The reduction constant of GCM, i.e. `x^7 + x^2 + x + 1` in the GCM bit order
+const R: u128 = 0xE1 << 120;
+
+/// Number of bits of the chunks which are multiplied together
+const CHUNK_BITS: usize = 16;
+
+/// Number of chunks of an element
+const CHUNKS: usize = GF128_BITS / CHUNK_BITS;
+
+/// Number of bytes of the product of two spread chunks (the degrees `0..2 CHUNK_BITS - 1`)
+const PRODUCT_BYTES: usize = 2 * CHUNK_BITS - 1;
+
+/// Multiplies two blocks (as big-endian integers), outside of the circuit.
+/// This is the algorithm of the GCM specification.
+pub fn gf128_mul_native(a: u128, b: u128) -> u128 {
+    let mut res = 0;
+    let mut v = b;
+    for i in 0..GF128_BITS {
+        if (a >> (127 - i)) & 1 == 1 {
+            res ^= v;
+        }
+        v = if v & 1 == 1 { (v >> 1) ^ R } else { v >> 1 };
+    }
+    res
+}
+
+/// Computes `GHASH_h(blocks)`, outside of the circuit.
+pub fn ghash_native(h: u128, blocks: &[u128]) -> u128 {
+    blocks.iter().fold(0, |y, x| gf128_mul_native(y ^ x, h))
+}
+
+/// Returns the reduction of `x^degree` (for `degree < 2 * 128 - 1`),
+/// with the bit `i` of the result being the coefficient of `x^i`.
+fn reduce_degree(degree: usize) -> u128 {
+    if degree < GF128_BITS {
+        1 << degree
+    } else {
+        // x^degree = x^(degree - 128) (x^7 + x^2 + x + 1)
+        [121, 126, 127, 128]
+            .iter()
+            .fold(0, |acc, k| acc ^ reduce_degree(degree - k))
+    }
+}
+
+/// Returns the value of a small field element.
+fn small<F: PrimeField>(x: F) -> u64 {
+    let repr = x.into_repr();
+    assert!(repr.num_bits() <= 64);
+    repr.as_ref()[0]
+}
+
+/// A variable containing an element of `GF(2^128)`.
+#[derive(Debug, Clone, Copy)]
+pub struct Gf128Var<F> {
+    /// The coefficients of `x^0, ..., x^127`, as constrained booleans
+    pub bits: [Var<F>; GF128_BITS],
+}
+
+impl<F: PrimeField> Gf128Var<F> {
+    /// Returns the value of the element, as a big-endian block.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the variable has no value (i.e. in circuit mode).
+    pub fn val(&self) -> u128 {
+        self.bits.iter().enumerate().fold(0, |acc, (i, b)| {
+            acc | (u128::from(b.val() == F::one()) << (127 - i))
+        })
+    }
+}
+
+/// `GF(2^128)` gadgets.
+///
+/// The `zero` variable taken by its functions is described
+/// in [the module documentation](super#the-zero-variable).
+pub trait Gf128<F: PrimeField>: Uint32<F> + RangeCheck<F> {
+    /// Converts a block given as four big-endian 32-bit words into an element.
+    fn gf128_from_words(&mut self, words: &[U32<F>; 4]) -> Gf128Var<F> {
+        let mut bits = vec![];
+        for w in words {
+            let mut word_bits = self.u32_to_bits(*w);
+            word_bits.reverse();
+            bits.extend(word_bits);
+        }
+        Gf128Var {
+            bits: bits.try_into().unwrap(),
+        }
+    }
+
+    /// Converts an element into a block, given as four big-endian 32-bit words.
+    fn gf128_to_words(&mut self, a: &Gf128Var<F>) -> [U32<F>; 4] {
+        std::array::from_fn(|i| {
+            let mut word_bits: [Var<F>; 32] = a.bits[32 * i..32 * (i + 1)].try_into().unwrap();
+            word_bits.reverse();
+            self.u32_from_bits(&word_bits)
+        })
+    }
+
+    /// Returns `a + b`, i.e. the XOR of the coefficients.
+    fn gf128_add(&mut self, a: &Gf128Var<F>, b: &Gf128Var<F>) -> Gf128Var<F> {
+        let bits = std::array::from_fn(|i| {
+            let (x, y) = (a.bits[i], b.bits[i]);
+            let xor = self.mul(x, y);
+            // x ^ y = x + y - 2 x y
+            self.linear_combination(
+                &[(F::one(), x), (F::one(), y), (-F::from(2u64), xor)],
+                F::zero(),
+            )
+        });
+        Gf128Var { bits }
+    }
+
+    /// Returns `a * b`.
+    fn gf128_mul(&mut self, zero: Var<F>, a: &Gf128Var<F>, b: &Gf128Var<F>) -> Gf128Var<F> {
+        // spread chunks: sum_i b_i 2^(8 i)
+        let mut spread = |bits: &[Var<F>]| {
+            let terms: Vec<_> = bits
+                .iter()
+                .enumerate()
+                .map(|(i, b)| (F::from(2u64).pow([8 * i as u64]), *b))
+                .collect();
+            self.linear_combination(&terms, F::zero())
+        };
+        let spread_a: Vec<_> = a.bits.chunks(CHUNK_BITS).map(&mut spread).collect();
+        let spread_b: Vec<_> = b.bits.chunks(CHUNK_BITS).map(&mut spread).collect();
+
+        // the bytes holding the counts of each degree of the carry-less product
+        let mut counts = vec![vec![]; 2 * GF128_BITS - 1];
+        let mut range_checks = vec![];
+        for w in 0..2 * CHUNKS - 1 {
+            let products: Vec<_> = (0..CHUNKS)
+                .filter(|u| w >= *u && w - u < CHUNKS)
+                .map(|u| (F::one(), self.mul(spread_a[u], spread_b[w - u])))
+                .collect();
+            let product = self.linear_combination(&products, F::zero());
+
+            // product = sum_k 2^(8 k) byte_k
+            let mut terms = vec![(-F::one(), product)];
+            for k in 0..PRODUCT_BYTES {
+                let byte = self.limb(product, 8 * k, 8 * (k + 1));
+                terms.push((F::from(2u64).pow([8 * k as u64]), byte));
+                range_checks.push((byte, 8));
+                counts[CHUNK_BITS * w + k].push(byte);
+            }
+            self.assert_linear(&terms, F::zero());
+        }
+
+        // the coefficient of x^i is the parity of the counts of the degrees reducing to x^i
+        let reductions: Vec<_> = (0..counts.len()).map(reduce_degree).collect();
+        let mut bits = vec![];
+        for i in 0..GF128_BITS {
+            let mut terms: Vec<_> = (0..counts.len())
+                .filter(|d| (reductions[*d] >> i) & 1 == 1)
+                .flat_map(|d| counts[d].iter().map(|c| (F::one(), *c)))
+                .collect();
+
+            // sum = 2 half + bit
+            let sum = || small(terms.iter().fold(F::zero(), |acc, (_, c)| acc + c.val()));
+            let bit = self.var(|| F::from(sum() & 1));
+            let half = self.var(|| F::from(sum() >> 1));
+            self.assert_boolean(bit);
+            let max_sum = 128 * terms.len() as u64;
+            range_checks.push((half, (64 - max_sum.leading_zeros()) as usize - 1));
+
+            terms.push((-F::one(), bit));
+            terms.push((-F::from(2u64), half));
+            self.assert_linear(&terms, F::zero());
+            bits.push(bit);
+        }
+        self.range_check_bits(zero, &range_checks);
+
+        Gf128Var {
+            bits: bits.try_into().unwrap(),
+        }
+    }
+
+    /// Computes `GHASH_h(blocks)`, i.e. `y_i = (y_(i-1) + x_i) h` with `y_0 = 0`.
+    fn ghash(&mut self, zero: Var<F>, h: &Gf128Var<F>, blocks: &[Gf128Var<F>]) -> Gf128Var<F> {
+        assert!(!blocks.is_empty());
+
+        let mut y = self.gf128_mul(zero, &blocks[0], h);
+        for x in &blocks[1..] {
+            let sum = self.gf128_add(&y, x);
+            y = self.gf128_mul(zero, &sum, h);
+        }
+        y
+    }
+}
+
+impl<F: PrimeField, C: Uint32<F> + RangeCheck<F>> Gf128<F> for C {}
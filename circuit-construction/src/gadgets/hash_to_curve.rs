@@ -0,0 +1,97 @@
+//! Hashing to a curve in the circuit, with the map of the `groupmap` crate.
+//!
+//! For a curve `y^2 = f(x) = x^3 + b`, the map derives three candidates `x1`, `x2`, `x3`
+//! from the field element `t`, one of which at least is on the curve,
+//! and picks the first one that is.
+//! The circuit computes the three candidates, and learns whether `f(x1)` and `f(x2)`
+//! are squares with [`Sqrt::sqrt_or_non_residue`], so that the prover can not pick another one.
+//!
+//! As with [`GroupMap::to_group`], whose output the gadget mirrors,
+//! the sign of `y` is not specified by the map:
+//! the circuit only constrains `y` to be a root of `f(x)`, the prover choosing the same root as `groupmap`.
+
+use super::sqrt::Sqrt;
+use crate::writer::{Cs, Var};
+use ark_ec::SWModelParameters;
+use ark_ff::{PrimeField, SquareRootField};
+use groupmap::{BWParameters, GroupMap};
+
+/// Hash-to-curve gadgets.
+pub trait HashToCurve<F: PrimeField + SquareRootField>: Sqrt<F> {
+    /// Returns `f(x) = x^3 + b`, the right-hand side of the equation of the curve `P`.
+    fn curve_eqn<P: SWModelParameters<BaseField = F>>(&mut self, x: Var<F>) -> Var<F> {
+        let x2 = self.mul(x, x);
+        let x3 = self.mul(x2, x);
+        self.linear_combination(&[(F::one(), x3)], P::COEFF_B)
+    }
+
+    /// Returns the inverse of `x`, or zero if `x` is zero.
+    fn inverse_or_zero(&mut self, x: Var<F>) -> Var<F> {
+        let inv = self.var(|| x.val().inverse().unwrap_or_else(F::zero));
+
+        // z = 1 - x inv, with x z = 0 and inv z = 0:
+        // either x != 0 and then z = 0, or x = 0 and then z = 1 and inv = 0
+        let x_inv = self.mul(x, inv);
+        let z = self.linear_combination(&[(-F::one(), x_inv)], F::one());
+        let x_z = self.mul(x, z);
+        self.assert_linear(&[(F::one(), x_z)], F::zero());
+        let inv_z = self.mul(inv, z);
+        self.assert_linear(&[(F::one(), inv_z)], F::zero());
+        inv
+    }
+
+    /// Returns the point of the curve `P` that `params` maps `t` to,
+    /// as [`GroupMap::to_group`] does (see the module documentation).
+    fn to_group<P: SWModelParameters<BaseField = F>>(
+        &mut self,
+        params: &BWParameters<P>,
+        t: Var<F>,
+    ) -> (Var<F>, Var<F>) {
+        assert!(P::COEFF_A.is_zero());
+
+        // alpha = 1 / (t^2 (t^2 + f(u))), or zero
+        let t2 = self.mul(t, t);
+        let t4 = self.mul(t2, t2);
+        let alpha_inv = self.linear_combination(&[(F::one(), t4), (params.fu, t2)], F::zero());
+        let alpha = self.inverse_or_zero(alpha_inv);
+
+        // x1 = (sqrt(-3u^2) - u) / 2 - t^4 alpha sqrt(-3u^2)
+        let t4_alpha = self.mul(t4, alpha);
+        let x1 = self.linear_combination(
+            &[(-params.sqrt_neg_three_u_squared, t4_alpha)],
+            params.sqrt_neg_three_u_squared_minus_u_over_2,
+        );
+
+        // x2 = -u - x1
+        let x2 = self.linear_combination(&[(-F::one(), x1)], -params.u);
+
+        // x3 = u - (t^2 + f(u))^3 alpha / (3u^2)
+        let s = self.linear_combination(&[(F::one(), t2)], params.fu);
+        let s2 = self.mul(s, s);
+        let s3 = self.mul(s2, s);
+        let s3_alpha = self.mul(s3, alpha);
+        let x3 = self.linear_combination(&[(-params.inv_three_u_squared, s3_alpha)], params.u);
+
+        // x = x1 if f(x1) is a square, x2 if f(x2) is, x3 otherwise
+        let fx1 = self.curve_eqn::<P>(x1);
+        let fx2 = self.curve_eqn::<P>(x2);
+        let (is_square1, _) = self.sqrt_or_non_residue(fx1);
+        let (is_square2, _) = self.sqrt_or_non_residue(fx2);
+        let x2_x3 = self.linear_combination(&[(F::one(), x2), (-F::one(), x3)], F::zero());
+        let x2_x3 = self.mul(is_square2, x2_x3);
+        let x23 = self.linear_combination(&[(F::one(), x3), (F::one(), x2_x3)], F::zero());
+        let x1_x23 = self.linear_combination(&[(F::one(), x1), (-F::one(), x23)], F::zero());
+        let x1_x23 = self.mul(is_square1, x1_x23);
+        let x = self.linear_combination(&[(F::one(), x23), (F::one(), x1_x23)], F::zero());
+
+        // y^2 = f(x), which also holds for x3 when neither f(x1) nor f(x2) is a square
+        let y = self.var(|| params.to_group(t.val()).1);
+        let y2 = self.mul(y, y);
+        let fx = self.curve_eqn::<P>(x);
+        self.assert_eq(y2, fx);
+
+        (x, y)
+    }
+}
+
+impl<F: PrimeField + SquareRootField, C: Cs<F>> HashToCurve<F> for C {}
@@ -0,0 +1,175 @@
+//! Gadgets operating on 64-bit signed integers.
+//!
+//! An [`I64`] is a variable containing the two's-complement encoding of the integer,
+//! that is an unsigned value `a < 2^64` representing `a - 2^64 s`, where `s` is the
+//! most significant bit of `a` (the sign). The encoding is constrained to 64 bits with
+//! the range check gates, and the sign is extracted when needed by decomposing
+//! `a = 2^63 s + low` with `low` range-checked to 63 bits.
+//!
+//! As with Rust's `wrapping_neg` and `wrapping_abs`, the negation and the absolute value
+//! of `i64::MIN` are `i64::MIN`.
+
+use super::{arith::Arith, range_check::RangeCheck};
+use crate::writer::{Cs, Var};
+use ark_ff::PrimeField;
+
+/// A variable containing the two's-complement encoding of a 64-bit signed integer.
+///
+/// Values of this type are only created by gadgets that constrain them to 64 bits
+/// (or by constants), which is what makes it sound to feed them into the other gadgets.
+#[derive(Debug, Clone, Copy)]
+pub struct I64<F>(Var<F>);
+
+impl<F: PrimeField> I64<F> {
+    /// Returns the underlying variable (containing the encoding of the integer).
+    pub fn var(&self) -> Var<F> {
+        self.0
+    }
+
+    /// Returns the value of the integer.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the variable has no value (i.e. in circuit mode),
+    /// or if the value does not fit in 64 bits.
+    pub fn val(&self) -> i64 {
+        value(self.0) as i64
+    }
+}
+
+/// Reads a 64-bit unsigned value out of a variable.
+fn value<F: PrimeField>(v: Var<F>) -> u64 {
+    let repr = v.val().into_repr();
+    let limbs = repr.as_ref();
+    assert!(
+        limbs[1..].iter().all(|l| *l == 0),
+        "value does not fit in 64 bits"
+    );
+    limbs[0]
+}
+
+/// Returns `2^64` in the field.
+fn two_to_64<F: PrimeField>() -> F {
+    F::from(2u64).pow([64])
+}
+
+/// Gadgets for 64-bit signed integers, built on the range check gates.
+///
+/// The `zero` variable taken by its functions is described
+/// in [the module documentation](super#the-zero-variable).
+pub trait Int64<F: PrimeField>: Arith<F> + RangeCheck<F> {
+    /// Creates a 64-bit signed integer with a value given by `g`,
+    /// and constrains its encoding to 64 bits.
+    fn i64_var<G>(&mut self, zero: Var<F>, g: G) -> I64<F>
+    where
+        G: FnOnce() -> i64,
+    {
+        let v = self.var(|| F::from(g() as u64));
+        self.range_check_bits(zero, &[(v, 64)]);
+        I64(v)
+    }
+
+    /// Creates a constant 64-bit signed integer.
+    fn i64_constant(&mut self, x: i64) -> I64<F> {
+        I64(self.constant(F::from(x as u64)))
+    }
+
+    /// Returns the sign of the `bits`-bit two's-complement encoding `x` (one if negative),
+    /// and constrains `x` to `bits` bits (with `1 <= bits <= 64`).
+    fn twos_complement_sign(&mut self, zero: Var<F>, x: Var<F>, bits: usize) -> Var<F> {
+        assert!((1..=64).contains(&bits));
+        let s = self.var(|| F::from(value(x) >> (bits - 1)));
+        let low = self.var(|| F::from(value(x) & ((1u64 << (bits - 1)) - 1)));
+        self.assert_boolean(s);
+        self.range_check_bits(zero, &[(low, bits - 1)]);
+
+        // x = 2^(bits - 1) s + low
+        self.assert_linear(
+            &[
+                (-F::one(), x),
+                (F::from(2u64).pow([bits as u64 - 1]), s),
+                (F::one(), low),
+            ],
+            F::zero(),
+        );
+        s
+    }
+
+    /// Returns the sign of `a` (one if `a` is negative, zero otherwise).
+    fn i64_sign(&mut self, zero: Var<F>, a: I64<F>) -> Var<F> {
+        self.twos_complement_sign(zero, a.0, 64)
+    }
+
+    /// Returns the value of `a` as a field element (`p - |a|` if `a` is negative).
+    fn i64_value(&mut self, zero: Var<F>, a: I64<F>) -> Var<F> {
+        let s = self.i64_sign(zero, a);
+        // a - 2^64 s
+        self.linear_combination(&[(F::one(), a.0), (-two_to_64::<F>(), s)], F::zero())
+    }
+
+    /// Sign-extends the `bits`-bit two's-complement encoding `x` to 64 bits
+    /// (e.g. to convert an `i8`, or an `i32` held in a [`super::uint32::U32`]),
+    /// and constrains `x` to `bits` bits (with `1 <= bits <= 64`).
+    fn i64_sign_extend(&mut self, zero: Var<F>, x: Var<F>, bits: usize) -> I64<F> {
+        let s = self.twos_complement_sign(zero, x, bits);
+        // x + (2^64 - 2^bits) s
+        let fill = two_to_64::<F>() - F::from(2u64).pow([bits as u64]);
+        I64(self.linear_combination(&[(F::one(), x), (fill, s)], F::zero()))
+    }
+
+    /// Computes `-a` (wrapping, so that `-i64::MIN = i64::MIN`).
+    ///
+    /// The result `r` is constrained to 64 bits with `a + r = 2^64 c` for a boolean `c`,
+    /// which only has the solution `r = c = 0` if `a = 0`, and `r = 2^64 - a, c = 1` otherwise.
+    fn i64_neg(&mut self, zero: Var<F>, a: I64<F>) -> I64<F> {
+        let r = self.var(|| F::from(a.val().wrapping_neg() as u64));
+        let c = self.var(|| F::from(u64::from(a.val() != 0)));
+        self.assert_boolean(c);
+        self.range_check_bits(zero, &[(r, 64)]);
+
+        // a + r - 2^64 c = 0
+        self.assert_linear(
+            &[(F::one(), a.0), (F::one(), r), (-two_to_64::<F>(), c)],
+            F::zero(),
+        );
+        I64(r)
+    }
+
+    /// Computes `|a|` (wrapping, so that `|i64::MIN| = i64::MIN`).
+    fn i64_abs(&mut self, zero: Var<F>, a: I64<F>) -> I64<F> {
+        let s = self.i64_sign(zero, a);
+        let sa = self.mul(s, a.0);
+        // a if s = 0, 2^64 - a if s = 1
+        I64(self.linear_combination(
+            &[(F::one(), a.0), (two_to_64::<F>(), s), (-F::from(2u64), sa)],
+            F::zero(),
+        ))
+    }
+
+    /// Returns a boolean which is one if `a < b`.
+    ///
+    /// With `d = a - b` (over the integers, so that `|d| < 2^64`), the boolean `lt` is
+    /// constrained by range-checking `d + 2^64 lt` to 64 bits, which only holds if
+    /// `lt = 1` when `d < 0` and `lt = 0` when `d >= 0`.
+    fn i64_less_than(&mut self, zero: Var<F>, a: I64<F>, b: I64<F>) -> Var<F> {
+        let va = self.i64_value(zero, a);
+        let vb = self.i64_value(zero, b);
+        let lt = self.var(|| F::from(u64::from(a.val() < b.val())));
+        self.assert_boolean(lt);
+
+        let r = self.linear_combination(
+            &[(F::one(), va), (-F::one(), vb), (two_to_64::<F>(), lt)],
+            F::zero(),
+        );
+        self.range_check_bits(zero, &[(r, 64)]);
+        lt
+    }
+
+    /// Returns a boolean which is one if `a <= b`.
+    fn i64_less_than_or_equal(&mut self, zero: Var<F>, a: I64<F>, b: I64<F>) -> Var<F> {
+        let gt = self.i64_less_than(zero, b, a);
+        self.linear_combination(&[(-F::one(), gt)], F::one())
+    }
+}
+
+impl<F: PrimeField, C: Cs<F>> Int64<F> for C {}
@@ -0,0 +1,191 @@
+//! MiMC and GMiMC over the native field.
+//!
+//! MiMC-p/p is the block cipher `E_k(x) = k + F_{r-1}(... F_0(x))`
+//! with the rounds `F_i(x) = (x + k + c_i)^d`, where `c_0 = 0`.
+//! Messages are hashed with the Miyaguchi-Preneel construction `h <- h + x + E_h(x)`,
+//! as done by circomlib's `MultiMiMC7`.
+//!
+//! GMiMC (here GMiMC-erf) is a permutation of `t` elements, built as a generalized Feistel network:
+//! each round computes `f = (x_0 + c_i)^d`, adds it to all the other elements,
+//! and rotates the state to the left by one element.
+//!
+//! The exponent `d` is the smallest integer greater than `2` that is coprime to `p - 1`
+//! (e.g. `5` for the Pasta fields), so that `x -> x^d` is a permutation.
+//! The round constants are derived from a seed with Blake2b, so that both instances can be
+//! regenerated from their seed. See <https://eprint.iacr.org/2016/492> and
+//! <https://eprint.iacr.org/2019/397>.
+
+use super::arith::Arith;
+use crate::writer::{Cs, Var};
+use ark_ff::PrimeField;
+use blake2::{Blake2b512, Digest};
+use num_bigint::BigUint;
+use o1_utils::field_helpers::FieldHelpers;
+
+/// The parameters of MiMC-p/p.
+#[derive(Debug, Clone)]
+pub struct MimcParams<F> {
+    /// The exponent of the round function
+    pub exponent: u64,
+    /// The round constants, the first of which is zero
+    pub round_constants: Vec<F>,
+}
+
+impl<F: PrimeField> MimcParams<F> {
+    /// Derives the parameters from `seed`, with `ceil(log_d(p))` rounds.
+    pub fn new(seed: &[u8]) -> Self {
+        let exponent = exponent::<F>();
+        Self::with_rounds(seed, log_modulus::<F>(exponent))
+    }
+
+    /// Derives the parameters from `seed`, with a given number of rounds.
+    pub fn with_rounds(seed: &[u8], rounds: usize) -> Self {
+        let mut round_constants = round_constants(seed, rounds);
+        round_constants[0] = F::zero();
+        Self {
+            exponent: exponent::<F>(),
+            round_constants,
+        }
+    }
+
+    /// Encrypts `x` with the key `key`, outside of the circuit.
+    pub fn encrypt(&self, key: F, x: F) -> F {
+        let x = self
+            .round_constants
+            .iter()
+            .fold(x, |x, c| (x + key + c).pow([self.exponent]));
+        x + key
+    }
+
+    /// Hashes `inputs` with the Miyaguchi-Preneel construction, starting from `key`,
+    /// outside of the circuit.
+    pub fn hash(&self, key: F, inputs: &[F]) -> F {
+        inputs.iter().fold(key, |h, x| h + x + self.encrypt(h, *x))
+    }
+}
+
+/// The parameters of GMiMC-erf.
+#[derive(Debug, Clone)]
+pub struct GmimcParams<F> {
+    /// The number of elements of the state
+    pub width: usize,
+    /// The exponent of the round function
+    pub exponent: u64,
+    /// The round constants
+    pub round_constants: Vec<F>,
+}
+
+impl<F: PrimeField> GmimcParams<F> {
+    /// Derives the parameters of a permutation of `width` elements from `seed`,
+    /// with a conservative number of rounds: twice the MiMC rounds, plus the width.
+    /// Use [`GmimcParams::with_rounds`] to match another instance.
+    pub fn new(seed: &[u8], width: usize) -> Self {
+        let rounds = 2 * log_modulus::<F>(exponent::<F>()) + width;
+        Self::with_rounds(seed, width, rounds)
+    }
+
+    /// Derives the parameters of a permutation of `width` elements from `seed`,
+    /// with a given number of rounds.
+    pub fn with_rounds(seed: &[u8], width: usize, rounds: usize) -> Self {
+        assert!(width >= 2);
+        Self {
+            width,
+            exponent: exponent::<F>(),
+            round_constants: round_constants(seed, rounds),
+        }
+    }
+
+    /// Applies the permutation to `state`, outside of the circuit.
+    pub fn permute(&self, state: &mut [F]) {
+        assert_eq!(state.len(), self.width);
+        for c in &self.round_constants {
+            let f = (state[0] + c).pow([self.exponent]);
+            for x in state[1..].iter_mut() {
+                *x += f;
+            }
+            state.rotate_left(1);
+        }
+    }
+}
+
+/// Returns the smallest integer `d > 2` such that `x -> x^d` is a permutation of the field.
+pub fn exponent<F: PrimeField>() -> u64 {
+    let order = F::modulus_biguint() - 1u32;
+    (3u64..)
+        .find(|d| gcd(*d, (&order % *d).try_into().unwrap()) == 1)
+        .unwrap()
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Returns `ceil(log_d(p))`.
+fn log_modulus<F: PrimeField>(d: u64) -> usize {
+    let modulus = F::modulus_biguint();
+    let mut power = BigUint::from(1u32);
+    let mut rounds = 0;
+    while power < modulus {
+        power *= d;
+        rounds += 1;
+    }
+    rounds
+}
+
+/// Derives `rounds` constants from `seed`, the constant `i` being `Blake2b(seed || i)`
+/// (with `i` as a big-endian 32-bit integer) reduced modulo `p`.
+fn round_constants<F: PrimeField>(seed: &[u8], rounds: usize) -> Vec<F> {
+    (0..rounds)
+        .map(|i| {
+            let mut h = Blake2b512::new();
+            h.update(seed);
+            h.update((i as u32).to_be_bytes());
+            F::from_le_bytes_mod_order(&h.finalize())
+        })
+        .collect()
+}
+
+/// MiMC and GMiMC gadgets.
+pub trait Mimc<F: PrimeField>: Arith<F> {
+    /// Encrypts `x` with the key `key` (see [`MimcParams::encrypt`]).
+    fn mimc_encrypt(&mut self, params: &MimcParams<F>, key: Var<F>, x: Var<F>) -> Var<F> {
+        let mut x = x;
+        for c in &params.round_constants {
+            let y = self.linear_combination(&[(F::one(), x), (F::one(), key)], *c);
+            x = self.pow(y, params.exponent);
+        }
+        self.linear_combination(&[(F::one(), x), (F::one(), key)], F::zero())
+    }
+
+    /// Hashes `inputs` starting from `key` (see [`MimcParams::hash`]).
+    fn mimc_hash(&mut self, params: &MimcParams<F>, key: Var<F>, inputs: &[Var<F>]) -> Var<F> {
+        let mut h = key;
+        for x in inputs {
+            let e = self.mimc_encrypt(params, h, *x);
+            h = self.linear_combination(&[(F::one(), h), (F::one(), *x), (F::one(), e)], F::zero());
+        }
+        h
+    }
+
+    /// Applies the GMiMC permutation to `state` (see [`GmimcParams::permute`]).
+    fn gmimc_permute(&mut self, params: &GmimcParams<F>, state: &[Var<F>]) -> Vec<Var<F>> {
+        assert_eq!(state.len(), params.width);
+
+        let mut state = state.to_vec();
+        for c in &params.round_constants {
+            let y = self.linear_combination(&[(F::one(), state[0])], *c);
+            let f = self.pow(y, params.exponent);
+            for x in state[1..].iter_mut() {
+                *x = self.linear_combination(&[(F::one(), *x), (F::one(), f)], F::zero());
+            }
+            state.rotate_left(1);
+        }
+        state
+    }
+}
+
+impl<F: PrimeField, C: Cs<F>> Mimc<F> for C {}
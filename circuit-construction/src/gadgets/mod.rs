@@ -0,0 +1,36 @@
+//! Gadgets built on top of the [`crate::writer::Cs`] trait.
+//!
+//! Each gadget family is exposed as an extension trait of [`crate::writer::Cs`],
+//! implemented for every constraint system, so that gadgets are called the same way
+//! as the built-in functions (e.g. `sys.ripemd160(zero, &message)`).
+//!
+//! # The `zero` variable
+//!
+//! Like the built-in [`Cs::add_group`](crate::writer::Cs::add_group), the gadgets that fill
+//! some cells of their rows with zero take a `zero` variable, which should contain the constant zero
+//! (e.g. `sys.constant(F::zero())`), so that these cells are wired to it.
+
+pub mod arith;
+pub mod bigint;
+pub mod bip340;
+pub mod bitwise;
+pub mod bytes;
+pub mod credentials;
+pub mod dfa;
+pub mod ecdsa;
+pub mod foreign_curve;
+pub mod foreign_field;
+pub mod gf128;
+pub mod hash_to_curve;
+pub mod int64;
+pub mod mimc;
+pub mod pedersen;
+pub mod prf;
+pub mod range_check;
+pub mod ripemd160;
+pub mod schnorr;
+pub mod sha256;
+pub mod sponge;
+pub mod sqrt;
+pub mod uint32;
+pub mod vrf;
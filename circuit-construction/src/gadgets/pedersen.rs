@@ -0,0 +1,234 @@
+//! Pedersen hash and commitments over the native curve (e.g. Pallas for a circuit over `Fp`),
+//! with fixed generators.
+//!
+//! The message is split into chunks of [`CHUNK_BITS`] bits, and the chunks into segments
+//! of [`SEGMENT_CHUNKS`] chunks, each segment having its own generator `G_i`.
+//! As in Zcash Sapling, the chunk `(b_0, b_1, b_2)` encodes `enc = (1 - 2 b_2) (1 + b_0 + 2 b_1)`,
+//! and the hash is `sum_i [sum_j enc(m_{i,j}) 2^(4 j)] G_i`.
+//! Within a segment, the sums are distinct, non-zero, and smaller than half of the group order,
+//! so a collision gives a relation between the generators.
+//!
+//! As the generators are fixed, so are the points `[k 2^(4 j)] G_i` for `k` in `1..=4`.
+//! They are precomputed in tables, and each chunk selects its point with a few generic gates
+//! (no scalar multiplication gate is needed), before the points are summed with `CompleteAdd` gates.
+//! Within a segment, the partial sum and the next point never share their `x` coordinate.
+
+use crate::prover::CoordinateCurve;
+use crate::writer::{Cs, Var};
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{FpParameters, PrimeField, Zero};
+use blake2::{Blake2b512, Digest};
+use commitment_dlog::{commitment::CommitmentCurve, srs::point_of_random_bytes};
+use groupmap::GroupMap;
+
+use super::arith::Arith;
+
+/// Number of bits of a chunk
+pub const CHUNK_BITS: usize = 3;
+
+/// Number of chunks of a segment
+pub const SEGMENT_CHUNKS: usize = 63;
+
+/// The table of a chunk: the points `[k 2^(4 j)] G_i` for `k` in `1..=4`,
+/// where `j` is the position of the chunk in the segment `i`.
+type ChunkTable<G> = [G; 4];
+
+/// The generators of the Pedersen hash, and their precomputed tables.
+pub struct PedersenParams<G: AffineCurve> {
+    /// The generators of the message segments
+    pub generators: Vec<G>,
+    /// The generators of the randomness segments of commitments
+    pub blinding_generators: Vec<G>,
+    tables: Vec<ChunkTable<G>>,
+    blinding_tables: Vec<ChunkTable<G>>,
+}
+
+impl<G: CommitmentCurve> PedersenParams<G> {
+    /// Derives the generators for messages of up to `segments` segments,
+    /// and enough randomness segments to hold [`Self::randomness_bits`] bits.
+    /// Different personalizations give independent generators.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the scalar field is too small for the segment size.
+    pub fn new(personalization: &[u8], segments: usize) -> Self {
+        // the largest encoding of a segment is below 2^(4 SEGMENT_CHUNKS - 1)
+        assert!(Self::randomness_bits() > 4 * SEGMENT_CHUNKS);
+
+        let map = G::Map::setup();
+        let derive = |domain: &[u8], count: usize| -> Vec<G> {
+            (0..count)
+                .map(|i| {
+                    let mut h = Blake2b512::new();
+                    h.update(domain);
+                    h.update(personalization);
+                    h.update((i as u32).to_be_bytes());
+                    point_of_random_bytes(&map, &h.finalize())
+                })
+                .collect()
+        };
+
+        let chunks = (Self::randomness_bits() + CHUNK_BITS - 1) / CHUNK_BITS;
+        let blinding_segments = (chunks + SEGMENT_CHUNKS - 1) / SEGMENT_CHUNKS;
+        let generators = derive(b"pedersen_generator", segments);
+        let blinding_generators = derive(b"pedersen_blinding", blinding_segments);
+
+        Self {
+            tables: chunk_tables(&generators),
+            blinding_tables: chunk_tables(&blinding_generators),
+            generators,
+            blinding_generators,
+        }
+    }
+}
+
+impl<G: AffineCurve> PedersenParams<G> {
+    /// Returns the number of random bits used by commitments,
+    /// i.e. the bit length of the scalar field.
+    pub fn randomness_bits() -> usize {
+        <G::ScalarField as PrimeField>::Params::MODULUS_BITS as usize
+    }
+
+    /// Returns the maximum number of bits of a message.
+    pub fn max_bits(&self) -> usize {
+        self.tables.len() * CHUNK_BITS
+    }
+
+    /// Hashes `bits`, outside of the circuit.
+    /// The last chunk is padded with zeros.
+    pub fn hash(&self, bits: &[bool]) -> G {
+        encode(&self.tables, bits).into_affine()
+    }
+
+    /// Commits to `bits` with the randomness `randomness` (given as bits), outside of the circuit.
+    /// This is the hash of `bits`, plus the encoding of `randomness` on the blinding generators.
+    pub fn commit(&self, bits: &[bool], randomness: &[bool]) -> G {
+        let mut res = encode(&self.tables, bits);
+        res += &encode(&self.blinding_tables, randomness);
+        res.into_affine()
+    }
+}
+
+/// Computes the tables of all the chunks of the segments of `generators`.
+fn chunk_tables<G: AffineCurve>(generators: &[G]) -> Vec<ChunkTable<G>> {
+    let mut tables = vec![];
+    for g in generators {
+        // base = 2^(4 j) G_i
+        let mut base = g.into_projective();
+        for _ in 0..SEGMENT_CHUNKS {
+            let mut points = [base; 4];
+            for k in 1..4 {
+                points[k] = points[k - 1] + base;
+            }
+            tables.push(points.map(|p| p.into_affine()));
+
+            for _ in 0..4 {
+                base.double_in_place();
+            }
+        }
+    }
+    tables
+}
+
+/// Returns the bits of the chunk `i`, padded with `pad`.
+fn chunk<T: Copy>(bits: &[T], i: usize, pad: T) -> [T; CHUNK_BITS] {
+    std::array::from_fn(|k| *bits.get(CHUNK_BITS * i + k).unwrap_or(&pad))
+}
+
+/// Encodes `bits` with the chunk tables `tables`, outside of the circuit.
+fn encode<G: AffineCurve>(tables: &[ChunkTable<G>], bits: &[bool]) -> G::Projective {
+    assert!(bits.len() <= tables.len() * CHUNK_BITS);
+
+    let mut res = G::Projective::zero();
+    for (i, table) in tables.iter().enumerate() {
+        if CHUNK_BITS * i >= bits.len() {
+            break;
+        }
+        let [b0, b1, b2] = chunk(bits, i, false);
+        let p = table[usize::from(b0) + 2 * usize::from(b1)];
+        res.add_assign_mixed(&if b2 { -p } else { p });
+    }
+    res
+}
+
+/// Pedersen hash gadgets.
+///
+/// The message bits should be constrained to be booleans,
+/// and the `zero` variable contain the constant zero
+/// (see [the module documentation](super#the-zero-variable)).
+pub trait Pedersen<F: PrimeField>: Arith<F> {
+    /// Returns the Pedersen hash of `bits`.
+    /// The last chunk is padded with zeros.
+    fn pedersen_hash<G: CoordinateCurve<BaseField = F>>(
+        &mut self,
+        zero: Var<F>,
+        params: &PedersenParams<G>,
+        bits: &[Var<F>],
+    ) -> (Var<F>, Var<F>) {
+        self.pedersen_encode(zero, &params.tables, bits)
+    }
+
+    /// Returns the Pedersen commitment to `bits` with the randomness `randomness`
+    /// (see [`PedersenParams::commit`]).
+    fn pedersen_commit<G: CoordinateCurve<BaseField = F>>(
+        &mut self,
+        zero: Var<F>,
+        params: &PedersenParams<G>,
+        bits: &[Var<F>],
+        randomness: &[Var<F>],
+    ) -> (Var<F>, Var<F>) {
+        let hash = self.pedersen_encode(zero, &params.tables, bits);
+        let blinding = self.pedersen_encode(zero, &params.blinding_tables, randomness);
+        self.add_group(zero, hash, blinding)
+    }
+
+    /// Sums the points selected by each chunk of `bits` in its table.
+    fn pedersen_encode<G: CoordinateCurve<BaseField = F>>(
+        &mut self,
+        zero: Var<F>,
+        tables: &[ChunkTable<G>],
+        bits: &[Var<F>],
+    ) -> (Var<F>, Var<F>) {
+        assert!(!bits.is_empty());
+        assert!(bits.len() <= tables.len() * CHUNK_BITS);
+
+        let chunks = (bits.len() + CHUNK_BITS - 1) / CHUNK_BITS;
+        let mut res = self.pedersen_select(&tables[0], chunk(bits, 0, zero));
+        for (i, table) in tables.iter().enumerate().take(chunks).skip(1) {
+            let p = self.pedersen_select(table, chunk(bits, i, zero));
+            res = self.add_group(zero, res, p);
+        }
+        res
+    }
+
+    /// Returns the point `(1 - 2 b_2) table[b_0 + 2 b_1]`.
+    fn pedersen_select<G: CoordinateCurve<BaseField = F>>(
+        &mut self,
+        table: &ChunkTable<G>,
+        [b0, b1, b2]: [Var<F>; CHUNK_BITS],
+    ) -> (Var<F>, Var<F>) {
+        let b01 = self.mul(b0, b1);
+
+        // bilinear interpolation of the four values v[b_0 + 2 b_1]
+        let coords = table.map(|p| p.to_coords().unwrap());
+        let mut select = |v: [F; 4]| {
+            self.linear_combination(
+                &[
+                    (v[1] - v[0], b0),
+                    (v[2] - v[0], b1),
+                    (v[3] - v[2] - v[1] + v[0], b01),
+                ],
+                v[0],
+            )
+        };
+        let x = select(coords.map(|(x, _)| x));
+        let y = select(coords.map(|(_, y)| y));
+
+        // y (1 - 2 b_2)
+        let b2_y = self.mul(b2, y);
+        let y = self.linear_combination(&[(F::one(), y), (-F::from(2u64), b2_y)], F::zero());
+        (x, y)
+    }
+}
+
+impl<F: PrimeField, C: Cs<F>> Pedersen<F> for C {}
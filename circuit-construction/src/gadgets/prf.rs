@@ -0,0 +1,74 @@
+//! A Poseidon-based pseudorandom function and nullifier derivation.
+//!
+//! Both are hashes with the Kimchi Poseidon sponge, initialized with a fixed domain string
+//! as Mina initializes its hashers (see [`super::sponge::domain_state`]):
+//!
+//! * `prf(key, input) = H_PRF_DOMAIN(key || input)`,
+//! * `nullifier(sk, note) = H_NULLIFIER_DOMAIN(sk || note)`.
+//!
+//! The domain strings separate both functions from each other and from Mina's hashes,
+//! so that application protocols derive compatible values, e.g. with
+//! `mina_hasher::create_kimchi` and a [`mina_hasher::Hashable`] whose domain string is
+//! [`PRF_DOMAIN`] or [`NULLIFIER_DOMAIN`] and which is made of the same field elements.
+
+use super::arith::Arith;
+use super::sponge::{domain_state, CircuitSponge};
+use crate::constants::Constants;
+use crate::writer::{Cs, Var};
+use ark_ff::PrimeField;
+use oracle::{
+    constants::PlonkSpongeConstantsKimchi,
+    poseidon::{ArithmeticSponge, Sponge},
+};
+
+/// The domain string of the PRF
+pub const PRF_DOMAIN: &str = "KimchiPrf";
+
+/// The domain string of the nullifiers
+pub const NULLIFIER_DOMAIN: &str = "KimchiNullifier";
+
+/// Hashes `inputs` with a sponge initialized with `domain`, outside of the circuit.
+pub(crate) fn hash_native<F: PrimeField>(
+    constants: &Constants<F>,
+    domain: &str,
+    inputs: &[F],
+) -> F {
+    let mut sponge = ArithmeticSponge::<F, PlonkSpongeConstantsKimchi>::new(constants.poseidon);
+    sponge.state = domain_state(constants, Some(domain.to_string()));
+    sponge.absorb(inputs);
+    sponge.squeeze()
+}
+
+/// Computes `prf(key, input)`, outside of the circuit.
+pub fn prf_native<F: PrimeField>(constants: &Constants<F>, key: F, input: &[F]) -> F {
+    let mut inputs = vec![key];
+    inputs.extend_from_slice(input);
+    hash_native(constants, PRF_DOMAIN, &inputs)
+}
+
+/// Computes `nullifier(sk, note)`, outside of the circuit.
+pub fn nullifier_native<F: PrimeField>(constants: &Constants<F>, sk: F, note: F) -> F {
+    hash_native(constants, NULLIFIER_DOMAIN, &[sk, note])
+}
+
+/// PRF and nullifier gadgets.
+pub trait Prf<F: PrimeField>: Arith<F> {
+    /// Returns `prf(key, input)` (see [`prf_native`]).
+    fn prf(&mut self, constants: &Constants<F>, key: Var<F>, input: &[Var<F>]) -> Var<F> {
+        let mut sponge = CircuitSponge::with_domain(self, constants, Some(PRF_DOMAIN.to_string()));
+        sponge.absorb(self, constants, &[key]);
+        sponge.absorb(self, constants, input);
+        sponge.squeeze(self, constants)
+    }
+
+    /// Returns the nullifier of `note` (e.g. a note commitment) for the secret key `sk`
+    /// (see [`nullifier_native`]).
+    fn nullifier(&mut self, constants: &Constants<F>, sk: Var<F>, note: Var<F>) -> Var<F> {
+        let mut sponge =
+            CircuitSponge::with_domain(self, constants, Some(NULLIFIER_DOMAIN.to_string()));
+        sponge.absorb(self, constants, &[sk, note]);
+        sponge.squeeze(self, constants)
+    }
+}
+
+impl<F: PrimeField, C: Cs<F>> Prf<F> for C {}
@@ -0,0 +1,106 @@
+//! Range check gadgets.
+//!
+//! These are thin wrappers around the `RangeCheck0`/`RangeCheck1` gates of kimchi,
+//! which constrain three values to 88 bits in four rows (the "multi range check"),
+//! using the 12-bit range check lookup table.
+//!
+//! Smaller bit lengths are obtained by range-checking both `v` and `v * 2^(88 - bits)`:
+//! as `v < 2^88` the product does not wrap around the field,
+//! so its fitting in 88 bits implies that `v` fits in `bits` bits.
+
+use crate::writer::{Cs, GateSpec, Var};
+use ark_ff::PrimeField;
+use kimchi::circuits::{gate::GateType, polynomials::range_check::witness::value_to_limb};
+
+/// The number of bits constrained by the range check gates.
+pub const RANGE_CHECK_BITS: usize = 88;
+
+/// Range check gadgets.
+///
+/// The `zero` variable taken by its functions is described
+/// in [the module documentation](super#the-zero-variable).
+pub trait RangeCheck<F: PrimeField>: Cs<F> {
+    /// Constrains the three values to be at most 88 bits, using a `RangeCheck0`, `RangeCheck0`,
+    /// `RangeCheck1` and `Zero` rows.
+    fn multi_range_check(&mut self, zero: Var<F>, [v0, v1, v2]: [Var<F>; 3]) {
+        // 12-bit limbs of v0 and v1 whose lookups are deferred to the last row
+        let [v0p0, v0p1, v1p0, v1p1] = [(v0, 76), (v0, 64), (v1, 76), (v1, 64)]
+            .map(|(v, start)| self.limb(v, start, start + 12));
+
+        for (v, p0, p1) in [(v0, v0p0, v0p1), (v1, v1p0, v1p1)] {
+            let mut row = vec![Some(v), Some(p0), Some(p1)];
+            // 12-bit plookups
+            for start in [52, 40, 28, 16] {
+                row.push(Some(self.limb(v, start, start + 12)));
+            }
+            // 2-bit crumbs
+            for start in (0..16).step_by(2).rev() {
+                row.push(Some(self.limb(v, start, start + 2)));
+            }
+            self.gate(GateSpec {
+                typ: GateType::RangeCheck0,
+                row,
+                coeffs: vec![],
+            });
+        }
+
+        // v2 is decomposed over the last two rows
+        let mut row = vec![Some(v2)];
+        row.push(Some(self.limb(v2, 86, 88)));
+        row.push(Some(self.limb(v2, 84, 86)));
+        for start in [72, 60, 48, 36] {
+            row.push(Some(self.limb(v2, start, start + 12)));
+        }
+        for start in (20..36).step_by(2).rev() {
+            row.push(Some(self.limb(v2, start, start + 2)));
+        }
+        self.gate(GateSpec {
+            typ: GateType::RangeCheck1,
+            row,
+            coeffs: vec![],
+        });
+
+        let mut row = vec![Some(zero)];
+        row.push(Some(self.limb(v2, 18, 20)));
+        row.push(Some(self.limb(v2, 16, 18)));
+        row.extend([v0p0, v0p1, v1p0, v1p1].map(Some));
+        for start in (0..16).step_by(2).rev() {
+            row.push(Some(self.limb(v2, start, start + 2)));
+        }
+        self.gate(GateSpec {
+            typ: GateType::Zero,
+            row,
+            coeffs: vec![],
+        });
+    }
+
+    /// Constrains all the values to be at most 88 bits,
+    /// batching them three by three into multi range checks.
+    fn range_check_limbs(&mut self, zero: Var<F>, values: &[Var<F>]) {
+        for chunk in values.chunks(3) {
+            let vars = [0, 1, 2].map(|i| chunk.get(i).copied().unwrap_or(zero));
+            self.multi_range_check(zero, vars);
+        }
+    }
+
+    /// Constrains each value `v` to fit in the associated number of bits (at most 88).
+    fn range_check_bits(&mut self, zero: Var<F>, values: &[(Var<F>, usize)]) {
+        let mut limbs = vec![];
+        for &(v, bits) in values {
+            assert!(bits <= RANGE_CHECK_BITS);
+            limbs.push(v);
+            if bits < RANGE_CHECK_BITS {
+                let shift = F::from(2u64).pow([(RANGE_CHECK_BITS - bits) as u64]);
+                limbs.push(self.scale(shift, v));
+            }
+        }
+        self.range_check_limbs(zero, &limbs);
+    }
+
+    /// Creates a variable containing the bits `start..end` of `v`.
+    fn limb(&mut self, v: Var<F>, start: usize, end: usize) -> Var<F> {
+        self.var(|| value_to_limb(v.val(), start, end))
+    }
+}
+
+impl<F: PrimeField, C: Cs<F>> RangeCheck<F> for C {}
@@ -0,0 +1,284 @@
+//! RIPEMD-160 gadget.
+//!
+//! The compression function is expressed over the 32-bit word gadgets of [`super::uint32`],
+//! so every XOR, rotation and modular addition is checked through the XOR lookup table.
+//! Together with SHA-256 this is what is needed to prove Bitcoin address derivations,
+//! i.e. `RIPEMD-160(SHA-256(public key))`.
+//!
+//! Messages are given as little-endian 32-bit words (the native word order of RIPEMD-160),
+//! and their length is fixed at circuit-construction time, so padding is made of constants.
+
+use super::uint32::{Uint32, U32};
+use crate::writer::Var;
+use ark_ff::PrimeField;
+
+/// Number of 32-bit words in a message block
+pub const BLOCK_WORDS: usize = 16;
+
+/// Number of 32-bit words in the digest
+pub const DIGEST_WORDS: usize = 5;
+
+/// The initial chaining value
+pub const IV: [u32; DIGEST_WORDS] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+/// Message word selection for the left line
+const R_LEFT: [[usize; 16]; 5] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [7, 4, 13, 1, 10, 6, 15, 3, 12, 0, 9, 5, 2, 14, 11, 8],
+    [3, 10, 14, 4, 9, 15, 8, 1, 2, 7, 0, 6, 13, 11, 5, 12],
+    [1, 9, 11, 10, 0, 8, 12, 4, 13, 3, 7, 15, 14, 5, 6, 2],
+    [4, 0, 5, 9, 7, 12, 2, 10, 14, 1, 3, 8, 11, 6, 15, 13],
+];
+
+/// Message word selection for the right line
+const R_RIGHT: [[usize; 16]; 5] = [
+    [5, 14, 7, 0, 9, 2, 11, 4, 13, 6, 15, 8, 1, 10, 3, 12],
+    [6, 11, 3, 7, 0, 13, 5, 10, 14, 15, 8, 12, 4, 9, 1, 2],
+    [15, 5, 1, 3, 7, 14, 6, 9, 11, 8, 12, 2, 10, 0, 4, 13],
+    [8, 6, 4, 1, 3, 11, 15, 0, 5, 12, 2, 13, 9, 7, 10, 14],
+    [12, 15, 10, 4, 1, 5, 8, 7, 6, 2, 13, 14, 0, 3, 9, 11],
+];
+
+/// Rotation amounts for the left line
+const S_LEFT: [[u32; 16]; 5] = [
+    [11, 14, 15, 12, 5, 8, 7, 9, 11, 13, 14, 15, 6, 7, 9, 8],
+    [7, 6, 8, 13, 11, 9, 7, 15, 7, 12, 15, 9, 11, 7, 13, 12],
+    [11, 13, 6, 7, 14, 9, 13, 15, 14, 8, 13, 6, 5, 12, 7, 5],
+    [11, 12, 14, 15, 14, 15, 9, 8, 9, 14, 5, 6, 8, 6, 5, 12],
+    [9, 15, 5, 11, 6, 8, 13, 12, 5, 12, 13, 14, 11, 8, 5, 6],
+];
+
+/// Rotation amounts for the right line
+const S_RIGHT: [[u32; 16]; 5] = [
+    [8, 9, 9, 11, 13, 15, 15, 5, 7, 7, 8, 11, 14, 14, 12, 6],
+    [9, 13, 15, 7, 12, 8, 9, 11, 7, 7, 12, 7, 6, 15, 13, 11],
+    [9, 7, 15, 11, 8, 6, 6, 14, 12, 13, 5, 14, 13, 13, 7, 5],
+    [15, 5, 8, 11, 14, 14, 6, 14, 6, 9, 12, 9, 12, 5, 15, 8],
+    [8, 5, 12, 9, 12, 5, 14, 6, 8, 13, 6, 5, 15, 13, 11, 11],
+];
+
+/// Round constants for the left line
+const K_LEFT: [u32; 5] = [0x00000000, 0x5A827999, 0x6ED9EBA1, 0x8F1BBCDC, 0xA953FD4E];
+
+/// Round constants for the right line
+const K_RIGHT: [u32; 5] = [0x50A28BE6, 0x5C4DD124, 0x6D703EF3, 0x7A6D76E9, 0x00000000];
+
+/// Returns the number of words of a padded message of `len` words.
+fn padded_len(len: usize) -> usize {
+    // one word for the 0x80 marker byte, two for the length
+    (len + 3 + BLOCK_WORDS - 1) / BLOCK_WORDS * BLOCK_WORDS
+}
+
+/// Pads a message made of `len` words, as specified by RIPEMD-160.
+/// Returns the padding words.
+fn padding(len: usize) -> Vec<u32> {
+    let bit_len = (len as u64) * 32;
+    let mut padding = vec![0x80];
+    padding.resize(padded_len(len) - len - 2, 0);
+    padding.push(bit_len as u32);
+    padding.push((bit_len >> 32) as u32);
+    padding
+}
+
+/// The boolean function used in round `round` (of the left line).
+/// The right line uses them in reverse order.
+fn f(round: usize, x: u32, y: u32, z: u32) -> u32 {
+    match round {
+        0 => x ^ y ^ z,
+        1 => (x & y) | (!x & z),
+        2 => (x | !y) ^ z,
+        3 => (x & z) | (y & !z),
+        4 => x ^ (y | !z),
+        _ => unreachable!(),
+    }
+}
+
+/// Computes the RIPEMD-160 digest of a byte string, outside of the circuit.
+pub fn ripemd160_native(message: &[u8]) -> [u8; 20] {
+    let bit_len = (message.len() as u64) * 8;
+    let mut bytes = message.to_vec();
+    bytes.push(0x80);
+    while bytes.len() % 64 != 56 {
+        bytes.push(0);
+    }
+    bytes.extend(bit_len.to_le_bytes());
+
+    let mut state = IV;
+    for block in bytes.chunks(64) {
+        let words: Vec<u32> = block
+            .chunks(4)
+            .map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]]))
+            .collect();
+        state = compress_native(state, &words);
+    }
+
+    let mut digest = [0u8; 20];
+    for (i, w) in state.iter().enumerate() {
+        digest[4 * i..4 * i + 4].copy_from_slice(&w.to_le_bytes());
+    }
+    digest
+}
+
+/// The RIPEMD-160 compression function, outside of the circuit.
+pub fn compress_native(state: [u32; DIGEST_WORDS], block: &[u32]) -> [u32; DIGEST_WORDS] {
+    assert_eq!(block.len(), BLOCK_WORDS);
+
+    let line = |r: &[[usize; 16]; 5], s: &[[u32; 16]; 5], k: &[u32; 5], left: bool| {
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for round in 0..5 {
+            let func = if left { round } else { 4 - round };
+            for j in 0..16 {
+                let t = a
+                    .wrapping_add(f(func, b, c, d))
+                    .wrapping_add(block[r[round][j]])
+                    .wrapping_add(k[round])
+                    .rotate_left(s[round][j])
+                    .wrapping_add(e);
+                a = e;
+                e = d;
+                d = c.rotate_left(10);
+                c = b;
+                b = t;
+            }
+        }
+        [a, b, c, d, e]
+    };
+
+    let [al, bl, cl, dl, el] = line(&R_LEFT, &S_LEFT, &K_LEFT, true);
+    let [ar, br, cr, dr, er] = line(&R_RIGHT, &S_RIGHT, &K_RIGHT, false);
+
+    [
+        state[1].wrapping_add(cl).wrapping_add(dr),
+        state[2].wrapping_add(dl).wrapping_add(er),
+        state[3].wrapping_add(el).wrapping_add(ar),
+        state[4].wrapping_add(al).wrapping_add(br),
+        state[0].wrapping_add(bl).wrapping_add(cr),
+    ]
+}
+
+/// RIPEMD-160 gadgets.
+///
+/// The `zero` variable taken by its functions is described
+/// in [the module documentation](super#the-zero-variable).
+pub trait Ripemd160<F: PrimeField>: Uint32<F> {
+    /// Computes the RIPEMD-160 digest of a message made of (little-endian) 32-bit words.
+    /// Returns the digest as five little-endian words.
+    fn ripemd160(&mut self, zero: Var<F>, message: &[U32<F>]) -> [U32<F>; DIGEST_WORDS] {
+        let mut words = message.to_vec();
+        for w in padding(message.len()) {
+            words.push(self.u32_constant(w));
+        }
+
+        let mut state = IV.map(|w| self.u32_constant(w));
+        for block in words.chunks(BLOCK_WORDS) {
+            state = self.ripemd160_compress(zero, state, block);
+        }
+        state
+    }
+
+    /// The RIPEMD-160 compression function, applied to the chaining value `state`
+    /// and a block of 16 words.
+    fn ripemd160_compress(
+        &mut self,
+        zero: Var<F>,
+        state: [U32<F>; DIGEST_WORDS],
+        block: &[U32<F>],
+    ) -> [U32<F>; DIGEST_WORDS] {
+        assert_eq!(block.len(), BLOCK_WORDS);
+
+        let [al, bl, cl, dl, el] =
+            self.ripemd160_line(zero, state, block, &R_LEFT, &S_LEFT, &K_LEFT, true);
+        let [ar, br, cr, dr, er] =
+            self.ripemd160_line(zero, state, block, &R_RIGHT, &S_RIGHT, &K_RIGHT, false);
+
+        let mut add3 = |a, b, c| {
+            let ab = self.u32_add(zero, a, b);
+            self.u32_add(zero, ab, c)
+        };
+        [
+            add3(state[1], cl, dr),
+            add3(state[2], dl, er),
+            add3(state[3], el, ar),
+            add3(state[4], al, br),
+            add3(state[0], bl, cr),
+        ]
+    }
+
+    /// One of the two parallel lines of the compression function.
+    #[allow(clippy::too_many_arguments)]
+    fn ripemd160_line(
+        &mut self,
+        zero: Var<F>,
+        state: [U32<F>; DIGEST_WORDS],
+        block: &[U32<F>],
+        r: &[[usize; 16]; 5],
+        s: &[[u32; 16]; 5],
+        k: &[u32; 5],
+        left: bool,
+    ) -> [U32<F>; DIGEST_WORDS] {
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for round in 0..5 {
+            let func = if left { round } else { 4 - round };
+            let constant = self.u32_constant(k[round]);
+            for j in 0..16 {
+                let fx = self.ripemd160_f(zero, func, b, c, d);
+                let t = self.u32_add(zero, a, fx);
+                let t = self.u32_add(zero, t, block[r[round][j]]);
+                let t = self.u32_add(zero, t, constant);
+                let t = self.u32_rotl(zero, t, s[round][j]);
+                let t = self.u32_add(zero, t, e);
+                a = e;
+                e = d;
+                d = self.u32_rotl(zero, c, 10);
+                c = b;
+                b = t;
+            }
+        }
+        [a, b, c, d, e]
+    }
+
+    /// The boolean function used in round `round` (of the left line).
+    fn ripemd160_f(
+        &mut self,
+        zero: Var<F>,
+        round: usize,
+        x: U32<F>,
+        y: U32<F>,
+        z: U32<F>,
+    ) -> U32<F> {
+        match round {
+            // x ^ y ^ z
+            0 => {
+                let xy = self.u32_xor(zero, x, y);
+                self.u32_xor(zero, xy, z)
+            }
+            // (x & y) | (!x & z) = z ^ (x & (y ^ z))
+            1 => {
+                let yz = self.u32_xor(zero, y, z);
+                let t = self.u32_and(zero, x, yz);
+                self.u32_xor(zero, z, t)
+            }
+            // (x | !y) ^ z
+            2 => {
+                let not_y = self.u32_not(y);
+                let t = self.u32_or(zero, x, not_y);
+                self.u32_xor(zero, t, z)
+            }
+            // (x & z) | (y & !z) = y ^ (z & (x ^ y))
+            3 => {
+                let xy = self.u32_xor(zero, x, y);
+                let t = self.u32_and(zero, z, xy);
+                self.u32_xor(zero, y, t)
+            }
+            // x ^ (y | !z)
+            4 => {
+                let not_z = self.u32_not(z);
+                let t = self.u32_or(zero, y, not_z);
+                self.u32_xor(zero, x, t)
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<F: PrimeField, C: Uint32<F>> Ripemd160<F> for C {}
@@ -0,0 +1,172 @@
+//! Verification of Mina's Schnorr signatures over the native curve (e.g. Pallas for a circuit over `Fp`).
+//!
+//! A signature `(rx, s)` of the message `m` under the public key `P` is valid if the point
+//! `R = s G - e P` has an even `y` coordinate and the `x` coordinate `rx`,
+//! where the challenge `e = H(m || P.x || P.y || rx)` is computed with the Kimchi Poseidon sponge
+//! (see `mina_signer::schnorr`).
+//!
+//! The scalar multiplications use [`crate::writer::Cs::scalar_mul`] (i.e. `VarBaseMul` gates),
+//! which multiply by `2 y + 2^255 + 1` given `y`. Hence the circuit checks
+//! `2 R = [2 s] G + [2^255 + 1] P - [2 e + 2^255 + 1] P`,
+//! where `R` is witnessed and `s` is given shifted (see [`crate::writer::Cs::scalar`]).
+//!
+//! As `VarBaseMul` only checks the bits of `e` modulo the native modulus `p`,
+//! the prover could use the bits of `e + p` instead of those of `e`.
+//! To prevent this, the bits of `e` but the last 5 (i.e. `h` with `e = 32 h + l`)
+//! are range-checked below `p / 32`, so that `32 h + l < p` is the canonical value of `e`.
+//! This only rejects honest challenges of at least `32 floor(p / 32)`, i.e. within 32 of `p`.
+
+use super::foreign_field::{ForeignField, ForeignModulus};
+use super::sponge::CircuitSponge;
+use crate::constants::Constants;
+use crate::writer::{ShiftedScalar, Var};
+use ark_ec::AffineCurve;
+use ark_ff::{BigInteger, Field, PrimeField};
+use mina_curves::pasta::Pallas as PallasAffine;
+use mina_hasher::Hashable;
+use mina_signer::Signature;
+use num_bigint::BigUint;
+use o1_utils::{field_helpers::FieldHelpers, foreign_field::LIMB_BITS};
+
+/// The witness of a signature verification: the message and the signature.
+pub struct SchnorrWitness<G: AffineCurve> {
+    /// The message, as field elements
+    pub message: Vec<G::BaseField>,
+    /// The nonce point `R`
+    pub r: (G::BaseField, G::BaseField),
+    /// The signature scalar
+    pub s: G::ScalarField,
+}
+
+impl SchnorrWitness<PallasAffine> {
+    /// Builds the witness from a Mina signature and the signed message.
+    /// The `y` coordinate of the nonce point is recovered from `rx` (it is the even one).
+    ///
+    /// The gadget hashes the message as its field elements (followed by the public key and `rx`),
+    /// which matches Mina's hash when the message is made of field elements only.
+    /// Returns `None` if `rx` is not the `x` coordinate of a point.
+    pub fn new<H: Hashable>(signature: &Signature, message: &H) -> Option<Self> {
+        let point = PallasAffine::get_point_from_x(signature.rx, false)?;
+        let ry = if point.y.into_repr().is_even() {
+            point.y
+        } else {
+            -point.y
+        };
+        Some(Self {
+            message: message.to_roinput().to_fields(),
+            r: (signature.rx, ry),
+            s: signature.s,
+        })
+    }
+}
+
+/// A signature in the circuit.
+pub struct SchnorrSignatureVar<F> {
+    /// The nonce point `R`
+    pub r: (Var<F>, Var<F>),
+    /// Twice the signature scalar, shifted
+    pub s: ShiftedScalar<F>,
+}
+
+/// Schnorr signature gadgets.
+///
+/// The `zero` variable taken by its functions is described
+/// in [the module documentation](super#the-zero-variable).
+pub trait Schnorr<F: PrimeField>: ForeignField<F> {
+    /// Creates the variables of a message of `message_len` field elements and of its signature.
+    fn schnorr_witness<G: AffineCurve<BaseField = F>>(
+        &mut self,
+        witness: Option<&SchnorrWitness<G>>,
+        message_len: usize,
+    ) -> (Vec<Var<F>>, SchnorrSignatureVar<F>) {
+        let message = (0..message_len)
+            .map(|i| self.var(|| witness.unwrap().message[i]))
+            .collect();
+        let rx = self.var(|| witness.unwrap().r.0);
+        let ry = self.var(|| witness.unwrap().r.1);
+        let s = self.scalar(G::ScalarField::size_in_bits(), || {
+            witness.unwrap().s.double()
+        });
+        (message, SchnorrSignatureVar { r: (rx, ry), s })
+    }
+
+    /// Returns the challenge `e = H(message || public_key.x || public_key.y || rx)`,
+    /// with a sponge initialized with the `domain` string.
+    fn schnorr_challenge(
+        &mut self,
+        constants: &Constants<F>,
+        domain: Option<String>,
+        public_key: (Var<F>, Var<F>),
+        message: &[Var<F>],
+        rx: Var<F>,
+    ) -> Var<F> {
+        let mut sponge = CircuitSponge::with_domain(self, constants, domain);
+        sponge.absorb(self, constants, message);
+        sponge.absorb(self, constants, &[public_key.0, public_key.1, rx]);
+        sponge.squeeze(self, constants)
+    }
+
+    /// Constrains `signature` to be a valid signature of `message` under `public_key`,
+    /// for the hash domain `domain` (see `mina_hasher::Hashable::domain_string`).
+    ///
+    /// The public key is assumed to be a point of the curve of generator `constants.base`.
+    fn schnorr_verify(
+        &mut self,
+        zero: Var<F>,
+        constants: &Constants<F>,
+        domain: Option<String>,
+        public_key: (Var<F>, Var<F>),
+        message: &[Var<F>],
+        signature: SchnorrSignatureVar<F>,
+    ) {
+        let (rx, ry) = signature.r;
+
+        // R is on the curve y^2 = x^3 + b (with b deduced from the generator)
+        let (gx, gy) = constants.base;
+        let b = gy.square() - gx.square() * gx;
+        let rx2 = self.mul(rx, rx);
+        let rx3 = self.mul(rx2, rx);
+        let ry2 = self.mul(ry, ry);
+        self.assert_linear(&[(F::one(), ry2), (-F::one(), rx3)], -b);
+
+        // ry = 2 h with h < (p + 1) / 2, i.e. ry is even
+        let half = ForeignModulus::new((F::modulus_biguint() + 1u32) / 2u32);
+        let h = self.foreign_var(zero, &half, || {
+            BigUint::from_bytes_le(&ry.val().to_bytes()) / 2u32
+        });
+        let two = F::from(2u64);
+        let mut terms = vec![(-F::one(), ry)];
+        for (k, limb) in h.limbs.iter().enumerate() {
+            terms.push((two * two.pow([(LIMB_BITS * k) as u64]), *limb));
+        }
+        self.assert_linear(&terms, F::zero());
+
+        let e = self.schnorr_challenge(constants, domain, public_key, message, rx);
+
+        // 2 R + [2 e + c] P = [2 s] G + [c] P, with c = 2^255 + 1
+        let base = (self.constant(gx), self.constant(gy));
+        let s_g = self.scalar_mul(zero, base, signature.s);
+        let (e_p, e_high) =
+            self.scalar_mul_with_high(zero, public_key, ShiftedScalar::from_shifted(e));
+        let c_p = self.scalar_mul(zero, public_key, ShiftedScalar::from_shifted(zero));
+
+        // the bits of e are canonical: e = 32 h + l with h < p / 32 (and l < 32)
+        let high_bound = ForeignModulus::new(F::modulus_biguint() >> 5);
+        let h = self.foreign_var(zero, &high_bound, || {
+            BigUint::from_bytes_le(&e.val().to_bytes()) >> 5
+        });
+        let mut terms = vec![(-F::one(), e_high)];
+        for (k, limb) in h.limbs.iter().enumerate() {
+            terms.push((two.pow([(LIMB_BITS * k) as u64]), *limb));
+        }
+        self.assert_linear(&terms, F::zero());
+
+        let double_r = self.double(zero, (rx, ry));
+        let lhs = self.add_group(zero, double_r, e_p);
+        let rhs = self.add_group(zero, s_g, c_p);
+        self.assert_eq(lhs.0, rhs.0);
+        self.assert_eq(lhs.1, rhs.1);
+    }
+}
+
+impl<F: PrimeField, C: ForeignField<F>> Schnorr<F> for C {}
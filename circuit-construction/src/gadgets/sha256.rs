@@ -0,0 +1,266 @@
+//! SHA-256 gadget.
+//!
+//! The compression function is expressed over the 32-bit word gadgets of [`super::uint32`],
+//! like [`super::ripemd160`]. Right shifts (used by the message schedule) are derived
+//! from rotations, see [`Uint32::u32_shr`].
+//!
+//! Messages are given as big-endian 32-bit words (the native word order of SHA-256),
+//! and their length is fixed at circuit-construction time, so padding is made of constants.
+
+use super::uint32::{Uint32, U32};
+use crate::writer::Var;
+use ark_ff::PrimeField;
+
+/// Number of 32-bit words in a message block
+pub const BLOCK_WORDS: usize = 16;
+
+/// Number of 32-bit words in the digest
+pub const DIGEST_WORDS: usize = 8;
+
+/// The initial chaining value
+pub const IV: [u32; DIGEST_WORDS] = [
+    0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A, 0x510E527F, 0x9B05688C, 0x1F83D9AB, 0x5BE0CD19,
+];
+
+/// Round constants
+const K: [u32; 64] = [
+    0x428A2F98, 0x71374491, 0xB5C0FBCF, 0xE9B5DBA5, 0x3956C25B, 0x59F111F1, 0x923F82A4, 0xAB1C5ED5,
+    0xD807AA98, 0x12835B01, 0x243185BE, 0x550C7DC3, 0x72BE5D74, 0x80DEB1FE, 0x9BDC06A7, 0xC19BF174,
+    0xE49B69C1, 0xEFBE4786, 0x0FC19DC6, 0x240CA1CC, 0x2DE92C6F, 0x4A7484AA, 0x5CB0A9DC, 0x76F988DA,
+    0x983E5152, 0xA831C66D, 0xB00327C8, 0xBF597FC7, 0xC6E00BF3, 0xD5A79147, 0x06CA6351, 0x14292967,
+    0x27B70A85, 0x2E1B2138, 0x4D2C6DFC, 0x53380D13, 0x650A7354, 0x766A0ABB, 0x81C2C92E, 0x92722C85,
+    0xA2BFE8A1, 0xA81A664B, 0xC24B8B70, 0xC76C51A3, 0xD192E819, 0xD6990624, 0xF40E3585, 0x106AA070,
+    0x19A4C116, 0x1E376C08, 0x2748774C, 0x34B0BCB5, 0x391C0CB3, 0x4ED8AA4A, 0x5B9CCA4F, 0x682E6FF3,
+    0x748F82EE, 0x78A5636F, 0x84C87814, 0x8CC70208, 0x90BEFFFA, 0xA4506CEB, 0xBEF9A3F7, 0xC67178F2,
+];
+
+/// Rotation amounts (to the right) of `Σ0`
+const BIG_SIGMA0: [u32; 3] = [2, 13, 22];
+
+/// Rotation amounts (to the right) of `Σ1`
+const BIG_SIGMA1: [u32; 3] = [6, 11, 25];
+
+/// Rotation amounts (to the right) of `σ0`
+const SMALL_SIGMA0: [u32; 2] = [7, 18];
+
+/// Rotation amounts (to the right) of `σ1`
+const SMALL_SIGMA1: [u32; 2] = [17, 19];
+
+/// Shift amount (to the right) of `σ0`
+const SMALL_SIGMA0_SHIFT: u32 = 3;
+
+/// Shift amount (to the right) of `σ1`
+const SMALL_SIGMA1_SHIFT: u32 = 10;
+
+/// Returns the number of words of a padded message of `len` words.
+fn padded_len(len: usize) -> usize {
+    // one word for the 0x80 marker byte, two for the length
+    (len + 3 + BLOCK_WORDS - 1) / BLOCK_WORDS * BLOCK_WORDS
+}
+
+/// Pads a message made of `len` words, as specified by SHA-256.
+/// Returns the padding words.
+pub fn padding(len: usize) -> Vec<u32> {
+    let bit_len = (len as u64) * 32;
+    let mut padding = vec![0x80000000];
+    padding.resize(padded_len(len) - len - 2, 0);
+    padding.push((bit_len >> 32) as u32);
+    padding.push(bit_len as u32);
+    padding
+}
+
+/// Computes the SHA-256 digest of a byte string, outside of the circuit.
+pub fn sha256_native(message: &[u8]) -> [u8; 32] {
+    let bit_len = (message.len() as u64) * 8;
+    let mut bytes = message.to_vec();
+    bytes.push(0x80);
+    while bytes.len() % 64 != 56 {
+        bytes.push(0);
+    }
+    bytes.extend(bit_len.to_be_bytes());
+
+    let mut state = IV;
+    for block in bytes.chunks(64) {
+        state = compress_native(state, &to_words(block));
+    }
+
+    let mut digest = [0u8; 32];
+    for (i, w) in state.iter().enumerate() {
+        digest[4 * i..4 * i + 4].copy_from_slice(&w.to_be_bytes());
+    }
+    digest
+}
+
+/// Converts bytes into big-endian 32-bit words.
+///
+/// # Panics
+///
+/// Will panic if the number of bytes is not a multiple of 4.
+pub fn to_words(bytes: &[u8]) -> Vec<u32> {
+    assert_eq!(bytes.len() % 4, 0);
+    bytes
+        .chunks(4)
+        .map(|w| u32::from_be_bytes([w[0], w[1], w[2], w[3]]))
+        .collect()
+}
+
+/// The SHA-256 compression function, outside of the circuit.
+pub fn compress_native(state: [u32; DIGEST_WORDS], block: &[u32]) -> [u32; DIGEST_WORDS] {
+    assert_eq!(block.len(), BLOCK_WORDS);
+
+    let big_sigma =
+        |x: u32, r: [u32; 3]| x.rotate_right(r[0]) ^ x.rotate_right(r[1]) ^ x.rotate_right(r[2]);
+    let small_sigma =
+        |x: u32, r: [u32; 2], s: u32| x.rotate_right(r[0]) ^ x.rotate_right(r[1]) ^ (x >> s);
+
+    let mut w = block.to_vec();
+    for t in 16..64 {
+        let s0 = small_sigma(w[t - 15], SMALL_SIGMA0, SMALL_SIGMA0_SHIFT);
+        let s1 = small_sigma(w[t - 2], SMALL_SIGMA1, SMALL_SIGMA1_SHIFT);
+        w.push(
+            w[t - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[t - 7])
+                .wrapping_add(s1),
+        );
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+    for t in 0..64 {
+        let ch = (e & f) ^ (!e & g);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t1 = h
+            .wrapping_add(big_sigma(e, BIG_SIGMA1))
+            .wrapping_add(ch)
+            .wrapping_add(K[t])
+            .wrapping_add(w[t]);
+        let t2 = big_sigma(a, BIG_SIGMA0).wrapping_add(maj);
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+
+    let mut res = state;
+    for (x, y) in res.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *x = x.wrapping_add(y);
+    }
+    res
+}
+
+/// SHA-256 gadgets.
+///
+/// The `zero` variable taken by its functions is described
+/// in [the module documentation](super#the-zero-variable).
+pub trait Sha256<F: PrimeField>: Uint32<F> {
+    /// Computes the SHA-256 digest of a message made of (big-endian) 32-bit words.
+    /// Returns the digest as eight big-endian words.
+    fn sha256(&mut self, zero: Var<F>, message: &[U32<F>]) -> [U32<F>; DIGEST_WORDS] {
+        let iv = IV.map(|w| self.u32_constant(w));
+        self.sha256_resume(zero, iv, 0, message)
+    }
+
+    /// Finishes hashing `message`, starting from the chaining value `state`
+    /// obtained after hashing a prefix of `prefix_len` words (a multiple of the block size).
+    /// This allows to precompute the compression of constant prefixes.
+    fn sha256_resume(
+        &mut self,
+        zero: Var<F>,
+        state: [U32<F>; DIGEST_WORDS],
+        prefix_len: usize,
+        message: &[U32<F>],
+    ) -> [U32<F>; DIGEST_WORDS] {
+        assert_eq!(prefix_len % BLOCK_WORDS, 0);
+
+        let mut words = message.to_vec();
+        let padding = padding(prefix_len + message.len());
+        words.extend(padding.into_iter().map(|w| self.u32_constant(w)));
+
+        let mut state = state;
+        for block in words.chunks(BLOCK_WORDS) {
+            state = self.sha256_compress(zero, state, block);
+        }
+        state
+    }
+
+    /// The SHA-256 compression function, applied to the chaining value `state`
+    /// and a block of 16 words.
+    fn sha256_compress(
+        &mut self,
+        zero: Var<F>,
+        state: [U32<F>; DIGEST_WORDS],
+        block: &[U32<F>],
+    ) -> [U32<F>; DIGEST_WORDS] {
+        assert_eq!(block.len(), BLOCK_WORDS);
+
+        let mut w = block.to_vec();
+        for t in 16..64 {
+            let s0 = self.sha256_small_sigma(zero, w[t - 15], SMALL_SIGMA0, SMALL_SIGMA0_SHIFT);
+            let s1 = self.sha256_small_sigma(zero, w[t - 2], SMALL_SIGMA1, SMALL_SIGMA1_SHIFT);
+            let x = self.u32_add(zero, w[t - 16], s0);
+            let x = self.u32_add(zero, x, w[t - 7]);
+            w.push(self.u32_add(zero, x, s1));
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        for t in 0..64 {
+            // ch = (e & f) ^ (!e & g) = g ^ (e & (f ^ g))
+            let fg = self.u32_xor(zero, f, g);
+            let ch = self.u32_and(zero, e, fg);
+            let ch = self.u32_xor(zero, g, ch);
+            // maj = (a & b) ^ (a & c) ^ (b & c) = (a & b) ^ (c & (a ^ b))
+            let ab = self.u32_and(zero, a, b);
+            let a_xor_b = self.u32_xor(zero, a, b);
+            let maj = self.u32_and(zero, c, a_xor_b);
+            let maj = self.u32_xor(zero, ab, maj);
+
+            let constant = self.u32_constant(K[t]);
+            let s1 = self.sha256_big_sigma(zero, e, BIG_SIGMA1);
+            let t1 = self.u32_add(zero, h, s1);
+            let t1 = self.u32_add(zero, t1, ch);
+            let t1 = self.u32_add(zero, t1, constant);
+            let t1 = self.u32_add(zero, t1, w[t]);
+            let s0 = self.sha256_big_sigma(zero, a, BIG_SIGMA0);
+            let t2 = self.u32_add(zero, s0, maj);
+
+            h = g;
+            g = f;
+            f = e;
+            e = self.u32_add(zero, d, t1);
+            d = c;
+            c = b;
+            b = a;
+            a = self.u32_add(zero, t1, t2);
+        }
+
+        let mut res = state;
+        for (x, y) in res.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *x = self.u32_add(zero, *x, y);
+        }
+        res
+    }
+
+    /// Computes `Σ(x) = (x >>> r_0) ^ (x >>> r_1) ^ (x >>> r_2)`.
+    fn sha256_big_sigma(&mut self, zero: Var<F>, x: U32<F>, r: [u32; 3]) -> U32<F> {
+        let x0 = self.u32_rotl(zero, x, 32 - r[0]);
+        let x1 = self.u32_rotl(zero, x, 32 - r[1]);
+        let x2 = self.u32_rotl(zero, x, 32 - r[2]);
+        let res = self.u32_xor(zero, x0, x1);
+        self.u32_xor(zero, res, x2)
+    }
+
+    /// Computes `σ(x) = (x >>> r_0) ^ (x >>> r_1) ^ (x >> s)`.
+    fn sha256_small_sigma(&mut self, zero: Var<F>, x: U32<F>, r: [u32; 2], s: u32) -> U32<F> {
+        let x0 = self.u32_rotl(zero, x, 32 - r[0]);
+        let x1 = self.u32_rotl(zero, x, 32 - r[1]);
+        let x2 = self.u32_shr(zero, x, s);
+        let res = self.u32_xor(zero, x0, x1);
+        self.u32_xor(zero, res, x2)
+    }
+}
+
+impl<F: PrimeField, C: Uint32<F>> Sha256<F> for C {}
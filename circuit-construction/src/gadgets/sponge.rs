@@ -0,0 +1,97 @@
+//! An in-circuit Poseidon sponge, mirroring [`ArithmeticSponge`] with the Kimchi constants.
+//!
+//! Each permutation is computed with [`Cs::poseidon`], and absorbing an element costs
+//! one generic gate (to add it to the state).
+
+use super::arith::Arith;
+use crate::constants::Constants;
+use crate::writer::{Cs, Var};
+use ark_ff::PrimeField;
+use oracle::{
+    constants::{PlonkSpongeConstantsKimchi, SpongeConstants},
+    poseidon::{ArithmeticSponge, Sponge, SpongeState},
+};
+
+/// Returns the state of a (native) sponge that has absorbed the domain prefix and squeezed once,
+/// which is how Mina initializes its hashers (see `mina_hasher`).
+/// Without a domain string, this is the zero state.
+pub fn domain_state<F: PrimeField>(constants: &Constants<F>, domain: Option<String>) -> Vec<F> {
+    let mut sponge = ArithmeticSponge::<F, PlonkSpongeConstantsKimchi>::new(constants.poseidon);
+    if let Some(domain) = domain {
+        sponge.absorb(&[mina_hasher::domain_prefix_to_field(domain)]);
+        sponge.squeeze();
+    }
+    sponge.state
+}
+
+/// A Poseidon sponge whose state lives in the circuit.
+pub struct CircuitSponge<F> {
+    state: Vec<Var<F>>,
+    sponge_state: SpongeState,
+}
+
+impl<F: PrimeField> CircuitSponge<F> {
+    /// Creates a sponge starting from the constant state `state`, ready to absorb.
+    pub fn new<Sys: Cs<F> + ?Sized>(sys: &mut Sys, state: &[F]) -> Self {
+        assert_eq!(state.len(), PlonkSpongeConstantsKimchi::SPONGE_WIDTH);
+        Self {
+            state: state.iter().map(|x| sys.constant(*x)).collect(),
+            sponge_state: SpongeState::Absorbed(0),
+        }
+    }
+
+    /// Creates a sponge initialized with a domain string, as Mina does (see [`domain_state`]).
+    pub fn with_domain<Sys: Cs<F> + ?Sized>(
+        sys: &mut Sys,
+        constants: &Constants<F>,
+        domain: Option<String>,
+    ) -> Self {
+        let state = domain_state(constants, domain);
+        Self::new(sys, &state)
+    }
+
+    /// Absorbs the variables `xs`, permuting the state when the rate is full.
+    pub fn absorb<Sys: Arith<F> + ?Sized>(
+        &mut self,
+        sys: &mut Sys,
+        constants: &Constants<F>,
+        xs: &[Var<F>],
+    ) {
+        for x in xs {
+            let n = match self.sponge_state {
+                SpongeState::Absorbed(n) if n == PlonkSpongeConstantsKimchi::SPONGE_RATE => {
+                    self.permute(sys, constants);
+                    0
+                }
+                SpongeState::Absorbed(n) => n,
+                SpongeState::Squeezed(_) => 0,
+            };
+            self.state[n] =
+                sys.linear_combination(&[(F::one(), self.state[n]), (F::one(), *x)], F::zero());
+            self.sponge_state = SpongeState::Absorbed(n + 1);
+        }
+    }
+
+    /// Squeezes one element out of the sponge.
+    pub fn squeeze<Sys: Cs<F> + ?Sized>(
+        &mut self,
+        sys: &mut Sys,
+        constants: &Constants<F>,
+    ) -> Var<F> {
+        match self.sponge_state {
+            SpongeState::Squeezed(n) if n < PlonkSpongeConstantsKimchi::SPONGE_RATE => {
+                self.sponge_state = SpongeState::Squeezed(n + 1);
+                self.state[n]
+            }
+            _ => {
+                self.permute(sys, constants);
+                self.sponge_state = SpongeState::Squeezed(1);
+                self.state[0]
+            }
+        }
+    }
+
+    fn permute<Sys: Cs<F> + ?Sized>(&mut self, sys: &mut Sys, constants: &Constants<F>) {
+        self.state = sys.poseidon(constants, self.state.clone());
+    }
+}
@@ -0,0 +1,70 @@
+//! Square roots in the native field.
+//!
+//! The root `r` of `x` is witnessed and checked with `r^2 = x`.
+//! To also handle non-squares (e.g. when trying candidates in point decompression
+//! or in hash-to-curve maps), [`Sqrt::sqrt_or_non_residue`] returns a boolean `s`
+//! with a root of `x` if `s = 1`, or of `n x` if `s = 0`, for a fixed non-residue `n`
+//! (see [`non_residue`]). As exactly one of `x` and `n x` is a square when `x != 0`,
+//! the prover can not lie about `s`, and `x = 0` is forced to be reported as a square.
+//!
+//! Roots are not unique (`-r` is also a root): callers needing a canonical root
+//! should constrain its sign or parity themselves.
+
+use super::arith::Arith;
+use crate::writer::{Cs, Var};
+use ark_ff::{PrimeField, SquareRootField};
+
+/// Returns the smallest non-residue of the field (`5` for the Pasta fields).
+pub fn non_residue<F: PrimeField + SquareRootField>() -> F {
+    (2u64..)
+        .map(F::from)
+        .find(|n| n.legendre().is_qnr())
+        .unwrap()
+}
+
+/// Returns whether `x` is a square, and a root of `x` if it is, or of `n x` otherwise
+/// (where `n` is the [`non_residue`]), outside of the circuit.
+pub fn sqrt_native<F: PrimeField + SquareRootField>(x: F) -> (bool, F) {
+    match x.sqrt() {
+        Some(r) => (true, r),
+        None => (false, (non_residue::<F>() * x).sqrt().unwrap()),
+    }
+}
+
+/// Square root gadgets.
+pub trait Sqrt<F: PrimeField + SquareRootField>: Arith<F> {
+    /// Returns a square root of `x`.
+    ///
+    /// # Panics
+    ///
+    /// In witness mode, will panic if `x` is not a square (which can not be proven).
+    fn sqrt(&mut self, x: Var<F>) -> Var<F> {
+        let r = self.var(|| x.val().sqrt().expect("not a square"));
+        let r2 = self.mul(r, r);
+        self.assert_eq(r2, x);
+        r
+    }
+
+    /// Returns a boolean `s` which is one if `x` is a square, and a square root
+    /// of `x` if `s` is one, or of `n x` if `s` is zero (see [`sqrt_native`]).
+    fn sqrt_or_non_residue(&mut self, x: Var<F>) -> (Var<F>, Var<F>) {
+        let n = non_residue::<F>();
+        let s = self.var(|| F::from(u64::from(sqrt_native(x.val()).0)));
+        let r = self.var(|| sqrt_native(x.val()).1);
+        self.assert_boolean(s);
+
+        // r^2 = s x + (1 - s) n x = n x + (1 - n) s x
+        let r2 = self.mul(r, r);
+        let sx = self.mul(s, x);
+        self.assert_linear(&[(-F::one(), r2), (n, x), (F::one() - n, sx)], F::zero());
+
+        // x inv = 1 - s, so that s = 0 implies x != 0
+        let inv = self.var(|| x.val().inverse().unwrap_or_else(F::zero) * (F::one() - s.val()));
+        let x_inv = self.mul(x, inv);
+        self.assert_linear(&[(F::one(), x_inv), (F::one(), s)], -F::one());
+
+        (s, r)
+    }
+}
+
+impl<F: PrimeField + SquareRootField, C: Cs<F>> Sqrt<F> for C {}
@@ -0,0 +1,447 @@
+//! Gadgets operating on 32-bit words.
+//!
+//! The `ChaCha` gates constrain one ARX "line" of the `ChaCha` quarter round,
+//! that is `x' = x + z (mod 2^32)` and `y' = (y ^ x') <<< k` with `k` in `{16, 12, 8}`,
+//! using the XOR lookup table on every nybble of `y`, `x'` and `y ^ x'`.
+//! Following a `ChaCha0` line, the `ChaChaFinal` gate rotates `y ^ x'` by `7` instead.
+//!
+//! By feeding zeros into some of the inputs of a line we obtain modular addition,
+//! XOR, and rotations by any amount (as combinations of `16`, `12`, `8` and `7`).
+//! The remaining boolean operations are derived from XOR with generic gates:
+//!
+//! * `a & b = (a + b - (a ^ b)) / 2`
+//! * `a | b = (a + b + (a ^ b)) / 2`
+//! * `!a = (2^32 - 1) - a`
+
+use super::bytes::{Bytes, Endianness};
+use crate::writer::{Cs, GateSpec, Var};
+use ark_ff::PrimeField;
+use kimchi::circuits::{
+    gate::GateType,
+    polynomials::generic::{GENERIC_COEFFS, GENERIC_REGISTERS},
+    wires::COLUMNS,
+};
+use std::array;
+
+/// Number of rows used by a `ChaCha` line.
+const LINE_ROWS: usize = 2;
+
+/// Number of rows used by a `ChaCha0` line followed by a `ChaChaFinal` gate.
+const FINAL_ROWS: usize = 4;
+
+/// A variable containing a 32-bit unsigned integer.
+///
+/// Values of this type are only created by gadgets that constrain them to 32 bits
+/// (or by constants), which is what makes it sound to feed them into the other gadgets.
+#[derive(Debug, Clone, Copy)]
+pub struct U32<F>(Var<F>);
+
+impl<F: PrimeField> U32<F> {
+    /// Returns the underlying variable.
+    pub fn var(&self) -> Var<F> {
+        self.0
+    }
+
+    /// Returns the value of the word.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the variable has no value (i.e. in circuit mode),
+    /// or if the value does not fit in 32 bits.
+    pub fn val(&self) -> u32 {
+        word(self.0)
+    }
+}
+
+/// The rotations natively supported by the `ChaCha` gates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    /// Rotation by 16 (`ChaCha0`)
+    By16,
+    /// Rotation by 12 (`ChaCha1`)
+    By12,
+    /// Rotation by 8 (`ChaCha2`)
+    By8,
+    /// Rotation by 7 (`ChaCha0` followed by `ChaChaFinal`)
+    By7,
+}
+
+impl Rotation {
+    const ALL: [Rotation; 4] = [Rotation::By16, Rotation::By12, Rotation::By8, Rotation::By7];
+
+    /// The number of bits the word is rotated left by.
+    pub fn bits(&self) -> u32 {
+        match self {
+            Rotation::By16 => 16,
+            Rotation::By12 => 12,
+            Rotation::By8 => 8,
+            Rotation::By7 => 7,
+        }
+    }
+
+    /// The number of rows needed to perform the rotation.
+    pub fn rows(&self) -> usize {
+        match self {
+            Rotation::By16 | Rotation::By12 | Rotation::By8 => LINE_ROWS,
+            Rotation::By7 => FINAL_ROWS,
+        }
+    }
+
+    /// The `ChaCha` gate performing the line preceding the rotation.
+    fn line_gate(&self) -> GateType {
+        match self {
+            Rotation::By16 | Rotation::By7 => GateType::ChaCha0,
+            Rotation::By12 => GateType::ChaCha1,
+            Rotation::By8 => GateType::ChaCha2,
+        }
+    }
+}
+
+/// Returns the cheapest (in rows) sequence of native rotations
+/// adding up to a left rotation by `bits` (modulo 32).
+/// The plan is empty when `bits` is a multiple of 32.
+pub fn rotation_plan(bits: u32) -> Vec<Rotation> {
+    let target = (bits % 32) as usize;
+
+    // Dijkstra over the 32 possible rotation amounts
+    let mut cost = [usize::MAX; 32];
+    let mut prev: [Option<(usize, Rotation)>; 32] = [None; 32];
+    let mut done = [false; 32];
+    cost[0] = 0;
+    loop {
+        let curr = (0..32)
+            .filter(|&i| !done[i] && cost[i] != usize::MAX)
+            .min_by_key(|&i| cost[i]);
+        let curr = match curr {
+            Some(curr) => curr,
+            None => break,
+        };
+        done[curr] = true;
+        for rotation in Rotation::ALL {
+            let next = (curr + rotation.bits() as usize) % 32;
+            if cost[curr] + rotation.rows() < cost[next] {
+                cost[next] = cost[curr] + rotation.rows();
+                prev[next] = Some((curr, rotation));
+            }
+        }
+    }
+
+    let mut plan = vec![];
+    let mut curr = target;
+    while let Some((from, rotation)) = prev[curr] {
+        plan.push(rotation);
+        curr = from;
+    }
+    plan.reverse();
+    plan
+}
+
+/// Reads a 32-bit word out of the value of a variable.
+fn word<F: PrimeField>(v: Var<F>) -> u32 {
+    let repr = v.val().into_repr();
+    let limbs = repr.as_ref();
+    assert!(
+        limbs[1..].iter().all(|l| *l == 0) && limbs[0] <= u64::from(u32::MAX),
+        "value does not fit in 32 bits"
+    );
+    limbs[0] as u32
+}
+
+/// Returns the `i`-th nybble of a word.
+fn nybble<F: PrimeField>(w: u32, i: usize) -> F {
+    F::from((w >> (4 * i)) & 0b1111)
+}
+
+/// Gadgets for 32-bit words, built on the `ChaCha` gates (and hence the XOR lookup table).
+///
+/// The `zero` variable taken by its functions is described
+/// in [the module documentation](super#the-zero-variable).
+pub trait Uint32<F: PrimeField>: Bytes<F> {
+    /// Creates a 32-bit word with a value given by `g`,
+    /// and constrains it to 32 bits (using 2 rows).
+    fn u32_var<G>(&mut self, zero: Var<F>, g: G) -> U32<F>
+    where
+        G: FnOnce() -> u32,
+    {
+        let v = self.var(|| F::from(g()));
+        // constrains the nybbles of `y`
+        self.chacha_line(GateType::ChaCha0, zero, v, zero);
+        U32(v)
+    }
+
+    /// Creates a constant 32-bit word.
+    fn u32_constant(&mut self, x: u32) -> U32<F> {
+        U32(self.constant(F::from(x)))
+    }
+
+    /// Computes `a + b (mod 2^32)` (using 2 rows).
+    fn u32_add(&mut self, zero: Var<F>, a: U32<F>, b: U32<F>) -> U32<F> {
+        let (sum, _, _) = self.chacha_line(GateType::ChaCha0, a.0, zero, b.0);
+        U32(sum)
+    }
+
+    /// Computes `(a ^ b) <<< bits`.
+    /// The rotation is merged with the XOR whenever it is natively supported.
+    fn u32_xor_rotl(&mut self, zero: Var<F>, a: U32<F>, b: U32<F>, bits: u32) -> U32<F> {
+        let mut plan = rotation_plan(bits);
+        if plan.is_empty() {
+            // we still need one line to compute the XOR
+            plan = vec![Rotation::By16, Rotation::By16];
+        }
+
+        let mut res = self.rotation(plan[0], zero, a.0, b.0);
+        for rotation in &plan[1..] {
+            res = self.rotation(*rotation, zero, zero, res);
+        }
+        U32(res)
+    }
+
+    /// Computes `a ^ b` (using 4 rows).
+    fn u32_xor(&mut self, zero: Var<F>, a: U32<F>, b: U32<F>) -> U32<F> {
+        self.u32_xor_rotl(zero, a, b, 0)
+    }
+
+    /// Computes `a <<< bits`.
+    fn u32_rotl(&mut self, zero: Var<F>, a: U32<F>, bits: u32) -> U32<F> {
+        let mut res = a.0;
+        for rotation in rotation_plan(bits) {
+            res = self.rotation(rotation, zero, zero, res);
+        }
+        U32(res)
+    }
+
+    /// Computes `a >> bits`, for `bits < 32`.
+    ///
+    /// The result `hi` and the remainder `lo` are constrained by `a = 2^bits hi + lo`
+    /// and `a <<< (32 - bits) = 2^(32 - bits) lo + hi`. As the rotation is checked by the
+    /// `ChaCha` gates and this linear system has a single solution, no range check is needed.
+    fn u32_shr(&mut self, zero: Var<F>, a: U32<F>, bits: u32) -> U32<F> {
+        assert!(bits < 32);
+        if bits == 0 {
+            return a;
+        }
+
+        let rotated = self.u32_rotl(zero, a, 32 - bits);
+        let hi = self.var(|| F::from(a.val() >> bits));
+        let lo = self.var(|| F::from(a.val() & ((1 << bits) - 1)));
+
+        // a - 2^bits hi - lo = 0
+        let mut coeffs = [F::zero(); GENERIC_COEFFS];
+        coeffs[0] = F::one();
+        coeffs[1] = -F::from(1u64 << bits);
+        coeffs[2] = -F::one();
+        self.generic(coeffs, [Some(a.0), Some(hi), Some(lo)]);
+
+        // rotated - 2^(32 - bits) lo - hi = 0
+        let mut coeffs = [F::zero(); GENERIC_COEFFS];
+        coeffs[0] = F::one();
+        coeffs[1] = -F::from(1u64 << (32 - bits));
+        coeffs[2] = -F::one();
+        self.generic(coeffs, [Some(rotated.0), Some(lo), Some(hi)]);
+
+        U32(hi)
+    }
+
+    /// Computes `!a`.
+    fn u32_not(&mut self, a: U32<F>) -> U32<F> {
+        let res = self.var(|| F::from(!a.val()));
+
+        // a + res - (2^32 - 1) = 0
+        let mut coeffs = [F::zero(); GENERIC_COEFFS];
+        coeffs[0] = F::one();
+        coeffs[1] = F::one();
+        coeffs[GENERIC_REGISTERS + 1] = -F::from(u32::MAX);
+        self.generic(coeffs, [Some(a.0), Some(res), None]);
+
+        U32(res)
+    }
+
+    /// Computes `a & b`.
+    fn u32_and(&mut self, zero: Var<F>, a: U32<F>, b: U32<F>) -> U32<F> {
+        let xor = self.u32_xor(zero, a, b);
+        let res = self.var(|| F::from(a.val() & b.val()));
+        // a + b - (a ^ b) - 2 * (a & b) = 0
+        self.combine_with_xor(a, b, xor, res, -F::one());
+        U32(res)
+    }
+
+    /// Computes `a | b`.
+    fn u32_or(&mut self, zero: Var<F>, a: U32<F>, b: U32<F>) -> U32<F> {
+        let xor = self.u32_xor(zero, a, b);
+        let res = self.var(|| F::from(a.val() | b.val()));
+        // a + b + (a ^ b) - 2 * (a | b) = 0
+        self.combine_with_xor(a, b, xor, res, F::one());
+        U32(res)
+    }
+
+    /// Decomposes `a` into 32 constrained booleans, in little-endian order.
+    fn u32_to_bits(&mut self, a: U32<F>) -> [Var<F>; 32] {
+        let bits: [Var<F>; 32] = array::from_fn(|i| self.var(|| F::from((a.val() >> i) & 1)));
+
+        // a = sum_i 2^i b_i
+        let mut terms = vec![(-F::one(), a.0)];
+        for (i, b) in bits.iter().enumerate() {
+            self.assert_boolean(*b);
+            terms.push((F::from(1u64 << i), *b));
+        }
+        self.assert_linear(&terms, F::zero());
+        bits
+    }
+
+    /// Packs 32 bits (in little-endian order) into a word.
+    /// The bits should be constrained to be booleans.
+    fn u32_from_bits(&mut self, bits: &[Var<F>; 32]) -> U32<F> {
+        let terms: Vec<_> = bits
+            .iter()
+            .enumerate()
+            .map(|(i, b)| (F::from(1u64 << i), *b))
+            .collect();
+        U32(self.linear_combination(&terms, F::zero()))
+    }
+
+    /// Decomposes `a` into 4 range-checked bytes, in the given order.
+    fn u32_to_bytes(&mut self, zero: Var<F>, a: U32<F>, endianness: Endianness) -> [Var<F>; 4] {
+        let bytes = self.byte_vars(zero, 4, || a.val().to_le_bytes().to_vec());
+
+        // a = sum_i 256^i b_i
+        let mut terms = vec![(-F::one(), a.0)];
+        for (i, b) in bytes.iter().enumerate() {
+            terms.push((F::from(1u64 << (8 * i)), *b));
+        }
+        self.assert_linear(&terms, F::zero());
+
+        endianness.to_le(&bytes).try_into().unwrap()
+    }
+
+    /// Packs 4 bytes (in the given order) into a word.
+    /// The bytes should be constrained (e.g. with [`Bytes::assert_bytes`]).
+    fn u32_from_bytes(&mut self, bytes: &[Var<F>; 4], endianness: Endianness) -> U32<F> {
+        U32(self.pack_bytes(bytes, endianness))
+    }
+
+    /// Constrains `a + b + xor_coeff * xor - 2 * res = 0` with two generic gates.
+    fn combine_with_xor(&mut self, a: U32<F>, b: U32<F>, xor: U32<F>, res: Var<F>, xor_coeff: F) {
+        let sum = self.var(|| a.0.val() + b.0.val());
+
+        // a + b - sum = 0
+        let mut coeffs = [F::zero(); GENERIC_COEFFS];
+        coeffs[0] = F::one();
+        coeffs[1] = F::one();
+        coeffs[2] = -F::one();
+        self.generic(coeffs, [Some(a.0), Some(b.0), Some(sum)]);
+
+        // sum + xor_coeff * xor - 2 * res = 0
+        let mut coeffs = [F::zero(); GENERIC_COEFFS];
+        coeffs[0] = F::one();
+        coeffs[1] = xor_coeff;
+        coeffs[2] = -F::from(2u64);
+        self.generic(coeffs, [Some(sum), Some(xor.0), Some(res)]);
+    }
+
+    /// Computes `(x ^ y) <<< rotation` with `x` and `y` being 32-bit words.
+    fn rotation(&mut self, rotation: Rotation, zero: Var<F>, x: Var<F>, y: Var<F>) -> Var<F> {
+        let (_, yprime, xor_nybbles) = self.chacha_line(rotation.line_gate(), x, y, zero);
+        match rotation {
+            Rotation::By16 | Rotation::By12 | Rotation::By8 => yprime,
+            Rotation::By7 => self.chacha_final(x, y, xor_nybbles),
+        }
+    }
+
+    /// Lays out a `ChaCha` line `x' = x + z (mod 2^32)`, `y' = (y ^ x') <<< k`
+    /// over two rows, where `k` depends on the gate type.
+    /// Returns `x'`, `y'` and the nybbles of `y ^ x'`.
+    ///
+    /// | row | 0  | 1  | 2        | 3..7             | 7..11            | 11..15      |
+    /// | --- | -- | -- | -------- | ---------------- | ---------------- | ----------- |
+    /// |  i  | x  | y  | z        | `(y^x')_{0..4}`  | `(x+z)_{0..4}`   | `y_{0..4}`  |
+    /// | i+1 | x' | y' | overflow | `(y^x')_{4..8}`  | `(x+z)_{4..8}`   | `y_{4..8}`  |
+    ///
+    fn chacha_line(
+        &mut self,
+        typ: GateType,
+        x: Var<F>,
+        y: Var<F>,
+        z: Var<F>,
+    ) -> (Var<F>, Var<F>, [Var<F>; 8]) {
+        let rotation = match typ {
+            GateType::ChaCha0 => 16,
+            GateType::ChaCha1 => 12,
+            GateType::ChaCha2 => 8,
+            _ => panic!("not a ChaCha line gate: {:?}", typ),
+        };
+
+        let sum = move || u64::from(word(x)) + u64::from(word(z));
+        let xprime = self.var(|| F::from(sum() as u32));
+        let overflow = self.var(|| F::from(sum() >> 32));
+        let xor = move || word(y) ^ word(xprime);
+        let yprime = self.var(|| F::from(xor().rotate_left(rotation)));
+
+        let xor_nybbles: [_; 8] = array::from_fn(|i| self.var(|| nybble(xor(), i)));
+        let xprime_nybbles: [_; 8] = array::from_fn(|i| self.var(|| nybble(word(xprime), i)));
+        let y_nybbles: [_; 8] = array::from_fn(|i| self.var(|| nybble(word(y), i)));
+
+        let row: [_; COLUMNS] = array::from_fn(|col| match col {
+            0 => x,
+            1 => y,
+            2 => z,
+            3..=6 => xor_nybbles[col - 3],
+            7..=10 => xprime_nybbles[col - 7],
+            _ => y_nybbles[col - 11],
+        });
+        let next_row: [_; COLUMNS] = array::from_fn(|col| match col {
+            0 => xprime,
+            1 => yprime,
+            2 => overflow,
+            3..=6 => xor_nybbles[col + 1],
+            7..=10 => xprime_nybbles[col - 3],
+            _ => y_nybbles[col - 7],
+        });
+
+        self.gate(GateSpec {
+            typ,
+            row: row.into_iter().map(Some).collect(),
+            coeffs: vec![],
+        });
+        self.gate(GateSpec {
+            typ: GateType::Zero,
+            row: next_row.into_iter().map(Some).collect(),
+            coeffs: vec![],
+        });
+
+        (xprime, yprime, xor_nybbles)
+    }
+
+    /// Lays out a `ChaChaFinal` gate rotating the XOR computed by the preceding
+    /// `ChaCha0` line (given by its nybbles) by 7, and returns the result.
+    ///
+    /// | row | 0   | 1..5            | 5..9                |
+    /// | --- | --- | --------------- | ------------------- |
+    /// |  i  | res | `(y^x')_{0..4}` | `lo((y^x')_{0..4})` |
+    /// | i+1 |     | `(y^x')_{4..8}` | `lo((y^x')_{4..8})` |
+    ///
+    fn chacha_final(&mut self, x: Var<F>, y: Var<F>, xor_nybbles: [Var<F>; 8]) -> Var<F> {
+        let xor = move || word(y) ^ word(x);
+        let res = self.var(|| F::from(xor().rotate_left(7)));
+        let low_bits: [_; 8] = array::from_fn(|i| self.var(|| F::from((xor() >> (4 * i)) & 1)));
+
+        for (typ, half) in [(GateType::ChaChaFinal, 0), (GateType::Zero, 4)] {
+            let row: Vec<_> = (0..9)
+                .map(|col| match col {
+                    0 if half == 0 => Some(res),
+                    0 => None,
+                    1..=4 => Some(xor_nybbles[half + col - 1]),
+                    _ => Some(low_bits[half + col - 5]),
+                })
+                .collect();
+            self.gate(GateSpec {
+                typ,
+                row,
+                coeffs: vec![],
+            });
+        }
+
+        res
+    }
+}
+
+impl<F: PrimeField, C: Cs<F>> Uint32<F> for C {}
@@ -0,0 +1,119 @@
+//! A verifiable random function over a curve and the Kimchi Poseidon sponge,
+//! built as Mina's VRF for the sortition of block producers.
+//!
+//! For a secret key `sk`, with public key `pk = [sk] G`, the VRF of a message `m`
+//! (e.g. a slot, an epoch seed and a delegator index) is computed as:
+//!
+//! * `H = group_map(H_VRF_MESSAGE_DOMAIN(m))`, hashing the message to the curve,
+//! * `O = [sk] H`,
+//! * `vrf(sk, m) = H_VRF_OUTPUT_DOMAIN(m || O.x || O.y)`.
+//!
+//! The output can only be computed with the secret key, and the circuit proves it is the output
+//! of the secret key of `pk`, without revealing `sk` nor `O`.
+//! As in Mina, comparing the output to a threshold (e.g. from the stake of the delegator)
+//! is left to the statement built on top of it.
+
+use super::hash_to_curve::HashToCurve;
+use super::prf::hash_native;
+use super::sponge::CircuitSponge;
+use crate::constants::Constants;
+use crate::writer::{Cs, ShiftedScalar, Var};
+use ark_ec::{
+    short_weierstrass_jacobian::GroupAffine, AffineCurve, ProjectiveCurve, SWModelParameters,
+};
+use ark_ff::{PrimeField, SquareRootField};
+use groupmap::{BWParameters, GroupMap};
+
+/// The domain string of the hash of VRF messages
+pub const VRF_MESSAGE_DOMAIN: &str = "KimchiVrfMessage";
+
+/// The domain string of the hash of VRF outputs
+pub const VRF_OUTPUT_DOMAIN: &str = "KimchiVrfOutput";
+
+/// The evaluation of the VRF, outside of the circuit
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VrfEvaluation<F> {
+    /// The point `O = [sk] H`
+    pub point: (F, F),
+    /// The output of the VRF
+    pub output: F,
+}
+
+/// Evaluates the VRF of `message` for the secret key `sk` on the curve `P`, outside of the circuit.
+pub fn vrf_native<P: SWModelParameters>(
+    constants: &Constants<P::BaseField>,
+    params: &BWParameters<P>,
+    sk: P::ScalarField,
+    message: &[P::BaseField],
+) -> VrfEvaluation<P::BaseField>
+where
+    P::BaseField: PrimeField,
+{
+    let t = hash_native(constants, VRF_MESSAGE_DOMAIN, message);
+    let (x, y) = params.to_group(t);
+    let point = GroupAffine::<P>::new(x, y, false).mul(sk).into_affine();
+
+    let mut inputs = message.to_vec();
+    inputs.extend([point.x, point.y]);
+    VrfEvaluation {
+        point: (point.x, point.y),
+        output: hash_native(constants, VRF_OUTPUT_DOMAIN, &inputs),
+    }
+}
+
+/// VRF gadgets.
+///
+/// The `zero` variable taken by its functions is described
+/// in [the module documentation](super#the-zero-variable).
+pub trait Vrf<F: PrimeField + SquareRootField>: HashToCurve<F> {
+    /// Creates the variable of a secret key, of the scalar field `Fr`.
+    fn vrf_secret_key<Fr: PrimeField>(&mut self, sk: Option<Fr>) -> ShiftedScalar<F> {
+        self.scalar(Fr::size_in_bits(), || sk.unwrap())
+    }
+
+    /// Returns the point `H` that `message` is hashed to (see the module documentation).
+    fn vrf_hash_to_group<P: SWModelParameters<BaseField = F>>(
+        &mut self,
+        constants: &Constants<F>,
+        params: &BWParameters<P>,
+        message: &[Var<F>],
+    ) -> (Var<F>, Var<F>) {
+        let mut sponge =
+            CircuitSponge::with_domain(self, constants, Some(VRF_MESSAGE_DOMAIN.to_string()));
+        sponge.absorb(self, constants, message);
+        let t = sponge.squeeze(self, constants);
+        self.to_group(params, t)
+    }
+
+    /// Returns the output of the VRF of `message`, for the secret key `sk`
+    /// of the public key `public_key` (on the curve of generator `constants.base`),
+    /// which is constrained to be `[sk] G`.
+    fn vrf_evaluate<P: SWModelParameters<BaseField = F>>(
+        &mut self,
+        zero: Var<F>,
+        constants: &Constants<F>,
+        params: &BWParameters<P>,
+        public_key: (Var<F>, Var<F>),
+        sk: ShiftedScalar<F>,
+        message: &[Var<F>],
+    ) -> Var<F> {
+        // pk = [sk] G
+        let (gx, gy) = constants.base;
+        let base = (self.constant(gx), self.constant(gy));
+        let sk_g = self.scalar_mul(zero, base, sk);
+        self.assert_eq(sk_g.0, public_key.0);
+        self.assert_eq(sk_g.1, public_key.1);
+
+        // O = [sk] H
+        let h = self.vrf_hash_to_group(constants, params, message);
+        let (ox, oy) = self.scalar_mul(zero, h, sk);
+
+        let mut sponge =
+            CircuitSponge::with_domain(self, constants, Some(VRF_OUTPUT_DOMAIN.to_string()));
+        sponge.absorb(self, constants, message);
+        sponge.absorb(self, constants, &[ox, oy]);
+        sponge.squeeze(self, constants)
+    }
+}
+
+impl<F: PrimeField + SquareRootField, C: Cs<F>> Vrf<F> for C {}
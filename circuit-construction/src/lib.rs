@@ -2,6 +2,8 @@
 
 /// Definition of possible constants in circuits
 pub mod constants;
+/// Gadgets (hash functions, word arithmetic, etc.) built on top of the writer
+pub mod gadgets;
 /// This contains the prover functions, ranging from curves definitions to prover index and proof generation
 pub mod prover;
 /// This is the actual writer with all of the available functions to set up a circuit and its corresponding constraint system
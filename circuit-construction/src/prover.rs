@@ -49,7 +49,8 @@ where
 
     // get the witness columns
     gen.curr_gate_count();
-    let columns = gen.columns();
+    let mut columns = gen.columns();
+    index.cs.fill_constants(&mut columns);
 
     // custom blinders for the witness commitment
     let blinders: [Option<PolyComm<G::ScalarField>>; COLUMNS] = match blinders {
@@ -114,8 +115,15 @@ where
     let (endo_q, _endo_r) = endos::<Curve::OtherCurve>();
     //let (endo_q, _endo_r) = Curve::endos();
 
-    let constraint_system = ConstraintSystem::<Curve::ScalarField>::create(gates)
+    let constraint_system = system
+        .constant_cells()
+        .into_iter()
+        .fold(
+            ConstraintSystem::<Curve::ScalarField>::create(gates),
+            |builder, (x, cell)| builder.constant(x, cell),
+        )
         .public(public)
+        .lookup(system.lookup_tables)
         .build()
         // TODO: return a Result instead of panicking
         .expect("couldn't construct constraint system");
@@ -0,0 +1,58 @@
+use crate::gadgets::arith::Arith;
+use crate::prologue::*;
+use crate::writer::System;
+use kimchi::circuits::gate::GateType;
+
+const LEN: usize = 5;
+
+/// Checks the inner product of two vectors of length `LEN`,
+/// given as public inputs followed by the expected result.
+fn circuit<F: PrimeField, Sys: Cs<F>>(sys: &mut Sys, public_input: Vec<Var<F>>) {
+    let (a, b) = public_input[..2 * LEN].split_at(LEN);
+    let res = sys.inner_product(a, b);
+    sys.assert_eq(res, public_input[2 * LEN]);
+}
+
+/// Checks the squares of `0, 1, ..., LEN - 1`, given as public inputs `x_i, x_i^2`.
+/// Each square takes three generic gates,
+/// the last of which (`x_i == i`) only uses its left register.
+fn squares_circuit<F: PrimeField, Sys: Cs<F>>(sys: &mut Sys, public_input: Vec<Var<F>>) {
+    for (i, x) in public_input.chunks(2).enumerate() {
+        let square = sys.mul(x[0], x[0]);
+        sys.assert_eq(square, x[1]);
+        sys.assert_linear(&[(F::one(), x[0])], -F::from(i as u64));
+    }
+}
+
+#[test]
+fn test_inner_product_circuit() {
+    let mut rng = rand::thread_rng();
+    let a: Vec<Fp> = (0..LEN).map(|_| Fp::rand(&mut rng)).collect();
+    let b: Vec<Fp> = (0..LEN).map(|_| Fp::rand(&mut rng)).collect();
+    let res = a.iter().zip(&b).map(|(a, b)| *a * b).sum();
+
+    let mut public_input = a;
+    public_input.extend(b);
+    public_input.push(res);
+
+    super::prove_and_verify(circuit, circuit, public_input, 1 << 5);
+}
+
+#[test]
+fn test_triple_generic_rows() {
+    let mut sys = System::<Fp>::default();
+    let public_input = (0..2 * LEN).map(|_| sys.var(|| unreachable!())).collect();
+    squares_circuit(&mut sys, public_input);
+    let gates = sys.gates();
+
+    // the 3 * LEN generic gates would take 3 * LEN / 2 rows as double generic gates,
+    // but each square fills a single triple generic gate
+    assert_eq!(gates.len(), LEN);
+    assert!(2 * gates.len() < 3 * LEN);
+    assert!(gates.iter().all(|gate| gate.typ == GateType::TripleGeneric));
+
+    let public_input = (0..LEN as u64)
+        .flat_map(|i| [Fp::from(i), Fp::from(i * i)])
+        .collect();
+    super::prove_and_verify(squares_circuit, squares_circuit, public_input, 1 << 5);
+}
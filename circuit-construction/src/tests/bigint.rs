@@ -0,0 +1,83 @@
+use crate::gadgets::bigint::{BigInt, BigModulus};
+use crate::prologue::*;
+use crate::writer::GateSpec;
+use kimchi::circuits::gate::GateType;
+use num_bigint::BigUint;
+use o1_utils::foreign_field::P256_MOD;
+
+fn big(hex: &str) -> BigUint {
+    BigUint::parse_bytes(hex.as_bytes(), 16).unwrap()
+}
+
+/// The modulus of P-521, `2^521 - 1`, whose elements have six limbs
+fn p521() -> BigModulus {
+    BigModulus::new((BigUint::from(1u32) << 521) - 1u32)
+}
+
+/// A 436-bit integer (five limbs)
+fn a_val() -> BigUint {
+    big("DEADBEEF0123456789ABCDEF0123456789ABCDEFDEADBEEF0123456789ABCDEF0123456789ABCDEF0123456789ABCDEF0123456789ABC")
+}
+
+/// A 520-bit integer (six limbs)
+fn b_val() -> BigUint {
+    big("FEDCBA9876543210FEDCBA9876543210FEDCBA9876543210FEDCBA9876543210FEDCBA9876543210FEDCBA9876543210FEDCBA9876543210FEDCBA9876543210FE")
+}
+
+#[test]
+fn test_bigint_modulus() {
+    assert_eq!(p521().limbs(), 6);
+    assert_eq!(BigModulus::from_be(P256_MOD).limbs(), 3);
+    assert_eq!(a_val().bits(), 436);
+    assert_eq!(b_val().bits(), 520);
+}
+
+/// Checks the operations on fixed inputs
+fn circuit<F: PrimeField, Sys: Cs<F>>(sys: &mut Sys, _public_input: Vec<Var<F>>) {
+    let zero = sys.constant(F::zero());
+    let m = p521();
+    let (a_val, b_val) = (a_val(), b_val());
+    let a = sys.bigint_var(zero, 5, || a_val.clone());
+    let b = sys.bigint_var(zero, 6, || b_val.clone());
+
+    let sum = sys.bigint_add(zero, &a, &b);
+    assert_eq!(sum.limbs.len(), 7);
+    let expected = sys.bigint_constant(&(&a_val + &b_val), 7);
+    sys.bigint_assert_eq(zero, &sum, &expected);
+
+    let product = sys.bigint_mul(zero, &a, &b);
+    assert_eq!(product.limbs.len(), 11);
+    let expected = sys.bigint_constant(&(&a_val * &b_val), 11);
+    sys.bigint_assert_eq(zero, &product, &expected);
+
+    // a * b mod m, computed directly and by reducing the product
+    let r = sys.bigint_mul_mod(zero, &m, &a, &b);
+    assert_eq!(r.limbs.len(), 6);
+    let expected = sys.bigint_constant(&(&a_val * &b_val % m.modulus()), 6);
+    sys.bigint_assert_eq(zero, &r, &expected);
+    let reduced = sys.bigint_reduce(zero, &m, &product);
+    sys.bigint_assert_eq(zero, &reduced, &r);
+
+    let one = sys.constant(F::one());
+    let lt = sys.bigint_less_than(zero, &a, &b);
+    sys.assert_eq(lt, one);
+    let lt = sys.bigint_less_than(zero, &b, &a);
+    sys.assert_eq(lt, zero);
+    let lt = sys.bigint_less_than(zero, &a, &a);
+    sys.assert_eq(lt, zero);
+    sys.bigint_assert_lt(zero, &a, &b);
+
+    // Temporary workaround for lookup-table/domain-size issue
+    while sys.curr_gate_count() < 1 << 12 {
+        sys.gate(GateSpec {
+            typ: GateType::Zero,
+            row: vec![],
+            coeffs: vec![],
+        });
+    }
+}
+
+#[test]
+fn test_bigint_circuit() {
+    super::prove_and_verify(circuit, circuit, vec![], 1 << 13);
+}
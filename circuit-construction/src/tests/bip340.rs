@@ -0,0 +1,203 @@
+use crate::gadgets::{
+    bip340::{bip340_sign, bip340_verify_native, lift_x, Bip340},
+    foreign_curve::{ForeignCurve, ForeignCurveGadgets},
+    foreign_field::ForeignField,
+    sha256::{sha256_native, to_words, Sha256, DIGEST_WORDS},
+    uint32::{Uint32, U32},
+};
+use crate::prologue::*;
+use crate::writer::GateSpec;
+use kimchi::circuits::gate::GateType;
+use num_bigint::BigUint;
+
+fn hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+/// A test vector of BIP-340
+struct TestVector {
+    sk: &'static str,
+    public_key: &'static str,
+    aux: &'static str,
+    message: &'static str,
+    signature: &'static str,
+}
+
+const VECTORS: [TestVector; 2] = [
+    TestVector {
+        sk: "0000000000000000000000000000000000000000000000000000000000000003",
+        public_key: "F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9",
+        aux: "0000000000000000000000000000000000000000000000000000000000000000",
+        message: "0000000000000000000000000000000000000000000000000000000000000000",
+        signature: "E907831F80848D1069A5371B402410364BDF1C5F8307B0084C55F1CE2DCA8215\
+                    25F66A4A85EA8B71E482A74F382D2CE5EBEEE8FDB2172F477DF4900D310536C0",
+    },
+    TestVector {
+        sk: "B7E151628AED2A6ABF7158809CF4F3C762E7160F38B4DA56A784D9045190CFEF",
+        public_key: "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+        aux: "0000000000000000000000000000000000000000000000000000000000000001",
+        message: "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+        signature: "6896BD60EEAE296DB48A229FF71DFE071BDE413E6D43F917DC8DCF8C78DE3341\
+                    8906D11AC976ABCCB20B091292BFF4EA897EFCB639EA871CFA95F6DE339E4B0A",
+    },
+];
+
+/// Creates word variables holding the big-endian words of `bytes`
+fn words_var<F: PrimeField, Sys: Cs<F>>(sys: &mut Sys, zero: Var<F>, bytes: &[u8]) -> Vec<U32<F>> {
+    to_words(bytes)
+        .into_iter()
+        .map(|w| sys.u32_var(zero, || w))
+        .collect()
+}
+
+#[test]
+fn test_sha256_native() {
+    let vectors: [(&[u8], &str); 3] = [
+        (
+            b"",
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        ),
+        (
+            b"abc",
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+        ),
+        (
+            b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+        ),
+    ];
+    for (message, digest) in vectors {
+        assert_eq!(sha256_native(message).to_vec(), hex(digest));
+    }
+}
+
+/// Prove knowledge of a 32-byte preimage of a SHA-256 digest
+fn sha256_circuit<F: PrimeField, Sys: Cs<F>>(
+    preimage: Option<&[u8; 32]>,
+    sys: &mut Sys,
+    public_input: Vec<Var<F>>,
+) {
+    let zero = sys.constant(F::zero());
+
+    let message: Vec<_> = (0..8)
+        .map(|i| {
+            sys.u32_var(zero, || {
+                let w = &preimage.unwrap()[4 * i..4 * i + 4];
+                u32::from_be_bytes([w[0], w[1], w[2], w[3]])
+            })
+        })
+        .collect();
+    let digest = sys.sha256(zero, &message);
+
+    for (word, expected) in digest.iter().zip(public_input) {
+        sys.assert_eq(word.var(), expected);
+    }
+}
+
+#[test]
+fn test_sha256_circuit() {
+    let preimage: [u8; 32] = std::array::from_fn(|i| i as u8);
+    let public_input: Vec<Fp> = to_words(&sha256_native(&preimage))
+        .into_iter()
+        .map(Fp::from)
+        .collect();
+
+    super::prove_and_verify(
+        |sys, p| sha256_circuit::<_, _>(None, sys, p),
+        |sys, p| sha256_circuit::<Fp, _>(Some(&preimage), sys, p),
+        public_input,
+        1 << 14,
+    );
+}
+
+#[test]
+fn test_secp256k1_parameters() {
+    let curve = ForeignCurve::secp256k1();
+    assert!(curve.is_on_curve(&curve.generator));
+    assert_eq!(curve.scale(&curve.generator, curve.scalar.modulus()), None);
+    assert_eq!(curve.scalar_bits(), 256);
+}
+
+#[test]
+fn test_bip340_native() {
+    let curve = ForeignCurve::secp256k1();
+
+    for v in VECTORS {
+        let sk = BigUint::from_bytes_be(&hex(v.sk));
+        let public_key: [u8; 32] = hex(v.public_key).try_into().unwrap();
+        let aux: [u8; 32] = hex(v.aux).try_into().unwrap();
+        let message = hex(v.message);
+
+        let signature = bip340_sign(&curve, &sk, &message, &aux).unwrap();
+        assert_eq!(signature.to_vec(), hex(v.signature));
+        assert!(bip340_verify_native(
+            &curve,
+            &public_key,
+            &message,
+            &signature
+        ));
+
+        let mut wrong = signature;
+        wrong[63] ^= 1;
+        assert!(!bip340_verify_native(&curve, &public_key, &message, &wrong));
+    }
+
+    // public key not on the curve
+    let public_key: [u8; 32] =
+        hex("EEFDEA4CDB677750A420FEE807EACF21EB9898AE79B9768766E4FAA04A2D4A34")
+            .try_into()
+            .unwrap();
+    assert_eq!(lift_x(&curve, &BigUint::from_bytes_be(&public_key)), None);
+}
+
+/// Lifts the public key of the second test vector from its words
+fn lift_circuit<F: PrimeField, Sys: Cs<F>>(sys: &mut Sys, _public_input: Vec<Var<F>>) {
+    let zero = sys.constant(F::zero());
+    let curve = ForeignCurve::secp256k1();
+    let public_key = hex(VECTORS[1].public_key);
+
+    let words = words_var(sys, zero, &public_key);
+    let px = sys.foreign_from_words(zero, &words);
+    let expected = sys.foreign_constant(&curve.base, &BigUint::from_bytes_be(&public_key));
+    sys.foreign_assert_eq(px, expected);
+
+    let p = sys.point_var(zero, &curve, || lift_x(&curve, &px.val()).unwrap());
+    sys.foreign_assert_eq(p.x, px);
+    sys.foreign_assert_even(zero, p.y);
+
+    // Temporary workaround for lookup-table/domain-size issue
+    while sys.curr_gate_count() < 1 << 12 {
+        sys.gate(GateSpec {
+            typ: GateType::Zero,
+            row: vec![],
+            coeffs: vec![],
+        });
+    }
+}
+
+/// Verifies the signature of the second test vector
+fn bip340_circuit<F: PrimeField, Sys: Cs<F>>(sys: &mut Sys, _public_input: Vec<Var<F>>) {
+    let zero = sys.constant(F::zero());
+    let curve = ForeignCurve::secp256k1();
+    let v = &VECTORS[1];
+
+    let public_key = words_var(sys, zero, &hex(v.public_key));
+    let message = words_var(sys, zero, &hex(v.message));
+    let signature = words_var(sys, zero, &hex(v.signature));
+    sys.bip340_verify(zero, &curve, &public_key, &message, &signature);
+}
+
+#[test]
+fn test_bip340_lift_circuit() {
+    super::prove_and_verify(lift_circuit, lift_circuit, vec![], 1 << 13);
+}
+
+// The circuit has about 2^18 rows, run with `cargo test --release -- --ignored`
+#[test]
+#[ignore]
+fn test_bip340_circuit() {
+    super::prove_and_verify(bip340_circuit, bip340_circuit, vec![], 1 << 18);
+}
@@ -0,0 +1,33 @@
+use crate::gadgets::{bitwise::Bitwise, bytes::Bytes};
+use crate::prologue::*;
+use crate::writer::GateSpec;
+use kimchi::circuits::gate::GateType;
+
+const INPUT: &[u8] = b"bitwise";
+
+/// Negate the bytes of the input (a private input) twice,
+/// the public input being the negation of its first byte
+fn circuit<F: PrimeField, Sys: Cs<F>>(sys: &mut Sys, public_input: Vec<Var<F>>) {
+    let zero = sys.constant(F::zero());
+    let input = sys.byte_vars(zero, INPUT.len(), || INPUT.to_vec());
+
+    let negated = sys.not8(&input);
+    sys.assert_eq(negated[0], public_input[0]);
+    for (x, y) in input.iter().zip(sys.not8(&negated)) {
+        sys.assert_eq(*x, y);
+    }
+
+    // Temporary workaround for lookup-table/domain-size issue
+    while sys.curr_gate_count() < 1 << 12 {
+        sys.gate(GateSpec {
+            typ: GateType::Zero,
+            row: vec![],
+            coeffs: vec![],
+        });
+    }
+}
+
+#[test]
+fn test_not8_circuit() {
+    super::prove_and_verify(circuit, circuit, vec![Fp::from(!INPUT[0])], 1 << 13);
+}
@@ -0,0 +1,91 @@
+use crate::gadgets::{
+    arith::Arith,
+    bytes::{field_from_bytes, field_to_bytes, packed_bytes, Bytes, Endianness},
+    uint32::Uint32,
+};
+use crate::prologue::*;
+use crate::writer::GateSpec;
+use kimchi::circuits::gate::GateType;
+use o1_utils::field_helpers::FieldHelpers;
+
+const WORD: u32 = 0x12345678;
+
+#[test]
+fn test_bytes_native() {
+    assert_eq!(packed_bytes::<Fp>(), 31);
+    assert_eq!(Fp::size_in_bytes(), 32);
+
+    let x = Fp::from(0x0102u64);
+    assert_eq!(field_to_bytes(x, 3, Endianness::Little), vec![2, 1, 0]);
+    assert_eq!(field_to_bytes(x, 3, Endianness::Big), vec![0, 1, 2]);
+
+    let x = Fp::rand(&mut rand::thread_rng());
+    for endianness in [Endianness::Little, Endianness::Big] {
+        let bytes = field_to_bytes(x, 32, endianness);
+        assert_eq!(field_from_bytes::<Fp>(&bytes, endianness), x);
+    }
+}
+
+/// Decompose the first public input into bytes, and pack them back,
+/// the second public input being the packing of its 31 low bytes
+fn circuit<F: PrimeField, Sys: Cs<F>>(sys: &mut Sys, public_input: Vec<Var<F>>) {
+    let zero = sys.constant(F::zero());
+    let (x, low) = (public_input[0], public_input[1]);
+
+    // canonical decompositions
+    let le = sys.unpack_bytes(zero, x, 32, Endianness::Little);
+    let be = sys.unpack_bytes(zero, x, 32, Endianness::Big);
+    for (a, b) in le.iter().zip(be.iter().rev()) {
+        sys.assert_eq(*a, *b);
+    }
+
+    // x = low + 2^248 high
+    let packed = sys.pack_bytes_chunked(&le, Endianness::Little);
+    assert_eq!(packed.len(), 2);
+    sys.assert_eq(packed[0], low);
+    sys.assert_linear(
+        &[
+            (F::one(), low),
+            (F::from(2u64).pow([248]), packed[1]),
+            (-F::one(), x),
+        ],
+        F::zero(),
+    );
+
+    // shorter decompositions
+    let bytes = sys.unpack_bytes(zero, low, 31, Endianness::Big);
+    let repacked = sys.pack_bytes(&bytes, Endianness::Big);
+    sys.assert_eq(repacked, low);
+
+    // words
+    let w = sys.u32_var(zero, || WORD);
+    let bytes = sys.u32_to_bytes(zero, w, Endianness::Big);
+    for (b, expected) in bytes.iter().zip(WORD.to_be_bytes()) {
+        let expected = sys.constant(F::from(expected));
+        sys.assert_eq(*b, expected);
+    }
+    let mut reversed = bytes;
+    reversed.reverse();
+    let repacked = sys.u32_from_bytes(&reversed, Endianness::Little);
+    sys.assert_eq(repacked.var(), w.var());
+
+    // Temporary workaround for lookup-table/domain-size issue
+    while sys.curr_gate_count() < 1 << 12 {
+        sys.gate(GateSpec {
+            typ: GateType::Zero,
+            row: vec![],
+            coeffs: vec![],
+        });
+    }
+}
+
+#[test]
+fn test_bytes_circuit() {
+    let x = Fp::rand(&mut rand::thread_rng());
+    let low = field_from_bytes(
+        &field_to_bytes(x, 32, Endianness::Little)[..31],
+        Endianness::Little,
+    );
+
+    super::prove_and_verify(circuit, circuit, vec![x, low], 1 << 13);
+}
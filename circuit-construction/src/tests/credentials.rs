@@ -0,0 +1,92 @@
+use crate::gadgets::credentials::{Credential, CredentialCommitment, Credentials};
+use crate::gadgets::schnorr::SchnorrWitness;
+use crate::prologue::*;
+use crate::writer::GateSpec;
+use ark_ff::One;
+use kimchi::circuits::gate::GateType;
+use mina_signer::{Keypair, Signer};
+
+// birth year, country code, document number
+const ATTRIBUTES: usize = 3;
+const BIRTH_YEAR: usize = 0;
+const COUNTRY: usize = 1;
+
+// Present a credential signed by an issuer (given as public input),
+// disclosing its country and showing that its holder was born in the 20th century
+fn circuit<F: PrimeField, G: AffineCurve<BaseField = F>, Sys: Cs<F>>(
+    constants: &Constants<F>,
+    witness: Option<(&Credential<F>, &SchnorrWitness<G>)>,
+    sys: &mut Sys,
+    public_input: Vec<Var<F>>,
+) {
+    let zero = sys.constant(F::zero());
+    let issuer = (public_input[0], public_input[1]);
+    let credential = sys.credential_witness(witness.map(|(c, _)| c), ATTRIBUTES);
+    let signature = sys.credential_signature(witness.map(|(_, s)| s));
+    sys.credential_verify(zero, constants, issuer, &credential, signature);
+    sys.credential_disclose(&credential, &[(COUNTRY, public_input[2])]);
+    sys.credential_range(zero, &credential, BIRTH_YEAR, (1900, 1999));
+
+    // Temporary workaround for lookup-table/domain-size issue
+    while sys.curr_gate_count() < 1 << 12 {
+        sys.gate(GateSpec {
+            typ: GateType::Zero,
+            row: vec![],
+            coeffs: vec![],
+        });
+    }
+}
+
+#[test]
+fn test_credential_commitment() {
+    let mut rng = rand::thread_rng();
+    let constants = fp_constants();
+    let credential = Credential {
+        attributes: (0..ATTRIBUTES).map(|_| Fp::rand(&mut rng)).collect(),
+        blinding: Fp::rand(&mut rng),
+    };
+    let commitment = credential.commitment(&constants);
+
+    let mut blinded = credential.clone();
+    blinded.blinding = Fp::rand(&mut rng);
+    assert_ne!(blinded.commitment(&constants), commitment);
+
+    let mut changed = credential;
+    changed.attributes[BIRTH_YEAR] += Fp::one();
+    assert_ne!(changed.commitment(&constants), commitment);
+}
+
+#[test]
+fn test_credentials_circuit() {
+    let proof_system_constants = fp_constants();
+
+    // issue a credential
+    let mut rng = rand::thread_rng();
+    let issuer = Keypair::rand(&mut rng);
+    let credential = Credential {
+        attributes: vec![Fp::from(1984u64), Fp::from(250u64), Fp::rand(&mut rng)],
+        blinding: Fp::rand(&mut rng),
+    };
+    let commitment = CredentialCommitment(credential.commitment(&proof_system_constants));
+    let mut signer = mina_signer::create_kimchi::<CredentialCommitment>(());
+    let signature = signer.sign(&issuer, &commitment);
+    assert!(signer.verify(&signature, &issuer.public, &commitment));
+
+    // create witness and public input
+    let witness = SchnorrWitness::new(&signature, &commitment).unwrap();
+    let public_key = issuer.public.point();
+
+    super::prove_and_verify(
+        |sys, p| circuit::<_, PallasAffine, _>(&proof_system_constants, None, sys, p),
+        |sys, p| {
+            circuit::<Fp, PallasAffine, _>(
+                &proof_system_constants,
+                Some((&credential, &witness)),
+                sys,
+                p,
+            )
+        },
+        vec![public_key.x, public_key.y, credential.attributes[COUNTRY]],
+        1 << 13,
+    );
+}
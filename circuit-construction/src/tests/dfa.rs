@@ -0,0 +1,72 @@
+use crate::gadgets::{
+    bytes::Bytes,
+    dfa::{Dfa, DfaGadgets},
+};
+use crate::prologue::*;
+use crate::writer::GateSpec;
+use kimchi::circuits::gate::GateType;
+
+const HEADER: &[u8] = b"From: alice@example.com\r\nTo: bob@example.com\r\n";
+
+/// The automaton of `[a-z]+@[a-z]+\.com`, states 0 (start), 1 (local part),
+/// 2 (after @), 3 (domain), 4 (after .), 5 (c), 6 (co) and 7 (com, accepting)
+fn email_dfa(table_id: i64) -> Dfa {
+    let mut dfa = Dfa::new(table_id, 0, vec![7]);
+    for b in b'a'..=b'z' {
+        dfa.add_transition(0, b, 1);
+        dfa.add_transition(1, b, 1);
+        dfa.add_transition(2, b, 3);
+        dfa.add_transition(3, b, 3);
+    }
+    dfa.add_transition(1, b'@', 2);
+    dfa.add_transition(3, b'.', 4);
+    dfa.add_transition(4, b'c', 5);
+    dfa.add_transition(5, b'o', 6);
+    dfa.add_transition(6, b'm', 7);
+    dfa
+}
+
+#[test]
+fn test_dfa_native() {
+    let dfa = email_dfa(2);
+    assert!(dfa.accepts(b"alice@example.com"));
+    assert!(!dfa.accepts(b"alice@example.org"));
+    assert!(!dfa.accepts(b"alice@example.co"));
+    assert_eq!(dfa.run(b"alice@example.co"), Some(6));
+    assert_eq!(dfa.run(b"Alice"), None);
+
+    let dfa = Dfa::substring(3, b"To: bob@");
+    assert!(dfa.accepts(HEADER));
+    assert!(dfa.accepts(b"To: To: bob@"));
+    assert!(!dfa.accepts(b"To: alice@example.com"));
+    assert_eq!(dfa.run(b"xxTo: b"), Some(5));
+}
+
+/// Match the header (a private input) against a substring automaton,
+/// and an email address (its bytes 6 to 22) against the email automaton
+fn circuit<F: PrimeField, Sys: Cs<F>>(sys: &mut Sys, public_input: Vec<Var<F>>) {
+    let zero = sys.constant(F::zero());
+    let header = sys.byte_vars(zero, HEADER.len(), || HEADER.to_vec());
+
+    sys.dfa_assert_accepts(&Dfa::substring(2, b"To: bob@"), &header);
+
+    let dfa = email_dfa(3);
+    sys.dfa_assert_accepts(&dfa, &header[6..23]);
+    // the same table can be used several times
+    let state = sys.dfa_run(&dfa, &header[6..22]);
+    sys.assert_eq(state, public_input[0]);
+
+    // Temporary workaround for lookup-table/domain-size issue
+    while sys.curr_gate_count() < 1 << 12 {
+        sys.gate(GateSpec {
+            typ: GateType::Zero,
+            row: vec![],
+            coeffs: vec![],
+        });
+    }
+}
+
+#[test]
+fn test_dfa_circuit() {
+    super::prove_and_verify(circuit, circuit, vec![Fp::from(6u64)], 1 << 13);
+}
@@ -0,0 +1,136 @@
+use crate::gadgets::{
+    ecdsa::{ecdsa_sign, ecdsa_verify_native, hash_to_scalar, Ecdsa, EcdsaSignature},
+    foreign_curve::{ForeignCurve, ForeignCurveGadgets, ForeignPoint},
+    foreign_field::{inverse_mod, ForeignField},
+};
+use crate::prologue::*;
+use crate::writer::GateSpec;
+use kimchi::circuits::gate::GateType;
+use num_bigint::BigUint;
+
+fn big(hex: &str) -> BigUint {
+    BigUint::parse_bytes(hex.as_bytes(), 16).unwrap()
+}
+
+/// The test vector of RFC 6979 (A.2.5), with SHA-256 and the message "sample"
+struct TestVector {
+    sk: BigUint,
+    public_key: ForeignPoint,
+    hash: BigUint,
+    k: BigUint,
+    signature: EcdsaSignature,
+}
+
+fn rfc6979_vector() -> TestVector {
+    TestVector {
+        sk: big("C9AFA9D845BA75166B5C215767B1D6934E50C3DB36E89B127B8A622B120F6721"),
+        public_key: ForeignPoint {
+            x: big("60FED4BA255A9D31C961EB74C6356D68C049B8923B61FA6CE669622E60F29FB6"),
+            y: big("7903FE1008B8BC99A41AE9E95628BC64F2F1B20C2D7E9F5177A3C294D4462299"),
+        },
+        hash: big("AF2BDBE1AA9B6EC1E2ADE1D694F41FC71A831D0268E9891562113D8A62ADD1BF"),
+        k: big("A6E3C57DD01ABE90086538398355DD4C3B17AA873382B0F24D6129493D8AAD60"),
+        signature: EcdsaSignature {
+            r: big("EFD48B2AACB6A8FD1140DD9CD45E81D69D2C877B56AAF991C34D0EA84EAF3716"),
+            s: big("F7CB1C942D657C41D436C7A1B6E29F65F3E900DBB9AFF4064DC4AB2F843ACDA8"),
+        },
+    }
+}
+
+#[test]
+fn test_p256_parameters() {
+    let curve = ForeignCurve::p256();
+    assert!(curve.is_on_curve(&curve.generator));
+    assert_eq!(curve.scale(&curve.generator, curve.scalar.modulus()), None);
+    assert_eq!(curve.scalar_bits(), 256);
+}
+
+#[test]
+fn test_p256_ecdsa_native() {
+    let curve = ForeignCurve::p256();
+    let v = rfc6979_vector();
+
+    assert_eq!(
+        curve.scale(&curve.generator, &v.sk),
+        Some(v.public_key.clone())
+    );
+
+    let z = hash_to_scalar(&curve, &v.hash.to_bytes_be());
+    let signature = ecdsa_sign(&curve, &v.sk, &v.k, &z).unwrap();
+    assert_eq!(signature, v.signature);
+    assert!(ecdsa_verify_native(&curve, &v.public_key, &z, &signature));
+
+    let wrong = z + 1u32;
+    assert!(!ecdsa_verify_native(
+        &curve,
+        &v.public_key,
+        &wrong,
+        &signature
+    ));
+}
+
+/// Checks a few foreign field operations on fixed inputs
+fn foreign_field_circuit<F: PrimeField, Sys: Cs<F>>(sys: &mut Sys, _public_input: Vec<Var<F>>) {
+    let zero = sys.constant(F::zero());
+    let m = ForeignCurve::p256().base;
+    let p = m.modulus().clone();
+
+    let a_val = p.clone() - 5u32;
+    let b_val = big("DEADBEEF0123456789ABCDEF0123456789ABCDEFDEADBEEF0123456789ABCDEF");
+    let a = sys.foreign_var(zero, &m, || a_val.clone());
+    let b = sys.foreign_var(zero, &m, || b_val.clone());
+
+    // (a b + a - b) / b
+    let ab = sys.foreign_mul(zero, &m, a, b);
+    let sum = sys.foreign_add(zero, &m, ab, a);
+    let diff = sys.foreign_sub(zero, &m, sum, b);
+    let res = sys.foreign_div(zero, &m, diff, b);
+
+    let expected = (&a_val * &b_val + &a_val + &p - &b_val) % &p * inverse_mod(&b_val, &p) % &p;
+    let expected = sys.foreign_constant(&m, &expected);
+    sys.foreign_assert_eq(res, expected);
+
+    // b = sum_i 2^i b_i
+    let bits = sys.foreign_to_bits(zero, b, 256);
+    assert_eq!(bits.len(), 256);
+
+    // Temporary workaround for lookup-table/domain-size issue
+    while sys.curr_gate_count() < 1 << 12 {
+        sys.gate(GateSpec {
+            typ: GateType::Zero,
+            row: vec![],
+            coeffs: vec![],
+        });
+    }
+}
+
+/// Verifies the RFC 6979 signature
+fn ecdsa_circuit<F: PrimeField, Sys: Cs<F>>(sys: &mut Sys, _public_input: Vec<Var<F>>) {
+    let zero = sys.constant(F::zero());
+    let curve = ForeignCurve::p256();
+    let n = &curve.scalar;
+    let v = rfc6979_vector();
+
+    let public_key = sys.point_var(zero, &curve, || v.public_key.clone());
+    let z = sys.foreign_var(zero, n, || hash_to_scalar(&curve, &v.hash.to_bytes_be()));
+    let r = sys.foreign_var(zero, n, || v.signature.r.clone());
+    let s = sys.foreign_var(zero, n, || v.signature.s.clone());
+    sys.ecdsa_verify(zero, &curve, public_key, z, r, s);
+}
+
+#[test]
+fn test_foreign_field_circuit() {
+    super::prove_and_verify(
+        foreign_field_circuit,
+        foreign_field_circuit,
+        vec![],
+        1 << 13,
+    );
+}
+
+// The circuit has about 2^18 rows, run with `cargo test --release -- --ignored`
+#[test]
+#[ignore]
+fn test_p256_ecdsa_circuit() {
+    super::prove_and_verify(ecdsa_circuit, ecdsa_circuit, vec![], 1 << 18);
+}
@@ -1,9 +1,6 @@
 use crate::prologue::*;
 use kimchi::curve::KimchiCurve;
 
-type SpongeQ = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
-type SpongeR = DefaultFrSponge<Fp, PlonkSpongeConstantsKimchi>;
-
 pub struct Witness<G: AffineCurve> {
     pub s: G::ScalarField,
     pub preimage: G::BaseField,
@@ -43,28 +40,12 @@ pub fn circuit<
     sys.assert_eq(actual_hash, public_input[2]);
 }
 
-const PUBLIC_INPUT_LENGTH: usize = 3;
-
 #[test]
 fn test_example_circuit() {
     use mina_curves::pasta::Pallas;
     use mina_curves::pasta::Vesta;
-    // create SRS
-    let srs = {
-        let mut srs = SRS::<Vesta>::create(1 << 7); // 2^7 = 128
-        srs.add_lagrange_basis(Radix2EvaluationDomain::new(srs.g.len()).unwrap());
-        Arc::new(srs)
-    };
-
     let proof_system_constants = fp_constants();
 
-    // generate circuit and index
-    let prover_index = generate_prover_index::<_, _>(srs, PUBLIC_INPUT_LENGTH, |sys, p| {
-        circuit::<_, Pallas, _>(&proof_system_constants, None, sys, p)
-    });
-
-    let group_map = <Vesta as CommitmentCurve>::Map::setup();
-
     let mut rng = rand::thread_rng();
 
     // create witness
@@ -87,17 +68,11 @@ fn test_example_circuit() {
         s.squeeze()
     };
 
-    // generate proof
-    let proof = prove::<Vesta, _, SpongeQ, SpongeR>(
-        &prover_index,
-        &group_map,
-        None,
-        vec![public_key.x, public_key.y, hash],
+    // generate circuit and index, then prove and verify with an SRS of 2^7 = 128
+    super::prove_and_verify(
+        |sys, p| circuit::<_, Pallas, _>(&proof_system_constants, None, sys, p),
         |sys, p| circuit::<Fp, Pallas, _>(&proof_system_constants, Some(&witness), sys, p),
+        vec![public_key.x, public_key.y, hash],
+        1 << 7,
     );
-
-    // verify proof
-    let verifier_index = prover_index.verifier_index();
-
-    verify::<_, SpongeQ, SpongeR>(&group_map, &verifier_index, &proof).unwrap();
 }
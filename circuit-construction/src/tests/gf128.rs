@@ -0,0 +1,77 @@
+use crate::gadgets::{
+    gf128::{gf128_mul_native, ghash_native, Gf128},
+    uint32::Uint32,
+};
+use crate::prologue::*;
+
+/// The hash key of the test case 2 of the GCM specification (`H = AES_0(0)`)
+const H: u128 = 0x66e94bd4ef8a2c3b884cfa59ca342b2e;
+
+/// The ciphertext of the test case 2
+const CIPHERTEXT: u128 = 0x0388dace60b6a392f328c2b971b2fe78;
+
+/// The length block of the test case 2 (no additional data, 128 bits of ciphertext)
+const LENGTHS: u128 = 128;
+
+/// The output of GHASH for the test case 2
+const GHASH: u128 = 0xf38cbb1ad69223dcc3457ae5b6b0f885;
+
+fn to_words(x: u128) -> [u32; 4] {
+    std::array::from_fn(|i| (x >> (96 - 32 * i)) as u32)
+}
+
+#[test]
+fn test_gf128_native() {
+    assert_eq!(
+        gf128_mul_native(CIPHERTEXT, H),
+        0x5e2ec746917062882c85b0685353deb7
+    );
+    assert_eq!(ghash_native(H, &[CIPHERTEXT, LENGTHS]), GHASH);
+
+    // 1 is the block with only its first bit set
+    let one = 1 << 127;
+    assert_eq!(gf128_mul_native(H, one), H);
+    assert_eq!(
+        gf128_mul_native(H, CIPHERTEXT),
+        gf128_mul_native(CIPHERTEXT, H)
+    );
+}
+
+/// Compute GHASH with a private key
+fn circuit<F: PrimeField, Sys: Cs<F>>(
+    witness: Option<(u128, u128)>,
+    sys: &mut Sys,
+    public_input: Vec<Var<F>>,
+) {
+    let zero = sys.constant(F::zero());
+    let mut block_var = |i: usize| {
+        let words: [_; 4] = std::array::from_fn(|k| {
+            sys.u32_var(zero, || {
+                let (h, c) = witness.unwrap();
+                to_words([h, c][i])[k]
+            })
+        });
+        sys.gf128_from_words(&words)
+    };
+    let h = block_var(0);
+    let ciphertext = block_var(1);
+
+    let lengths = to_words(LENGTHS).map(|w| sys.u32_constant(w));
+    let lengths = sys.gf128_from_words(&lengths);
+
+    let ghash = sys.ghash(zero, &h, &[ciphertext, lengths]);
+    let words = sys.gf128_to_words(&ghash);
+    for (word, expected) in words.iter().zip(public_input) {
+        sys.assert_eq(word.var(), expected);
+    }
+}
+
+#[test]
+fn test_gf128_circuit() {
+    super::prove_and_verify(
+        |sys, p| circuit(None, sys, p),
+        |sys, p| circuit(Some((H, CIPHERTEXT)), sys, p),
+        to_words(GHASH).map(Fp::from).to_vec(),
+        1 << 13,
+    );
+}
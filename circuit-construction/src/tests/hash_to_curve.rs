@@ -0,0 +1,30 @@
+use crate::gadgets::hash_to_curve::HashToCurve;
+use crate::prologue::*;
+use ark_ff::Zero;
+use groupmap::BWParameters;
+use mina_curves::pasta::PallasParameters;
+
+/// Map a field element and zero to Pallas, and compare them with the points
+/// computed by `groupmap` (given as public inputs)
+fn circuit<Sys: Cs<Fp>>(sys: &mut Sys, public_input: Vec<Var<Fp>>) {
+    let params = BWParameters::<PallasParameters>::setup();
+    let zero = sys.constant(Fp::zero());
+
+    for (t, i) in [(public_input[0], 1), (zero, 3)] {
+        let (x, y) = sys.to_group(&params, t);
+        sys.assert_eq(x, public_input[i]);
+        sys.assert_eq(y, public_input[i + 1]);
+    }
+}
+
+#[test]
+fn test_hash_to_curve_circuit() {
+    let params = BWParameters::<PallasParameters>::setup();
+    let mut rng = rand::thread_rng();
+    let t = Fp::rand(&mut rng);
+    let (x, y) = params.to_group(t);
+    let (x0, y0) = params.to_group(Fp::zero());
+    assert!(PallasAffine::new(x, y, false).is_on_curve());
+
+    super::prove_and_verify(circuit, circuit, vec![t, x, y, x0, y0], 1 << 7);
+}
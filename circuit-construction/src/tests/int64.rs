@@ -0,0 +1,79 @@
+use crate::gadgets::int64::{Int64, I64};
+use crate::prologue::*;
+use crate::writer::GateSpec;
+use kimchi::circuits::gate::GateType;
+
+const A: i64 = -5;
+const B: i64 = 7;
+
+fn assert_i64<F: PrimeField, Sys: Cs<F>>(sys: &mut Sys, a: I64<F>, expected: i64) {
+    let expected = sys.i64_constant(expected);
+    sys.assert_eq(a.var(), expected.var());
+}
+
+fn assert_bool<F: PrimeField, Sys: Cs<F>>(sys: &mut Sys, b: Var<F>, expected: bool) {
+    let expected = sys.constant(F::from(u64::from(expected)));
+    sys.assert_eq(b, expected);
+}
+
+/// Operate on private signed integers, the public input being the value of `A`
+fn circuit<F: PrimeField, Sys: Cs<F>>(sys: &mut Sys, public_input: Vec<Var<F>>) {
+    let zero = sys.constant(F::zero());
+    let a = sys.i64_var(zero, || A);
+    let b = sys.i64_var(zero, || B);
+    let min = sys.i64_var(zero, || i64::MIN);
+    let z = sys.i64_constant(0);
+
+    // negation and absolute value
+    let neg = sys.i64_neg(zero, a);
+    assert_i64(sys, neg, -A);
+    let neg = sys.i64_neg(zero, min);
+    assert_i64(sys, neg, i64::MIN);
+    let neg = sys.i64_neg(zero, z);
+    assert_i64(sys, neg, 0);
+    for (x, expected) in [(a, A.abs()), (b, B), (min, i64::MIN), (z, 0)] {
+        let abs = sys.i64_abs(zero, x);
+        assert_i64(sys, abs, expected);
+    }
+
+    // sign and value
+    let sign = sys.i64_sign(zero, a);
+    assert_bool(sys, sign, true);
+    let sign = sys.i64_sign(zero, z);
+    assert_bool(sys, sign, false);
+    let value = sys.i64_value(zero, a);
+    sys.assert_eq(value, public_input[0]);
+
+    // comparisons
+    let ints = [(a, A), (b, B), (min, i64::MIN), (z, 0)];
+    for (x, vx) in ints {
+        for (y, vy) in ints {
+            let lt = sys.i64_less_than(zero, x, y);
+            assert_bool(sys, lt, vx < vy);
+            let le = sys.i64_less_than_or_equal(zero, x, y);
+            assert_bool(sys, le, vx <= vy);
+        }
+    }
+
+    // sign extension
+    let byte = sys.constant(F::from(A as u8));
+    let extended = sys.i64_sign_extend(zero, byte, 8);
+    assert_i64(sys, extended, A);
+    let word = sys.constant(F::from(B as u32));
+    let extended = sys.i64_sign_extend(zero, word, 32);
+    assert_i64(sys, extended, B);
+
+    // Temporary workaround for lookup-table/domain-size issue
+    while sys.curr_gate_count() < 1 << 12 {
+        sys.gate(GateSpec {
+            typ: GateType::Zero,
+            row: vec![],
+            coeffs: vec![],
+        });
+    }
+}
+
+#[test]
+fn test_int64_circuit() {
+    super::prove_and_verify(circuit, circuit, vec![-Fp::from(A.unsigned_abs())], 1 << 13);
+}
@@ -0,0 +1,110 @@
+use crate::gadgets::mimc::{exponent, GmimcParams, Mimc, MimcParams};
+use crate::prologue::*;
+use ark_ff::{Field, Zero};
+use mina_curves::pasta::Fq;
+
+const SEED: &[u8] = b"mimc";
+
+const WIDTH: usize = 3;
+
+#[test]
+fn test_mimc_params() {
+    // x^3 is not a permutation of the Pasta fields
+    assert_eq!(exponent::<Fp>(), 5);
+    assert_eq!(exponent::<Fq>(), 5);
+
+    let params = MimcParams::<Fp>::new(SEED);
+    assert_eq!(params.exponent, 5);
+    assert_eq!(params.round_constants.len(), 110);
+    assert_eq!(params.round_constants[0], Fp::zero());
+    assert_eq!(
+        params.round_constants,
+        MimcParams::<Fp>::new(SEED).round_constants
+    );
+    assert_ne!(
+        params.round_constants[1..],
+        MimcParams::<Fp>::new(b"other").round_constants[1..]
+    );
+
+    let params = GmimcParams::<Fp>::new(SEED, WIDTH);
+    assert_eq!(params.round_constants.len(), 2 * 110 + WIDTH);
+}
+
+#[test]
+fn test_mimc_native() {
+    let mut rng = rand::thread_rng();
+    let params = MimcParams::<Fp>::with_rounds(SEED, 3);
+    let (key, x) = (Fp::rand(&mut rng), Fp::rand(&mut rng));
+
+    // compare with the rounds written out
+    let c = &params.round_constants;
+    let y = (x + key).pow([5]);
+    let y = (y + key + c[1]).pow([5]);
+    let y = (y + key + c[2]).pow([5]);
+    assert_eq!(params.encrypt(key, x), y + key);
+
+    let h = key + x + params.encrypt(key, x);
+    assert_eq!(params.hash(key, &[x]), h);
+    assert_eq!(params.hash(key, &[x, x]), h + x + params.encrypt(h, x));
+}
+
+#[test]
+fn test_gmimc_native() {
+    let mut rng = rand::thread_rng();
+    let params = GmimcParams::<Fp>::with_rounds(SEED, WIDTH, 2);
+    let input: Vec<_> = (0..WIDTH).map(|_| Fp::rand(&mut rng)).collect();
+
+    // compare with the rounds written out
+    let c = &params.round_constants;
+    let [a, b, d] = [input[0], input[1], input[2]];
+    let f = (a + c[0]).pow([5]);
+    let [a, b, d] = [b + f, d + f, a];
+    let f = (a + c[1]).pow([5]);
+    let expected = vec![b + f, d + f, a];
+
+    let mut state = input;
+    params.permute(&mut state);
+    assert_eq!(state, expected);
+}
+
+/// Hash two elements with MiMC, and permute three elements with GMiMC
+fn circuit<F: PrimeField, Sys: Cs<F>>(
+    mimc: &MimcParams<F>,
+    gmimc: &GmimcParams<F>,
+    witness: Option<&[F]>,
+    sys: &mut Sys,
+    public_input: Vec<Var<F>>,
+) {
+    let zero = sys.constant(F::zero());
+    let inputs: Vec<_> = (0..WIDTH)
+        .map(|i| sys.var(|| witness.unwrap()[i]))
+        .collect();
+
+    let hash = sys.mimc_hash(mimc, zero, &inputs[..2]);
+    sys.assert_eq(hash, public_input[0]);
+
+    let state = sys.gmimc_permute(gmimc, &inputs);
+    for (x, expected) in state.into_iter().zip(&public_input[1..]) {
+        sys.assert_eq(x, *expected);
+    }
+}
+
+#[test]
+fn test_mimc_circuit() {
+    let mimc = MimcParams::<Fp>::new(SEED);
+    let gmimc = GmimcParams::<Fp>::new(SEED, WIDTH);
+
+    let mut rng = rand::thread_rng();
+    let inputs: Vec<_> = (0..WIDTH).map(|_| Fp::rand(&mut rng)).collect();
+    let mut public_input = vec![mimc.hash(Fp::zero(), &inputs[..2])];
+    let mut state = inputs.clone();
+    gmimc.permute(&mut state);
+    public_input.extend(state);
+
+    super::prove_and_verify(
+        |sys, p| circuit(&mimc, &gmimc, None, sys, p),
+        |sys, p| circuit(&mimc, &gmimc, Some(&inputs), sys, p),
+        public_input,
+        1 << 11,
+    );
+}
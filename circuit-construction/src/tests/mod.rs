@@ -1 +1,57 @@
+mod arith;
+mod bigint;
+mod bip340;
+mod bitwise;
+mod bytes;
+mod credentials;
+mod dfa;
+mod ecdsa;
 mod example_proof;
+mod gf128;
+mod hash_to_curve;
+mod int64;
+mod mimc;
+mod pedersen;
+mod prf;
+mod ripemd160;
+mod schnorr;
+mod sqrt;
+mod vrf;
+
+use crate::prologue::*;
+use crate::writer::{System, WitnessGenerator};
+
+type SpongeQ = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
+type SpongeR = DefaultFrSponge<Fp, PlonkSpongeConstantsKimchi>;
+
+/// Generates the index of `circuit` with an SRS of `srs_size`,
+/// then proves `witness` (the same circuit, run by the witness generator)
+/// on `public_input`, and verifies the proof.
+fn prove_and_verify<C, W>(circuit: C, witness: W, public_input: Vec<Fp>, srs_size: usize)
+where
+    C: FnOnce(&mut System<Fp>, Vec<Var<Fp>>),
+    W: FnMut(&mut WitnessGenerator<Fp>, Vec<Var<Fp>>),
+{
+    use mina_curves::pasta::Vesta;
+
+    // create SRS
+    let srs = {
+        let mut srs = SRS::<Vesta>::create(srs_size);
+        srs.add_lagrange_basis(Radix2EvaluationDomain::new(srs.g.len()).unwrap());
+        Arc::new(srs)
+    };
+
+    // generate circuit and index
+    let prover_index = generate_prover_index::<_, _>(srs, public_input.len(), circuit);
+
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+
+    // generate proof
+    let proof =
+        prove::<Vesta, _, SpongeQ, SpongeR>(&prover_index, &group_map, None, public_input, witness);
+
+    // verify proof
+    let verifier_index = prover_index.verifier_index();
+
+    verify::<_, SpongeQ, SpongeR, _>(&group_map, &verifier_index, &proof).unwrap();
+}
@@ -0,0 +1,120 @@
+use crate::gadgets::{
+    arith::Arith,
+    pedersen::{Pedersen, PedersenParams, CHUNK_BITS, SEGMENT_CHUNKS},
+};
+use crate::prologue::*;
+use ark_ff::{One, Zero};
+use mina_curves::pasta::Fq;
+use rand::Rng;
+
+const PERSONALIZATION: &[u8] = b"test";
+
+/// A message spanning two segments
+const MESSAGE_BITS: usize = 200;
+
+fn random_bits(len: usize) -> Vec<bool> {
+    let mut rng = rand::thread_rng();
+    (0..len).map(|_| rng.gen()).collect()
+}
+
+/// Computes the hash with scalar multiplications, encoding each segment as a scalar
+fn hash_with_scalars(generators: &[PallasAffine], bits: &[bool]) -> PallasAffine {
+    let mut res = <PallasAffine as AffineCurve>::Projective::zero();
+    for (segment, g) in bits.chunks(CHUNK_BITS * SEGMENT_CHUNKS).zip(generators) {
+        let mut scalar = Fq::zero();
+        let mut power = Fq::one();
+        for chunk in segment.chunks(CHUNK_BITS) {
+            let bit = |i: usize| chunk.get(i).copied().unwrap_or(false);
+            let value = Fq::from(1 + u64::from(bit(0)) + 2 * u64::from(bit(1)));
+            scalar += if bit(2) { -value } else { value } * power;
+            power *= Fq::from(16u64);
+        }
+        res += &g.mul(scalar);
+    }
+    res.into_affine()
+}
+
+#[test]
+fn test_pedersen_native() {
+    let params = PedersenParams::<PallasAffine>::new(PERSONALIZATION, 2);
+    assert_eq!(params.generators.len(), 2);
+    assert_eq!(params.blinding_generators.len(), 2);
+    assert_eq!(params.max_bits(), 2 * CHUNK_BITS * SEGMENT_CHUNKS);
+
+    let bits = random_bits(MESSAGE_BITS);
+    let hash = params.hash(&bits);
+    assert_eq!(hash, hash_with_scalars(&params.generators, &bits));
+
+    // the last chunk is padded with zeros
+    let mut padded = bits.clone();
+    padded.push(false);
+    assert_eq!(params.hash(&padded), hash);
+
+    let mut other = bits.clone();
+    other[MESSAGE_BITS - 1] = !other[MESSAGE_BITS - 1];
+    assert_ne!(params.hash(&other), hash);
+
+    // commitments add the encoding of the randomness on the blinding generators
+    let randomness = random_bits(PedersenParams::<PallasAffine>::randomness_bits());
+    let blinding = hash_with_scalars(&params.blinding_generators, &randomness);
+    assert_eq!(params.commit(&bits, &randomness), hash + blinding);
+
+    // generators depend on the personalization
+    let other_params = PedersenParams::<PallasAffine>::new(b"other", 2);
+    assert_ne!(other_params.generators, params.generators);
+}
+
+/// Creates `len` boolean variables, holding `bits` in witness mode
+fn bits_var<F: PrimeField, Sys: Cs<F>>(
+    sys: &mut Sys,
+    len: usize,
+    bits: Option<&[bool]>,
+) -> Vec<Var<F>> {
+    (0..len)
+        .map(|i| {
+            let b = sys.var(|| F::from(u64::from(bits.unwrap()[i])));
+            sys.assert_boolean(b);
+            b
+        })
+        .collect()
+}
+
+/// Commit to a message, and hash it, the results being public inputs
+fn circuit<F: PrimeField, G: CoordinateCurve<BaseField = F>, Sys: Cs<F>>(
+    params: &PedersenParams<G>,
+    witness: Option<(&[bool], &[bool])>,
+    sys: &mut Sys,
+    public_input: Vec<Var<F>>,
+) {
+    let zero = sys.constant(F::zero());
+    let message = bits_var(sys, MESSAGE_BITS, witness.map(|w| w.0));
+    let randomness = bits_var(
+        sys,
+        PedersenParams::<G>::randomness_bits(),
+        witness.map(|w| w.1),
+    );
+
+    let hash = sys.pedersen_hash(zero, params, &message);
+    let commitment = sys.pedersen_commit(zero, params, &message, &randomness);
+
+    sys.assert_eq(hash.0, public_input[0]);
+    sys.assert_eq(hash.1, public_input[1]);
+    sys.assert_eq(commitment.0, public_input[2]);
+    sys.assert_eq(commitment.1, public_input[3]);
+}
+
+#[test]
+fn test_pedersen_circuit() {
+    let params = PedersenParams::<PallasAffine>::new(PERSONALIZATION, 2);
+    let message = random_bits(MESSAGE_BITS);
+    let randomness = random_bits(PedersenParams::<PallasAffine>::randomness_bits());
+    let hash = params.hash(&message);
+    let commitment = params.commit(&message, &randomness);
+
+    super::prove_and_verify(
+        |sys, p| circuit::<_, PallasAffine, _>(&params, None, sys, p),
+        |sys, p| circuit(&params, Some((&message, &randomness)), sys, p),
+        vec![hash.x, hash.y, commitment.x, commitment.y],
+        1 << 11,
+    );
+}
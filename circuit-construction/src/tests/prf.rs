@@ -0,0 +1,90 @@
+use crate::gadgets::prf::{nullifier_native, prf_native, Prf, NULLIFIER_DOMAIN, PRF_DOMAIN};
+use crate::prologue::*;
+use mina_hasher::{Hashable, Hasher, ROInput};
+
+const INPUT_LEN: usize = 3;
+
+/// Field elements hashed by Mina's hasher, under the PRF or the nullifier domain
+#[derive(Clone)]
+struct Fields<const PRF: bool>(Vec<Fp>);
+
+impl<const PRF: bool> Hashable for Fields<PRF> {
+    type D = ();
+
+    fn to_roinput(&self) -> ROInput {
+        self.0
+            .iter()
+            .fold(ROInput::new(), |roi, x| roi.append_field(*x))
+    }
+
+    fn domain_string(_: Self::D) -> Option<String> {
+        let domain = if PRF { PRF_DOMAIN } else { NULLIFIER_DOMAIN };
+        domain.to_string().into()
+    }
+}
+
+#[test]
+fn test_prf_native() {
+    let constants = fp_constants();
+    let mut rng = rand::thread_rng();
+    let key = Fp::rand(&mut rng);
+    let input: Vec<_> = (0..INPUT_LEN).map(|_| Fp::rand(&mut rng)).collect();
+
+    // compatible with Mina's hasher
+    let mut message = vec![key];
+    message.extend(&input);
+    let mut hasher = mina_hasher::create_kimchi::<Fields<true>>(());
+    assert_eq!(
+        prf_native(&constants, key, &input),
+        hasher.hash(&Fields(message))
+    );
+
+    let mut hasher = mina_hasher::create_kimchi::<Fields<false>>(());
+    assert_eq!(
+        nullifier_native(&constants, key, input[0]),
+        hasher.hash(&Fields(vec![key, input[0]]))
+    );
+
+    // the domains are separated
+    assert_ne!(
+        prf_native(&constants, key, &input[..1]),
+        nullifier_native(&constants, key, input[0])
+    );
+}
+
+/// Derive a PRF output and a nullifier from private inputs
+fn circuit<F: PrimeField, Sys: Cs<F>>(
+    constants: &Constants<F>,
+    witness: Option<&[F]>,
+    sys: &mut Sys,
+    public_input: Vec<Var<F>>,
+) {
+    let inputs: Vec<_> = (0..INPUT_LEN + 1)
+        .map(|i| sys.var(|| witness.unwrap()[i]))
+        .collect();
+    let (key, input) = (inputs[0], &inputs[1..]);
+
+    let output = sys.prf(constants, key, input);
+    sys.assert_eq(output, public_input[0]);
+
+    let nullifier = sys.nullifier(constants, key, input[0]);
+    sys.assert_eq(nullifier, public_input[1]);
+}
+
+#[test]
+fn test_prf_circuit() {
+    let constants = fp_constants();
+    let mut rng = rand::thread_rng();
+    let inputs: Vec<_> = (0..INPUT_LEN + 1).map(|_| Fp::rand(&mut rng)).collect();
+    let public_input = vec![
+        prf_native(&constants, inputs[0], &inputs[1..]),
+        nullifier_native(&constants, inputs[0], inputs[1]),
+    ];
+
+    super::prove_and_verify(
+        |sys, p| circuit(&constants, None, sys, p),
+        |sys, p| circuit(&constants, Some(&inputs), sys, p),
+        public_input,
+        1 << 6,
+    );
+}
@@ -0,0 +1,84 @@
+use crate::gadgets::{
+    ripemd160::{ripemd160_native, Ripemd160, DIGEST_WORDS},
+    uint32::{rotation_plan, Rotation, Uint32},
+};
+use crate::prologue::*;
+
+/// Prove knowledge of a 32-byte preimage of a RIPEMD-160 digest
+pub fn circuit<F: PrimeField, Sys: Cs<F>>(
+    preimage: Option<&[u32; 8]>,
+    sys: &mut Sys,
+    public_input: Vec<Var<F>>,
+) {
+    let zero = sys.constant(F::zero());
+
+    let message: Vec<_> = (0..8)
+        .map(|i| sys.u32_var(zero, || preimage.unwrap()[i]))
+        .collect();
+    let digest = sys.ripemd160(zero, &message);
+
+    for (word, expected) in digest.iter().zip(public_input) {
+        sys.assert_eq(word.var(), expected);
+    }
+}
+
+#[test]
+fn test_ripemd160_native() {
+    let vectors: [(&[u8], &str); 5] = [
+        (b"", "9c1185a5c5e9fc54612808977ee8f548b2258d31"),
+        (b"a", "0bdc9d2d256b3ee9daae347be6f4dc835a467ffe"),
+        (b"abc", "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc"),
+        (
+            b"message digest",
+            "5d0689ef49d2fae572b881b123a85ffa21595f36",
+        ),
+        (
+            b"12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+            "9b752e45573d4b39f4dbd3323cab82bf63326bfb",
+        ),
+    ];
+    for (message, digest) in vectors {
+        let actual: String = ripemd160_native(message)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        assert_eq!(actual, digest);
+    }
+}
+
+#[test]
+fn test_rotation_plan() {
+    for bits in 0..32 {
+        let total: u32 = rotation_plan(bits).iter().map(Rotation::bits).sum();
+        assert_eq!(total % 32, bits);
+    }
+    assert!(rotation_plan(0).is_empty());
+    assert_eq!(rotation_plan(16), vec![Rotation::By16]);
+    assert_eq!(rotation_plan(7), vec![Rotation::By7]);
+}
+
+#[test]
+fn test_ripemd160_circuit() {
+    let preimage: Vec<u8> = (0u8..32).collect();
+    let words: [u32; 8] = std::array::from_fn(|i| {
+        u32::from_le_bytes([
+            preimage[4 * i],
+            preimage[4 * i + 1],
+            preimage[4 * i + 2],
+            preimage[4 * i + 3],
+        ])
+    });
+    let digest = ripemd160_native(&preimage);
+    let public_input: Vec<Fp> = digest
+        .chunks(4)
+        .map(|w| Fp::from(u32::from_le_bytes([w[0], w[1], w[2], w[3]])))
+        .collect();
+    assert_eq!(public_input.len(), DIGEST_WORDS);
+
+    super::prove_and_verify(
+        |sys, p| circuit::<_, _>(None, sys, p),
+        |sys, p| circuit::<Fp, _>(Some(&words), sys, p),
+        public_input,
+        1 << 13,
+    );
+}
@@ -0,0 +1,94 @@
+use crate::gadgets::schnorr::{Schnorr, SchnorrWitness};
+use crate::prologue::*;
+use crate::writer::GateSpec;
+use ark_ff::BigInteger;
+use kimchi::circuits::gate::GateType;
+use mina_hasher::{Hashable, ROInput};
+use mina_signer::{Keypair, Signer};
+
+const MESSAGE_LEN: usize = 3;
+
+#[derive(Clone)]
+struct Message(Vec<Fp>);
+
+impl Hashable for Message {
+    type D = ();
+
+    fn to_roinput(&self) -> ROInput {
+        self.0
+            .iter()
+            .fold(ROInput::new(), |roi, x| roi.append_field(*x))
+    }
+
+    fn domain_string(_: Self::D) -> Option<String> {
+        "CodaSignature".to_string().into()
+    }
+}
+
+// Verify a signature of a message under a public key (given as public input)
+fn circuit<F: PrimeField, G: AffineCurve<BaseField = F>, Sys: Cs<F>>(
+    constants: &Constants<F>,
+    witness: Option<&SchnorrWitness<G>>,
+    sys: &mut Sys,
+    public_input: Vec<Var<F>>,
+) {
+    let zero = sys.constant(F::zero());
+    let public_key = (public_input[0], public_input[1]);
+    let (message, signature) = sys.schnorr_witness(witness, MESSAGE_LEN);
+    sys.schnorr_verify(
+        zero,
+        constants,
+        Message::domain_string(()),
+        public_key,
+        &message,
+        signature,
+    );
+
+    // Temporary workaround for lookup-table/domain-size issue
+    while sys.curr_gate_count() < 1 << 12 {
+        sys.gate(GateSpec {
+            typ: GateType::Zero,
+            row: vec![],
+            coeffs: vec![],
+        });
+    }
+}
+
+#[test]
+fn test_schnorr_witness() {
+    let mut rng = rand::thread_rng();
+    let keypair = Keypair::rand(&mut rng);
+    let message = Message((0..MESSAGE_LEN).map(|_| Fp::rand(&mut rng)).collect());
+    let signature = mina_signer::create_kimchi::<Message>(()).sign(&keypair, &message);
+
+    let witness = SchnorrWitness::new(&signature, &message).unwrap();
+    let (rx, ry) = witness.r;
+    assert_eq!(rx, signature.rx);
+    assert!(ry.into_repr().is_even());
+    assert!(PallasAffine::new(rx, ry, false).is_on_curve());
+    assert_eq!(witness.message, message.0);
+}
+
+#[test]
+fn test_schnorr_circuit() {
+    let proof_system_constants = fp_constants();
+
+    // sign a random message
+    let mut rng = rand::thread_rng();
+    let keypair = Keypair::rand(&mut rng);
+    let message = Message((0..MESSAGE_LEN).map(|_| Fp::rand(&mut rng)).collect());
+    let mut signer = mina_signer::create_kimchi::<Message>(());
+    let signature = signer.sign(&keypair, &message);
+    assert!(signer.verify(&signature, &keypair.public, &message));
+
+    // create witness and public input
+    let witness = SchnorrWitness::new(&signature, &message).unwrap();
+    let public_key = keypair.public.point();
+
+    super::prove_and_verify(
+        |sys, p| circuit::<_, PallasAffine, _>(&proof_system_constants, None, sys, p),
+        |sys, p| circuit::<Fp, PallasAffine, _>(&proof_system_constants, Some(&witness), sys, p),
+        vec![public_key.x, public_key.y],
+        1 << 13,
+    );
+}
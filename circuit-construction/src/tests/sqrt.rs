@@ -0,0 +1,50 @@
+use crate::gadgets::{
+    arith::Arith,
+    sqrt::{non_residue, sqrt_native, Sqrt},
+};
+use crate::prologue::*;
+use ark_ff::{Field, SquareRootField, Zero};
+use mina_curves::pasta::Fq;
+
+#[test]
+fn test_sqrt_native() {
+    assert_eq!(non_residue::<Fp>(), Fp::from(5u64));
+    assert_eq!(non_residue::<Fq>(), Fq::from(5u64));
+
+    let mut rng = rand::thread_rng();
+    let x = Fp::rand(&mut rng).square();
+    let (is_square, r) = sqrt_native(x);
+    assert!(is_square);
+    assert_eq!(r.square(), x);
+
+    let y = x * non_residue::<Fp>();
+    let (is_square, r) = sqrt_native(y);
+    assert!(!is_square);
+    assert_eq!(r.square(), y * non_residue::<Fp>());
+
+    assert_eq!(sqrt_native(Fp::zero()), (true, Fp::zero()));
+}
+
+/// Take the square root of a square, a non-square and zero (given as public inputs)
+fn circuit<F: PrimeField + SquareRootField, Sys: Cs<F>>(sys: &mut Sys, public_input: Vec<Var<F>>) {
+    let zero = sys.constant(F::zero());
+    let one = sys.constant(F::one());
+
+    let r = sys.sqrt(public_input[0]);
+    let r2 = sys.mul(r, r);
+    sys.assert_eq(r2, public_input[0]);
+
+    for (x, expected) in [(public_input[0], one), (public_input[1], zero), (zero, one)] {
+        let (is_square, _) = sys.sqrt_or_non_residue(x);
+        sys.assert_eq(is_square, expected);
+    }
+}
+
+#[test]
+fn test_sqrt_circuit() {
+    let mut rng = rand::thread_rng();
+    let square = Fp::rand(&mut rng).square();
+    let non_square = square * non_residue::<Fp>();
+
+    super::prove_and_verify(circuit, circuit, vec![square, non_square], 1 << 5);
+}
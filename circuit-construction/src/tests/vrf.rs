@@ -0,0 +1,78 @@
+use crate::gadgets::vrf::{vrf_native, Vrf};
+use crate::prologue::*;
+use ark_ff::Zero;
+use groupmap::BWParameters;
+use mina_curves::pasta::{Fq, PallasParameters};
+
+// global slot, epoch seed, delegator index
+const MESSAGE_LEN: usize = 3;
+
+// Evaluate the VRF of a message for the secret key of a public key,
+// the public key, the message and the output being public inputs
+fn circuit<Sys: Cs<Fp>>(
+    constants: &Constants<Fp>,
+    sk: Option<Fq>,
+    sys: &mut Sys,
+    public_input: Vec<Var<Fp>>,
+) {
+    let params = BWParameters::<PallasParameters>::setup();
+    let zero = sys.constant(Fp::zero());
+    let public_key = (public_input[0], public_input[1]);
+    let message = &public_input[2..2 + MESSAGE_LEN];
+
+    let sk = sys.vrf_secret_key(sk);
+    let output = sys.vrf_evaluate(zero, constants, &params, public_key, sk, message);
+    sys.assert_eq(output, public_input[2 + MESSAGE_LEN]);
+}
+
+#[test]
+fn test_vrf_native() {
+    let constants = fp_constants();
+    let params = BWParameters::<PallasParameters>::setup();
+    let mut rng = rand::thread_rng();
+    let sk = Fq::rand(&mut rng);
+    let message: Vec<_> = (0..MESSAGE_LEN).map(|_| Fp::rand(&mut rng)).collect();
+
+    let evaluation = vrf_native(&constants, &params, sk, &message);
+    let (x, y) = evaluation.point;
+    assert!(PallasAffine::new(x, y, false).is_on_curve());
+    assert_eq!(vrf_native(&constants, &params, sk, &message), evaluation);
+
+    let other_sk = Fq::rand(&mut rng);
+    assert_ne!(
+        vrf_native(&constants, &params, other_sk, &message).output,
+        evaluation.output
+    );
+    let mut other_message = message;
+    other_message[0] += Fp::from(1u64);
+    assert_ne!(
+        vrf_native(&constants, &params, sk, &other_message).output,
+        evaluation.output
+    );
+}
+
+#[test]
+fn test_vrf_circuit() {
+    let proof_system_constants = fp_constants();
+
+    // evaluate the VRF
+    let mut rng = rand::thread_rng();
+    let sk = Fq::rand(&mut rng);
+    let public_key = PallasAffine::prime_subgroup_generator()
+        .mul(sk)
+        .into_affine();
+    let message: Vec<_> = (0..MESSAGE_LEN).map(|_| Fp::rand(&mut rng)).collect();
+    let params = BWParameters::<PallasParameters>::setup();
+    let evaluation = vrf_native(&proof_system_constants, &params, sk, &message);
+
+    let mut public_input = vec![public_key.x, public_key.y];
+    public_input.extend(&message);
+    public_input.push(evaluation.output);
+
+    super::prove_and_verify(
+        |sys, p| circuit(&proof_system_constants, None, sys, p),
+        |sys, p| circuit(&proof_system_constants, Some(sk), sys, p),
+        public_input,
+        1 << 10,
+    );
+}
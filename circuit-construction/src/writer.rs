@@ -1,8 +1,10 @@
 use ark_ff::{BigInteger, FftField, PrimeField};
 use kimchi::circuits::{
     gate::{CircuitGate, GateType},
+    lookup::tables::LookupTable,
     polynomials::generic::{
         DOUBLE_GENERIC_COEFFS, DOUBLE_GENERIC_REGISTERS, GENERIC_COEFFS, GENERIC_REGISTERS,
+        TRIPLE_GENERIC_COEFFS, TRIPLE_GENERIC_REGISTERS,
     },
     wires::{Wire, COLUMNS},
 };
@@ -11,7 +13,7 @@ use oracle::{
     permutation::full_round,
 };
 use std::array;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::constants::Constants;
 
@@ -36,8 +38,17 @@ impl<F: Copy> Var<F> {
 }
 
 /// A variable that corresponds to scalar that is shifted by a certain amount.
+#[derive(Clone, Copy)]
 pub struct ShiftedScalar<F>(Var<F>);
 
+impl<F> ShiftedScalar<F> {
+    /// Interprets a variable `y` as the shifted scalar `2 y + 2^255 + 1`,
+    /// which is the scalar that [`Cs::scalar_mul`] multiplies by.
+    pub fn from_shifted(y: Var<F>) -> Self {
+        ShiftedScalar(y)
+    }
+}
+
 /// Specifies a gate within a circuit.
 /// A gate will have a type,
 /// will refer to a row of variables,
@@ -75,6 +86,7 @@ pub struct System<F> {
     // pub equivalence_classes: HashMap<Var, Vec<Position>>,
     pub gates: Vec<GateSpec<F>>,
     pub cached_constants: HashMap<F, Var<F>>,
+    pub lookup_tables: Vec<LookupTable<F>>,
 }
 
 /// Carries a vector of rows corresponding to the witness, a queue of generic gates, and stores the cached constants
@@ -200,28 +212,24 @@ pub trait Cs<F: PrimeField> {
     /// Otherwise, it creates a variable for it and caches it.
     fn cached_constants(&mut self, x: F) -> Var<F>;
 
-    /// Creates a `Generic` gate to include a constant in the circuit, and returns the variable containing it.
-    /// It sets the left wire to be the variable containing the constant `x` and the rest to zero.
-    /// Then the left coefficient is set to one and the coefficient for constants is set to `-x`.
-    /// This way, the constraint `1 * x - x = 0` holds.
+    /// Returns the variable containing the constant `x`.
+    /// The constant does not use a gate of its own: the cells of the variable are pinned to
+    /// the constants pool of the circuit when it is compiled (see [`System::constant_cells`]),
+    /// which holds each distinct constant once.
     fn constant(&mut self, x: F) -> Var<F> {
-        let v = self.cached_constants(x);
-
-        let mut coeffs = [F::zero(); GENERIC_COEFFS];
-        coeffs[0] = F::one();
-        coeffs[GENERIC_REGISTERS + 1] = -x;
-
-        let vars = [Some(v), None, None];
-
-        self.generic(coeffs, vars);
-
-        v
+        self.cached_constants(x)
     }
 
     /// Stores a generic gate until it can combine two of them
-    /// into a double generic gate.
+    /// into a double generic gate, or three of them into a triple generic gate
+    /// when one of them only uses its left register.
     fn generic_queue(&mut self, gate: GateSpec<F>) -> Option<GateSpec<F>>;
 
+    /// Registers a fixed lookup table, used by the `Lookup` gates of the circuit.
+    /// In circuit mode, the table is added to the constraint system (once per table ID).
+    /// In witness generation mode, this does nothing.
+    fn lookup_table(&mut self, table: LookupTable<F>);
+
     /// Adds a generic gate.
     ///
     /// Warning: this assumes that some finalization occurs to flush
@@ -232,9 +240,9 @@ pub trait Cs<F: PrimeField> {
             row: vars.to_vec(),
             coeffs: coeffs.to_vec(),
         };
-        // we queue the single generic gate until we have two of them
-        if let Some(double_generic_gate) = self.generic_queue(gate) {
-            self.gate(double_generic_gate);
+        // we queue the single generic gate until we have two or three of them
+        if let Some(generic_gate) = self.generic_queue(gate) {
+            self.gate(generic_gate);
         }
     }
 
@@ -466,9 +474,23 @@ pub trait Cs<F: PrimeField> {
     fn scalar_mul(
         &mut self,
         zero: Var<F>,
-        (xt, yt): (Var<F>, Var<F>),
+        point: (Var<F>, Var<F>),
         scalar: ShiftedScalar<F>,
     ) -> (Var<F>, Var<F>) {
+        self.scalar_mul_with_high(zero, point, scalar).0
+    }
+
+    /// Same as [`Cs::scalar_mul`], but also returns the variable `h` holding the scalar `y`
+    /// without its last 5 bits, as accumulated by the `VarBaseMul` gates,
+    /// so that `y = 32 h + l` (in the native field) for some `l < 32`.
+    /// Note that `h` is not range-checked: it is up to the caller to constrain it,
+    /// e.g. to check that the bits of `y` are canonical.
+    fn scalar_mul_with_high(
+        &mut self,
+        zero: Var<F>,
+        (xt, yt): (Var<F>, Var<F>),
+        scalar: ShiftedScalar<F>,
+    ) -> ((Var<F>, Var<F>), Var<F>) {
         let num_bits = 255;
         let num_row_pairs = num_bits / 5;
         let mut witness: [Vec<F>; COLUMNS] = array::from_fn(|_| vec![]);
@@ -514,7 +536,7 @@ pub trait Cs<F: PrimeField> {
             }
             if i == num_row_pairs - 1 {
                 row1[5] = scalar.0;
-                res = Some((row2[0], row2[1]));
+                res = Some(((row2[0], row2[1]), row1[4]));
             }
 
             self.gate(GateSpec {
@@ -847,6 +869,58 @@ pub trait Cs<F: PrimeField> {
     }
 }
 
+/// Queues a single generic gate, and returns the row of generic gates it completes, if any.
+///
+/// Once two gates are queued, the next one completes a triple generic gate if it,
+/// or one of the queued gates, can be its third gate, as only the left register
+/// of the third gate is wired: the other registers of that gate must be unused.
+/// Otherwise, the two queued gates are returned as a double generic gate,
+/// and the new one is queued.
+fn queue_generic_gate<F: Copy>(
+    queue: &mut Vec<GateSpec<F>>,
+    gate: GateSpec<F>,
+) -> Option<GateSpec<F>> {
+    if queue.len() < 2 {
+        queue.push(gate);
+        return None;
+    }
+
+    let unwired = |gate: &GateSpec<F>| gate.row[1..].iter().all(Option::is_none);
+    queue.push(gate);
+    match queue.iter().rposition(unwired) {
+        Some(third) => {
+            let third = queue.remove(third);
+            let mut gate = flush_generic_gates(queue).unwrap();
+            gate.typ = GateType::TripleGeneric;
+            gate.row.extend(&third.row);
+            assert_eq!(gate.row.len(), TRIPLE_GENERIC_REGISTERS);
+            gate.coeffs.extend(&third.coeffs);
+            assert_eq!(gate.coeffs.len(), TRIPLE_GENERIC_COEFFS);
+            Some(gate)
+        }
+        None => {
+            let gate = queue.pop().unwrap();
+            let double = flush_generic_gates(queue);
+            queue.push(gate);
+            double
+        }
+    }
+}
+
+/// Empties the queue of generic gates, returning its gates as a single row.
+fn flush_generic_gates<F: Copy>(queue: &mut Vec<GateSpec<F>>) -> Option<GateSpec<F>> {
+    let mut gates = queue.drain(..);
+    let mut gate = gates.next()?;
+    if let Some(other) = gates.next() {
+        gate.row.extend(&other.row);
+        assert_eq!(gate.row.len(), DOUBLE_GENERIC_REGISTERS);
+        gate.coeffs.extend(&other.coeffs);
+        assert_eq!(gate.coeffs.len(), DOUBLE_GENERIC_COEFFS);
+    }
+    assert!(gates.next().is_none());
+    Some(gate)
+}
+
 impl<F: PrimeField> Cs<F> for WitnessGenerator<F> {
     /// Creates a variable with value given by a function `g` with index `0`
     fn var<G>(&mut self, g: G) -> Var<F>
@@ -873,14 +947,7 @@ impl<F: PrimeField> Cs<F> for WitnessGenerator<F> {
     }
 
     fn generic_queue(&mut self, gate: GateSpec<F>) -> Option<GateSpec<F>> {
-        if let Some(mut other) = self.generic_gate_queue.pop() {
-            other.row.extend(&gate.row);
-            assert_eq!(other.row.len(), DOUBLE_GENERIC_REGISTERS);
-            Some(other)
-        } else {
-            self.generic_gate_queue.push(gate);
-            None
-        }
+        queue_generic_gate(&mut self.generic_gate_queue, gate)
     }
 
     fn cached_constants(&mut self, x: F) -> Var<F> {
@@ -893,13 +960,15 @@ impl<F: PrimeField> Cs<F> for WitnessGenerator<F> {
             }
         }
     }
+
+    fn lookup_table(&mut self, _: LookupTable<F>) {}
 }
 
 impl<F: PrimeField> WitnessGenerator<F> {
     /// Returns the columns of the witness.
     pub fn columns(&mut self) -> [Vec<F>; COLUMNS] {
         // flush any queued generic gate
-        if let Some(gate) = self.generic_gate_queue.pop() {
+        if let Some(gate) = flush_generic_gates(&mut self.generic_gate_queue) {
             self.gate(gate);
         }
 
@@ -928,16 +997,7 @@ impl<F: PrimeField> Cs<F> for System<F> {
     }
 
     fn generic_queue(&mut self, gate: GateSpec<F>) -> Option<GateSpec<F>> {
-        if let Some(mut other) = self.generic_gate_queue.pop() {
-            other.row.extend(&gate.row);
-            assert_eq!(other.row.len(), DOUBLE_GENERIC_REGISTERS);
-            other.coeffs.extend(&gate.coeffs);
-            assert_eq!(other.coeffs.len(), DOUBLE_GENERIC_COEFFS);
-            Some(other)
-        } else {
-            self.generic_gate_queue.push(gate);
-            None
-        }
+        queue_generic_gate(&mut self.generic_gate_queue, gate)
     }
 
     fn cached_constants(&mut self, x: F) -> Var<F> {
@@ -950,6 +1010,18 @@ impl<F: PrimeField> Cs<F> for System<F> {
             }
         }
     }
+
+    /// Adds the table to the constraint system, unless a table with the same ID was added.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if a different table with the same ID was added.
+    fn lookup_table(&mut self, table: LookupTable<F>) {
+        match self.lookup_tables.iter().find(|t| t.id == table.id) {
+            Some(t) => assert!(t.data == table.data, "conflicting lookup table ID"),
+            None => self.lookup_tables.push(table),
+        }
+    }
 }
 
 impl<F: PrimeField> System<F> {
@@ -964,7 +1036,7 @@ impl<F: PrimeField> System<F> {
         let mut gates = vec![];
 
         // flush any queued generic gate
-        if let Some(gate) = self.generic_gate_queue.pop() {
+        if let Some(gate) = flush_generic_gates(&mut self.generic_gate_queue) {
             self.gate(gate);
         }
 
@@ -1008,4 +1080,31 @@ impl<F: PrimeField> System<F> {
 
         gates
     }
+
+    /// Returns the constants of the circuit, along with the first cell of their variable,
+    /// to pin to the constants pool with
+    /// [`Builder::constant`](kimchi::circuits::constraints::Builder::constant).
+    /// The other cells of a variable are wired to its first one by [`System::gates`].
+    pub fn constant_cells(&self) -> Vec<(F, Wire)> {
+        let constants: HashMap<usize, F> = self
+            .cached_constants
+            .iter()
+            .map(|(x, var)| (var.index, *x))
+            .collect();
+
+        let mut pinned = HashSet::new();
+        let mut cells = vec![];
+        for (row, gate) in self.gates.iter().enumerate() {
+            for col in 0..gate.row.len() {
+                if let Some(index) = gate.get_var_idx(col) {
+                    if let Some(x) = constants.get(&index) {
+                        if pinned.insert(index) {
+                            cells.push((*x, Wire { row, col }));
+                        }
+                    }
+                }
+            }
+        }
+        cells
+    }
 }
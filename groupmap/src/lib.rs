@@ -26,15 +26,25 @@ pub trait GroupMap<F> {
     fn setup() -> Self;
     fn to_group(&self, u: F) -> (F, F);
     fn batch_to_group_x(&self, ts: Vec<F>) -> Vec<[F; 3]>;
+    /// Maps each of `ts` to the curve, as [`GroupMap::to_group`] does,
+    /// sharing a single field inversion between all of them.
+    fn batch_to_group(&self, ts: &[F]) -> Vec<(F, F)>;
 }
 
+/// The parameters of the map, which only depend on the curve.
+/// They are public so that the map can be mirrored elsewhere (e.g. in circuits).
 #[derive(Clone, Copy)]
 pub struct BWParameters<G: SWModelParameters> {
-    u: G::BaseField,
-    fu: G::BaseField,
-    sqrt_neg_three_u_squared_minus_u_over_2: G::BaseField,
-    sqrt_neg_three_u_squared: G::BaseField,
-    inv_three_u_squared: G::BaseField,
+    /// the first u = 1, 2, ... such that f(u) != 0
+    pub u: G::BaseField,
+    /// f(u)
+    pub fu: G::BaseField,
+    /// (sqrt(-3u^2) - u) / 2
+    pub sqrt_neg_three_u_squared_minus_u_over_2: G::BaseField,
+    /// sqrt(-3u^2)
+    pub sqrt_neg_three_u_squared: G::BaseField,
+    /// (3u^2)^-1
+    pub inv_three_u_squared: G::BaseField,
 }
 
 /// returns the right-hand side of the Short Weierstrass curve equation for a given x
@@ -113,12 +123,9 @@ pub fn get_y<G: SWModelParameters>(x: G::BaseField) -> Option<G::BaseField> {
     fx.sqrt()
 }
 
-fn get_xy<G: SWModelParameters>(
-    params: &BWParameters<G>,
-    t: G::BaseField,
-) -> (G::BaseField, G::BaseField) {
-    let xvec = potential_xs(params, t);
-    for x in &xvec {
+/// returns the first of the potential x-coordinates that is on the curve, with its y-coordinate
+fn first_xy<G: SWModelParameters>(xvec: &[G::BaseField; 3]) -> (G::BaseField, G::BaseField) {
+    for x in xvec {
         if let Some(y) = get_y::<G>(*x) {
             return (*x, y);
         }
@@ -126,6 +133,13 @@ fn get_xy<G: SWModelParameters>(
     panic!("get_xy")
 }
 
+fn get_xy<G: SWModelParameters>(
+    params: &BWParameters<G>,
+    t: G::BaseField,
+) -> (G::BaseField, G::BaseField) {
+    first_xy::<G>(&potential_xs(params, t))
+}
+
 impl<G: SWModelParameters> GroupMap<G::BaseField> for BWParameters<G> {
     fn setup() -> Self {
         assert!(G::COEFF_A.is_zero());
@@ -183,4 +197,11 @@ impl<G: SWModelParameters> GroupMap<G::BaseField> for BWParameters<G> {
     fn to_group(&self, t: G::BaseField) -> (G::BaseField, G::BaseField) {
         get_xy(self, t)
     }
+
+    fn batch_to_group(&self, ts: &[G::BaseField]) -> Vec<(G::BaseField, G::BaseField)> {
+        self.batch_to_group_x(ts.to_vec())
+            .iter()
+            .map(first_xy::<G>)
+            .collect()
+    }
 }
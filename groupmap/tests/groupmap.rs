@@ -32,3 +32,15 @@ fn test_batch_group_map_on_curve() {
         assert!(g.is_on_curve());
     }
 }
+
+#[test]
+fn test_batch_to_group() {
+    let params = BWParameters::<G>::setup();
+    let mut ts: Vec<Fq> = (0..100).map(|_| rand::random()).collect();
+    ts.push(Fq::from(0u64));
+    let points = BWParameters::<G>::batch_to_group(&params, &ts);
+    assert_eq!(points.len(), ts.len());
+    for (t, point) in ts.iter().zip(points) {
+        assert_eq!(point, BWParameters::<G>::to_group(&params, *t));
+    }
+}
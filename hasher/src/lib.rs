@@ -156,7 +156,7 @@ pub trait Hasher<H: Hashable> {
 }
 
 /// Transform domain prefix string to field element
-fn domain_prefix_to_field<F: PrimeField>(prefix: String) -> F {
+pub fn domain_prefix_to_field<F: PrimeField>(prefix: String) -> F {
     const MAX_DOMAIN_STRING_LEN: usize = 20;
     assert!(prefix.len() <= MAX_DOMAIN_STRING_LEN);
     let prefix = &prefix[..std::cmp::min(prefix.len(), MAX_DOMAIN_STRING_LEN)];
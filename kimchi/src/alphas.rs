@@ -166,6 +166,57 @@ impl<F: Field> Alphas<F> {
     }
 }
 
+/// The range of powers of alpha registered for an [ArgumentType],
+/// as listed by [Alphas::layout].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlphasRange {
+    /// The argument using the powers of alpha.
+    /// As all gates share the same powers of alpha, they are listed as the zero gate.
+    pub argument: ArgumentType,
+    /// The exponent of alpha used by the first constraint of the argument
+    pub offset: u32,
+    /// The number of powers of alpha registered for the argument
+    pub powers: u32,
+}
+
+impl<F> Alphas<F> {
+    /// Returns the number of powers of alpha registered so far.
+    pub fn num_powers(&self) -> u32 {
+        self.next_power
+    }
+
+    /// Returns the ranges of powers of alpha of all the registered arguments,
+    /// ordered by their offset.
+    /// This layout can be serialized, so that other implementations of the verifier
+    /// can combine the constraints of each argument in the same order.
+    pub fn layout(&self) -> Vec<AlphasRange> {
+        let mut layout: Vec<_> = self
+            .mapping
+            .iter()
+            .map(|(argument, (offset, powers))| AlphasRange {
+                argument: *argument,
+                offset: *offset,
+                powers: *powers,
+            })
+            .collect();
+        layout.sort_by_key(|range| range.offset);
+        layout
+    }
+
+    /// Returns the exponent of alpha used by the `constraint`-th constraint of an [ArgumentType],
+    /// or [None] if the argument was not registered with that many constraints.
+    pub fn exponent(&self, ty: ArgumentType, constraint: u32) -> Option<u32> {
+        let ty = if matches!(ty, ArgumentType::Gate(_)) {
+            ArgumentType::Gate(GateType::Zero)
+        } else {
+            ty
+        };
+
+        let (offset, powers) = self.mapping.get(&ty)?;
+        (constraint < *powers).then(|| offset + constraint)
+    }
+}
+
 impl<T> Display for Alphas<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for arg in [
@@ -287,7 +338,7 @@ mod tests {
     fn registered_alpha_powers_for_some_constraint_twice() {
         let mut alphas = Alphas::<Fp>::default();
         alphas.register(ArgumentType::Gate(GateType::Poseidon), 2);
-        alphas.register(ArgumentType::Gate(GateType::ChaCha0), 3);
+        alphas.register(ArgumentType::Gate(GateType::CompleteAdd), 3);
     }
 
     #[test]
@@ -311,28 +362,55 @@ mod tests {
         assert_eq!(alphas.next(), Some(8.into()));
     }
 
-    // useful for the spec
-
     use crate::{
         circuits::{gate::CircuitGate, wires::Wire},
-        linearization::expr_linearization,
         prover_index::testing::new_index_for_test,
     };
 
     #[test]
-    fn get_alphas_for_spec() {
+    fn alphas_layout() {
         let gates = vec![CircuitGate::<Fp>::zero(Wire::new(0)); 2];
         let index = new_index_for_test(gates, 0);
-        let (_linearization, powers_of_alpha) = expr_linearization(
-            index.cs.chacha8.is_some(),
-            index.cs.range_check_selector_polys.is_some(),
-            index
-                .cs
-                .lookup_constraint_system
-                .as_ref()
-                .map(|lcs| &lcs.configuration),
-            index.cs.foreign_field_add_selector_poly.is_some(),
+        let powers_of_alpha = &index.powers_of_alpha;
+
+        // the ranges are contiguous and cover all the powers of alpha
+        let layout = powers_of_alpha.layout();
+        let mut next = 0;
+        for range in &layout {
+            assert_eq!(range.offset, next);
+            next += range.powers;
+        }
+        assert_eq!(next, powers_of_alpha.num_powers());
+
+        // and give the exponents of each constraint
+        for range in &layout {
+            let mut exponents = powers_of_alpha.get_exponents(range.argument, range.powers);
+            for constraint in 0..range.powers {
+                assert_eq!(
+                    powers_of_alpha.exponent(range.argument, constraint),
+                    exponents.next()
+                );
+            }
+            assert_eq!(powers_of_alpha.exponent(range.argument, range.powers), None);
+        }
+        assert_eq!(
+            powers_of_alpha.exponent(ArgumentType::Gate(GateType::Poseidon), 1),
+            powers_of_alpha.exponent(ArgumentType::Gate(GateType::Zero), 1)
         );
+
+        // the layout can be shared with other implementations
+        let serialized = serde_json::to_string(&layout).unwrap();
+        let deserialized: Vec<AlphasRange> = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, layout);
+    }
+
+    // useful for the spec
+
+    #[test]
+    fn get_alphas_for_spec() {
+        let gates = vec![CircuitGate::<Fp>::zero(Wire::new(0)); 2];
+        let index = new_index_for_test(gates, 0);
+        let powers_of_alpha = &index.powers_of_alpha;
         // make sure this is present in the specification
         let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
         let spec_path = Path::new(&manifest_dir)
@@ -86,7 +86,7 @@ impl BenchmarkCtx {
             .iter()
             .map(|proof| (&self.verifier_index, proof))
             .collect();
-        batch_verify::<Vesta, BaseSponge, ScalarSponge>(&self.group_map, &batch).unwrap();
+        batch_verify::<Vesta, BaseSponge, ScalarSponge, _>(&self.group_map, &batch).unwrap();
     }
 }
 
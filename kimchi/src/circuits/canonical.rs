@@ -0,0 +1,210 @@
+//! This module implements a canonicalization pass on circuits, for frontends
+//! (e.g. R1CS importers or DSLs) that generate their rows in no particular order.
+//!
+//! The rows are ordered deterministically, so that two circuits that only differ
+//! by the order of their rows give the same index, and thus the same verifier key.
+//!
+//! The public input rows are kept first, in their order.
+//! The other rows are split into blocks, a block starting at each row
+//! that the gate of the previous row does not constrain (i.e. is a generic or a zero gate),
+//! so that the gates that span several rows are kept with the rest of their layout.
+//! The blocks are then sorted by the gate types and coefficients of their rows,
+//! and the blocks that are alike by the blocks they are wired to,
+//! refining their order until it is stable.
+//! The blocks that remain alike keep their relative order.
+//!
+//! The wiring is updated accordingly, each permutation cycle going through its cells
+//! in the order of their rows and columns.
+
+use crate::circuits::{
+    gate::{CircuitGate, GateType},
+    wires::{Wire, COLUMNS, PERMUTS},
+};
+use ark_ff::PrimeField;
+use std::{array, ops::Range};
+
+/// A circuit, once ordered canonically
+pub struct CanonicalCircuit<F: PrimeField> {
+    /// the gates of the canonical circuit
+    pub gates: Vec<CircuitGate<F>>,
+    /// for each row of the original circuit, its row in the canonical circuit
+    pub rows: Vec<usize>,
+}
+
+impl<F: PrimeField> CanonicalCircuit<F> {
+    /// Returns the witness of the canonical circuit,
+    /// given a witness of the original circuit.
+    pub fn witness(&self, witness: &[Vec<F>; COLUMNS]) -> [Vec<F>; COLUMNS] {
+        array::from_fn(|col| {
+            let mut values = vec![F::zero(); self.rows.len()];
+            for (value, row) in witness[col].iter().zip(&self.rows) {
+                values[*row] = *value;
+            }
+            values
+        })
+    }
+}
+
+/// Returns the rank of each item among the distinct items.
+fn ranks<T: Ord>(items: Vec<T>) -> Vec<usize> {
+    let mut distinct: Vec<_> = items.iter().collect();
+    distinct.sort_unstable();
+    distinct.dedup();
+    items
+        .iter()
+        .map(|item| distinct.binary_search(&item).expect("the item is listed"))
+        .collect()
+}
+
+/// Returns the number of distinct values of `ranks`.
+fn count(ranks: &[usize]) -> usize {
+    ranks.iter().max().map_or(0, |max| max + 1)
+}
+
+/// Splits the rows of `gates` into blocks, each public input row being a block of its own.
+fn blocks<F: PrimeField>(gates: &[CircuitGate<F>], public: usize) -> Vec<Range<usize>> {
+    let mut blocks: Vec<Range<usize>> = (0..public).map(|row| row..row + 1).collect();
+    for row in public..gates.len() {
+        let constrained =
+            row > public && !matches!(gates[row - 1].typ, GateType::Generic | GateType::Zero);
+        match blocks.last_mut() {
+            Some(block) if constrained => block.end = row + 1,
+            _ => blocks.push(row..row + 1),
+        }
+    }
+    blocks
+}
+
+/// Returns the permutation cycles of the wiring of `gates`,
+/// and the cycle of each cell.
+fn cycles<F: PrimeField>(gates: &[CircuitGate<F>]) -> (Vec<Vec<Wire>>, Vec<[usize; PERMUTS]>) {
+    let mut cycles = vec![];
+    let mut cycle_of = vec![[usize::MAX; PERMUTS]; gates.len()];
+    for row in 0..gates.len() {
+        for col in 0..PERMUTS {
+            if cycle_of[row][col] != usize::MAX {
+                continue;
+            }
+            let mut cycle = vec![];
+            let mut cell = Wire { row, col };
+            while cycle_of[cell.row][cell.col] == usize::MAX {
+                cycle_of[cell.row][cell.col] = cycles.len();
+                cycle.push(cell);
+                cell = gates[cell.row].wires[cell.col];
+            }
+            cycles.push(cycle);
+        }
+    }
+    (cycles, cycle_of)
+}
+
+/// Orders the rows of `gates` canonically (see the module documentation),
+/// the first `public` rows being the public input rows.
+pub fn canonicalize<F: PrimeField>(gates: &[CircuitGate<F>], public: usize) -> CanonicalCircuit<F> {
+    let blocks = blocks(gates, public);
+    let mut block_of = vec![(0, 0); gates.len()];
+    for (i, block) in blocks.iter().enumerate() {
+        for row in block.clone() {
+            block_of[row] = (i, row - block.start);
+        }
+    }
+    let (cycles, cycle_of) = cycles(gates);
+
+    // the blocks are first told apart by the public input rows and their gates
+    let mut colors = ranks(
+        blocks
+            .iter()
+            .enumerate()
+            .map(|(i, block)| {
+                let gates: Vec<_> = gates[block.clone()]
+                    .iter()
+                    .map(|gate| (gate.typ, gate.coeffs.clone()))
+                    .collect();
+                ((i < public).then(|| i), gates)
+            })
+            .collect(),
+    );
+
+    // then by the blocks of the cells they are wired to
+    loop {
+        let cycle_colors = ranks(
+            cycles
+                .iter()
+                .map(|cycle| {
+                    let mut cells: Vec<_> = cycle
+                        .iter()
+                        .map(|cell| {
+                            let (block, offset) = block_of[cell.row];
+                            (colors[block], offset, cell.col)
+                        })
+                        .collect();
+                    cells.sort_unstable();
+                    cells
+                })
+                .collect(),
+        );
+        let refined = ranks(
+            blocks
+                .iter()
+                .zip(&colors)
+                .map(|(block, color)| {
+                    let cells: Vec<_> = block
+                        .clone()
+                        .flat_map(|row| cycle_of[row].iter().map(|cycle| cycle_colors[*cycle]))
+                        .collect();
+                    (*color, cells)
+                })
+                .collect(),
+        );
+        let stable = count(&refined) == count(&colors);
+        colors = refined;
+        if stable {
+            break;
+        }
+    }
+
+    // the public input rows stay first, and the other blocks are sorted by color
+    let mut order: Vec<_> = (public..blocks.len()).collect();
+    order.sort_by_key(|block| colors[*block]);
+    let mut rows = vec![0; gates.len()];
+    let mut next_row = 0;
+    for block in (0..public).chain(order) {
+        for row in blocks[block].clone() {
+            rows[row] = next_row;
+            next_row += 1;
+        }
+    }
+
+    // each cycle goes through its cells in order
+    let mut canonical: Vec<_> = vec![None; gates.len()];
+    for (row, gate) in gates.iter().enumerate() {
+        canonical[rows[row]] = Some(CircuitGate {
+            typ: gate.typ,
+            wires: array::from_fn(|col| Wire {
+                row: rows[row],
+                col,
+            }),
+            coeffs: gate.coeffs.clone(),
+        });
+    }
+    let mut gates: Vec<_> = canonical
+        .into_iter()
+        .map(|gate| gate.expect("every row is moved"))
+        .collect();
+    for cycle in cycles {
+        let mut cells: Vec<_> = cycle
+            .iter()
+            .map(|cell| (rows[cell.row], cell.col))
+            .collect();
+        cells.sort_unstable();
+        for (i, (row, col)) in cells.iter().enumerate() {
+            let (next_row, next_col) = cells[(i + 1) % cells.len()];
+            gates[*row].wires[*col] = Wire {
+                row: next_row,
+                col: next_col,
+            };
+        }
+    }
+
+    CanonicalCircuit { gates, rows }
+}
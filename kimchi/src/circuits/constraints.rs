@@ -3,29 +3,44 @@ use super::{gate::SelectorPolynomial, lookup::runtime_tables::RuntimeTableCfg};
 use crate::{
     circuits::{
         domain_constant_evaluation::DomainConstantEvaluations,
+        domain_plan::DomainPlan,
         domains::EvaluationDomains,
-        gate::{CircuitGate, GateType},
+        gate::{CircuitGate, Connect, GateType},
         lookup::{index::LookupConstraintSystem, tables::LookupTable},
         polynomial::{WitnessEvals, WitnessOverDomains, WitnessShifts},
-        polynomials::permutation::{Shifts, ZK_ROWS},
-        polynomials::{foreign_field_add, range_check},
+        polynomials::{
+            generic::{
+                GenericGateSpec, DOUBLE_GENERIC_COEFFS, GENERIC_COEFFS, GENERIC_GATES_REGISTERS,
+            },
+            permutation::{Shifts, ZK_ROWS},
+        },
+        run_length::RunLengthColumn,
         wires::*,
     },
     curve::KimchiCurve,
     error::SetupError,
+    linearization::compare_snapshots,
 };
 use ark_ff::{PrimeField, SquareRootField, Zero};
 use ark_poly::{
     univariate::DensePolynomial as DP, EvaluationDomain, Evaluations as E,
     Radix2EvaluationDomain as D,
 };
+use blake2::{Blake2b512, Digest};
 use num_bigint::BigUint;
 use o1_utils::{ExtendedEvaluations, FieldHelpers};
 use once_cell::sync::OnceCell;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_with::serde_as;
 use std::array;
-use std::{collections::HashSet, sync::Arc};
+use std::sync::Arc;
+
+#[cfg(feature = "foreign_field")]
+use crate::circuits::polynomials::foreign_field_add;
+#[cfg(feature = "keccak")]
+use crate::circuits::polynomials::keccak;
+#[cfg(feature = "range_check")]
+use crate::circuits::polynomials::range_check;
 
 //
 // ConstraintSystem
@@ -46,23 +61,35 @@ pub struct ConstraintSystem<F: PrimeField> {
     /// circuit gates
     #[serde(bound = "CircuitGate<F>: Serialize + DeserializeOwned")]
     pub gates: Vec<CircuitGate<F>>,
+    /// witness columns declared identically zero,
+    /// which are neither committed to nor evaluated in proofs
+    pub zero_columns: [bool; COLUMNS],
+    /// the constants pool, at the end of the circuit (see [`Builder::constant`])
+    #[serde(bound = "PoolConstant<F>: Serialize + DeserializeOwned")]
+    pub constants: Vec<PoolConstant<F>>,
 
     // Polynomials over the monomial base
     // ----------------------------------
-    /// permutation polynomial array
-    #[serde_as(as = "[o1_utils::serialization::SerdeAs; PERMUTS]")]
-    pub sigmam: [DP<F>; PERMUTS],
+    /// permutation polynomial array, one for each column taking part in the permutation
+    #[serde_as(as = "Vec<o1_utils::serialization::SerdeAs>")]
+    pub sigmam: Vec<DP<F>>,
 
     // Coefficient polynomials. These define constant that gates can use as they like.
     // ---------------------------------------
-    /// coefficients polynomials in evaluation form
-    #[serde_as(as = "[o1_utils::serialization::SerdeAs; COLUMNS]")]
-    pub coefficients8: [E<F, D<F>>; COLUMNS],
+    /// coefficients columns over domain d1, stored as runs of equal values
+    #[serde(bound = "RunLengthColumn<F>: Serialize + DeserializeOwned")]
+    pub coefficients: [RunLengthColumn<F>; COLUMNS],
+    /// coefficients polynomials in evaluation form over domain d8, computed on first use
+    #[serde(skip)]
+    coefficients8: OnceCell<[E<F, D<F>>; COLUMNS]>,
 
     // Generic constraint selector polynomials
     // ---------------------------------------
     #[serde_as(as = "o1_utils::serialization::SerdeAs")]
     pub genericm: DP<F>,
+    /// whether the circuit uses the triple generic gate,
+    /// which adds the constraint of the third generic gate to the rows of all the generic gates
+    pub triple_generic: bool,
 
     // Poseidon selector polynomials
     // -----------------------------
@@ -79,11 +106,11 @@ pub struct ConstraintSystem<F: PrimeField> {
     // permutation polynomials
     // -----------------------
     /// permutation polynomial array evaluations over domain d1
-    #[serde_as(as = "[o1_utils::serialization::SerdeAs; PERMUTS]")]
-    pub sigmal1: [E<F, D<F>>; PERMUTS],
+    #[serde_as(as = "Vec<o1_utils::serialization::SerdeAs>")]
+    pub sigmal1: Vec<E<F, D<F>>>,
     /// permutation polynomial array evaluations over domain d8
-    #[serde_as(as = "[o1_utils::serialization::SerdeAs; PERMUTS]")]
-    pub sigmal8: [E<F, D<F>>; PERMUTS],
+    #[serde_as(as = "Vec<o1_utils::serialization::SerdeAs>")]
+    pub sigmal8: Vec<E<F, D<F>>>,
     /// SID polynomial
     #[serde_as(as = "Vec<o1_utils::serialization::SerdeAs>")]
     pub sid: Vec<F>,
@@ -106,6 +133,7 @@ pub struct ConstraintSystem<F: PrimeField> {
     #[serde_as(as = "o1_utils::serialization::SerdeAs")]
     pub emull: E<F, D<F>>,
     /// ChaCha indexes
+    #[cfg(feature = "chacha")]
     #[serde_as(as = "Option<[o1_utils::serialization::SerdeAs; 4]>")]
     pub chacha8: Option<[E<F, D<F>>; 4]>,
     /// EC point addition selector evaluations w over domain.d8
@@ -113,6 +141,7 @@ pub struct ConstraintSystem<F: PrimeField> {
     pub endomul_scalar8: E<F, D<F>>,
 
     /// Range check gate selector polynomials
+    #[cfg(feature = "range_check")]
     #[serde(
         bound = "[SelectorPolynomial<F>; range_check::gadget::GATE_COUNT]: Serialize + DeserializeOwned"
     )]
@@ -122,10 +151,27 @@ pub struct ConstraintSystem<F: PrimeField> {
     /// Foreign field modulus
     pub foreign_field_modulus: Option<BigUint>,
 
+    /// Named constants of the application (e.g. a protocol version or a chain id),
+    /// bound to the proofs of the circuit through the digest of its verifier index
+    #[serde_as(as = "Vec<(_, o1_utils::serialization::SerdeAs)>")]
+    pub app_constants: Vec<(String, F)>,
+
     /// Foreign field addition gate selector polynomial
+    #[cfg(feature = "foreign_field")]
     #[serde(bound = "Option<SelectorPolynomial<F>>: Serialize + DeserializeOwned")]
     pub foreign_field_add_selector_poly: Option<SelectorPolynomial<F>>,
 
+    /// Copy gate selector polynomial, `None` if the circuit does not use the gate
+    #[serde(bound = "Option<SelectorPolynomial<F>>: Serialize + DeserializeOwned")]
+    pub copy_cells_selector_poly: Option<SelectorPolynomial<F>>,
+
+    /// Keccak gates selector polynomials, `None` if the circuit does not use the gates
+    #[cfg(feature = "keccak")]
+    #[serde(
+        bound = "[SelectorPolynomial<F>; keccak::gadget::GATE_COUNT]: Serialize + DeserializeOwned"
+    )]
+    pub keccak_selector_polys: Option<[SelectorPolynomial<F>; keccak::gadget::GATE_COUNT]>,
+
     /// wire coordinate shifts
     #[serde_as(as = "[o1_utils::serialization::SerdeAs; PERMUTS]")]
     pub shift: [F; PERMUTS],
@@ -140,6 +186,17 @@ pub struct ConstraintSystem<F: PrimeField> {
     precomputations: OnceCell<Arc<DomainConstantEvaluations<F>>>,
 }
 
+/// A constant of the constants pool of a circuit, and the cell holding it.
+#[serde_as]
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+pub struct PoolConstant<F: PrimeField> {
+    /// The constant
+    #[serde_as(as = "o1_utils::serialization::SerdeAs")]
+    pub value: F,
+    /// The cell of the pool holding the constant
+    pub cell: CellRef,
+}
+
 /// Represents an error found when verifying a witness with a gate
 #[derive(Debug)]
 pub enum GateError {
@@ -155,10 +212,17 @@ pub struct Builder<F: PrimeField> {
     gates: Vec<CircuitGate<F>>,
     public: usize,
     prev_challenges: usize,
+    permuted_columns: usize,
+    zero_columns: Vec<usize>,
     lookup_tables: Vec<LookupTable<F>>,
     runtime_tables: Option<Vec<RuntimeTableCfg<F>>>,
     precomputations: Option<Arc<DomainConstantEvaluations<F>>>,
     foreign_field_modulus: Option<BigUint>,
+    app_constants: Vec<(String, F)>,
+    shifts: Option<[F; PERMUTS]>,
+    linearization_snapshot: Option<String>,
+    connection_error: Option<SetupError>,
+    constants: Vec<(F, CellRef, String)>,
 }
 
 /// Create selector polynomial for a circuit gate
@@ -208,6 +272,8 @@ impl<F: PrimeField> ConstraintSystem<F> {
     /// It also defaults to the following values of the builder:
     /// - `public: 0`
     /// - `prev_challenges: 0`
+    /// - `permuted_columns: PERMUTS`
+    /// - `zero_columns: vec![]`
     /// - `lookup_tables: vec![]`,
     /// - `runtime_tables: None`,
     /// - `precomputations: None`,
@@ -221,16 +287,51 @@ impl<F: PrimeField> ConstraintSystem<F> {
             gates,
             public: 0,
             prev_challenges: 0,
+            permuted_columns: PERMUTS,
+            zero_columns: vec![],
             lookup_tables: vec![],
             runtime_tables: None,
             precomputations: None,
             foreign_field_modulus: None,
+            app_constants: vec![],
+            shifts: None,
+            linearization_snapshot: None,
+            connection_error: None,
+            constants: vec![],
+        }
+    }
+
+    /// Writes the constants pool in `witness`, extending the columns to the end of the pool.
+    pub fn fill_constants(&self, witness: &mut [Vec<F>; COLUMNS]) {
+        let end = self.constants.iter().map(|c| c.cell.row + 1).max();
+        for col in witness.iter_mut() {
+            match end {
+                Some(end) if col.len() < end => col.resize(end, F::zero()),
+                _ => (),
+            }
         }
+        for constant in &self.constants {
+            witness[constant.cell.col][constant.cell.row] = constant.value;
+        }
+    }
+
+    /// Returns the evaluations of the coefficient polynomials over domain d8,
+    /// interpolated from the coefficient columns on first use.
+    // TODO: This doesn't need to be degree 8 but that would require some changes in expr
+    pub fn coefficients8(&self) -> &[E<F, D<F>>; COLUMNS] {
+        self.coefficients8.get_or_init(|| {
+            array::from_fn(|i| {
+                self.coefficients[i]
+                    .evaluations(self.domain.d1)
+                    .interpolate()
+                    .evaluate_over_domain(self.domain.d8)
+            })
+        })
     }
 
     pub fn precomputations(&self) -> &Arc<DomainConstantEvaluations<F>> {
         self.precomputations
-            .get_or_init(|| Arc::new(DomainConstantEvaluations::create(self.domain).unwrap()))
+            .get_or_init(|| DomainConstantEvaluations::cached(self.domain).unwrap())
     }
 
     pub fn set_precomputations(&self, precomputations: Arc<DomainConstantEvaluations<F>>) {
@@ -239,6 +340,12 @@ impl<F: PrimeField> ConstraintSystem<F> {
             .expect("Precomputation has been set before");
     }
 
+    /// Returns the number of columns taking part in the permutation,
+    /// which are the first columns of the witness.
+    pub fn permuted_columns(&self) -> usize {
+        self.sigmam.len()
+    }
+
     /// This function verifies the consistency of the wire
     /// assignments (witness) against the constraints
     ///     witness: wire assignment witness
@@ -256,18 +363,28 @@ impl<F: PrimeField> ConstraintSystem<F> {
             w
         });
 
+        // check that the columns declared zero are
+        for col in (0..COLUMNS).filter(|col| self.zero_columns[*col]) {
+            if let Some(row) = witness[col].iter().position(|cell| !cell.is_zero()) {
+                return Err(GateError::Custom {
+                    row,
+                    err: format!("the column {col} is declared zero"),
+                });
+            }
+        }
+
         // check each rows' wiring
         for (row, gate) in self.gates.iter().enumerate() {
             // check if wires are connected
-            for col in 0..PERMUTS {
+            for col in 0..self.permuted_columns() {
                 let wire = gate.wires[col];
 
-                if wire.col >= PERMUTS {
+                if wire.col >= self.permuted_columns() {
                     return Err(GateError::Custom {
                         row,
                         err: format!(
                             "a wire can only be connected to the first {} columns",
-                            PERMUTS
+                            self.permuted_columns()
                         ),
                     });
                 }
@@ -352,6 +469,173 @@ impl<F: PrimeField + SquareRootField> Builder<F> {
         self
     }
 
+    /// Set up the number of columns taking part in the permutation,
+    /// which are the first `permuted_columns` columns of the witness.
+    /// Circuits that never wire the last columns can leave them out,
+    /// saving their permutation polynomials.
+    /// If not invoked, it equals `PERMUTS` by default.
+    pub fn permuted_columns(mut self, permuted_columns: usize) -> Self {
+        self.permuted_columns = permuted_columns;
+        self
+    }
+
+    /// Connects the cells `a` and `b`, so that they hold the same value in the witness.
+    /// Unlike [`Connect::connect_cell_pair`](crate::circuits::gate::Connect::connect_cell_pair),
+    /// the cells must be in the circuit, and not already connected
+    /// (as swapping their wires would then split them apart).
+    /// The first invalid connection is reported by [`Builder::build`],
+    /// along with the location of the call.
+    #[track_caller]
+    pub fn connect(mut self, a: impl Into<CellRef>, b: impl Into<CellRef>) -> Self {
+        let (a, b) = (a.into(), b.into());
+        if self.connection_error.is_none() {
+            if let Err(reason) = self.try_connect(a, b) {
+                let location = std::panic::Location::caller().to_string();
+                self.connection_error = Some(SetupError::Connection(a, b, reason, location));
+            }
+        }
+        self
+    }
+
+    /// Swaps the wires of the cells `a` and `b`, after checking that they are in distinct cycles.
+    fn try_connect(&mut self, a: CellRef, b: CellRef) -> Result<(), &'static str> {
+        let in_circuit = |cell: CellRef| cell.row < self.gates.len() && cell.col < PERMUTS;
+        if !in_circuit(a) || !in_circuit(b) {
+            return Err("the cells must be in the rows of the circuit, and in the wired columns");
+        }
+
+        // walk the cycle of `a`, which is bounded by the number of cells
+        let mut cell = CellRef::from(self.gates[a.row].wires[a.col]);
+        for _ in 0..self.gates.len() * PERMUTS {
+            if cell == b {
+                return Err("the cells are already connected");
+            }
+            if cell == a {
+                self.gates.connect_cell_pair(a, b);
+                return Ok(());
+            }
+            if !in_circuit(cell) {
+                return Err("the wiring of the circuit is malformed");
+            }
+            cell = self.gates[cell.row].wires[cell.col].into();
+        }
+        Err("the wiring of the circuit is malformed")
+    }
+
+    /// Pins the cell `cell` to the constant `value`.
+    /// Each distinct constant is registered once in the constants pool,
+    /// rows of generic gates appended to the circuit holding up to two constants each,
+    /// and the cells pinned to it are connected to its cell of the pool
+    /// instead of each using a generic gate.
+    /// As with [`Builder::connect`], the cell must be in the circuit and not already connected,
+    /// and the first invalid cell is reported by [`Builder::build`].
+    /// The prover fills the pool in the witness with [`ConstraintSystem::fill_constants`].
+    #[track_caller]
+    pub fn constant(mut self, value: F, cell: impl Into<CellRef>) -> Self {
+        let location = std::panic::Location::caller().to_string();
+        self.constants.push((value, cell.into(), location));
+        self
+    }
+
+    /// Returns the slots of a row of the constants pool, as the generic gate and the column
+    /// holding each constant.
+    fn constant_slots(&self) -> Vec<(usize, usize)> {
+        // each generic gate of a row holds a constant, in its left register
+        [
+            (0, GENERIC_GATES_REGISTERS[0][0]),
+            (1, GENERIC_GATES_REGISTERS[1][0]),
+        ]
+        .into_iter()
+        .filter(|(_, col)| *col < self.permuted_columns)
+        .collect()
+    }
+
+    /// Returns the plan of the evaluation domains that [`Builder::build`] creates,
+    /// which counts the rows of the constants pool and the lookup tables (see [`DomainPlan`]).
+    pub fn domain_plan(&self) -> DomainPlan {
+        let mut constants: Vec<_> = self.constants.iter().map(|(value, _, _)| *value).collect();
+        constants.sort_unstable();
+        constants.dedup();
+        let slots = self.constant_slots().len().max(1);
+        let pool_rows = (constants.len() + slots - 1) / slots;
+
+        let plan = DomainPlan::for_circuit(
+            &self.gates,
+            &self.lookup_tables,
+            self.runtime_tables.as_deref(),
+        );
+        DomainPlan::new(plan.rows + pool_rows, plan.lookup_entries, plan.zk_rows)
+    }
+
+    /// Appends the rows of the constants pool to the circuit,
+    /// and connects the pinned cells to the cells of the pool.
+    fn append_constants_pool(&mut self) -> Result<Vec<PoolConstant<F>>, SetupError> {
+        let slots = self.constant_slots();
+
+        let start = self.gates.len();
+        let mut pool: Vec<PoolConstant<F>> = vec![];
+        let mut rows: Vec<[F; DOUBLE_GENERIC_COEFFS]> = vec![];
+        let mut pinned = vec![];
+        for (value, cell, location) in std::mem::take(&mut self.constants) {
+            if cell.row >= start || cell.col >= self.permuted_columns {
+                return Err(SetupError::Constant(
+                    cell,
+                    "the cell must be in the rows of the circuit, and in the wired columns",
+                    location,
+                ));
+            }
+            if pinned.iter().any(|(c, _, _)| *c == cell) {
+                return Err(SetupError::Constant(
+                    cell,
+                    "the cell is already pinned to a constant",
+                    location,
+                ));
+            }
+
+            let pool_cell = match pool.iter().find(|c| c.value == value) {
+                Some(constant) => constant.cell,
+                None => {
+                    let (row, (gate, col)) =
+                        (pool.len() / slots.len(), slots[pool.len() % slots.len()]);
+                    if row == rows.len() {
+                        rows.push([F::zero(); DOUBLE_GENERIC_COEFFS]);
+                    }
+                    let coeffs = GenericGateSpec::Const(value)
+                        .coeffs(GateType::Generic, gate == 0)
+                        .expect("a constant is not a public input");
+                    rows[row][gate * GENERIC_COEFFS..(gate + 1) * GENERIC_COEFFS]
+                        .copy_from_slice(&coeffs);
+                    let cell = CellRef::new(start + row, col);
+                    pool.push(PoolConstant { value, cell });
+                    cell
+                }
+            };
+            pinned.push((cell, pool_cell, location));
+        }
+
+        for (i, coeffs) in rows.into_iter().enumerate() {
+            self.gates.push(CircuitGate {
+                typ: GateType::Generic,
+                wires: Wire::new(start + i),
+                coeffs: coeffs.to_vec(),
+            });
+        }
+        for (cell, pool_cell, location) in pinned {
+            self.try_connect(cell, pool_cell)
+                .map_err(|reason| SetupError::Constant(cell, reason, location))?;
+        }
+        Ok(pool)
+    }
+
+    /// Set up the witness columns that are identically zero in all the witnesses of the circuit.
+    /// These columns are neither committed to nor evaluated in proofs,
+    /// and the verifier takes them to be zero.
+    /// If not invoked, it is `vec![]` by default.
+    pub fn zero_columns(mut self, zero_columns: Vec<usize>) -> Self {
+        self.zero_columns = zero_columns;
+        self
+    }
+
     /// Set up the lookup tables.
     /// If not invoked, it is `vec![]` by default.
     ///
@@ -399,24 +683,159 @@ impl<F: PrimeField + SquareRootField> Builder<F> {
         self
     }
 
+    /// Adds a named constant of the application (e.g. a protocol version or a chain id).
+    /// The constants are absorbed in the digest of the verifier index,
+    /// so that proofs only verify against an index holding the same constants,
+    /// without taking any public input.
+    pub fn app_constant(mut self, name: impl Into<String>, value: F) -> Self {
+        self.app_constants.push((name.into(), value));
+        self
+    }
+
+    /// Sets custom shifts for the permutation, in place of the derived ones
+    /// (see [`derive_shifts`](crate::circuits::polynomials::permutation::derive_shifts)).
+    /// The shifts are checked when building, and absorbed in the digest of the verifier index.
+    /// If not invoked, the shifts are derived from the domain.
+    pub fn shifts(mut self, shifts: [F; PERMUTS]) -> Self {
+        self.shifts = Some(shifts);
+        self
+    }
+
+    /// Sets the expected snapshot of the linearization of the circuit
+    /// (see [`linearization_snapshot`](crate::linearization::linearization_snapshot)),
+    /// typically committed along with the circuit.
+    /// Building fails if the linearization does not match it,
+    /// so that an accidental change of the constraints is caught before any proof is made.
+    pub fn linearization_snapshot(mut self, snapshot: impl Into<String>) -> Self {
+        self.linearization_snapshot = Some(snapshot.into());
+        self
+    }
+
+    /// Returns a digest of the circuit described by the builder,
+    /// covering everything the resulting [ConstraintSystem] depends on
+    /// (the shared precomputations being only a cache, they are left out).
+    pub fn digest(&self) -> [u8; 32] {
+        let mut h = Blake2b512::new();
+        let int = |h: &mut Blake2b512, n: usize| h.update((n as u64).to_le_bytes());
+        let field = |h: &mut Blake2b512, x: &F| h.update(x.to_bytes());
+
+        h.update(rmp_serde::to_vec(&self.gates).expect("gates are serializable"));
+        int(&mut h, self.constants.len());
+        for (value, cell, _) in &self.constants {
+            field(&mut h, value);
+            int(&mut h, cell.row);
+            int(&mut h, cell.col);
+        }
+        int(&mut h, self.public);
+        int(&mut h, self.prev_challenges);
+        int(&mut h, self.permuted_columns);
+        int(&mut h, self.zero_columns.len());
+        for col in &self.zero_columns {
+            int(&mut h, *col);
+        }
+
+        int(&mut h, self.lookup_tables.len());
+        for table in &self.lookup_tables {
+            h.update(table.id.to_le_bytes());
+            int(&mut h, table.data.len());
+            for column in &table.data {
+                int(&mut h, column.len());
+                column.iter().for_each(|x| field(&mut h, x));
+            }
+        }
+
+        match &self.runtime_tables {
+            None => h.update([0]),
+            Some(runtime_tables) => {
+                h.update([1]);
+                int(&mut h, runtime_tables.len());
+                for cfg in runtime_tables {
+                    match cfg {
+                        RuntimeTableCfg::Indexed(spec) => {
+                            h.update([0]);
+                            h.update(spec.id.to_le_bytes());
+                            int(&mut h, spec.len);
+                            h.update([u8::from(spec.variable_len)]);
+                        }
+                        RuntimeTableCfg::Custom { id, first_column } => {
+                            h.update([1]);
+                            h.update(id.to_le_bytes());
+                            int(&mut h, first_column.len());
+                            first_column.iter().for_each(|x| field(&mut h, x));
+                        }
+                    }
+                }
+            }
+        }
+
+        match &self.foreign_field_modulus {
+            None => h.update([0]),
+            Some(modulus) => {
+                h.update([1]);
+                let bytes = modulus.to_bytes_le();
+                int(&mut h, bytes.len());
+                h.update(bytes);
+            }
+        }
+
+        int(&mut h, self.app_constants.len());
+        for (name, value) in &self.app_constants {
+            int(&mut h, name.len());
+            h.update(name.as_bytes());
+            field(&mut h, value);
+        }
+
+        // nothing is added without custom shifts, so that the digest of other circuits is unchanged
+        if let Some(shifts) = &self.shifts {
+            shifts.iter().for_each(|x| field(&mut h, x));
+        }
+
+        let mut digest = [0; 32];
+        digest.copy_from_slice(&h.finalize()[..32]);
+        digest
+    }
+
     /// Build the [ConstraintSystem] from a [Builder].
-    pub fn build(self) -> Result<ConstraintSystem<F>, SetupError> {
+    pub fn build(mut self) -> Result<ConstraintSystem<F>, SetupError> {
+        if let Some(err) = self.connection_error {
+            return Err(err);
+        }
+        for (i, (name, _)) in self.app_constants.iter().enumerate() {
+            if self.app_constants[..i]
+                .iter()
+                .any(|(other, _)| other == name)
+            {
+                return Err(SetupError::ConstraintSystem(format!(
+                    "the application constant {name} is defined twice"
+                )));
+            }
+        }
+
+        //~ 1. Append the constants pool to the circuit: rows of generic gates
+        //~    holding each distinct constant the circuit pins cells to,
+        //~    up to two per row (one per generic gate of the row),
+        //~    and wire each pinned cell to the cell of its constant.
+        let constants = self.append_constants_pool()?;
+
         let mut gates = self.gates;
         let lookup_tables = self.lookup_tables;
         let runtime_tables = self.runtime_tables;
 
-        //~ 1. If the circuit is less than 2 gates, abort.
+        //~ 2. If the circuit is less than 2 gates, abort.
         // for some reason we need more than 1 gate for the circuit to work, see TODO below
         assert!(gates.len() > 1);
 
-        //~ 2. Create a domain for the circuit. That is,
+        //~ 3. Create a domain for the circuit. That is,
         //~    compute the smallest subgroup of the field that
-        //~    has order greater or equal to `n + ZK_ROWS` elements.
-        let domain = EvaluationDomains::<F>::create(gates.len() + ZK_ROWS as usize)?;
+        //~    has order greater or equal to `n + ZK_ROWS` elements,
+        //~    and, if the circuit uses lookups, to the number of entries of all the lookup tables
+        //~    plus `ZK_ROWS + 2` elements (see [`DomainPlan`]).
+        let plan = DomainPlan::for_circuit(&gates, &lookup_tables, runtime_tables.as_deref());
+        let domain = plan.domains::<F>()?;
 
         assert!(domain.d1.size > ZK_ROWS);
 
-        //~ 3. Pad the circuit: add zero gates to reach the domain size.
+        //~ 4. Pad the circuit: add zero gates to reach the domain size.
         let d1_size = domain.d1.size();
         let mut padding = (gates.len()..d1_size)
             .map(|i| {
@@ -429,13 +848,43 @@ impl<F: PrimeField + SquareRootField> Builder<F> {
         gates.append(&mut padding);
 
         // Record which gates are used by this constraint system
-        let mut circuit_gates_used = HashSet::<GateType>::default();
-        gates.iter().for_each(|gate| {
-            circuit_gates_used.insert(gate.typ);
-        });
+        let circuit_gates_used: std::collections::HashSet<GateType> =
+            gates.iter().map(|gate| gate.typ).collect();
+
+        //~ 5. Check that the gates only wire the columns taking part in the permutation.
+        let permuted_columns = self.permuted_columns;
+        if permuted_columns == 0 || permuted_columns > PERMUTS {
+            return Err(SetupError::ConstraintSystem(format!(
+                "the number of permuted columns should be between 1 and {PERMUTS}, got {permuted_columns}"
+            )));
+        }
+        for (row, gate) in gates.iter().enumerate() {
+            for (col, wire) in gate.wires.iter().enumerate() {
+                let permuted = col < permuted_columns && wire.col < permuted_columns;
+                if !permuted && *wire != (Wire { row, col }) {
+                    return Err(SetupError::ConstraintSystem(format!(
+                        "the cell ({row}, {col}) is wired but only the first {permuted_columns} columns take part in the permutation"
+                    )));
+                }
+            }
+        }
+
+        //~ 6. Check that the columns declared zero exist.
+        let mut zero_columns = [false; COLUMNS];
+        for col in self.zero_columns {
+            if col >= COLUMNS {
+                return Err(SetupError::ConstraintSystem(format!(
+                    "cannot declare the column {col} zero, as there are only {COLUMNS} columns"
+                )));
+            }
+            zero_columns[col] = true;
+        }
 
-        //~ 4. sample the `PERMUTS` shifts.
-        let shifts = Shifts::new(&domain.d1);
+        //~ 7. sample the `PERMUTS` shifts, unless custom shifts are given, and check them.
+        let shifts = match self.shifts {
+            None => Shifts::new(&domain.d1),
+            Some(shifts) => Shifts::custom(&domain.d1, shifts).map_err(SetupError::Shifts)?,
+        };
 
         // Precomputations
         // ===============
@@ -445,31 +894,23 @@ impl<F: PrimeField + SquareRootField> Builder<F> {
         // Permutation
         // -----------
 
-        // compute permutation polynomials
-        let mut sigmal1: [Vec<F>; PERMUTS] = array::from_fn(|_| vec![F::zero(); domain.d1.size()]);
-
-        for (row, gate) in gates.iter().enumerate() {
-            for (cell, sigma) in gate.wires.iter().zip(sigmal1.iter_mut()) {
-                sigma[row] = shifts.cell_to_field(cell);
-            }
-        }
-
-        let sigmal1: [_; PERMUTS] = {
-            let [s0, s1, s2, s3, s4, s5, s6] = sigmal1;
-            [
-                E::<F, D<F>>::from_vec_and_domain(s0, domain.d1),
-                E::<F, D<F>>::from_vec_and_domain(s1, domain.d1),
-                E::<F, D<F>>::from_vec_and_domain(s2, domain.d1),
-                E::<F, D<F>>::from_vec_and_domain(s3, domain.d1),
-                E::<F, D<F>>::from_vec_and_domain(s4, domain.d1),
-                E::<F, D<F>>::from_vec_and_domain(s5, domain.d1),
-                E::<F, D<F>>::from_vec_and_domain(s6, domain.d1),
-            ]
-        };
+        // compute permutation polynomials, for the permuted columns only
+        let sigmal1: Vec<_> = (0..permuted_columns)
+            .map(|col| {
+                let sigma = gates
+                    .iter()
+                    .map(|gate| shifts.cell_to_field(&gate.wires[col]))
+                    .collect();
+                E::<F, D<F>>::from_vec_and_domain(sigma, domain.d1)
+            })
+            .collect();
 
-        let sigmam: [DP<F>; PERMUTS] = array::from_fn(|i| sigmal1[i].clone().interpolate());
+        let sigmam: Vec<DP<F>> = sigmal1.iter().map(|s| s.clone().interpolate()).collect();
 
-        let sigmal8 = array::from_fn(|i| sigmam[i].evaluate_over_domain_by_ref(domain.d8));
+        let sigmal8 = sigmam
+            .iter()
+            .map(|s| s.evaluate_over_domain_by_ref(domain.d8))
+            .collect();
 
         // Gates
         // -----
@@ -522,12 +963,12 @@ impl<F: PrimeField + SquareRootField> Builder<F> {
         .interpolate();
         let endomul_scalar8 = endomul_scalarm.evaluate_over_domain_by_ref(domain.d8);
 
-        // double generic gate
+        // double and triple generic gates
         let genericm = E::<F, D<F>>::from_vec_and_domain(
             gates
                 .iter()
                 .map(|gate| {
-                    if matches!(gate.typ, GateType::Generic) {
+                    if matches!(gate.typ, GateType::Generic | GateType::TripleGeneric) {
                         F::one()
                     } else {
                         F::zero()
@@ -538,8 +979,10 @@ impl<F: PrimeField + SquareRootField> Builder<F> {
         )
         .interpolate();
         let generic4 = genericm.evaluate_over_domain_by_ref(domain.d4);
+        let triple_generic = circuit_gates_used.contains(&GateType::TripleGeneric);
 
         // chacha gate
+        #[cfg(feature = "chacha")]
         let chacha8 = {
             use GateType::*;
             let has_chacha_gate = gates
@@ -571,8 +1014,9 @@ impl<F: PrimeField + SquareRootField> Builder<F> {
         };
 
         // Range check constraint selector polynomials
-        let range_gates = range_check::gadget::circuit_gates();
+        #[cfg(feature = "range_check")]
         let range_check_selector_polys = {
+            let range_gates = range_check::gadget::circuit_gates();
             if circuit_gates_used.is_disjoint(&range_gates.into_iter().collect()) {
                 None
             } else {
@@ -583,8 +1027,9 @@ impl<F: PrimeField + SquareRootField> Builder<F> {
         };
 
         // Foreign field addition constraint selector polynomial
-        let ffadd_gates = foreign_field_add::gadget::circuit_gates();
+        #[cfg(feature = "foreign_field")]
         let foreign_field_add_selector_poly = {
+            let ffadd_gates = foreign_field_add::gadget::circuit_gates();
             if circuit_gates_used.is_disjoint(&ffadd_gates.into_iter().collect()) {
                 None
             } else {
@@ -592,23 +1037,37 @@ impl<F: PrimeField + SquareRootField> Builder<F> {
             }
         };
 
+        // Copy gate selector polynomial
+        let copy_cells_selector_poly = circuit_gates_used
+            .contains(&GateType::CopyCells)
+            .then(|| selector_polynomial(GateType::CopyCells, &gates, &domain));
+
+        // Keccak gates selector polynomials
+        #[cfg(feature = "keccak")]
+        let keccak_selector_polys = {
+            let keccak_gates = keccak::gadget::circuit_gates();
+            if circuit_gates_used.is_disjoint(&keccak_gates.into_iter().collect()) {
+                None
+            } else {
+                Some(array::from_fn(|i| {
+                    selector_polynomial(keccak_gates[i], &gates, &domain)
+                }))
+            }
+        };
+
         //
         // Coefficient
         // -----------
         //
 
-        // coefficient polynomial
-        let coefficientsm: [_; COLUMNS] = array::from_fn(|i| {
-            let padded = gates
-                .iter()
-                .map(|gate| gate.coeffs.get(i).cloned().unwrap_or_else(F::zero))
-                .collect();
-            let eval = E::from_vec_and_domain(padded, domain.d1);
-            eval.interpolate()
+        // coefficient columns
+        let coefficients = array::from_fn(|i| {
+            RunLengthColumn::new(
+                gates
+                    .iter()
+                    .map(|gate| gate.coeffs.get(i).cloned().unwrap_or_else(F::zero)),
+            )
         });
-        // TODO: This doesn't need to be degree 8 but that would require some changes in expr
-        let coefficients8 =
-            array::from_fn(|i| coefficientsm[i].evaluate_over_domain_by_ref(domain.d8));
 
         //
         // Lookup
@@ -625,26 +1084,37 @@ impl<F: PrimeField + SquareRootField> Builder<F> {
         let domain_constant_evaluation = OnceCell::new();
 
         let constraints = ConstraintSystem {
+            #[cfg(feature = "chacha")]
             chacha8,
             endomul_scalar8,
             domain,
             public: self.public,
             prev_challenges: self.prev_challenges,
+            zero_columns,
+            constants,
             sid,
             sigmal1,
             sigmal8,
             sigmam,
             genericm,
+            triple_generic,
             generic4,
-            coefficients8,
+            coefficients,
+            coefficients8: OnceCell::new(),
             ps8,
             psm,
             complete_addl4,
             mull8,
             emull,
+            #[cfg(feature = "range_check")]
             range_check_selector_polys,
+            #[cfg(feature = "foreign_field")]
             foreign_field_add_selector_poly,
+            copy_cells_selector_poly,
+            #[cfg(feature = "keccak")]
+            keccak_selector_polys,
             foreign_field_modulus: self.foreign_field_modulus,
+            app_constants: self.app_constants,
             gates,
             shift: shifts.shifts,
             endo,
@@ -653,6 +1123,20 @@ impl<F: PrimeField + SquareRootField> Builder<F> {
             precomputations: domain_constant_evaluation,
         };
 
+        //~ 8. Check the invariants of each gate of the padded circuit:
+        //~    its number of coefficients, that its wires stay within the circuit,
+        //~    and that the gates whose constraints span several rows are followed by the rest of their layout.
+        for (row, gate) in constraints.gates.iter().enumerate() {
+            gate.validate(row, &constraints)
+                .map_err(|e| SetupError::Gate(row, e))?;
+        }
+
+        //~ 9. If a snapshot of the linearization is expected, check that the linearization matches it.
+        if let Some(expected) = &self.linearization_snapshot {
+            compare_snapshots(expected, &constraints.linearization_snapshot())
+                .map_err(SetupError::LinearizationSnapshot)?;
+        }
+
         match self.precomputations {
             Some(t) => {
                 constraints.set_precomputations(t);
@@ -1,14 +1,24 @@
 //! This contains the [DomainConstantEvaluations] which is used to provide precomputations to a [ConstraintSystem](super::constraints::ConstraintSystem).
+//!
+//! The precomputations are shared by the constraint systems over the same field and domain
+//! (see [DomainConstantEvaluations::cached]).
+//! The twiddle factors of the FFTs are not part of them:
+//! the FFTs of arkworks compute their twiddle factors themselves, and do not take precomputed ones.
 
 use crate::circuits::domains::EvaluationDomains;
+use crate::circuits::expr::l0_1;
 use crate::circuits::polynomials::permutation::zk_polynomial;
 use crate::circuits::polynomials::permutation::ZK_ROWS;
 use ark_ff::FftField;
 use ark_poly::EvaluationDomain;
 use ark_poly::UVPolynomial;
 use ark_poly::{univariate::DensePolynomial as DP, Evaluations as E, Radix2EvaluationDomain as D};
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
 
 use super::polynomials::permutation::vanishes_on_last_4_rows;
 
@@ -34,8 +44,18 @@ pub struct DomainConstantEvaluations<F: FftField> {
     pub zkpl: E<F, D<F>>,
     #[serde_as(as = "o1_utils::serialization::SerdeAs")]
     pub zkpm: DP<F>,
+    /// `prod_{j != 0} (1 - omega^j)`, used to compute the unnormalized Lagrange basis
+    #[serde_as(as = "o1_utils::serialization::SerdeAs")]
+    pub l0_1: F,
 }
 
+/// Precomputations of any field, indexed by field and domain size,
+/// only referenced weakly so that they are freed with the last constraint system using them
+type Cache = HashMap<(TypeId, u64), Weak<dyn Any + Send + Sync>>;
+
+/// The precomputations shared by all the constraint systems of the process
+static CACHE: Lazy<Mutex<Cache>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
 impl<F: FftField> DomainConstantEvaluations<F> {
     pub fn create(domain: EvaluationDomains<F>) -> Option<Self> {
         let poly_x_d1 = DP::from_coefficients_slice(&[F::zero(), F::one()])
@@ -61,6 +81,24 @@ impl<F: FftField> DomainConstantEvaluations<F> {
             vanishes_on_last_4_rows,
             zkpl,
             zkpm,
+            l0_1: l0_1(domain.d1),
         })
     }
+
+    /// Returns the precomputations for `domain`, creating them only if
+    /// no precomputations for this field and domain size are in use in the process.
+    /// The precomputations are freed when the last of their users drops them,
+    /// so that the cache does not grow with all the domains ever used.
+    pub fn cached(domain: EvaluationDomains<F>) -> Option<Arc<Self>> {
+        let key = (TypeId::of::<F>(), domain.d1.size);
+        let mut cache = CACHE.lock().unwrap();
+        if let Some(cached) = cache.get(&key).and_then(Weak::upgrade) {
+            return cached.downcast().ok();
+        }
+        let precomputations = Arc::new(Self::create(domain)?);
+        cache.retain(|_, cached| cached.strong_count() > 0);
+        let shared: Arc<dyn Any + Send + Sync> = precomputations.clone();
+        cache.insert(key, Arc::downgrade(&shared));
+        Some(precomputations)
+    }
 }
@@ -0,0 +1,178 @@
+//! This module implements the planning of the evaluation domains of a circuit.
+//!
+//! The domain `d1` must hold the rows of the circuit followed by the [`ZK_ROWS`] rows,
+//! but also, when lookups are used, the concatenation of all the lookup tables
+//! (the tables of the gates, the fixed tables and the runtime tables),
+//! followed by the [`ZK_ROWS`] rows, the row asserting the final product of the lookup argument
+//! and the dummy entry.
+//! A small circuit using the range check gate (with a table of 4096 entries) thus gets
+//! a domain of 8192 rows.
+//!
+//! A [`DomainPlan`] makes this choice explicit: it gives the size of the domains,
+//! which of the rows or the lookup tables determine them, the padding this adds to the circuit,
+//! and the smallest SRS that can commit to the circuit without chunking.
+//! It can be printed as a short report.
+
+use crate::circuits::{
+    domains::EvaluationDomains,
+    gate::CircuitGate,
+    lookup::{
+        lookups::LookupInfo,
+        runtime_tables::RuntimeTableCfg,
+        tables::{get_table, LookupTable},
+    },
+    polynomials::permutation::ZK_ROWS,
+};
+use crate::error::SetupError;
+use ark_ff::{FftField, PrimeField};
+use std::{collections::HashSet, fmt};
+
+/// What determines the size of the domain of a circuit
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DomainLimit {
+    /// the rows of the circuit
+    Rows,
+    /// the entries of the lookup tables
+    LookupTables,
+}
+
+/// The evaluation domains chosen for a circuit (see the module documentation)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DomainPlan {
+    /// the number of rows of the circuit (before padding)
+    pub rows: usize,
+    /// the number of entries of all the lookup tables (zero if the circuit does not use lookups)
+    pub lookup_entries: usize,
+    /// the number of rows reserved for zero-knowledge
+    pub zk_rows: usize,
+    /// the size of the domain `d1`, and thus of the padded circuit
+    pub d1_size: usize,
+    /// what determines the size of `d1`
+    pub limit: DomainLimit,
+}
+
+impl DomainPlan {
+    /// Plans the domains of a circuit of `rows` rows, with lookup tables of `lookup_entries`
+    /// entries in total (zero without lookups), and `zk_rows` zero-knowledge rows.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the domain is larger than `usize` can represent.
+    pub fn new(rows: usize, lookup_entries: usize, zk_rows: usize) -> Self {
+        let rows_min = rows + zk_rows;
+        // the final product of the lookup argument and the dummy entry take a row each
+        let lookups_min = if lookup_entries == 0 {
+            0
+        } else {
+            lookup_entries + zk_rows + 2
+        };
+
+        let (min, limit) = if lookups_min > rows_min.next_power_of_two() {
+            (lookups_min, DomainLimit::LookupTables)
+        } else {
+            (rows_min, DomainLimit::Rows)
+        };
+        Self {
+            rows,
+            lookup_entries,
+            zk_rows,
+            d1_size: min.max(2).next_power_of_two(),
+            limit,
+        }
+    }
+
+    /// Plans the domains of the circuit made of `gates`, with the fixed `lookup_tables`
+    /// and the `runtime_tables`, as [`crate::circuits::constraints::ConstraintSystem`] does.
+    /// The lookup tables are only counted if a gate of the circuit uses lookups.
+    pub fn for_circuit<F: PrimeField>(
+        gates: &[CircuitGate<F>],
+        lookup_tables: &[LookupTable<F>],
+        runtime_tables: Option<&[RuntimeTableCfg<F>]>,
+    ) -> Self {
+        let lookup_entries = match LookupInfo::create_from_gates(gates, runtime_tables.is_some()) {
+            Some(info) if info.lookup_used().is_some() => {
+                let gate_tables: HashSet<_> =
+                    info.kinds.iter().filter_map(|kind| kind.table()).collect();
+                let gate_entries: usize = gate_tables
+                    .into_iter()
+                    .map(|table| get_table::<F>(table).len())
+                    .sum();
+                let fixed_entries: usize = lookup_tables.iter().map(LookupTable::len).sum();
+                let runtime_entries: usize = runtime_tables
+                    .into_iter()
+                    .flatten()
+                    .map(RuntimeTableCfg::len)
+                    .sum();
+                gate_entries + fixed_entries + runtime_entries
+            }
+            _ => 0,
+        };
+        Self::new(gates.len(), lookup_entries, ZK_ROWS as usize)
+    }
+
+    /// Returns the size of the domain `d4`.
+    pub fn d4_size(&self) -> usize {
+        4 * self.d1_size
+    }
+
+    /// Returns the size of the domain `d8`.
+    pub fn d8_size(&self) -> usize {
+        8 * self.d1_size
+    }
+
+    /// Returns the number of rows added to the circuit to reach the size of `d1`.
+    pub fn padding(&self) -> usize {
+        self.d1_size - self.rows
+    }
+
+    /// Returns the share of `d1` taken by the padding, in percent.
+    pub fn overhead(&self) -> f64 {
+        100. * self.padding() as f64 / self.d1_size as f64
+    }
+
+    /// Returns the smallest SRS size with which the polynomials of the circuit
+    /// are committed to without being split in chunks.
+    pub fn srs_size(&self) -> usize {
+        self.d1_size
+    }
+
+    /// Creates the evaluation domains of the plan.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the field does not have a domain of the planned size.
+    pub fn domains<F: FftField>(&self) -> Result<EvaluationDomains<F>, SetupError> {
+        EvaluationDomains::<F>::create(self.d1_size)
+    }
+}
+
+impl fmt::Display for DomainPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "domains: d1 = {}, d4 = {}, d8 = {}",
+            self.d1_size,
+            self.d4_size(),
+            self.d8_size()
+        )?;
+        match self.limit {
+            DomainLimit::Rows => writeln!(
+                f,
+                "limited by the rows: {} rows and {} zero-knowledge rows",
+                self.rows, self.zk_rows
+            )?,
+            DomainLimit::LookupTables => writeln!(
+                f,
+                "limited by the lookup tables: {} entries, {} zero-knowledge rows and 2 reserved rows (the circuit has {} rows)",
+                self.lookup_entries, self.zk_rows, self.rows
+            )?,
+        }
+        writeln!(
+            f,
+            "padding: {} rows ({:.1}% of d1)",
+            self.padding(),
+            self.overhead()
+        )?;
+        write!(f, "minimal SRS size: {}", self.srs_size())
+    }
+}
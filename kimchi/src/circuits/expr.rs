@@ -7,6 +7,7 @@ use crate::{
         wires::COLUMNS,
     },
     proof::ProofEvaluations,
+    verifier_math::unnormalized_lagrange_basis,
 };
 use ark_ff::{FftField, Field, One, PrimeField, Zero};
 use ark_poly::{
@@ -152,16 +153,6 @@ pub fn l0_1<F: FftField>(d: D<F>) -> F {
         .fold(F::one(), |acc, omega_j| acc * (F::one() - omega_j))
 }
 
-// Compute the ith unnormalized lagrange basis
-fn unnormalized_lagrange_basis<F: FftField>(domain: &D<F>, i: i32, pt: &F) -> F {
-    let omega_i = if i < 0 {
-        domain.group_gen.pow(&[-i as u64]).inverse().unwrap()
-    } else {
-        domain.group_gen.pow(&[i as u64])
-    };
-    domain.evaluate_vanishing_polynomial(*pt) / (*pt - omega_i)
-}
-
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 /// A type representing one of the polynomials involved in the PLONK IOP.
 pub enum Column {
@@ -202,7 +193,7 @@ impl Column {
         }
     }
 
-    fn text(&self) -> String {
+    pub(crate) fn text(&self) -> String {
         match self {
             Column::Witness(i) => format!("w[{i}]"),
             Column::Z => "Z".to_string(),
@@ -243,7 +234,7 @@ impl Variable {
         }
     }
 
-    fn text(&self) -> String {
+    pub(crate) fn text(&self) -> String {
         let col = self.col.text();
         match self.row {
             Curr => format!("Curr({col})"),
@@ -2539,10 +2530,10 @@ pub mod test {
                 foreign_field_modulus: None,
             },
             witness: &domain_evals.d8.this.w,
-            coefficient: &constraint_system.coefficients8,
+            coefficient: constraint_system.coefficients8(),
             vanishes_on_last_4_rows: &constraint_system.precomputations().vanishes_on_last_4_rows,
             z: &domain_evals.d8.this.z,
-            l0_1: l0_1(constraint_system.domain.d1),
+            l0_1: constraint_system.precomputations().l0_1,
             domain: constraint_system.domain,
             index: HashMap::new(),
             lookup: None,
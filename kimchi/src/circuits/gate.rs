@@ -1,12 +1,21 @@
 //! This module implements Plonk constraint gate primitive.
 
+#[cfg(feature = "chacha")]
+use crate::circuits::polynomials::chacha;
+#[cfg(feature = "foreign_field")]
+use crate::circuits::polynomials::foreign_field_add;
+#[cfg(feature = "keccak")]
+use crate::circuits::polynomials::keccak;
+#[cfg(feature = "range_check")]
+use crate::circuits::polynomials::range_check;
+#[cfg(feature = "cairo")]
+use crate::circuits::polynomials::turshi;
 use crate::{
     circuits::{
         argument::{Argument, ArgumentEnv},
         constraints::ConstraintSystem,
         polynomials::{
-            chacha, complete_add, endomul_scalar, endosclmul, foreign_field_add, poseidon,
-            range_check, turshi, varbasemul,
+            complete_add, copy_cells, endomul_scalar, endosclmul, generic, poseidon, varbasemul,
         },
         wires::*,
     },
@@ -93,22 +102,44 @@ pub enum GateType {
     /// Gate for computing the scalar corresponding to an endoscaling
     EndoMulScalar = 6,
     /// ChaCha
+    #[cfg(feature = "chacha")]
     ChaCha0 = 7,
+    #[cfg(feature = "chacha")]
     ChaCha1 = 8,
+    #[cfg(feature = "chacha")]
     ChaCha2 = 9,
+    #[cfg(feature = "chacha")]
     ChaChaFinal = 10,
     // Lookup
     Lookup = 11,
     /// Cairo
+    #[cfg(feature = "cairo")]
     CairoClaim = 12,
+    #[cfg(feature = "cairo")]
     CairoInstruction = 13,
+    #[cfg(feature = "cairo")]
     CairoFlags = 14,
+    #[cfg(feature = "cairo")]
     CairoTransition = 15,
     /// Range check (16-24)
+    #[cfg(feature = "range_check")]
     RangeCheck0 = 16,
+    #[cfg(feature = "range_check")]
     RangeCheck1 = 17,
+    #[cfg(feature = "foreign_field")]
     ForeignFieldAdd = 25,
     //ForeignFieldMul = 26,
+    /// Lookups into several tables, with a table ID per lookup
+    MultiTableLookup = 27,
+    /// Copies of the non-permuted cells of the next row into permuted cells
+    CopyCells = 28,
+    /// Keccak
+    #[cfg(feature = "keccak")]
+    Xor16 = 29,
+    #[cfg(feature = "keccak")]
+    Rot64 = 30,
+    /// Generic arithmetic gate with a third generic gate, on registers of its own
+    TripleGeneric = 31,
 }
 
 /// Selector polynomial
@@ -153,6 +184,33 @@ pub enum CircuitGateError {
     /// Failed to get witness for row
     #[error("Failed to get {0:?} witness for row {1}")]
     FailedToGetWitnessForRow(GateType, usize),
+    /// No witness fill-in for the gate type
+    #[error("No witness fill-in registered for {0:?}")]
+    MissingWitnessFiller(GateType),
+    /// Invalid number of witness inputs
+    #[error("Invalid number of {0:?} witness inputs: expected {1}, got {2}")]
+    WitnessInputCount(GateType, usize, usize),
+    /// Invalid witness inputs
+    #[error("Invalid {0:?} witness inputs")]
+    InvalidWitnessInputs(GateType),
+    /// Invalid number of coefficients
+    #[error("Invalid number of {0:?} coefficients: {1}")]
+    CoefficientCount(GateType, usize),
+    /// Wire to a row outside of the circuit
+    #[error("Invalid {0:?} wire of column {1}: the row {2} is outside of the circuit")]
+    WireRow(GateType, usize, usize),
+    /// Invalid gate on the next row
+    #[error("Invalid gate following a {0:?} gate: {1:?}")]
+    NextGate(GateType, GateType),
+    /// Missing next row
+    #[error("Missing row following a {0:?} gate")]
+    MissingNextRow(GateType),
+    /// Invalid number of rows of a block of gates
+    #[error("Invalid {0:?} block of {1} rows")]
+    BlockLength(GateType, usize),
+    /// Public input in another generic gate than the first one of a row
+    #[error("The public input can only be added to the first generic gate of a {0:?} row")]
+    PublicInputGate(GateType),
 }
 
 /// Gate result
@@ -214,28 +272,110 @@ impl<F: PrimeField> CircuitGate<F> {
         use GateType::*;
         match self.typ {
             Zero => Ok(()),
-            Generic => self.verify_generic(row, witness, public),
+            Generic | TripleGeneric => self.verify_generic(row, witness, public),
             Poseidon => self.verify_poseidon::<G>(row, witness),
             CompleteAdd => self.verify_complete_add(row, witness),
             VarBaseMul => self.verify_vbmul(row, witness),
             EndoMul => self.verify_endomul::<G>(row, witness, cs),
             EndoMulScalar => self.verify_endomul_scalar::<G>(row, witness, cs),
             // TODO: implement the verification for chacha
+            #[cfg(feature = "chacha")]
             ChaCha0 | ChaCha1 | ChaCha2 | ChaChaFinal => Ok(()),
-            // TODO: implement the verification for the lookup gate
-            Lookup => Ok(()),
+            // TODO: implement the verification for the lookup gates
+            Lookup | MultiTableLookup => Ok(()),
+            CopyCells => self.verify_copy_cells(row, witness),
+            #[cfg(feature = "cairo")]
             CairoClaim | CairoInstruction | CairoFlags | CairoTransition => {
                 self.verify_cairo_gate::<G>(row, witness, cs)
             }
+            #[cfg(feature = "range_check")]
             RangeCheck0 | RangeCheck1 => self
                 .verify_range_check::<G>(row, witness, cs)
                 .map_err(|e| e.to_string()),
+            #[cfg(feature = "foreign_field")]
             ForeignFieldAdd => self
                 .verify_foreign_field_add::<G>(row, witness, cs)
                 .map_err(|e| e.to_string()),
+            #[cfg(feature = "keccak")]
+            Xor16 => self.verify_xor16(row, witness),
+            #[cfg(feature = "keccak")]
+            Rot64 => self.verify_rot64(row, witness),
         }
     }
 
+    /// Checks the invariants of the gate at `row` of the circuit of `cs`:
+    /// its number of coefficients, that its wires stay within the circuit,
+    /// and for the gates whose constraints span several rows, the gates following it.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if an invariant does not hold.
+    pub fn validate(&self, row: usize, cs: &ConstraintSystem<F>) -> CircuitGateResult<()> {
+        use GateType::*;
+        let gates = &cs.gates;
+
+        // only the generic and poseidon gates read coefficients
+        let coeffs = match self.typ {
+            Generic => 0..=generic::DOUBLE_GENERIC_COEFFS,
+            TripleGeneric => 0..=generic::TRIPLE_GENERIC_COEFFS,
+            Poseidon => {
+                let round_constants = poseidon::SPONGE_WIDTH * poseidon::ROUNDS_PER_ROW;
+                round_constants..=round_constants
+            }
+            _ => 0..=0,
+        };
+        if !coeffs.contains(&self.coeffs.len()) {
+            return Err(CircuitGateError::CoefficientCount(
+                self.typ,
+                self.coeffs.len(),
+            ));
+        }
+
+        for (col, wire) in self.wires.iter().enumerate() {
+            if wire.row >= gates.len() {
+                return Err(CircuitGateError::WireRow(self.typ, col, wire.row));
+            }
+        }
+
+        // the gates whose constraints read the next row are followed by the rest of their layout
+        let next: &[GateType] = match self.typ {
+            Poseidon => &[Poseidon, Zero],
+            VarBaseMul => &[Zero],
+            EndoMul => &[EndoMul, Zero],
+            #[cfg(feature = "range_check")]
+            RangeCheck1 => &[Zero],
+            #[cfg(feature = "foreign_field")]
+            ForeignFieldAdd => &[ForeignFieldAdd, Zero],
+            #[cfg(feature = "keccak")]
+            Xor16 => &[Xor16, Generic, Zero],
+            _ => &[],
+        };
+        if !next.is_empty() {
+            match gates.get(row + 1) {
+                Some(gate) if next.contains(&gate.typ) => (),
+                Some(gate) => return Err(CircuitGateError::NextGate(self.typ, gate.typ)),
+                None => return Err(CircuitGateError::MissingNextRow(self.typ)),
+            }
+        }
+        // the copy gate reads the next row, whatever its gate
+        if self.typ == CopyCells && row + 1 >= gates.len() {
+            return Err(CircuitGateError::MissingNextRow(CopyCells));
+        }
+
+        // a poseidon permutation spans a block of rows of 5 rounds each
+        if self.typ == Poseidon && (row == 0 || gates[row - 1].typ != Poseidon) {
+            let rows = gates[row..]
+                .iter()
+                .take_while(|gate| gate.typ == Poseidon)
+                .count();
+            if rows % poseidon::POS_ROWS_PER_HASH != 0 {
+                return Err(CircuitGateError::BlockLength(Poseidon, rows));
+            }
+        }
+
+        Ok(())
+    }
+
     /// Verify the witness against the constraints
     pub fn verify_witness<G: KimchiCurve<ScalarField = F>>(
         &self,
@@ -265,10 +405,10 @@ impl<F: PrimeField> CircuitGate<F> {
         //       It could be nice for gates to know this and then
         //       this code could be adapted to check Curr or Curr
         //       and Next depending on the gate definition
-        for col in 0..PERMUTS {
+        for col in 0..cs.permuted_columns() {
             let wire = self.wires[col];
 
-            if wire.col >= PERMUTS {
+            if wire.col >= cs.permuted_columns() {
                 return Err(CircuitGateError::WireColumn(self.typ, col));
             }
 
@@ -287,7 +427,7 @@ impl<F: PrimeField> CircuitGate<F> {
             GateType::Zero => {
                 vec![]
             }
-            GateType::Generic => {
+            GateType::Generic | GateType::TripleGeneric => {
                 // TODO: implement the verification for the generic gate
                 vec![]
             }
@@ -296,27 +436,43 @@ impl<F: PrimeField> CircuitGate<F> {
             GateType::VarBaseMul => varbasemul::VarbaseMul::constraint_checks(&env),
             GateType::EndoMul => endosclmul::EndosclMul::constraint_checks(&env),
             GateType::EndoMulScalar => endomul_scalar::EndomulScalar::constraint_checks(&env),
+            #[cfg(feature = "chacha")]
             GateType::ChaCha0 => chacha::ChaCha0::constraint_checks(&env),
+            #[cfg(feature = "chacha")]
             GateType::ChaCha1 => chacha::ChaCha1::constraint_checks(&env),
+            #[cfg(feature = "chacha")]
             GateType::ChaCha2 => chacha::ChaCha2::constraint_checks(&env),
+            #[cfg(feature = "chacha")]
             GateType::ChaChaFinal => chacha::ChaChaFinal::constraint_checks(&env),
-            GateType::Lookup => {
-                // TODO: implement the verification for the lookup gate
+            GateType::Lookup | GateType::MultiTableLookup => {
+                // TODO: implement the verification for the lookup gates
                 vec![]
             }
+            GateType::CopyCells => copy_cells::CopyCells::constraint_checks(&env),
+            #[cfg(feature = "cairo")]
             GateType::CairoClaim => turshi::Claim::constraint_checks(&env),
+            #[cfg(feature = "cairo")]
             GateType::CairoInstruction => turshi::Instruction::constraint_checks(&env),
+            #[cfg(feature = "cairo")]
             GateType::CairoFlags => turshi::Flags::constraint_checks(&env),
+            #[cfg(feature = "cairo")]
             GateType::CairoTransition => turshi::Transition::constraint_checks(&env),
+            #[cfg(feature = "range_check")]
             GateType::RangeCheck0 => {
                 range_check::circuitgates::RangeCheck0::constraint_checks(&env)
             }
+            #[cfg(feature = "range_check")]
             GateType::RangeCheck1 => {
                 range_check::circuitgates::RangeCheck1::constraint_checks(&env)
             }
+            #[cfg(feature = "foreign_field")]
             GateType::ForeignFieldAdd => {
                 foreign_field_add::circuitgates::ForeignFieldAdd::constraint_checks(&env)
             }
+            #[cfg(feature = "keccak")]
+            GateType::Xor16 => keccak::circuitgates::Xor16::constraint_checks(&env),
+            #[cfg(feature = "keccak")]
+            GateType::Rot64 => keccak::circuitgates::Rot64::constraint_checks(&env),
         };
 
         // Check for failed constraints
@@ -369,14 +525,18 @@ pub trait Connect {
     /// Note: This function assumes that the targeted cells are freshly instantiated
     ///       with self-connections.  If the two cells are transitively already part
     ///       of the same permutation then this would split it.
-    fn connect_cell_pair(&mut self, cell1: (usize, usize), cell2: (usize, usize));
+    ///
+    /// See [`Builder::connect`](crate::circuits::constraints::Builder::connect)
+    /// for a version checking the cells.
+    fn connect_cell_pair(&mut self, cell1: impl Into<CellRef>, cell2: impl Into<CellRef>);
 }
 
 impl<F: PrimeField> Connect for Vec<CircuitGate<F>> {
-    fn connect_cell_pair(&mut self, cell_pre: (usize, usize), cell_new: (usize, usize)) {
-        let wire_tmp = self[cell_pre.0].wires[cell_pre.1];
-        self[cell_pre.0].wires[cell_pre.1] = self[cell_new.0].wires[cell_new.1];
-        self[cell_new.0].wires[cell_new.1] = wire_tmp;
+    fn connect_cell_pair(&mut self, cell_pre: impl Into<CellRef>, cell_new: impl Into<CellRef>) {
+        let (cell_pre, cell_new) = (cell_pre.into(), cell_new.into());
+        let wire_tmp = self[cell_pre.row].wires[cell_pre.col];
+        self[cell_pre.row].wires[cell_pre.col] = self[cell_new.row].wires[cell_new.col];
+        self[cell_new.row].wires[cell_new.col] = wire_tmp;
     }
 }
 
@@ -0,0 +1,275 @@
+//! This module implements the metadata of the gates, as a [`GateInfo`] for each [`GateType`]:
+//! the number of constraints of the gate, the cells its constraints read,
+//! the meaning of its coefficients and the lookups it performs.
+//!
+//! The constraints and the lookups are read from the implementation of the gates,
+//! so that frontends and debuggers can render accurate information on each gate
+//! without maintaining their own tables.
+
+#[cfg(feature = "chacha")]
+use crate::circuits::polynomials::chacha;
+#[cfg(feature = "foreign_field")]
+use crate::circuits::polynomials::foreign_field_add;
+#[cfg(feature = "keccak")]
+use crate::circuits::polynomials::keccak;
+#[cfg(feature = "range_check")]
+use crate::circuits::polynomials::range_check;
+#[cfg(feature = "cairo")]
+use crate::circuits::polynomials::turshi;
+use crate::circuits::{
+    argument::Argument,
+    expr::{Column, Expr, Variable, E},
+    gate::{CurrOrNext, GateType},
+    lookup::lookups::LookupPattern,
+    polynomials::{
+        complete_add, copy_cells, endomul_scalar, endosclmul, generic,
+        poseidon::{self, SPONGE_WIDTH},
+        varbasemul,
+    },
+};
+use mina_curves::pasta::Fp;
+use num_traits::FromPrimitive;
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+/// The metadata of a gate type
+#[derive(Clone, Debug, Serialize)]
+pub struct GateInfo {
+    /// the gate type
+    pub typ: GateType,
+    /// a short description of the gate
+    pub description: &'static str,
+    /// the number of constraints of the gate
+    pub constraints: u32,
+    /// the cells read by the constraints of the gate (witness and coefficient cells), sorted
+    pub cells: Vec<Variable>,
+    /// the meaning of each coefficient of the gate, in order
+    pub coefficients: Vec<String>,
+    /// the lookup patterns applied on the row of the gate and on the next one
+    pub lookups: Vec<(CurrOrNext, LookupPattern)>,
+}
+
+impl GateInfo {
+    /// Returns the metadata of all the gate types (of the enabled features).
+    pub fn all() -> Vec<Self> {
+        (0..=u8::MAX)
+            .filter_map(GateType::from_u8)
+            .map(GateType::info)
+            .collect()
+    }
+
+    /// Returns the witness columns of the row `row` read by the constraints of the gate.
+    pub fn witness_columns(&self, row: CurrOrNext) -> Vec<usize> {
+        self.cells
+            .iter()
+            .filter_map(|cell| match cell.col {
+                Column::Witness(col) if cell.row == row => Some(col),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns whether the gate reads the witness of the next row,
+    /// by its constraints or by its lookups.
+    pub fn uses_next_row(&self) -> bool {
+        !self.witness_columns(CurrOrNext::Next).is_empty()
+            || self.lookups.iter().any(|(row, _)| *row == CurrOrNext::Next)
+    }
+}
+
+impl GateType {
+    /// Returns the metadata of the gate type (see [`GateInfo`]).
+    pub fn info(self) -> GateInfo {
+        use GateType::*;
+        let (description, constraints): (_, Vec<E<Fp>>) = match self {
+            Zero => ("Zero gate, without constraints", vec![]),
+            // the generic gate is not written with the expression framework
+            Generic => ("Generic arithmetic gate, made of two generic gates", vec![]),
+            TripleGeneric => (
+                "Generic arithmetic gate, made of three generic gates",
+                vec![],
+            ),
+            Poseidon => (
+                "Poseidon permutation gate, computing 5 rounds of the permutation",
+                poseidon::Poseidon::constraints(),
+            ),
+            CompleteAdd => (
+                "Complete EC addition in affine form",
+                complete_add::CompleteAdd::constraints(),
+            ),
+            VarBaseMul => (
+                "EC variable base scalar multiplication, on 5 bits of the scalar",
+                varbasemul::VarbaseMul::constraints(),
+            ),
+            EndoMul => (
+                "EC variable base scalar multiplication with group endomorphism optimization",
+                endosclmul::EndosclMul::constraints(),
+            ),
+            EndoMulScalar => (
+                "Computation of the scalar corresponding to an endoscaling",
+                endomul_scalar::EndomulScalar::constraints(),
+            ),
+            #[cfg(feature = "chacha")]
+            ChaCha0 => (
+                "ChaCha quarter round, first step",
+                chacha::ChaCha0::constraints(),
+            ),
+            #[cfg(feature = "chacha")]
+            ChaCha1 => (
+                "ChaCha quarter round, second step",
+                chacha::ChaCha1::constraints(),
+            ),
+            #[cfg(feature = "chacha")]
+            ChaCha2 => (
+                "ChaCha quarter round, third step",
+                chacha::ChaCha2::constraints(),
+            ),
+            #[cfg(feature = "chacha")]
+            ChaChaFinal => (
+                "ChaCha quarter round, final step",
+                chacha::ChaChaFinal::constraints(),
+            ),
+            Lookup => ("Lookups into a single table, without constraints", vec![]),
+            #[cfg(feature = "cairo")]
+            CairoClaim => ("Cairo claim", turshi::Claim::constraints()),
+            #[cfg(feature = "cairo")]
+            CairoInstruction => ("Cairo instruction", turshi::Instruction::constraints()),
+            #[cfg(feature = "cairo")]
+            CairoFlags => ("Cairo flags", turshi::Flags::constraints()),
+            #[cfg(feature = "cairo")]
+            CairoTransition => ("Cairo transition", turshi::Transition::constraints()),
+            #[cfg(feature = "range_check")]
+            RangeCheck0 => (
+                "Range check of a value of up to 88 bits",
+                range_check::circuitgates::RangeCheck0::constraints(),
+            ),
+            #[cfg(feature = "range_check")]
+            RangeCheck1 => (
+                "Range check of two values of up to 88 bits, and of their compact limbs",
+                range_check::circuitgates::RangeCheck1::constraints(),
+            ),
+            #[cfg(feature = "foreign_field")]
+            ForeignFieldAdd => (
+                "Foreign field addition",
+                foreign_field_add::circuitgates::ForeignFieldAdd::constraints(),
+            ),
+            MultiTableLookup => (
+                "Lookups into several tables, with a table ID per lookup, without constraints",
+                vec![],
+            ),
+            CopyCells => (
+                "Copies of the non-permuted cells of the next row into the permuted cells of the row",
+                copy_cells::CopyCells::constraints(),
+            ),
+            #[cfg(feature = "keccak")]
+            Xor16 => (
+                "XOR of the 16 lowest bits of two values, by nybbles",
+                keccak::circuitgates::Xor16::constraints(),
+            ),
+            #[cfg(feature = "keccak")]
+            Rot64 => (
+                "Rotation of a 64-bit value",
+                keccak::circuitgates::Rot64::constraints(),
+            ),
+        };
+
+        let (constraints, cells, coefficients) = match self {
+            Generic | TripleGeneric => {
+                // the generic gates read their registers, and their coefficients
+                let (constraints, registers, coeffs) = if self == Generic {
+                    (
+                        generic::CONSTRAINTS,
+                        generic::DOUBLE_GENERIC_REGISTERS,
+                        generic::DOUBLE_GENERIC_COEFFS,
+                    )
+                } else {
+                    (
+                        generic::TRIPLE_GENERIC_CONSTRAINTS,
+                        generic::TRIPLE_GENERIC_REGISTERS,
+                        generic::TRIPLE_GENERIC_COEFFS,
+                    )
+                };
+                let cells = (0..registers)
+                    .map(Column::Witness)
+                    .chain((0..coeffs).map(Column::Coefficient))
+                    .map(|col| Variable {
+                        col,
+                        row: CurrOrNext::Curr,
+                    })
+                    .collect();
+                let coefficients = (1..=constraints)
+                    .flat_map(|gate| {
+                        ["left", "right", "output", "mul", "constant"].map(|selector| {
+                            format!("{selector} selector of the generic gate {gate}")
+                        })
+                    })
+                    .collect();
+                (constraints, cells, coefficients)
+            }
+            _ => {
+                let mut cells = BTreeSet::new();
+                for constraint in &constraints {
+                    add_cells(constraint, &mut cells);
+                }
+                let count = cells
+                    .iter()
+                    .filter_map(|cell| match cell.col {
+                        Column::Coefficient(i) => Some(i + 1),
+                        _ => None,
+                    })
+                    .max()
+                    .unwrap_or(0);
+                let coefficients = match self {
+                    Poseidon => (0..count)
+                        .map(|i| {
+                            format!(
+                                "round constant {} of the round {}",
+                                i % SPONGE_WIDTH,
+                                i / SPONGE_WIDTH
+                            )
+                        })
+                        .collect(),
+                    _ => (0..count).map(|i| format!("coefficient {i}")).collect(),
+                };
+                (
+                    constraints.len() as u32,
+                    cells.into_iter().collect(),
+                    coefficients,
+                )
+            }
+        };
+
+        let lookups = [CurrOrNext::Curr, CurrOrNext::Next]
+            .into_iter()
+            .filter_map(|row| LookupPattern::from_gate(self, row).map(|pattern| (row, pattern)))
+            .collect();
+
+        GateInfo {
+            typ: self,
+            description,
+            constraints,
+            cells,
+            coefficients,
+            lookups,
+        }
+    }
+}
+
+/// Adds the witness and coefficient cells read by `expr` to `cells`.
+fn add_cells(expr: &E<Fp>, cells: &mut BTreeSet<Variable>) {
+    match expr {
+        Expr::Cell(var) => {
+            if matches!(var.col, Column::Witness(_) | Column::Coefficient(_)) {
+                cells.insert(*var);
+            }
+        }
+        Expr::Double(x) | Expr::Square(x) | Expr::Pow(x, _) | Expr::Cache(_, x) => {
+            add_cells(x, cells)
+        }
+        Expr::BinOp(_, x, y) => {
+            add_cells(x, cells);
+            add_cells(y, cells);
+        }
+        Expr::Constant(_) | Expr::VanishesOnLast4Rows | Expr::UnnormalizedLagrangeBasis(_) => {}
+    }
+}
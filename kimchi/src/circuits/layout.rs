@@ -0,0 +1,254 @@
+//! This module implements the [`WitnessLayout`], an export of the witness table of a circuit
+//! along with its gate layout, to debug circuits row by row.
+//!
+//! Each row is annotated with its gate, and with the error of its gate constraints if any,
+//! and each cell with the cell it is wired to, and whether the copy constraint holds.
+//! The layout renders to CSV (see [`WitnessLayout::to_csv`])
+//! or to a standalone HTML page (see [`WitnessLayout::to_html`]).
+
+use crate::{
+    circuits::{
+        constraints::ConstraintSystem,
+        gate::GateType,
+        wires::{CellRef, COLUMNS},
+    },
+    curve::KimchiCurve,
+};
+use ark_ff::PrimeField;
+use num_bigint::BigUint;
+use std::{array, fmt::Write};
+
+/// The annotations of a cell of the witness.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CellAnnotation {
+    /// The cell this cell is wired to, if not itself
+    pub wire: Option<CellRef>,
+    /// Whether the cell holds the same value as the cell it is wired to
+    pub copy_ok: bool,
+    /// Whether the column of the cell is declared zero
+    pub zero_column: bool,
+}
+
+/// The annotations of a row of the witness.
+#[derive(Clone, Debug)]
+pub struct RowAnnotation {
+    /// The gate of the row
+    pub gate: GateType,
+    /// Whether the row holds a public input
+    pub public: bool,
+    /// The error of the gate constraints of the row, if they do not hold
+    pub error: Option<String>,
+    /// The annotations of the cells of the row
+    pub cells: [CellAnnotation; COLUMNS],
+}
+
+/// The witness table of a circuit, annotated with its gate layout and constraints.
+#[derive(Clone, Debug)]
+pub struct WitnessLayout<F> {
+    /// The witness, one column per register
+    pub witness: [Vec<F>; COLUMNS],
+    /// The annotations of the rows of the witness
+    pub rows: Vec<RowAnnotation>,
+}
+
+impl<F: PrimeField> WitnessLayout<F> {
+    /// Annotates the rows of `witness` with the gates and constraints of `cs`.
+    /// Unlike [`ConstraintSystem::verify`], all the constraints are checked,
+    /// instead of stopping at the first one that does not hold.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the witness columns are not all the same size,
+    /// or if the witness is longer than the domain of the circuit.
+    pub fn new<G: KimchiCurve<ScalarField = F>>(
+        cs: &ConstraintSystem<F>,
+        witness: &[Vec<F>; COLUMNS],
+        public: &[F],
+    ) -> Self {
+        let length = witness[0].len();
+        assert!(witness.iter().all(|w| w.len() == length));
+        assert!(length <= cs.domain.d1.size());
+
+        // the gate constraints may look at the next rows, so they are checked on the padded witness
+        let padded: [Vec<F>; COLUMNS] = array::from_fn(|col| {
+            let mut w = witness[col].clone();
+            w.resize(cs.domain.d1.size(), F::zero());
+            w
+        });
+
+        let rows = cs.gates[..length]
+            .iter()
+            .enumerate()
+            .map(|(row, gate)| {
+                let cells = array::from_fn(|col| {
+                    let wire = gate.wires.get(col).map(|w| CellRef::from(*w));
+                    let wire = wire.filter(|w| *w != CellRef::new(row, col));
+                    let copy_ok = wire.map_or(true, |w| {
+                        padded[col][row] == padded[w.col][w.row]
+                            && col < cs.permuted_columns()
+                            && w.col < cs.permuted_columns()
+                    });
+                    CellAnnotation {
+                        wire,
+                        copy_ok,
+                        zero_column: cs.zero_columns[col],
+                    }
+                });
+
+                let public_row = row < cs.public;
+                let error = if public_row && gate.coeffs.first() != Some(&F::one()) {
+                    Some("the public input gate does not toggle its left wire".to_string())
+                } else {
+                    gate.verify::<G>(row, &padded, cs, public).err()
+                };
+
+                RowAnnotation {
+                    gate: gate.typ,
+                    public: public_row,
+                    error,
+                    cells,
+                }
+            })
+            .collect();
+
+        Self {
+            witness: witness.clone(),
+            rows,
+        }
+    }
+
+    /// Returns the rows whose constraints do not hold, be it their gate or copy constraints.
+    pub fn failing_rows(&self) -> impl Iterator<Item = usize> + '_ {
+        self.rows
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| r.error.is_some() || r.cells.iter().any(|c| !c.copy_ok))
+            .map(|(row, _)| row)
+    }
+
+    /// Returns the value of a cell, in decimal.
+    fn value(&self, row: usize, col: usize) -> String {
+        let value: BigUint = self.witness[col][row].into();
+        value.to_string()
+    }
+
+    /// Renders the layout as CSV, one line per row,
+    /// with the row, the gate, the 15 cells and the error of the row.
+    /// The wiring of a cell follows its value, as `-> (row, col)`,
+    /// followed by `!` if the copy constraint does not hold.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("row,gate");
+        for col in 0..COLUMNS {
+            write!(csv, ",w{col}").unwrap();
+        }
+        csv.push_str(",error\n");
+
+        for (row, annotation) in self.rows.iter().enumerate() {
+            write!(csv, "{row},{:?}", annotation.gate).unwrap();
+            for (col, cell) in annotation.cells.iter().enumerate() {
+                let mut text = self.value(row, col);
+                if let Some(wire) = cell.wire {
+                    write!(text, " -> {wire}").unwrap();
+                }
+                if !cell.copy_ok {
+                    text.push_str(" !");
+                }
+                write!(csv, ",{}", csv_escape(&text)).unwrap();
+            }
+            let error = annotation.error.as_deref().unwrap_or_default();
+            writeln!(csv, ",{}", csv_escape(error)).unwrap();
+        }
+        csv
+    }
+
+    /// Renders the layout as a standalone HTML page, with a table of the witness.
+    /// Wired cells, failed copy constraints, zero columns and failing rows are highlighted,
+    /// and the annotations of a cell show on hover.
+    pub fn to_html(&self) -> String {
+        let mut html = String::from(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>witness</title>\n\
+             <style>\n\
+             table { border-collapse: collapse; font-family: monospace; }\n\
+             td, th { border: 1px solid #ccc; padding: 2px 6px; max-width: 12em; overflow: hidden; text-overflow: ellipsis; }\n\
+             .wired { background: #e0ecff; }\n\
+             .zero { color: #aaa; }\n\
+             .public { background: #fff8d0; }\n\
+             .mismatch { background: #ffb0b0; }\n\
+             .error { background: #ffe0e0; }\n\
+             </style>\n</head>\n<body>\n<table>\n<tr><th>row</th><th>gate</th>",
+        );
+        for col in 0..COLUMNS {
+            write!(html, "<th>w{col}</th>").unwrap();
+        }
+        html.push_str("<th>error</th></tr>\n");
+
+        for (row, annotation) in self.rows.iter().enumerate() {
+            let class = match (&annotation.error, annotation.public) {
+                (Some(_), _) => " class=\"error\"",
+                (None, true) => " class=\"public\"",
+                (None, false) => "",
+            };
+            write!(
+                html,
+                "<tr{class}><td>{row}</td><td>{:?}</td>",
+                annotation.gate
+            )
+            .unwrap();
+
+            for (col, cell) in annotation.cells.iter().enumerate() {
+                let value = self.value(row, col);
+                let mut classes = vec![];
+                let mut title = format!("({row}, {col}) = {value}");
+                if let Some(wire) = cell.wire {
+                    classes.push("wired");
+                    write!(title, ", wired to {wire}").unwrap();
+                }
+                if !cell.copy_ok {
+                    classes.push("mismatch");
+                    title.push_str(", copy constraint failed");
+                }
+                if cell.zero_column {
+                    classes.push("zero");
+                }
+                write!(
+                    html,
+                    "<td class=\"{}\" title=\"{}\">{}</td>",
+                    classes.join(" "),
+                    html_escape(&title),
+                    html_escape(&value)
+                )
+                .unwrap();
+            }
+
+            let error = annotation.error.as_deref().unwrap_or_default();
+            writeln!(html, "<td>{}</td></tr>", html_escape(error)).unwrap();
+        }
+
+        html.push_str("</table>\n</body>\n</html>\n");
+        html
+    }
+}
+
+/// Quotes a CSV field if needed.
+fn csv_escape(field: &str) -> String {
+    if field.contains(|c| matches!(c, ',' | '"' | '\n')) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Escapes the characters of `text` that are special in HTML.
+fn html_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
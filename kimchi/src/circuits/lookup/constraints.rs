@@ -2,20 +2,29 @@ use crate::{
     circuits::{
         expr::{prologue::*, Column, ConstantExpr},
         gate::{CircuitGate, CurrOrNext},
-        lookup::lookups::{
-            JointLookup, JointLookupSpec, JointLookupValue, LocalPosition, LookupInfo, LookupsUsed,
+        lookup::{
+            index::LookupConstraintSystem,
+            lookups::{
+                JointLookup, JointLookupSpec, JointLookupValue, LocalPosition, LookupInfo,
+                LookupsUsed,
+            },
+            runtime_tables::{RuntimeTable, UnusedEntry},
         },
+        polynomials::permutation::prefix_product,
         wires::COLUMNS,
     },
-    error::ProverError,
+    error::{ProverError, WitnessError},
 };
 use ark_ff::{FftField, One, PrimeField, Zero};
 use ark_poly::{EvaluationDomain, Evaluations, Radix2EvaluationDomain as D};
-use o1_utils::adjacent_pairs::AdjacentPairs;
 use rand::Rng;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    iter,
+};
 use CurrOrNext::{Curr, Next};
 
 use super::runtime_tables;
@@ -106,12 +115,15 @@ pub fn sorted<F: PrimeField>(
     let by_row = lookup_info.by_row(gates);
     let max_lookups_per_row = lookup_info.max_per_row;
 
-    for t in joint_lookup_table_d8
+    let table: Vec<_> = joint_lookup_table_d8
         .evals
         .iter()
         .step_by(8)
+        // avoid zk rows
         .take(lookup_rows)
-    {
+        .collect();
+
+    for t in table.iter().copied() {
         // Don't multiply-count duplicate values in the table, or they'll be duplicated for each
         // duplicate!
         // E.g. A value duplicated in the table 3 times would be entered into the sorted array 3
@@ -120,45 +132,51 @@ pub fn sorted<F: PrimeField>(
     }
 
     // TODO: shouldn't we make sure that lookup rows is the same as the number of active gates in the circuit as well? danger: What if we have gates that use lookup but are not counted here?
-    for (i, row) in by_row
-        .iter()
+    // the lookups are evaluated and counted in parallel, each thread counting in its own map
+    let lookup_counts = by_row
+        .par_iter()
         .enumerate()
         // avoid zk rows
         .take(lookup_rows)
-    {
-        let spec = row;
-        let padding = max_lookups_per_row - spec.len();
-        for joint_lookup in spec.iter() {
-            let eval = |pos: LocalPosition| -> F {
-                let row = match pos.row {
-                    Curr => i,
-                    Next => i + 1,
+        .fold(HashMap::new, |mut lookup_counts, (i, spec)| {
+            for joint_lookup in spec.iter() {
+                let eval = |pos: LocalPosition| -> F {
+                    let row = match pos.row {
+                        Curr => i,
+                        Next => i + 1,
+                    };
+                    witness[pos.column][row]
                 };
-                witness[pos.column][row]
-            };
-            let joint_lookup_evaluation =
-                joint_lookup.evaluate(&joint_combiner, &table_id_combiner, &eval);
-            match counts.get_mut(&joint_lookup_evaluation) {
-                None => return Err(ProverError::ValueNotInTable),
-                Some(count) => *count += 1,
+                let joint_lookup_evaluation =
+                    joint_lookup.evaluate(&joint_combiner, &table_id_combiner, &eval);
+                *lookup_counts.entry(joint_lookup_evaluation).or_insert(0) += 1;
             }
+            lookup_counts
+        })
+        .reduce(HashMap::new, |mut lookup_counts, other| {
+            for (value, count) in other {
+                *lookup_counts.entry(value).or_insert(0) += count;
+            }
+            lookup_counts
+        });
+    for (value, lookup_count) in lookup_counts {
+        match counts.get_mut(&value) {
+            None => return Err(ProverError::ValueNotInTable),
+            Some(count) => *count += lookup_count,
         }
-        *counts.entry(&dummy_lookup_value).or_insert(0) += padding;
     }
+    let padding: usize = by_row
+        .iter()
+        .take(lookup_rows)
+        .map(|spec| max_lookups_per_row - spec.len())
+        .sum();
+    *counts.entry(&dummy_lookup_value).or_insert(0) += padding;
 
     let sorted = {
-        let mut sorted: Vec<Vec<F>> =
-            vec![Vec::with_capacity(lookup_rows + 1); max_lookups_per_row + 1];
-
-        let mut i = 0;
-        for t in joint_lookup_table_d8
-            .evals
+        // the number of times each entry of the table appears in the sorted table
+        let t_counts: Vec<usize> = table
             .iter()
-            .step_by(8)
-            // avoid zk rows
-            .take(lookup_rows)
-        {
-            let t_count = match counts.get_mut(&t) {
+            .map(|t| match counts.get_mut(t) {
                 None => panic!("Value has disappeared from count table"),
                 Some(x) => {
                     let res = *x;
@@ -166,13 +184,19 @@ pub fn sorted<F: PrimeField>(
                     *x = 1;
                     res
                 }
-            };
-            for j in 0..t_count {
-                let idx = i + j;
-                let col = idx / lookup_rows;
-                sorted[col].push(*t);
-            }
-            i += t_count;
+            })
+            .collect();
+
+        // the entries are then repeated in parallel, and split in columns
+        let entries: Vec<F> = table
+            .par_iter()
+            .zip(t_counts)
+            .flat_map_iter(|(t, t_count)| iter::repeat(**t).take(t_count))
+            .collect();
+        let mut sorted: Vec<Vec<F>> =
+            vec![Vec::with_capacity(lookup_rows + 1); max_lookups_per_row + 1];
+        for (col, chunk) in entries.chunks(lookup_rows).enumerate() {
+            sorted[col].extend_from_slice(chunk);
         }
 
         for i in 0..max_lookups_per_row {
@@ -198,6 +222,111 @@ pub fn sorted<F: PrimeField>(
     Ok(sorted)
 }
 
+/// Checks that the lookups of each row of the witness are in their tables,
+/// the runtime tables being checked against their configuration first.
+/// Unlike [`sorted`], this pinpoints the first lookup that is not in its table.
+///
+/// # Errors
+///
+/// Will give error if the runtime tables do not match their configuration,
+/// or with the row of the first lookup that is not in its table.
+pub fn check_lookups<F: PrimeField>(
+    lcs: &LookupConstraintSystem<F>,
+    d1: D<F>,
+    gates: &[CircuitGate<F>],
+    witness: &[Vec<F>; COLUMNS],
+    runtime_tables: &[RuntimeTable<F>],
+) -> Result<(), ProverError> {
+    let n = d1.size();
+    let lookup_rows = n - ZK_ROWS - 1;
+
+    // the runtime tables are added to the second column of the table,
+    // the unused entries of the runtime tables of variable length being the dummy entry
+    let mut runtime = vec![F::zero(); n];
+    let mut unused = HashSet::new();
+    if let Some(specs) = &lcs.runtime_tables {
+        RuntimeTable::check(specs, runtime_tables)
+            .map_err(ProverError::RuntimeTablesInconsistent)?;
+        let mut offset = lcs
+            .runtime_table_offset
+            .expect("runtime configuration missing offset");
+        for (spec, table) in specs.iter().zip(runtime_tables) {
+            runtime[offset..(offset + table.data.len())].copy_from_slice(&table.data);
+            offset += spec.len;
+        }
+        let runtime_lens: Vec<_> = specs
+            .iter()
+            .zip(runtime_tables)
+            .filter(|(spec, _)| spec.variable_len)
+            .map(|(_, table)| table.data.len())
+            .collect();
+        unused = UnusedEntry::all(&lcs.variable_runtime_tables(), &runtime_lens)
+            .expect("the runtime tables match their configuration")
+            .into_iter()
+            .map(|entry| entry.row)
+            .collect();
+    }
+
+    // the entries of the table, with their table ID,
+    // the trailing zeros of the entries being trimmed as lookups of any width are combined
+    let trim = |mut entry: Vec<F>| {
+        while entry.last().map_or(false, Zero::is_zero) {
+            entry.pop();
+        }
+        entry
+    };
+    let table: HashSet<(F, Vec<F>)> = (0..lookup_rows)
+        .map(|row| {
+            if unused.contains(&row) {
+                return (F::zero(), vec![]);
+            }
+            let table_id = lcs
+                .table_ids8
+                .as_ref()
+                .map_or_else(F::zero, |ids| ids.evals[8 * row]);
+            let entry = lcs
+                .lookup_table8
+                .iter()
+                .enumerate()
+                .map(|(col, column)| {
+                    let entry = column.evals[8 * row];
+                    if col == 1 {
+                        entry + runtime[row]
+                    } else {
+                        entry
+                    }
+                })
+                .collect();
+            (table_id, trim(entry))
+        })
+        .collect();
+
+    let by_row = lcs.configuration.lookup_info.by_row(gates);
+    for (row, lookups) in by_row.iter().enumerate().take(lookup_rows) {
+        let eval = |pos: LocalPosition| -> F {
+            let row = match pos.row {
+                Curr => row,
+                Next => row + 1,
+            };
+            witness[pos.column]
+                .get(row)
+                .copied()
+                .unwrap_or_else(F::zero)
+        };
+        for (i, lookup) in lookups.iter().enumerate() {
+            let JointLookup { table_id, entry } = lookup.reduce(&eval);
+            if !table.contains(&(table_id, trim(entry))) {
+                return Err(ProverError::WitnessNotSatisfied {
+                    row,
+                    gate: gates[row].typ,
+                    cause: WitnessError::Lookup(i),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Computes the aggregation polynomial for maximum n lookups per row, whose kth entry is the product of terms
 ///
 ///  (gamma(1 + beta) + t_i + beta t_{i+1}) \prod_{0 <= j < n} ( (1 + beta) (gamma + f_{i,j}) )
@@ -251,7 +380,7 @@ where
     let gammabeta1 = gamma * beta1;
     let mut lookup_aggreg = vec![F::one()];
 
-    lookup_aggreg.extend((0..lookup_rows).map(|row| {
+    lookup_aggreg.par_extend((0..lookup_rows).into_par_iter().map(|row| {
         sorted
             .iter()
             .enumerate()
@@ -282,11 +411,13 @@ where
         v
     };
 
-    AdjacentPairs::from(joint_lookup_table_d8.evals.iter().step_by(8))
-        .take(lookup_rows)
+    let table = &joint_lookup_table_d8.evals;
+    lookup_aggreg[1..]
+        .par_iter_mut()
         .zip(lookup_info.by_row(gates))
         .enumerate()
-        .for_each(|(i, ((t0, t1), spec))| {
+        .for_each(|(i, (aggreg, spec))| {
+            let (t0, t1) = (table[8 * i], table[8 * (i + 1)]);
             let f_chunk = {
                 let eval = |pos: LocalPosition| -> F {
                     let row = match pos.row {
@@ -308,16 +439,16 @@ where
                 })
             };
 
-            // At this point, lookup_aggreg[i + 1] contains 1/s_chunk
+            // At this point, aggreg contains 1/s_chunk
             // f_chunk / s_chunk
-            lookup_aggreg[i + 1] *= f_chunk;
+            *aggreg *= f_chunk;
             // f_chunk * t_chunk / s_chunk
-            lookup_aggreg[i + 1] *= gammabeta1 + t0 + beta * t1;
-            let prev = lookup_aggreg[i];
-            // prev * f_chunk * t_chunk / s_chunk
-            lookup_aggreg[i + 1] *= prev;
+            *aggreg *= gammabeta1 + t0 + beta * t1;
         });
 
+    // the aggregation is the prefix product of the ratios
+    prefix_product(&mut lookup_aggreg[1..]);
+
     let res = zk_patch(lookup_aggreg, d1, rng);
 
     // check that the final evaluation is equal to 1
@@ -1,11 +1,11 @@
-use super::runtime_tables::{RuntimeTableCfg, RuntimeTableSpec};
+use super::runtime_tables::{RuntimeTableCfg, RuntimeTableSpec, VariableRuntimeTable};
 use crate::circuits::{
     domains::EvaluationDomains,
-    gate::CircuitGate,
+    gate::{CircuitGate, CurrOrNext},
     lookup::{
         constraints::LookupConfiguration,
         lookups::{JointLookup, LookupInfo, LookupPattern},
-        tables::LookupTable,
+        tables::{get_table, table_id_namespace, GateLookupTable, LookupTable},
     },
     polynomials::permutation::ZK_ROWS,
 };
@@ -15,10 +15,13 @@ use ark_poly::{
     Radix2EvaluationDomain as D,
 };
 use itertools::repeat_n;
-use o1_utils::field_helpers::i32_to_field;
+use o1_utils::field_helpers::i64_to_field;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_with::serde_as;
-use std::iter;
+use std::{
+    collections::{BTreeSet, HashMap},
+    iter,
+};
 use thiserror::Error;
 
 /// Represents an error found when computing the lookup constraint system
@@ -33,32 +36,138 @@ pub enum LookupError {
     },
     #[error("The table with id 0 must have an entry of all zeros")]
     TableIDZeroMustHaveZeroEntry,
+    #[error("The table ID {id} (namespace {namespace}, id {local}) is used by both a {first:?} table and a {second:?} table")]
+    TableIDCollision {
+        id: i64,
+        namespace: u32,
+        local: u32,
+        first: LookupTableKind,
+        second: LookupTableKind,
+    },
+}
+
+/// The kinds of lookup tables of a circuit
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LookupTableKind {
+    /// A table used by the gates of kimchi
+    Gate,
+    /// A fixed table given at setup time
+    Fixed,
+    /// A runtime table
+    Runtime,
+}
+
+/// A registry of the table IDs used by a circuit, which detects the different tables
+/// sharing the same ID (as they would otherwise be silently merged into a single table).
+/// A fixed table can be given again, for example a table used by the gates.
+#[derive(Clone, Debug)]
+pub struct TableIdRegistry<F> {
+    /// The kind of the table registered with each ID
+    kinds: HashMap<i64, LookupTableKind>,
+    /// The content of the tables registered with each ID, except runtime tables
+    data: HashMap<i64, Vec<Vec<F>>>,
+}
+
+impl<F> Default for TableIdRegistry<F> {
+    fn default() -> Self {
+        Self {
+            kinds: HashMap::new(),
+            data: HashMap::new(),
+        }
+    }
+}
+
+impl<F: PartialEq + Clone> TableIdRegistry<F> {
+    fn register(
+        &mut self,
+        id: i64,
+        kind: LookupTableKind,
+        data: Option<&Vec<Vec<F>>>,
+    ) -> Result<(), LookupError> {
+        match self.kinds.get(&id) {
+            None => {
+                self.kinds.insert(id, kind);
+                if let Some(data) = data {
+                    self.data.insert(id, data.clone());
+                }
+                Ok(())
+            }
+            Some(_) if data.is_some() && self.data.get(&id) == data => Ok(()),
+            Some(&first) => {
+                let (namespace, local) = table_id_namespace(id);
+                Err(LookupError::TableIDCollision {
+                    id,
+                    namespace,
+                    local,
+                    first,
+                    second: kind,
+                })
+            }
+        }
+    }
+
+    /// Registers a table used by the gates, or a fixed table.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if a different table, or a runtime table, was registered with the same ID.
+    pub fn register_table(
+        &mut self,
+        table: &LookupTable<F>,
+        kind: LookupTableKind,
+    ) -> Result<(), LookupError> {
+        self.register(table.id, kind, Some(&table.data))
+    }
+
+    /// Registers the ID of a runtime table.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if any table was registered with the same ID.
+    pub fn register_runtime_table(&mut self, id: i64) -> Result<(), LookupError> {
+        self.register(id, LookupTableKind::Runtime, None)
+    }
+
+    /// Returns `true` if a table with this ID was registered.
+    pub fn contains(&self, id: i64) -> bool {
+        self.kinds.contains_key(&id)
+    }
 }
 
 /// Lookup selectors
 #[derive(Clone, Serialize, Deserialize, Debug, Default)]
 pub struct LookupSelectors<T> {
     /// Chacha pattern lookup selector
+    #[cfg(feature = "chacha")]
     pub chacha: Option<T>,
     /// ChachaFinal pattern lookup selector
+    #[cfg(feature = "chacha")]
     pub chacha_final: Option<T>,
     /// LookupGate pattern lookup selector
     pub lookup_gate: Option<T>,
     /// RangeCheckGate pattern lookup selector
+    #[cfg(feature = "range_check")]
     pub range_check_gate: Option<T>,
+    /// MultiTableLookupGate pattern lookup selector
+    pub multi_table_lookup_gate: Option<T>,
 }
 
 #[serde_as]
 #[derive(Clone, Serialize, Deserialize, Debug, Default)]
 struct LookupSelectorsSerdeAs<F: FftField> {
+    #[cfg(feature = "chacha")]
     #[serde_as(as = "Option<o1_utils::serialization::SerdeAs>")]
     pub chacha: Option<E<F, D<F>>>,
+    #[cfg(feature = "chacha")]
     #[serde_as(as = "Option<o1_utils::serialization::SerdeAs>")]
     pub chacha_final: Option<E<F, D<F>>>,
     #[serde_as(as = "Option<o1_utils::serialization::SerdeAs>")]
     pub lookup_gate: Option<E<F, D<F>>>,
+    #[cfg(feature = "range_check")]
     #[serde_as(as = "Option<o1_utils::serialization::SerdeAs>")]
     pub range_check_gate: Option<E<F, D<F>>>,
+    #[serde_as(as = "Option<o1_utils::serialization::SerdeAs>")]
+    pub multi_table_lookup_gate: Option<E<F, D<F>>>,
 }
 
 impl<F: FftField> serde_with::SerializeAs<LookupSelectors<E<F, D<F>>>>
@@ -69,10 +178,14 @@ impl<F: FftField> serde_with::SerializeAs<LookupSelectors<E<F, D<F>>>>
         S: serde::Serializer,
     {
         let repr = LookupSelectorsSerdeAs {
+            #[cfg(feature = "chacha")]
             chacha: val.chacha.clone(),
+            #[cfg(feature = "chacha")]
             chacha_final: val.chacha_final.clone(),
             lookup_gate: val.lookup_gate.clone(),
+            #[cfg(feature = "range_check")]
             range_check_gate: val.range_check_gate.clone(),
+            multi_table_lookup_gate: val.multi_table_lookup_gate.clone(),
         };
         repr.serialize(serializer)
     }
@@ -86,16 +199,24 @@ impl<'de, F: FftField> serde_with::DeserializeAs<'de, LookupSelectors<E<F, D<F>>
         Dz: serde::Deserializer<'de>,
     {
         let LookupSelectorsSerdeAs {
+            #[cfg(feature = "chacha")]
             chacha,
+            #[cfg(feature = "chacha")]
             chacha_final,
             lookup_gate,
+            #[cfg(feature = "range_check")]
             range_check_gate,
+            multi_table_lookup_gate,
         } = LookupSelectorsSerdeAs::deserialize(deserializer)?;
         Ok(LookupSelectors {
+            #[cfg(feature = "chacha")]
             chacha,
+            #[cfg(feature = "chacha")]
             chacha_final,
             lookup_gate,
+            #[cfg(feature = "range_check")]
             range_check_gate,
+            multi_table_lookup_gate,
         })
     }
 }
@@ -105,10 +226,14 @@ impl<T> std::ops::Index<LookupPattern> for LookupSelectors<T> {
 
     fn index(&self, index: LookupPattern) -> &Self::Output {
         match index {
+            #[cfg(feature = "chacha")]
             LookupPattern::ChaCha => &self.chacha,
+            #[cfg(feature = "chacha")]
             LookupPattern::ChaChaFinal => &self.chacha_final,
             LookupPattern::LookupGate => &self.lookup_gate,
+            #[cfg(feature = "range_check")]
             LookupPattern::RangeCheckGate => &self.range_check_gate,
+            LookupPattern::MultiTableLookupGate => &self.multi_table_lookup_gate,
         }
     }
 }
@@ -116,10 +241,14 @@ impl<T> std::ops::Index<LookupPattern> for LookupSelectors<T> {
 impl<T> std::ops::IndexMut<LookupPattern> for LookupSelectors<T> {
     fn index_mut(&mut self, index: LookupPattern) -> &mut Self::Output {
         match index {
+            #[cfg(feature = "chacha")]
             LookupPattern::ChaCha => &mut self.chacha,
+            #[cfg(feature = "chacha")]
             LookupPattern::ChaChaFinal => &mut self.chacha_final,
             LookupPattern::LookupGate => &mut self.lookup_gate,
+            #[cfg(feature = "range_check")]
             LookupPattern::RangeCheckGate => &mut self.range_check_gate,
+            LookupPattern::MultiTableLookupGate => &mut self.multi_table_lookup_gate,
         }
     }
 }
@@ -127,29 +256,41 @@ impl<T> std::ops::IndexMut<LookupPattern> for LookupSelectors<T> {
 impl<T> LookupSelectors<T> {
     pub fn map<U, F: Fn(T) -> U>(self, f: F) -> LookupSelectors<U> {
         let LookupSelectors {
+            #[cfg(feature = "chacha")]
             chacha,
+            #[cfg(feature = "chacha")]
             chacha_final,
             lookup_gate,
+            #[cfg(feature = "range_check")]
             range_check_gate,
+            multi_table_lookup_gate,
         } = self;
         // This closure isn't really redundant -- it shields the parameter from a copy -- but
         // clippy isn't smart enough to figure that out..
         #[allow(clippy::redundant_closure)]
         let f = |x| f(x);
         LookupSelectors {
+            #[cfg(feature = "chacha")]
             chacha: chacha.map(f),
+            #[cfg(feature = "chacha")]
             chacha_final: chacha_final.map(f),
             lookup_gate: lookup_gate.map(f),
+            #[cfg(feature = "range_check")]
             range_check_gate: range_check_gate.map(f),
+            multi_table_lookup_gate: multi_table_lookup_gate.map(f),
         }
     }
 
     pub fn as_ref(&self) -> LookupSelectors<&T> {
         LookupSelectors {
+            #[cfg(feature = "chacha")]
             chacha: self.chacha.as_ref(),
+            #[cfg(feature = "chacha")]
             chacha_final: self.chacha_final.as_ref(),
             lookup_gate: self.lookup_gate.as_ref(),
+            #[cfg(feature = "range_check")]
             range_check_gate: self.range_check_gate.as_ref(),
+            multi_table_lookup_gate: self.multi_table_lookup_gate.as_ref(),
         }
     }
 }
@@ -225,14 +366,25 @@ impl<F: PrimeField + SquareRootField> LookupConstraintSystem<F> {
                 let (lookup_selectors, gate_lookup_tables) =
                     lookup_info.selector_polynomials_and_tables(domain, gates);
 
-                //~ 3. Concatenate runtime lookup tables with the ones used by gates
+                //~ 3. Check that all the lookup tables (used by gates, fixed, or runtime) have distinct IDs
+                //~    (a fixed table can however be a copy of a table used by gates)
+                let mut registry = TableIdRegistry::default();
+                for table in &gate_lookup_tables {
+                    registry.register_table(table, LookupTableKind::Gate)?;
+                }
+                for table in &lookup_tables {
+                    registry.register_table(table, LookupTableKind::Fixed)?;
+                }
+                for table in runtime_tables.iter().flatten() {
+                    registry.register_runtime_table(table.id())?;
+                }
+
+                //~ 4. Concatenate runtime lookup tables with the ones used by gates
                 let mut lookup_tables: Vec<_> = gate_lookup_tables
                     .into_iter()
                     .chain(lookup_tables.into_iter())
                     .collect();
 
-                let mut has_table_id_0 = false;
-
                 // if we are using runtime tables
                 let (runtime_table_offset, runtime_selector) =
                     if let Some(runtime_tables) = &runtime_tables {
@@ -272,135 +424,16 @@ impl<F: PrimeField + SquareRootField> LookupConstraintSystem<F> {
                         };
 
                         // create fixed tables for indexing the runtime tables
-                        for runtime_table in runtime_tables {
-                            use RuntimeTableCfg::{Custom, Indexed};
-                            let (id, first_column) = match runtime_table {
-                                &Indexed(RuntimeTableSpec { id, len }) => {
-                                    let indexes = (0..(len as u32)).map(F::from).collect();
-                                    (id, indexes)
-                                }
-                                Custom { id, first_column } => (*id, first_column.clone()),
-                            };
-
-                            // record if table ID 0 is used in one of the runtime tables
-                            // note: the check later will still force you to have a fixed table with ID 0
-                            if id == 0 {
-                                has_table_id_0 = true;
-                            }
-
-                            // important: we still need a placeholder column to make sure that
-                            // if all other tables have a single column
-                            // we don't use the second table as table ID column.
-                            let placeholders = vec![F::zero(); first_column.len()];
-                            let data = vec![first_column, placeholders];
-                            let table = LookupTable { id, data };
-                            lookup_tables.push(table);
-                        }
+                        lookup_tables.extend(runtime_tables.iter().map(runtime_index_table));
 
                         (Some(runtime_table_offset), Some(runtime_selector))
                     } else {
                         (None, None)
                     };
 
-                //~ 4. Get the highest number of columns `max_table_width`
-                //~    that a lookup table can have.
-                let max_table_width = lookup_tables
-                    .iter()
-                    .map(|table| table.data.len())
-                    .max()
-                    .unwrap_or(0);
-
-                //~ 5. Create the concatenated table of all the fixed lookup tables.
-                //~    It will be of height the size of the domain,
-                //~    and of width the maximum width of any of the lookup tables.
-                //~    In addition, create an additional column to store all the tables' table IDs.
-                //~
-                //~    For example, if you have a table with ID 0
-                //~
-                //~    |       |       |       |
-                //~    | :---: | :---: | :---: |
-                //~    |   1   |   2   |   3   |
-                //~    |   5   |   6   |   7   |
-                //~    |   0   |   0   |   0   |
-                //~
-                //~    and another table with ID 1
-                //~
-                //~    |       |       |
-                //~    | :---: | :---: |
-                //~    |   8   |   9   |
-                //~
-                //~    the concatenated table in a domain of size 5 looks like this:
-                //~
-                //~    |       |       |       |
-                //~    | :---: | :---: | :---: |
-                //~    |   1   |   2   |   3   |
-                //~    |   5   |   6   |   7   |
-                //~    |   0   |   0   |   0   |
-                //~    |   8   |   9   |   0   |
-                //~    |   0   |   0   |   0   |
-                //~
-                //~    with the table id vector:
-                //~
-                //~    | table id |
-                //~    | :------: |
-                //~    |    0     |
-                //~    |    0     |
-                //~    |    0     |
-                //~    |    1     |
-                //~    |    0     |
-                //~
-                //~    To do this, for each table:
-                //~
-                let mut lookup_table = vec![Vec::with_capacity(d1_size); max_table_width];
-                let mut table_ids: Vec<F> = Vec::with_capacity(d1_size);
-
-                let mut non_zero_table_id = false;
-                let mut has_table_id_0_with_zero_entry = false;
-
-                for table in &lookup_tables {
-                    let table_len = table.data[0].len();
-
-                    if table.id == 0 {
-                        has_table_id_0 = true;
-                        if table.has_zero_entry() {
-                            has_table_id_0_with_zero_entry = true;
-                        }
-                    } else {
-                        non_zero_table_id = true;
-                    }
-
-                    //~~ - Update the corresponding entries in a table id vector (of size the domain as well)
-                    //~    with the table ID of the table.
-                    let table_id: F = i32_to_field(table.id);
-                    table_ids.extend(repeat_n(table_id, table_len));
-
-                    //~~ - Copy the entries from the table to new rows in the corresponding columns of the concatenated table.
-                    for (i, col) in table.data.iter().enumerate() {
-                        if col.len() != table_len {
-                            return Err(LookupError::InconsistentTableLength);
-                        }
-                        lookup_table[i].extend(col);
-                    }
-
-                    //~~ - Fill in any unused columns with 0 (to match the dummy value)
-                    for lookup_table in lookup_table.iter_mut().skip(table.data.len()) {
-                        lookup_table.extend(repeat_n(F::zero(), table_len));
-                    }
-                }
-
-                // If a table has ID 0, then it must have a zero entry.
-                // This is for the dummy lookups to work.
-                if has_table_id_0 && !has_table_id_0_with_zero_entry {
-                    return Err(LookupError::TableIDZeroMustHaveZeroEntry);
-                }
-
-                // Note: we use `>=` here to leave space for the dummy value.
-                if lookup_table[0].len() >= max_num_entries {
-                    return Err(LookupError::LookupTableTooLong {
-                        length: lookup_table[0].len(),
-                        maximum_allowed: max_num_entries - 1,
-                    });
-                }
+                //~ 5. Concatenate the lookup tables, as described below.
+                let (lookup_table, table_ids) =
+                    concatenate_tables(&lookup_tables, max_num_entries)?;
 
                 // For computational efficiency, we choose the dummy lookup value to be all 0s in
                 // table 0.
@@ -409,15 +442,7 @@ impl<F: PrimeField + SquareRootField> LookupConstraintSystem<F> {
                     table_id: F::zero(),
                 };
 
-                //~ 6. Pad the end of the concatened table with the dummy value.
-                lookup_table
-                    .iter_mut()
-                    .for_each(|col| col.extend(repeat_n(F::zero(), max_num_entries - col.len())));
-
-                //~ 7. Pad the end of the table id vector with 0s.
-                table_ids.extend(repeat_n(F::zero(), max_num_entries - table_ids.len()));
-
-                //~ 8. pre-compute polynomial and evaluation form for the look up tables
+                //~ 6. pre-compute polynomial and evaluation form for the look up tables
                 let mut lookup_table_polys: Vec<DP<F>> = vec![];
                 let mut lookup_table8: Vec<E<F, D<F>>> = vec![];
                 for col in lookup_table {
@@ -427,15 +452,17 @@ impl<F: PrimeField + SquareRootField> LookupConstraintSystem<F> {
                     lookup_table8.push(eval);
                 }
 
-                //~ 9. pre-compute polynomial and evaluation form for the table IDs,
+                //~ 7. pre-compute polynomial and evaluation form for the table IDs,
                 //~    only if a table with an ID different from zero was used.
-                let (table_ids, table_ids8) = if non_zero_table_id {
-                    let table_ids: DP<F> =
-                        E::<F, D<F>>::from_vec_and_domain(table_ids, domain.d1).interpolate();
-                    let table_ids8: E<F, D<F>> = table_ids.evaluate_over_domain_by_ref(domain.d8);
-                    (Some(table_ids), Some(table_ids8))
-                } else {
-                    (None, None)
+                let (table_ids, table_ids8) = match table_ids {
+                    Some(table_ids) => {
+                        let table_ids: DP<F> =
+                            E::<F, D<F>>::from_vec_and_domain(table_ids, domain.d1).interpolate();
+                        let table_ids8: E<F, D<F>> =
+                            table_ids.evaluate_over_domain_by_ref(domain.d8);
+                        (Some(table_ids), Some(table_ids8))
+                    }
+                    None => (None, None),
                 };
 
                 // store only the length of custom runtime tables in the index
@@ -460,4 +487,230 @@ impl<F: PrimeField + SquareRootField> LookupConstraintSystem<F> {
             }
         }
     }
+
+    /// Returns the runtime tables of variable length, with the rows where they start.
+    pub fn variable_runtime_tables(&self) -> Vec<VariableRuntimeTable> {
+        let mut offset = self.runtime_table_offset.unwrap_or(0);
+        let mut tables = vec![];
+        for spec in self.runtime_tables.iter().flatten() {
+            if spec.variable_len {
+                tables.push(VariableRuntimeTable {
+                    id: spec.id,
+                    offset,
+                    len: spec.len,
+                });
+            }
+            offset += spec.len;
+        }
+        tables
+    }
+}
+
+/// The specification of the lookup tables of a circuit, from which the concatenated lookup table
+/// of its index can be reproduced, for instance to audit the commitments of a verifier index
+/// (see [`VerifierIndex::verify_table_commitments`](crate::verifier_index::VerifierIndex::verify_table_commitments)).
+#[derive(Clone, Debug)]
+pub struct LookupTableSpec<F> {
+    /// The tables used by the gates of the circuit
+    pub gate_tables: Vec<GateLookupTable>,
+    /// The fixed tables given at setup time, in order
+    pub fixed_tables: Vec<LookupTable<F>>,
+    /// The configuration of the runtime tables given at setup time, if any
+    pub runtime_tables: Option<Vec<RuntimeTableCfg<F>>>,
+}
+
+impl<F: PrimeField> LookupTableSpec<F> {
+    /// Creates the specification of the lookup tables of the circuit of `gates`,
+    /// set up with the fixed tables `fixed_tables` and the runtime tables `runtime_tables`.
+    pub fn create(
+        gates: &[CircuitGate<F>],
+        fixed_tables: Vec<LookupTable<F>>,
+        runtime_tables: Option<Vec<RuntimeTableCfg<F>>>,
+    ) -> Self {
+        let gate_tables: BTreeSet<_> = gates
+            .iter()
+            .flat_map(|gate| {
+                [CurrOrNext::Curr, CurrOrNext::Next]
+                    .into_iter()
+                    .filter_map(move |row| LookupPattern::from_gate(gate.typ, row))
+            })
+            .filter_map(|pattern| pattern.table())
+            .collect();
+        Self {
+            gate_tables: gate_tables.into_iter().collect(),
+            fixed_tables,
+            runtime_tables,
+        }
+    }
+
+    /// Returns the columns of the concatenated lookup table over a domain of size `domain_size`,
+    /// and its vector of table IDs if a table has an ID different from zero,
+    /// without the rows of the zero-knowledge and of the final product of the lookup argument.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the tables are not valid lookup tables,
+    /// or if they do not fit in the domain.
+    pub fn concatenate(
+        &self,
+        domain_size: usize,
+    ) -> Result<(Vec<Vec<F>>, Option<Vec<F>>), LookupError> {
+        // the gate tables are concatenated in order, once each
+        let gate_tables: BTreeSet<_> = self.gate_tables.iter().copied().collect();
+        let lookup_tables: Vec<_> = gate_tables
+            .into_iter()
+            .map(get_table)
+            .chain(self.fixed_tables.iter().cloned())
+            .chain(
+                self.runtime_tables
+                    .iter()
+                    .flatten()
+                    .map(runtime_index_table),
+            )
+            .collect();
+
+        // the rows left to the tables, as in [`LookupConstraintSystem::create`]
+        let max_num_entries = domain_size.saturating_sub(ZK_ROWS as usize + 1);
+        concatenate_tables(&lookup_tables, max_num_entries)
+    }
+}
+
+/// Returns the fixed table indexing the runtime table of configuration `runtime_table`,
+/// which is concatenated with the other lookup tables.
+fn runtime_index_table<F: PrimeField>(runtime_table: &RuntimeTableCfg<F>) -> LookupTable<F> {
+    use RuntimeTableCfg::{Custom, Indexed};
+    let (id, first_column) = match runtime_table {
+        &Indexed(RuntimeTableSpec { id, len, .. }) => {
+            let indexes = (0..(len as u32)).map(F::from).collect();
+            (id, indexes)
+        }
+        Custom { id, first_column } => (*id, first_column.clone()),
+    };
+
+    // important: we still need a placeholder column to make sure that
+    // if all other tables have a single column
+    // we don't use the second table as table ID column.
+    let placeholders = vec![F::zero(); first_column.len()];
+    let data = vec![first_column, placeholders];
+    LookupTable { id, data }
+}
+
+/// Concatenates the lookup tables into the columns of the concatenated table
+/// and its vector of table IDs, padded to `max_num_entries` rows.
+/// The table IDs are `None` if all the tables have the ID 0.
+fn concatenate_tables<F: PrimeField>(
+    lookup_tables: &[LookupTable<F>],
+    max_num_entries: usize,
+) -> Result<(Vec<Vec<F>>, Option<Vec<F>>), LookupError> {
+    //~ 1. Get the highest number of columns `max_table_width`
+    //~    that a lookup table can have.
+    let max_table_width = lookup_tables
+        .iter()
+        .map(|table| table.data.len())
+        .max()
+        .unwrap_or(0);
+
+    //~ 1. Create the concatenated table of all the fixed lookup tables.
+    //~    It will be of height the size of the domain,
+    //~    and of width the maximum width of any of the lookup tables.
+    //~    In addition, create an additional column to store all the tables' table IDs.
+    //~
+    //~    For example, if you have a table with ID 0
+    //~
+    //~    |       |       |       |
+    //~    | :---: | :---: | :---: |
+    //~    |   1   |   2   |   3   |
+    //~    |   5   |   6   |   7   |
+    //~    |   0   |   0   |   0   |
+    //~
+    //~    and another table with ID 1
+    //~
+    //~    |       |       |
+    //~    | :---: | :---: |
+    //~    |   8   |   9   |
+    //~
+    //~    the concatenated table in a domain of size 5 looks like this:
+    //~
+    //~    |       |       |       |
+    //~    | :---: | :---: | :---: |
+    //~    |   1   |   2   |   3   |
+    //~    |   5   |   6   |   7   |
+    //~    |   0   |   0   |   0   |
+    //~    |   8   |   9   |   0   |
+    //~    |   0   |   0   |   0   |
+    //~
+    //~    with the table id vector:
+    //~
+    //~    | table id |
+    //~    | :------: |
+    //~    |    0     |
+    //~    |    0     |
+    //~    |    0     |
+    //~    |    1     |
+    //~    |    0     |
+    //~
+    //~    To do this, for each table:
+    //~
+    let mut lookup_table = vec![Vec::with_capacity(max_num_entries); max_table_width];
+    let mut table_ids: Vec<F> = Vec::with_capacity(max_num_entries);
+
+    let mut has_table_id_0 = false;
+    let mut non_zero_table_id = false;
+    let mut has_table_id_0_with_zero_entry = false;
+
+    for table in lookup_tables {
+        let table_len = table.data[0].len();
+
+        if table.id == 0 {
+            has_table_id_0 = true;
+            if table.has_zero_entry() {
+                has_table_id_0_with_zero_entry = true;
+            }
+        } else {
+            non_zero_table_id = true;
+        }
+
+        //~~ - Update the corresponding entries in a table id vector (of size the domain as well)
+        //~    with the table ID of the table.
+        let table_id: F = i64_to_field(table.id);
+        table_ids.extend(repeat_n(table_id, table_len));
+
+        //~~ - Copy the entries from the table to new rows in the corresponding columns of the concatenated table.
+        for (i, col) in table.data.iter().enumerate() {
+            if col.len() != table_len {
+                return Err(LookupError::InconsistentTableLength);
+            }
+            lookup_table[i].extend(col);
+        }
+
+        //~~ - Fill in any unused columns with 0 (to match the dummy value)
+        for lookup_table in lookup_table.iter_mut().skip(table.data.len()) {
+            lookup_table.extend(repeat_n(F::zero(), table_len));
+        }
+    }
+
+    // If a table has ID 0, then it must have a zero entry.
+    // This is for the dummy lookups to work.
+    if has_table_id_0 && !has_table_id_0_with_zero_entry {
+        return Err(LookupError::TableIDZeroMustHaveZeroEntry);
+    }
+
+    // Note: we use `>=` here to leave space for the dummy value.
+    if table_ids.len() >= max_num_entries {
+        return Err(LookupError::LookupTableTooLong {
+            length: table_ids.len(),
+            maximum_allowed: max_num_entries.saturating_sub(1),
+        });
+    }
+
+    //~ 1. Pad the end of the concatened table with the dummy value.
+    lookup_table
+        .iter_mut()
+        .for_each(|col| col.extend(repeat_n(F::zero(), max_num_entries - col.len())));
+
+    //~ 1. Pad the end of the table id vector with 0s.
+    table_ids.extend(repeat_n(F::zero(), max_num_entries - table_ids.len()));
+
+    let table_ids = non_zero_table_id.then_some(table_ids);
+    Ok((lookup_table, table_ids))
 }
@@ -1,17 +1,18 @@
+#[cfg(feature = "range_check")]
+use crate::circuits::lookup::tables::RANGE_CHECK_TABLE_ID;
+#[cfg(feature = "chacha")]
+use crate::circuits::lookup::tables::XOR_TABLE_ID;
 use crate::circuits::{
     domains::EvaluationDomains,
     gate::{CircuitGate, CurrOrNext, GateType},
     lookup::index::LookupSelectors,
-    lookup::tables::{
-        combine_table_entry, get_table, GateLookupTable, LookupTable, RANGE_CHECK_TABLE_ID,
-        XOR_TABLE_ID,
-    },
+    lookup::tables::{combine_table_entry, get_table, GateLookupTable, LookupTable},
 };
 use ark_ff::{Field, One, PrimeField, Zero};
 use ark_poly::{EvaluationDomain, Evaluations as E, Radix2EvaluationDomain as D};
-use o1_utils::field_helpers::i32_to_field;
+use o1_utils::field_helpers::i64_to_field;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
 use std::ops::{Mul, Neg};
 use strum_macros::EnumIter;
 
@@ -25,7 +26,11 @@ fn max_lookups_per_row(kinds: &[LookupPattern]) -> usize {
 
 /// Specifies whether a constraint system uses joint lookups. Used to make sure we
 /// squeeze the challenge `joint_combiner` when needed, and not when not needed.
-#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "ocaml_types",
+    derive(ocaml::IntoValue, ocaml::FromValue, ocaml_gen::Enum)
+)]
 pub enum LookupsUsed {
     Single,
     Joint,
@@ -110,7 +115,8 @@ impl LookupInfo {
             selector_values[*kind] = Some(vec![F::zero(); n]);
         }
 
-        let mut gate_tables = HashSet::new();
+        // the tables are ordered, so that the concatenated lookup table is reproducible
+        let mut gate_tables = BTreeSet::new();
 
         let mut update_selector = |lookup_pattern, i| {
             let selector = selector_values[lookup_pattern]
@@ -197,12 +203,13 @@ impl<F: Copy> SingleLookup<F> {
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub enum LookupTableID {
     /// Look up the value from the given fixed table ID
-    Constant(i32),
+    Constant(i64),
     /// Look up the value in the table with ID given by the value in the witness column
     WitnessColumn(usize),
 }
 
 /// A spec for checking that the given vector belongs to a vector-valued lookup table.
+/// The lookups of a same row can target different tables.
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct JointLookup<SingleLookup, LookupTableID> {
     /// The ID for the table associated with this lookup.
@@ -244,7 +251,7 @@ impl<F: Copy> JointLookup<SingleLookup<F>, LookupTableID> {
         K: From<u64>,
     {
         let table_id = match self.table_id {
-            LookupTableID::Constant(table_id) => i32_to_field(table_id),
+            LookupTableID::Constant(table_id) => i64_to_field(table_id),
             LookupTableID::WitnessColumn(column) => eval(LocalPosition {
                 row: CurrOrNext::Curr,
                 column,
@@ -278,28 +285,43 @@ impl<F: Copy> JointLookup<SingleLookup<F>, LookupTableID> {
 #[derive(
     Copy, Clone, Serialize, Deserialize, Debug, EnumIter, PartialEq, Eq, PartialOrd, Ord, Hash,
 )]
+#[cfg_attr(
+    feature = "ocaml_types",
+    derive(ocaml::IntoValue, ocaml::FromValue, ocaml_gen::Enum)
+)]
 pub enum LookupPattern {
+    #[cfg(feature = "chacha")]
     ChaCha,
+    #[cfg(feature = "chacha")]
     ChaChaFinal,
     LookupGate,
+    #[cfg(feature = "range_check")]
     RangeCheckGate,
+    MultiTableLookupGate,
 }
 
 impl LookupPattern {
     /// Returns the maximum number of lookups per row that are used by the pattern.
     pub fn max_lookups_per_row(&self) -> usize {
         match self {
-            LookupPattern::ChaCha | LookupPattern::ChaChaFinal | LookupPattern::RangeCheckGate => 4,
+            #[cfg(feature = "chacha")]
+            LookupPattern::ChaCha | LookupPattern::ChaChaFinal => 4,
             LookupPattern::LookupGate => 3,
+            #[cfg(feature = "range_check")]
+            LookupPattern::RangeCheckGate => 4,
+            LookupPattern::MultiTableLookupGate => 4,
         }
     }
 
     /// Returns the maximum number of values that are used in any vector lookup in this pattern.
     pub fn max_joint_size(&self) -> u32 {
         match self {
+            #[cfg(feature = "chacha")]
             LookupPattern::ChaCha | LookupPattern::ChaChaFinal => 3,
             LookupPattern::LookupGate => 2,
+            #[cfg(feature = "range_check")]
             LookupPattern::RangeCheckGate => 1,
+            LookupPattern::MultiTableLookupGate => 2,
         }
     }
 
@@ -314,6 +336,7 @@ impl LookupPattern {
             column,
         };
         match self {
+            #[cfg(feature = "chacha")]
             LookupPattern::ChaCha => {
                 (0..4)
                     .map(|i| {
@@ -338,6 +361,7 @@ impl LookupPattern {
                     })
                     .collect()
             }
+            #[cfg(feature = "chacha")]
             LookupPattern::ChaChaFinal => {
                 let one_half = F::from(2u64).inverse().unwrap();
                 let neg_one_half = -one_half;
@@ -376,6 +400,7 @@ impl LookupPattern {
                     })
                     .collect()
             }
+            #[cfg(feature = "range_check")]
             LookupPattern::RangeCheckGate => {
                 (3..=6)
                     .map(|column| {
@@ -390,27 +415,59 @@ impl LookupPattern {
                     })
                     .collect()
             }
+            LookupPattern::MultiTableLookupGate => {
+                (0..4)
+                    .map(|i| {
+                        // each lookup has its own table ID,
+                        // so that a row can look up values in different tables
+                        //
+                        // 0 1 2 3 4 5 6 7 8 9 10 11 12 13 14
+                        // t i v - - - - - - - -  -  -  -  -
+                        // - - - t i v - - - - -  -  -  -  -
+                        // - - - - - - t i v - -  -  -  -  -
+                        // - - - - - - - - - t i  v  -  -  -
+                        let index = curr_row(3 * i + 1);
+                        let value = curr_row(3 * i + 2);
+                        let l = |loc: LocalPosition| SingleLookup {
+                            value: vec![(F::one(), loc)],
+                        };
+                        JointLookup {
+                            table_id: LookupTableID::WitnessColumn(3 * i),
+                            entry: vec![l(index), l(value)],
+                        }
+                    })
+                    .collect()
+            }
         }
     }
 
     /// Returns the lookup table used by the pattern, or `None` if no specific table is rqeuired.
     pub fn table(&self) -> Option<GateLookupTable> {
         match self {
+            #[cfg(feature = "chacha")]
             LookupPattern::ChaCha | LookupPattern::ChaChaFinal => Some(GateLookupTable::Xor),
             LookupPattern::LookupGate => None,
+            #[cfg(feature = "range_check")]
             LookupPattern::RangeCheckGate => Some(GateLookupTable::RangeCheck),
+            LookupPattern::MultiTableLookupGate => None,
         }
     }
 
     /// Returns the lookup pattern used by a [`GateType`] on a given row (current or next).
     pub fn from_gate(gate_type: GateType, curr_or_next: CurrOrNext) -> Option<Self> {
-        use CurrOrNext::{Curr, Next};
+        use CurrOrNext::Curr;
         use GateType::*;
         match (gate_type, curr_or_next) {
-            (ChaCha0 | ChaCha1 | ChaCha2, Curr | Next) => Some(LookupPattern::ChaCha),
-            (ChaChaFinal, Curr | Next) => Some(LookupPattern::ChaChaFinal),
+            #[cfg(feature = "chacha")]
+            (ChaCha0 | ChaCha1 | ChaCha2, _) => Some(LookupPattern::ChaCha),
+            #[cfg(feature = "chacha")]
+            (ChaChaFinal, _) => Some(LookupPattern::ChaChaFinal),
+            #[cfg(feature = "keccak")]
+            (Xor16, Curr) => Some(LookupPattern::ChaCha),
             (Lookup, Curr) => Some(LookupPattern::LookupGate),
-            (RangeCheck0, Curr) | (RangeCheck1, Curr | Next) => Some(LookupPattern::RangeCheckGate),
+            #[cfg(feature = "range_check")]
+            (RangeCheck0, Curr) | (RangeCheck1, _) => Some(LookupPattern::RangeCheckGate),
+            (MultiTableLookup, Curr) => Some(LookupPattern::MultiTableLookupGate),
             _ => None,
         }
     }
@@ -424,12 +481,9 @@ impl GateType {
     /// See circuits/kimchi/src/polynomials/chacha.rs for an explanation of
     /// how these work.
     pub fn lookup_kinds() -> Vec<LookupPattern> {
-        vec![
-            LookupPattern::ChaCha,
-            LookupPattern::ChaChaFinal,
-            LookupPattern::LookupGate,
-            LookupPattern::RangeCheckGate,
-        ]
+        use strum::IntoEnumIterator;
+
+        LookupPattern::iter().collect()
     }
 }
 
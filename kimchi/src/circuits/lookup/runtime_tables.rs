@@ -1,23 +1,139 @@
 //! Runtime tables are tables (or arrays) that can be produced during proof creation.
 //! The setup has to prepare for their presence using [`RuntimeTableCfg`].
 //! At proving time, the prover can use [`RuntimeTable`] to specify the actual tables.
+//!
+//! The length of an indexed runtime table can also be left to the prover,
+//! up to the length fixed at setup time (see [`RuntimeTableSpec::variable_len`]).
+//! A proof then gives the number of entries it uses in each such table,
+//! and the entries it leaves unused are masked (see [`UnusedEntry`]):
+//! they are removed from the combined table, which holds the dummy entry at their rows instead,
+//! and the runtime table is constrained to be zero at their rows, as if the runtime selector covered them.
+//! Both masks are combinations of Lagrange polynomials, whose commitments the verifier computes,
+//! so that the index does not depend on the number of entries actually used.
+//!
+//! [`RuntimeTable::builder`] creates the runtime tables of a proof from their configuration,
+//! checking them before proving.
 
-use crate::circuits::{
-    expr::{prologue::*, Column},
-    gate::CurrOrNext,
+use crate::{
+    circuits::{
+        expr::{prologue::*, Column},
+        gate::CurrOrNext,
+    },
+    error::RuntimeTableError,
 };
-use ark_ff::Field;
+use ark_ff::{FftField, Field, PrimeField, Zero};
+use ark_poly::{Evaluations, Radix2EvaluationDomain as D};
+use o1_utils::field_helpers::i64_to_field;
 use serde::{Deserialize, Serialize};
 
 /// The specification of a runtime table.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RuntimeTableSpec {
     /// The table ID.
-    pub id: i32,
-    /// The number of entries contained in the runtime table.
+    pub id: i64,
+    /// The number of entries contained in the runtime table,
+    /// or the maximum number of entries if the length is variable.
+    pub len: usize,
+    /// Whether the prover can provide fewer than `len` entries,
+    /// the entries it does not provide being unused (see [`UnusedEntry`]).
+    #[serde(default)]
+    pub variable_len: bool,
+}
+
+impl RuntimeTableSpec {
+    /// Returns `true` if `len` entries can be provided at proving time for this runtime table.
+    pub fn accepts_len(&self, len: usize) -> bool {
+        if self.variable_len {
+            len <= self.len
+        } else {
+            len == self.len
+        }
+    }
+}
+
+/// A runtime table of variable length, placed in the concatenated lookup table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VariableRuntimeTable {
+    /// The table ID.
+    pub id: i64,
+    /// The row of the concatenated table where the runtime table starts.
+    pub offset: usize,
+    /// The maximum number of entries of the runtime table.
     pub len: usize,
 }
 
+/// An entry of a runtime table of variable length that a proof leaves unused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnusedEntry {
+    /// The row of the entry in the concatenated table.
+    pub row: usize,
+    /// The index of the entry in its runtime table.
+    pub index: usize,
+    /// The ID of its runtime table.
+    pub table_id: i64,
+}
+
+impl UnusedEntry {
+    /// Returns the entries left unused in the runtime tables of variable length `tables`,
+    /// given the number of entries `used` in each of them (in the configured order).
+    ///
+    /// Returns `None` if `used` does not give a number of entries,
+    /// at most the maximum, for each table.
+    pub fn all(tables: &[VariableRuntimeTable], used: &[usize]) -> Option<Vec<Self>> {
+        if tables.len() != used.len() || tables.iter().zip(used).any(|(t, used)| *used > t.len) {
+            return None;
+        }
+        let entries = tables
+            .iter()
+            .zip(used)
+            .flat_map(|(table, used)| {
+                (*used..table.len).map(move |index| UnusedEntry {
+                    row: table.offset + index,
+                    index,
+                    table_id: table.id,
+                })
+            })
+            .collect();
+        Some(entries)
+    }
+
+    /// Returns the combination of the fixed part of the entry (its index, and its table ID),
+    /// which masking the entry removes from the combined table.
+    pub fn fixed_part<F: PrimeField>(&self, table_id_combiner: F) -> F {
+        F::from(self.index as u64) + table_id_combiner * i64_to_field::<F>(self.table_id)
+    }
+
+    /// Returns the evaluations over `domain` of the mask that is `value(entry)`
+    /// at the row of each entry of `entries`, and zero at the other rows.
+    pub fn mask<F: FftField>(
+        entries: &[Self],
+        domain: D<F>,
+        value: impl Fn(&Self) -> F,
+    ) -> Evaluations<F, D<F>> {
+        let mut evals = vec![F::zero(); domain.size as usize];
+        for entry in entries {
+            evals[entry.row] = value(entry);
+        }
+        Evaluations::from_vec_and_domain(evals, domain)
+    }
+
+    /// Returns the mask of [`UnusedEntry::mask`] as runs of consecutive rows
+    /// (see [`SRS::commit_runs_non_hiding`](commitment_dlog::srs::SRS::commit_runs_non_hiding)),
+    /// the entries being sorted by row.
+    pub fn mask_runs<F: Zero>(entries: &[Self], value: impl Fn(&Self) -> F) -> Vec<(F, usize)> {
+        let mut runs = vec![];
+        let mut next_row = 0;
+        for entry in entries {
+            if entry.row > next_row {
+                runs.push((F::zero(), entry.row - next_row));
+            }
+            runs.push((value(entry), 1));
+            next_row = entry.row + 1;
+        }
+        runs
+    }
+}
+
 /// Use this type at setup time, to list all the runtime tables.
 ///
 /// Note: care must be taken as table IDs can collide with IDs of other types of lookup tables.
@@ -27,7 +143,7 @@ pub enum RuntimeTableCfg<F> {
     /// A custom runtime table can contain arbitrary values in its first column.
     Custom {
         /// The table ID.
-        id: i32,
+        id: i64,
         /// The content of the first column of the runtime table.
         first_column: Vec<F>,
     },
@@ -35,7 +151,7 @@ pub enum RuntimeTableCfg<F> {
 
 impl<F> RuntimeTableCfg<F> {
     /// Returns the ID of the runtime table.
-    pub fn id(&self) -> i32 {
+    pub fn id(&self) -> i64 {
         use RuntimeTableCfg::{Custom, Indexed};
         match self {
             Indexed(cfg) => cfg.id,
@@ -70,21 +186,130 @@ impl<F> From<RuntimeTableCfg<F>> for RuntimeTableSpec {
             Custom { id, first_column } => RuntimeTableSpec {
                 id,
                 len: first_column.len(),
+                variable_len: false,
             },
         }
     }
 }
 
 /// A runtime table. Runtime tables must match the configuration
-/// that was specified in [`RuntimeTableCfg`].
+/// that was specified in [`RuntimeTableCfg`]
+/// (with at most the configured number of entries if its length is variable,
+/// the entries it does not give being unused).
 #[derive(Debug, Clone)]
 pub struct RuntimeTable<F> {
     /// The table id.
-    pub id: i32,
+    pub id: i64,
     /// A single column.
     pub data: Vec<F>,
 }
 
+impl<F: Field> RuntimeTable<F> {
+    /// Returns a builder of the runtime tables configured by `specs`
+    /// (see [`LookupConstraintSystem::runtime_tables`](super::index::LookupConstraintSystem::runtime_tables)).
+    pub fn builder(specs: &[RuntimeTableSpec]) -> RuntimeTableBuilder<'_, F> {
+        RuntimeTableBuilder {
+            specs,
+            data: vec![None; specs.len()],
+            error: None,
+        }
+    }
+
+    /// Checks that the runtime tables `tables` match their configuration `specs`, in order.
+    ///
+    /// # Errors
+    ///
+    /// Will give error on the first runtime table that is missing, not configured,
+    /// out of order, or of a length that its configuration does not accept.
+    pub fn check(specs: &[RuntimeTableSpec], tables: &[Self]) -> Result<(), RuntimeTableError> {
+        for (i, spec) in specs.iter().enumerate() {
+            let table = tables.get(i).ok_or(RuntimeTableError::Missing(spec.id))?;
+            if table.id != spec.id {
+                return Err(if specs.iter().any(|s| s.id == table.id) {
+                    RuntimeTableError::Order {
+                        expected: spec.id,
+                        got: table.id,
+                    }
+                } else {
+                    RuntimeTableError::Unknown(table.id)
+                });
+            }
+            if !spec.accepts_len(table.data.len()) {
+                return Err(RuntimeTableError::Length {
+                    id: spec.id,
+                    len: table.data.len(),
+                    max: spec.len,
+                });
+            }
+        }
+        match tables.get(specs.len()) {
+            Some(table) => Err(RuntimeTableError::Unknown(table.id)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A builder of the runtime tables of a proof, checking them against their configuration.
+/// The runtime tables can be given in any order, and are built in the configured order.
+pub struct RuntimeTableBuilder<'a, F> {
+    specs: &'a [RuntimeTableSpec],
+    data: Vec<Option<Vec<F>>>,
+    error: Option<RuntimeTableError>,
+}
+
+impl<'a, F: Field> RuntimeTableBuilder<'a, F> {
+    /// Sets the entries of the runtime table `id`.
+    /// The first invalid runtime table is reported by [`RuntimeTableBuilder::build`].
+    pub fn table(mut self, id: i64, data: Vec<F>) -> Self {
+        if self.error.is_none() {
+            if let Err(err) = self.set(id, data) {
+                self.error = Some(err);
+            }
+        }
+        self
+    }
+
+    fn set(&mut self, id: i64, data: Vec<F>) -> Result<(), RuntimeTableError> {
+        let i = self
+            .specs
+            .iter()
+            .position(|spec| spec.id == id)
+            .ok_or(RuntimeTableError::Unknown(id))?;
+        let spec = &self.specs[i];
+        if self.data[i].is_some() {
+            return Err(RuntimeTableError::Duplicate(id));
+        }
+        if !spec.accepts_len(data.len()) {
+            return Err(RuntimeTableError::Length {
+                id,
+                len: data.len(),
+                max: spec.len,
+            });
+        }
+        self.data[i] = Some(data);
+        Ok(())
+    }
+
+    /// Returns the runtime tables, in the configured order.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if a runtime table was invalid, or if a configured runtime table is missing.
+    pub fn build(self) -> Result<Vec<RuntimeTable<F>>, RuntimeTableError> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        self.specs
+            .iter()
+            .zip(self.data)
+            .map(|(spec, data)| {
+                let data = data.ok_or(RuntimeTableError::Missing(spec.id))?;
+                Ok(RuntimeTable { id: spec.id, data })
+            })
+            .collect()
+    }
+}
+
 /// Returns the constraints related to the runtime tables.
 pub fn constraints<F>() -> Vec<E<F>>
 where
@@ -101,3 +326,105 @@ where
 
     vec![rt_check]
 }
+
+//
+// OCaml types
+//
+
+#[cfg(feature = "ocaml_types")]
+pub mod caml {
+    use super::*;
+    use std::convert::TryInto;
+
+    //
+    // RuntimeTableCfg<F> <-> CamlRuntimeTableCfg<CamlF>
+    //
+
+    /// A [`RuntimeTableCfg`], which is a custom runtime table if its `first_column` is given,
+    /// and an indexed runtime table of `len` entries (possibly fewer if `variable_len`) otherwise.
+    #[derive(ocaml::IntoValue, ocaml::FromValue, ocaml_gen::Struct)]
+    pub struct CamlRuntimeTableCfg<CamlF> {
+        pub id: ocaml::Int,
+        pub len: ocaml::Int,
+        pub variable_len: bool,
+        pub first_column: Option<Vec<CamlF>>,
+    }
+
+    impl<F, CamlF> From<RuntimeTableCfg<F>> for CamlRuntimeTableCfg<CamlF>
+    where
+        CamlF: From<F>,
+    {
+        fn from(cfg: RuntimeTableCfg<F>) -> Self {
+            let id = cfg.id().try_into().expect("i64 -> isize");
+            let len = cfg.len().try_into().expect("usize -> isize");
+            match cfg {
+                RuntimeTableCfg::Indexed(spec) => Self {
+                    id,
+                    len,
+                    variable_len: spec.variable_len,
+                    first_column: None,
+                },
+                RuntimeTableCfg::Custom { first_column, .. } => Self {
+                    id,
+                    len,
+                    variable_len: false,
+                    first_column: Some(first_column.into_iter().map(Into::into).collect()),
+                },
+            }
+        }
+    }
+
+    impl<F, CamlF> From<CamlRuntimeTableCfg<CamlF>> for RuntimeTableCfg<F>
+    where
+        F: From<CamlF>,
+    {
+        fn from(cfg: CamlRuntimeTableCfg<CamlF>) -> Self {
+            let id = cfg.id.try_into().expect("isize -> i64");
+            match cfg.first_column {
+                Some(first_column) => RuntimeTableCfg::Custom {
+                    id,
+                    first_column: first_column.into_iter().map(Into::into).collect(),
+                },
+                None => RuntimeTableCfg::Indexed(RuntimeTableSpec {
+                    id,
+                    len: cfg.len.try_into().expect("isize -> usize"),
+                    variable_len: cfg.variable_len,
+                }),
+            }
+        }
+    }
+
+    //
+    // RuntimeTable<F> <-> CamlRuntimeTable<CamlF>
+    //
+
+    #[derive(ocaml::IntoValue, ocaml::FromValue, ocaml_gen::Struct)]
+    pub struct CamlRuntimeTable<CamlF> {
+        pub id: ocaml::Int,
+        pub data: Vec<CamlF>,
+    }
+
+    impl<F, CamlF> From<RuntimeTable<F>> for CamlRuntimeTable<CamlF>
+    where
+        CamlF: From<F>,
+    {
+        fn from(rt: RuntimeTable<F>) -> Self {
+            Self {
+                id: rt.id.try_into().expect("i64 -> isize"),
+                data: rt.data.into_iter().map(Into::into).collect(),
+            }
+        }
+    }
+
+    impl<F, CamlF> From<CamlRuntimeTable<CamlF>> for RuntimeTable<F>
+    where
+        F: From<CamlF>,
+    {
+        fn from(rt: CamlRuntimeTable<CamlF>) -> Self {
+            Self {
+                id: rt.id.try_into().expect("isize -> i64"),
+                data: rt.data.into_iter().map(Into::into).collect(),
+            }
+        }
+    }
+}
@@ -0,0 +1,54 @@
+//! Lookup tables for 8-bit bitwise operations.
+//!
+//! These tables are not used by any gate, but can be added to a circuit
+//! (see [`super::get_table`]) and looked up with `Lookup` gates,
+//! whose lookups are pairs `(index, value)`.
+//! The two operands `x` and `y` of a binary operation are thus looked up
+//! at the index `256 x + y`, and the operand `x` of NOT at the index `x`.
+//!
+//! The lookups only constrain the index to be in the table,
+//! so the operands of binary operations must be range-checked to 8 bits separately.
+
+use crate::circuits::lookup::tables::{LookupTable, AND_TABLE_ID, NOT_TABLE_ID, OR_TABLE_ID};
+use ark_ff::Field;
+
+/// The number of bits of the operands of the bitwise tables.
+pub const BITWISE_BITS: u32 = 8;
+
+/// The largest operand of the bitwise tables.
+const MAX: u32 = (1 << BITWISE_BITS) - 1;
+
+/// Returns the table of a binary operation, with the entry `(256 x + y, op(x, y))`
+/// for all 8-bit `x` and `y`.
+fn binary_table<F: Field>(id: i64, op: impl Fn(u32, u32) -> u32) -> LookupTable<F> {
+    let mut data = vec![vec![]; 2];
+    for x in 0..=MAX {
+        for y in 0..=MAX {
+            data[0].push(F::from((x << BITWISE_BITS) + y));
+            data[1].push(F::from(op(x, y)));
+        }
+    }
+    LookupTable { id, data }
+}
+
+/// Returns the 8-bit AND lookup table
+pub fn and_table<F: Field>() -> LookupTable<F> {
+    binary_table(AND_TABLE_ID, |x, y| x & y)
+}
+
+/// Returns the 8-bit OR lookup table
+pub fn or_table<F: Field>() -> LookupTable<F> {
+    binary_table(OR_TABLE_ID, |x, y| x | y)
+}
+
+/// Returns the 8-bit NOT lookup table, with the entry `(x, !x)` for all 8-bit `x`
+pub fn not_table<F: Field>() -> LookupTable<F> {
+    let data = vec![
+        (0..=MAX).map(F::from).collect(),
+        (0..=MAX).map(|x| F::from(MAX - x)).collect(),
+    ];
+    LookupTable {
+        id: NOT_TABLE_ID,
+        data,
+    }
+}
@@ -2,28 +2,66 @@ use ark_ff::{FftField, One, Zero};
 use commitment_dlog::PolyComm;
 use serde::{Deserialize, Serialize};
 
+pub mod bitwise;
+#[cfg(feature = "range_check")]
 pub mod range_check;
+#[cfg(feature = "chacha")]
 pub mod xor;
 
 //~ spec:startcode
 /// The table ID associated with the XOR lookup table.
-pub const XOR_TABLE_ID: i32 = 0;
+pub const XOR_TABLE_ID: i64 = 0;
 
 /// The range check table ID.
-pub const RANGE_CHECK_TABLE_ID: i32 = 1;
+pub const RANGE_CHECK_TABLE_ID: i64 = 1;
 //~ spec:endcode
 
+/// The namespace of the preset tables, which are not used by any gate of kimchi
+/// but are provided for circuits to use (see [`bitwise`]).
+pub const PRESET_TABLE_NAMESPACE: u32 = u32::MAX;
+
+/// The table ID associated with the 8-bit AND lookup table.
+pub const AND_TABLE_ID: i64 = namespaced_table_id(PRESET_TABLE_NAMESPACE, 0);
+
+/// The table ID associated with the 8-bit OR lookup table.
+pub const OR_TABLE_ID: i64 = namespaced_table_id(PRESET_TABLE_NAMESPACE, 1);
+
+/// The table ID associated with the 8-bit NOT lookup table.
+pub const NOT_TABLE_ID: i64 = namespaced_table_id(PRESET_TABLE_NAMESPACE, 2);
+
+/// The namespace of the tables used by the gates of kimchi.
+/// Table IDs given as small non-negative integers also live in this namespace.
+pub const KIMCHI_TABLE_NAMESPACE: u32 = 0;
+
+/// Returns the ID of the table `id` within the namespace `namespace`:
+/// the namespace makes the 32 high bits of the table ID, and `id` its 32 low bits.
+/// Circuits composed together should use different namespaces, so that their tables don't collide.
+pub const fn namespaced_table_id(namespace: u32, id: u32) -> i64 {
+    (((namespace as u64) << 32) | id as u64) as i64
+}
+
+/// Returns the namespace and the ID within this namespace of a table ID,
+/// as given to [`namespaced_table_id`].
+pub const fn table_id_namespace(table_id: i64) -> (u32, u32) {
+    ((table_id as u64 >> 32) as u32, table_id as u32)
+}
+
 /// Enumerates the different 'fixed' lookup tables used by individual gates
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum GateLookupTable {
+    #[cfg(feature = "chacha")]
     Xor,
+    #[cfg(feature = "range_check")]
     RangeCheck,
+    And,
+    Or,
+    Not,
 }
 
 /// A table of values that can be used for a lookup, along with the ID for the table.
 #[derive(Debug, Clone)]
 pub struct LookupTable<F> {
-    pub id: i32,
+    pub id: i64,
     pub data: Vec<Vec<F>>,
 }
 
@@ -61,8 +99,13 @@ where
 /// Returns the lookup table associated to a [`GateLookupTable`].
 pub fn get_table<F: FftField>(table_name: GateLookupTable) -> LookupTable<F> {
     match table_name {
+        #[cfg(feature = "chacha")]
         GateLookupTable::Xor => xor::xor_table(),
+        #[cfg(feature = "range_check")]
         GateLookupTable::RangeCheck => range_check::range_check_table(),
+        GateLookupTable::And => bitwise::and_table(),
+        GateLookupTable::Or => bitwise::or_table(),
+        GateLookupTable::Not => bitwise::not_table(),
     }
 }
 
@@ -138,3 +181,55 @@ where
 
     PolyComm::multi_scalar_mul(&commitments, &scalars)
 }
+
+//
+// OCaml types
+//
+
+#[cfg(feature = "ocaml_types")]
+pub mod caml {
+    use super::*;
+    use std::convert::TryInto;
+
+    //
+    // LookupTable<F> <-> CamlLookupTable<CamlF>
+    //
+
+    #[derive(ocaml::IntoValue, ocaml::FromValue, ocaml_gen::Struct)]
+    pub struct CamlLookupTable<CamlF> {
+        pub id: ocaml::Int,
+        pub data: Vec<Vec<CamlF>>,
+    }
+
+    impl<F, CamlF> From<LookupTable<F>> for CamlLookupTable<CamlF>
+    where
+        CamlF: From<F>,
+    {
+        fn from(lt: LookupTable<F>) -> Self {
+            Self {
+                id: lt.id.try_into().expect("i64 -> isize"),
+                data: lt
+                    .data
+                    .into_iter()
+                    .map(|column| column.into_iter().map(Into::into).collect())
+                    .collect(),
+            }
+        }
+    }
+
+    impl<F, CamlF> From<CamlLookupTable<CamlF>> for LookupTable<F>
+    where
+        F: From<CamlF>,
+    {
+        fn from(lt: CamlLookupTable<CamlF>) -> Self {
+            Self {
+                id: lt.id.try_into().expect("isize -> i64"),
+                data: lt
+                    .data
+                    .into_iter()
+                    .map(|column| column.into_iter().map(Into::into).collect())
+                    .collect(),
+            }
+        }
+    }
+}
@@ -2,14 +2,23 @@
 pub mod macros;
 
 pub mod argument;
+pub mod canonical;
 pub mod constraints;
 pub mod domain_constant_evaluation;
+pub mod domain_plan;
 pub mod domains;
 pub mod expr;
 pub mod gate;
+pub mod gate_info;
+pub mod layout;
 pub mod lookup;
+pub mod optimizer;
 pub mod polynomial;
 pub mod polynomials;
+pub mod run_length;
 pub mod scalars;
 mod serialization_helper;
+pub mod trace;
 pub mod wires;
+pub mod witness;
+pub mod witness_audit;
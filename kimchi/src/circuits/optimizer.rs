@@ -0,0 +1,122 @@
+//! This module implements an optimization pass on circuits, for frontends
+//! (e.g. R1CS importers or DSLs) that generate redundant rows.
+//!
+//! A row is eliminated if it is not a public input row, if the gate of the previous row
+//! does not constrain it (i.e. is a generic or a zero gate), and if either:
+//!
+//! - it does not constrain anything (a zero gate, or a generic gate with zero coefficients),
+//!   in which case its cells are removed from the permutation cycles they belong to, or
+//! - it is a generic gate with no constant term whose cells are not wired to any other row,
+//!   in which case the cells it constrains are used nowhere else,
+//!   and its constraints are satisfied by zeros.
+//!
+//! The remaining rows are then compacted, and their wiring updated accordingly.
+
+use crate::circuits::{
+    gate::{CircuitGate, GateType},
+    polynomials::generic::GENERIC_COEFFS,
+    wires::{Wire, COLUMNS, PERMUTS},
+};
+use ark_ff::PrimeField;
+use std::array;
+
+/// A circuit, once optimized
+pub struct OptimizedCircuit<F: PrimeField> {
+    /// the gates of the optimized circuit
+    pub gates: Vec<CircuitGate<F>>,
+    /// for each row of the original circuit, its row in the optimized circuit
+    /// (or `None` if it was eliminated)
+    pub rows: Vec<Option<usize>>,
+}
+
+impl<F: PrimeField> OptimizedCircuit<F> {
+    /// Returns the witness of the optimized circuit,
+    /// given a witness of the original circuit.
+    pub fn witness(&self, witness: &[Vec<F>; COLUMNS]) -> [Vec<F>; COLUMNS] {
+        array::from_fn(|col| {
+            witness[col]
+                .iter()
+                .zip(&self.rows)
+                .filter_map(|(value, row)| row.map(|_| *value))
+                .collect()
+        })
+    }
+}
+
+/// Returns `true` if the gate does not constrain its row.
+fn is_noop<F: PrimeField>(gate: &CircuitGate<F>) -> bool {
+    match gate.typ {
+        GateType::Zero => true,
+        GateType::Generic => gate.coeffs.iter().all(F::is_zero),
+        _ => false,
+    }
+}
+
+/// Returns `true` if the gate is a generic gate without constant terms,
+/// whose cells are not wired to any other row.
+fn is_dead<F: PrimeField>(row: usize, gate: &CircuitGate<F>) -> bool {
+    let constant = |i| {
+        gate.coeffs
+            .get(i)
+            .copied()
+            .unwrap_or_else(F::zero)
+            .is_zero()
+    };
+    gate.typ == GateType::Generic
+        && constant(GENERIC_COEFFS - 1)
+        && constant(2 * GENERIC_COEFFS - 1)
+        && gate.wires.iter().all(|wire| wire.row == row)
+}
+
+/// Eliminates the rows of `gates` that are not needed (see the module documentation),
+/// the first `public` rows being the public input rows.
+pub fn optimize<F: PrimeField>(gates: &[CircuitGate<F>], public: usize) -> OptimizedCircuit<F> {
+    // find the rows to eliminate
+    let removed: Vec<bool> = gates
+        .iter()
+        .enumerate()
+        .map(|(row, gate)| {
+            let unconstrained =
+                row == 0 || matches!(gates[row - 1].typ, GateType::Generic | GateType::Zero);
+            row >= public && unconstrained && (is_noop(gate) || is_dead(row, gate))
+        })
+        .collect();
+
+    // compute the new rows
+    let mut next_row = 0;
+    let rows: Vec<_> = removed
+        .iter()
+        .map(|removed| {
+            (!removed).then(|| {
+                next_row += 1;
+                next_row - 1
+            })
+        })
+        .collect();
+
+    // skip the eliminated cells in the permutation cycles, and update the rows
+    let rewire = |mut wire: Wire| {
+        while removed[wire.row] {
+            wire = gates[wire.row].wires[wire.col];
+        }
+        Wire {
+            row: rows[wire.row].expect("the wire should point to a remaining row"),
+            col: wire.col,
+        }
+    };
+    let gates = gates
+        .iter()
+        .zip(&removed)
+        .filter(|(_, removed)| !**removed)
+        .map(|(gate, _)| {
+            let wires: [Wire; PERMUTS] = array::from_fn(|col| rewire(gate.wires[col]));
+            CircuitGate {
+                typ: gate.typ,
+                wires,
+                coeffs: gate.coeffs.clone(),
+            }
+        })
+        .collect();
+
+    OptimizedCircuit { gates, rows }
+}
@@ -548,7 +548,7 @@ mod tests {
         let mut eval = || ProofEvaluations {
             w: array::from_fn(|_| F::rand(rng)),
             z: F::rand(rng),
-            s: array::from_fn(|_| F::rand(rng)),
+            s: (0..PERMUTS - 1).map(|_| F::rand(rng)).collect(),
             generic_selector: F::zero(),
             poseidon_selector: F::zero(),
             lookup: Some(LookupEvaluations {
@@ -0,0 +1,81 @@
+//! This module implements the copy gate, [`CopyCells`].
+
+//~ The copy gate copies the cells of the next row that the permutation cannot wire
+//~ into the cells of its own row that it can, with the constraints
+//~
+//~ $$w_i = w'_{7+i}$$
+//~
+//~ for $i$ from 0 to 6, where $w'$ are the witness columns of the next row.
+//~
+//~ The copies are always enforced, so that the first 7 cells of the row of the gate
+//~ always hold the cells 7 to 13 of the next row,
+//~ whether they are wired or not.
+//~ Wiring the cells of the copy gate thus connects the non-permuted cells of the next gate
+//~ to any other cell of the circuit,
+//~ including cells pinned to constants (see the constants pool),
+//~ without spending a generic gate on each equality.
+//~
+//~ The constraints are linear in the witness, so that the gate only adds
+//~ its selector to the linearization, multiplied by the evaluation of
+//~ $\sum_i \alpha^i (w_i(\zeta) - w_{7+i}(\zeta\omega))$.
+
+use crate::circuits::{
+    argument::{Argument, ArgumentEnv, ArgumentType},
+    expr::constraints::ExprOps,
+    gate::{CircuitGate, GateType},
+    wires::{Wire, COLUMNS, PERMUTS},
+};
+use ark_ff::PrimeField;
+use std::marker::PhantomData;
+
+impl<F: PrimeField> CircuitGate<F> {
+    /// Creates a copy gate at row `row`, copying the cells 7 to 13 of the next row
+    /// into the cells 0 to 6 of its row, which can be wired.
+    pub fn create_copy_cells(row: usize) -> Self {
+        CircuitGate {
+            typ: GateType::CopyCells,
+            wires: Wire::new(row),
+            coeffs: vec![],
+        }
+    }
+
+    /// Verifies the copy gate at `row`.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `self.typ` is not `GateType::CopyCells`,
+    /// if there is no next row, or if a copy does not hold.
+    pub fn verify_copy_cells(&self, row: usize, witness: &[Vec<F>; COLUMNS]) -> Result<(), String> {
+        ensure_eq!(self.typ, GateType::CopyCells, "incorrect gate type");
+        if row + 1 >= witness[0].len() {
+            return Err("the copy gate has no next row".to_string());
+        }
+
+        for i in 0..PERMUTS {
+            if witness[i][row] != witness[PERMUTS + i][row + 1] {
+                return Err(format!(
+                    "the cell {i} is not a copy of the cell {} of the next row",
+                    PERMUTS + i
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The copy gate (see the module documentation)
+pub struct CopyCells<F>(PhantomData<F>);
+
+impl<F> Argument<F> for CopyCells<F>
+where
+    F: PrimeField,
+{
+    const ARGUMENT_TYPE: ArgumentType = ArgumentType::Gate(GateType::CopyCells);
+    const CONSTRAINTS: u32 = PERMUTS as u32;
+
+    fn constraint_checks<T: ExprOps<F>>(env: &ArgumentEnv<F, T>) -> Vec<T> {
+        (0..PERMUTS)
+            .map(|i| env.witness_curr(i) - env.witness_next(PERMUTS + i))
+            .collect()
+    }
+}
@@ -279,7 +279,7 @@ pub fn gen_witness<F: PrimeField + std::fmt::Display>(
     a * endo_scalar + b
 }
 
-fn c_func<F: Field>(x: F) -> F {
+pub(crate) fn c_func<F: Field>(x: F) -> F {
     let zero = F::zero();
     let one = F::one();
     let two = F::from(2u64);
@@ -294,7 +294,7 @@ fn c_func<F: Field>(x: F) -> F {
     }
 }
 
-fn d_func<F: Field>(x: F) -> F {
+pub(crate) fn d_func<F: Field>(x: F) -> F {
     let zero = F::zero();
     let one = F::one();
     let two = F::from(2u64);
@@ -14,7 +14,7 @@ use crate::{
     circuits::{
         argument::{Argument, ArgumentType},
         constraints::ConstraintSystem,
-        expr::{self, l0_1, Environment, LookupEnvironment, E},
+        expr::{self, Environment, LookupEnvironment, E},
         gate::{CircuitGate, CircuitGateError, CircuitGateResult, Connect, GateType},
         lookup::{
             self,
@@ -227,10 +227,10 @@ impl<F: PrimeField> CircuitGate<F> {
                     foreign_field_modulus: cs.foreign_field_modulus.clone(),
                 },
                 witness: &witness_evals.d8.this.w,
-                coefficient: &cs.coefficients8,
+                coefficient: cs.coefficients8(),
                 vanishes_on_last_4_rows: &cs.precomputations().vanishes_on_last_4_rows,
                 z: &witness_evals.d8.this.z,
-                l0_1: l0_1(cs.domain.d1),
+                l0_1: cs.precomputations().l0_1,
                 domain: cs.domain,
                 index: index_evals,
                 lookup: lookup_env,
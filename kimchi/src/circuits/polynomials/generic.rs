@@ -1,4 +1,5 @@
-//! This module implements the double generic gate.
+//! This module implements the double generic gate,
+//! and the triple generic gate that adds a third generic gate to it.
 
 //~ The double generic gate contains two generic gates.
 //~
@@ -32,10 +33,31 @@
 //~ with m1 (resp. m2) the mul selector for the first (resp. second) gate,
 //~ and c1 (resp. c2) the constant selector for the first (resp. second) gate.
 //~
+//~ The triple generic gate (`TripleGeneric`) adds a third generic gate,
+//~ with registers of its own in the next three columns:
+//~
+//~ |  0 |  1 |  2 |  3 |  4 |  5 |  6 |  7 |  8 | 9 | 10 | 11 | 12 | 13 | 14 |
+//~ |:--:|:--:|:--:|:--:|:--:|:--:|:--:|:--:|:--:|:-:|:--:|:--:|:--:|:--:|:--:|
+//~ | l1 | r1 | o1 | l2 | r2 | o2 | l3 | r3 | o3 |   |    |    |    |    |    |
+//~
+//~ and its selectors in the next five coefficients:
+//~
+//~ |  0 |  1 |  2 |  3 |  4 |  5 | 6  |  7 |  8 |  9 | 10 | 11 | 12 | 13 | 14 |
+//~ |:--:|:--:|:--:|:--:|:--:|:--:|:--:|:--:|:--:|:--:|:--:|:--:|:--:|:--:|:--:|
+//~ | l1 | r1 | o1 | m1 | c1 | l2 | r2 | o2 | m2 | c2 | l3 | r3 | o3 | m3 | c3 |
+//~
+//~ As only the first 7 columns are wired by the permutation,
+//~ r3 and o3 can only be connected to other cells through a copy gate on the previous row.
+//~
+//~ The third generic gate is opt-in: its constraint is only added to the circuits
+//~ that use the triple generic gate, where it applies to the rows of both gates
+//~ (the double generic gates leaving the coefficients of the third gate to zero).
+//~ The generic selector is set on the rows of both gates.
+//~
 
 use crate::circuits::{
     constraints::ConstraintSystem,
-    gate::{CircuitGate, GateType},
+    gate::{CircuitGate, CircuitGateError, CircuitGateResult, GateType},
     polynomial::COLUMNS,
     wires::GateWires,
 };
@@ -49,6 +71,9 @@ use std::array;
 /// Number of constraints produced by the gate.
 pub const CONSTRAINTS: u32 = 2;
 
+/// Number of constraints produced by the gate in the circuits using the triple generic gate.
+pub const TRIPLE_GENERIC_CONSTRAINTS: u32 = 3;
+
 /// Number of generic of registers by a single generic gate
 pub const GENERIC_REGISTERS: usize = 3;
 
@@ -63,6 +88,27 @@ pub const DOUBLE_GENERIC_COEFFS: usize = GENERIC_COEFFS * 2;
 /// Number of generic of registers by a double generic gate.
 pub const DOUBLE_GENERIC_REGISTERS: usize = GENERIC_REGISTERS * 2;
 
+/// The triple generic gate adds a third generic gate to the double generic gate.
+pub const TRIPLE_GENERIC_COEFFS: usize = GENERIC_COEFFS * 3;
+
+/// Number of registers used by a triple generic gate.
+pub const TRIPLE_GENERIC_REGISTERS: usize = GENERIC_REGISTERS * 3;
+
+/// The left, right and output registers of each of the three generic gates of a row,
+/// the third one being only used by the triple generic gate.
+pub const GENERIC_GATES_REGISTERS: [[usize; GENERIC_REGISTERS]; 3] =
+    [[0, 1, 2], [3, 4, 5], [6, 7, 8]];
+
+/// Returns the number of constraints of the generic gates,
+/// for a circuit using the triple generic gate or not.
+pub fn constraints(triple_generic: bool) -> u32 {
+    if triple_generic {
+        TRIPLE_GENERIC_CONSTRAINTS
+    } else {
+        CONSTRAINTS
+    }
+}
+
 /// The different type of computation that are possible with a generic gate.
 /// This type is useful to create a generic gate via the [`CircuitGate::create_generic_gadget`] function.
 pub enum GenericGateSpec<F> {
@@ -88,25 +134,20 @@ pub enum GenericGateSpec<F> {
     Pub,
 }
 
-impl<F: PrimeField> CircuitGate<F> {
-    /// This allows you to create two generic gates that will fit in one row, check [`Self::create_generic_gadget`] for a better to way to create these gates.
-    pub fn create_generic(wires: GateWires, c: [F; GENERIC_COEFFS * 2]) -> Self {
-        CircuitGate {
-            typ: GateType::Generic,
-            wires,
-            coeffs: c.to_vec(),
-        }
-    }
-
-    /// This allows you to create two generic gates by passing the desired
-    /// `gate1` and `gate2` as two [`GenericGateSpec`].
-    pub fn create_generic_gadget(
-        wires: GateWires,
-        gate1: GenericGateSpec<F>,
-        gate2: Option<GenericGateSpec<F>>,
-    ) -> Self {
-        let mut coeffs = [F::zero(); GENERIC_COEFFS * 2];
-        match gate1 {
+impl<F: PrimeField> GenericGateSpec<F> {
+    /// Returns the coefficients of a single generic gate.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the public input is added to another generic gate
+    /// than the first one of a row of type `typ`.
+    pub(crate) fn coeffs(
+        self,
+        typ: GateType,
+        first: bool,
+    ) -> CircuitGateResult<[F; GENERIC_COEFFS]> {
+        let mut coeffs = [F::zero(); GENERIC_COEFFS];
+        match self {
             GenericGateSpec::Add {
                 left_coeff,
                 right_coeff,
@@ -128,37 +169,73 @@ impl<F: PrimeField> CircuitGate<F> {
                 coeffs[4] = -cst;
             }
             GenericGateSpec::Pub => {
+                if !first {
+                    return Err(CircuitGateError::PublicInputGate(typ));
+                }
                 coeffs[0] = F::one();
             }
         };
-        match gate2 {
-            Some(GenericGateSpec::Add {
-                left_coeff,
-                right_coeff,
-                output_coeff,
-            }) => {
-                coeffs[5] = left_coeff.unwrap_or_else(F::one);
-                coeffs[6] = right_coeff.unwrap_or_else(F::one);
-                coeffs[7] = output_coeff.unwrap_or_else(|| -F::one());
-            }
-            Some(GenericGateSpec::Mul {
-                output_coeff,
-                mul_coeff,
-            }) => {
-                coeffs[7] = output_coeff.unwrap_or_else(|| -F::one());
-                coeffs[8] = mul_coeff.unwrap_or_else(F::one);
-            }
-            Some(GenericGateSpec::Const(cst)) => {
-                coeffs[5] = F::one();
-                coeffs[9] = -cst;
-            }
-            Some(GenericGateSpec::Pub) => {
-                coeffs[5] = F::one();
-                unimplemented!();
-            }
-            None => (),
+        Ok(coeffs)
+    }
+}
+
+impl<F: PrimeField> CircuitGate<F> {
+    /// This allows you to create two generic gates that will fit in one row, check [`Self::create_generic_gadget`] for a better to way to create these gates.
+    pub fn create_generic(wires: GateWires, c: [F; GENERIC_COEFFS * 2]) -> Self {
+        CircuitGate {
+            typ: GateType::Generic,
+            wires,
+            coeffs: c.to_vec(),
+        }
+    }
+
+    /// This allows you to create two generic gates by passing the desired
+    /// `gate1` and `gate2` as two [`GenericGateSpec`].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `gate2` is [`GenericGateSpec::Pub`].
+    pub fn create_generic_gadget(
+        wires: GateWires,
+        gate1: GenericGateSpec<F>,
+        gate2: Option<GenericGateSpec<F>>,
+    ) -> Self {
+        let coeffs = |gate: GenericGateSpec<F>, first| {
+            gate.coeffs(GateType::Generic, first)
+                .expect("the public input can only be added to the first generic gate")
         };
-        Self::create_generic(wires, coeffs)
+        let mut c = [F::zero(); GENERIC_COEFFS * 2];
+        c[..GENERIC_COEFFS].copy_from_slice(&coeffs(gate1, true));
+        if let Some(gate2) = gate2 {
+            c[GENERIC_COEFFS..].copy_from_slice(&coeffs(gate2, false));
+        }
+        Self::create_generic(wires, c)
+    }
+
+    /// This allows you to create three generic gates that will fit in one row,
+    /// as a triple generic gate, the third gate `gate3` having its registers
+    /// in the columns 6 to 8 (see [`GENERIC_GATES_REGISTERS`]).
+    /// Only the first of them is wired: the others can be wired through a copy gate
+    /// on the previous row (see [`CircuitGate::create_copy_cells`]).
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `gate2` or `gate3` is [`GenericGateSpec::Pub`],
+    /// as the public input can only be added to the first generic gate.
+    pub fn create_triple_generic_gadget(
+        wires: GateWires,
+        gate1: GenericGateSpec<F>,
+        gate2: GenericGateSpec<F>,
+        gate3: GenericGateSpec<F>,
+    ) -> CircuitGateResult<Self> {
+        let typ = GateType::TripleGeneric;
+        let coeffs = [
+            gate1.coeffs(typ, true)?,
+            gate2.coeffs(typ, false)?,
+            gate3.coeffs(typ, false)?,
+        ]
+        .concat();
+        Ok(CircuitGate { typ, wires, coeffs })
     }
 }
 
@@ -169,28 +246,32 @@ impl<F: PrimeField> CircuitGate<F> {
 //~ * $w_0 \cdot c_0 + w_1 \cdot c_1 + w_2 \cdot c_2 + w_0 \cdot w_1 \cdot c_3 + c_4$
 //~ * $w_3 \cdot c_5 + w_4 \cdot c_6 + w_5 \cdot c_7 + w_3 w_4 c_8 + c_9$
 //~
+//~ and, in the circuits using the triple generic gate,
+//~
+//~ * $w_6 \cdot c_{10} + w_7 \cdot c_{11} + w_8 \cdot c_{12} + w_6 w_7 c_{13} + c_{14}$
+//~
 //~ where the $c_i$ are the [coefficients]().
 
 impl<F: PrimeField> ConstraintSystem<F> {
-    /// generic constraint quotient poly contribution computation
+    /// generic constraint quotient poly contribution computation,
+    /// with the third generic gate if the circuit uses the triple generic gate
     pub fn gnrc_quot(
         &self,
         mut alphas: impl Iterator<Item = F>,
         witness_cols_d4: &[Evaluations<F, D<F>>; COLUMNS],
     ) -> Evaluations<F, D<F>> {
-        let generic_gate = |alpha_pow, coeff_offset, register_offset| {
+        let generic_gate = |alpha_pow, coeff_offset, registers: &[usize; GENERIC_REGISTERS]| {
             let mut res = Evaluations::from_vec_and_domain(
                 vec![F::zero(); self.domain.d4.size()],
                 self.domain.d4,
             );
 
             // addition
-            for (witness_d4, selector_d8) in witness_cols_d4
+            for (register, selector_d8) in registers
                 .iter()
-                .skip(register_offset)
-                .zip(self.coefficients8.iter().skip(coeff_offset))
-                .take(GENERIC_REGISTERS)
+                .zip(self.coefficients8().iter().skip(coeff_offset))
             {
+                let witness_d4 = &witness_cols_d4[*register];
                 res.evals
                     .par_iter_mut()
                     .enumerate()
@@ -198,8 +279,8 @@ impl<F: PrimeField> ConstraintSystem<F> {
             }
 
             // multiplication
-            let mut mul = &witness_cols_d4[register_offset] * &witness_cols_d4[register_offset + 1];
-            let mul_selector_d8 = &self.coefficients8[coeff_offset + 3];
+            let mut mul = &witness_cols_d4[registers[0]] * &witness_cols_d4[registers[1]];
+            let mul_selector_d8 = &self.coefficients8()[coeff_offset + 3];
             mul.evals
                 .par_iter_mut()
                 .enumerate()
@@ -207,7 +288,7 @@ impl<F: PrimeField> ConstraintSystem<F> {
             res += &mul;
 
             // constant
-            let constant_d8 = &self.coefficients8[coeff_offset + 4];
+            let constant_d8 = &self.coefficients8()[coeff_offset + 4];
             res.evals
                 .par_iter_mut()
                 .enumerate()
@@ -223,60 +304,64 @@ impl<F: PrimeField> ConstraintSystem<F> {
             &res * &alpha_pow
         };
 
-        let alpha_pow1 = alphas
-            .next()
-            .expect("not enough powers of alpha for the generic gate");
-        let mut res = generic_gate(alpha_pow1, 0, 0);
-
-        let alpha_pow2 = alphas
-            .next()
-            .expect("not enough powers of alpha for the generic gate");
-        res += &generic_gate(alpha_pow2, GENERIC_COEFFS, GENERIC_REGISTERS);
+        let mut res = Evaluations::from_vec_and_domain(
+            vec![F::zero(); self.domain.d4.size()],
+            self.domain.d4,
+        );
+        let gates = constraints(self.triple_generic) as usize;
+        for (gate, registers) in GENERIC_GATES_REGISTERS.iter().take(gates).enumerate() {
+            let alpha_pow = alphas
+                .next()
+                .expect("not enough powers of alpha for the generic gate");
+            res += &generic_gate(alpha_pow, gate * GENERIC_COEFFS, registers);
+        }
 
         // generic selector
         &res * &self.generic4
     }
 
-    /// produces
+    /// produces, for each of the generic gates of the row and its registers `l, r, o`,
     ///
     /// ```ignore
-    /// alpha * generic(zeta) * w[0](zeta) * w[1](zeta),
-    /// alpha * generic(zeta) * w[0](zeta),
-    /// alpha * generic(zeta) * w[1](zeta),
-    /// alpha * generic(zeta) * w[2](zeta)
+    /// alpha * generic(zeta) * w[l](zeta),
+    /// alpha * generic(zeta) * w[r](zeta),
+    /// alpha * generic(zeta) * w[o](zeta),
+    /// alpha * generic(zeta) * w[l](zeta) * w[r](zeta),
+    /// alpha * generic(zeta)
     /// ```
+    ///
+    /// the third generic gate being included if `triple_generic` is set
     pub fn gnrc_scalars(
         mut alphas: impl Iterator<Item = F>,
         w_zeta: &[F; COLUMNS],
         generic_zeta: F,
+        triple_generic: bool,
     ) -> Vec<F> {
         // setup
         let mut res = vec![];
 
-        let mut generic_gate = |alpha_pow, register_offset| {
+        let mut generic_gate = |alpha_pow, [l, r, o]: [usize; GENERIC_REGISTERS]| {
             let alpha_generic = alpha_pow * generic_zeta;
 
             // addition
-            res.push(alpha_generic * w_zeta[register_offset]);
-            res.push(alpha_generic * w_zeta[register_offset + 1]);
-            res.push(alpha_generic * w_zeta[register_offset + 2]);
+            res.push(alpha_generic * w_zeta[l]);
+            res.push(alpha_generic * w_zeta[r]);
+            res.push(alpha_generic * w_zeta[o]);
 
             // multplication
-            res.push(alpha_generic * w_zeta[register_offset] * w_zeta[register_offset + 1]);
+            res.push(alpha_generic * w_zeta[l] * w_zeta[r]);
 
             // constant
             res.push(alpha_generic);
         };
 
-        let alpha_pow1 = alphas
-            .next()
-            .expect("not enough alpha powers for generic gate");
-        generic_gate(alpha_pow1, 0);
-
-        let alpha_pow2 = alphas
-            .next()
-            .expect("not enough alpha powers for generic gate");
-        generic_gate(alpha_pow2, GENERIC_REGISTERS);
+        let gates = constraints(triple_generic) as usize;
+        for registers in GENERIC_GATES_REGISTERS.into_iter().take(gates) {
+            let alpha_pow = alphas
+                .next()
+                .expect("not enough alpha powers for generic gate");
+            generic_gate(alpha_pow, registers);
+        }
 
         res
     }
@@ -292,18 +377,17 @@ impl<F: PrimeField> ConstraintSystem<F> {
         let n = d1.size();
 
         // get scalars
-        let scalars = Self::gnrc_scalars(alphas, w_zeta, generic_zeta);
+        let scalars = Self::gnrc_scalars(alphas, w_zeta, generic_zeta, self.triple_generic);
 
         //
         let mut res = Evaluations::from_vec_and_domain(vec![F::zero(); n], d1);
 
-        let scale = self.coefficients8[0].evals.len() / n;
-
-        let coeffs = self.coefficients8.iter();
-        for (scalar, coeff) in scalars.into_iter().zip(coeffs) {
-            res.evals.par_iter_mut().enumerate().for_each(|(i, e)| {
-                *e += scalar * coeff[scale * i];
-            });
+        // the coefficient columns only hold a few runs of nonzero values
+        for (scalar, coeff) in scalars.into_iter().zip(&self.coefficients) {
+            for (rows, value) in coeff.nonzero_runs() {
+                let term = scalar * value;
+                res.evals[rows].par_iter_mut().for_each(|e| *e += term);
+            }
         }
 
         // l * qwm[0] + r * qwm[1] + o * qwm[2] + l * r * qmm + qc
@@ -323,7 +407,7 @@ pub mod testing {
         ///
         /// # Errors
         ///
-        /// Will give error if `self.typ` is not `GateType::Generic`.
+        /// Will give error if `self.typ` is not `GateType::Generic` or `GateType::TripleGeneric`.
         pub fn verify_generic(
             &self,
             row: usize,
@@ -337,9 +421,13 @@ pub mod testing {
             let zero = F::zero();
 
             // check if it's the correct gate
-            ensure_eq!(self.typ, GateType::Generic, "generic: incorrect gate");
+            let gates = match self.typ {
+                GateType::Generic => CONSTRAINTS,
+                GateType::TripleGeneric => TRIPLE_GENERIC_CONSTRAINTS,
+                _ => return Err("generic: incorrect gate".to_string()),
+            };
 
-            let check_single = |coeffs_offset, register_offset| {
+            let check_single = |coeffs_offset, [l, r, o]: [usize; GENERIC_REGISTERS]| {
                 let get = |offset| {
                     self.coeffs
                         .get(offset)
@@ -352,10 +440,8 @@ pub mod testing {
                 let m_coeff = get(coeffs_offset + 3);
                 let c_coeff = get(coeffs_offset + 4);
 
-                let sum = l_coeff * this[register_offset]
-                    + r_coeff * this[register_offset + 1]
-                    + o_coeff * this[register_offset + 2];
-                let mul = m_coeff * this[register_offset] * this[register_offset + 1];
+                let sum = l_coeff * this[l] + r_coeff * this[r] + o_coeff * this[o];
+                let mul = m_coeff * this[l] * this[r];
                 let public = if coeffs_offset == 0 {
                     public.get(row).copied().unwrap_or_else(F::zero)
                 } else {
@@ -369,8 +455,11 @@ pub mod testing {
                 Ok(())
             };
 
-            check_single(0, 0)?;
-            check_single(GENERIC_COEFFS, GENERIC_REGISTERS)
+            GENERIC_GATES_REGISTERS
+                .into_iter()
+                .take(gates as usize)
+                .enumerate()
+                .try_for_each(|(gate, registers)| check_single(gate * GENERIC_COEFFS, registers))
         }
     }
 
@@ -382,17 +471,16 @@ pub mod testing {
             public: &DensePolynomial<F>,
         ) -> bool {
             let coefficientsm: [_; COLUMNS] =
-                array::from_fn(|i| self.coefficients8[i].clone().interpolate());
+                array::from_fn(|i| self.coefficients8()[i].clone().interpolate());
 
-            let generic_gate = |coeff_offset, register_offset| {
+            let generic_gate = |coeff_offset, [l, r, o]: [usize; GENERIC_REGISTERS]| {
                 // addition (of left, right, output wires)
-                let mut ff = &coefficientsm[coeff_offset] * &witness[register_offset];
-                ff += &(&coefficientsm[coeff_offset + 1] * &witness[register_offset + 1]);
-                ff += &(&coefficientsm[coeff_offset + 2] * &witness[register_offset + 2]);
+                let mut ff = &coefficientsm[coeff_offset] * &witness[l];
+                ff += &(&coefficientsm[coeff_offset + 1] * &witness[r]);
+                ff += &(&coefficientsm[coeff_offset + 2] * &witness[o]);
 
                 // multiplication
-                ff += &(&(&witness[register_offset] * &witness[register_offset + 1])
-                    * &coefficientsm[coeff_offset + 3]);
+                ff += &(&(&witness[l] * &witness[r]) * &coefficientsm[coeff_offset + 3]);
 
                 // constant
                 &ff + &coefficientsm[coeff_offset + 4]
@@ -400,8 +488,11 @@ pub mod testing {
                 // note: skip alpha power, as we're testing for completeness
             };
 
-            let mut res = generic_gate(0, 0);
-            res += &generic_gate(GENERIC_COEFFS, GENERIC_REGISTERS);
+            let mut res = DensePolynomial::zero();
+            let gates = constraints(self.triple_generic) as usize;
+            for (gate, registers) in GENERIC_GATES_REGISTERS.into_iter().take(gates).enumerate() {
+                res += &generic_gate(gate * GENERIC_COEFFS, registers);
+            }
 
             // public inputs
             res += public;
@@ -0,0 +1,124 @@
+//! Keccak gates
+
+use crate::circuits::{
+    argument::{Argument, ArgumentEnv, ArgumentType},
+    expr::constraints::ExprOps,
+    gate::GateType,
+};
+use ark_ff::PrimeField;
+use std::marker::PhantomData;
+
+//~ The Keccak gadgets prove the permutation Keccak-f[1600] over its 64-bit lanes,
+//~ with two gates: `Xor16` for the XOR of two lanes, and `Rot64` for the rotation of a lane.
+//~ The other operations of the rounds are linear combinations of lanes:
+//~ the negation is $\bar{b} = 2^{64} - 1 - b$ and the conjunction is
+//~ $a \wedge b = (a + b - (a \oplus b)) / 2$, so that the step χ only adds generic gates
+//~ to the XORs, as $\bar{b} \wedge c = (c - b + (b \oplus c)) / 2$.
+//~
+//~ ##### `Xor16` - XOR of 16 bits
+//~
+//~ The gate XORs the lowest 16 bits of its inputs, whose remaining bits are moved to the next row,
+//~ so that four chained `Xor16` rows XOR two 64-bit lanes.
+//~ The nybbles of the inputs and of the output are looked up in the XOR table
+//~ (with the lookup pattern of the ChaCha gates, in the columns 3 to 14).
+//~
+//~ | Column | `Curr`    | `Next`   |
+//~ | ------ | --------- | -------- |
+//~ |      0 | `in1`     | `in1'`   |
+//~ |      1 | `in2`     | `in2'`   |
+//~ |      2 | `out`     | `out'`   |
+//~ |  3 - 6 | `in1_i`   |          |
+//~ | 7 - 10 | `in2_i`   |          |
+//~ | 11- 14 | `out_i`   |          |
+//~
+//~ with the constraints
+//~
+//~ * $in1 = \sum_{i=0}^{3} 2^{4i} in1_i + 2^{16} in1'$
+//~ * $in2 = \sum_{i=0}^{3} 2^{4i} in2_i + 2^{16} in2'$
+//~ * $out = \sum_{i=0}^{3} 2^{4i} out_i + 2^{16} out'$
+//~
+//~ The row following the fourth `Xor16` row of a lane holds zeros in its columns 0 to 2
+//~ (a generic gate constraining them to zero),
+//~ which also checks that the inputs and the output are 64-bit values.
+
+/// The XOR gate on 16 bits (see the module documentation)
+#[derive(Default)]
+pub struct Xor16<F>(PhantomData<F>);
+
+impl<F> Argument<F> for Xor16<F>
+where
+    F: PrimeField,
+{
+    const ARGUMENT_TYPE: ArgumentType = ArgumentType::Gate(GateType::Xor16);
+    const CONSTRAINTS: u32 = 3;
+
+    fn constraint_checks<T: ExprOps<F>>(env: &ArgumentEnv<F, T>) -> Vec<T> {
+        (0..3)
+            .map(|i| {
+                // the nybbles of the variable i are in the columns 3 + 4 i to 6 + 4 i
+                let mut power_of_2 = T::one();
+                let mut sum_of_nybbles = T::zero();
+                for col in 3 + 4 * i..7 + 4 * i {
+                    sum_of_nybbles += power_of_2.clone() * env.witness_curr(col);
+                    power_of_2 *= T::from(16u64);
+                }
+                sum_of_nybbles + power_of_2 * env.witness_next(i) - env.witness_curr(i)
+            })
+            .collect()
+    }
+}
+
+//~ ##### `Rot64` - Rotation of 64 bits
+//~
+//~ The gate rotates a 64-bit `word` to the left by `rot` bits into `rotated`,
+//~ splitting the `word` shifted by `rot` bits into its 64 lowest bits `shifted`
+//~ and its `rot` highest bits `excess`:
+//~
+//~ | Column | `Curr`      |
+//~ | ------ | ----------- |
+//~ |      0 | `word`      |
+//~ |      1 | `rotated`   |
+//~ |      2 | `excess`    |
+//~ |      3 | `shifted`   |
+//~ |      4 | `bound`     |
+//~ |      5 | `two_to_rot`|
+//~
+//~ with the constraints
+//~
+//~ * $word \cdot 2^{rot} = excess \cdot 2^{64} + shifted$
+//~ * $rotated = shifted + excess$
+//~ * $bound = excess - 2^{rot} + 2^{64}$
+//~
+//~ The gate has no coefficients, as the linearization does not evaluate the coefficients:
+//~ `two_to_rot` is wired to a constant $2^{rot}$ of a generic gate.
+//~ The cells `word`, `shifted` and `bound` are wired to `RangeCheck0` rows
+//~ checking that they are 64-bit values (with their columns 1 and 2 wired to zero),
+//~ so that `excess` is less than $2^{rot}$, and the decomposition is unique.
+
+/// The rotation gate on 64 bits (see the module documentation)
+#[derive(Default)]
+pub struct Rot64<F>(PhantomData<F>);
+
+impl<F> Argument<F> for Rot64<F>
+where
+    F: PrimeField,
+{
+    const ARGUMENT_TYPE: ArgumentType = ArgumentType::Gate(GateType::Rot64);
+    const CONSTRAINTS: u32 = 3;
+
+    fn constraint_checks<T: ExprOps<F>>(env: &ArgumentEnv<F, T>) -> Vec<T> {
+        let word = env.witness_curr(0);
+        let rotated = env.witness_curr(1);
+        let excess = env.witness_curr(2);
+        let shifted = env.witness_curr(3);
+        let bound = env.witness_curr(4);
+        let two_to_rot = env.witness_curr(5);
+        let two_to_64 = T::literal(F::from(2u64).pow([64]));
+
+        vec![
+            word * two_to_rot.clone() - (excess.clone() * two_to_64.clone() + shifted.clone()),
+            rotated - (shifted + excess.clone()),
+            bound - (excess - two_to_rot + two_to_64),
+        ]
+    }
+}
@@ -0,0 +1,450 @@
+//! This module obtains the gates of the Keccak gadgets, and lays them out for the permutation.
+
+use ark_ff::PrimeField;
+use std::array;
+
+use crate::{
+    alphas::Alphas,
+    circuits::{
+        argument::Argument,
+        expr::E,
+        gate::{CircuitGate, Connect, GateType},
+        polynomial::COLUMNS,
+        polynomials::generic::GenericGateSpec,
+        wires::{Wire, PERMUTS},
+    },
+};
+
+use super::{
+    circuitgates::{Rot64, Xor16},
+    lane, pad, witness, DIGEST_LANES, RATE_LANES, ROTATION_OFFSETS, ROUNDS, ROUND_CONSTANTS,
+    STATE_LANES,
+};
+
+/// Number of gates of the Keccak gadgets
+pub const GATE_COUNT: usize = 2;
+
+/// Number of rows of a XOR of two lanes (see [`CircuitGate::create_xor64`])
+pub const XOR64_ROWS: usize = 5;
+
+/// Number of rows of a rotation of a lane (see [`CircuitGate::create_rot64`])
+pub const ROT64_ROWS: usize = 5;
+
+impl<F: PrimeField> CircuitGate<F> {
+    /// Create a XOR of two 64-bit lanes: four `Xor16` gates, followed by a generic gate
+    /// constraining the highest parts of the lanes to zero.
+    /// The inputs are the cells 0 and 1 of the first row, and the output is its cell 2.
+    ///     Inputs the starting row
+    ///     Outputs tuple (`next_row`, `circuit_gates`) where
+    ///       `next_row`      - next row after this gadget
+    ///       `circuit_gates` - vector of circuit gates comprising this gadget
+    pub fn create_xor64(start_row: usize) -> (usize, Vec<Self>) {
+        let mut circuit_gates: Vec<_> = (0..4)
+            .map(|i| CircuitGate {
+                typ: GateType::Xor16,
+                wires: Wire::new(start_row + i),
+                coeffs: vec![],
+            })
+            .collect();
+        circuit_gates.push(CircuitGate::create_generic_gadget(
+            Wire::new(start_row + 4),
+            GenericGateSpec::Const(F::zero()),
+            None,
+        ));
+
+        // the highest parts of the inputs and of the output are the zero of the generic gate
+        circuit_gates.connect_cell_pair((4, 0), (4, 1));
+        circuit_gates.connect_cell_pair((4, 0), (4, 2));
+
+        (start_row + circuit_gates.len(), circuit_gates)
+    }
+
+    /// Create a rotation of a 64-bit lane by `rot` bits to the left: a `Rot64` gate,
+    /// the `RangeCheck0` gates of its shifted lane, of its bound and of its input,
+    /// and a generic gate holding the constants 0 and `2^rot`.
+    /// The input is the cell 0 of the first row, and the output is its cell 1.
+    ///     Inputs the starting row and the rotation
+    ///     Outputs tuple (`next_row`, `circuit_gates`) where
+    ///       `next_row`      - next row after this gadget
+    ///       `circuit_gates` - vector of circuit gates comprising this gadget
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `rot` is not less than 64.
+    pub fn create_rot64(start_row: usize, rot: u32) -> (usize, Vec<Self>) {
+        assert!(rot < 64, "cannot rotate a lane by {rot} bits");
+        let mut circuit_gates = vec![CircuitGate {
+            typ: GateType::Rot64,
+            wires: Wire::new(start_row),
+            coeffs: vec![],
+        }];
+        for row in 1..4 {
+            circuit_gates.push(CircuitGate {
+                typ: GateType::RangeCheck0,
+                wires: Wire::new(start_row + row),
+                coeffs: vec![],
+            });
+        }
+        circuit_gates.push(CircuitGate::create_generic_gadget(
+            Wire::new(start_row + 4),
+            GenericGateSpec::Const(F::zero()),
+            Some(GenericGateSpec::Const(F::from(2u64).pow([u64::from(rot)]))),
+        ));
+
+        // the shifted lane, the bound and the input are range checked
+        circuit_gates.connect_cell_pair((0, 3), (1, 0));
+        circuit_gates.connect_cell_pair((0, 4), (2, 0));
+        circuit_gates.connect_cell_pair((0, 0), (3, 0));
+        // on 64 bits, with the highest limbs of the range checks set to zero
+        for row in 1..4 {
+            circuit_gates.connect_cell_pair((4, 0), (row, 1));
+            circuit_gates.connect_cell_pair((4, 0), (row, 2));
+        }
+        // and the power of two is the constant of the generic gate
+        circuit_gates.connect_cell_pair((0, 5), (4, 3));
+
+        (start_row + circuit_gates.len(), circuit_gates)
+    }
+
+    /// Verifies the `Xor16` gate at `row`, checking the nybbles and their XOR,
+    /// and the decompositions of the inputs and of the output.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `self.typ` is not `GateType::Xor16`,
+    /// if there is no next row, or if a check does not hold.
+    pub fn verify_xor16(&self, row: usize, witness: &[Vec<F>; COLUMNS]) -> Result<(), String> {
+        ensure_eq!(self.typ, GateType::Xor16, "incorrect gate type");
+        if row + 1 >= witness[0].len() {
+            return Err("the XOR gate has no next row".to_string());
+        }
+
+        let nybble = |col: usize| {
+            to_u64(witness[col][row])
+                .filter(|value| *value < 16)
+                .ok_or_else(|| format!("the cell {col} is not a nybble"))
+        };
+        for i in 0..4 {
+            if nybble(3 + i)? ^ nybble(7 + i)? != nybble(11 + i)? {
+                return Err(format!(
+                    "the nybbles {i} of the inputs do not XOR to the output"
+                ));
+            }
+        }
+
+        for i in 0..3 {
+            let sum = (3 + 4 * i..7 + 4 * i)
+                .rev()
+                .fold(witness[i][row + 1], |acc, col| {
+                    acc * F::from(16u64) + witness[col][row]
+                });
+            if sum != witness[i][row] {
+                return Err(format!("the cell {i} is not the sum of its nybbles"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Verifies the `Rot64` gate at `row`, checking that the cells hold the rotation
+    /// of a 64-bit lane by a power of two, and its intermediate values.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `self.typ` is not `GateType::Rot64`, or if a check does not hold.
+    pub fn verify_rot64(&self, row: usize, witness: &[Vec<F>; COLUMNS]) -> Result<(), String> {
+        ensure_eq!(self.typ, GateType::Rot64, "incorrect gate type");
+
+        let two_to_rot = witness[5][row];
+        let rot = (0..64)
+            .find(|rot| F::from(2u64).pow([*rot]) == two_to_rot)
+            .ok_or("the cell 5 is not a power of two less than 2^64")?;
+        let word = to_u64(witness[0][row]).ok_or("the cell 0 is not a 64-bit value")?;
+
+        let expected = witness::rot64_rows::<F>(word, rot as u32).remove(0);
+        for (col, name) in ["rotated", "excess", "shifted", "bound"]
+            .into_iter()
+            .enumerate()
+        {
+            if witness[col + 1][row] != expected[col + 1] {
+                return Err(format!("the cell {} is not the {name} lane", col + 1));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the value of `x` if it is less than `2^64`.
+fn to_u64<F: PrimeField>(x: F) -> Option<u64> {
+    let repr = x.into_repr();
+    let limbs = repr.as_ref();
+    limbs[1..].iter().all(|limb| *limb == 0).then_some(limbs[0])
+}
+
+/// A 64-bit lane of a circuit laid out by a [`KeccakBuilder`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Lane {
+    /// the row and the column of a cell holding the lane, from the start of the circuit
+    pub cell: (usize, usize),
+    /// the value of the lane
+    pub value: u64,
+}
+
+/// Lays out the Keccak gadgets row after row, along with their witness,
+/// wiring their inputs to the cells of the lanes they take.
+///
+/// The rows of the circuit are numbered from `start_row`,
+/// and the cells of the [`Lane`]s from the start of the circuit.
+pub struct KeccakBuilder<F: PrimeField> {
+    start_row: usize,
+    gates: Vec<CircuitGate<F>>,
+    witness: Vec<[F; COLUMNS]>,
+}
+
+impl<F: PrimeField> KeccakBuilder<F> {
+    /// Creates a builder laying out its first gadget at `start_row`.
+    pub fn new(start_row: usize) -> Self {
+        Self {
+            start_row,
+            gates: vec![],
+            witness: vec![],
+        }
+    }
+
+    /// Returns the next row of the circuit.
+    pub fn next_row(&self) -> usize {
+        self.start_row + self.gates.len()
+    }
+
+    /// Appends the rows of a gadget, and wires its `inputs` to the cells of the lanes.
+    fn append(
+        &mut self,
+        gates: Vec<CircuitGate<F>>,
+        witness: Vec<[F; COLUMNS]>,
+        inputs: &[(Lane, (usize, usize))],
+    ) -> usize {
+        let first = self.gates.len();
+        self.gates.extend(gates);
+        self.witness.extend(witness);
+        for (lane, (row, col)) in inputs {
+            self.gates.connect_cell_pair(lane.cell, (first + row, *col));
+        }
+        first
+    }
+
+    /// Returns lanes holding `values`, in the cells of `Zero` gates, seven per row.
+    /// The lanes are not constrained: they are the inputs of the circuit,
+    /// to be wired to the rest of the circuit.
+    pub fn inputs(&mut self, values: &[u64]) -> Vec<Lane> {
+        let mut lanes = vec![];
+        for chunk in values.chunks(PERMUTS) {
+            let mut row = [F::zero(); COLUMNS];
+            for (cell, value) in row.iter_mut().zip(chunk) {
+                *cell = F::from(*value);
+            }
+            let gate = CircuitGate::zero(Wire::new(self.next_row()));
+            let first = self.append(vec![gate], vec![row], &[]);
+            lanes.extend(chunk.iter().enumerate().map(|(col, value)| Lane {
+                cell: (first, col),
+                value: *value,
+            }));
+        }
+        lanes
+    }
+
+    /// Returns a lane holding the constant `value`, in a generic gate.
+    pub fn constant(&mut self, value: u64) -> Lane {
+        let gate = CircuitGate::create_generic_gadget(
+            Wire::new(self.next_row()),
+            GenericGateSpec::Const(F::from(value)),
+            None,
+        );
+        let mut row = [F::zero(); COLUMNS];
+        row[0] = F::from(value);
+        let first = self.append(vec![gate], vec![row], &[]);
+        Lane {
+            cell: (first, 0),
+            value,
+        }
+    }
+
+    /// Returns the XOR of the lanes `a` and `b` (see [`CircuitGate::create_xor64`]).
+    pub fn xor64(&mut self, a: Lane, b: Lane) -> Lane {
+        let (_, gates) = CircuitGate::create_xor64(self.next_row());
+        let first = self.append(
+            gates,
+            witness::xor64_rows(a.value, b.value),
+            &[(a, (0, 0)), (b, (0, 1))],
+        );
+        Lane {
+            cell: (first, 2),
+            value: a.value ^ b.value,
+        }
+    }
+
+    /// Returns the rotation of the lane `word` by `rot` bits to the left
+    /// (see [`CircuitGate::create_rot64`]).
+    pub fn rot64(&mut self, word: Lane, rot: u32) -> Lane {
+        let (_, gates) = CircuitGate::create_rot64(self.next_row(), rot);
+        let first = self.append(
+            gates,
+            witness::rot64_rows(word.value, rot),
+            &[(word, (0, 0))],
+        );
+        Lane {
+            cell: (first, 1),
+            value: word.value.rotate_left(rot),
+        }
+    }
+
+    /// Returns the conjunction of the negation of the lane `b` and of the lane `c`,
+    /// from their XOR, as `(c - b + (b ^ c)) / 2`.
+    pub fn not_and(&mut self, b: Lane, c: Lane) -> Lane {
+        let x = self.xor64(b, c);
+
+        // c - b - s = 0, and s + x - 2 t = 0
+        let gate = CircuitGate::create_generic_gadget(
+            Wire::new(self.next_row()),
+            GenericGateSpec::Add {
+                left_coeff: None,
+                right_coeff: Some(-F::one()),
+                output_coeff: None,
+            },
+            Some(GenericGateSpec::Add {
+                left_coeff: None,
+                right_coeff: None,
+                output_coeff: Some(-F::from(2u64)),
+            }),
+        );
+        let value = !b.value & c.value;
+        let s = F::from(c.value) - F::from(b.value);
+        let mut row = [F::zero(); COLUMNS];
+        row[..6].copy_from_slice(&[
+            F::from(c.value),
+            F::from(b.value),
+            s,
+            s,
+            F::from(x.value),
+            F::from(value),
+        ]);
+        let first = self.append(
+            vec![gate],
+            vec![row],
+            &[(c, (0, 0)), (b, (0, 1)), (x, (0, 4))],
+        );
+        self.gates.connect_cell_pair((first, 2), (first, 3));
+
+        Lane {
+            cell: (first, 5),
+            value,
+        }
+    }
+
+    /// Returns the state after the round `round` of the permutation
+    /// (see [`keccak_round`](super::keccak_round)).
+    pub fn round(&mut self, mut state: [Lane; STATE_LANES], round: usize) -> [Lane; STATE_LANES] {
+        // θ
+        let c: Vec<_> = (0..5)
+            .map(|x| {
+                (1..5).fold(state[lane(x, 0)], |acc, y| {
+                    self.xor64(acc, state[lane(x, y)])
+                })
+            })
+            .collect();
+        for x in 0..5 {
+            let rotated = self.rot64(c[(x + 1) % 5], 1);
+            let d = self.xor64(c[(x + 4) % 5], rotated);
+            for y in 0..5 {
+                state[lane(x, y)] = self.xor64(state[lane(x, y)], d);
+            }
+        }
+
+        // ρ and π
+        let mut b = state;
+        for x in 0..5 {
+            for y in 0..5 {
+                let rot = ROTATION_OFFSETS[lane(x, y)];
+                b[lane(y, 2 * x + 3 * y)] = if rot == 0 {
+                    state[lane(x, y)]
+                } else {
+                    self.rot64(state[lane(x, y)], rot)
+                };
+            }
+        }
+
+        // χ
+        for x in 0..5 {
+            for y in 0..5 {
+                let t = self.not_and(b[lane(x + 1, y)], b[lane(x + 2, y)]);
+                state[lane(x, y)] = self.xor64(b[lane(x, y)], t);
+            }
+        }
+
+        // ι
+        let constant = self.constant(ROUND_CONSTANTS[round]);
+        state[0] = self.xor64(state[0], constant);
+
+        state
+    }
+
+    /// Returns the state after the permutation Keccak-f\[1600\].
+    pub fn permutation(&mut self, state: [Lane; STATE_LANES]) -> [Lane; STATE_LANES] {
+        (0..ROUNDS).fold(state, |state, round| self.round(state, round))
+    }
+
+    /// Returns the input lanes of the padded `message` (see [`pad`](super::pad)),
+    /// and the lanes of its Keccak-256 hash, whose bytes are the little-endian bytes of the lanes.
+    pub fn keccak256(&mut self, message: &[u8]) -> (Vec<Lane>, [Lane; DIGEST_LANES]) {
+        let values: Vec<_> = pad(message).into_iter().flatten().collect();
+        let inputs = self.inputs(&values);
+
+        let mut state: Option<[Lane; STATE_LANES]> = None;
+        for block in inputs.chunks(RATE_LANES) {
+            let absorbed = match state {
+                // the first block is absorbed into the zero state
+                None => {
+                    let zero = self.constant(0);
+                    array::from_fn(|i| block.get(i).copied().unwrap_or(zero))
+                }
+                Some(mut state) => {
+                    for (word, input) in state.iter_mut().zip(block) {
+                        *word = self.xor64(*word, *input);
+                    }
+                    state
+                }
+            };
+            state = Some(self.permutation(absorbed));
+        }
+
+        let state = state.expect("a padded message has at least one block");
+        (inputs, array::from_fn(|i| state[i]))
+    }
+
+    /// Returns the gates of the circuit, and its witness.
+    pub fn build(self) -> (Vec<CircuitGate<F>>, [Vec<F>; COLUMNS]) {
+        let witness = array::from_fn(|col| self.witness.iter().map(|row| row[col]).collect());
+        (self.gates, witness)
+    }
+}
+
+/// Get vector of Keccak circuit gate types
+pub fn circuit_gates() -> [GateType; GATE_COUNT] {
+    [GateType::Xor16, GateType::Rot64]
+}
+
+/// Get combined constraints for a given Keccak circuit gate type
+///
+/// # Panics
+///
+/// Will panic if `typ` is not a Keccak gate type.
+pub fn circuit_gate_constraints<F: PrimeField>(typ: GateType, alphas: &Alphas<F>) -> E<F> {
+    match typ {
+        GateType::Xor16 => Xor16::combined_constraints(alphas),
+        GateType::Rot64 => Rot64::combined_constraints(alphas),
+        _ => panic!("invalid gate type"),
+    }
+}
+
+/// Get the combined constraints for all Keccak circuit gate types
+pub fn combined_constraints<F: PrimeField>(alphas: &Alphas<F>) -> E<F> {
+    Xor16::combined_constraints(alphas) + Rot64::combined_constraints(alphas)
+}
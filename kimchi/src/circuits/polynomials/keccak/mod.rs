@@ -0,0 +1,145 @@
+//! Keccak module
+//!
+//! The gates of the Keccak family, [`Xor16`](circuitgates::Xor16) and [`Rot64`](circuitgates::Rot64),
+//! prove the bitwise operations of the permutation Keccak-f\[1600\] on 64-bit lanes:
+//! a XOR of two lanes spans four `Xor16` rows looking up their nybbles in the XOR table,
+//! and a rotation of a lane is a `Rot64` row whose lanes are range checked by `RangeCheck0` rows.
+//! The NOT and AND of the χ step are linear combinations of lanes and of their XOR,
+//! so that they only cost generic gates.
+//!
+//! [`gadget::KeccakBuilder`] lays out these gadgets for the rounds of the permutation,
+//! and for the Keccak-256 hash of Ethereum, along with their witness.
+//! This module also implements the permutation and the hash natively,
+//! as the reference of the circuits.
+
+pub mod circuitgates;
+pub mod gadget;
+pub mod witness;
+
+/// Number of lanes of the state of the permutation
+pub const STATE_LANES: usize = 25;
+
+/// Number of rounds of the permutation
+pub const ROUNDS: usize = 24;
+
+/// Number of bytes absorbed per permutation by Keccak-256
+pub const RATE_BYTES: usize = 136;
+
+/// Number of lanes absorbed per permutation by Keccak-256
+pub const RATE_LANES: usize = RATE_BYTES / 8;
+
+/// Number of lanes of a Keccak-256 digest
+pub const DIGEST_LANES: usize = 4;
+
+/// The round constants of the ι step
+pub const ROUND_CONSTANTS: [u64; ROUNDS] = [
+    0x0000_0000_0000_0001,
+    0x0000_0000_0000_8082,
+    0x8000_0000_0000_808A,
+    0x8000_0000_8000_8000,
+    0x0000_0000_0000_808B,
+    0x0000_0000_8000_0001,
+    0x8000_0000_8000_8081,
+    0x8000_0000_0000_8009,
+    0x0000_0000_0000_008A,
+    0x0000_0000_0000_0088,
+    0x0000_0000_8000_8009,
+    0x0000_0000_8000_000A,
+    0x0000_0000_8000_808B,
+    0x8000_0000_0000_008B,
+    0x8000_0000_0000_8089,
+    0x8000_0000_0000_8003,
+    0x8000_0000_0000_8002,
+    0x8000_0000_0000_0080,
+    0x0000_0000_0000_800A,
+    0x8000_0000_8000_000A,
+    0x8000_0000_8000_8081,
+    0x8000_0000_0000_8080,
+    0x0000_0000_8000_0001,
+    0x8000_0000_8000_8008,
+];
+
+/// The rotation offsets of the ρ step, of the lane `(x, y)` at the index `x + 5 y`
+pub const ROTATION_OFFSETS: [u32; STATE_LANES] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+/// Returns the index in the state of the lane `(x, y)`, the coordinates being taken modulo 5.
+pub fn lane(x: usize, y: usize) -> usize {
+    (x % 5) + 5 * (y % 5)
+}
+
+/// Applies the round `round` of the permutation to `state`.
+pub fn keccak_round(state: &mut [u64; STATE_LANES], round: usize) {
+    // θ
+    let c: [u64; 5] = std::array::from_fn(|x| (0..5).fold(0, |acc, y| acc ^ state[lane(x, y)]));
+    for x in 0..5 {
+        let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+        for y in 0..5 {
+            state[lane(x, y)] ^= d;
+        }
+    }
+
+    // ρ and π
+    let mut b = [0; STATE_LANES];
+    for x in 0..5 {
+        for y in 0..5 {
+            b[lane(y, 2 * x + 3 * y)] = state[lane(x, y)].rotate_left(ROTATION_OFFSETS[lane(x, y)]);
+        }
+    }
+
+    // χ
+    for x in 0..5 {
+        for y in 0..5 {
+            state[lane(x, y)] = b[lane(x, y)] ^ (!b[lane(x + 1, y)] & b[lane(x + 2, y)]);
+        }
+    }
+
+    // ι
+    state[0] ^= ROUND_CONSTANTS[round];
+}
+
+/// Applies the permutation Keccak-f\[1600\] to `state`.
+pub fn keccak_f(state: &mut [u64; STATE_LANES]) {
+    for round in 0..ROUNDS {
+        keccak_round(state, round);
+    }
+}
+
+/// Returns the lanes of the blocks of `message`, padded as by Keccak-256
+/// (with the original padding of Keccak, as Ethereum does, not the one of SHA-3).
+pub fn pad(message: &[u8]) -> Vec<[u64; RATE_LANES]> {
+    let mut bytes = message.to_vec();
+    bytes.push(0x01);
+    bytes.resize(
+        ((bytes.len() + RATE_BYTES - 1) / RATE_BYTES) * RATE_BYTES,
+        0,
+    );
+    *bytes.last_mut().unwrap() |= 0x80;
+
+    bytes
+        .chunks(RATE_BYTES)
+        .map(|block| {
+            std::array::from_fn(|i| {
+                u64::from_le_bytes(block[8 * i..8 * (i + 1)].try_into().unwrap())
+            })
+        })
+        .collect()
+}
+
+/// Returns the Keccak-256 hash of `message`.
+pub fn keccak256(message: &[u8]) -> [u8; 32] {
+    let mut state = [0; STATE_LANES];
+    for block in pad(message) {
+        for (lane, word) in state.iter_mut().zip(block) {
+            *lane ^= word;
+        }
+        keccak_f(&mut state);
+    }
+
+    let mut digest = [0; 32];
+    for (bytes, lane) in digest.chunks_mut(8).zip(state) {
+        bytes.copy_from_slice(&lane.to_le_bytes());
+    }
+    digest
+}
@@ -0,0 +1,68 @@
+//! Keccak witness computation
+
+use ark_ff::PrimeField;
+use std::array;
+
+use crate::circuits::{polynomial::COLUMNS, polynomials::range_check};
+
+/// Returns the rows of the witness of a XOR of two 64-bit lanes
+/// (see [`CircuitGate::create_xor64`](crate::circuits::gate::CircuitGate::create_xor64)).
+pub fn xor64_rows<F: PrimeField>(in1: u64, in2: u64) -> Vec<[F; COLUMNS]> {
+    let out = in1 ^ in2;
+    let mut rows: Vec<_> = (0..4)
+        .map(|i| {
+            // the row i XORs the bits 16 i to 16 i + 15, and moves the higher ones to the next row
+            let values = [in1, in2, out].map(|value| value >> (16 * i));
+            let mut row = [F::zero(); COLUMNS];
+            for (j, value) in values.into_iter().enumerate() {
+                row[j] = F::from(value);
+                for k in 0..4 {
+                    row[3 + 4 * j + k] = F::from((value >> (4 * k)) & 0xF);
+                }
+            }
+            row
+        })
+        .collect();
+
+    // the highest parts of the lanes, all zero
+    rows.push([F::zero(); COLUMNS]);
+    rows
+}
+
+/// Returns the rows of the witness of the rotation of a 64-bit `word` by `rot` bits to the left
+/// (see [`CircuitGate::create_rot64`](crate::circuits::gate::CircuitGate::create_rot64)).
+pub fn rot64_rows<F: PrimeField>(word: u64, rot: u32) -> Vec<[F; COLUMNS]> {
+    let (excess, shifted) = if rot == 0 {
+        (0, word)
+    } else {
+        (word >> (64 - rot), word << rot)
+    };
+    // excess - 2^rot + 2^64, which is less than 2^64 as the excess is less than 2^rot
+    let bound = excess.wrapping_sub(1 << rot);
+    let two_to_rot = F::from(2u64).pow([u64::from(rot)]);
+
+    let mut rot_row = [F::zero(); COLUMNS];
+    rot_row[0] = F::from(word);
+    rot_row[1] = F::from(word.rotate_left(rot));
+    rot_row[2] = F::from(excess);
+    rot_row[3] = F::from(shifted);
+    rot_row[4] = F::from(bound);
+    rot_row[5] = two_to_rot;
+
+    let mut constants_row = [F::zero(); COLUMNS];
+    constants_row[3] = two_to_rot;
+
+    vec![
+        rot_row,
+        range_check_row(shifted),
+        range_check_row(bound),
+        range_check_row(word),
+        constants_row,
+    ]
+}
+
+/// Returns the row of a `RangeCheck0` gate checking that `value` is a 64-bit value.
+fn range_check_row<F: PrimeField>(value: u64) -> [F; COLUMNS] {
+    let witness = range_check::witness::create_witness(F::from(value));
+    array::from_fn(|col| witness[col][0])
+}
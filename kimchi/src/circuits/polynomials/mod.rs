@@ -1,11 +1,18 @@
+#[cfg(feature = "chacha")]
 pub mod chacha;
 pub mod complete_add;
+pub mod copy_cells;
 pub mod endomul_scalar;
 pub mod endosclmul;
+#[cfg(feature = "foreign_field")]
 pub mod foreign_field_add;
 pub mod generic;
+#[cfg(feature = "keccak")]
+pub mod keccak;
 pub mod permutation;
 pub mod poseidon;
+#[cfg(feature = "range_check")]
 pub mod range_check;
+#[cfg(feature = "cairo")]
 pub mod turshi;
 pub mod varbasemul;
@@ -49,15 +49,15 @@ use crate::{
     error::ProverError,
     proof::ProofEvaluations,
 };
-use ark_ff::{FftField, PrimeField, SquareRootField, Zero};
+use ark_ff::{FftField, Field, PrimeField, SquareRootField, Zero};
 use ark_poly::{
-    univariate::{DenseOrSparsePolynomial, DensePolynomial},
-    EvaluationDomain, Evaluations, Radix2EvaluationDomain as D,
+    univariate::DensePolynomial, EvaluationDomain, Evaluations, Radix2EvaluationDomain as D,
 };
 use ark_poly::{Polynomial, UVPolynomial};
 use blake2::{Blake2b512, Digest};
 use o1_utils::{ExtendedDensePolynomial, ExtendedEvaluations};
 use rand::{CryptoRng, RngCore};
+use rayon::prelude::*;
 use std::array;
 
 /// Number of constraints produced by the argument.
@@ -118,6 +118,69 @@ pub fn zk_polynomial<F: FftField>(domain: D<F>) -> DensePolynomial<F> {
     ])
 }
 
+/// The integer hashed before the first sample of [`derive_shifts`]
+const SHIFTS_SEED: u32 = 7;
+
+/// Derives `count` shifts for the permutation of a circuit over `domain`, deterministically.
+///
+/// The first shift is one. The next ones are sampled from the Blake2b-512 digests
+/// of the successive integers from 8, as 4 big-endian bytes,
+/// whose first 31 bytes are read as a field element.
+/// A sample is rejected if it is a quadratic residue,
+/// or if it is in the coset of `domain` of a previous shift.
+///
+/// The domain only takes part in the rejection of the samples,
+/// so that the shifts of the circuits of most sizes are the same,
+/// and the shifts of `count` columns are the first shifts of more columns.
+/// The derived shifts pass [`check_shifts`].
+pub fn derive_shifts<F: FftField + SquareRootField>(domain: &D<F>, count: usize) -> Vec<F> {
+    let mut shifts = vec![];
+    // the shifts are in distinct cosets if their n-th powers are distinct
+    let mut powers = vec![];
+    let mut input = SHIFTS_SEED;
+    while shifts.len() < count {
+        let shift = if shifts.is_empty() {
+            F::one()
+        } else {
+            input += 1;
+            let mut h = Blake2b512::new();
+            h.update(&input.to_be_bytes());
+            let shift = F::from_random_bytes(&h.finalize()[..31])
+                .expect("our field elements fit in more than 31 bytes");
+            if !shift.legendre().is_qnr() {
+                continue;
+            }
+            shift
+        };
+        let power = shift.pow([domain.size]);
+        if !powers.contains(&power) {
+            shifts.push(shift);
+            powers.push(power);
+        }
+    }
+    shifts
+}
+
+/// Checks that `shifts` are valid shifts for the permutation of a circuit over `domain`:
+/// they must be non-zero and in distinct cosets of `domain`,
+/// so that the cells of distinct columns are mapped to distinct field elements.
+///
+/// # Errors
+///
+/// Will give error if the shifts are not valid.
+pub fn check_shifts<F: FftField>(domain: &D<F>, shifts: &[F]) -> Result<(), &'static str> {
+    if shifts.iter().any(Zero::is_zero) {
+        return Err("a shift is zero");
+    }
+    let powers: Vec<_> = shifts.iter().map(|s| s.pow([domain.size])).collect();
+    for (i, power) in powers.iter().enumerate() {
+        if powers[..i].contains(power) {
+            return Err("two shifts are in the same coset of the domain");
+        }
+    }
+    Ok(())
+}
+
 /// Shifts represent the shifts required in the permutation argument of PLONK.
 /// It also caches the shifted powers of omega for optimization purposes.
 pub struct Shifts<F> {
@@ -133,29 +196,32 @@ impl<F> Shifts<F>
 where
     F: FftField + SquareRootField,
 {
-    /// Generates the shifts for a given domain
+    /// Generates the shifts for a given domain (see [`derive_shifts`])
     pub fn new(domain: &D<F>) -> Self {
-        let mut shifts = [F::zero(); PERMUTS];
-
-        // first shift is the identity
-        shifts[0] = F::one();
-
-        // sample the other shifts
-        let mut i: u32 = 7;
-        for idx in 1..(PERMUTS) {
-            let mut shift = Self::sample(domain, &mut i);
-            // they have to be distincts
-            while shifts.contains(&shift) {
-                shift = Self::sample(domain, &mut i);
-            }
-            shifts[idx] = shift;
-        }
+        Self::with_shifts(domain, Self::derived(domain))
+    }
+
+    /// Uses custom shifts for a given domain
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the shifts are not valid (see [`check_shifts`]).
+    pub fn custom(domain: &D<F>, shifts: [F; PERMUTS]) -> Result<Self, &'static str> {
+        check_shifts(domain, &shifts)?;
+        Ok(Self::with_shifts(domain, shifts))
+    }
+
+    /// Returns the shifts derived for a given domain, without their map of the cells
+    pub fn derived(domain: &D<F>) -> [F; PERMUTS] {
+        let shifts = derive_shifts(domain, PERMUTS);
+        array::from_fn(|i| shifts[i])
+    }
 
+    fn with_shifts(domain: &D<F>, shifts: [F; PERMUTS]) -> Self {
         // create a map of cells to their shifted value
         let map: [Vec<F>; PERMUTS] =
             array::from_fn(|i| domain.elements().map(|elm| shifts[i] * elm).collect());
 
-        //
         Self { shifts, map }
     }
 
@@ -164,26 +230,6 @@ where
         &self.shifts
     }
 
-    /// sample coordinate shifts deterministically
-    fn sample(domain: &D<F>, input: &mut u32) -> F {
-        let mut h = Blake2b512::new();
-
-        *input += 1;
-        h.update(&input.to_be_bytes());
-
-        let mut shift = F::from_random_bytes(&h.finalize()[..31])
-            .expect("our field elements fit in more than 31 bytes");
-
-        while !shift.legendre().is_qnr() || domain.evaluate_vanishing_polynomial(shift).is_zero() {
-            let mut h = Blake2b512::new();
-            *input += 1;
-            h.update(&input.to_be_bytes());
-            shift = F::from_random_bytes(&h.finalize()[..31])
-                .expect("our field elements fit in more than 31 bytes");
-        }
-        shift
-    }
-
     /// Returns the field element that represents a position
     pub(crate) fn cell_to_field(&self, &Wire { row, col }: &Wire) -> F {
         self.map[col][row]
@@ -251,7 +297,14 @@ impl<F: PrimeField> ConstraintSystem<F> {
             // (w[6](x) + gamma + x * beta * shift[6])
             // in evaluation form in d8
             let mut shifts = lagrange.d8.this.z.clone();
-            for (witness, shift) in lagrange.d8.this.w.iter().zip(self.shift.iter()) {
+            for (witness, shift) in lagrange
+                .d8
+                .this
+                .w
+                .iter()
+                .zip(self.shift.iter())
+                .take(self.permuted_columns())
+            {
                 let term =
                     &(witness + gamma) + &self.precomputations().poly_x_d1.scale(beta * shift);
                 shifts = &shifts * &term;
@@ -284,26 +337,13 @@ impl<F: PrimeField> ConstraintSystem<F> {
 
             // TODO(mimoo): use self.sid[0] instead of 1
             // accumulator init := (z(x) - 1) / (x - 1)
-            let x_minus_1 = DensePolynomial::from_coefficients_slice(&[-F::one(), F::one()]);
-            let (bnd1, res) = DenseOrSparsePolynomial::divide_with_q_and_r(
-                &z_minus_1.clone().into(),
-                &x_minus_1.into(),
-            )
-            .ok_or(ProverError::Permutation("first division"))?;
+            let (bnd1, res) = z_minus_1.divide_by_linear(F::one());
             if !res.is_zero() {
                 return Err(ProverError::Permutation("first division rest"));
             }
 
             // accumulator end := (z(x) - 1) / (x - sid[n-3])
-            let denominator = DensePolynomial::from_coefficients_slice(&[
-                -self.sid[self.domain.d1.size() - 3],
-                F::one(),
-            ]);
-            let (bnd2, res) = DenseOrSparsePolynomial::divide_with_q_and_r(
-                &z_minus_1.into(),
-                &denominator.into(),
-            )
-            .ok_or(ProverError::Permutation("second division"))?;
+            let (bnd2, res) = z_minus_1.divide_by_linear(self.sid[self.domain.d1.size() - 3]);
             if !res.is_zero() {
                 return Err(ProverError::Permutation("second division rest"));
             }
@@ -329,9 +369,13 @@ impl<F: PrimeField> ConstraintSystem<F> {
         //~
         //~ $\text{scalar} \cdot \sigma_6(x)$
         //~
+        //~ When only the first $k$ columns take part in the permutation,
+        //~ the products above only range over these columns,
+        //~ and $\sigma_{k-1}$ takes the place of $\sigma_6$.
+        //~
         let zkpm_zeta = self.precomputations().zkpm.evaluate(&zeta);
         let scalar = Self::perm_scalars(e, beta, gamma, alphas, zkpm_zeta);
-        self.sigmam[PERMUTS - 1].scale(scalar)
+        self.sigmam[self.permuted_columns() - 1].scale(scalar)
     }
 
     pub fn perm_scalars(
@@ -438,24 +482,33 @@ impl<F: PrimeField> ConstraintSystem<F> {
         //~ $$
         //~
         //~
-        for j in 0..n - 3 {
-            z[j + 1] = witness
-                .iter()
-                .zip(self.sigmal1.iter())
-                .map(|(w, s)| w[j] + (s[j] * beta) + gamma)
-                .fold(F::one(), |x, y| x * y);
-        }
-
-        ark_ff::fields::batch_inversion::<F>(&mut z[1..=n - 3]);
-
-        for j in 0..n - 3 {
-            let x = z[j];
-            z[j + 1] *= witness
+        //~ The ratios $z_1 / z_2$ are computed for all the rows in parallel,
+        //~ with a single batched inversion of the $z_2$,
+        //~ and their prefix products then give the evaluations of $z$.
+        let mut ratios: Vec<F> = (0..n - 3)
+            .into_par_iter()
+            .map(|j| {
+                witness
+                    .iter()
+                    .zip(self.sigmal1.iter())
+                    .map(|(w, s)| w[j] + (s[j] * beta) + gamma)
+                    .fold(F::one(), |x, y| x * y)
+            })
+            .collect();
+
+        ark_ff::fields::batch_inversion::<F>(&mut ratios);
+
+        ratios.par_iter_mut().enumerate().for_each(|(j, ratio)| {
+            *ratio = witness
                 .iter()
                 .zip(self.shift.iter())
+                .take(self.permuted_columns())
                 .map(|(w, s)| w[j] + (self.sid[j] * beta * s) + gamma)
-                .fold(x, |z, y| z * y);
-        }
+                .fold(*ratio, |z, y| z * y);
+        });
+
+        prefix_product(&mut ratios);
+        z[1..=n - 3].copy_from_slice(&ratios);
 
         //~ If computed correctly, we should have $z(g^{n-3}) = 1$.
         //~
@@ -472,3 +525,38 @@ impl<F: PrimeField> ConstraintSystem<F> {
         Ok(res)
     }
 }
+
+/// Replaces each value of `values` by the product of the values up to it.
+/// The values are split in a chunk per thread, whose prefix products are computed in parallel,
+/// and then multiplied (also in parallel) by the product of the previous chunks.
+pub(crate) fn prefix_product<F: Field>(values: &mut [F]) {
+    let threads = rayon::current_num_threads();
+    let chunk_size = ((values.len() + threads - 1) / threads).max(1);
+
+    values.par_chunks_mut(chunk_size).for_each(|chunk| {
+        let mut acc = F::one();
+        for value in chunk {
+            acc *= *value;
+            *value = acc;
+        }
+    });
+
+    let offsets: Vec<F> = values
+        .chunks(chunk_size)
+        .scan(F::one(), |acc, chunk| {
+            let offset = *acc;
+            *acc *= chunk[chunk.len() - 1];
+            Some(offset)
+        })
+        .collect();
+
+    values
+        .par_chunks_mut(chunk_size)
+        .zip(offsets)
+        .skip(1)
+        .for_each(|(chunk, offset)| {
+            for value in chunk {
+                *value *= offset;
+            }
+        });
+}
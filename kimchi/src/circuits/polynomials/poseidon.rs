@@ -34,10 +34,12 @@ use crate::{
         wires::{GateWires, Wire},
     },
     curve::KimchiCurve,
+    error::SetupError,
 };
 use ark_ff::{Field, PrimeField};
 use oracle::{
-    constants::{PlonkSpongeConstantsKimchi, SpongeConstants},
+    constants::{PlonkSpongeConstantsKimchi, SpongeConfig, SpongeConstants},
+    permutation::round_with_config,
     poseidon::{sbox, ArithmeticSponge, ArithmeticSpongeParams, Sponge},
 };
 use std::{marker::PhantomData, ops::Range};
@@ -77,6 +79,41 @@ pub const fn round_to_cols(i: usize) -> Range<usize> {
     start..(start + SPONGE_WIDTH)
 }
 
+/// Returns the number of Poseidon gates a hash of the sponge configuration `config` takes.
+///
+/// The gate constrains full rounds of the Kimchi S-box and MDS matrix, on a state of
+/// [`SPONGE_WIDTH`] field elements, [`ROUNDS_PER_ROW`] rounds at a time:
+/// only the number of full rounds (and the round constants) can vary.
+///
+/// # Errors
+///
+/// Will give error if the gate can not constrain the permutation of `config`.
+pub fn rows_per_hash(config: &SpongeConfig) -> Result<usize, SetupError> {
+    let kimchi = SpongeConfig::KIMCHI;
+    let err = |msg: String| Err(SetupError::ConstraintSystem(msg));
+    if config.width() != SPONGE_WIDTH {
+        return err(format!(
+            "the poseidon gate permutes {SPONGE_WIDTH} field elements, not {}",
+            config.width()
+        ));
+    }
+    if config.sbox != kimchi.sbox || !config.full_mds {
+        return err("the poseidon gate only uses the kimchi S-box and MDS matrix".into());
+    }
+    if config.rounds_partial != 0 || config.initial_ark {
+        return err(
+            "the poseidon gate does not support partial rounds nor initial round constants".into(),
+        );
+    }
+    if config.rounds_full == 0 || config.rounds_full % ROUNDS_PER_ROW != 0 {
+        return err(format!(
+            "the poseidon gate needs a positive multiple of {ROUNDS_PER_ROW} rounds, not {}",
+            config.rounds_full
+        ));
+    }
+    Ok(config.rounds_full / ROUNDS_PER_ROW)
+}
+
 impl<F: PrimeField> CircuitGate<F> {
     pub fn create_poseidon(
         wires: GateWires,
@@ -102,12 +139,54 @@ impl<F: PrimeField> CircuitGate<F> {
         // first and last row of the poseidon circuit (because they are used in the permutation)
         first_and_last_row: [GateWires; 2],
         round_constants: &[Vec<F>],
+    ) -> (Vec<Self>, usize) {
+        Self::create_poseidon_rows(row, first_and_last_row, round_constants, POS_ROWS_PER_HASH)
+    }
+
+    /// Creates the constraints of a Poseidon hash of the sponge configuration `config`
+    /// (see [`rows_per_hash`]), as [`CircuitGate::create_poseidon_gadget`] does
+    /// for the Kimchi configuration.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the gate can not constrain the permutation of `config`,
+    /// or if `round_constants` does not hold a round constant per round.
+    pub fn create_poseidon_gadget_with_config(
+        row: usize,
+        first_and_last_row: [GateWires; 2],
+        config: &SpongeConfig,
+        round_constants: &[Vec<F>],
+    ) -> Result<(Vec<Self>, usize), SetupError> {
+        let rows = rows_per_hash(config)?;
+        if round_constants.len() < config.rounds_full
+            || round_constants[..config.rounds_full]
+                .iter()
+                .any(|rc| rc.len() != SPONGE_WIDTH)
+        {
+            return Err(SetupError::ConstraintSystem(format!(
+                "the poseidon gadget needs {} round constants of {SPONGE_WIDTH} field elements",
+                config.rounds_full
+            )));
+        }
+        Ok(Self::create_poseidon_rows(
+            row,
+            first_and_last_row,
+            round_constants,
+            rows,
+        ))
+    }
+
+    fn create_poseidon_rows(
+        row: usize,
+        first_and_last_row: [GateWires; 2],
+        round_constants: &[Vec<F>],
+        rows: usize,
     ) -> (Vec<Self>, usize) {
         let mut gates = vec![];
 
         // create the gates
-        let relative_rows = 0..POS_ROWS_PER_HASH;
-        let last_row = row + POS_ROWS_PER_HASH;
+        let relative_rows = 0..rows;
+        let last_row = row + rows;
         let absolute_rows = row..last_row;
 
         for (abs_row, rel_row) in absolute_rows.zip(relative_rows) {
@@ -273,6 +352,54 @@ pub fn generate_witness<F: Field>(
     }
 }
 
+/// `generate_witness_with_config(row, config, params, witness_cols, input)` generates
+/// the witness of a hash of the sponge configuration `config` and the parameters `params`,
+/// as [`generate_witness`] does for the Kimchi configuration.
+///
+/// # Panics
+///
+/// Will panic if the gate can not constrain the permutation of `config` (see [`rows_per_hash`]),
+/// or if `params` does not match it.
+pub fn generate_witness_with_config<F: Field>(
+    row: usize,
+    config: &SpongeConfig,
+    params: &ArithmeticSpongeParams<F>,
+    witness_cols: &mut [Vec<F>; COLUMNS],
+    input: [F; SPONGE_WIDTH],
+) {
+    let rows = rows_per_hash(config).expect("the poseidon gate can not use this configuration");
+    config
+        .check_params(params)
+        .expect("the sponge parameters do not match the configuration");
+
+    // add the input into the witness
+    witness_cols[0][row] = input[0];
+    witness_cols[1][row] = input[1];
+    witness_cols[2][row] = input[2];
+
+    let mut state = input;
+    for row_idx in 0..rows {
+        let row = row + row_idx;
+        for round in 0..ROUNDS_PER_ROW {
+            // the last round makes use of the next row
+            let maybe_next_row = if round == ROUNDS_PER_ROW - 1 {
+                row + 1
+            } else {
+                row
+            };
+
+            let abs_round = round + row_idx * ROUNDS_PER_ROW;
+            round_with_config(config, params, &mut state, abs_round, false);
+
+            let cols_to_update = round_to_cols((round + 1) % ROUNDS_PER_ROW);
+            witness_cols[cols_to_update]
+                .iter_mut()
+                .zip(state.iter())
+                .for_each(|(w, s)| w[maybe_next_row] = *s);
+        }
+    }
+}
+
 /// An equation of the form `(curr | next)[i] = round(curr[j])`
 struct RoundEquation {
     pub source: usize,
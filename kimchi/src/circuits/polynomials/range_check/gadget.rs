@@ -13,7 +13,7 @@ use crate::{
     circuits::{
         argument::{Argument, ArgumentType},
         constraints::ConstraintSystem,
-        expr::{self, l0_1, Environment, LookupEnvironment, E},
+        expr::{self, Environment, LookupEnvironment, E},
         gate::{CircuitGate, CircuitGateError, CircuitGateResult, Connect, GateType},
         lookup::{
             self,
@@ -21,6 +21,7 @@ use crate::{
             tables::{GateLookupTable, LookupTable},
         },
         polynomial::COLUMNS,
+        polynomials::generic::{GenericGateSpec, GENERIC_COEFFS},
         wires::Wire,
     },
     curve::KimchiCurve,
@@ -30,6 +31,115 @@ use super::circuitgates::{RangeCheck0, RangeCheck1};
 
 pub const GATE_COUNT: usize = 2;
 
+/// Number of bits of the values checked by a multi range check
+pub const LIMB_BITS: usize = 88;
+
+/// Maximum number of bits of a value checked by [`CircuitGate::create_range_check_bits`]
+pub const MAX_BITS: usize = 255;
+
+/// The strategy used by [`CircuitGate::create_range_check_bits`] to check a value
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RangeCheckStrategy {
+    /// Split the value into 88-bit limbs, checked with multi range checks
+    /// (which look up 12-bit chunks of the limbs in the range check table)
+    Lookup,
+    /// Decompose the value into bits, constrained with generic gates
+    Gates,
+}
+
+impl RangeCheckStrategy {
+    /// Number of rows used to check a value of `bits` bits with this strategy
+    pub fn rows(self, bits: usize) -> usize {
+        match self {
+            RangeCheckStrategy::Lookup => {
+                let (constraints, slots) = limbs_layout(bits);
+                (constraints.len() + 1) / 2 + 4 * ((slots.len() + 2) / 3)
+            }
+            RangeCheckStrategy::Gates => bits,
+        }
+    }
+
+    /// Returns the strategy using the fewest rows for a value of `bits` bits,
+    /// preferring generic gates when both use the same number of rows
+    pub fn choose(bits: usize) -> Self {
+        if RangeCheckStrategy::Gates.rows(bits) <= RangeCheckStrategy::Lookup.rows(bits) {
+            RangeCheckStrategy::Gates
+        } else {
+            RangeCheckStrategy::Lookup
+        }
+    }
+}
+
+/// A variable of the [`RangeCheckStrategy::Lookup`] layout
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum LimbsVar {
+    /// The checked value
+    Value,
+    /// The sum of the two lowest limbs, for values of more than two limbs
+    Partial,
+    /// The limb with the given index, starting from the lowest bits
+    Limb(usize),
+    /// The highest limb, shifted to check that it fits in its number of bits
+    Scaled,
+}
+
+/// A generic constraint `left - right - 2^shift * output = 0` of the
+/// [`RangeCheckStrategy::Lookup`] layout, given as (`left`, `right`, `output`, `shift`)
+pub(crate) type LimbsConstraint = (LimbsVar, Option<LimbsVar>, Option<LimbsVar>, usize);
+
+/// Returns the generic constraints and the multi range check values
+/// of the [`RangeCheckStrategy::Lookup`] layout for a value of `bits` bits.
+///
+/// The value is split into 88-bit limbs.
+/// As the multi range check only bounds each limb by 2^88,
+/// a highest limb `v` of `k < 88` bits is also checked as `2^(88 - k) * v`,
+/// which fits in 88 bits only if `v` fits in `k` bits.
+pub(crate) fn limbs_layout(bits: usize) -> (Vec<LimbsConstraint>, Vec<LimbsVar>) {
+    assert!(
+        bits > 0 && bits <= MAX_BITS,
+        "cannot range check {bits} bits"
+    );
+    let limbs = (bits + LIMB_BITS - 1) / LIMB_BITS;
+    let top_bits = bits - (limbs - 1) * LIMB_BITS;
+
+    let mut constraints = match limbs {
+        1 => vec![(LimbsVar::Value, Some(LimbsVar::Limb(0)), None, 0)],
+        2 => vec![(
+            LimbsVar::Value,
+            Some(LimbsVar::Limb(0)),
+            Some(LimbsVar::Limb(1)),
+            LIMB_BITS,
+        )],
+        _ => vec![
+            (
+                LimbsVar::Value,
+                Some(LimbsVar::Partial),
+                Some(LimbsVar::Limb(2)),
+                2 * LIMB_BITS,
+            ),
+            (
+                LimbsVar::Partial,
+                Some(LimbsVar::Limb(0)),
+                Some(LimbsVar::Limb(1)),
+                LIMB_BITS,
+            ),
+        ],
+    };
+    let mut slots: Vec<_> = (0..limbs).map(LimbsVar::Limb).collect();
+
+    if top_bits < LIMB_BITS {
+        constraints.push((
+            LimbsVar::Scaled,
+            None,
+            Some(LimbsVar::Limb(limbs - 1)),
+            LIMB_BITS - top_bits,
+        ));
+        slots.push(LimbsVar::Scaled);
+    }
+
+    (constraints, slots)
+}
+
 impl<F: PrimeField> CircuitGate<F> {
     /// Create range check gate for constraining three 88-bit values.
     ///     Inputs the starting row
@@ -91,6 +201,121 @@ impl<F: PrimeField> CircuitGate<F> {
         )
     }
 
+    /// Create a range check gadget constraining a value to `bits` bits, for any width up to [`MAX_BITS`].
+    /// The checked value is the first cell of the gadget (column 0 of its first row),
+    /// to be wired to the value to check.
+    /// The gadget uses the layout of [`RangeCheckStrategy::choose`].
+    ///     Inputs the starting row and the number of bits
+    ///     Outputs tuple (`next_row`, `circuit_gates`) where
+    ///       `next_row`      - next row after this gadget
+    ///       `circuit_gates` - vector of circuit gates comprising this gadget
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `bits` is zero or larger than [`MAX_BITS`].
+    pub fn create_range_check_bits(start_row: usize, bits: usize) -> (usize, Vec<Self>) {
+        match RangeCheckStrategy::choose(bits) {
+            RangeCheckStrategy::Lookup => Self::create_range_check_limbs(start_row, bits),
+            RangeCheckStrategy::Gates => Self::create_range_check_decomposition(start_row, bits),
+        }
+    }
+
+    /// Create the [`RangeCheckStrategy::Lookup`] layout of [`Self::create_range_check_bits`]:
+    /// generic gates composing the value from its limbs, followed by the multi range checks of the limbs
+    fn create_range_check_limbs(start_row: usize, bits: usize) -> (usize, Vec<Self>) {
+        let (constraints, slots) = limbs_layout(bits);
+        let mut circuit_gates = vec![];
+        let mut cells = vec![];
+
+        // generic gates, two constraints per row
+        for (row, pair) in constraints.chunks(2).enumerate() {
+            let mut coeffs = [F::zero(); GENERIC_COEFFS * 2];
+            for (i, (left, right, output, shift)) in pair.iter().enumerate() {
+                let offset = i * GENERIC_COEFFS;
+                coeffs[offset] = F::one();
+                if right.is_some() {
+                    coeffs[offset + 1] = -F::one();
+                }
+                if output.is_some() {
+                    coeffs[offset + 2] = -F::from(2u64).pow([*shift as u64]);
+                }
+                for (col, var) in [Some(*left), *right, *output].into_iter().enumerate() {
+                    if let Some(var) = var {
+                        cells.push((var, (row, 3 * i + col)));
+                    }
+                }
+            }
+            circuit_gates.push(CircuitGate::create_generic(
+                Wire::new(start_row + row),
+                coeffs,
+            ));
+        }
+
+        // multi range checks, three values each
+        let mut next_row = start_row + circuit_gates.len();
+        for chunk in slots.chunks(3) {
+            let first = circuit_gates.len();
+            for (i, var) in chunk.iter().enumerate() {
+                cells.push((*var, (first + i, 0)));
+            }
+            let (subsequent_row, mut range_check_circuit_gates) =
+                CircuitGate::create_multi_range_check(next_row);
+            circuit_gates.append(&mut range_check_circuit_gates);
+            next_row = subsequent_row;
+        }
+
+        // wire the cells of each variable together
+        for (i, (var, cell)) in cells.iter().enumerate() {
+            if let Some((_, next)) = cells[i + 1..].iter().find(|(other, _)| other == var) {
+                circuit_gates.connect_cell_pair(*cell, *next);
+            }
+        }
+
+        (next_row, circuit_gates)
+    }
+
+    /// Create the [`RangeCheckStrategy::Gates`] layout of [`Self::create_range_check_bits`]:
+    /// a generic gate per bit `b_i` of the value, constraining `b_i` to be a bit
+    /// and the value shifted by `i` bits `r_i` to be `b_i + 2 * r_(i+1)`,
+    /// the value shifted by `bits` bits being zero
+    fn create_range_check_decomposition(start_row: usize, bits: usize) -> (usize, Vec<Self>) {
+        assert!(
+            bits > 0 && bits <= MAX_BITS,
+            "cannot range check {bits} bits"
+        );
+        let mut circuit_gates: Vec<_> = (0..bits)
+            .map(|i| {
+                // r_i - b_i - 2 * r_(i+1) = 0
+                let shift = GenericGateSpec::Add {
+                    left_coeff: None,
+                    right_coeff: Some(-F::one()),
+                    output_coeff: Some(if i + 1 < bits {
+                        -F::from(2u64)
+                    } else {
+                        F::zero()
+                    }),
+                };
+                // b_i * b_i - b_i = 0
+                let bit = GenericGateSpec::Mul {
+                    output_coeff: None,
+                    mul_coeff: None,
+                };
+                CircuitGate::create_generic_gadget(Wire::new(start_row + i), shift, Some(bit))
+            })
+            .collect();
+
+        for row in 0..bits {
+            circuit_gates.connect_cell_pair((row, 1), (row, 3));
+            circuit_gates.connect_cell_pair((row, 3), (row, 4));
+            circuit_gates.connect_cell_pair((row, 4), (row, 5));
+            if row + 1 < bits {
+                circuit_gates.connect_cell_pair((row, 2), (row + 1, 0));
+            }
+        }
+
+        (start_row + bits, circuit_gates)
+    }
+
     /// Verify the witness against a range check (related) circuit gate
     ///
     /// The following verification checks are performed
@@ -188,10 +413,10 @@ impl<F: PrimeField> CircuitGate<F> {
                     foreign_field_modulus: None,
                 },
                 witness: &witness_evals.d8.this.w,
-                coefficient: &cs.coefficients8,
+                coefficient: cs.coefficients8(),
                 vanishes_on_last_4_rows: &cs.precomputations().vanishes_on_last_4_rows,
                 z: &witness_evals.d8.this.z,
-                l0_1: l0_1(cs.domain.d1),
+                l0_1: cs.precomputations().l0_1,
                 domain: cs.domain,
                 index: index_evals,
                 lookup: lookup_env,
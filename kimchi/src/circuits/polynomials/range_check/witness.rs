@@ -6,6 +6,8 @@ use std::array;
 
 use crate::circuits::polynomial::COLUMNS;
 
+use super::gadget::{limbs_layout, LimbsVar, RangeCheckStrategy, LIMB_BITS};
+
 /// Witness cell for range check gadget
 pub enum WitnessCell {
     Copy(CopyWitnessCell),
@@ -229,3 +231,85 @@ pub fn extend_witness<F: PrimeField>(witness: &mut [Vec<F>; COLUMNS], fe: Foreig
         witness[col].extend(limbs_witness[col].iter())
     }
 }
+
+/// Create a witness for the range check gadget of `CircuitGate::create_range_check_bits`
+/// Input: the value and its number of bits
+pub fn create_bits_witness<F: PrimeField>(value: F, bits: usize) -> [Vec<F>; COLUMNS] {
+    match RangeCheckStrategy::choose(bits) {
+        RangeCheckStrategy::Lookup => create_limbs_witness(value, bits),
+        RangeCheckStrategy::Gates => create_decomposition_witness(value, bits),
+    }
+}
+
+/// Create a witness for the `RangeCheckStrategy::Lookup` layout
+fn create_limbs_witness<F: PrimeField>(value: F, bits: usize) -> [Vec<F>; COLUMNS] {
+    let (constraints, slots) = limbs_layout(bits);
+    let value_bits = value.to_bits();
+    let limbs = (bits + LIMB_BITS - 1) / LIMB_BITS;
+    let top_bits = bits - (limbs - 1) * LIMB_BITS;
+
+    // the highest limb gets all the remaining bits, so that it is out of range for a too large value
+    let limb = |i: usize| {
+        let end = if i + 1 == limbs {
+            value_bits.len()
+        } else {
+            (i + 1) * LIMB_BITS
+        };
+        F::from_bits(&value_bits[i * LIMB_BITS..end]).expect("failed to deserialize field bits")
+    };
+    let two_to_limb = F::from(2u64).pow([LIMB_BITS as u64]);
+    let var_value = |var: LimbsVar| match var {
+        LimbsVar::Value => value,
+        LimbsVar::Partial => limb(0) + two_to_limb * limb(1),
+        LimbsVar::Limb(i) => limb(i),
+        LimbsVar::Scaled => F::from(2u64).pow([(LIMB_BITS - top_bits) as u64]) * limb(limbs - 1),
+    };
+
+    // generic gates, two constraints per row
+    let mut witness: [Vec<F>; COLUMNS] = array::from_fn(|_| vec![]);
+    for pair in constraints.chunks(2) {
+        let mut row = [F::zero(); COLUMNS];
+        for (i, (left, right, output, _)) in pair.iter().enumerate() {
+            for (col, var) in [Some(*left), *right, *output].into_iter().enumerate() {
+                if let Some(var) = var {
+                    row[3 * i + col] = var_value(var);
+                }
+            }
+        }
+        for col in 0..COLUMNS {
+            witness[col].push(row[col]);
+        }
+    }
+
+    // multi range checks, three values each
+    for chunk in slots.chunks(3) {
+        let mut values = chunk.iter().map(|var| var_value(*var));
+        let mut next = || values.next().unwrap_or_else(F::zero);
+        let limbs_witness = create_multi_witness(next(), next(), next());
+        for col in 0..COLUMNS {
+            witness[col].extend(limbs_witness[col].iter())
+        }
+    }
+
+    witness
+}
+
+/// Create a witness for the `RangeCheckStrategy::Gates` layout
+fn create_decomposition_witness<F: PrimeField>(value: F, bits: usize) -> [Vec<F>; COLUMNS] {
+    let value_bits = value.to_bits();
+    let shifted = |i: usize| {
+        F::from_bits(&value_bits[i.min(value_bits.len())..])
+            .expect("failed to deserialize field bits")
+    };
+
+    let mut witness: [Vec<F>; COLUMNS] = array::from_fn(|_| vec![]);
+    for i in 0..bits {
+        let bit = F::from(value_bits[i] as u64);
+        let row = [shifted(i), bit, shifted(i + 1), bit, bit, bit];
+        for (col, cell) in witness.iter_mut().enumerate() {
+            cell.push(row.get(col).copied().unwrap_or_else(F::zero));
+        }
+    }
+
+    witness
+}
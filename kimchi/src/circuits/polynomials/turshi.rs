@@ -271,6 +271,19 @@ impl<F: PrimeField> CircuitGate<F> {
 
 pub mod witness {
     use super::*;
+    use crate::circuits::trace::{witness_from_rows, StepLayout, TraceToWitness};
+
+    /// Returns the layout of the steps of a Cairo execution, from its instructions:
+    /// the rows of the `CairoInstruction` and `CairoFlags` gates of an instruction,
+    /// followed by the rows of the `CairoTransition` gate and of the auxiliary row
+    /// (a `Zero` gate) to the next instruction
+    pub fn cairo_layout<F: Field>() -> StepLayout<CairoInstruction<F>, F> {
+        StepLayout::default()
+            .row(instruction_witness)
+            .row(flag_witness)
+            .transition(transition_witness)
+            .transition(|_, next| auxiliary_witness(next))
+    }
 
     /// Returns the witness of an execution of a Cairo program in `CircuitGate` format
     pub fn cairo_witness<F: Field>(prog: &CairoProgram<F>) -> [Vec<F>; COLUMNS] {
@@ -282,37 +295,9 @@ pub mod witness {
         // ...
         // 4n-3: 1 row for last instruction
         // 4n-2: 1 row for Auxiliary argument (no constraints)
-        let n = prog.trace().len();
-        let rows = 4 * n - 1;
-        let mut table: Vec<[F; COLUMNS]> = Vec::new();
-        table.resize(rows, [F::zero(); COLUMNS]);
-        for (i, inst) in prog.trace().iter().enumerate() {
-            if i == 0 {
-                let claim_wit = claim_witness(prog);
-                table[i] = claim_wit;
-            }
-            let ins_wit = instruction_witness(inst);
-            let flg_wit = flag_witness(inst);
-            table[4 * i + 1] = ins_wit;
-            table[4 * i + 2] = flg_wit;
-            if i != n - 1 {
-                // all but last instruction
-                let tra_wit = transition_witness(inst, &prog.trace()[i + 1]);
-                let aux_wit = auxiliary_witness(&prog.trace()[i + 1]);
-                table[4 * i + 3] = tra_wit;
-                table[4 * i + 4] = aux_wit;
-            }
-        }
-
-        let mut witness: [Vec<F>; COLUMNS] = Default::default();
-        for col in 0..COLUMNS {
-            // initialize column with zeroes
-            witness[col].resize(table.len(), F::zero());
-            for (row, wit) in table.iter().enumerate() {
-                witness[col][row] = wit[col];
-            }
-        }
-        witness
+        let mut table = vec![claim_witness(prog)];
+        table.extend(cairo_layout().rows(prog.trace()));
+        witness_from_rows(&table)
     }
 
     fn claim_witness<F: Field>(prog: &CairoProgram<F>) -> [F; COLUMNS] {
@@ -0,0 +1,88 @@
+//! This module implements [`RunLengthColumn`], a column of values over the rows of a circuit
+//! stored as runs of equal values.
+//!
+//! The coefficient columns of a circuit are mostly zero, or repeat the same constants
+//! over the rows of the same gate type, so that they take a few runs
+//! instead of a value per row of the domain.
+
+use ark_ff::{FftField, Zero};
+use ark_poly::{EvaluationDomain, Evaluations, Radix2EvaluationDomain as D};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use std::ops::Range;
+
+/// A column of values stored as runs of equal values, the rows past the runs being zero.
+#[serde_as]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunLengthColumn<F: FftField> {
+    /// The runs, as a value and the number of consecutive rows holding it
+    #[serde_as(as = "Vec<(o1_utils::serialization::SerdeAs, _)>")]
+    runs: Vec<(F, usize)>,
+}
+
+impl<F: FftField> RunLengthColumn<F> {
+    /// Compresses the values of a column, from its first row.
+    pub fn new(values: impl IntoIterator<Item = F>) -> Self {
+        let mut runs: Vec<(F, usize)> = vec![];
+        for value in values {
+            match runs.last_mut() {
+                Some((last, length)) if *last == value => *length += 1,
+                _ => runs.push((value, 1)),
+            }
+        }
+        // the trailing zeros are implicit
+        if matches!(runs.last(), Some((last, _)) if last.is_zero()) {
+            runs.pop();
+        }
+        Self { runs }
+    }
+
+    /// Returns the runs of the column, as a value and the number of consecutive rows holding it.
+    pub fn runs(&self) -> &[(F, usize)] {
+        &self.runs
+    }
+
+    /// Returns the number of rows covered by the runs,
+    /// past which the column is zero.
+    pub fn len(&self) -> usize {
+        self.runs.iter().map(|(_, length)| length).sum()
+    }
+
+    /// Returns `true` if the column is zero.
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    /// Returns the runs of nonzero values, as the rows they cover and their value.
+    pub fn nonzero_runs(&self) -> impl Iterator<Item = (Range<usize>, F)> + '_ {
+        self.runs
+            .iter()
+            .scan(0, |start, (value, length)| {
+                let rows = *start..*start + length;
+                *start = rows.end;
+                Some((rows, *value))
+            })
+            .filter(|(_, value)| !value.is_zero())
+    }
+
+    /// Returns the value of the column at `row`.
+    pub fn get(&self, row: usize) -> F {
+        self.nonzero_runs()
+            .find(|(rows, _)| rows.contains(&row))
+            .map_or_else(F::zero, |(_, value)| value)
+    }
+
+    /// Returns the evaluations of the column over `domain`.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the runs cover more rows than `domain`.
+    pub fn evaluations(&self, domain: D<F>) -> Evaluations<F, D<F>> {
+        assert!(self.len() <= domain.size());
+        let mut evals = vec![F::zero(); domain.size()];
+        for (rows, value) in self.nonzero_runs() {
+            evals[rows].fill(value);
+        }
+        Evaluations::from_vec_and_domain(evals, domain)
+    }
+}
@@ -1,17 +1,29 @@
 //! This module implements Plonk prover polynomial evaluations primitive.
 
 use ark_ff::Field;
+use o1_utils::serialization::SerdeAs;
 use oracle::sponge::ScalarChallenge;
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, Same};
 
-#[derive(Clone, Debug)]
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "F: Field")]
 pub struct RandomOracles<F: Field> {
+    #[serde_as(as = "Option<(Same, SerdeAs)>")]
     pub joint_combiner: Option<(ScalarChallenge<F>, F)>,
+    #[serde_as(as = "SerdeAs")]
     pub beta: F,
+    #[serde_as(as = "SerdeAs")]
     pub gamma: F,
     pub alpha_chal: ScalarChallenge<F>,
+    #[serde_as(as = "SerdeAs")]
     pub alpha: F,
+    #[serde_as(as = "SerdeAs")]
     pub zeta: F,
+    #[serde_as(as = "SerdeAs")]
     pub v: F,
+    #[serde_as(as = "SerdeAs")]
     pub u: F,
     pub zeta_chal: ScalarChallenge<F>,
     pub v_chal: ScalarChallenge<F>,
@@ -0,0 +1,107 @@
+//! This module implements the conversion of execution traces into the witness of a circuit.
+//!
+//! A VM-style frontend runs its program into an execution trace, a sequence of typed records
+//! (one per step of the execution), and proves it with a circuit repeating the same gates per step.
+//! [`TraceToWitness`] lays out such a trace into the rows of the witness,
+//! and [`StepLayout`] implements it from the layout functions of the gates of a step,
+//! so that the frontends only write the row of each of their gates:
+//!
+//! - [`StepLayout::row`] lays out a row from the record of its step,
+//! - [`StepLayout::transition`] lays out a row from the record of its step and of the next one,
+//!   the row being omitted at the last step.
+//!
+//! The rows of the steps follow each other in the order of the trace,
+//! the rows of a step being in the order the layout functions were added.
+
+use crate::circuits::wires::COLUMNS;
+use ark_ff::Field;
+use std::array;
+
+/// Lays out the row of a gate from the record of a step.
+pub type RowLayout<R, F> = fn(&R) -> [F; COLUMNS];
+
+/// Lays out the row of a gate from the record of a step and the record of the next step.
+pub type TransitionLayout<R, F> = fn(&R, &R) -> [F; COLUMNS];
+
+/// The conversion of an execution trace of typed records into the witness of a circuit
+pub trait TraceToWitness<F: Field> {
+    /// The record of a step of the execution
+    type Record;
+
+    /// Returns the rows of the witness of `trace`.
+    fn rows(&self, trace: &[Self::Record]) -> Vec<[F; COLUMNS]>;
+
+    /// Returns the witness of `trace`, with a row per row of [`TraceToWitness::rows`].
+    fn witness(&self, trace: &[Self::Record]) -> [Vec<F>; COLUMNS] {
+        witness_from_rows(&self.rows(trace))
+    }
+}
+
+/// Returns the witness columns of `rows`.
+pub fn witness_from_rows<F: Field>(rows: &[[F; COLUMNS]]) -> [Vec<F>; COLUMNS] {
+    array::from_fn(|col| rows.iter().map(|row| row[col]).collect())
+}
+
+/// The layout function of a row of a step
+enum StepRow<R, F> {
+    Row(RowLayout<R, F>),
+    Transition(TransitionLayout<R, F>),
+}
+
+/// The layout of the rows of a step of an execution, from the layout functions of their gates
+/// (see the [module documentation](self))
+pub struct StepLayout<R, F> {
+    rows: Vec<StepRow<R, F>>,
+}
+
+impl<R, F> Default for StepLayout<R, F> {
+    fn default() -> Self {
+        Self { rows: vec![] }
+    }
+}
+
+impl<R, F: Field> StepLayout<R, F> {
+    /// Appends a row to the steps, laid out by `layout` from the record of the step.
+    #[must_use]
+    pub fn row(mut self, layout: RowLayout<R, F>) -> Self {
+        self.rows.push(StepRow::Row(layout));
+        self
+    }
+
+    /// Appends a row to the steps but the last one,
+    /// laid out by `layout` from the record of the step and the record of the next step.
+    #[must_use]
+    pub fn transition(mut self, layout: TransitionLayout<R, F>) -> Self {
+        self.rows.push(StepRow::Transition(layout));
+        self
+    }
+
+    /// Returns the number of rows of a trace of `steps` steps.
+    pub fn num_rows(&self, steps: usize) -> usize {
+        let transitions = self
+            .rows
+            .iter()
+            .filter(|row| matches!(row, StepRow::Transition(_)))
+            .count();
+        steps * self.rows.len() - steps.min(1) * transitions
+    }
+}
+
+impl<R, F: Field> TraceToWitness<F> for StepLayout<R, F> {
+    type Record = R;
+
+    fn rows(&self, trace: &[R]) -> Vec<[F; COLUMNS]> {
+        let mut rows = Vec::with_capacity(self.num_rows(trace.len()));
+        for (i, record) in trace.iter().enumerate() {
+            let next = trace.get(i + 1);
+            for row in &self.rows {
+                match (row, next) {
+                    (StepRow::Row(layout), _) => rows.push(layout(record)),
+                    (StepRow::Transition(layout), Some(next)) => rows.push(layout(record, next)),
+                    (StepRow::Transition(_), None) => (),
+                }
+            }
+        }
+        rows
+    }
+}
@@ -2,8 +2,8 @@
 
 use ark_ff::bytes::{FromBytes, ToBytes};
 use serde::{Deserialize, Serialize};
-use std::array;
 use std::io::{Read, Result as IoResult, Write};
+use std::{array, fmt};
 
 /// Number of registers
 pub const COLUMNS: usize = 15;
@@ -33,6 +33,42 @@ impl Wire {
     }
 }
 
+/// A typed reference to a cell of a circuit, by its row and column.
+/// It converts from a `(row, col)` pair.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct CellRef {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl CellRef {
+    /// Creates a reference to the cell at `row` and `col`.
+    pub fn new(row: usize, col: usize) -> Self {
+        Self { row, col }
+    }
+}
+
+impl From<(usize, usize)> for CellRef {
+    fn from((row, col): (usize, usize)) -> Self {
+        Self { row, col }
+    }
+}
+
+impl From<Wire> for CellRef {
+    fn from(wire: Wire) -> Self {
+        Self {
+            row: wire.row,
+            col: wire.col,
+        }
+    }
+}
+
+impl fmt::Display for CellRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.row, self.col)
+    }
+}
+
 /// `GateWires` document the wiring of a gate. More specifically, each value either
 /// represents the same cell (row and column) or a different cell in another row.
 /// (This is to help the permutation argument.)
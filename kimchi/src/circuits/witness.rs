@@ -0,0 +1,386 @@
+//! This module implements the witness fill-in of the gates.
+//!
+//! Each gate type is associated with a [FillWitness] function that,
+//! given the inputs of a gate at some row, computes and writes all the cells
+//! that the gate constrains, so that the gate is satisfied.
+//! Frontends can thus compute their witness gate by gate,
+//! instead of duplicating the semantics of each gate.
+//!
+//! The built-in fill-ins take the following inputs:
+//!
+//! | gate            | inputs                                           | rows written |
+//! |:---------------:|:------------------------------------------------:|:------------:|
+//! | `Zero`          | none                                             | none         |
+//! | `Generic`       | `[l1, r1, l2, r2]`                               | 1            |
+//! | `TripleGeneric` | `[l1, r1, l2, r2, l3, r3]`                       | 1            |
+//! | `Poseidon`      | the state `[s0, s1, s2]` before the row's rounds | 2            |
+//! | `CompleteAdd`   | `[x1, y1, x2, y2]`                               | 1            |
+//! | `VarBaseMul`    | `[xT, yT, x0, y0, n, b0, b1, b2, b3, b4]`        | 2            |
+//! | `EndoMul`       | `[xT, yT, xP, yP, n, b1, b2, b3, b4]`            | 2            |
+//! | `EndoMulScalar` | `[n0, a0, b0, x0, ..., x7]`                      | 1            |
+//! | `CopyCells`     | none, the next row is copied                     | 1            |
+//!
+//! The other gates (lookups, ChaCha, Cairo, range checks, foreign field additions, Keccak)
+//! span gadgets whose witness is computed by their own modules,
+//! but a [WitnessTable] can be extended with fill-ins for them, or for custom gates.
+
+use crate::{
+    circuits::{
+        gate::{CircuitGate, CircuitGateError, CircuitGateResult, GateType},
+        polynomials::{
+            endomul_scalar, endosclmul,
+            generic::{GENERIC_COEFFS, GENERIC_GATES_REGISTERS},
+            poseidon::{round_to_cols, ROUNDS_PER_ROW, SPONGE_WIDTH},
+            varbasemul,
+        },
+        wires::{COLUMNS, PERMUTS},
+    },
+    curve::KimchiCurve,
+};
+use ark_ff::{Field, PrimeField};
+use oracle::{
+    constants::PlonkSpongeConstantsKimchi, permutation::full_round,
+    poseidon::ArithmeticSpongeParams,
+};
+use std::collections::HashMap;
+
+/// Fills in the witness of `gate` at row `row` from the inputs of the gate.
+pub type FillWitness<F> =
+    fn(&CircuitGate<F>, usize, &mut [Vec<F>; COLUMNS], &[F]) -> CircuitGateResult<()>;
+
+/// The witness fill-ins of the gate types,
+/// defaulting to the built-in fill-ins of the module documentation.
+pub struct WitnessTable<G: KimchiCurve> {
+    fillers: HashMap<GateType, FillWitness<G::ScalarField>>,
+}
+
+impl<G: KimchiCurve> Default for WitnessTable<G> {
+    fn default() -> Self {
+        Self {
+            fillers: HashMap::new(),
+        }
+    }
+}
+
+impl<G: KimchiCurve> WitnessTable<G> {
+    /// Registers the fill-in of a gate type, replacing its built-in fill-in if any.
+    pub fn register(mut self, typ: GateType, fill: FillWitness<G::ScalarField>) -> Self {
+        self.fillers.insert(typ, fill);
+        self
+    }
+
+    /// Returns the fill-in of a gate type, if any.
+    pub fn get(&self, typ: GateType) -> Option<FillWitness<G::ScalarField>> {
+        self.fillers
+            .get(&typ)
+            .copied()
+            .or_else(|| builtin::<G>(typ))
+    }
+
+    /// Fills in the witness of `gate` at row `row` from its `inputs`.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if there is no fill-in for the gate type,
+    /// if the inputs are invalid, or if the witness does not have the rows of the gate.
+    pub fn fill_witness(
+        &self,
+        gate: &CircuitGate<G::ScalarField>,
+        row: usize,
+        witness: &mut [Vec<G::ScalarField>; COLUMNS],
+        inputs: &[G::ScalarField],
+    ) -> CircuitGateResult<()> {
+        let fill = self
+            .get(gate.typ)
+            .ok_or(CircuitGateError::MissingWitnessFiller(gate.typ))?;
+        fill(gate, row, witness, inputs)
+    }
+}
+
+impl<F: PrimeField> CircuitGate<F> {
+    /// Fills in the witness of the gate at row `row` from its `inputs`,
+    /// using the built-in fill-in of its type (see [`WitnessTable`]).
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the gate type has no built-in fill-in,
+    /// if the inputs are invalid, or if the witness does not have the rows of the gate.
+    pub fn generate_witness<G: KimchiCurve<ScalarField = F>>(
+        &self,
+        row: usize,
+        witness: &mut [Vec<F>; COLUMNS],
+        inputs: &[F],
+    ) -> CircuitGateResult<()> {
+        WitnessTable::<G>::default().fill_witness(self, row, witness, inputs)
+    }
+}
+
+/// Returns the built-in fill-in of a gate type, if any.
+fn builtin<G: KimchiCurve>(typ: GateType) -> Option<FillWitness<G::ScalarField>> {
+    match typ {
+        GateType::Zero => Some(fill_zero),
+        GateType::Generic | GateType::TripleGeneric => Some(fill_generic),
+        GateType::Poseidon => Some(fill_poseidon::<G>),
+        GateType::CompleteAdd => Some(fill_complete_add),
+        GateType::VarBaseMul => Some(fill_varbasemul),
+        GateType::EndoMul => Some(fill_endomul::<G>),
+        GateType::EndoMulScalar => Some(fill_endomul_scalar),
+        GateType::CopyCells => Some(fill_copy_cells),
+        _ => None,
+    }
+}
+
+/// Returns the inputs of `gate`, checking their number.
+fn inputs<F: PrimeField, const N: usize>(
+    gate: &CircuitGate<F>,
+    inputs: &[F],
+) -> CircuitGateResult<[F; N]> {
+    inputs
+        .try_into()
+        .map_err(|_| CircuitGateError::WitnessInputCount(gate.typ, N, inputs.len()))
+}
+
+/// Checks that the witness has the `rows` rows of `gate` starting at row `row`.
+fn check_rows<F: PrimeField>(
+    gate: &CircuitGate<F>,
+    witness: &[Vec<F>; COLUMNS],
+    row: usize,
+    rows: usize,
+) -> CircuitGateResult<()> {
+    if witness.iter().all(|col| col.len() >= row + rows) {
+        Ok(())
+    } else {
+        Err(CircuitGateError::FailedToGetWitnessForRow(gate.typ, row))
+    }
+}
+
+/// Returns the bits of `values`, checking that they are booleans.
+fn bits<F: PrimeField>(gate: &CircuitGate<F>, values: &[F]) -> CircuitGateResult<Vec<bool>> {
+    values
+        .iter()
+        .map(|b| match b {
+            b if b.is_zero() => Ok(false),
+            b if b.is_one() => Ok(true),
+            _ => Err(CircuitGateError::InvalidWitnessInputs(gate.typ)),
+        })
+        .collect()
+}
+
+fn fill_zero<F: PrimeField>(
+    gate: &CircuitGate<F>,
+    _row: usize,
+    _witness: &mut [Vec<F>; COLUMNS],
+    values: &[F],
+) -> CircuitGateResult<()> {
+    inputs::<F, 0>(gate, values).map(|_| ())
+}
+
+/// Sets the left and right registers of the generic gates of the row (two or three),
+/// and their output registers.
+/// An output whose coefficient is zero is unconstrained, and set to zero.
+fn fill_generic<F: PrimeField>(
+    gate: &CircuitGate<F>,
+    row: usize,
+    witness: &mut [Vec<F>; COLUMNS],
+    values: &[F],
+) -> CircuitGateResult<()> {
+    let gates = if gate.typ == GateType::TripleGeneric {
+        let [l1, r1, l2, r2, l3, r3] = inputs(gate, values)?;
+        vec![(l1, r1), (l2, r2), (l3, r3)]
+    } else {
+        let [l1, r1, l2, r2] = inputs(gate, values)?;
+        vec![(l1, r1), (l2, r2)]
+    };
+    check_rows(gate, witness, row, 1)?;
+
+    for ((left, right), [l, r, _]) in gates.iter().zip(GENERIC_GATES_REGISTERS) {
+        witness[l][row] = *left;
+        witness[r][row] = *right;
+    }
+    for (i, [l, r, o]) in GENERIC_GATES_REGISTERS
+        .into_iter()
+        .take(gates.len())
+        .enumerate()
+    {
+        let coeff = |j| {
+            gate.coeffs
+                .get(i * GENERIC_COEFFS + j)
+                .copied()
+                .unwrap_or_else(F::zero)
+        };
+        let (left, right) = (witness[l][row], witness[r][row]);
+        let rest = coeff(0) * left + coeff(1) * right + coeff(3) * left * right + coeff(4);
+        witness[o][row] = coeff(2).inverse().map_or_else(F::zero, |inv| -rest * inv);
+    }
+
+    Ok(())
+}
+
+/// Applies the rounds of the row, with the round constants of the gate.
+fn fill_poseidon<G: KimchiCurve>(
+    gate: &CircuitGate<G::ScalarField>,
+    row: usize,
+    witness: &mut [Vec<G::ScalarField>; COLUMNS],
+    values: &[G::ScalarField],
+) -> CircuitGateResult<()> {
+    let state: [_; SPONGE_WIDTH] = inputs(gate, values)?;
+    check_rows(gate, witness, row, 2)?;
+
+    let params = ArithmeticSpongeParams {
+        round_constants: gate.rc().iter().map(|rc| rc.to_vec()).collect(),
+        mds: G::sponge_params().mds.clone(),
+    };
+    let mut state = state.to_vec();
+    for (col, s) in round_to_cols(0).zip(&state) {
+        witness[col][row] = *s;
+    }
+    for round in 0..ROUNDS_PER_ROW {
+        full_round::<_, PlonkSpongeConstantsKimchi>(&params, &mut state, round);
+
+        // the last state is stored in the next row
+        let next = (round + 1) % ROUNDS_PER_ROW;
+        let row = if next == 0 { row + 1 } else { row };
+        for (col, s) in round_to_cols(next).zip(&state) {
+            witness[col][row] = *s;
+        }
+    }
+
+    Ok(())
+}
+
+fn fill_complete_add<F: PrimeField>(
+    gate: &CircuitGate<F>,
+    row: usize,
+    witness: &mut [Vec<F>; COLUMNS],
+    values: &[F],
+) -> CircuitGateResult<()> {
+    let [x1, y1, x2, y2] = inputs(gate, values)?;
+    check_rows(gate, witness, row, 1)?;
+
+    let same_x = x1 == x2;
+    let s = if same_x {
+        let x1_squared = x1.square();
+        let y1_inv = y1
+            .double()
+            .inverse()
+            .ok_or(CircuitGateError::InvalidWitnessInputs(gate.typ))?;
+        (x1_squared.double() + x1_squared) * y1_inv
+    } else {
+        (y2 - y1) / (x2 - x1)
+    };
+    let x3 = s.square() - x1 - x2;
+    let y3 = s * (x1 - x3) - y1;
+    let inf = same_x && y1 != y2;
+    let inf_z = if inf {
+        (y2 - y1).inverse().unwrap()
+    } else {
+        F::zero()
+    };
+    let x21_inv = (x2 - x1).inverse().unwrap_or_else(F::zero);
+
+    let row_values = [
+        x1,
+        y1,
+        x2,
+        y2,
+        x3,
+        y3,
+        F::from(u64::from(inf)),
+        F::from(u64::from(same_x)),
+        s,
+        inf_z,
+        x21_inv,
+    ];
+    for (col, value) in row_values.into_iter().enumerate() {
+        witness[col][row] = value;
+    }
+
+    Ok(())
+}
+
+fn fill_varbasemul<F: PrimeField>(
+    gate: &CircuitGate<F>,
+    row: usize,
+    witness: &mut [Vec<F>; COLUMNS],
+    values: &[F],
+) -> CircuitGateResult<()> {
+    let [xt, yt, x0, y0, n, b0, b1, b2, b3, b4] = inputs(gate, values)?;
+    let bs = [b0, b1, b2, b3, b4];
+    let bits = bits(gate, &bs)?;
+    check_rows(gate, witness, row, 2)?;
+
+    varbasemul::witness(witness, row, (xt, yt), &bits, (x0, y0));
+
+    // the scalar accumulates on top of `n`
+    witness[4][row] = n;
+    witness[5][row] = bs.iter().fold(n, |acc, b| acc.double() + b);
+
+    Ok(())
+}
+
+fn fill_endomul<G: KimchiCurve>(
+    gate: &CircuitGate<G::ScalarField>,
+    row: usize,
+    witness: &mut [Vec<G::ScalarField>; COLUMNS],
+    values: &[G::ScalarField],
+) -> CircuitGateResult<()> {
+    let [xt, yt, xp, yp, n, b1, b2, b3, b4] = inputs(gate, values)?;
+    let bs = [b1, b2, b3, b4];
+    let bits = bits(gate, &bs)?;
+    check_rows(gate, witness, row, 2)?;
+
+    let endo = G::OtherCurve::endos().0;
+    endosclmul::gen_witness(witness, row, endo, (xt, yt), &bits, (xp, yp));
+
+    // the scalar accumulates on top of `n`
+    witness[6][row] = n;
+    witness[6][row + 1] = bs.iter().fold(n, |acc, b| acc.double() + b);
+
+    Ok(())
+}
+
+fn fill_endomul_scalar<F: PrimeField>(
+    gate: &CircuitGate<F>,
+    row: usize,
+    witness: &mut [Vec<F>; COLUMNS],
+    values: &[F],
+) -> CircuitGateResult<()> {
+    let [n0, a0, b0, xs @ ..]: [F; 11] = inputs(gate, values)?;
+    let crumbs = [0u64, 1, 2, 3].map(F::from);
+    if !xs.iter().all(|x| crumbs.contains(x)) {
+        return Err(CircuitGateError::InvalidWitnessInputs(gate.typ));
+    }
+    check_rows(gate, witness, row, 1)?;
+
+    let (mut n, mut a, mut b) = (n0, a0, b0);
+    for x in xs {
+        n = n.double().double() + x;
+        a = a.double() + endomul_scalar::c_func(x);
+        b = b.double() + endomul_scalar::d_func(x);
+    }
+
+    let row_values = [n0, n, a0, b0, a, b].into_iter().chain(xs);
+    for (col, value) in row_values.enumerate() {
+        witness[col][row] = value;
+    }
+    witness[14][row] = F::zero();
+
+    Ok(())
+}
+
+/// Copies the non-permuted cells of the next row, which must be filled in first.
+fn fill_copy_cells<F: PrimeField>(
+    gate: &CircuitGate<F>,
+    row: usize,
+    witness: &mut [Vec<F>; COLUMNS],
+    values: &[F],
+) -> CircuitGateResult<()> {
+    inputs::<F, 0>(gate, values)?;
+    check_rows(gate, witness, row, 2)?;
+
+    for col in 0..PERMUTS {
+        witness[col][row] = witness[PERMUTS + col][row + 1];
+    }
+
+    Ok(())
+}
@@ -0,0 +1,201 @@
+//! This module implements the audit of a witness against the ranges of its cells, as [`WitnessAudit`].
+//!
+//! Some cells of the gates must hold a boolean, a 2-bit crumb or a 12-bit limb,
+//! which the constraints (or the lookups) of the gates enforce.
+//! A witness violating them only fails as an unsatisfied quotient in the prover,
+//! without pointing to the faulty cell.
+//! The audit scans the witness before proving, and reports each cell whose value is out of its range,
+//! along with the gate of the row and the violated range.
+//!
+//! The audit knows the ranges of the cells of the built-in gates:
+//!
+//! | gate            | cells                                                 |
+//! |:---------------:|:-----------------------------------------------------:|
+//! | `CompleteAdd`   | booleans `inf` and `same_x` (columns 6 and 7)         |
+//! | `VarBaseMul`    | bits `b0` to `b4` (columns 2 to 6 of the next row)    |
+//! | `EndoMul`       | bits `b1` to `b4` (columns 11 to 14)                  |
+//! | `EndoMulScalar` | crumbs `x0` to `x7` (columns 6 to 13)                 |
+//! | `RangeCheck0`   | 12-bit limbs (columns 1 to 6) and crumbs (7 to 14)    |
+//! | `RangeCheck1`   | limbs and crumbs of its row and of the next one       |
+//! | `Xor16`         | nybbles of the inputs and output (columns 3 to 14)    |
+//!
+//! and frontends can declare the ranges of the cells of their circuits,
+//! for all the rows of a gate type or for a single cell.
+
+use crate::circuits::{
+    gate::{CircuitGate, CurrOrNext, GateType},
+    wires::COLUMNS,
+};
+use ark_ff::{BigInteger, One, PrimeField, Zero};
+use std::{collections::HashMap, fmt, ops::Range};
+
+/// The range of the values of a cell
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CellRange {
+    /// the value is 0 or 1
+    Boolean,
+    /// the value is less than `2^bits`
+    Bits(u32),
+}
+
+impl CellRange {
+    /// Returns whether `value` is in the range.
+    pub fn contains<F: PrimeField>(&self, value: F) -> bool {
+        match self {
+            CellRange::Boolean => value.is_zero() || value.is_one(),
+            CellRange::Bits(bits) => value.into_repr().num_bits() <= *bits,
+        }
+    }
+}
+
+impl fmt::Display for CellRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CellRange::Boolean => write!(f, "a boolean"),
+            CellRange::Bits(bits) => write!(f, "a {bits}-bit value"),
+        }
+    }
+}
+
+/// A cell of the witness whose value is out of its range
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RangeViolation<F> {
+    /// the row of the cell
+    pub row: usize,
+    /// the column of the cell
+    pub col: usize,
+    /// the gate that the range belongs to,
+    /// or for a single cell, the gate of its row if the circuit has that row
+    pub typ: Option<GateType>,
+    /// the range of the cell
+    pub range: CellRange,
+    /// the value of the cell
+    pub value: F,
+}
+
+impl<F: PrimeField> fmt::Display for RangeViolation<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "row {}, column {}", self.row, self.col)?;
+        if let Some(typ) = self.typ {
+            write!(f, " ({typ:?} gate)")?;
+        }
+        write!(f, ": {} is not {}", self.value, self.range)
+    }
+}
+
+/// The audit of a witness against the ranges of its cells (see the module documentation)
+#[derive(Clone, Debug)]
+pub struct WitnessAudit {
+    /// the ranges of the cells of the gate types, relative to the row of the gate
+    gate_ranges: HashMap<GateType, Vec<(CurrOrNext, usize, CellRange)>>,
+    /// the ranges of single cells, as their row, column and range
+    cell_ranges: Vec<(usize, usize, CellRange)>,
+}
+
+impl Default for WitnessAudit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WitnessAudit {
+    /// Creates an audit of the ranges of the built-in gates.
+    pub fn new() -> Self {
+        use CellRange::*;
+        use CurrOrNext::*;
+
+        let builtin = [
+            (GateType::CompleteAdd, Curr, 6..8, Boolean),
+            (GateType::VarBaseMul, Next, 2..7, Boolean),
+            (GateType::EndoMul, Curr, 11..15, Boolean),
+            (GateType::EndoMulScalar, Curr, 6..14, Bits(2)),
+            #[cfg(feature = "range_check")]
+            (GateType::RangeCheck0, Curr, 1..7, Bits(12)),
+            #[cfg(feature = "range_check")]
+            (GateType::RangeCheck0, Curr, 7..COLUMNS, Bits(2)),
+            #[cfg(feature = "range_check")]
+            (GateType::RangeCheck1, Curr, 1..3, Bits(2)),
+            #[cfg(feature = "range_check")]
+            (GateType::RangeCheck1, Curr, 3..7, Bits(12)),
+            #[cfg(feature = "range_check")]
+            (GateType::RangeCheck1, Curr, 7..COLUMNS, Bits(2)),
+            #[cfg(feature = "range_check")]
+            (GateType::RangeCheck1, Next, 1..3, Bits(2)),
+            #[cfg(feature = "range_check")]
+            (GateType::RangeCheck1, Next, 3..7, Bits(12)),
+            #[cfg(feature = "range_check")]
+            (GateType::RangeCheck1, Next, 7..COLUMNS, Bits(2)),
+            #[cfg(feature = "keccak")]
+            (GateType::Xor16, Curr, 3..COLUMNS, Bits(4)),
+        ];
+        let audit = Self {
+            gate_ranges: HashMap::new(),
+            cell_ranges: vec![],
+        };
+        builtin
+            .into_iter()
+            .fold(audit, |audit, (typ, row, cols, range)| {
+                audit.gate_range(typ, row, cols, range)
+            })
+    }
+
+    /// Declares the range of the columns `cols` of the row `row` (relative to the gate),
+    /// for all the gates of type `typ`.
+    pub fn gate_range(
+        mut self,
+        typ: GateType,
+        row: CurrOrNext,
+        cols: Range<usize>,
+        range: CellRange,
+    ) -> Self {
+        self.gate_ranges
+            .entry(typ)
+            .or_default()
+            .extend(cols.map(|col| (row, col, range)));
+        self
+    }
+
+    /// Declares the range of the cell of row `row` and column `col`.
+    pub fn cell_range(mut self, row: usize, col: usize, range: CellRange) -> Self {
+        self.cell_ranges.push((row, col, range));
+        self
+    }
+
+    /// Returns the cells of `witness`, for the circuit of `gates`, whose values are out of their ranges,
+    /// by row and column.
+    /// The cells beyond the witness are not audited.
+    pub fn run<F: PrimeField>(
+        &self,
+        gates: &[CircuitGate<F>],
+        witness: &[Vec<F>; COLUMNS],
+    ) -> Vec<RangeViolation<F>> {
+        let gate_cells = gates.iter().enumerate().flat_map(|(row, gate)| {
+            self.gate_ranges.get(&gate.typ).into_iter().flatten().map(
+                move |(curr_or_next, col, range)| {
+                    (row + curr_or_next.shift(), *col, Some(gate.typ), *range)
+                },
+            )
+        });
+        let single_cells = self
+            .cell_ranges
+            .iter()
+            .map(|(row, col, range)| (*row, *col, gates.get(*row).map(|gate| gate.typ), *range));
+
+        let mut violations: Vec<_> = gate_cells
+            .chain(single_cells)
+            .filter_map(|(row, col, typ, range)| {
+                let value = *witness.get(col)?.get(row)?;
+                (!range.contains(value)).then(|| RangeViolation {
+                    row,
+                    col,
+                    typ,
+                    range,
+                    value,
+                })
+            })
+            .collect();
+        violations.sort_by_key(|violation| (violation.row, violation.col));
+        violations.dedup();
+        violations
+    }
+}
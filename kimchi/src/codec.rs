@@ -0,0 +1,253 @@
+//! This module implements a versioned binary codec of [`ProverProof`]s and [`VerifierIndex`]es,
+//! to ship them across a network or to store them, between independently built provers and verifiers.
+//!
+//! An encoding is a header of 6 bytes, followed by the MessagePack encoding of the value
+//! (as given by `rmp_serde::to_vec`):
+//!
+//! | bytes    | content                                                       |
+//! |:--------:|:-------------------------------------------------------------:|
+//! | 0 to 3   | the magic bytes [`MAGIC`]                                     |
+//! | 4        | the kind of the value: 1 for a proof, 2 for a verifier index  |
+//! | 5        | the version of the layout of the value                        |
+//!
+//! The version of a proof is [`PROOF_VERSION`],
+//! and the version of a verifier index is the number of its [`LayoutVersion`]
+//! (0 for [`LayoutVersion::V0`], 1 for [`LayoutVersion::V1`]),
+//! so that the verifier indexes of an older layout are migrated as they are decoded
+//! (see [`crate::migration`]).
+//!
+//! Decoding only accepts canonical encodings: the value must be followed by no trailing data,
+//! and its bytes must be exactly the encoding of the decoded value
+//! (see [`decode_canonical_proof`]), so that a value has a single accepted encoding.
+//! As a verifier index leaves out of its encoding the parts derived from the rest
+//! (the SRS, the linearization, ...), it is rebuilt against the SRS as it is decoded,
+//! and checked against its digest.
+
+use crate::{
+    circuits::{lookup::constraints::LookupConfiguration, wires::COLUMNS},
+    curve::KimchiCurve,
+    error::{CodecError, VerifierIndexError},
+    migration::{LayoutVersion, Migration, VerifierIndexV0},
+    proof::ProverProof,
+    verifier::decode_canonical_proof,
+    verifier_index::{zero_comm, VerifierIndex, VerifierIndexBuilder},
+};
+use ark_ff::PrimeField;
+use commitment_dlog::srs::SRS;
+use oracle::FqSponge;
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// The magic bytes starting an encoding
+pub const MAGIC: [u8; 4] = *b"KIMC";
+
+/// The version of the encodings of proofs
+pub const PROOF_VERSION: u8 = 1;
+
+/// The kind of an encoded proof
+const PROOF: u8 = 1;
+
+/// The kind of an encoded verifier index
+const VERIFIER_INDEX: u8 = 2;
+
+/// Returns the name of the kind of an encoded value.
+fn kind_name(kind: u8) -> &'static str {
+    match kind {
+        PROOF => "proof",
+        VERIFIER_INDEX => "verifier index",
+        _ => "unknown value",
+    }
+}
+
+/// Returns the version of a layout of verifier indexes.
+fn version_of(layout: LayoutVersion) -> u8 {
+    match layout {
+        LayoutVersion::V0 => 0,
+        LayoutVersion::V1 => 1,
+    }
+}
+
+/// Returns the layout of verifier indexes of a version, if any.
+fn layout_of(version: u8) -> Option<LayoutVersion> {
+    match version {
+        0 => Some(LayoutVersion::V0),
+        1 => Some(LayoutVersion::V1),
+        _ => None,
+    }
+}
+
+/// Returns the encoding of `payload`, as a value of the kind `kind` and of the version `version`.
+fn encode(kind: u8, version: u8, payload: Vec<u8>) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(MAGIC.len() + 2 + payload.len());
+    bytes.extend(MAGIC);
+    bytes.extend([kind, version]);
+    bytes.extend(payload);
+    bytes
+}
+
+/// Checks the header of `bytes`, which must encode a value of the kind `kind`,
+/// and returns the version of the value and its MessagePack encoding.
+fn decode(kind: u8, bytes: &[u8]) -> Result<(u8, &[u8]), CodecError> {
+    match bytes {
+        [m0, m1, m2, m3, actual, version, payload @ ..] if [*m0, *m1, *m2, *m3] == MAGIC => {
+            if *actual != kind {
+                return Err(CodecError::UnexpectedKind(
+                    kind_name(kind),
+                    kind_name(*actual),
+                ));
+            }
+            Ok((*version, payload))
+        }
+        _ => Err(CodecError::MissingHeader),
+    }
+}
+
+/// Encodes a proof.
+///
+/// # Panics
+///
+/// Will panic if the proof cannot be serialized, which it always can.
+pub fn encode_proof<G: KimchiCurve>(proof: &ProverProof<G>) -> Vec<u8> {
+    let payload = rmp_serde::to_vec(proof).expect("proofs are serializable");
+    encode(PROOF, PROOF_VERSION, payload)
+}
+
+/// Decodes a proof encoded by [`encode_proof`], only accepting its canonical encoding.
+///
+/// # Errors
+///
+/// Will give error if `bytes` do not encode a proof of a supported version, or not canonically.
+pub fn decode_proof<G: KimchiCurve>(bytes: &[u8]) -> Result<ProverProof<G>, CodecError> {
+    let (version, payload) = decode(PROOF, bytes)?;
+    if version != PROOF_VERSION {
+        return Err(CodecError::UnsupportedVersion(kind_name(PROOF), version));
+    }
+    decode_canonical_proof(payload).map_err(CodecError::Proof)
+}
+
+/// Encodes a verifier index, with the current layout.
+///
+/// # Panics
+///
+/// Will panic if the verifier index cannot be serialized, which it always can.
+pub fn encode_verifier_index<G: KimchiCurve>(index: &VerifierIndex<G>) -> Vec<u8> {
+    let payload = rmp_serde::to_vec(index).expect("verifier indexes are serializable");
+    encode(VERIFIER_INDEX, version_of(LayoutVersion::CURRENT), payload)
+}
+
+/// Decodes a verifier index encoded by [`encode_verifier_index`], or with an older layout,
+/// only accepting its canonical encoding.
+/// The index is rebuilt against `srs`, which must contain the Lagrange basis of its domain,
+/// and against its lookup configuration, which must be given for circuits using lookups
+/// (as for [`VerifierIndexBuilder::lookup`]).
+/// If `expected_digest` is given, typically the digest of the index the verifier trusts,
+/// the rebuilt index must have this digest.
+/// Returns the index along with its digest and the layout it was encoded with.
+///
+/// # Errors
+///
+/// Will give error if `bytes` do not encode a verifier index of a supported layout,
+/// or not canonically, if the index does not match `srs` or the lookup configuration,
+/// or if the rebuilt index does not have the expected digest.
+pub fn decode_verifier_index<G, EFqSponge>(
+    bytes: &[u8],
+    srs: Arc<SRS<G>>,
+    lookup_configuration: Option<LookupConfiguration<G::ScalarField>>,
+    expected_digest: Option<G::BaseField>,
+) -> Result<Migration<VerifierIndex<G>, G::BaseField>, CodecError>
+where
+    G: KimchiCurve,
+    G::BaseField: PrimeField,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+{
+    let (version, payload) = decode(VERIFIER_INDEX, bytes)?;
+    let layout = layout_of(version).ok_or(CodecError::UnsupportedVersion(
+        kind_name(VERIFIER_INDEX),
+        version,
+    ))?;
+    let migration = match layout {
+        LayoutVersion::V0 => VerifierIndexV0::<G>::from_bytes(payload)
+            .and_then(|index| index.migrate::<EFqSponge>(srs, lookup_configuration)),
+        LayoutVersion::V1 => decode_current::<G, EFqSponge>(payload, srs, lookup_configuration),
+    };
+    let migration = migration.map_err(CodecError::VerifierIndex)?;
+    match expected_digest {
+        Some(expected) if expected != migration.digest => Err(CodecError::VerifierIndex(
+            VerifierIndexError::DigestMismatch,
+        )),
+        _ => Ok(migration),
+    }
+}
+
+/// Decodes a verifier index of the current layout from its canonical MessagePack encoding,
+/// and rebuilds the parts of the index left out of its encoding.
+fn decode_current<G, EFqSponge>(
+    payload: &[u8],
+    srs: Arc<SRS<G>>,
+    lookup_configuration: Option<LookupConfiguration<G::ScalarField>>,
+) -> Result<Migration<VerifierIndex<G>, G::BaseField>, VerifierIndexError>
+where
+    G: KimchiCurve,
+    G::BaseField: PrimeField,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+{
+    let incorrect =
+        |reason: &str| VerifierIndexError::IncorrectLayout(LayoutVersion::V1, reason.to_string());
+
+    let mut reader = payload;
+    let index = VerifierIndex::<G>::deserialize(&mut rmp_serde::Deserializer::new(&mut reader))
+        .map_err(|e| incorrect(&e.to_string()))?;
+    if !reader.is_empty() {
+        return Err(incorrect("trailing data"));
+    }
+    let encoding = rmp_serde::to_vec(&index).map_err(|e| incorrect(&e.to_string()))?;
+    if encoding != payload {
+        return Err(incorrect("non-canonical encoding"));
+    }
+    if index.max_poly_size != srs.g.len() {
+        return Err(incorrect("the SRS is not the one of the index"));
+    }
+
+    let zero = zero_comm(index.chunks());
+    let mut builder = VerifierIndexBuilder::new(srs, index.domain.size as usize)?
+        .public(index.public)
+        .prev_challenges(index.prev_challenges)
+        .zero_columns(
+            (0..COLUMNS)
+                .filter(|col| index.zero_columns[*col])
+                .collect(),
+        )
+        .triple_generic(index.triple_generic)
+        .sigma(index.sigma_comm.clone())
+        .coefficients(
+            index
+                .coefficients_comm
+                .iter()
+                .map(|comm| comm.as_ref().unwrap_or(&zero).clone())
+                .collect(),
+        )
+        .shifts(index.shift);
+    for (typ, comm) in index.selectors() {
+        builder = builder.selector(typ, comm);
+    }
+    if let Some(modulus) = index.foreign_field_modulus {
+        builder = builder.foreign_field_modulus(modulus);
+    }
+    for (name, value) in index.app_constants {
+        builder = builder.app_constant(name, value);
+    }
+    match (index.lookup_index, lookup_configuration) {
+        (None, None) => (),
+        (Some(lookup_index), Some(configuration)) => {
+            builder = builder.lookup(configuration, lookup_index);
+        }
+        _ => return Err(VerifierIndexError::InconsistentLookup("configuration")),
+    }
+
+    let (index, digest) = builder.build::<EFqSponge>()?;
+    Ok(Migration {
+        from: LayoutVersion::V1,
+        index,
+        digest,
+    })
+}
@@ -0,0 +1,438 @@
+//! This module implements the [`ColumnOpening`], an opening of some witness columns of a proof
+//! at points chosen after the proof was created, for example to reveal a cell of the witness.
+//!
+//! The columns are opened against the commitments of the proof, with an opening proof of their own,
+//! so that the proof is left as it is.
+//! Opening a column at the point `omega^i` of the domain reveals its cell at row `i`.
+//! Any other point reveals an evaluation of the column that the zero-knowledge rows
+//! only hide for a few such points, on top of the ones of the proof.
+//!
+//! Opening a column requires the blinders of its commitment,
+//! which are kept by the [`ProverWitness`] the proof was created from
+//! with [`ProverProof::create_incremental`].
+//!
+//! The scaling factors of the opening proof are derived from a fresh sponge,
+//! after absorbing the digest of the verifier index, the commitments of the opened columns,
+//! the points and the evaluations.
+//!
+//! As every column of a [`ColumnOpening`] is opened at every point,
+//! disclosing some cells of different rows and columns would disclose their whole cross product.
+//! A [`CellDisclosure`] instead opens each column only at the rows of its disclosed cells,
+//! with a single multi-point opening proof (see [`SRS::open_multi_point`](commitment_dlog::srs::SRS::open_multi_point)),
+//! so that no other cell is revealed.
+
+use crate::{
+    circuits::wires::{CellRef, COLUMNS},
+    curve::KimchiCurve,
+    error::{ProverError, VerifyError},
+    proof::ProverProof,
+    prover_index::ProverIndex,
+    prover_witness::ProverWitness,
+    verifier_index::VerifierIndex,
+};
+use ark_ec::AffineCurve;
+use ark_ff::{Field, PrimeField, Zero};
+use ark_poly::EvaluationDomain;
+use commitment_dlog::{
+    commitment::{BatchEvaluationProof, Evaluation, PolyComm},
+    evaluation_proof::OpeningProof,
+    multi_point::{
+        MultiPointEvaluation, MultiPointEvaluationProof, MultiPointOpeningProof,
+        MultiPointPolynomial,
+    },
+};
+use o1_utils::{serialization::SerdeAs, ExtendedDensePolynomial};
+use oracle::FqSponge;
+use rand::thread_rng;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use std::collections::BTreeMap;
+
+/// An opening of some witness columns of a proof, at some points.
+#[serde_as]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "G: ark_serialize::CanonicalDeserialize + ark_serialize::CanonicalSerialize")]
+pub struct ColumnOpening<G: AffineCurve> {
+    /// The opened witness columns
+    pub columns: Vec<usize>,
+    /// The points the columns are opened at
+    #[serde_as(as = "Vec<SerdeAs>")]
+    pub points: Vec<G::ScalarField>,
+    /// The evaluations of each opened column at each point, in chunks
+    #[serde_as(as = "Vec<Vec<Vec<SerdeAs>>>")]
+    pub evals: Vec<Vec<Vec<G::ScalarField>>>,
+    /// The opening proof of the evaluations
+    pub proof: OpeningProof<G>,
+}
+
+/// A disclosure of some cells of the witness of a proof.
+#[serde_as]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "G: ark_serialize::CanonicalDeserialize + ark_serialize::CanonicalSerialize")]
+pub struct CellDisclosure<G: AffineCurve> {
+    /// The disclosed cells, sorted by column and row
+    pub cells: Vec<CellRef>,
+    /// The evaluations of the column of each cell at its row, in chunks
+    #[serde_as(as = "Vec<Vec<SerdeAs>>")]
+    pub evals: Vec<Vec<G::ScalarField>>,
+    /// The opening proof of the evaluations
+    pub proof: MultiPointOpeningProof<G>,
+}
+
+/// Absorbs the opened commitments, points and evaluations,
+/// and returns the sponge along with the scaling factors of the opening proof.
+fn opening_sponge<'a, G, EFqSponge>(
+    verifier_index_digest: G::BaseField,
+    comms: &[&PolyComm<G>],
+    points: &[G::ScalarField],
+    evals: impl Iterator<Item = &'a Vec<G::ScalarField>>,
+) -> (EFqSponge, G::ScalarField, G::ScalarField)
+where
+    G: KimchiCurve,
+    EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
+{
+    let mut sponge = EFqSponge::new(G::OtherCurve::sponge_params());
+    sponge.absorb_fq(&[verifier_index_digest]);
+    for comm in comms {
+        sponge.absorb_g(&comm.unshifted);
+    }
+    sponge.absorb_fr(points);
+    for eval in evals {
+        sponge.absorb_fr(eval);
+    }
+    let polyscale = sponge.challenge();
+    let evalscale = sponge.challenge();
+    (sponge, polyscale, evalscale)
+}
+
+/// Groups the disclosed cells by column, in increasing order.
+/// Returns the distinct rows of the cells, in increasing order,
+/// and for each column the indices in the rows and in `cells` of its cells,
+/// or `None` if a cell is disclosed twice.
+#[allow(clippy::type_complexity)]
+fn group_cells(cells: &[CellRef]) -> Option<(Vec<usize>, BTreeMap<usize, Vec<(usize, usize)>>)> {
+    let mut rows: Vec<_> = cells.iter().map(|c| c.row).collect();
+    rows.sort_unstable();
+    rows.dedup();
+
+    let mut columns = BTreeMap::<_, Vec<_>>::new();
+    for (i, cell) in cells.iter().enumerate() {
+        let point = rows
+            .binary_search(&cell.row)
+            .expect("the row of the cell is listed");
+        columns.entry(cell.col).or_default().push((point, i));
+    }
+    for points in columns.values_mut() {
+        points.sort_unstable();
+        if points.windows(2).any(|w| w[0].0 == w[1].0) {
+            return None;
+        }
+    }
+    Some((rows, columns))
+}
+
+impl<G: KimchiCurve> ColumnOpening<G>
+where
+    G::BaseField: PrimeField,
+{
+    /// Opens the witness `columns` at `points`, against the commitments of the proof
+    /// created from `witness`.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if a column does not exist or is declared zero by the index.
+    pub fn create<EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>>(
+        group_map: &G::Map,
+        index: &ProverIndex<G>,
+        witness: &ProverWitness<G>,
+        columns: &[usize],
+        points: &[G::ScalarField],
+    ) -> Result<Self, ProverError> {
+        if let Some(col) = columns
+            .iter()
+            .find(|col| **col >= COLUMNS || index.cs.zero_columns[**col])
+        {
+            return Err(ProverError::InvalidColumn(*col));
+        }
+
+        let evals: Vec<Vec<_>> = columns
+            .iter()
+            .map(|col| {
+                let poly = witness.polys[*col].to_chunked_polynomial(index.max_poly_size);
+                points.iter().map(|x| poly.evaluate_chunks(*x)).collect()
+            })
+            .collect();
+
+        let comms: Vec<_> = columns
+            .iter()
+            .map(|col| &witness.comm[*col].commitment)
+            .collect();
+        let (sponge, polyscale, evalscale) = opening_sponge::<G, EFqSponge>(
+            index.verifier_index_digest::<EFqSponge>(),
+            &comms,
+            points,
+            evals.iter().flatten(),
+        );
+
+        let polynomials: Vec<_> = columns
+            .iter()
+            .map(|col| {
+                (
+                    &witness.polys[*col],
+                    None,
+                    witness.comm[*col].blinders.clone(),
+                )
+            })
+            .collect();
+        let proof = index.srs.open(
+            group_map,
+            &polynomials,
+            points,
+            polyscale,
+            evalscale,
+            sponge,
+            &mut rand::rngs::OsRng,
+        );
+
+        Ok(Self {
+            columns: columns.to_vec(),
+            points: points.to_vec(),
+            evals,
+            proof,
+        })
+    }
+
+    /// Checks the opening against the commitments of `proof`.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if a column does not exist or is declared zero by the index,
+    /// if the evaluations do not match the columns, points and commitments,
+    /// or if the opening proof fails to verify.
+    pub fn verify<EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>>(
+        &self,
+        group_map: &G::Map,
+        index: &VerifierIndex<G>,
+        proof: &ProverProof<G>,
+    ) -> Result<(), VerifyError> {
+        if let Some(col) = self
+            .columns
+            .iter()
+            .find(|col| **col >= COLUMNS || index.zero_columns[**col])
+        {
+            return Err(VerifyError::InvalidColumn(*col));
+        }
+
+        let comms: Vec<_> = self
+            .columns
+            .iter()
+            .map(|col| &proof.commitments.w_comm[*col])
+            .collect();
+        let consistent = self.evals.len() == comms.len()
+            && self.evals.iter().zip(&comms).all(|(evals, comm)| {
+                evals.len() == self.points.len()
+                    && evals.iter().all(|e| e.len() == comm.unshifted.len())
+            });
+        if !consistent {
+            return Err(VerifyError::IncorrectEvaluationsLength("column opening"));
+        }
+
+        let (sponge, polyscale, evalscale) = opening_sponge::<G, EFqSponge>(
+            index.digest::<EFqSponge>(),
+            &comms,
+            &self.points,
+            self.evals.iter().flatten(),
+        );
+
+        let mut batch = vec![BatchEvaluationProof {
+            sponge,
+            evaluations: comms
+                .iter()
+                .zip(&self.evals)
+                .map(|(comm, evals)| Evaluation {
+                    commitment: (*comm).clone(),
+                    evaluations: evals.clone(),
+                    degree_bound: None,
+                })
+                .collect(),
+            evaluation_points: self.points.clone(),
+            polyscale,
+            evalscale,
+            opening: &self.proof,
+        }];
+        if index
+            .srs()
+            .verify::<EFqSponge, _>(group_map, &mut batch, &mut thread_rng())
+        {
+            Ok(())
+        } else {
+            Err(VerifyError::OpenProof)
+        }
+    }
+}
+
+impl<G: KimchiCurve> CellDisclosure<G>
+where
+    G::BaseField: PrimeField,
+{
+    /// Discloses the witness `cells`, against the commitments of the proof created from `witness`.
+    /// Cells listed more than once are disclosed once.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the column of a cell does not exist or is declared zero by the index.
+    pub fn create<EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>>(
+        group_map: &G::Map,
+        index: &ProverIndex<G>,
+        witness: &ProverWitness<G>,
+        cells: &[CellRef],
+    ) -> Result<Self, ProverError> {
+        if let Some(cell) = cells
+            .iter()
+            .find(|c| c.col >= COLUMNS || index.cs.zero_columns[c.col])
+        {
+            return Err(ProverError::InvalidColumn(cell.col));
+        }
+
+        let mut cells = cells.to_vec();
+        cells.sort_unstable_by_key(|c| (c.col, c.row));
+        cells.dedup();
+        let (rows, columns) = group_cells(&cells).expect("the cells are deduplicated");
+        let domain = index.cs.domain.d1;
+        let points: Vec<_> = rows.iter().map(|row| domain.element(*row)).collect();
+
+        let evals: Vec<_> = cells
+            .iter()
+            .map(|c| {
+                witness.polys[c.col]
+                    .to_chunked_polynomial(index.max_poly_size)
+                    .evaluate_chunks(domain.element(c.row))
+            })
+            .collect();
+
+        let comms: Vec<_> = columns
+            .keys()
+            .map(|col| &witness.comm[*col].commitment)
+            .collect();
+        let (sponge, polyscale, evalscale) = opening_sponge::<G, EFqSponge>(
+            index.verifier_index_digest::<EFqSponge>(),
+            &comms,
+            &points,
+            evals.iter(),
+        );
+
+        let polynomials: Vec<_> = columns
+            .iter()
+            .map(|(col, cells)| MultiPointPolynomial {
+                poly: &witness.polys[*col],
+                blinders: witness.comm[*col].blinders.clone(),
+                points: cells.iter().map(|(point, _)| *point).collect(),
+            })
+            .collect();
+        let proof = index.srs.open_multi_point(
+            group_map,
+            &polynomials,
+            &points,
+            polyscale,
+            evalscale,
+            sponge,
+            &mut rand::rngs::OsRng,
+        );
+
+        Ok(Self {
+            cells,
+            evals,
+            proof,
+        })
+    }
+
+    /// Checks the disclosure against the commitments of `proof`,
+    /// and returns the values of the disclosed cells.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the column of a cell does not exist or is declared zero by the index,
+    /// if a cell is out of the domain or disclosed twice,
+    /// if the evaluations do not match the cells and commitments,
+    /// or if the opening proof fails to verify.
+    pub fn verify<EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>>(
+        &self,
+        group_map: &G::Map,
+        index: &VerifierIndex<G>,
+        proof: &ProverProof<G>,
+    ) -> Result<Vec<(CellRef, G::ScalarField)>, VerifyError> {
+        if let Some(cell) = self
+            .cells
+            .iter()
+            .find(|c| c.col >= COLUMNS || index.zero_columns[c.col])
+        {
+            return Err(VerifyError::InvalidColumn(cell.col));
+        }
+
+        let grouped = group_cells(&self.cells);
+        let consistent = self.evals.len() == self.cells.len()
+            && self.cells.iter().all(|c| c.row < index.domain.size())
+            && self
+                .cells
+                .iter()
+                .zip(&self.evals)
+                .all(|(c, e)| e.len() == proof.commitments.w_comm[c.col].unshifted.len());
+        let (rows, columns) = match grouped {
+            Some(grouped) if consistent => grouped,
+            _ => return Err(VerifyError::IncorrectEvaluationsLength("cell disclosure")),
+        };
+        let points: Vec<_> = rows.iter().map(|row| index.domain.element(*row)).collect();
+
+        let comms: Vec<_> = columns
+            .keys()
+            .map(|col| &proof.commitments.w_comm[*col])
+            .collect();
+        let (sponge, polyscale, evalscale) = opening_sponge::<G, EFqSponge>(
+            index.digest::<EFqSponge>(),
+            &comms,
+            &points,
+            self.evals.iter(),
+        );
+
+        let mut batch = vec![MultiPointEvaluationProof {
+            sponge,
+            evaluations: columns
+                .iter()
+                .zip(&comms)
+                .map(|((_, cells), comm)| MultiPointEvaluation {
+                    commitment: (*comm).clone(),
+                    points: cells.iter().map(|(point, _)| *point).collect(),
+                    evaluations: cells.iter().map(|(_, i)| self.evals[*i].clone()).collect(),
+                })
+                .collect(),
+            evaluation_points: points,
+            polyscale,
+            evalscale,
+            opening: &self.proof,
+        }];
+        if !index
+            .srs()
+            .verify_multi_point(group_map, &mut batch, &mut thread_rng())
+        {
+            return Err(VerifyError::OpenProof);
+        }
+
+        // the value of a cell combines the evaluations of the chunks of its column
+        Ok(self
+            .cells
+            .iter()
+            .zip(&self.evals)
+            .map(|(cell, chunks)| {
+                let shift = index
+                    .domain
+                    .element(cell.row)
+                    .pow([index.max_poly_size as u64]);
+                let value = chunks
+                    .iter()
+                    .rev()
+                    .fold(G::ScalarField::zero(), |acc, chunk| acc * shift + chunk);
+                (*cell, value)
+            })
+            .collect())
+    }
+}
@@ -1,11 +1,18 @@
 use ark_ec::{short_weierstrass_jacobian::GroupAffine, ModelParameters};
+use ark_ff::PrimeField;
 use commitment_dlog::{commitment::CommitmentCurve, srs::endos};
 use mina_curves::pasta::curves::{
     pallas::{LegacyPallasParameters, PallasParameters},
     vesta::{LegacyVestaParameters, VestaParameters},
 };
+use num_bigint::BigUint;
+use o1_utils::{
+    field_helpers::FieldHelpers,
+    foreign_field::{LIMB_BITS, LIMB_COUNT},
+};
 use once_cell::sync::Lazy;
 use oracle::poseidon::ArithmeticSpongeParams;
+use std::array;
 
 ///Represents additional information that a curve needs in order to be used with Kimchi
 pub trait KimchiCurve: CommitmentCurve {
@@ -83,3 +90,49 @@ impl KimchiCurve for GroupAffine<LegacyPallasParameters> {
         GroupAffine::<PallasParameters>::endos()
     }
 }
+
+//
+// conversions between the fields of the cycle
+//
+
+/// Converts an element of the scalar field of `G` into its base field,
+/// returning `None` if it is not smaller than the base field modulus.
+pub fn scalar_to_base_checked<G: KimchiCurve>(x: G::ScalarField) -> Option<G::BaseField> {
+    to_field_checked(x.into())
+}
+
+/// Converts an element of the base field of `G` into its scalar field,
+/// returning `None` if it is not smaller than the scalar field modulus.
+pub fn base_to_scalar_checked<G: KimchiCurve>(x: G::BaseField) -> Option<G::ScalarField> {
+    to_field_checked(x.into())
+}
+
+/// Encodes an element of the base field of `G` as [LIMB_COUNT] limbs of [LIMB_BITS] bits
+/// in its scalar field (in little endian), as expected by the foreign field gates.
+pub fn to_other_field_limbs<G: KimchiCurve>(x: G::BaseField) -> [G::ScalarField; LIMB_COUNT] {
+    let big: BigUint = x.into();
+    let mask = (BigUint::from(1u8) << LIMB_BITS) - 1u8;
+    array::from_fn(|i| G::ScalarField::from((&big >> (i * LIMB_BITS)) & &mask))
+}
+
+/// Decodes an element of the base field of `G` from its limbs in the scalar field
+/// (see [to_other_field_limbs]), returning `None` if a limb has more than [LIMB_BITS] bits
+/// or if the encoded value is not smaller than the base field modulus.
+pub fn from_limbs_checked<G: KimchiCurve>(
+    limbs: &[G::ScalarField; LIMB_COUNT],
+) -> Option<G::BaseField> {
+    let mut big = BigUint::from(0u8);
+    for limb in limbs.iter().rev() {
+        let limb: BigUint = (*limb).into();
+        if limb.bits() > LIMB_BITS as u64 {
+            return None;
+        }
+        big = (big << LIMB_BITS) + limb;
+    }
+    to_field_checked(big)
+}
+
+/// Converts an integer into a field element, if it is smaller than the modulus.
+fn to_field_checked<F: PrimeField>(big: BigUint) -> Option<F> {
+    (big < F::modulus_biguint()).then(|| F::from(big))
+}
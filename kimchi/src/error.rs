@@ -1,8 +1,46 @@
 //! This module implements the [`ProverError`] type.
 
-use commitment_dlog::error::CommitmentError;
+use crate::circuits::{expr::Column, gate::GateType, wires::CellRef};
+use crate::migration::LayoutVersion;
+use commitment_dlog::error::{CommitmentError, OpeningError};
+use o1_utils::serialization::LengthLimitExceeded;
 use thiserror::Error;
 
+/// Errors of the runtime tables of a proof, against their configuration in the index
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuntimeTableError {
+    #[error("the runtime table {0} is not configured")]
+    Unknown(i64),
+
+    #[error("the runtime table {0} is given more than once")]
+    Duplicate(i64),
+
+    #[error("the runtime table {0} is missing")]
+    Missing(i64),
+
+    #[error("the runtime table {got} is given where the runtime table {expected} is configured")]
+    Order { expected: i64, got: i64 },
+
+    #[error("the runtime table {id} has {len} entries, which its configuration (of {max} entries) does not accept")]
+    Length { id: i64, len: usize, max: usize },
+}
+
+/// The ways a witness does not satisfy a row of its circuit (see [`ProverError::WitnessNotSatisfied`])
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WitnessError {
+    #[error("the gate does not hold")]
+    Gate,
+
+    #[error("the cell in column {0} is wired to the cell {1:?}, which holds a different value")]
+    Wiring(usize, CellRef),
+
+    #[error("the row is a public input, but its gate does not expose it")]
+    Public,
+
+    #[error("the lookup {0} of the row is not in its table")]
+    Lookup(usize),
+}
+
 /// Errors that can arise when creating a proof
 // TODO(mimoo): move this out of oracle
 #[derive(Error, Debug, Clone, Copy)]
@@ -22,14 +60,73 @@ pub enum ProverError {
     #[error("the lookup failed to find a match in the table")]
     ValueNotInTable,
 
-    #[error("SRS size is smaller than the domain size required by the circuit")]
+    #[error(
+        "SRS size is smaller than the domain size required by the public input of the circuit"
+    )]
     SRSTooSmall,
 
-    #[error("the runtime tables provided did not match the index's configuration")]
-    RuntimeTablesInconsistent,
+    #[error("the runtime tables provided did not match the index's configuration: {0}")]
+    RuntimeTablesInconsistent(RuntimeTableError),
 
     #[error("wrong number of custom blinders given: {0}")]
     WrongBlinders(CommitmentError),
+
+    #[error("the witness column {0} is declared zero, but is not")]
+    NonZeroColumn(usize),
+
+    #[error("the witness column {0} cannot be opened, as it does not exist or is declared zero")]
+    InvalidColumn(usize),
+
+    #[error("the witness column {0} cannot be linked, as it is committed in several chunks")]
+    ChunkedColumn(usize),
+
+    #[error("the linked cells {0:?} and {1:?} do not hold the same value")]
+    UnequalLink(CellRef, CellRef),
+
+    #[error("cannot link proofs using different SRSes")]
+    DifferentSRS,
+
+    #[error("the proof was cancelled")]
+    Cancelled,
+
+    #[error("the {constraint} constraint does not hold at row {row} (a {gate:?} gate), nor at {others} other rows")]
+    ConstraintNotSatisfied {
+        constraint: &'static str,
+        row: usize,
+        gate: GateType,
+        others: usize,
+    },
+
+    #[error("the witness does not satisfy the circuit at row {row} (a {gate:?} gate): {cause}")]
+    WitnessNotSatisfied {
+        row: usize,
+        gate: GateType,
+        cause: WitnessError,
+    },
+}
+
+/// A polynomial opened by a proof, as named by the errors of [`crate::verifier::batch_verify_with_diagnostics`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenedPolynomial {
+    /// The polynomial of the challenges of the given previous proof
+    PrevChallenges(usize),
+    /// The (negated) public input polynomial
+    Public,
+    /// The linearized quotient polynomial $ft$
+    Ft,
+    /// The permutation polynomial of the given column
+    Sigma(usize),
+    /// A polynomial of the circuit
+    Column(Column),
+}
+
+/// A point the polynomials of a proof are evaluated at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EvaluationPoint {
+    /// The evaluation point $\zeta$
+    Zeta,
+    /// The evaluation point $\zeta\omega$
+    ZetaOmega,
 }
 
 /// Errors that can arise when verifying a proof
@@ -38,6 +135,9 @@ pub enum VerifyError {
     #[error("the commitment to {0} is of an unexpected size")]
     IncorrectCommitmentLength(&'static str),
 
+    #[error("the evaluations of {0} are of an unexpected size")]
+    IncorrectEvaluationsLength(&'static str),
+
     #[error("the public input is of an unexpected size (expected {0})")]
     IncorrectPubicInputLength(usize),
 
@@ -47,9 +147,33 @@ pub enum VerifyError {
     #[error("the opening proof failed to verify")]
     OpenProof,
 
+    #[error("the opening proof failed to verify ({0})")]
+    OpenProofDiagnostics(OpeningError),
+
+    #[error(
+        "proof {proof}: the evaluations of {polynomial:?} are given at {rows} points (expected 2)"
+    )]
+    EvaluationPoints {
+        proof: usize,
+        polynomial: OpenedPolynomial,
+        rows: usize,
+    },
+
+    #[error("proof {proof}: the evaluation of {polynomial:?} at {point:?} has {chunks} chunks, but its commitment has {expected}")]
+    EvaluationChunks {
+        proof: usize,
+        polynomial: OpenedPolynomial,
+        point: EvaluationPoint,
+        chunks: usize,
+        expected: usize,
+    },
+
     #[error("lookup used in circuit, but proof is missing lookup commitments")]
     LookupCommitmentMissing,
 
+    #[error("lookup not used in circuit, but proof has lookup commitments")]
+    UnexpectedLookupCommitment,
+
     #[error("lookup used in circuit, but proof is missing lookup evaluations")]
     LookupEvalsMissing,
 
@@ -59,11 +183,56 @@ pub enum VerifyError {
     #[error("cannot batch proofs using different SRSes")]
     DifferentSRS,
 
-    #[error("SRS size is smaller than the domain size required by the circuit")]
+    #[error(
+        "SRS size is smaller than the domain size required by the public input of the circuit"
+    )]
     SRSTooSmall,
 
     #[error("runtime tables are used, but missing from the proof")]
     IncorrectRuntimeProof,
+
+    #[error("the oracles do not match the captured transcript ({0})")]
+    OraclesMismatch(&'static str),
+
+    #[error("the commitment to {0} has {2} chunks (expected {1})")]
+    IncorrectCommitmentChunks(&'static str, usize, usize),
+
+    #[error("the commitment to {0} has an unexpected shifted part")]
+    UnexpectedShiftedCommitment(&'static str),
+
+    #[error("the evaluations of {0} have {2} chunks (expected at most {1})")]
+    IncorrectEvaluationChunks(&'static str, usize, usize),
+
+    #[error("the evaluations of {0} have {2} chunks, unlike its commitment ({1})")]
+    EvaluationChunksMismatch(&'static str, usize, usize),
+
+    #[error("the proof has {1} sorted lookup polynomials (expected {0})")]
+    IncorrectSortedLength(usize, usize),
+
+    #[error("the opening proof has {1} rounds (expected {0})")]
+    IncorrectOpeningRounds(usize, usize),
+
+    #[error("the witness column {0} cannot be opened, as it does not exist or is declared zero")]
+    InvalidColumn(usize),
+
+    #[error("the proof is not canonically encoded ({0})")]
+    NonCanonicalProof(&'static str),
+
+    #[error("the proof exceeds the limits of its encoding ({0})")]
+    LengthLimitExceeded(LengthLimitExceeded),
+}
+
+/// Errors of the verification of the proofs on both curves of a cycle,
+/// as reported by [`crate::verifier::batch_verify_cycle`]
+#[derive(Error, Debug, Clone, Copy)]
+#[error(
+    "the proofs of the cycle failed verification (first curve: {first:?}, other curve: {other:?})"
+)]
+pub struct CycleVerifyError {
+    /// the error of the proofs on the first curve, if any
+    pub first: Option<VerifyError>,
+    /// the error of the proofs on the other curve, if any
+    pub other: Option<VerifyError>,
 }
 
 /// Errors that can arise when preparing the setup
@@ -74,6 +243,21 @@ pub enum SetupError {
 
     #[error("the domain could not be constructed: {0}")]
     DomainCreation(&'static str),
+
+    #[error("the cells {0} and {1} connected at {3} cannot be connected: {2}")]
+    Connection(CellRef, CellRef, &'static str, String),
+
+    #[error("the cell {0} pinned to a constant at {2} cannot be pinned: {1}")]
+    Constant(CellRef, &'static str, String),
+
+    #[error("the gate at row {0} is invalid: {1}")]
+    Gate(usize, CircuitGateError),
+
+    #[error("the shifts of the permutation are invalid: {0}")]
+    Shifts(&'static str),
+
+    #[error("the linearization does not match its snapshot: {0}")]
+    LinearizationSnapshot(String),
 }
 
 /// Errors that can arise when creating a verifier index
@@ -81,4 +265,87 @@ pub enum SetupError {
 pub enum VerifierIndexError {
     #[error("srs has already been set")]
     SRSHasBeenSet,
+
+    #[error("the domain could not be constructed")]
+    DomainCreation,
+
+    #[error("the srs does not contain the lagrange basis of the domain of size {0}")]
+    LagrangeBasisMissing(usize),
+
+    #[error("expected {1} commitments to {0}, got {2}")]
+    IncorrectCommitmentCount(&'static str, usize, usize),
+
+    #[error("the commitment to {0} should have {1} unshifted chunks only")]
+    IncorrectCommitmentSize(&'static str, usize),
+
+    #[error("the column {0} does not exist")]
+    ColumnMissing(usize),
+
+    #[error("the selector of the gate {0:?} is missing")]
+    SelectorMissing(GateType),
+
+    #[error("the gate {0:?} does not have a selector in the verifier index")]
+    UnexpectedSelector(GateType),
+
+    #[error("the lookup commitments are inconsistent with the lookup configuration ({0})")]
+    InconsistentLookup(&'static str),
+
+    #[error("the digest of the verifier index is not the expected one")]
+    DigestMismatch,
+
+    #[error("the linearization of the verifier index is not the exported one")]
+    LinearizationMismatch,
+
+    #[error("the exported verifier index has an invalid {0}")]
+    InvalidExport(&'static str),
+
+    #[error("the exported verifier index could not be parsed: {0}")]
+    Json(String),
+
+    #[error("the index is not a valid index of the layout {0:?}: {1}")]
+    IncorrectLayout(LayoutVersion, String),
+
+    #[error("the index uses the {0} gates, whose feature is not enabled")]
+    GateDisabled(&'static str),
+
+    #[error("the specification of the lookup tables is invalid: {0}")]
+    InvalidTableSpec(String),
+
+    #[error("the commitments to the {0} do not match the specification of the lookup tables")]
+    TableMismatch(&'static str),
+
+    #[error("the shifts of the permutation are invalid: {0}")]
+    InvalidShifts(&'static str),
+}
+
+/// Errors that can arise when storing or loading indexes with a [`crate::keystore::KeyStore`]
+#[derive(Error, Debug, Clone)]
+pub enum KeyStoreError {
+    #[error("the key store could not be accessed: {0}")]
+    Io(String),
+
+    #[error("the constraint system could not be built: {0}")]
+    Setup(SetupError),
+
+    #[error("the indexes could not be serialized: {0}")]
+    Serialization(String),
+}
+
+/// Errors of the decoding of proofs and verifier indexes with [`crate::codec`]
+#[derive(Error, Debug, Clone)]
+pub enum CodecError {
+    #[error("the bytes do not start with the header of an encoding")]
+    MissingHeader,
+
+    #[error("the bytes encode a {1}, not a {0}")]
+    UnexpectedKind(&'static str, &'static str),
+
+    #[error("the version {1} of the encoding of a {0} is not supported")]
+    UnsupportedVersion(&'static str, u8),
+
+    #[error("the proof could not be decoded: {0}")]
+    Proof(VerifyError),
+
+    #[error("the verifier index could not be decoded: {0}")]
+    VerifierIndex(VerifierIndexError),
 }
@@ -0,0 +1,241 @@
+//! This module implements the [`IndexAuditLog`], a structured log of the inputs and the commitments
+//! that go into the creation of an index, for parties that build a same index independently
+//! and need to find out why their verifier indexes (or their digests) disagree.
+//!
+//! The log is a flat list of entries, each made of the path of a value and of the value itself,
+//! in a fixed order:
+//!
+//! - the optional gates enabled in the build (`features`),
+//! - the SRS: its size, its blinding generator and a digest of its generators (`srs.*`),
+//! - the constraint system: its domains and a fingerprint of each of its gates (`cs.*`),
+//! - every value of the verifier index, as exported by [`VerifierIndexJson`]
+//!   (shifts, selector and coefficient commitments, lookup tables, digests, ...),
+//!   one leaf per entry (`verifier_index.*`).
+//!
+//! Two logs serialize to JSON Lines with [`IndexAuditLog::write_jsonl`], so that they diff with
+//! standard tools, and [`IndexAuditLog::diff`] returns the entries they disagree on.
+//! A different SRS or different gates usually explain different commitments.
+//!
+//! The log is only built with the `index_audit` feature.
+
+use crate::{
+    circuits::lookup::constraints::LookupConfiguration, curve::KimchiCurve,
+    error::VerifierIndexError, prover_index::ProverIndex, verifier_index::VerifierIndex,
+    verifier_index_json::VerifierIndexJson,
+};
+use ark_ff::PrimeField;
+use ark_poly::EvaluationDomain;
+use ark_serialize::CanonicalSerialize;
+use blake2::{Blake2b512, Digest};
+use commitment_dlog::commitment::CommitmentCurve;
+use o1_utils::FieldHelpers;
+use oracle::FqSponge;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::io::{self, Write};
+
+/// An entry of an [`IndexAuditLog`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// the path of the value, as dot-separated names and positions
+    pub path: String,
+    /// the value
+    pub value: Value,
+}
+
+/// An entry on which two [`IndexAuditLog`]s disagree
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuditDifference<'a> {
+    /// the path of the entry
+    pub path: &'a str,
+    /// the value of the entry in the first log, if it has the entry
+    pub left: Option<&'a Value>,
+    /// the value of the entry in the second log, if it has the entry
+    pub right: Option<&'a Value>,
+}
+
+/// The audit log of the creation of an index (see the [module documentation](self))
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexAuditLog {
+    /// the entries of the log, in order
+    pub entries: Vec<AuditEntry>,
+}
+
+impl IndexAuditLog {
+    /// Returns the log of the values of `index`, given the lookup configuration of its circuit
+    /// if it uses lookups (see [`VerifierIndexJson::export`]).
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the lookup configuration is missing or not expected.
+    pub fn verifier_index<G, EFqSponge>(
+        index: &VerifierIndex<G>,
+        lookup_configuration: Option<&LookupConfiguration<G::ScalarField>>,
+    ) -> Result<Self, VerifierIndexError>
+    where
+        G: KimchiCurve,
+        G::BaseField: PrimeField,
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    {
+        let export = VerifierIndexJson::export::<G, EFqSponge>(index, lookup_configuration)?;
+        let export = serde_json::to_value(export).expect("the export serializes to JSON");
+
+        let mut log = Self::default();
+        log.flatten("verifier_index".into(), export);
+        Ok(log)
+    }
+
+    /// Appends an entry to the log.
+    pub fn push(&mut self, path: impl Into<String>, value: impl Into<Value>) {
+        self.entries.push(AuditEntry {
+            path: path.into(),
+            value: value.into(),
+        });
+    }
+
+    /// Appends the leaves of `value` to the log, below `path`.
+    fn flatten(&mut self, path: String, value: Value) {
+        match value {
+            Value::Object(fields) => {
+                for (name, value) in fields {
+                    self.flatten(format!("{path}.{name}"), value);
+                }
+            }
+            Value::Array(values) => {
+                if values.is_empty() {
+                    self.push(path, Value::Array(values));
+                } else {
+                    for (i, value) in values.into_iter().enumerate() {
+                        self.flatten(format!("{path}.{i}"), value);
+                    }
+                }
+            }
+            leaf => self.push(path, leaf),
+        }
+    }
+
+    /// Writes the log as JSON Lines, an entry `{"path": ..., "value": ...}` per line.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `writer` fails.
+    pub fn write_jsonl<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        for entry in &self.entries {
+            serde_json::to_writer(&mut writer, entry)?;
+            writer.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    /// Returns the entries that `self` and `other` disagree on:
+    /// the entries of `self` with another value or absent from `other`, in the order of `self`,
+    /// then the entries of `other` absent from `self`.
+    pub fn diff<'a>(&'a self, other: &'a Self) -> Vec<AuditDifference<'a>> {
+        let find = |log: &'a Self, path: &str| {
+            log.entries
+                .iter()
+                .find(|entry| entry.path == path)
+                .map(|entry| &entry.value)
+        };
+
+        let mut differences: Vec<_> = self
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                let right = find(other, &entry.path);
+                (right != Some(&entry.value)).then_some(AuditDifference {
+                    path: &entry.path,
+                    left: Some(&entry.value),
+                    right,
+                })
+            })
+            .collect();
+        differences.extend(other.entries.iter().filter_map(|entry| {
+            find(self, &entry.path)
+                .is_none()
+                .then_some(AuditDifference {
+                    path: &entry.path,
+                    left: None,
+                    right: Some(&entry.value),
+                })
+        }));
+        differences
+    }
+}
+
+/// Returns the Blake2b-512 digest of `bytes`, in hex.
+fn digest(bytes: impl AsRef<[u8]>) -> String {
+    hex::encode(Blake2b512::digest(bytes))
+}
+
+impl<G: KimchiCurve> ProverIndex<G>
+where
+    G::BaseField: PrimeField,
+{
+    /// Returns the audit log of the creation of the index (see [`IndexAuditLog`]).
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the verifier index can not be exported.
+    pub fn audit_log<EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>>(
+        &self,
+    ) -> Result<IndexAuditLog, VerifierIndexError> {
+        let mut log = IndexAuditLog::default();
+
+        let features: &[&str] = &[
+            #[cfg(feature = "chacha")]
+            "chacha",
+            #[cfg(feature = "range_check")]
+            "range_check",
+            #[cfg(feature = "foreign_field")]
+            "foreign_field",
+            #[cfg(feature = "keccak")]
+            "keccak",
+            #[cfg(feature = "cairo")]
+            "cairo",
+        ];
+        log.push("features", json!(features));
+
+        // the points of the SRS, in their canonical encoding
+        let mut generators = vec![];
+        for g in &self.srs.g {
+            g.serialize(&mut generators)
+                .expect("the points of the SRS serialize");
+        }
+        let h = self
+            .srs
+            .h
+            .to_coordinates()
+            .map(|(x, y)| json!({ "x": x.to_hex(), "y": y.to_hex() }));
+        log.push("srs.size", self.srs.g.len());
+        log.push("srs.h", json!(h));
+        log.push("srs.digest", digest(generators));
+
+        // the gates are fingerprinted as their debug representations
+        let domain = &self.cs.domain;
+        for (name, size) in [
+            ("d1", domain.d1.size()),
+            ("d2", domain.d2.size()),
+            ("d4", domain.d4.size()),
+            ("d8", domain.d8.size()),
+        ] {
+            log.push(format!("cs.domain.{name}"), size);
+        }
+        log.push("cs.gates.count", self.cs.gates.len());
+        for (row, gate) in self.cs.gates.iter().enumerate() {
+            log.push(format!("cs.gates.{row}"), digest(format!("{gate:?}")));
+        }
+
+        let lookup_configuration = self
+            .cs
+            .lookup_constraint_system
+            .as_ref()
+            .map(|lcs| &lcs.configuration);
+        let verifier_index = IndexAuditLog::verifier_index::<G, EFqSponge>(
+            &self.verifier_index(),
+            lookup_configuration,
+        )?;
+        log.entries.extend(verifier_index.entries);
+        Ok(log)
+    }
+}
@@ -0,0 +1,154 @@
+//! This module implements the [`KeyStore`], a cache of indexes on disk,
+//! for applications that would otherwise rebuild the same indexes over and over.
+//!
+//! The prover and verifier indexes of a circuit are stored together in a single file,
+//! named after the digest of the circuit (see [`Builder::digest`])
+//! and the digest of the SRS it is compiled against.
+//! [`KeyStore::prover_index`] returns the stored indexes when they exist,
+//! and otherwise creates them and stores them for the next time.
+//!
+//! A file that cannot be read back, for example when it was written by a build of kimchi
+//! with other gates enabled, is treated as missing, and overwritten.
+
+use crate::{
+    circuits::constraints::Builder, curve::KimchiCurve, error::KeyStoreError,
+    prover_index::ProverIndex, verifier_index::VerifierIndex,
+};
+use blake2::{Blake2b512, Digest};
+use commitment_dlog::srs::SRS;
+use std::{
+    fs::{self, File},
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// The extension of the files storing indexes.
+const EXTENSION: &str = "index";
+
+/// A directory storing the prover and verifier indexes of circuits.
+#[derive(Debug, Clone)]
+pub struct KeyStore {
+    dir: PathBuf,
+}
+
+/// Returns a digest of the SRS, including its blinding generator.
+fn srs_digest<G: KimchiCurve>(srs: &SRS<G>) -> [u8; 32] {
+    let mut h = Blake2b512::new();
+    h.update(rmp_serde::to_vec(srs).expect("the SRS is serializable"));
+    let mut digest = [0; 32];
+    digest.copy_from_slice(&h.finalize()[..32]);
+    digest
+}
+
+impl KeyStore {
+    /// Opens the key store in the directory `dir`, creating the directory if needed.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the directory cannot be created.
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self, KeyStoreError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).map_err(|e| KeyStoreError::Io(e.to_string()))?;
+        Ok(Self { dir })
+    }
+
+    /// Returns the path of the file storing the indexes of `circuit` compiled against `srs`.
+    pub fn path<G: KimchiCurve>(&self, circuit: &Builder<G::ScalarField>, srs: &SRS<G>) -> PathBuf {
+        // the gates enabled change the layout of the indexes
+        let features = [
+            cfg!(feature = "chacha"),
+            cfg!(feature = "range_check"),
+            cfg!(feature = "foreign_field"),
+            cfg!(feature = "keccak"),
+        ];
+        let features: String = features
+            .iter()
+            .map(|f| if *f { '1' } else { '0' })
+            .collect();
+
+        let name = format!(
+            "{}-{}-{}",
+            hex::encode(circuit.digest()),
+            hex::encode(srs_digest(srs)),
+            features
+        );
+        self.dir.join(name).with_extension(EXTENSION)
+    }
+
+    /// Returns the prover index of `circuit` compiled against `srs`,
+    /// with its verifier index already computed (see [`ProverIndex::verifier_index`]).
+    /// The indexes are read from the key store if they are stored there,
+    /// and are otherwise created and stored.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the constraint system cannot be built,
+    /// or if the indexes cannot be stored.
+    pub fn prover_index<G: KimchiCurve>(
+        &self,
+        circuit: Builder<G::ScalarField>,
+        endo_q: G::ScalarField,
+        srs: Arc<SRS<G>>,
+    ) -> Result<ProverIndex<G>, KeyStoreError> {
+        let path = self.path(&circuit, &srs);
+        if let Some(index) = Self::load(&path, &srs) {
+            return Ok(index);
+        }
+
+        let cs = circuit.build().map_err(KeyStoreError::Setup)?;
+        let mut index = ProverIndex::create(cs, endo_q, srs);
+        index.verifier_index = Some(index.verifier_index());
+        Self::store(&path, &index)?;
+        Ok(index)
+    }
+
+    /// Returns `true` if the indexes of `circuit` compiled against `srs` are stored.
+    pub fn contains<G: KimchiCurve>(
+        &self,
+        circuit: &Builder<G::ScalarField>,
+        srs: &SRS<G>,
+    ) -> bool {
+        self.path(circuit, srs).is_file()
+    }
+
+    /// Reads the indexes stored at `path`, and restores the fields left out of their serialization.
+    fn load<G: KimchiCurve>(path: &Path, srs: &Arc<SRS<G>>) -> Option<ProverIndex<G>> {
+        let reader = BufReader::new(File::open(path).ok()?);
+        let (mut index, mut verifier_index): (ProverIndex<G>, VerifierIndex<G>) =
+            rmp_serde::from_read(reader).ok()?;
+
+        let (linearization, powers_of_alpha) = ProverIndex::<G>::linearization(&index.cs);
+        verifier_index.srs.set(Arc::clone(srs)).ok()?;
+        verifier_index.endo = index.cs.endo;
+        verifier_index.linearization = linearization.clone();
+        verifier_index.powers_of_alpha = powers_of_alpha.clone();
+
+        index.srs = Arc::clone(srs);
+        index.linearization = linearization;
+        index.powers_of_alpha = powers_of_alpha;
+        index.verifier_index = Some(verifier_index);
+        Some(index)
+    }
+
+    /// Writes the indexes to `path`, through a temporary file
+    /// so that a concurrent reader never sees a partially written file.
+    fn store<G: KimchiCurve>(path: &Path, index: &ProverIndex<G>) -> Result<(), KeyStoreError> {
+        let verifier_index = index
+            .verifier_index
+            .as_ref()
+            .expect("the verifier index is computed before storing");
+
+        let tmp = path.with_extension(format!("{EXTENSION}.{}.tmp", std::process::id()));
+        let file = File::create(&tmp).map_err(|e| KeyStoreError::Io(e.to_string()))?;
+        let mut writer = BufWriter::new(file);
+        rmp_serde::encode::write(&mut writer, &(index, verifier_index))
+            .map_err(|e| KeyStoreError::Serialization(e.to_string()))?;
+        writer
+            .into_inner()
+            .map_err(|e| KeyStoreError::Io(e.to_string()))?
+            .sync_all()
+            .map_err(|e| KeyStoreError::Io(e.to_string()))?;
+        fs::rename(&tmp, path).map_err(|e| KeyStoreError::Io(e.to_string()))
+    }
+}
@@ -3,6 +3,7 @@
 #[macro_use]
 extern crate num_derive;
 
+#[cfg(feature = "cairo")]
 pub use cairo;
 pub use commitment_dlog;
 pub use groupmap;
@@ -13,17 +14,30 @@ pub use oracle;
 pub mod alphas;
 pub mod bench;
 pub mod circuits;
+pub mod codec;
+pub mod column_opening;
 pub mod curve;
 pub mod error;
+#[cfg(feature = "index_audit")]
+pub mod index_audit;
+pub mod keystore;
 pub mod linearization;
+pub mod migration;
 pub mod oracles;
 pub mod plonk_sponge;
 pub mod proof;
+pub mod proof_link;
 pub mod prover;
 pub mod prover_index;
+pub mod prover_stream;
+pub mod prover_task;
+pub mod prover_witness;
 pub mod snarky;
+pub mod transcript;
 pub mod verifier;
 pub mod verifier_index;
+pub mod verifier_index_json;
+pub mod verifier_math;
 
 #[cfg(test)]
 mod tests;
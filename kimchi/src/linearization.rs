@@ -4,32 +4,45 @@ use crate::alphas::Alphas;
 use crate::circuits::argument::{Argument, ArgumentType};
 use crate::circuits::lookup;
 use crate::circuits::lookup::constraints::LookupConfiguration;
+#[cfg(feature = "chacha")]
 use crate::circuits::polynomials::chacha::{ChaCha0, ChaCha1, ChaCha2, ChaChaFinal};
 use crate::circuits::polynomials::complete_add::CompleteAdd;
+use crate::circuits::polynomials::copy_cells::CopyCells;
 use crate::circuits::polynomials::endomul_scalar::EndomulScalar;
 use crate::circuits::polynomials::endosclmul::EndosclMul;
+#[cfg(feature = "foreign_field")]
 use crate::circuits::polynomials::foreign_field_add::circuitgates::ForeignFieldAdd;
+#[cfg(feature = "keccak")]
+use crate::circuits::polynomials::keccak;
 use crate::circuits::polynomials::permutation;
 use crate::circuits::polynomials::poseidon::Poseidon;
+#[cfg(feature = "range_check")]
 use crate::circuits::polynomials::range_check;
 use crate::circuits::polynomials::varbasemul::VarbaseMul;
 use crate::circuits::{
-    expr::{Column, ConstantExpr, Expr, Linearization, PolishToken},
+    constraints::ConstraintSystem,
+    expr::{CacheId, Column, ConstantExpr, Expr, Linearization, Op2, PolishToken, Variable},
     gate::GateType,
     wires::COLUMNS,
 };
-use ark_ff::{FftField, PrimeField, SquareRootField};
+use ark_ff::{BigInteger, FftField, One, PrimeField, SquareRootField, Zero};
+use itertools::{EitherOrBoth, Itertools};
+use o1_utils::FieldHelpers;
+use std::collections::{BTreeMap, HashMap};
 
 /// Get the expresion of constraints.
 ///
 /// # Panics
 ///
-/// Will panic if `generic_gate` is not associate with `alpha^0`.
+/// Will panic if `generic_gate` is not associate with `alpha^0`,
+/// or if an optional gate is used without its feature being enabled.
 pub fn constraints_expr<F: PrimeField + SquareRootField>(
     chacha: bool,
     range_check: bool,
     lookup_constraint_system: Option<&LookupConfiguration<F>>,
     foreign_field_add: bool,
+    copy_cells: bool,
+    keccak: bool,
 ) -> (Expr<ConstantExpr<F>>, Alphas<F>) {
     // register powers of alpha so that we don't reuse them across mutually inclusive constraints
     let mut powers_of_alpha = Alphas::<F>::default();
@@ -47,20 +60,46 @@ pub fn constraints_expr<F: PrimeField + SquareRootField>(
     expr += EndosclMul::combined_constraints(&powers_of_alpha);
     expr += EndomulScalar::combined_constraints(&powers_of_alpha);
 
+    #[cfg(feature = "chacha")]
     if chacha {
         expr += ChaCha0::combined_constraints(&powers_of_alpha);
         expr += ChaCha1::combined_constraints(&powers_of_alpha);
         expr += ChaCha2::combined_constraints(&powers_of_alpha);
         expr += ChaChaFinal::combined_constraints(&powers_of_alpha);
     }
+    #[cfg(not(feature = "chacha"))]
+    assert!(!chacha, "the chacha gates require the `chacha` feature");
 
+    #[cfg(feature = "range_check")]
     if range_check {
         expr += range_check::gadget::combined_constraints(&powers_of_alpha);
     }
+    #[cfg(not(feature = "range_check"))]
+    assert!(
+        !range_check,
+        "the range check gates require the `range_check` feature"
+    );
 
+    #[cfg(feature = "foreign_field")]
     if foreign_field_add {
         expr += ForeignFieldAdd::combined_constraints(&powers_of_alpha);
     }
+    #[cfg(not(feature = "foreign_field"))]
+    assert!(
+        !foreign_field_add,
+        "the foreign field addition gate requires the `foreign_field` feature"
+    );
+
+    if copy_cells {
+        expr += CopyCells::combined_constraints(&powers_of_alpha);
+    }
+
+    #[cfg(feature = "keccak")]
+    if keccak {
+        expr += keccak::gadget::combined_constraints(&powers_of_alpha);
+    }
+    #[cfg(not(feature = "keccak"))]
+    assert!(!keccak, "the keccak gates require the `keccak` feature");
 
     // permutation
     powers_of_alpha.register(ArgumentType::Permutation, permutation::CONSTRAINTS);
@@ -143,6 +182,8 @@ pub fn expr_linearization<F: PrimeField + SquareRootField>(
     range_check: bool,
     lookup_constraint_system: Option<&LookupConfiguration<F>>,
     foreign_field_addition: bool,
+    copy_cells: bool,
+    keccak: bool,
 ) -> (Linearization<Vec<PolishToken<F>>>, Alphas<F>) {
     let evaluated_cols = linearization_columns::<F>(lookup_constraint_system);
 
@@ -151,6 +192,8 @@ pub fn expr_linearization<F: PrimeField + SquareRootField>(
         range_check,
         lookup_constraint_system,
         foreign_field_addition,
+        copy_cells,
+        keccak,
     );
 
     let linearization = expr
@@ -160,3 +203,286 @@ pub fn expr_linearization<F: PrimeField + SquareRootField>(
 
     (linearization, powers_of_alpha)
 }
+
+/// A variable of a fully-expanded linearization: a cell, or a symbolic constant
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Atom {
+    Alpha,
+    Beta,
+    Gamma,
+    JointCombiner,
+    EndoCoefficient,
+    Mds { row: usize, col: usize },
+    ForeignFieldModulus(usize),
+    VanishesOnLast4Rows,
+    UnnormalizedLagrangeBasis(i32),
+    Cell(Variable),
+}
+
+impl Atom {
+    fn text(&self) -> String {
+        use Atom::*;
+        match self {
+            Alpha => "alpha".to_string(),
+            Beta => "beta".to_string(),
+            Gamma => "gamma".to_string(),
+            JointCombiner => "joint_combiner".to_string(),
+            EndoCoefficient => "endo_coefficient".to_string(),
+            Mds { row, col } => format!("mds({row}, {col})"),
+            ForeignFieldModulus(i) => format!("foreign_field_modulus({i})"),
+            VanishesOnLast4Rows => "vanishes_on_last_4_rows".to_string(),
+            UnnormalizedLagrangeBasis(i) => format!("unnormalized_lagrange_basis({i})"),
+            Cell(v) => v.text(),
+        }
+    }
+}
+
+/// A product of atoms, as their exponents
+type Monomial = BTreeMap<Atom, u64>;
+
+/// A polynomial in the atoms, as the non-zero coefficients of its monomials
+#[derive(Clone)]
+struct Expanded<F>(BTreeMap<Monomial, F>);
+
+impl<F: PrimeField> Expanded<F> {
+    fn constant(c: F) -> Self {
+        let mut terms = BTreeMap::new();
+        if !c.is_zero() {
+            terms.insert(Monomial::new(), c);
+        }
+        Self(terms)
+    }
+
+    fn atom(atom: Atom) -> Self {
+        Self(BTreeMap::from([(BTreeMap::from([(atom, 1)]), F::one())]))
+    }
+
+    /// Adds `c` times `monomial`.
+    fn add_term(&mut self, monomial: Monomial, c: F) {
+        let sum = self.0.get(&monomial).copied().unwrap_or_else(F::zero) + c;
+        if sum.is_zero() {
+            self.0.remove(&monomial);
+        } else {
+            self.0.insert(monomial, sum);
+        }
+    }
+
+    /// Returns `self + sign * other`.
+    fn add(mut self, other: &Self, sign: F) -> Self {
+        for (monomial, c) in &other.0 {
+            self.add_term(monomial.clone(), sign * c);
+        }
+        self
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        let mut res = Self(BTreeMap::new());
+        for (m1, c1) in &self.0 {
+            for (m2, c2) in &other.0 {
+                let mut monomial = m1.clone();
+                for (atom, e) in m2 {
+                    *monomial.entry(*atom).or_insert(0) += e;
+                }
+                res.add_term(monomial, *c1 * c2);
+            }
+        }
+        res
+    }
+
+    fn pow(&self, mut e: u64) -> Self {
+        let mut res = Self::constant(F::one());
+        let mut base = self.clone();
+        while e > 0 {
+            if e & 1 == 1 {
+                res = res.mul(&base);
+            }
+            e >>= 1;
+            if e > 0 {
+                base = base.mul(&base);
+            }
+        }
+        res
+    }
+
+    fn from_constant(c: &ConstantExpr<F>) -> Self {
+        use ConstantExpr::*;
+        match c {
+            Alpha => Self::atom(Atom::Alpha),
+            Beta => Self::atom(Atom::Beta),
+            Gamma => Self::atom(Atom::Gamma),
+            JointCombiner => Self::atom(Atom::JointCombiner),
+            EndoCoefficient => Self::atom(Atom::EndoCoefficient),
+            Mds { row, col } => Self::atom(Atom::Mds {
+                row: *row,
+                col: *col,
+            }),
+            ForeignFieldModulus(i) => Self::atom(Atom::ForeignFieldModulus(*i)),
+            Literal(x) => Self::constant(*x),
+            Pow(x, e) => Self::from_constant(x).pow(*e),
+            Add(x, y) => Self::from_constant(x).add(&Self::from_constant(y), F::one()),
+            Mul(x, y) => Self::from_constant(x).mul(&Self::from_constant(y)),
+            Sub(x, y) => Self::from_constant(x).add(&Self::from_constant(y), -F::one()),
+        }
+    }
+
+    fn from_expr(e: &Expr<ConstantExpr<F>>, cache: &mut HashMap<CacheId, Self>) -> Self {
+        match e {
+            Expr::Constant(c) => Self::from_constant(c),
+            Expr::Cell(v) => Self::atom(Atom::Cell(*v)),
+            Expr::Double(x) => {
+                let x = Self::from_expr(x, cache);
+                x.clone().add(&x, F::one())
+            }
+            Expr::Square(x) => Self::from_expr(x, cache).pow(2),
+            Expr::BinOp(op, x, y) => {
+                let (x, y) = (Self::from_expr(x, cache), Self::from_expr(y, cache));
+                match op {
+                    Op2::Add => x.add(&y, F::one()),
+                    Op2::Sub => x.add(&y, -F::one()),
+                    Op2::Mul => x.mul(&y),
+                }
+            }
+            Expr::VanishesOnLast4Rows => Self::atom(Atom::VanishesOnLast4Rows),
+            Expr::UnnormalizedLagrangeBasis(i) => Self::atom(Atom::UnnormalizedLagrangeBasis(*i)),
+            Expr::Pow(x, e) => Self::from_expr(x, cache).pow(*e),
+            Expr::Cache(id, x) => {
+                if let Some(expanded) = cache.get(id) {
+                    return expanded.clone();
+                }
+                let expanded = Self::from_expr(x, cache);
+                cache.insert(*id, expanded.clone());
+                expanded
+            }
+        }
+    }
+
+    /// Writes a term per line, in the order of the monomials.
+    fn write(&self, res: &mut String) {
+        for (monomial, c) in &self.0 {
+            let factors: Vec<_> = monomial
+                .iter()
+                .map(|(atom, e)| match e {
+                    1 => atom.text(),
+                    e => format!("{}^{e}", atom.text()),
+                })
+                .collect();
+            res.push_str("  ");
+            res.push_str(&coefficient_text(*c));
+            for factor in factors {
+                res.push_str(" * ");
+                res.push_str(&factor);
+            }
+            res.push('\n');
+        }
+    }
+}
+
+/// Writes a coefficient as a (signed) integer if it is small, in hexadecimal otherwise.
+fn coefficient_text<F: PrimeField>(c: F) -> String {
+    let small = |x: F| {
+        let repr = x.into_repr();
+        (repr.num_bits() <= 64).then(|| repr.as_ref()[0])
+    };
+    match (small(c), small(-c)) {
+        (Some(n), _) => n.to_string(),
+        (None, Some(n)) => format!("-{n}"),
+        (None, None) => format!("0x{}", c.to_hex()),
+    }
+}
+
+/// Returns the snapshot of the linearization of the constraints of [`constraints_expr`]:
+/// a stable textual form of the fully-expanded linearization,
+/// with the terms of the constant term then those of each index column (in the order of the columns),
+/// a term per line, each as its coefficient and its product of cells and symbolic constants.
+///
+/// Two snapshots are equal if and only if the linearizations are equal as polynomials,
+/// whatever the way their expressions are written,
+/// so that a snapshot committed along with a circuit catches the accidental changes of its constraints
+/// (see [`Builder::linearization_snapshot`](crate::circuits::constraints::Builder::linearization_snapshot)).
+///
+/// # Panics
+///
+/// Will panic if the `linearization` process fails.
+pub fn linearization_snapshot<F: PrimeField + SquareRootField>(
+    chacha: bool,
+    range_check: bool,
+    lookup_constraint_system: Option<&LookupConfiguration<F>>,
+    foreign_field_addition: bool,
+    copy_cells: bool,
+    keccak: bool,
+) -> String {
+    let evaluated_cols = linearization_columns::<F>(lookup_constraint_system);
+
+    let (expr, _) = constraints_expr(
+        chacha,
+        range_check,
+        lookup_constraint_system,
+        foreign_field_addition,
+        copy_cells,
+        keccak,
+    );
+    let mut linearization = expr.linearize(evaluated_cols).unwrap();
+    linearization.index_terms.sort_by_key(|(col, _)| *col);
+
+    let mut cache = HashMap::new();
+    let mut res = "constant_term\n".to_string();
+    Expanded::from_expr(&linearization.constant_term, &mut cache).write(&mut res);
+    for (col, e) in &linearization.index_terms {
+        res.push_str(&format!("index_term {}\n", col.text()));
+        Expanded::from_expr(e, &mut cache).write(&mut res);
+    }
+    res
+}
+
+/// Compares the snapshot of a linearization with the `expected` one, line by line
+/// (so that the line endings do not matter), and describes their first difference if any.
+pub(crate) fn compare_snapshots(expected: &str, snapshot: &str) -> Result<(), String> {
+    for (i, lines) in expected.lines().zip_longest(snapshot.lines()).enumerate() {
+        let (expected, found) = match lines {
+            EitherOrBoth::Both(e, f) => (Some(e), Some(f)),
+            EitherOrBoth::Left(e) => (Some(e), None),
+            EitherOrBoth::Right(f) => (None, Some(f)),
+        };
+        if expected != found {
+            return Err(format!(
+                "line {} is `{}` instead of `{}`",
+                i + 1,
+                found.unwrap_or("<end>"),
+                expected.unwrap_or("<end>")
+            ));
+        }
+    }
+    Ok(())
+}
+
+impl<F: PrimeField + SquareRootField> ConstraintSystem<F> {
+    /// Returns the snapshot of the linearization of the circuit (see [`linearization_snapshot`]).
+    pub fn linearization_snapshot(&self) -> String {
+        #[cfg(feature = "chacha")]
+        let chacha = self.chacha8.is_some();
+        #[cfg(not(feature = "chacha"))]
+        let chacha = false;
+        #[cfg(feature = "range_check")]
+        let range_check = self.range_check_selector_polys.is_some();
+        #[cfg(not(feature = "range_check"))]
+        let range_check = false;
+        #[cfg(feature = "foreign_field")]
+        let foreign_field_add = self.foreign_field_add_selector_poly.is_some();
+        #[cfg(not(feature = "foreign_field"))]
+        let foreign_field_add = false;
+        #[cfg(feature = "keccak")]
+        let keccak = self.keccak_selector_polys.is_some();
+        #[cfg(not(feature = "keccak"))]
+        let keccak = false;
+        linearization_snapshot(
+            chacha,
+            range_check,
+            self.lookup_constraint_system
+                .as_ref()
+                .map(|lcs| &lcs.configuration),
+            foreign_field_add,
+            self.copy_cells_selector_poly.is_some(),
+            keccak,
+        )
+    }
+}
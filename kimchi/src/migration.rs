@@ -0,0 +1,496 @@
+//! This module implements the migration of indexes serialized with older layouts.
+//!
+//! The prover and verifier indexes are serialized with MessagePack, as arrays of their fields,
+//! so that adding a field to [`VerifierIndex`] or to [`ConstraintSystem`] changes their layout:
+//! the indexes serialized before can not be deserialized as the current types anymore.
+//! Each older layout is thus kept here as its own type (e.g. [`VerifierIndexV0`]),
+//! which deserializes the indexes of that layout, and migrates them to the current types.
+//!
+//! A migration never changes the digest of a verifier index,
+//! so that the verifier keys distributed before, and the proofs made against them, stay valid:
+//! the digest of the migrated index is checked against the digest of the index under its older layout,
+//! and returned along with the migrated index (see [`Migration`]).
+//!
+//! The layouts are:
+//!
+//! - [`LayoutVersion::V0`]: the layout of the first release,
+//!   before the witness columns declared zero, the lookups with a table ID per lookup,
+//!   the constants pool and the application constants,
+//! - [`LayoutVersion::V1`]: the current layout.
+
+#[cfg(feature = "foreign_field")]
+use crate::circuits::polynomials::foreign_field_add;
+#[cfg(feature = "range_check")]
+use crate::circuits::polynomials::range_check;
+use crate::{
+    circuits::{
+        constraints::{Builder, ConstraintSystem},
+        gate::{CircuitGate, GateType},
+        lookup::{constraints::LookupConfiguration, index::LookupSelectors, lookups::LookupsUsed},
+        wires::{Wire, PERMUTS},
+    },
+    curve::KimchiCurve,
+    error::VerifierIndexError,
+    prover_index::ProverIndex,
+    verifier_index::{LookupVerifierIndex, VerifierIndex, VerifierIndexBuilder},
+};
+use ark_ff::PrimeField;
+use ark_poly::{EvaluationDomain, Radix2EvaluationDomain as D};
+use commitment_dlog::{commitment::PolyComm, srs::SRS};
+use num_bigint::BigUint;
+use oracle::FqSponge;
+use serde::{
+    de::{DeserializeOwned, IgnoredAny},
+    Deserialize, Serialize,
+};
+use serde_with::serde_as;
+use std::sync::Arc;
+
+/// The layouts of serialized indexes (see the [module documentation](self)).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LayoutVersion {
+    /// The layout of the first release
+    V0,
+    /// The current layout
+    V1,
+}
+
+impl LayoutVersion {
+    /// The layout of the indexes serialized by this version of the crate
+    pub const CURRENT: Self = Self::V1;
+}
+
+/// An index migrated from an older layout.
+#[derive(Debug)]
+pub struct Migration<T, D> {
+    /// The layout the index was serialized with
+    pub from: LayoutVersion,
+    /// The migrated index
+    pub index: T,
+    /// The digest of the verifier index, which is the same under both layouts
+    pub digest: D,
+}
+
+/// Deserializes an index from its MessagePack encoding, which must not have trailing data.
+fn from_bytes<T: DeserializeOwned>(
+    version: LayoutVersion,
+    bytes: &[u8],
+) -> Result<T, VerifierIndexError> {
+    let mut reader = bytes;
+    let index = T::deserialize(&mut rmp_serde::Deserializer::new(&mut reader))
+        .map_err(|e| VerifierIndexError::IncorrectLayout(version, e.to_string()))?;
+    if !reader.is_empty() {
+        return Err(VerifierIndexError::IncorrectLayout(
+            version,
+            "trailing data".to_string(),
+        ));
+    }
+    Ok(index)
+}
+
+//
+// Verifier index
+//
+
+/// The lookup selectors of the layout [`LayoutVersion::V0`]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LookupSelectorsV0<T> {
+    pub chacha: Option<T>,
+    pub chacha_final: Option<T>,
+    pub lookup_gate: Option<T>,
+    pub range_check_gate: Option<T>,
+}
+
+/// The lookup part of a verifier index of the layout [`LayoutVersion::V0`]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "G: ark_serialize::CanonicalDeserialize + ark_serialize::CanonicalSerialize")]
+pub struct LookupVerifierIndexV0<G: KimchiCurve> {
+    pub lookup_used: LookupsUsed,
+    pub lookup_table: Vec<PolyComm<G>>,
+    pub lookup_selectors: LookupSelectorsV0<PolyComm<G>>,
+    pub table_ids: Option<PolyComm<G>>,
+    pub max_joint_size: u32,
+    pub runtime_tables_selector: Option<PolyComm<G>>,
+}
+
+/// A verifier index of the layout [`LayoutVersion::V0`].
+/// The optional gates are always part of this layout, whichever features are enabled.
+#[serde_as]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "G: ark_serialize::CanonicalDeserialize + ark_serialize::CanonicalSerialize")]
+pub struct VerifierIndexV0<G: KimchiCurve> {
+    #[serde_as(as = "o1_utils::serialization::SerdeAs")]
+    pub domain: D<G::ScalarField>,
+    pub max_poly_size: usize,
+    pub max_quot_size: usize,
+    pub public: usize,
+    pub prev_challenges: usize,
+    pub sigma_comm: Vec<PolyComm<G>>,
+    pub coefficients_comm: Vec<PolyComm<G>>,
+    pub generic_comm: PolyComm<G>,
+    pub psm_comm: PolyComm<G>,
+    pub complete_add_comm: PolyComm<G>,
+    pub mul_comm: PolyComm<G>,
+    pub emul_comm: PolyComm<G>,
+    pub endomul_scalar_comm: PolyComm<G>,
+    pub chacha_comm: Option<Vec<PolyComm<G>>>,
+    pub range_check_comm: Option<Vec<PolyComm<G>>>,
+    pub foreign_field_modulus: Option<BigUint>,
+    pub foreign_field_add_comm: Option<PolyComm<G>>,
+    #[serde_as(as = "[o1_utils::serialization::SerdeAs; PERMUTS]")]
+    pub shift: [G::ScalarField; PERMUTS],
+    pub lookup_index: Option<LookupVerifierIndexV0<G>>,
+}
+
+/// The gates of the selectors of the chacha commitments.
+#[cfg(feature = "chacha")]
+fn chacha_gates() -> Result<Vec<GateType>, VerifierIndexError> {
+    Ok(vec![
+        GateType::ChaCha0,
+        GateType::ChaCha1,
+        GateType::ChaCha2,
+        GateType::ChaChaFinal,
+    ])
+}
+
+#[cfg(not(feature = "chacha"))]
+fn chacha_gates() -> Result<Vec<GateType>, VerifierIndexError> {
+    Err(VerifierIndexError::GateDisabled("chacha"))
+}
+
+/// The gates of the selectors of the range check commitments.
+#[cfg(feature = "range_check")]
+fn range_check_gates() -> Result<Vec<GateType>, VerifierIndexError> {
+    Ok(range_check::gadget::circuit_gates().to_vec())
+}
+
+#[cfg(not(feature = "range_check"))]
+fn range_check_gates() -> Result<Vec<GateType>, VerifierIndexError> {
+    Err(VerifierIndexError::GateDisabled("range_check"))
+}
+
+/// The gates of the selectors of the foreign field addition commitments.
+#[cfg(feature = "foreign_field")]
+fn foreign_field_add_gates() -> Result<Vec<GateType>, VerifierIndexError> {
+    Ok(foreign_field_add::gadget::circuit_gates().to_vec())
+}
+
+#[cfg(not(feature = "foreign_field"))]
+fn foreign_field_add_gates() -> Result<Vec<GateType>, VerifierIndexError> {
+    Err(VerifierIndexError::GateDisabled("foreign_field"))
+}
+
+impl<G: KimchiCurve> VerifierIndexV0<G>
+where
+    G::BaseField: PrimeField,
+{
+    /// Deserializes a verifier index of this layout from its MessagePack encoding.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `bytes` do not encode a verifier index of this layout.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, VerifierIndexError> {
+        from_bytes(LayoutVersion::V0, bytes)
+    }
+
+    /// Computes the digest of the verifier index, as this layout did.
+    pub fn digest<EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>>(
+        &self,
+    ) -> G::BaseField {
+        let mut fq_sponge = EFqSponge::new(G::OtherCurve::sponge_params());
+
+        let always_present = [
+            &self.generic_comm,
+            &self.psm_comm,
+            &self.complete_add_comm,
+            &self.mul_comm,
+            &self.emul_comm,
+            &self.endomul_scalar_comm,
+        ];
+        let optional_gates = self
+            .chacha_comm
+            .iter()
+            .flatten()
+            .chain(self.range_check_comm.iter().flatten())
+            .chain(&self.foreign_field_add_comm);
+        for comm in self
+            .sigma_comm
+            .iter()
+            .chain(&self.coefficients_comm)
+            .chain(always_present)
+            .chain(optional_gates)
+        {
+            fq_sponge.absorb_g(&comm.unshifted);
+        }
+
+        if let Some(lookup_index) = &self.lookup_index {
+            let selectors = &lookup_index.lookup_selectors;
+            for comm in lookup_index
+                .lookup_table
+                .iter()
+                .chain(&lookup_index.table_ids)
+                .chain(&lookup_index.runtime_tables_selector)
+                .chain(&selectors.chacha)
+                .chain(&selectors.chacha_final)
+                .chain(&selectors.lookup_gate)
+                .chain(&selectors.range_check_gate)
+            {
+                fq_sponge.absorb_g(&comm.unshifted);
+            }
+        }
+        fq_sponge.digest_fq()
+    }
+
+    /// Migrates the verifier index to the current layout, against `srs`,
+    /// which must contain the Lagrange basis of its domain.
+    /// As the lookup configuration is not part of the index,
+    /// it must be given for circuits using lookups, as for [`VerifierIndexBuilder::lookup`].
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the index does not match `srs` or the lookup configuration,
+    /// if it uses gates whose feature is not enabled,
+    /// or if the migrated index does not have the same digest.
+    pub fn migrate<EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>>(
+        self,
+        srs: Arc<SRS<G>>,
+        lookup_configuration: Option<LookupConfiguration<G::ScalarField>>,
+    ) -> Result<Migration<VerifierIndex<G>, G::BaseField>, VerifierIndexError> {
+        if self.max_poly_size != srs.g.len() {
+            return Err(VerifierIndexError::IncorrectLayout(
+                LayoutVersion::V0,
+                "the SRS is not the one of the index".to_string(),
+            ));
+        }
+        let digest = self.digest::<EFqSponge>();
+
+        let mut builder = VerifierIndexBuilder::new(srs, self.domain.size())?
+            .public(self.public)
+            .prev_challenges(self.prev_challenges)
+            .sigma(self.sigma_comm)
+            .coefficients(self.coefficients_comm)
+            .selector(GateType::Generic, self.generic_comm)
+            .selector(GateType::Poseidon, self.psm_comm)
+            .selector(GateType::CompleteAdd, self.complete_add_comm)
+            .selector(GateType::VarBaseMul, self.mul_comm)
+            .selector(GateType::EndoMul, self.emul_comm)
+            .selector(GateType::EndoMulScalar, self.endomul_scalar_comm)
+            .expected_digest(digest);
+
+        let optional_gates = [
+            (
+                "chacha",
+                self.chacha_comm.map(|comms| (chacha_gates(), comms)),
+            ),
+            (
+                "range_check",
+                self.range_check_comm
+                    .map(|comms| (range_check_gates(), comms)),
+            ),
+            (
+                "foreign_field_add",
+                self.foreign_field_add_comm
+                    .map(|comm| (foreign_field_add_gates(), vec![comm])),
+            ),
+        ];
+        for (name, selectors) in optional_gates {
+            if let Some((gates, comms)) = selectors {
+                let gates = gates?;
+                if gates.len() != comms.len() {
+                    return Err(VerifierIndexError::IncorrectCommitmentCount(
+                        name,
+                        gates.len(),
+                        comms.len(),
+                    ));
+                }
+                for (typ, comm) in gates.into_iter().zip(comms) {
+                    builder = builder.selector(typ, comm);
+                }
+            }
+        }
+        if let Some(modulus) = self.foreign_field_modulus {
+            builder = builder.foreign_field_modulus(modulus);
+        }
+
+        match (self.lookup_index, lookup_configuration) {
+            (None, None) => (),
+            (Some(lookup_index), Some(configuration)) => {
+                builder = builder.lookup(configuration, lookup_index.migrate()?);
+            }
+            _ => return Err(VerifierIndexError::InconsistentLookup("configuration")),
+        }
+
+        let (index, digest) = builder.build::<EFqSponge>()?;
+        if index.shift != self.shift {
+            return Err(VerifierIndexError::IncorrectLayout(
+                LayoutVersion::V0,
+                "the shifts are not the ones of the domain".to_string(),
+            ));
+        }
+        Ok(Migration {
+            from: LayoutVersion::V0,
+            index,
+            digest,
+        })
+    }
+}
+
+impl<G: KimchiCurve> LookupVerifierIndexV0<G> {
+    /// Migrates the lookup part of a verifier index to the current layout.
+    fn migrate(self) -> Result<LookupVerifierIndex<G>, VerifierIndexError> {
+        let LookupSelectorsV0 {
+            chacha,
+            chacha_final,
+            lookup_gate,
+            range_check_gate,
+        } = self.lookup_selectors;
+        #[cfg(not(feature = "chacha"))]
+        if chacha.is_some() || chacha_final.is_some() {
+            return Err(VerifierIndexError::GateDisabled("chacha"));
+        }
+        #[cfg(not(feature = "range_check"))]
+        if range_check_gate.is_some() {
+            return Err(VerifierIndexError::GateDisabled("range_check"));
+        }
+
+        Ok(LookupVerifierIndex {
+            lookup_used: self.lookup_used,
+            lookup_table: self.lookup_table,
+            lookup_selectors: LookupSelectors {
+                #[cfg(feature = "chacha")]
+                chacha,
+                #[cfg(feature = "chacha")]
+                chacha_final,
+                lookup_gate,
+                #[cfg(feature = "range_check")]
+                range_check_gate,
+                multi_table_lookup_gate: None,
+            },
+            table_ids: self.table_ids,
+            max_joint_size: self.max_joint_size,
+            runtime_tables_selector: self.runtime_tables_selector,
+            variable_runtime_tables: vec![],
+        })
+    }
+}
+
+//
+// Prover index
+//
+
+/// The constraint system of a prover index of the layout [`LayoutVersion::V0`].
+/// Only the description of the circuit is kept, the polynomials being computed again by the migration.
+// the ignored fields are only there to be skipped when deserializing
+#[allow(dead_code)]
+#[derive(Deserialize)]
+#[serde(bound = "F: PrimeField")]
+pub struct ConstraintSystemV0<F: PrimeField> {
+    pub public: usize,
+    pub prev_challenges: usize,
+    domain: IgnoredAny,
+    pub gates: Vec<CircuitGate<F>>,
+    sigmam: IgnoredAny,
+    coefficients8: IgnoredAny,
+    genericm: IgnoredAny,
+    psm: IgnoredAny,
+    generic4: IgnoredAny,
+    sigmal1: IgnoredAny,
+    sigmal8: IgnoredAny,
+    sid: IgnoredAny,
+    ps8: IgnoredAny,
+    complete_addl4: IgnoredAny,
+    mull8: IgnoredAny,
+    emull: IgnoredAny,
+    chacha8: IgnoredAny,
+    endomul_scalar8: IgnoredAny,
+    range_check_selector_polys: IgnoredAny,
+    pub foreign_field_modulus: Option<BigUint>,
+    foreign_field_add_selector_poly: IgnoredAny,
+    shift: IgnoredAny,
+    endo: IgnoredAny,
+    lookup_constraint_system: Option<IgnoredAny>,
+}
+
+/// A prover index of the layout [`LayoutVersion::V0`].
+#[serde_as]
+#[derive(Deserialize)]
+#[serde(bound = "G: ark_serialize::CanonicalDeserialize + ark_serialize::CanonicalSerialize")]
+pub struct ProverIndexV0<G: KimchiCurve> {
+    pub cs: ConstraintSystemV0<G::ScalarField>,
+    pub max_poly_size: usize,
+    pub max_quot_size: usize,
+    #[serde_as(as = "Option<o1_utils::serialization::SerdeAs>")]
+    pub verifier_index_digest: Option<G::BaseField>,
+}
+
+impl<G: KimchiCurve> ProverIndexV0<G>
+where
+    G::BaseField: PrimeField,
+{
+    /// Deserializes a prover index of this layout from its MessagePack encoding.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `bytes` do not encode a prover index of this layout.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, VerifierIndexError> {
+        from_bytes(LayoutVersion::V0, bytes)
+    }
+
+    /// Returns whether the circuit uses lookups,
+    /// whose tables must then be given again to the builder of [`ProverIndexV0::constraint_system`].
+    pub fn uses_lookups(&self) -> bool {
+        self.cs.lookup_constraint_system.is_some()
+    }
+
+    /// Returns a builder of the constraint system of the index,
+    /// with its gates (without the zero gates padding the circuit to the domain), public inputs,
+    /// previous challenges and foreign field modulus.
+    /// The lookup tables are not part of the layout in a form they can be recovered from:
+    /// if the circuit uses lookups, its tables must be given to the builder again.
+    pub fn constraint_system(&self) -> Builder<G::ScalarField> {
+        let mut gates = self.cs.gates.clone();
+        while let Some(gate) = gates.last() {
+            let row = gates.len() - 1;
+            if gate.typ != GateType::Zero || !gate.coeffs.is_empty() || gate.wires != Wire::new(row)
+            {
+                break;
+            }
+            gates.pop();
+        }
+        ConstraintSystem::create(gates)
+            .public(self.cs.public)
+            .prev_challenges(self.cs.prev_challenges)
+            .foreign_field_modulus(&self.cs.foreign_field_modulus)
+    }
+
+    /// Migrates the prover index to the current layout,
+    /// from the constraint system `cs` built from [`ProverIndexV0::constraint_system`].
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `srs` is not the one of the index,
+    /// or if the digest of the migrated index is not the one cached in the index.
+    pub fn migrate<EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>>(
+        self,
+        cs: ConstraintSystem<G::ScalarField>,
+        endo_q: G::ScalarField,
+        srs: Arc<SRS<G>>,
+    ) -> Result<Migration<ProverIndex<G>, G::BaseField>, VerifierIndexError> {
+        if self.max_poly_size != srs.g.len() {
+            return Err(VerifierIndexError::IncorrectLayout(
+                LayoutVersion::V0,
+                "the SRS is not the one of the index".to_string(),
+            ));
+        }
+        let mut index = ProverIndex::create(cs, endo_q, srs);
+        let digest = index.compute_verifier_index_digest::<EFqSponge>();
+        match self.verifier_index_digest {
+            Some(expected) if expected != digest => Err(VerifierIndexError::DigestMismatch),
+            _ => Ok(Migration {
+                from: LayoutVersion::V0,
+                index,
+                digest,
+            }),
+        }
+    }
+}
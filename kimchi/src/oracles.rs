@@ -3,8 +3,11 @@
 //! we hope to be able to remove this code in the future.
 
 use crate::{alphas::Alphas, circuits::scalars::RandomOracles};
+use ark_ff::Field;
 use commitment_dlog::commitment::{CommitmentCurve, PolyComm};
 use oracle::FqSponge;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
 
 /// The result of running the oracle protocol
 pub struct OraclesResult<G, EFqSponge>
@@ -35,6 +38,55 @@ where
     pub combined_inner_product: G::ScalarField,
 }
 
+impl<G, EFqSponge> OraclesResult<G, EFqSponge>
+where
+    G: CommitmentCurve,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+{
+    /// Captures the scalars of the oracle protocol, see [OraclesTranscript]
+    pub fn transcript(&self) -> OraclesTranscript<G::ScalarField> {
+        OraclesTranscript {
+            digest: self.digest,
+            oracles: self.oracles.clone(),
+            public_evals: self.public_evals.clone(),
+            powers_of_eval_points_for_chunks: self.powers_of_eval_points_for_chunks,
+            zeta1: self.zeta1,
+            ft_eval0: self.ft_eval0,
+            combined_inner_product: self.combined_inner_product,
+        }
+    }
+}
+
+/// The scalars produced by the oracle protocol for a given proof (see [OraclesResult]).
+/// It can be captured with [crate::verifier::capture_oracles] and serialized,
+/// to pin the exact challenges a proof must produce,
+/// and later replayed with [crate::verifier::replay_oracles].
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "F: Field")]
+pub struct OraclesTranscript<F: Field> {
+    /// the last evaluation of the Fq-Sponge in this protocol
+    #[serde_as(as = "o1_utils::serialization::SerdeAs")]
+    pub digest: F,
+    /// the challenges produced in the protocol
+    pub oracles: RandomOracles<F>,
+    /// public polynomial evaluations
+    #[serde_as(as = "[Vec<o1_utils::serialization::SerdeAs>; 2]")]
+    pub public_evals: [Vec<F>; 2],
+    /// zeta^n and (zeta * omega)^n
+    #[serde_as(as = "[o1_utils::serialization::SerdeAs; 2]")]
+    pub powers_of_eval_points_for_chunks: [F; 2],
+    /// pre-computed zeta^n
+    #[serde_as(as = "o1_utils::serialization::SerdeAs")]
+    pub zeta1: F,
+    /// The evaluation f(zeta) - t(zeta) * Z_H(zeta)
+    #[serde_as(as = "o1_utils::serialization::SerdeAs")]
+    pub ft_eval0: F,
+    /// the combined inner product of the evaluations
+    #[serde_as(as = "o1_utils::serialization::SerdeAs")]
+    pub combined_inner_product: F,
+}
+
 #[cfg(feature = "ocaml_types")]
 pub mod caml {
     use ark_ff::PrimeField;
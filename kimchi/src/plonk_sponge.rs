@@ -1,13 +1,34 @@
-use ark_ff::{Field, PrimeField};
+use ark_ff::{Field, FpParameters, PrimeField, Zero};
+use blake2::{Blake2b512, Digest};
+use commitment_dlog::commitment::{CommitmentCurve, PolyComm};
+use num_bigint::BigUint;
 use oracle::sponge::{DefaultFrSponge, ScalarChallenge};
 use oracle::{
-    constants::PlonkSpongeConstantsKimchi as SC,
+    constants::SpongeConstants,
     poseidon::{ArithmeticSponge, ArithmeticSpongeParams, Sponge},
+    FqSponge,
 };
 
-use crate::proof::ProofEvaluations;
+/// Absorbs the `context` of a proof (e.g. a domain tag or a nonce) into the Fq-sponge, as the digest of its bytes,
+/// binding the proof to the application context it was created in.
+/// An empty context absorbs nothing, so that the proofs created without a context are unchanged.
+pub fn absorb_context<G, EFqSponge>(fq_sponge: &mut EFqSponge, context: &[u8])
+where
+    G: CommitmentCurve,
+    G::BaseField: PrimeField,
+    EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
+{
+    if !context.is_empty() {
+        let digest = G::BaseField::from_le_bytes_mod_order(&Blake2b512::digest(context));
+        fq_sponge.absorb_fq(&[digest]);
+    }
+}
 
 pub trait FrSponge<Fr: Field> {
+    /// The number of limbs of 64 bits of the challenges squeezed from the sponge
+    /// (see [`FqSponge::CHALLENGE_LENGTH_IN_LIMBS`]).
+    const CHALLENGE_LENGTH_IN_LIMBS: usize = oracle::sponge::CHALLENGE_LENGTH_IN_LIMBS;
+
     /// Creates a new Fr-Sponge.
     fn new(p: &'static ArithmeticSpongeParams<Fr>) -> Self;
 
@@ -17,18 +38,46 @@ pub trait FrSponge<Fr: Field> {
     /// Absorbs a slice of field elements into the sponge.
     fn absorb_multiple(&mut self, x: &[Fr]);
 
+    /// Absorbs the (possibly chunked) commitment into the sponge.
+    /// Each point is absorbed as its two coordinates, or as `(0, 0)` if it is the point at infinity,
+    /// and a coordinate that might not fit in the scalar field is absorbed as
+    /// its high bits followed by its low bit.
+    fn absorb_commitment<G>(&mut self, comm: &PolyComm<G>)
+    where
+        G: CommitmentCurve<ScalarField = Fr>,
+        G::BaseField: PrimeField;
+
     /// Creates a [`ScalarChallenge`] by squeezing the sponge.
     fn challenge(&mut self) -> ScalarChallenge<Fr>;
 
+    /// Creates `n` [`ScalarChallenge`] from the current state of the sponge,
+    /// the same as `n` successive calls to [`FrSponge::challenge`].
+    fn squeeze_n(&mut self, n: usize) -> Vec<ScalarChallenge<Fr>> {
+        (0..n).map(|_| self.challenge()).collect()
+    }
+
     /// Consumes the sponge and returns the current digest, by squeezing.
     fn digest(self) -> Fr;
+}
 
-    /// Absorbs the given evaluations into the sponge.
-    // TODO: IMO this function should be inlined in prover/verifier
-    fn absorb_evaluations<const N: usize>(&mut self, e: [&ProofEvaluations<Vec<Fr>>; N]);
+/// Converts a base field element into one scalar field element if the base field is smaller,
+/// and into its high bits and low bit otherwise.
+fn base_to_scalars<Fq: PrimeField, Fr: PrimeField>(x: Fq) -> Vec<Fr> {
+    let modulus_fq: BigUint = Fq::Params::MODULUS.into();
+    let modulus_fr: BigUint = Fr::Params::MODULUS.into();
+    let x: BigUint = x.into();
+
+    if modulus_fq <= modulus_fr {
+        vec![Fr::from(x)]
+    } else {
+        let low_bit = if x.bit(0) { Fr::one() } else { Fr::zero() };
+        vec![Fr::from(x >> 1), low_bit]
+    }
 }
 
-impl<Fr: PrimeField> FrSponge<Fr> for DefaultFrSponge<Fr, SC> {
+impl<Fr: PrimeField, SC: SpongeConstants> FrSponge<Fr> for DefaultFrSponge<Fr, SC> {
+    const CHALLENGE_LENGTH_IN_LIMBS: usize = SC::CHALLENGE_LENGTH_IN_LIMBS;
+
     fn new(params: &'static ArithmeticSpongeParams<Fr>) -> DefaultFrSponge<Fr, SC> {
         DefaultFrSponge {
             sponge: ArithmeticSponge::new(params),
@@ -46,61 +95,28 @@ impl<Fr: PrimeField> FrSponge<Fr> for DefaultFrSponge<Fr, SC> {
         self.sponge.absorb(x);
     }
 
+    fn absorb_commitment<G>(&mut self, comm: &PolyComm<G>)
+    where
+        G: CommitmentCurve<ScalarField = Fr>,
+        G::BaseField: PrimeField,
+    {
+        self.last_squeezed = vec![];
+
+        for g in comm.unshifted.iter().chain(&comm.shifted) {
+            let (x, y) = g
+                .to_coordinates()
+                .unwrap_or((G::BaseField::zero(), G::BaseField::zero()));
+            self.sponge.absorb(&base_to_scalars(x));
+            self.sponge.absorb(&base_to_scalars(y));
+        }
+    }
+
     fn challenge(&mut self) -> ScalarChallenge<Fr> {
         // TODO: why involve sponge_5_wires here?
-        ScalarChallenge(self.squeeze(oracle::sponge::CHALLENGE_LENGTH_IN_LIMBS))
+        ScalarChallenge(self.squeeze(SC::CHALLENGE_LENGTH_IN_LIMBS))
     }
 
     fn digest(mut self) -> Fr {
         self.sponge.squeeze()
     }
-
-    // We absorb all evaluations of the same polynomial at the same time
-    fn absorb_evaluations<const N: usize>(&mut self, e: [&ProofEvaluations<Vec<Fr>>; N]) {
-        self.last_squeezed = vec![];
-
-        let e = ProofEvaluations::transpose(e);
-
-        let mut points = vec![
-            &e.z,
-            &e.generic_selector,
-            &e.poseidon_selector,
-            &e.w[0],
-            &e.w[1],
-            &e.w[2],
-            &e.w[3],
-            &e.w[4],
-            &e.w[5],
-            &e.w[6],
-            &e.w[7],
-            &e.w[8],
-            &e.w[9],
-            &e.w[10],
-            &e.w[11],
-            &e.w[12],
-            &e.w[13],
-            &e.w[14],
-            &e.s[0],
-            &e.s[1],
-            &e.s[2],
-            &e.s[3],
-            &e.s[4],
-            &e.s[5],
-        ];
-
-        if let Some(l) = e.lookup.as_ref() {
-            points.push(&l.aggreg);
-            points.push(&l.table);
-            for s in &l.sorted {
-                points.push(s);
-            }
-            l.runtime.iter().for_each(|x| points.push(x));
-        }
-
-        for p in points {
-            for x in p {
-                self.sponge.absorb(x);
-            }
-        }
-    }
 }
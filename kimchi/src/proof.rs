@@ -5,36 +5,56 @@ use ark_ec::AffineCurve;
 use ark_ff::{FftField, One, Zero};
 use ark_poly::univariate::DensePolynomial;
 use commitment_dlog::{
-    commitment::{b_poly, b_poly_coefficients, PolyComm},
-    evaluation_proof::OpeningProof,
+    commitment::{b_poly, b_poly_coefficients, CommitmentCurve, PolyComm, MAX_CHUNKS},
+    evaluation_proof::MAX_ROUNDS,
+    scheme::PolynomialCommitment,
+    srs::SRS,
+};
+use o1_utils::{
+    serialization::{Bounded, SerdeAs},
+    ExtendedDensePolynomial,
 };
-use o1_utils::ExtendedDensePolynomial;
 use serde::{Deserialize, Serialize};
-use serde_with::serde_as;
+use serde_with::{serde_as, Same};
 use std::array;
 
+/// The maximum number of sorted lookup polynomials accepted when deserializing a proof
+pub const MAX_LOOKUP_SORTED: usize = 1 << 5;
+
+/// The maximum number of runtime tables of variable length accepted when deserializing a proof
+pub const MAX_VARIABLE_RUNTIME_TABLES: usize = 1 << 8;
+
+/// The maximum number of previous challenges accepted when deserializing a proof
+pub const MAX_PREV_CHALLENGES: usize = 1 << 4;
+
+/// The maximum number of public inputs accepted when deserializing a proof
+pub const MAX_PUBLIC: usize = 1 << 16;
+
+/// The maximum number of permutation polynomial evaluations accepted when deserializing a proof
+pub const MAX_SIGMA_EVALS: usize = PERMUTS - 1;
+
 //~ spec:startcode
 /// Evaluations of lookup polynomials
 #[serde_as]
 #[derive(Clone, Serialize, Deserialize)]
 #[serde(bound(
-    serialize = "Vec<o1_utils::serialization::SerdeAs>: serde_with::SerializeAs<Field>",
-    deserialize = "Vec<o1_utils::serialization::SerdeAs>: serde_with::DeserializeAs<'de, Field>"
+    serialize = "Bounded<SerdeAs, MAX_CHUNKS>: serde_with::SerializeAs<Field>",
+    deserialize = "Bounded<SerdeAs, MAX_CHUNKS>: serde_with::DeserializeAs<'de, Field>"
 ))]
 pub struct LookupEvaluations<Field> {
     /// sorted lookup table polynomial
-    #[serde_as(as = "Vec<Vec<o1_utils::serialization::SerdeAs>>")]
+    #[serde_as(as = "Bounded<Bounded<SerdeAs, MAX_CHUNKS>, MAX_LOOKUP_SORTED>")]
     pub sorted: Vec<Field>,
     /// lookup aggregation polynomial
-    #[serde_as(as = "Vec<o1_utils::serialization::SerdeAs>")]
+    #[serde_as(as = "Bounded<SerdeAs, MAX_CHUNKS>")]
     pub aggreg: Field,
     // TODO: May be possible to optimize this away?
     /// lookup table polynomial
-    #[serde_as(as = "Vec<o1_utils::serialization::SerdeAs>")]
+    #[serde_as(as = "Bounded<SerdeAs, MAX_CHUNKS>")]
     pub table: Field,
 
     /// Optionally, a runtime table polynomial.
-    #[serde_as(as = "Option<Vec<o1_utils::serialization::SerdeAs>>")]
+    #[serde_as(as = "Option<Bounded<SerdeAs, MAX_CHUNKS>>")]
     pub runtime: Option<Field>,
 }
 
@@ -45,27 +65,27 @@ pub struct LookupEvaluations<Field> {
 #[serde_as]
 #[derive(Clone, Serialize, Deserialize)]
 #[serde(bound(
-    serialize = "Vec<o1_utils::serialization::SerdeAs>: serde_with::SerializeAs<Field>",
-    deserialize = "Vec<o1_utils::serialization::SerdeAs>: serde_with::DeserializeAs<'de, Field>"
+    serialize = "Bounded<SerdeAs, MAX_CHUNKS>: serde_with::SerializeAs<Field>",
+    deserialize = "Bounded<SerdeAs, MAX_CHUNKS>: serde_with::DeserializeAs<'de, Field>"
 ))]
 pub struct ProofEvaluations<Field> {
     /// witness polynomials
-    #[serde_as(as = "[Vec<o1_utils::serialization::SerdeAs>; COLUMNS]")]
+    #[serde_as(as = "[Bounded<SerdeAs, MAX_CHUNKS>; COLUMNS]")]
     pub w: [Field; COLUMNS],
     /// permutation polynomial
-    #[serde_as(as = "Vec<o1_utils::serialization::SerdeAs>")]
+    #[serde_as(as = "Bounded<SerdeAs, MAX_CHUNKS>")]
     pub z: Field,
-    /// permutation polynomials
-    /// (PERMUTS-1 evaluations because the last permutation is only used in commitment form)
-    #[serde_as(as = "[Vec<o1_utils::serialization::SerdeAs>; PERMUTS - 1]")]
-    pub s: [Field; PERMUTS - 1],
+    /// permutation polynomials, one for each column taking part in the permutation but the last
+    /// (as the last permutation is only used in commitment form)
+    #[serde_as(as = "Bounded<Bounded<SerdeAs, MAX_CHUNKS>, MAX_SIGMA_EVALS>")]
+    pub s: Vec<Field>,
     /// lookup-related evaluations
     pub lookup: Option<LookupEvaluations<Field>>,
     /// evaluation of the generic selector polynomial
-    #[serde_as(as = "Vec<o1_utils::serialization::SerdeAs>")]
+    #[serde_as(as = "Bounded<SerdeAs, MAX_CHUNKS>")]
     pub generic_selector: Field,
     /// evaluation of the poseidon selector polynomial
-    #[serde_as(as = "Vec<o1_utils::serialization::SerdeAs>")]
+    #[serde_as(as = "Bounded<SerdeAs, MAX_CHUNKS>")]
     pub poseidon_selector: Field,
 }
 
@@ -75,11 +95,16 @@ pub struct ProofEvaluations<Field> {
 #[serde(bound = "G: ark_serialize::CanonicalDeserialize + ark_serialize::CanonicalSerialize")]
 pub struct LookupCommitments<G: AffineCurve> {
     /// Commitments to the sorted lookup table polynomial (may have chunks)
+    #[serde_as(as = "Bounded<Same, MAX_LOOKUP_SORTED>")]
     pub sorted: Vec<PolyComm<G>>,
     /// Commitment to the lookup aggregation polynomial
     pub aggreg: PolyComm<G>,
     /// Optional commitment to concatenated runtime tables
     pub runtime: Option<PolyComm<G>>,
+    /// The number of entries used in each runtime table of variable length, in the configured order
+    #[serde(default)]
+    #[serde_as(as = "Bounded<Same, MAX_VARIABLE_RUNTIME_TABLES>")]
+    pub runtime_lens: Vec<usize>,
 }
 
 /// All the commitments that the prover creates as part of the proof.
@@ -98,15 +123,20 @@ pub struct ProverCommitments<G: AffineCurve> {
 }
 
 /// The proof that the prover creates from a [ProverIndex](super::prover_index::ProverIndex) and a `witness`.
+/// The opening proof is the one of the polynomial commitment scheme `S`
+/// (by default, the inner product argument of the [`SRS`]).
 #[serde_as]
 #[derive(Clone, Serialize, Deserialize)]
-#[serde(bound = "G: ark_serialize::CanonicalDeserialize + ark_serialize::CanonicalSerialize")]
-pub struct ProverProof<G: AffineCurve> {
+#[serde(bound(
+    serialize = "G: ark_serialize::CanonicalDeserialize + ark_serialize::CanonicalSerialize, S::OpeningProof: Serialize",
+    deserialize = "G: ark_serialize::CanonicalDeserialize + ark_serialize::CanonicalSerialize, S::OpeningProof: Deserialize<'de>"
+))]
+pub struct ProverProof<G: CommitmentCurve, S: PolynomialCommitment<G> = SRS<G>> {
     /// All the polynomial commitments required in the proof
     pub commitments: ProverCommitments<G>,
 
     /// batched commitment opening proof
-    pub proof: OpeningProof<G>,
+    pub proof: S::OpeningProof,
 
     /// Two evaluations over a number of committed polynomials
     // TODO(mimoo): that really should be a type Evals { z: PE, zw: PE }
@@ -117,10 +147,11 @@ pub struct ProverProof<G: AffineCurve> {
     pub ft_eval1: G::ScalarField,
 
     /// The public input
-    #[serde_as(as = "Vec<o1_utils::serialization::SerdeAs>")]
+    #[serde_as(as = "Bounded<SerdeAs, MAX_PUBLIC>")]
     pub public: Vec<G::ScalarField>,
 
     /// The challenges underlying the optional polynomials folded into the proof
+    #[serde_as(as = "Bounded<Same, MAX_PREV_CHALLENGES>")]
     pub prev_challenges: Vec<RecursionChallenge<G>>,
 }
 
@@ -133,7 +164,7 @@ where
     G: AffineCurve,
 {
     /// Vector of scalar field elements
-    #[serde_as(as = "Vec<o1_utils::serialization::SerdeAs>")]
+    #[serde_as(as = "Bounded<SerdeAs, MAX_ROUNDS>")]
     pub chals: Vec<G::ScalarField>,
     /// Polynomial commitment
     pub comm: PolyComm<G>,
@@ -161,7 +192,9 @@ impl<F> ProofEvaluations<F> {
             poseidon_selector: array::from_fn(|i| &evals[i].poseidon_selector),
             z: array::from_fn(|i| &evals[i].z),
             w: array::from_fn(|j| array::from_fn(|i| &evals[i].w[j])),
-            s: array::from_fn(|j| array::from_fn(|i| &evals[i].s[j])),
+            s: (0..evals[0].s.len())
+                .map(|j| array::from_fn(|i| &evals[i].s[j]))
+                .collect(),
             lookup: if has_lookup {
                 let sorted_length = evals[0].lookup.as_ref().unwrap().sorted.len();
                 Some(LookupEvaluations {
@@ -237,7 +270,7 @@ impl<F: Zero> ProofEvaluations<F> {
         ProofEvaluations {
             w,
             z: F::zero(),
-            s: array::from_fn(|_| F::zero()),
+            s: (0..PERMUTS - 1).map(|_| F::zero()).collect(),
             lookup: None,
             generic_selector: F::zero(),
             poseidon_selector: F::zero(),
@@ -248,7 +281,11 @@ impl<F: Zero> ProofEvaluations<F> {
 impl<F: FftField> ProofEvaluations<Vec<F>> {
     pub fn combine(&self, pt: F) -> ProofEvaluations<F> {
         ProofEvaluations::<F> {
-            s: array::from_fn(|i| DensePolynomial::eval_polynomial(&self.s[i], pt)),
+            s: self
+                .s
+                .iter()
+                .map(|s| DensePolynomial::eval_polynomial(s, pt))
+                .collect(),
             w: array::from_fn(|i| DensePolynomial::eval_polynomial(&self.w[i], pt)),
             z: DensePolynomial::eval_polynomial(&self.z, pt),
             lookup: self.lookup.as_ref().map(|l| LookupEvaluations {
@@ -435,6 +472,7 @@ pub mod caml {
                 pe.w[13].iter().cloned().map(Into::into).collect(),
                 pe.w[14].iter().cloned().map(Into::into).collect(),
             );
+            // the OCaml proofs always permute all the `PERMUTS` columns
             let s = (
                 pe.s[0].iter().cloned().map(Into::into).collect(),
                 pe.s[1].iter().cloned().map(Into::into).collect(),
@@ -478,7 +516,7 @@ pub mod caml {
                 cpe.w.13.into_iter().map(Into::into).collect(),
                 cpe.w.14.into_iter().map(Into::into).collect(),
             ];
-            let s = [
+            let s = vec![
                 cpe.s.0.into_iter().map(Into::into).collect(),
                 cpe.s.1.into_iter().map(Into::into).collect(),
                 cpe.s.2.into_iter().map(Into::into).collect(),
@@ -0,0 +1,388 @@
+//! This module implements the [`ProofLink`], a proof that designated cells of the witnesses
+//! of two proofs, possibly of different circuits, hold the same values.
+//!
+//! This lets an application split its logic across circuits,
+//! without routing the values they share through public inputs.
+//!
+//! Each link is given a shared commitment `C = v G_0 + r H` to its value `v`,
+//! a commitment to the constant polynomial `v` with a blinder `r`.
+//! A single multi-point opening proof (see [`SRS::open_multi_point`](commitment_dlog::srs::SRS::open_multi_point))
+//! then shows that the difference between the column of each linked cell and the constant `v`
+//! is zero at the row of the cell, against the commitments of both proofs.
+//! As the differences are opened to zero, the linked values are not revealed.
+//!
+//! For the openings to bind both cells to the same value, the shared commitments must
+//! commit to constant polynomials: this is shown by a proof of knowledge of their openings
+//! on `G_0` and `H` alone, batched over the links with a random combination.
+//!
+//! The linked columns must be committed in a single chunk,
+//! and both proofs must use the same SRS.
+//! The multi-point opening reveals an evaluation of a combination of the linked columns
+//! at a random point, which the zero-knowledge rows hide as they hide the ones of the proof.
+
+use crate::{
+    circuits::wires::{CellRef, COLUMNS},
+    curve::KimchiCurve,
+    error::{ProverError, VerifyError},
+    proof::ProverProof,
+    prover_index::ProverIndex,
+    prover_witness::ProverWitness,
+    verifier_index::VerifierIndex,
+};
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{PrimeField, UniformRand, Zero};
+use ark_poly::EvaluationDomain;
+use commitment_dlog::{
+    commitment::PolyComm,
+    multi_point::{
+        MultiPointEvaluation, MultiPointEvaluationProof, MultiPointOpeningProof,
+        MultiPointPolynomial,
+    },
+    srs::SRS,
+};
+use o1_utils::{curve_helpers::batch_normalize, serialization::SerdeAs};
+use oracle::FqSponge;
+use rand::{rngs::OsRng, thread_rng};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+
+/// A proof that some cells of a first proof (the left one)
+/// hold the same values as some cells of a second proof (the right one).
+#[serde_as]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "G: ark_serialize::CanonicalDeserialize + ark_serialize::CanonicalSerialize")]
+pub struct ProofLink<G: AffineCurve> {
+    /// The linked cells, as a cell of the left proof and the cell of the right proof it equals
+    pub links: Vec<(CellRef, CellRef)>,
+    /// The shared commitments to the values of the links
+    #[serde_as(as = "Vec<SerdeAs>")]
+    pub commitments: Vec<G>,
+    /// The commitment to the nonces of the proof of knowledge of the shared commitments
+    #[serde_as(as = "SerdeAs")]
+    pub nonce_commitment: G,
+    /// The responses of the proof of knowledge, on `G_0` and on `H`
+    #[serde_as(as = "(SerdeAs, SerdeAs)")]
+    pub responses: (G::ScalarField, G::ScalarField),
+    /// The opening proof of the differences between the linked cells and their values
+    pub proof: MultiPointOpeningProof<G>,
+}
+
+/// The sponge of a link, after absorbing the digests of both verifier indexes,
+/// the linked commitments of both proofs and the shared commitments.
+fn link_sponge<G, EFqSponge>(
+    digests: [G::BaseField; 2],
+    linked: &[G],
+    commitments: &[G],
+) -> EFqSponge
+where
+    G: KimchiCurve,
+    EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
+{
+    let mut sponge = EFqSponge::new(G::OtherCurve::sponge_params());
+    sponge.absorb_fq(&digests);
+    sponge.absorb_g(linked);
+    sponge.absorb_g(commitments);
+    sponge
+}
+
+/// Returns the distinct points of the cells of both sides,
+/// and the index in these points of each cell, left cells first.
+fn link_points<F: PrimeField>(
+    links: &[(CellRef, CellRef)],
+    domains: [impl EvaluationDomain<F>; 2],
+) -> (Vec<F>, Vec<usize>) {
+    let mut points = vec![];
+    let left = links.iter().map(|(cell, _)| domains[0].element(cell.row));
+    let right = links.iter().map(|(_, cell)| domains[1].element(cell.row));
+    let indices = left
+        .chain(right)
+        .map(|x| match points.iter().position(|p| *p == x) {
+            Some(i) => i,
+            None => {
+                points.push(x);
+                points.len() - 1
+            }
+        })
+        .collect();
+    (points, indices)
+}
+
+/// Combines the shared commitments with the powers of `rho`.
+fn combine_commitments<G: AffineCurve>(commitments: &[G], rho: G::ScalarField) -> G::Projective {
+    commitments
+        .iter()
+        .rev()
+        .fold(G::Projective::zero(), |acc, c| {
+            acc.mul(rho.into_repr()) + c.into_projective()
+        })
+}
+
+impl<G: KimchiCurve> ProofLink<G>
+where
+    G::BaseField: PrimeField,
+{
+    /// Links the `links` cells of the proofs created from the `left` and `right` witnesses,
+    /// with `left` and `right` their indexes.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the column of a cell does not exist or is declared zero by its index,
+    /// if it is committed in several chunks, if the indexes do not use the same SRS,
+    /// or if two linked cells do not hold the same value.
+    pub fn create<EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>>(
+        group_map: &G::Map,
+        left: (&ProverIndex<G>, &ProverWitness<G>),
+        right: (&ProverIndex<G>, &ProverWitness<G>),
+        links: &[(CellRef, CellRef)],
+    ) -> Result<Self, ProverError> {
+        let sides = [left, right];
+        if left.0.srs.g != right.0.srs.g || left.0.srs.h != right.0.srs.h {
+            return Err(ProverError::DifferentSRS);
+        }
+        for (side, (index, witness)) in sides.iter().enumerate() {
+            for cell in links
+                .iter()
+                .map(|link| if side == 0 { link.0 } else { link.1 })
+            {
+                if cell.col >= COLUMNS || index.cs.zero_columns[cell.col] {
+                    return Err(ProverError::InvalidColumn(cell.col));
+                }
+                if cell.row >= index.cs.domain.d1.size() {
+                    return Err(ProverError::Prover("a linked cell is out of the domain"));
+                }
+                if witness.comm[cell.col].commitment.unshifted.len() != 1 {
+                    return Err(ProverError::ChunkedColumn(cell.col));
+                }
+            }
+        }
+
+        let values: Vec<_> = links
+            .iter()
+            .map(|(l, r)| {
+                let value = left.1.columns[l.col][l.row];
+                if value == right.1.columns[r.col][r.row] {
+                    Ok(value)
+                } else {
+                    Err(ProverError::UnequalLink(*l, *r))
+                }
+            })
+            .collect::<Result<_, _>>()?;
+
+        // the shared commitments, v G_0 + r H
+        let srs = &left.0.srs;
+        let blinders: Vec<_> = links
+            .iter()
+            .map(|_| G::ScalarField::rand(&mut OsRng))
+            .collect();
+        let commitments = batch_normalize(
+            &values
+                .iter()
+                .zip(&blinders)
+                .map(|(v, r)| srs.g[0].mul(v.into_repr()) + srs.h.mul(r.into_repr()))
+                .collect::<Vec<_>>(),
+        );
+
+        let linked: Vec<_> = links
+            .iter()
+            .map(|(l, _)| left.1.comm[l.col].commitment.unshifted[0])
+            .chain(
+                links
+                    .iter()
+                    .map(|(_, r)| right.1.comm[r.col].commitment.unshifted[0]),
+            )
+            .collect();
+        let mut sponge = link_sponge::<G, EFqSponge>(
+            [
+                left.0.verifier_index_digest::<EFqSponge>(),
+                right.0.verifier_index_digest::<EFqSponge>(),
+            ],
+            &linked,
+            &commitments,
+        );
+
+        // the proof of knowledge of the openings of the shared commitments on G_0 and H
+        let rho = sponge.challenge();
+        let nonces = (
+            G::ScalarField::rand(&mut OsRng),
+            G::ScalarField::rand(&mut OsRng),
+        );
+        let nonce_commitment =
+            (srs.g[0].mul(nonces.0.into_repr()) + srs.h.mul(nonces.1.into_repr())).into_affine();
+        sponge.absorb_g(&[nonce_commitment]);
+        let c = sponge.challenge();
+        let combine = |scalars: &[G::ScalarField]| {
+            scalars
+                .iter()
+                .rev()
+                .fold(G::ScalarField::zero(), |acc, s| acc * rho + s)
+        };
+        let responses = (
+            nonces.0 + c * combine(&values),
+            nonces.1 + c * combine(&blinders),
+        );
+
+        // the differences between the linked columns and the values, opened to zero
+        let (points, indices) = link_points(links, [left.0.cs.domain.d1, right.0.cs.domain.d1]);
+        sponge.absorb_fr(&points);
+        let polyscale = sponge.challenge();
+        let evalscale = sponge.challenge();
+
+        let cells = links
+            .iter()
+            .map(|(l, _)| (left.1, l))
+            .chain(links.iter().map(|(_, r)| (right.1, r)));
+        let differences: Vec<_> = cells
+            .clone()
+            .zip(values.iter().chain(&values))
+            .map(|((witness, cell), value)| {
+                let mut poly = witness.polys[cell.col].clone();
+                if poly.coeffs.is_empty() {
+                    poly.coeffs.push(G::ScalarField::zero());
+                }
+                poly.coeffs[0] -= value;
+                poly
+            })
+            .collect();
+        let polynomials: Vec<_> = cells
+            .zip(blinders.iter().chain(&blinders))
+            .zip(&differences)
+            .zip(&indices)
+            .map(|((((witness, cell), r), poly), i)| MultiPointPolynomial {
+                poly,
+                blinders: PolyComm {
+                    unshifted: vec![witness.comm[cell.col].blinders.unshifted[0] - r],
+                    shifted: None,
+                },
+                points: vec![*i],
+            })
+            .collect();
+        let proof = srs.open_multi_point(
+            group_map,
+            &polynomials,
+            &points,
+            polyscale,
+            evalscale,
+            sponge,
+            &mut OsRng,
+        );
+
+        Ok(Self {
+            links: links.to_vec(),
+            commitments,
+            nonce_commitment,
+            responses,
+            proof,
+        })
+    }
+
+    /// Checks the link against the commitments of the `left` and `right` proofs,
+    /// with `left` and `right` their verifier indexes.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the column of a cell does not exist or is declared zero by its index,
+    /// if a cell is out of the domain or its column is committed in several chunks,
+    /// if the indexes do not use the same SRS,
+    /// or if the proof of knowledge or the opening proof fails to verify.
+    pub fn verify<EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>>(
+        &self,
+        group_map: &G::Map,
+        left: (&VerifierIndex<G>, &ProverProof<G>),
+        right: (&VerifierIndex<G>, &ProverProof<G>),
+    ) -> Result<(), VerifyError> {
+        let srs: &SRS<G> = left.0.srs();
+        if srs.g != right.0.srs().g || srs.h != right.0.srs().h {
+            return Err(VerifyError::DifferentSRS);
+        }
+        for (side, (index, proof)) in [left, right].iter().enumerate() {
+            for cell in self
+                .links
+                .iter()
+                .map(|link| if side == 0 { link.0 } else { link.1 })
+            {
+                if cell.col >= COLUMNS || index.zero_columns[cell.col] {
+                    return Err(VerifyError::InvalidColumn(cell.col));
+                }
+                if cell.row >= index.domain.size() {
+                    return Err(VerifyError::IncorrectEvaluationsLength("proof link"));
+                }
+                let chunks = proof.commitments.w_comm[cell.col].unshifted.len();
+                if chunks != 1 {
+                    return Err(VerifyError::IncorrectCommitmentChunks(
+                        "linked column",
+                        1,
+                        chunks,
+                    ));
+                }
+            }
+        }
+        if self.commitments.len() != self.links.len() {
+            return Err(VerifyError::IncorrectCommitmentLength("proof link"));
+        }
+
+        let linked: Vec<_> = self
+            .links
+            .iter()
+            .map(|(l, _)| left.1.commitments.w_comm[l.col].unshifted[0])
+            .chain(
+                self.links
+                    .iter()
+                    .map(|(_, r)| right.1.commitments.w_comm[r.col].unshifted[0]),
+            )
+            .collect();
+        let mut sponge = link_sponge::<G, EFqSponge>(
+            [left.0.digest::<EFqSponge>(), right.0.digest::<EFqSponge>()],
+            &linked,
+            &self.commitments,
+        );
+
+        // z_0 G_0 + z_1 H = R + c sum_i rho^i C_i
+        let rho = sponge.challenge();
+        sponge.absorb_g(&[self.nonce_commitment]);
+        let c = sponge.challenge();
+        let (z0, z1) = self.responses;
+        let knowledge = srs.g[0].mul(z0.into_repr()) + srs.h.mul(z1.into_repr())
+            == self.nonce_commitment.into_projective()
+                + combine_commitments(&self.commitments, rho).mul(c.into_repr());
+        if !knowledge {
+            return Err(VerifyError::OpenProof);
+        }
+
+        let (points, indices) = link_points(&self.links, [left.0.domain, right.0.domain]);
+        sponge.absorb_fr(&points);
+        let polyscale = sponge.challenge();
+        let evalscale = sponge.challenge();
+
+        let differences = batch_normalize(
+            &linked
+                .iter()
+                .zip(self.commitments.iter().chain(&self.commitments))
+                .map(|(w, v)| w.into_projective() - v.into_projective())
+                .collect::<Vec<_>>(),
+        );
+        let mut batch = vec![MultiPointEvaluationProof {
+            sponge,
+            evaluations: differences
+                .into_iter()
+                .zip(&indices)
+                .map(|(difference, i)| MultiPointEvaluation {
+                    commitment: PolyComm {
+                        unshifted: vec![difference],
+                        shifted: None,
+                    },
+                    points: vec![*i],
+                    evaluations: vec![vec![G::ScalarField::zero()]],
+                })
+                .collect(),
+            evaluation_points: points,
+            polyscale,
+            evalscale,
+            opening: &self.proof,
+        }];
+        if srs.verify_multi_point(group_map, &mut batch, &mut thread_rng()) {
+            Ok(())
+        } else {
+            Err(VerifyError::OpenProof)
+        }
+    }
+}
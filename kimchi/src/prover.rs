@@ -1,47 +1,57 @@
 //! This module implements prover's zk-proof primitive.
 
+#[cfg(feature = "chacha")]
+use crate::circuits::polynomials::chacha::{ChaCha0, ChaCha1, ChaCha2, ChaChaFinal};
+#[cfg(feature = "foreign_field")]
+use crate::circuits::polynomials::foreign_field_add;
+#[cfg(feature = "keccak")]
+use crate::circuits::polynomials::keccak;
+#[cfg(feature = "range_check")]
+use crate::circuits::polynomials::range_check;
 use crate::{
     circuits::{
         argument::{Argument, ArgumentType},
-        expr::{l0_1, Constants, Environment, LookupEnvironment},
+        constraints::{ConstraintSystem, GateError},
+        expr::{Constants, Environment, LookupEnvironment},
         gate::GateType,
         lookup::{
-            self, lookups::LookupsUsed, runtime_tables::RuntimeTable, tables::combine_table_entry,
+            self,
+            lookups::LookupsUsed,
+            runtime_tables::{RuntimeTable, UnusedEntry},
+            tables::combine_table_entry,
         },
         polynomials::{
-            chacha::{ChaCha0, ChaCha1, ChaCha2, ChaChaFinal},
-            complete_add::CompleteAdd,
-            endomul_scalar::EndomulScalar,
-            endosclmul::EndosclMul,
-            foreign_field_add, generic, permutation,
-            permutation::ZK_ROWS,
-            poseidon::Poseidon,
-            range_check,
+            complete_add::CompleteAdd, copy_cells::CopyCells, endomul_scalar::EndomulScalar,
+            endosclmul::EndosclMul, generic, permutation, permutation::ZK_ROWS, poseidon::Poseidon,
             varbasemul::VarbaseMul,
         },
-        wires::{COLUMNS, PERMUTS},
+        wires::{CellRef, COLUMNS, PERMUTS},
     },
     curve::KimchiCurve,
-    error::ProverError,
-    plonk_sponge::FrSponge,
+    error::{ProverError, WitnessError},
+    plonk_sponge::{absorb_context, FrSponge},
     proof::{
         LookupCommitments, LookupEvaluations, ProofEvaluations, ProverCommitments, ProverProof,
         RecursionChallenge,
     },
     prover_index::ProverIndex,
+    prover_task::ProvingPhase,
+    prover_witness::ProverWitness,
+    transcript::{Round, TranscriptAbsorb},
 };
-use ark_ec::ProjectiveCurve;
 use ark_ff::{FftField, Field, One, PrimeField, UniformRand, Zero};
 use ark_poly::{
     univariate::DensePolynomial, EvaluationDomain, Evaluations, Polynomial,
     Radix2EvaluationDomain as D, UVPolynomial,
 };
-use commitment_dlog::commitment::{
-    b_poly_coefficients, BlindedCommitment, CommitmentCurve, PolyComm,
+use commitment_dlog::{
+    commitment::{b_poly_coefficients, BlindedCommitment, CommitmentCurve, PolyComm},
+    scheme::PolynomialCommitment,
 };
 use itertools::Itertools;
-use o1_utils::ExtendedDensePolynomial as _;
+use o1_utils::{curve_helpers::batch_normalize, ExtendedDensePolynomial as _};
 use oracle::{sponge::ScalarChallenge, FqSponge};
+use rand::{CryptoRng, RngCore};
 use rayon::prelude::*;
 use std::array;
 use std::collections::HashMap;
@@ -49,24 +59,128 @@ use std::collections::HashMap;
 /// The result of a proof creation or verification.
 type Result<T> = std::result::Result<T, ProverError>;
 
-/// Helper to quickly test if a witness satisfies a constraint
+/// Helper to quickly test if a witness satisfies a constraint,
+/// in debug builds or with the `check_constraints` feature (see [`check_constraint`])
 macro_rules! check_constraint {
     ($index:expr, $evaluation:expr) => {{
         check_constraint!($index, stringify!($evaluation), $evaluation);
     }};
     ($index:expr, $label:expr, $evaluation:expr) => {{
-        if cfg!(debug_assertions) {
-            let (_, res) = $evaluation
-                .interpolate_by_ref()
-                .divide_by_vanishing_poly($index.cs.domain.d1)
-                .unwrap();
-            if !res.is_zero() {
-                panic!("couldn't divide by vanishing polynomial: {}", $label);
-            }
+        if cfg!(any(debug_assertions, feature = "check_constraints")) {
+            check_constraint(&$index.cs, $label, &$evaluation)?;
         }
     }};
 }
 
+/// Checks that the evaluations of a constraint vanish on the rows of the circuit,
+/// which is exactly when the constraint divides by the vanishing polynomial.
+/// Unlike the division, this pinpoints the rows where the constraint does not hold.
+///
+/// # Errors
+///
+/// Will give error with the first row where the constraint does not hold, and its gate.
+pub(crate) fn check_constraint<F: PrimeField>(
+    cs: &ConstraintSystem<F>,
+    label: &'static str,
+    evaluation: &Evaluations<F, D<F>>,
+) -> Result<()> {
+    let d1_size = cs.domain.d1.size();
+    let scale = evaluation.evals.len() / d1_size;
+    let mut rows = (0..d1_size).filter(|row| !evaluation.evals[row * scale].is_zero());
+    match rows.next() {
+        None => Ok(()),
+        Some(row) => Err(ProverError::ConstraintNotSatisfied {
+            constraint: label,
+            row,
+            gate: cs.gates[row].typ,
+            others: rows.count(),
+        }),
+    }
+}
+
+/// Checks the witness against the gates, the permutation and the lookups of the circuit,
+/// before creating a proof (see [`ProverIndex::check_witness`]).
+/// Unlike the proof, which would not verify, this pinpoints the row where the witness is incorrect.
+/// The reason a gate does not hold is given by [`ConstraintSystem::verify`].
+///
+/// # Errors
+///
+/// Will give error if the witness is not of the shape of the circuit,
+/// or with the first row where it does not satisfy the circuit, and the reason.
+pub(crate) fn check_witness<G: KimchiCurve, S>(
+    index: &ProverIndex<G, S>,
+    witness: &[Vec<G::ScalarField>; COLUMNS],
+    runtime_tables: &[RuntimeTable<G::ScalarField>],
+) -> Result<()> {
+    let cs = &index.cs;
+    let length = witness[0].len();
+    if length + ZK_ROWS as usize > cs.domain.d1.size() {
+        return Err(ProverError::NoRoomForZkInWitness);
+    }
+    if length < cs.public || witness.iter().any(|col| col.len() != length) {
+        return Err(ProverError::WitnessCsInconsistent);
+    }
+    if let Some(col) = (0..COLUMNS)
+        .find(|col| cs.zero_columns[*col] && witness[*col].iter().any(|cell| !cell.is_zero()))
+    {
+        return Err(ProverError::NonZeroColumn(col));
+    }
+
+    let located = |row: usize, cause| ProverError::WitnessNotSatisfied {
+        row,
+        gate: cs.gates[row].typ,
+        cause,
+    };
+    let public = &witness[0][0..cs.public];
+    cs.verify::<G>(witness, public).map_err(|err| match err {
+        GateError::DisconnectedWires(wire, other) => located(
+            wire.row,
+            WitnessError::Wiring(wire.col, CellRef::new(other.row, other.col)),
+        ),
+        GateError::IncorrectPublic(row) => located(row, WitnessError::Public),
+        GateError::Custom { row, .. } => located(row, WitnessError::Gate),
+    })?;
+
+    if let Some(lcs) = &cs.lookup_constraint_system {
+        lookup::constraints::check_lookups(lcs, cs.domain.d1, &cs.gates, witness, runtime_tables)?;
+    }
+    Ok(())
+}
+
+/// Commits to the polynomial of coefficients `coeffs` and evaluations `evals` over the domain.
+/// The Lagrange bases of the SRS only span a single chunk,
+/// so that a domain larger than `max_poly_size` is committed from the coefficients, in chunks.
+pub(crate) fn commit_chunked<G: KimchiCurve, S: PolynomialCommitment<G>>(
+    index: &ProverIndex<G, S>,
+    evals: &Evaluations<G::ScalarField, D<G::ScalarField>>,
+    coeffs: &DensePolynomial<G::ScalarField>,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> BlindedCommitment<G> {
+    if index.cs.domain.d1.size() <= index.max_poly_size {
+        index
+            .srs
+            .commit_evaluations(index.cs.domain.d1, evals, None, rng)
+    } else {
+        index.srs.commit(coeffs, None, rng)
+    }
+}
+
+/// Checks that the SRS of `index` is large enough for its circuit.
+/// The polynomials over a domain larger than `max_poly_size` are committed and evaluated in chunks,
+/// but the public input is committed with the Lagrange bases of the SRS, which span a single chunk.
+///
+/// Note that the `ZK_ROWS` random rows of a polynomial only hide its evaluations
+/// at $\zeta$ and $\zeta\omega$ when it is in a single chunk:
+/// the proof of a circuit whose domain is larger than the SRS is not zero-knowledge.
+pub(crate) fn check_srs_size<G: KimchiCurve, S: PolynomialCommitment<G>>(
+    index: &ProverIndex<G, S>,
+) -> Result<()> {
+    if index.cs.public > 0 && index.srs.max_poly_size() < index.cs.domain.d1.size() {
+        return Err(ProverError::SRSTooSmall);
+    }
+    Ok(())
+}
+
 /// Contains variables needed for lookup in the prover algorithm.
 #[derive(Default)]
 struct LookupContext<G, F>
@@ -108,9 +222,16 @@ where
     runtime_table_d8: Option<Evaluations<F, D<F>>>,
     runtime_table_comm: Option<BlindedCommitment<G>>,
     runtime_second_col_d8: Option<Evaluations<F, D<F>>>,
+
+    /// The number of entries used in each runtime table of variable length,
+    /// and the entries left unused
+    runtime_lens: Vec<usize>,
+    unused_entries: Vec<UnusedEntry>,
+    /// The runtime selector, extended to the unused entries
+    runtime_selector8: Option<Evaluations<F, D<F>>>,
 }
 
-impl<G: KimchiCurve> ProverProof<G>
+impl<G: KimchiCurve, S: PolynomialCommitment<G>> ProverProof<G, S>
 where
     G::BaseField: PrimeField,
 {
@@ -126,7 +247,7 @@ where
         groupmap: &G::Map,
         witness: [Vec<G::ScalarField>; COLUMNS],
         runtime_tables: &[RuntimeTable<G::ScalarField>],
-        index: &ProverIndex<G>,
+        index: &ProverIndex<G, S>,
     ) -> Result<Self> {
         Self::create_recursive::<EFqSponge, EFrSponge>(
             groupmap,
@@ -152,30 +273,76 @@ where
         EFrSponge: FrSponge<G::ScalarField>,
     >(
         group_map: &G::Map,
-        mut witness: [Vec<G::ScalarField>; COLUMNS],
+        witness: [Vec<G::ScalarField>; COLUMNS],
         runtime_tables: &[RuntimeTable<G::ScalarField>],
-        index: &ProverIndex<G>,
+        index: &ProverIndex<G, S>,
         prev_challenges: Vec<RecursionChallenge<G>>,
         blinders: Option<[Option<PolyComm<G::ScalarField>>; COLUMNS]>,
     ) -> Result<Self> {
-        // make sure that the SRS is not smaller than the domain size
-        let d1_size = index.cs.domain.d1.size();
-        if index.srs.max_degree() < d1_size {
-            return Err(ProverError::SRSTooSmall);
-        }
+        Self::create_in_context::<EFqSponge, EFrSponge>(
+            group_map,
+            witness,
+            runtime_tables,
+            index,
+            prev_challenges,
+            blinders,
+            &[],
+        )
+    }
 
-        let (_, endo_r) = G::endos();
+    /// This function constructs prover's recursive zk-proof like [`ProverProof::create_recursive`],
+    /// bound to the application `context` (e.g. a domain tag or a nonce):
+    /// the proof only verifies with the same context (see [`verify_with_context`](crate::verifier::verify_with_context)),
+    /// so that it cannot be replayed in another application context.
+    /// An empty context is the same as no context.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `create_recursive` process fails.
+    pub fn create_with_context<
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+        EFrSponge: FrSponge<G::ScalarField>,
+    >(
+        group_map: &G::Map,
+        witness: [Vec<G::ScalarField>; COLUMNS],
+        runtime_tables: &[RuntimeTable<G::ScalarField>],
+        index: &ProverIndex<G, S>,
+        prev_challenges: Vec<RecursionChallenge<G>>,
+        context: &[u8],
+    ) -> Result<Self> {
+        Self::create_in_context::<EFqSponge, EFrSponge>(
+            group_map,
+            witness,
+            runtime_tables,
+            index,
+            prev_challenges,
+            None,
+            context,
+        )
+    }
+
+    /// Constructs the proof from the witness, blinded with `blinders` when given, and bound to `context`.
+    fn create_in_context<
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+        EFrSponge: FrSponge<G::ScalarField>,
+    >(
+        group_map: &G::Map,
+        witness: [Vec<G::ScalarField>; COLUMNS],
+        runtime_tables: &[RuntimeTable<G::ScalarField>],
+        index: &ProverIndex<G, S>,
+        prev_challenges: Vec<RecursionChallenge<G>>,
+        blinders: Option<[Option<PolyComm<G::ScalarField>>; COLUMNS]>,
+        context: &[u8],
+    ) -> Result<Self> {
+        // make sure that the SRS is large enough for the circuit
+        check_srs_size(index)?;
 
         // TODO: rng should be passed as arg
         let rng = &mut rand::rngs::OsRng;
 
         // double-check the witness
-        if cfg!(debug_assertions) {
-            let public = witness[0][0..index.cs.public].to_vec();
-            index
-                .cs
-                .verify::<G>(&witness, &public)
-                .expect("incorrect witness");
+        if cfg!(debug_assertions) || index.check_witness {
+            check_witness(index, &witness, runtime_tables)?;
         }
 
         //~ 1. Ensure we have room in the witness for the zero-knowledge rows.
@@ -183,34 +350,86 @@ where
         //~    but instead be of the length of the (smaller) circuit.
         //~    If we cannot add `ZK_ROWS` rows to the columns of the witness before reaching
         //~    the size of the domain, abort.
-        let length_witness = witness[0].len();
-        let length_padding = d1_size
-            .checked_sub(length_witness)
-            .ok_or(ProverError::NoRoomForZkInWitness)?;
-
-        if length_padding < ZK_ROWS as usize {
-            return Err(ProverError::NoRoomForZkInWitness);
-        }
-
         //~ 1. Pad the witness columns with Zero gates to make them the same length as the domain.
         //~    Then, randomize the last `ZK_ROWS` of each columns.
-        for w in &mut witness {
-            if w.len() != length_witness {
-                return Err(ProverError::WitnessCsInconsistent);
-            }
+        let witness = ProverWitness::create_with_blinders(index, witness, blinders.as_ref(), rng)?;
 
-            // padding
-            w.extend(std::iter::repeat(G::ScalarField::zero()).take(length_padding));
+        Self::create_from_witness::<EFqSponge, EFrSponge>(
+            group_map,
+            &witness,
+            runtime_tables,
+            index,
+            prev_challenges,
+            context,
+            &mut |_| Ok(()),
+        )
+    }
 
-            // zk-rows
-            for row in w.iter_mut().rev().take(ZK_ROWS as usize) {
-                *row = <G::ScalarField as UniformRand>::rand(rng);
-            }
+    /// This function constructs prover's recursive zk-proof from a [`ProverWitness`],
+    /// refreshing its randomness first if it was already used by a proof.
+    /// Combined with [`ProverWitness::update`], only the rows that changed since the previous proof
+    /// are committed to and interpolated again.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `create_recursive` process fails.
+    pub fn create_incremental<
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+        EFrSponge: FrSponge<G::ScalarField>,
+    >(
+        group_map: &G::Map,
+        witness: &mut ProverWitness<G>,
+        runtime_tables: &[RuntimeTable<G::ScalarField>],
+        index: &ProverIndex<G, S>,
+        prev_challenges: Vec<RecursionChallenge<G>>,
+    ) -> Result<Self> {
+        // make sure that the SRS is large enough for the circuit
+        check_srs_size(index)?;
+
+        // double-check the witness
+        if cfg!(debug_assertions) || index.check_witness {
+            check_witness(index, &witness.witness(), runtime_tables)?;
         }
 
+        witness.use_randomness(index, &mut rand::rngs::OsRng);
+
+        Self::create_from_witness::<EFqSponge, EFrSponge>(
+            group_map,
+            witness,
+            runtime_tables,
+            index,
+            prev_challenges,
+            &[],
+            &mut |_| Ok(()),
+        )
+    }
+
+    /// Constructs the proof once the witness is padded, committed to and interpolated, bound to `context`,
+    /// reporting each phase to `progress` as it starts, which can abort the proof by giving error.
+    pub(crate) fn create_from_witness<
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+        EFrSponge: FrSponge<G::ScalarField>,
+    >(
+        group_map: &G::Map,
+        witness: &ProverWitness<G>,
+        runtime_tables: &[RuntimeTable<G::ScalarField>],
+        index: &ProverIndex<G, S>,
+        prev_challenges: Vec<RecursionChallenge<G>>,
+        context: &[u8],
+        progress: &mut dyn FnMut(ProvingPhase) -> Result<()>,
+    ) -> Result<Self> {
+        let d1_size = index.cs.domain.d1.size();
+        let (_, endo_r) = G::endos();
+
+        // TODO: rng should be passed as arg
+        let rng = &mut rand::rngs::OsRng;
+
         //~ 1. Setup the Fq-Sponge.
         let mut fq_sponge = EFqSponge::new(G::OtherCurve::sponge_params());
 
+        //~ 1. If the proof is bound to an application context, absorb the digest of the context.
+        absorb_context::<G, _>(&mut fq_sponge, context);
+
         //~ 1. Absorb the digest of the VerifierIndex.
         let verifier_index_digest = index.verifier_index_digest::<EFqSponge>();
         fq_sponge.absorb_fq(&[verifier_index_digest]);
@@ -223,15 +442,19 @@ where
         //~ 1. Compute the negated public input polynomial as
         //~    the polynomial that evaluates to $-p_i$ for the first `public_input_size` values of the domain,
         //~    and $0$ for the rest.
-        let public = witness[0][0..index.cs.public].to_vec();
-        let public_poly = -Evaluations::<G::ScalarField, D<G::ScalarField>>::from_vec_and_domain(
-            public.clone(),
+        let public = witness.columns[0][0..index.cs.public].to_vec();
+        let public_evals = Evaluations::<G::ScalarField, D<G::ScalarField>>::from_vec_and_domain(
+            public.iter().map(|p| -*p).collect(),
             index.cs.domain.d1,
-        )
-        .interpolate();
+        );
+        let public_poly = public_evals.interpolate_by_ref();
 
-        //~ 1. Commit (non-hiding) to the negated public input polynomial.
-        let public_comm = index.srs.commit_non_hiding(&public_poly, None);
+        //~ 1. Commit (non-hiding) to the negated public input polynomial,
+        //~    from its evaluations.
+        let public_comm =
+            index
+                .srs
+                .commit_evaluations_non_hiding(index.cs.domain.d1, &public_evals, None);
         let public_comm = {
             index
                 .srs
@@ -254,56 +477,14 @@ where
         //~
         //~    Note: since the witness is in evaluation form,
         //~    we can use the `commit_evaluation` optimization.
-        let mut w_comm = vec![];
-        for col in 0..COLUMNS {
-            // witness coeff -> witness eval
-            let witness_eval =
-                Evaluations::<G::ScalarField, D<G::ScalarField>>::from_vec_and_domain(
-                    witness[col].clone(),
-                    index.cs.domain.d1,
-                );
-
-            let com = match blinders.as_ref().and_then(|b| b[col].as_ref()) {
-                // no blinders: blind the witness
-                None => index
-                    .srs
-                    .commit_evaluations(index.cs.domain.d1, &witness_eval, None, rng),
-                // blinders: blind the witness with them
-                Some(blinder) => {
-                    // TODO: make this a function rather no? mask_with_custom()
-                    let witness_com = index.srs.commit_evaluations_non_hiding(
-                        index.cs.domain.d1,
-                        &witness_eval,
-                        None,
-                    );
-                    index
-                        .srs
-                        .mask_custom(witness_com, blinder)
-                        .map_err(ProverError::WrongBlinders)?
-                }
-            };
-
-            w_comm.push(com);
-        }
-
-        let w_comm: [BlindedCommitment<G>; COLUMNS] = w_comm
-            .try_into()
-            .expect("previous loop is of the correct length");
-
-        //~ 1. Absorb the witness commitments with the Fq-Sponge.
-        w_comm
-            .iter()
-            .for_each(|c| fq_sponge.absorb_g(&c.commitment.unshifted));
+        //~    The columns declared zero by the index have an empty commitment instead.
+        let w_comm = &witness.comm;
 
         //~ 1. Compute the witness polynomials by interpolating each `COLUMNS` of the witness.
         //~    TODO: why not do this first, and then commit? Why commit from evaluation directly?
-        let witness_poly: [DensePolynomial<G::ScalarField>; COLUMNS] = array::from_fn(|i| {
-            Evaluations::<G::ScalarField, D<G::ScalarField>>::from_vec_and_domain(
-                witness[i].clone(),
-                index.cs.domain.d1,
-            )
-            .interpolate()
-        });
+        let witness_poly = &witness.polys;
+
+        progress(ProvingPhase::Lookup)?;
 
         let mut lookup_context = LookupContext::default();
 
@@ -312,31 +493,54 @@ where
             //~~ - if using runtime table:
             if let Some(cfg_runtime_tables) = &lcs.runtime_tables {
                 //~~~ - check that all the provided runtime tables have length and IDs that match the runtime table configuration of the index
+                //~~~   (a runtime table of variable length can have fewer entries than configured)
                 //~~~   we expect the given runtime tables to be sorted as configured, this makes it easier afterwards
-                let expected_runtime: Vec<_> = cfg_runtime_tables
-                    .iter()
-                    .map(|rt| (rt.id, rt.len))
-                    .collect();
-                let runtime: Vec<_> = runtime_tables
+                RuntimeTable::check(cfg_runtime_tables, runtime_tables)
+                    .map_err(ProverError::RuntimeTablesInconsistent)?;
+
+                //~~~ - list the entries left unused in the runtime tables of variable length,
+                //~~~   from the number of entries given for each of them
+                let runtime_lens: Vec<_> = cfg_runtime_tables
                     .iter()
-                    .map(|rt| (rt.id, rt.data.len()))
+                    .zip(runtime_tables)
+                    .filter(|(cfg, _)| cfg.variable_len)
+                    .map(|(_, rt)| rt.data.len())
                     .collect();
-                if expected_runtime != runtime {
-                    return Err(ProverError::RuntimeTablesInconsistent);
+                let unused_entries =
+                    UnusedEntry::all(&lcs.variable_runtime_tables(), &runtime_lens)
+                        .expect("the runtime tables match their configuration");
+
+                //~~~ - extend the runtime selector to the unused entries,
+                //~~~   so that the runtime table is zero at their rows
+                if !unused_entries.is_empty() {
+                    let mut runtime_selector8 =
+                        UnusedEntry::mask(&unused_entries, index.cs.domain.d1, |_| {
+                            G::ScalarField::one()
+                        })
+                        .interpolate()
+                        .evaluate_over_domain(index.cs.domain.d8);
+                    let selector8 = lcs
+                        .runtime_selector
+                        .as_ref()
+                        .expect("runtime configuration missing selector");
+                    runtime_selector8 += selector8;
+                    lookup_context.runtime_selector8 = Some(runtime_selector8);
                 }
+                lookup_context.runtime_lens = runtime_lens;
+                lookup_context.unused_entries = unused_entries;
 
                 //~~~ - calculate the contribution to the second column of the lookup table
-                //~~~   (the runtime vector)
+                //~~~   (the runtime vector, zero at the unused entries)
                 let (runtime_table_contribution, runtime_table_contribution_d8) = {
                     let mut offset = lcs
                         .runtime_table_offset
                         .expect("runtime configuration missing offset");
 
                     let mut evals = vec![G::ScalarField::zero(); d1_size];
-                    for rt in runtime_tables {
+                    for (cfg, rt) in cfg_runtime_tables.iter().zip(runtime_tables) {
                         let range = offset..(offset + rt.data.len());
                         evals[range].copy_from_slice(&rt.data);
-                        offset += rt.data.len();
+                        offset += cfg.len;
                     }
 
                     // zero-knowledge
@@ -358,9 +562,6 @@ where
                 // (and save it to the proof)
                 let runtime_table_comm = index.srs.commit(&runtime_table_contribution, None, rng);
 
-                // absorb the commitment
-                fq_sponge.absorb_g(&runtime_table_comm.commitment.unshifted);
-
                 // pre-compute the updated second column of the lookup table
                 let mut second_column_d8 = runtime_table_contribution_d8.clone();
                 second_column_d8
@@ -376,7 +577,38 @@ where
                 lookup_context.runtime_table_comm = Some(runtime_table_comm);
                 lookup_context.runtime_second_col_d8 = Some(second_column_d8);
             }
+        }
 
+        //~ 1. Absorb the witness commitments with the Fq-Sponge,
+        //~    followed by the commitment to the runtime table if runtime tables are used,
+        //~    and by the number of entries used in each runtime table of variable length, if any.
+        //~    The other commitments of the proof are absorbed as they are computed.
+        let empty = PolyComm {
+            unshifted: vec![],
+            shifted: None,
+        };
+        let mut commitments = ProverCommitments {
+            w_comm: array::from_fn(|i| w_comm[i].commitment.clone()),
+            z_comm: empty.clone(),
+            t_comm: empty.clone(),
+            lookup: index
+                .cs
+                .lookup_constraint_system
+                .as_ref()
+                .map(|_| LookupCommitments {
+                    sorted: vec![],
+                    aggreg: empty,
+                    runtime: lookup_context
+                        .runtime_table_comm
+                        .as_ref()
+                        .map(|c| c.commitment.clone()),
+                    runtime_lens: lookup_context.runtime_lens.clone(),
+                }),
+        };
+        commitments.absorb(Round::Witness, &mut fq_sponge);
+
+        //~ 1. If using lookup:
+        if let Some(lcs) = &index.cs.lookup_constraint_system {
             //~~ - If queries involve a lookup table with multiple columns
             //~~   then squeeze the Fq-Sponge to obtain the joint combiner challenge $j'$,
             //~~   otherwise set the joint combiner challenge $j'$ to $0$.
@@ -389,7 +621,8 @@ where
             };
 
             //~~ - Derive the scalar joint combiner $j$ from $j'$ using the endomorphism (TOOD: specify)
-            let joint_combiner: G::ScalarField = ScalarChallenge(joint_combiner).to_field(endo_r);
+            let joint_combiner: G::ScalarField = ScalarChallenge(joint_combiner)
+                .to_field_with_limbs(EFqSponge::CHALLENGE_LENGTH_IN_LIMBS, endo_r);
 
             //~~ - If multiple lookup tables are involved,
             //~~   set the `table_id_combiner` as the $j^i$ with $i$ the maximum width of any used table.
@@ -459,6 +692,22 @@ where
                 Evaluations::from_vec_and_domain(evals, index.cs.domain.d8)
             };
 
+            //~~ - Mask the unused entries of the runtime tables of variable length,
+            //~~   by removing the combination of their index and table ID,
+            //~~   so that the combined table holds the dummy value $0$ at their rows.
+            let joint_lookup_table_d8 = if lookup_context.unused_entries.is_empty() {
+                joint_lookup_table_d8
+            } else {
+                let mask8 = UnusedEntry::mask(
+                    &lookup_context.unused_entries,
+                    index.cs.domain.d1,
+                    |entry| entry.fixed_part(table_id_combiner),
+                )
+                .interpolate()
+                .evaluate_over_domain(index.cs.domain.d8);
+                &joint_lookup_table_d8 - &mask8
+            };
+
             let joint_lookup_table = joint_lookup_table_d8.interpolate_by_ref();
 
             //~~ - Compute the sorted evaluations.
@@ -470,7 +719,7 @@ where
                 &joint_lookup_table_d8,
                 index.cs.domain.d1,
                 &index.cs.gates,
-                &witness,
+                &witness.columns,
                 joint_combiner,
                 table_id_combiner,
                 &lcs.configuration.lookup_info,
@@ -483,24 +732,22 @@ where
                 .map(|chunk| lookup::constraints::zk_patch(chunk, index.cs.domain.d1, rng))
                 .collect();
 
-            //~~ - Commit each of the sorted polynomials.
+            // precompute different forms of the sorted polynomials for later
+            // TODO: We can avoid storing these coefficients.
+            let sorted_coeffs: Vec<_> = sorted.iter().map(|e| e.clone().interpolate()).collect();
+
+            //~~ - Commit each of the sorted polynomials,
+            //~~   in chunks if the domain is larger than `max_poly_size`.
             let sorted_comms: Vec<_> = sorted
                 .iter()
-                .map(|v| {
-                    index
-                        .srs
-                        .commit_evaluations(index.cs.domain.d1, v, None, rng)
-                })
+                .zip(&sorted_coeffs)
+                .map(|(evals, coeffs)| commit_chunked(index, evals, coeffs, rng))
                 .collect();
 
-            //~~ - Absorb each commitments to the sorted polynomials.
-            sorted_comms
-                .iter()
-                .for_each(|c| fq_sponge.absorb_g(&c.commitment.unshifted));
+            if let Some(lookup) = &mut commitments.lookup {
+                lookup.sorted = sorted_comms.iter().map(|c| c.commitment.clone()).collect();
+            }
 
-            // precompute different forms of the sorted polynomials for later
-            // TODO: We can avoid storing these coefficients.
-            let sorted_coeffs: Vec<_> = sorted.iter().map(|e| e.clone().interpolate()).collect();
             let sorted8: Vec<_> = sorted_coeffs
                 .iter()
                 .map(|v| v.evaluate_over_domain_by_ref(index.cs.domain.d8))
@@ -515,6 +762,9 @@ where
             lookup_context.joint_lookup_table = Some(joint_lookup_table);
         }
 
+        //~ 1. If using lookup, absorb each commitments to the sorted polynomials.
+        commitments.absorb(Round::Sorted, &mut fq_sponge);
+
         //~ 1. Sample $\beta$ with the Fq-Sponge.
         let beta = fq_sponge.challenge();
 
@@ -531,7 +781,7 @@ where
                 joint_lookup_table_d8,
                 index.cs.domain.d1,
                 &index.cs.gates,
-                &witness,
+                &witness.columns,
                 &lookup_context.joint_combiner.unwrap(),
                 &lookup_context.table_id_combiner.unwrap(),
                 beta,
@@ -541,16 +791,17 @@ where
                 &lcs.configuration.lookup_info,
             )?;
 
-            //~~ - Commit to the aggregation polynomial.
-            let aggreg_comm = index
-                .srs
-                .commit_evaluations(index.cs.domain.d1, &aggreg, None, rng);
+            // precompute different forms of the aggregation polynomial for later
+            let aggreg_coeffs = aggreg.interpolate_by_ref();
 
-            //~~ - Absorb the commitment to the aggregation polynomial with the Fq-Sponge.
-            fq_sponge.absorb_g(&aggreg_comm.commitment.unshifted);
+            //~~ - Commit to the aggregation polynomial,
+            //~~   in chunks if the domain is larger than `max_poly_size`.
+            let aggreg_comm = commit_chunked(index, &aggreg, &aggreg_coeffs, rng);
+
+            if let Some(lookup) = &mut commitments.lookup {
+                lookup.aggreg = aggreg_comm.commitment.clone();
+            }
 
-            // precompute different forms of the aggregation polynomial for later
-            let aggreg_coeffs = aggreg.interpolate();
             // TODO: There's probably a clever way to expand the domain without
             // interpolating
             let aggreg8 = aggreg_coeffs.evaluate_over_domain_by_ref(index.cs.domain.d8);
@@ -560,25 +811,32 @@ where
             lookup_context.aggreg8 = Some(aggreg8);
         }
 
+        progress(ProvingPhase::Permutation)?;
+
         //~ 1. Compute the permutation aggregation polynomial $z$.
-        let z_poly = index.cs.perm_aggreg(&witness, &beta, &gamma, rng)?;
+        let z_poly = index.cs.perm_aggreg(&witness.columns, &beta, &gamma, rng)?;
 
         //~ 1. Commit (hidding) to the permutation aggregation polynomial $z$.
         let z_comm = index.srs.commit(&z_poly, None, rng);
 
-        //~ 1. Absorb the permutation aggregation polynomial $z$ with the Fq-Sponge.
-        fq_sponge.absorb_g(&z_comm.commitment.unshifted);
+        //~ 1. If using lookup, absorb the commitment to the aggregation polynomial with the Fq-Sponge,
+        //~    then absorb the permutation aggregation polynomial $z$.
+        commitments.z_comm = z_comm.commitment.clone();
+        commitments.absorb(Round::Permutation, &mut fq_sponge);
 
         //~ 1. Sample $\alpha'$ with the Fq-Sponge.
         let alpha_chal = ScalarChallenge(fq_sponge.challenge());
 
         //~ 1. Derive $\alpha$ from $\alpha'$ using the endomorphism (TODO: details)
-        let alpha: G::ScalarField = alpha_chal.to_field(endo_r);
+        let alpha: G::ScalarField =
+            alpha_chal.to_field_with_limbs(EFqSponge::CHALLENGE_LENGTH_IN_LIMBS, endo_r);
 
         //~ 1. TODO: instantiate alpha?
         let mut all_alphas = index.powers_of_alpha.clone();
         all_alphas.instantiate(alpha);
 
+        progress(ProvingPhase::Quotient)?;
+
         //~ 1. Compute the quotient polynomial (the $t$ in $f = Z_H \cdot t$).
         //~    The quotient polynomial is computed by adding all these polynomials together:
         //~~ - the combined constraints for all the gates
@@ -595,14 +853,17 @@ where
                 sorted: lookup_context.sorted8.as_ref().unwrap(),
                 selectors: &lcs.lookup_selectors,
                 table: joint_lookup_table_d8,
-                runtime_selector: lcs.runtime_selector.as_ref(),
+                runtime_selector: lookup_context
+                    .runtime_selector8
+                    .as_ref()
+                    .or(lcs.runtime_selector.as_ref()),
                 runtime_table: lookup_context.runtime_table_d8.as_ref(),
             })
         } else {
             None
         };
 
-        let lagrange = index.cs.evaluate(&witness_poly, &z_poly);
+        let lagrange = index.cs.evaluate(witness_poly, &z_poly);
         let env = {
             let mut index_evals = HashMap::new();
             use GateType::*;
@@ -611,6 +872,7 @@ where
             index_evals.insert(VarBaseMul, &index.cs.mull8);
             index_evals.insert(EndoMul, &index.cs.emull);
             index_evals.insert(EndoMulScalar, &index.cs.endomul_scalar8);
+            #[cfg(feature = "chacha")]
             [ChaCha0, ChaCha1, ChaCha2, ChaChaFinal]
                 .iter()
                 .enumerate()
@@ -620,6 +882,7 @@ where
                     }
                 });
 
+            #[cfg(feature = "range_check")]
             if let Some(polys) = &index.cs.range_check_selector_polys {
                 index_evals.extend(
                     range_check::gadget::circuit_gates()
@@ -629,6 +892,7 @@ where
                 );
             }
 
+            #[cfg(feature = "foreign_field")]
             if let Some(selector) = index.cs.foreign_field_add_selector_poly.as_ref() {
                 index_evals.extend(
                     foreign_field_add::gadget::circuit_gates()
@@ -638,6 +902,20 @@ where
                 );
             }
 
+            if let Some(selector) = index.cs.copy_cells_selector_poly.as_ref() {
+                index_evals.insert(GateType::CopyCells, &selector.eval8);
+            }
+
+            #[cfg(feature = "keccak")]
+            if let Some(polys) = &index.cs.keccak_selector_polys {
+                index_evals.extend(
+                    keccak::gadget::circuit_gates()
+                        .iter()
+                        .enumerate()
+                        .map(|(i, gate_type)| (*gate_type, &polys[i].eval8)),
+                );
+            }
+
             let mds = &G::sponge_params().mds;
             Environment {
                 constants: Constants {
@@ -650,10 +928,10 @@ where
                     foreign_field_modulus: index.cs.foreign_field_modulus.clone(),
                 },
                 witness: &lagrange.d8.this.w,
-                coefficient: &index.cs.coefficients8,
+                coefficient: index.cs.coefficients8(),
                 vanishes_on_last_4_rows: &index.cs.precomputations().vanishes_on_last_4_rows,
                 z: &lagrange.d8.this.z,
-                l0_1: l0_1(index.cs.domain.d1),
+                l0_1: index.cs.precomputations().l0_1,
                 domain: index.cs.domain,
                 index: index_evals,
                 lookup: lookup_env,
@@ -662,8 +940,10 @@ where
 
         let quotient_poly = {
             // generic
-            let alphas =
-                all_alphas.get_alphas(ArgumentType::Gate(GateType::Generic), generic::CONSTRAINTS);
+            let alphas = all_alphas.get_alphas(
+                ArgumentType::Gate(GateType::Generic),
+                generic::constraints(index.cs.triple_generic),
+            );
             let mut t4 = index.cs.gnrc_quot(alphas, &lagrange.d4.this.w);
 
             if cfg!(debug_assertions) {
@@ -729,6 +1009,7 @@ where
             }
 
             // chacha
+            #[cfg(feature = "chacha")]
             {
                 if index.cs.chacha8.as_ref().is_some() {
                     let chacha0 = ChaCha0::combined_constraints(&all_alphas).evaluations(&env);
@@ -752,6 +1033,7 @@ where
             }
 
             // range check gates
+            #[cfg(feature = "range_check")]
             if index.cs.range_check_selector_polys.is_some() {
                 for gate_type in range_check::gadget::circuit_gates() {
                     let range_check_constraint =
@@ -764,6 +1046,7 @@ where
             }
 
             // foreign field addition
+            #[cfg(feature = "foreign_field")]
             {
                 if index.cs.foreign_field_add_selector_poly.is_some() {
                     let ffadd = foreign_field_add::gadget::combined_constraints(&all_alphas)
@@ -774,6 +1057,26 @@ where
                 }
             }
 
+            // copy gate
+            if index.cs.copy_cells_selector_poly.is_some() {
+                let copy4 = CopyCells::combined_constraints(&all_alphas).evaluations(&env);
+                assert_eq!(copy4.domain().size, t4.domain().size);
+                t4 += &copy4;
+                check_constraint!(index, copy4);
+            }
+
+            // keccak gates
+            #[cfg(feature = "keccak")]
+            if index.cs.keccak_selector_polys.is_some() {
+                for gate_type in keccak::gadget::circuit_gates() {
+                    let keccak4 = keccak::gadget::circuit_gate_constraints(gate_type, &all_alphas)
+                        .evaluations(&env);
+                    assert_eq!(keccak4.domain().size, t4.domain().size);
+                    t4 += &keccak4;
+                    check_constraint!(index, keccak4);
+                }
+            }
+
             // lookup
             {
                 if let Some(lcs) = index.cs.lookup_constraint_system.as_ref() {
@@ -785,9 +1088,7 @@ where
 
                     // as lookup constraints are computed with the expression framework,
                     // each of them can result in Evaluations of different domains
-                    for (ii, (constraint, alpha_pow)) in
-                        constraints.into_iter().zip_eq(lookup_alphas).enumerate()
-                    {
+                    for (constraint, alpha_pow) in constraints.into_iter().zip_eq(lookup_alphas) {
                         let mut eval = constraint.evaluations(&env);
                         eval.evals.par_iter_mut().for_each(|x| *x *= alpha_pow);
 
@@ -799,7 +1100,7 @@ where
                             panic!("Bad evaluation")
                         }
 
-                        check_constraint!(index, format!("lookup constraint #{ii}"), eval);
+                        check_constraint!(index, "lookup", eval);
                     }
                 }
             }
@@ -809,9 +1110,8 @@ where
             f += &public_poly;
 
             // divide contributions with vanishing polynomial
-            let (mut quotient, res) = f
-                .divide_by_vanishing_poly(index.cs.domain.d1)
-                .ok_or(ProverError::Prover("division by vanishing polynomial"))?;
+            let (mut quotient, res) =
+                f.divide_by_vanishing_coset(index.cs.domain.d1, G::ScalarField::one());
             if !res.is_zero() {
                 return Err(ProverError::Prover(
                     "rest of division by vanishing polynomial",
@@ -822,35 +1122,41 @@ where
             quotient
         };
 
-        //~ 1. commit (hiding) to the quotient polynomial $t$
+        //~ 1. commit (hiding) to the quotient polynomial $t$,
+        //~    padded to `PERMUTS` chunks per chunk of the domain (see `max_poly_size`)
         //~    TODO: specify the dummies
         let t_comm = {
             let mut t_comm = index.srs.commit(&quotient_poly, None, rng);
 
-            let expected_t_size = PERMUTS;
+            let num_chunks = (d1_size + index.max_poly_size - 1) / index.max_poly_size;
+            let expected_t_size = PERMUTS * num_chunks;
             let dummies = expected_t_size - t_comm.commitment.unshifted.len();
             // Add `dummies` many hiding commitments to the 0 polynomial, since if the
             // number of commitments in `t_comm` is less than the max size, it means that
             // the higher degree coefficients of `t` are 0.
-            for _ in 0..dummies {
-                let w = <G::ScalarField as UniformRand>::rand(rng);
-                t_comm
-                    .commitment
-                    .unshifted
-                    .push(index.srs.h.mul(w).into_affine());
-                t_comm.blinders.unshifted.push(w);
-            }
+            let blinders: Vec<_> = (0..dummies)
+                .map(|_| <G::ScalarField as UniformRand>::rand(rng))
+                .collect();
+            let masks: Vec<_> = blinders
+                .iter()
+                .map(|w| index.srs.blinding_commitment().mul(*w))
+                .collect();
+            t_comm.commitment.unshifted.extend(batch_normalize(&masks));
+            t_comm.blinders.unshifted.extend(blinders);
             t_comm
         };
 
         //~ 1. Absorb the the commitment of the quotient polynomial with the Fq-Sponge.
-        fq_sponge.absorb_g(&t_comm.commitment.unshifted);
+        commitments.t_comm = t_comm.commitment.clone();
+        commitments.absorb(Round::Quotient, &mut fq_sponge);
 
         //~ 1. Sample $\zeta'$ with the Fq-Sponge.
         let zeta_chal = ScalarChallenge(fq_sponge.challenge());
 
         //~ 1. Derive $\zeta$ from $\zeta'$ using the endomorphism (TODO: specify)
-        let zeta = zeta_chal.to_field(endo_r);
+        let zeta = zeta_chal.to_field_with_limbs(EFqSponge::CHALLENGE_LENGTH_IN_LIMBS, endo_r);
+
+        progress(ProvingPhase::Evaluations)?;
 
         let omega = index.cs.domain.d1.group_gen;
         let zeta_omega = zeta * omega;
@@ -920,15 +1226,21 @@ where
         //~    TODO: do we want to specify more on that? It seems unecessary except for the t polynomial (or if for some reason someone sets that to a low value)
         let chunked_evals = {
             let chunked_evals_zeta = ProofEvaluations::<Vec<G::ScalarField>> {
-                s: array::from_fn(|i| {
-                    index.cs.sigmam[0..PERMUTS - 1][i]
-                        .to_chunked_polynomial(index.max_poly_size)
-                        .evaluate_chunks(zeta)
-                }),
+                s: index.cs.sigmam[0..index.cs.permuted_columns() - 1]
+                    .iter()
+                    .map(|s| {
+                        s.to_chunked_polynomial(index.max_poly_size)
+                            .evaluate_chunks(zeta)
+                    })
+                    .collect(),
                 w: array::from_fn(|i| {
-                    witness_poly[i]
-                        .to_chunked_polynomial(index.max_poly_size)
-                        .evaluate_chunks(zeta)
+                    if index.cs.zero_columns[i] {
+                        vec![]
+                    } else {
+                        witness_poly[i]
+                            .to_chunked_polynomial(index.max_poly_size)
+                            .evaluate_chunks(zeta)
+                    }
                 }),
 
                 z: z_poly
@@ -950,16 +1262,22 @@ where
                     .evaluate_chunks(zeta),
             };
             let chunked_evals_zeta_omega = ProofEvaluations::<Vec<G::ScalarField>> {
-                s: array::from_fn(|i| {
-                    index.cs.sigmam[0..PERMUTS - 1][i]
-                        .to_chunked_polynomial(index.max_poly_size)
-                        .evaluate_chunks(zeta_omega)
-                }),
+                s: index.cs.sigmam[0..index.cs.permuted_columns() - 1]
+                    .iter()
+                    .map(|s| {
+                        s.to_chunked_polynomial(index.max_poly_size)
+                            .evaluate_chunks(zeta_omega)
+                    })
+                    .collect(),
 
                 w: array::from_fn(|i| {
-                    witness_poly[i]
-                        .to_chunked_polynomial(index.max_poly_size)
-                        .evaluate_chunks(zeta_omega)
+                    if index.cs.zero_columns[i] {
+                        vec![]
+                    } else {
+                        witness_poly[i]
+                            .to_chunked_polynomial(index.max_poly_size)
+                            .evaluate_chunks(zeta_omega)
+                    }
                 }),
 
                 z: z_poly
@@ -996,7 +1314,11 @@ where
                 .iter()
                 .zip(power_of_eval_points_for_chunks.iter()) // (zeta , zeta_omega)
                 .map(|(es, &e1)| ProofEvaluations::<G::ScalarField> {
-                    s: array::from_fn(|i| DensePolynomial::eval_polynomial(&es.s[i], e1)),
+                    s: es
+                        .s
+                        .iter()
+                        .map(|s| DensePolynomial::eval_polynomial(s, e1))
+                        .collect(),
                     w: array::from_fn(|i| DensePolynomial::eval_polynomial(&es.w[i], e1)),
                     z: DensePolynomial::eval_polynomial(&es.z, e1),
                     lookup: es.lookup.as_ref().map(|l| LookupEvaluations {
@@ -1027,8 +1349,10 @@ where
                 // the constraint system struct
 
                 // generic (not part of linearization yet)
-                let alphas = all_alphas
-                    .get_alphas(ArgumentType::Gate(GateType::Generic), generic::CONSTRAINTS);
+                let alphas = all_alphas.get_alphas(
+                    ArgumentType::Gate(GateType::Generic),
+                    generic::constraints(index.cs.triple_generic),
+                );
                 let mut f = index
                     .cs
                     .gnrc_lnrz(alphas, &evals[0].w, evals[0].generic_selector)
@@ -1129,19 +1453,21 @@ where
         //~~ - 6 sigmas evaluations (the last one is not evaluated)
         fr_sponge.absorb_multiple(&public_evals[0]);
         fr_sponge.absorb_multiple(&public_evals[1]);
-        fr_sponge.absorb_evaluations([&chunked_evals[0], &chunked_evals[1]]);
+        chunked_evals.absorb(Round::Evaluations, &mut fr_sponge);
 
         //~ 1. Sample $v'$ with the Fr-Sponge
         let v_chal = fr_sponge.challenge();
 
         //~ 1. Derive $v$ from $v'$ using the endomorphism (TODO: specify)
-        let v = v_chal.to_field(endo_r);
+        let v = v_chal.to_field_with_limbs(EFrSponge::CHALLENGE_LENGTH_IN_LIMBS, endo_r);
 
         //~ 1. Sample $u'$ with the Fr-Sponge
         let u_chal = fr_sponge.challenge();
 
         //~ 1. Derive $u$ from $u'$ using the endomorphism (TODO: specify)
-        let u = u_chal.to_field(endo_r);
+        let u = u_chal.to_field_with_limbs(EFrSponge::CHALLENGE_LENGTH_IN_LIMBS, endo_r);
+
+        progress(ProvingPhase::Opening)?;
 
         //~ 1. Create a list of all polynomials that will require evaluations
         //~    (and evaluation proofs) in the protocol.
@@ -1167,8 +1493,8 @@ where
         //~~ - the permutation aggregation polynomial z polynomial
         //~~ - the generic selector
         //~~ - the poseidon selector
-        //~~ - the 15 registers/witness columns
-        //~~ - the 6 sigmas
+        //~~ - the 15 registers/witness columns, but the ones declared zero
+        //~~ - the sigmas, but the last one
         //~~ - optionally, the runtime table
         polynomials.extend(vec![(&public_poly, None, fixed_hiding(1))]);
         polynomials.extend(vec![(&ft, None, blinding_ft)]);
@@ -1179,11 +1505,13 @@ where
             witness_poly
                 .iter()
                 .zip(w_comm.iter())
-                .map(|(w, c)| (w, None, c.blinders.clone()))
+                .zip(index.cs.zero_columns)
+                .filter(|(_, zero)| !zero)
+                .map(|((w, c), _)| (w, None, c.blinders.clone()))
                 .collect::<Vec<_>>(),
         );
         polynomials.extend(
-            index.cs.sigmam[0..PERMUTS - 1]
+            index.cs.sigmam[0..index.cs.permuted_columns() - 1]
                 .iter()
                 .map(|w| (w, None, non_hiding(1)))
                 .collect::<Vec<_>>(),
@@ -1204,20 +1532,24 @@ where
             let aggreg_comm = lookup_context.aggreg_comm.as_ref().unwrap();
             polynomials.push((aggreg_poly, None, aggreg_comm.blinders.clone()));
 
-            //~~ - add the combined table polynomial
-            let table_blinding = if lcs.runtime_selector.is_some() {
+            //~~ - add the combined table polynomial,
+            //~~   with one blinder per chunk of the domain (see `max_poly_size`)
+            let num_chunks = (d1_size + index.max_poly_size - 1) / index.max_poly_size;
+            let mut table_blinding = non_hiding(num_chunks);
+            if lcs.runtime_selector.is_some() {
+                // the runtime table is part of the second column of the table,
+                // so its blinders are scaled by the joint combiner, chunk by chunk
                 let runtime_comm = lookup_context.runtime_table_comm.as_ref().unwrap();
                 let joint_combiner = lookup_context.joint_combiner.as_ref().unwrap();
 
-                let blinding = runtime_comm.blinders.unshifted[0];
-
-                PolyComm {
-                    unshifted: vec![*joint_combiner * blinding],
-                    shifted: None,
+                for (blinding, runtime_blinding) in table_blinding
+                    .unshifted
+                    .iter_mut()
+                    .zip(&runtime_comm.blinders.unshifted)
+                {
+                    *blinding += *joint_combiner * runtime_blinding;
                 }
-            } else {
-                non_hiding(1)
-            };
+            }
 
             let joint_lookup_table = lookup_context.joint_lookup_table.as_ref().unwrap();
 
@@ -1233,7 +1565,8 @@ where
         }
 
         //~ 1. Create an aggregated evaluation proof for all of these polynomials at $\zeta$ and $\zeta\omega$ using $u$ and $v$.
-        let proof = index.srs.open(
+        let proof = PolynomialCommitment::open(
+            &*index.srs,
             group_map,
             &polynomials,
             &[zeta, zeta_omega],
@@ -1243,22 +1576,8 @@ where
             rng,
         );
 
-        let lookup = lookup_context
-            .aggreg_comm
-            .zip(lookup_context.sorted_comms)
-            .map(|(a, s)| LookupCommitments {
-                aggreg: a.commitment,
-                sorted: s.iter().map(|c| c.commitment.clone()).collect(),
-                runtime: lookup_context.runtime_table_comm.map(|x| x.commitment),
-            });
-
         Ok(Self {
-            commitments: ProverCommitments {
-                w_comm: array::from_fn(|i| w_comm[i].commitment.clone()),
-                z_comm: z_comm.commitment,
-                t_comm: t_comm.commitment,
-                lookup,
-            },
+            commitments,
             proof,
             evals: chunked_evals,
             ft_eval1,
@@ -1299,6 +1618,7 @@ pub mod caml {
         pub sorted: Vec<CamlPolyComm<CamlG>>,
         pub aggreg: CamlPolyComm<CamlG>,
         pub runtime: Option<CamlPolyComm<CamlG>>,
+        pub runtime_lens: Vec<ocaml::Int>,
     }
 
     #[allow(clippy::type_complexity)]
@@ -1356,12 +1676,17 @@ pub mod caml {
                 aggreg,
                 sorted,
                 runtime,
+                runtime_lens,
             }: LookupCommitments<G>,
         ) -> Self {
             Self {
                 aggreg: aggreg.into(),
                 sorted: sorted.into_iter().map(Into::into).collect(),
                 runtime: runtime.map(Into::into),
+                runtime_lens: runtime_lens
+                    .into_iter()
+                    .map(|len| len.try_into().expect("usize -> isize"))
+                    .collect(),
             }
         }
     }
@@ -1376,12 +1701,17 @@ pub mod caml {
                 aggreg,
                 sorted,
                 runtime,
+                runtime_lens,
             }: CamlLookupCommitments<CamlG>,
         ) -> LookupCommitments<G> {
             LookupCommitments {
                 aggreg: aggreg.into(),
                 sorted: sorted.into_iter().map(Into::into).collect(),
                 runtime: runtime.map(Into::into),
+                runtime_lens: runtime_lens
+                    .into_iter()
+                    .map(|len| len.try_into().expect("isize -> usize"))
+                    .collect(),
             }
         }
     }
@@ -1477,7 +1807,7 @@ pub mod caml {
 
     impl<G, CamlG, CamlF> From<ProverProof<G>> for CamlProverProof<CamlG, CamlF>
     where
-        G: AffineCurve,
+        G: CommitmentCurve,
         CamlG: From<G>,
         CamlF: From<G::ScalarField>,
     {
@@ -1495,7 +1825,7 @@ pub mod caml {
 
     impl<G, CamlG, CamlF> From<CamlProverProof<CamlG, CamlF>> for ProverProof<G>
     where
-        G: AffineCurve + From<CamlG>,
+        G: CommitmentCurve + From<CamlG>,
         G::ScalarField: From<CamlF>,
     {
         fn from(caml_pp: CamlProverProof<CamlG, CamlF>) -> ProverProof<G> {
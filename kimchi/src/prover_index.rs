@@ -12,17 +12,18 @@ use crate::{
     verifier_index::VerifierIndex,
 };
 use ark_poly::EvaluationDomain;
-use commitment_dlog::srs::SRS;
+use commitment_dlog::{scheme::PolynomialCommitment, srs::SRS};
 use oracle::FqSponge;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_with::serde_as;
 use std::sync::Arc;
 
-/// The index used by the prover
+/// The index used by the prover, committing with the polynomial commitment scheme `S`
+/// (by default, the inner product argument of the [`SRS`])
 #[serde_as]
 #[derive(Serialize, Deserialize, Debug)]
 //~spec:startcode
-pub struct ProverIndex<G: KimchiCurve> {
+pub struct ProverIndex<G: KimchiCurve, S = SRS<G>> {
     /// constraints system polynomials
     #[serde(bound = "ConstraintSystem<G::ScalarField>: Serialize + DeserializeOwned")]
     pub cs: ConstraintSystem<G::ScalarField>,
@@ -37,7 +38,7 @@ pub struct ProverIndex<G: KimchiCurve> {
 
     /// polynomial commitment keys
     #[serde(skip)]
-    pub srs: Arc<SRS<G>>,
+    pub srs: Arc<S>,
 
     /// maximal size of polynomial section
     pub max_poly_size: usize,
@@ -47,15 +48,22 @@ pub struct ProverIndex<G: KimchiCurve> {
 
     /// The verifier index corresponding to this prover index
     #[serde(skip)]
-    pub verifier_index: Option<VerifierIndex<G>>,
+    pub verifier_index: Option<VerifierIndex<G, S>>,
 
     /// The verifier index digest corresponding to this prover index
     #[serde_as(as = "Option<o1_utils::serialization::SerdeAs>")]
     pub verifier_index_digest: Option<G::BaseField>,
+
+    /// Whether the prover checks the witness against the gates, the permutation and the lookups
+    /// before creating a proof, failing fast with the row where the witness is incorrect
+    /// instead of creating a proof that does not verify.
+    /// The witness is always checked in debug builds.
+    #[serde(skip)]
+    pub check_witness: bool,
 }
 //~spec:endcode
 
-impl<G: KimchiCurve> ProverIndex<G> {
+impl<G: KimchiCurve, S: PolynomialCommitment<G>> ProverIndex<G, S> {
     /// this function compiles the index from constraints
     ///
     /// # Panics
@@ -64,9 +72,9 @@ impl<G: KimchiCurve> ProverIndex<G> {
     pub fn create(
         mut cs: ConstraintSystem<G::ScalarField>,
         endo_q: G::ScalarField,
-        srs: Arc<SRS<G>>,
+        srs: Arc<S>,
     ) -> Self {
-        let max_poly_size = srs.g.len();
+        let max_poly_size = srs.max_poly_size();
         if cs.public > 0 {
             assert!(
                 max_poly_size >= cs.domain.d1.size(),
@@ -76,14 +84,7 @@ impl<G: KimchiCurve> ProverIndex<G> {
         cs.endo = endo_q;
 
         // pre-compute the linearization
-        let (linearization, powers_of_alpha) = expr_linearization(
-            cs.chacha8.is_some(),
-            cs.range_check_selector_polys.is_some(),
-            cs.lookup_constraint_system
-                .as_ref()
-                .map(|lcs| &lcs.configuration),
-            cs.foreign_field_add_selector_poly.is_some(),
-        );
+        let (linearization, powers_of_alpha) = Self::linearization(&cs);
 
         // set `max_quot_size` to the degree of the quotient polynomial,
         // which is obtained by looking at the highest monomial in the sum
@@ -100,9 +101,45 @@ impl<G: KimchiCurve> ProverIndex<G> {
             max_quot_size,
             verifier_index: None,
             verifier_index_digest: None,
+            check_witness: false,
         }
     }
 
+    /// Returns the linearization of the constraints of `cs`, and the mapping between powers of alpha and constraints.
+    pub(crate) fn linearization(
+        cs: &ConstraintSystem<G::ScalarField>,
+    ) -> (
+        Linearization<Vec<PolishToken<G::ScalarField>>>,
+        Alphas<G::ScalarField>,
+    ) {
+        #[cfg(feature = "chacha")]
+        let chacha = cs.chacha8.is_some();
+        #[cfg(not(feature = "chacha"))]
+        let chacha = false;
+        #[cfg(feature = "range_check")]
+        let range_check = cs.range_check_selector_polys.is_some();
+        #[cfg(not(feature = "range_check"))]
+        let range_check = false;
+        #[cfg(feature = "foreign_field")]
+        let foreign_field_add = cs.foreign_field_add_selector_poly.is_some();
+        #[cfg(not(feature = "foreign_field"))]
+        let foreign_field_add = false;
+        #[cfg(feature = "keccak")]
+        let keccak = cs.keccak_selector_polys.is_some();
+        #[cfg(not(feature = "keccak"))]
+        let keccak = false;
+        expr_linearization(
+            chacha,
+            range_check,
+            cs.lookup_constraint_system
+                .as_ref()
+                .map(|lcs| &lcs.configuration),
+            foreign_field_add,
+            cs.copy_cells_selector_poly.is_some(),
+            keccak,
+        )
+    }
+
     /// Retrieve or compute the digest for the corresponding verifier index.
     /// If the digest is not already cached inside the index, store it.
     pub fn compute_verifier_index_digest<
@@ -0,0 +1,161 @@
+//! This module implements the [`ProofStream`], the proofs of a stream of witnesses,
+//! for services that continuously produce proofs against a same index (e.g. sequencers).
+//!
+//! A stream is started with [`ProverProof::create_stream`], from an iterator of [`ProvingJob`]s.
+//! A bounded number of worker threads take the jobs from the iterator, one at a time
+//! and only once they are free, so that the iterator can produce the witnesses on demand.
+//! The stream is an iterator of the proofs, tagged with the position of their job,
+//! that yields them as they are created (and thus not necessarily in the order of the jobs).
+//!
+//! The proofs not consumed yet are buffered up to the number of workers:
+//! past that, the workers wait for the proofs to be consumed before taking new jobs.
+//! Dropping the stream stops the workers once their current proofs are created,
+//! and cancelling it (see [`CancellationToken`]) also stops these proofs at the start of their next phase.
+
+use crate::{
+    circuits::{lookup::runtime_tables::RuntimeTable, wires::COLUMNS},
+    curve::KimchiCurve,
+    error::ProverError,
+    plonk_sponge::FrSponge,
+    proof::{ProverProof, RecursionChallenge},
+    prover_index::ProverIndex,
+    prover_task::{CancellationToken, ProvingPhase},
+};
+use ark_ff::PrimeField;
+use oracle::FqSponge;
+use std::{
+    sync::{
+        mpsc::{sync_channel, Receiver},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+/// The inputs of a proof of a [`ProofStream`]
+pub struct ProvingJob<G: KimchiCurve> {
+    /// the witness of the proof
+    pub witness: [Vec<G::ScalarField>; COLUMNS],
+    /// the runtime tables of the proof, if the circuit uses some
+    pub runtime_tables: Vec<RuntimeTable<G::ScalarField>>,
+    /// the challenges of the previous proofs, for recursive proving
+    pub prev_challenges: Vec<RecursionChallenge<G>>,
+    /// the application context the proof is bound to, if any (see [`ProverProof::create_with_context`])
+    pub context: Vec<u8>,
+}
+
+impl<G: KimchiCurve> ProvingJob<G> {
+    /// Creates the job of a proof of `witness`, without runtime tables, previous challenges nor context.
+    pub fn new(witness: [Vec<G::ScalarField>; COLUMNS]) -> Self {
+        Self {
+            witness,
+            runtime_tables: vec![],
+            prev_challenges: vec![],
+            context: vec![],
+        }
+    }
+}
+
+/// The proofs of a stream of witnesses (see the module documentation)
+pub struct ProofStream<G: KimchiCurve> {
+    proofs: Receiver<(usize, Result<ProverProof<G>, ProverError>)>,
+    token: CancellationToken,
+}
+
+impl<G: KimchiCurve> ProofStream<G> {
+    /// Returns the token cancelling the stream.
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    /// Cancels the stream: the workers stop taking jobs,
+    /// and their current proofs give the [`ProverError::Cancelled`] error
+    /// unless they are already created.
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+}
+
+impl<G: KimchiCurve> Iterator for ProofStream<G> {
+    type Item = (usize, Result<ProverProof<G>, ProverError>);
+
+    /// Waits for the next proof to be created, and returns it along with the position of its job,
+    /// or `None` once the jobs are all proven (or the stream cancelled).
+    fn next(&mut self) -> Option<Self::Item> {
+        self.proofs.recv().ok()
+    }
+}
+
+impl<G: KimchiCurve> ProverProof<G>
+where
+    G: Send + Sync + 'static,
+    G::BaseField: PrimeField,
+    G::Map: Clone + Send + 'static,
+{
+    /// Starts creating the proofs of `jobs` against `index`, on `workers` threads (at least one),
+    /// as [`ProverProof::create_with_context`] does.
+    /// The jobs are not taken from the iterator once `token` is cancelled.
+    pub fn create_stream<
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField> + 'static,
+        EFrSponge: FrSponge<G::ScalarField> + 'static,
+        I: IntoIterator<Item = ProvingJob<G>>,
+    >(
+        group_map: &G::Map,
+        jobs: I,
+        index: Arc<ProverIndex<G>>,
+        workers: usize,
+        token: CancellationToken,
+    ) -> ProofStream<G>
+    where
+        I::IntoIter: Send + 'static,
+    {
+        let workers = workers.max(1);
+        let jobs = Arc::new(Mutex::new(jobs.into_iter().enumerate()));
+        let (sender, proofs) = sync_channel(workers);
+
+        for _ in 0..workers {
+            let group_map = group_map.clone();
+            let jobs = jobs.clone();
+            let index = index.clone();
+            let token = token.clone();
+            let sender = sender.clone();
+            thread::spawn(move || {
+                let mut progress = |_: ProvingPhase| {
+                    if token.is_cancelled() {
+                        Err(ProverError::Cancelled)
+                    } else {
+                        Ok(())
+                    }
+                };
+                while !token.is_cancelled() {
+                    // the workers stop if the iterator of the jobs panicked
+                    let job = match jobs.lock() {
+                        Ok(mut jobs) => jobs.next(),
+                        Err(_) => None,
+                    };
+                    let (position, job) = match job {
+                        Some(job) => job,
+                        None => break,
+                    };
+
+                    let proof = Self::create_with_progress::<EFqSponge, EFrSponge>(
+                        &group_map,
+                        job.witness,
+                        &job.runtime_tables,
+                        &index,
+                        job.prev_challenges,
+                        &job.context,
+                        &mut progress,
+                    );
+                    // a closed channel means that the stream was dropped
+                    if sender.send((position, proof)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        // the stream ends once the workers are done
+        drop(sender);
+
+        ProofStream { proofs, token }
+    }
+}
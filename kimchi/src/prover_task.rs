@@ -0,0 +1,266 @@
+//! This module implements the [`ProvingTask`], a proof created in the background,
+//! for services that embed the prover and need to report its progress or abandon it.
+//!
+//! A task is started with [`ProverProof::create_async`], which runs the prover on a thread of its own.
+//! The task is a [`Future`] of the proof, that does not depend on any async runtime.
+//! While the proof is created, the task yields a [`ProgressEvent`] at the start of each [`ProvingPhase`],
+//! with the share of the work done so far and an estimate of the time left.
+//!
+//! The task is cancelled through its [`CancellationToken`]: the prover then stops
+//! at the start of its next phase, and the task gives the [`ProverError::Cancelled`] error.
+//! If the prover panics, the task gives an error instead of never completing.
+
+use crate::{
+    circuits::{lookup::runtime_tables::RuntimeTable, wires::COLUMNS},
+    curve::KimchiCurve,
+    error::ProverError,
+    plonk_sponge::FrSponge,
+    proof::{ProverProof, RecursionChallenge},
+    prover::check_srs_size,
+    prover_index::ProverIndex,
+    prover_witness::ProverWitness,
+};
+use ark_ff::PrimeField;
+use oracle::FqSponge;
+use std::{
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, Receiver},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Waker},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// The phases of the creation of a proof, in order
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ProvingPhase {
+    /// padding, committing to and interpolating the witness
+    Witness,
+    /// computing the lookup polynomials, if lookups are used
+    Lookup,
+    /// computing the permutation aggregation polynomial
+    Permutation,
+    /// computing the quotient polynomial
+    Quotient,
+    /// evaluating the polynomials at the evaluation points
+    Evaluations,
+    /// creating the opening proof
+    Opening,
+}
+
+impl ProvingPhase {
+    /// Returns the share of the work of a proof done before the phase starts, in percent.
+    /// The shares are rough estimates, the quotient and the opening proof taking the most time.
+    pub fn percent(self) -> u8 {
+        match self {
+            ProvingPhase::Witness => 0,
+            ProvingPhase::Lookup => 15,
+            ProvingPhase::Permutation => 20,
+            ProvingPhase::Quotient => 30,
+            ProvingPhase::Evaluations => 70,
+            ProvingPhase::Opening => 80,
+        }
+    }
+}
+
+/// An event of the progress of a [`ProvingTask`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProgressEvent {
+    /// the phase that starts, or `None` once the proof is created
+    pub phase: Option<ProvingPhase>,
+    /// the share of the work done so far, in percent
+    pub percent: u8,
+    /// the estimated time left, from the time spent so far
+    /// (`None` until some of the work is done)
+    pub eta: Option<Duration>,
+}
+
+impl ProgressEvent {
+    fn new(phase: Option<ProvingPhase>, start: Instant) -> Self {
+        let percent = phase.map_or(100, ProvingPhase::percent);
+        let eta = (percent > 0).then(|| {
+            let elapsed = start.elapsed();
+            elapsed.mul_f64(f64::from(100 - percent) / f64::from(percent))
+        });
+        Self {
+            phase,
+            percent,
+            eta,
+        }
+    }
+}
+
+/// A token to cancel a [`ProvingTask`], which can be shared with the code that decides to cancel it
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Creates a token, not cancelled yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the tasks of the token.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if the token was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// The result of a task, and the waker of its future until the result is known
+struct TaskState<G: KimchiCurve> {
+    result: Option<Result<ProverProof<G>, ProverError>>,
+    waker: Option<Waker>,
+}
+
+/// A proof created in the background (see the module documentation)
+pub struct ProvingTask<G: KimchiCurve> {
+    state: Arc<Mutex<TaskState<G>>>,
+    events: Receiver<ProgressEvent>,
+    token: CancellationToken,
+}
+
+impl<G: KimchiCurve> ProvingTask<G> {
+    /// Returns the progress events yielded since the last call, without waiting for new ones.
+    pub fn events(&self) -> impl Iterator<Item = ProgressEvent> + '_ {
+        self.events.try_iter()
+    }
+
+    /// Returns the token cancelling the task.
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    /// Cancels the task, which then gives the [`ProverError::Cancelled`] error
+    /// unless the proof is already created.
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+}
+
+impl<G: KimchiCurve> Future for ProvingTask<G> {
+    type Output = Result<ProverProof<G>, ProverError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.lock().expect("the prover thread does not panic");
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<G: KimchiCurve> ProverProof<G>
+where
+    G: Send + Sync + 'static,
+    G::BaseField: PrimeField,
+    G::Map: Clone + Send + 'static,
+{
+    /// Starts creating a proof in the background, as [`ProverProof::create_recursive`] does,
+    /// with the progress events and the cancellation of the returned [`ProvingTask`].
+    /// The proof is not created if `token` is cancelled first.
+    pub fn create_async<
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField> + 'static,
+        EFrSponge: FrSponge<G::ScalarField> + 'static,
+    >(
+        group_map: &G::Map,
+        witness: [Vec<G::ScalarField>; COLUMNS],
+        runtime_tables: Vec<RuntimeTable<G::ScalarField>>,
+        index: Arc<ProverIndex<G>>,
+        prev_challenges: Vec<RecursionChallenge<G>>,
+        token: CancellationToken,
+    ) -> ProvingTask<G> {
+        let state = Arc::new(Mutex::new(TaskState {
+            result: None,
+            waker: None,
+        }));
+        let (sender, events) = channel();
+
+        let group_map = group_map.clone();
+        let task_state = state.clone();
+        let task_token = token.clone();
+        thread::spawn(move || {
+            let start = Instant::now();
+            // a closed channel only means that nobody listens to the events
+            let mut progress = |phase| {
+                if task_token.is_cancelled() {
+                    return Err(ProverError::Cancelled);
+                }
+                let _ = sender.send(ProgressEvent::new(Some(phase), start));
+                Ok(())
+            };
+
+            let result = Self::create_with_progress::<EFqSponge, EFrSponge>(
+                &group_map,
+                witness,
+                &runtime_tables,
+                &index,
+                prev_challenges,
+                &[],
+                &mut progress,
+            );
+            if result.is_ok() {
+                let _ = sender.send(ProgressEvent::new(None, start));
+            }
+
+            let mut state = task_state.lock().expect("the future does not panic");
+            state.result = Some(result);
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        });
+
+        ProvingTask {
+            state,
+            events,
+            token,
+        }
+    }
+
+    /// Creates a proof as [`ProverProof::create_with_context`] does, calling `progress`
+    /// at the start of each phase, and giving an error if the prover panics.
+    pub(crate) fn create_with_progress<
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+        EFrSponge: FrSponge<G::ScalarField>,
+    >(
+        group_map: &G::Map,
+        witness: [Vec<G::ScalarField>; COLUMNS],
+        runtime_tables: &[RuntimeTable<G::ScalarField>],
+        index: &ProverIndex<G>,
+        prev_challenges: Vec<RecursionChallenge<G>>,
+        context: &[u8],
+        progress: &mut dyn FnMut(ProvingPhase) -> Result<(), ProverError>,
+    ) -> Result<Self, ProverError> {
+        panic::catch_unwind(AssertUnwindSafe(|| {
+            progress(ProvingPhase::Witness)
+                .and_then(|_| {
+                    check_srs_size(index)?;
+                    ProverWitness::create(index, witness)
+                })
+                .and_then(|witness| {
+                    Self::create_from_witness::<EFqSponge, EFrSponge>(
+                        group_map,
+                        &witness,
+                        runtime_tables,
+                        index,
+                        prev_challenges,
+                        context,
+                        progress,
+                    )
+                })
+        }))
+        .unwrap_or(Err(ProverError::Prover("the prover panicked")))
+    }
+}
@@ -0,0 +1,395 @@
+//! This module implements the [`ProverWitness`], the witness of a proof
+//! along with its commitments and polynomials.
+//!
+//! Applications that repeatedly prove near-identical witnesses
+//! can [update](ProverWitness::update) a [`ProverWitness`] with a new witness,
+//! and create their proofs with [`ProverProof::create_incremental`](crate::proof::ProverProof::create_incremental).
+//! Only the contribution of the changed rows is then added to the commitments and polynomials
+//! of the witness columns, instead of recomputing them from scratch.
+//!
+//! The zero-knowledge rows and the blinders of the commitments
+//! are refreshed before each proof, so that no randomness is shared between proofs.
+//!
+//! The columns declared zero by the index are left as they are:
+//! they have no zero-knowledge rows, and an empty commitment.
+//!
+//! The witness can also be committed to before it is interpolated:
+//! [`ProverWitness::commit`] pads the witness and commits to its columns with fresh randomness,
+//! giving a [`CommittedWitness`] whose commitments are known before the FFTs of the columns.
+//! It is then [interpolated](CommittedWitness::interpolate) into a [`ProverWitness`],
+//! and proven with [`ProverProof::create_incremental`](crate::proof::ProverProof::create_incremental).
+//!
+//! This is not a split of the prover into a secret-dependent phase and a secret-free one:
+//! every step of the proof after the commitments (the permutation aggregation, the quotient,
+//! the evaluations and the opening proof) is computed from the witness or the blinders,
+//! so that no input of these steps can leave the process holding the secrets.
+//! The [`CommittedWitness`] thus holds the padded witness and the blinders:
+//! it is not serializable, nor cloneable, and its randomness is used by a single proof.
+
+use crate::{
+    circuits::{polynomials::permutation::ZK_ROWS, wires::COLUMNS},
+    curve::KimchiCurve,
+    error::ProverError,
+    prover_index::ProverIndex,
+};
+use ark_ec::{msm::VariableBaseMSM, ProjectiveCurve};
+use ark_ff::{Field, PrimeField, UniformRand, Zero};
+use ark_poly::{
+    univariate::DensePolynomial, EvaluationDomain, Evaluations, Radix2EvaluationDomain as D,
+    UVPolynomial,
+};
+use commitment_dlog::{
+    commitment::{BlindedCommitment, PolyComm},
+    scheme::PolynomialCommitment,
+};
+use o1_utils::math;
+use rand::{CryptoRng, RngCore};
+use std::array;
+
+/// The result of a witness creation or update.
+type Result<T> = std::result::Result<T, ProverError>;
+
+/// The witness of a proof, padded to the domain size with its zero-knowledge rows,
+/// along with the commitments and polynomials of its columns.
+pub struct ProverWitness<G: KimchiCurve> {
+    /// the length of the witness, without padding
+    length: usize,
+    /// the witness columns, padded to the domain size
+    pub(crate) columns: [Vec<G::ScalarField>; COLUMNS],
+    /// the non-hiding commitments to the witness columns
+    pub(crate) non_hiding: [PolyComm<G>; COLUMNS],
+    /// the hiding commitments to the witness columns
+    pub(crate) comm: [BlindedCommitment<G>; COLUMNS],
+    /// the witness polynomials
+    pub(crate) polys: [DensePolynomial<G::ScalarField>; COLUMNS],
+    /// whether the randomness of the witness was used by a proof
+    used: bool,
+}
+
+impl<G: KimchiCurve> ProverWitness<G> {
+    /// Pads the witness and computes the commitments and polynomials of its columns.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the witness columns are not all the same size,
+    /// if there is no room for the zero-knowledge rows in the domain,
+    /// or if a column declared zero by the index is not.
+    pub fn create<S: PolynomialCommitment<G>>(
+        index: &ProverIndex<G, S>,
+        witness: [Vec<G::ScalarField>; COLUMNS],
+    ) -> Result<Self> {
+        Self::create_with_blinders(index, witness, None, &mut rand::rngs::OsRng)
+    }
+
+    /// Same as [`ProverWitness::create`], but blinds the commitments with `blinders` when given.
+    pub(crate) fn create_with_blinders<S: PolynomialCommitment<G>>(
+        index: &ProverIndex<G, S>,
+        witness: [Vec<G::ScalarField>; COLUMNS],
+        blinders: Option<&[Option<PolyComm<G::ScalarField>>; COLUMNS]>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<Self> {
+        Self::commit_with_blinders(index, witness, blinders, rng)?.interpolate(index)
+    }
+
+    /// Pads the witness and computes the commitments of its columns,
+    /// leaving their interpolation to [`CommittedWitness::interpolate`].
+    ///
+    /// # Errors
+    ///
+    /// Will give error in the same cases as [`ProverWitness::create`].
+    pub fn commit<S: PolynomialCommitment<G>>(
+        index: &ProverIndex<G, S>,
+        witness: [Vec<G::ScalarField>; COLUMNS],
+    ) -> Result<CommittedWitness<G>> {
+        Self::commit_with_blinders(index, witness, None, &mut rand::rngs::OsRng)
+    }
+
+    /// Same as [`ProverWitness::commit`], but blinds the commitments with `blinders` when given.
+    fn commit_with_blinders<S: PolynomialCommitment<G>>(
+        index: &ProverIndex<G, S>,
+        mut witness: [Vec<G::ScalarField>; COLUMNS],
+        blinders: Option<&[Option<PolyComm<G::ScalarField>>; COLUMNS]>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<CommittedWitness<G>> {
+        let d1 = index.cs.domain.d1;
+        let length = witness[0].len();
+        let length_padding = d1
+            .size()
+            .checked_sub(length)
+            .ok_or(ProverError::NoRoomForZkInWitness)?;
+
+        if length_padding < ZK_ROWS as usize {
+            return Err(ProverError::NoRoomForZkInWitness);
+        }
+
+        for (col, w) in witness.iter_mut().enumerate() {
+            if w.len() != length {
+                return Err(ProverError::WitnessCsInconsistent);
+            }
+
+            // padding
+            w.extend(std::iter::repeat(G::ScalarField::zero()).take(length_padding));
+
+            // the columns declared zero are not hidden
+            if index.cs.zero_columns[col] {
+                if w.iter().any(|cell| !cell.is_zero()) {
+                    return Err(ProverError::NonZeroColumn(col));
+                }
+                continue;
+            }
+
+            // zk-rows
+            for row in w.iter_mut().rev().take(ZK_ROWS as usize) {
+                *row = <G::ScalarField as UniformRand>::rand(rng);
+            }
+        }
+
+        let non_hiding: [_; COLUMNS] = array::from_fn(|col| {
+            if index.cs.zero_columns[col] {
+                PolyComm {
+                    unshifted: vec![],
+                    shifted: None,
+                }
+            } else {
+                let evals = Evaluations::<G::ScalarField, D<G::ScalarField>>::from_vec_and_domain(
+                    witness[col].clone(),
+                    d1,
+                );
+                index.srs.commit_evaluations_non_hiding(d1, &evals, None)
+            }
+        });
+
+        let mut comm = vec![];
+        for (col, com) in non_hiding.iter().enumerate() {
+            let com = match blinders.and_then(|b| b[col].as_ref()) {
+                // zero columns: nothing to blind
+                _ if index.cs.zero_columns[col] => BlindedCommitment {
+                    commitment: com.clone(),
+                    blinders: PolyComm {
+                        unshifted: vec![],
+                        shifted: None,
+                    },
+                },
+                // no blinders: blind the witness
+                None => index.srs.mask(com.clone(), rng),
+                // blinders: blind the witness with them
+                Some(blinder) => index
+                    .srs
+                    .mask_custom(com.clone(), blinder)
+                    .map_err(ProverError::WrongBlinders)?,
+            };
+            comm.push(com);
+        }
+        let (comm, blinders): (Vec<_>, Vec<_>) = comm
+            .into_iter()
+            .map(|com| (com.commitment, com.blinders))
+            .unzip();
+        let comm = comm
+            .try_into()
+            .expect("previous loop is of the correct length");
+        let blinders = blinders
+            .try_into()
+            .expect("previous loop is of the correct length");
+
+        Ok(CommittedWitness {
+            length,
+            columns: witness,
+            non_hiding,
+            comm,
+            blinders,
+        })
+    }
+
+    /// Replaces the witness with `witness`, which must be of the same length.
+    /// Only the rows that changed contribute to the cost of the update.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the witness columns are not of the length of the previous witness,
+    /// or if a column declared zero by the index is not.
+    pub fn update<S: PolynomialCommitment<G>>(
+        &mut self,
+        index: &ProverIndex<G, S>,
+        witness: &[Vec<G::ScalarField>; COLUMNS],
+    ) -> Result<()> {
+        if witness.iter().any(|w| w.len() != self.length) {
+            return Err(ProverError::WitnessCsInconsistent);
+        }
+        for (col, w) in witness.iter().enumerate() {
+            if index.cs.zero_columns[col] && w.iter().any(|cell| !cell.is_zero()) {
+                return Err(ProverError::NonZeroColumn(col));
+            }
+        }
+
+        let changes = array::from_fn(|col| {
+            witness[col]
+                .iter()
+                .zip(&self.columns[col])
+                .enumerate()
+                .filter(|(_, (new, old))| new != old)
+                .map(|(row, (new, _))| (row, *new))
+                .collect()
+        });
+        self.apply(index, changes, &mut rand::rngs::OsRng);
+        self.used = false;
+
+        Ok(())
+    }
+
+    /// Returns the witness without its padding.
+    pub fn witness(&self) -> [Vec<G::ScalarField>; COLUMNS] {
+        array::from_fn(|col| self.columns[col][..self.length].to_vec())
+    }
+
+    /// Marks the randomness of the witness as used by a proof,
+    /// refreshing it first if a previous proof used it.
+    pub(crate) fn use_randomness<S: PolynomialCommitment<G>>(
+        &mut self,
+        index: &ProverIndex<G, S>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) {
+        if self.used {
+            self.apply(index, array::from_fn(|_| vec![]), rng);
+        }
+        self.used = true;
+    }
+
+    /// Sets the `changes` (rows and values) of each column,
+    /// as well as fresh zero-knowledge rows and blinders,
+    /// and updates the commitments and polynomials accordingly.
+    fn apply<S: PolynomialCommitment<G>>(
+        &mut self,
+        index: &ProverIndex<G, S>,
+        mut changes: [Vec<(usize, G::ScalarField)>; COLUMNS],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) {
+        let d1 = index.cs.domain.d1;
+        let n = d1.size();
+        let log_n = math::ceil_log2(n);
+        let basis = index.srs.lagrange_basis(d1);
+
+        for (col, changes) in changes.iter_mut().enumerate() {
+            // the columns declared zero never change
+            if index.cs.zero_columns[col] {
+                continue;
+            }
+
+            // fresh zero-knowledge rows
+            changes.extend((n - ZK_ROWS as usize..n).map(|row| (row, G::ScalarField::rand(rng))));
+
+            let deltas: Vec<_> = changes
+                .iter()
+                .map(|(row, value)| {
+                    let delta = *value - self.columns[col][*row];
+                    self.columns[col][*row] = *value;
+                    (*row, delta)
+                })
+                .collect();
+
+            // the commitment of a column is the MSM of its values with the Lagrange bases,
+            // so it changes by the MSM of the deltas with the bases of their rows
+            // (which span a single chunk)
+            if let Some(basis) = basis.filter(|_| deltas.len() * log_n < n) {
+                let bases: Vec<_> = deltas.iter().map(|(row, _)| basis[*row]).collect();
+                let scalars: Vec<_> = deltas.iter().map(|(_, d)| d.into_repr()).collect();
+                let delta = VariableBaseMSM::multi_scalar_mul(&bases, &scalars);
+                let com = &mut self.non_hiding[col].unshifted[0];
+                *com = (com.into_projective() + delta).into_affine();
+            } else {
+                let evals = Evaluations::from_vec_and_domain(self.columns[col].clone(), d1);
+                self.non_hiding[col] = index.srs.commit_evaluations_non_hiding(d1, &evals, None);
+            }
+            self.comm[col] = index.srs.mask(self.non_hiding[col].clone(), rng);
+
+            // similarly, the polynomial of a column changes by the deltas times
+            // the Lagrange polynomials L_i(X) = 1/n sum_j (w^-i X)^j of their rows
+            if deltas.len() < log_n {
+                let mut coeffs = std::mem::take(&mut self.polys[col].coeffs);
+                coeffs.resize(n, G::ScalarField::zero());
+                for (row, delta) in deltas {
+                    let step = d1.group_gen_inv.pow([row as u64]);
+                    let mut acc = delta * d1.size_inv;
+                    for coeff in &mut coeffs {
+                        *coeff += acc;
+                        acc *= step;
+                    }
+                }
+                self.polys[col] = DensePolynomial::from_coefficients_vec(coeffs);
+            } else {
+                self.polys[col] =
+                    Evaluations::from_vec_and_domain(self.columns[col].clone(), d1).interpolate();
+            }
+        }
+    }
+}
+
+/// The witness of a proof, padded and committed to, but not interpolated yet
+/// (see the module documentation).
+pub struct CommittedWitness<G: KimchiCurve> {
+    /// the length of the witness, without padding
+    length: usize,
+    /// the witness columns, padded to the domain size
+    columns: [Vec<G::ScalarField>; COLUMNS],
+    /// the non-hiding commitments to the witness columns
+    non_hiding: [PolyComm<G>; COLUMNS],
+    /// the hiding commitments to the witness columns
+    comm: [PolyComm<G>; COLUMNS],
+    /// the blinders of the hiding commitments
+    blinders: [PolyComm<G::ScalarField>; COLUMNS],
+}
+
+impl<G: KimchiCurve> CommittedWitness<G> {
+    /// Returns the hiding commitments to the witness columns, as they appear in the proof.
+    pub fn commitments(&self) -> &[PolyComm<G>; COLUMNS] {
+        &self.comm
+    }
+
+    /// Interpolates the witness columns, giving the [`ProverWitness`] to prove against `index`.
+    /// The committed witness is consumed, so that its randomness is only used by the next proof
+    /// of the [`ProverWitness`], any later proof refreshing it.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the witness was not padded to the domain size of `index`,
+    /// or if its commitments are not of the shape given by `index`.
+    pub fn interpolate<S>(self, index: &ProverIndex<G, S>) -> Result<ProverWitness<G>> {
+        let d1 = index.cs.domain.d1;
+        let room = d1.size().checked_sub(ZK_ROWS as usize);
+        if room.map_or(true, |room| self.length > room)
+            || self.columns.iter().any(|w| w.len() != d1.size())
+        {
+            return Err(ProverError::WitnessCsInconsistent);
+        }
+        for col in 0..COLUMNS {
+            let chunks = self.non_hiding[col].unshifted.len();
+            if self.comm[col].unshifted.len() != chunks
+                || self.blinders[col].unshifted.len() != chunks
+                || (chunks == 0) != index.cs.zero_columns[col]
+            {
+                return Err(ProverError::WitnessCsInconsistent);
+            }
+        }
+
+        let polys = array::from_fn(|col| {
+            Evaluations::<G::ScalarField, D<G::ScalarField>>::from_vec_and_domain(
+                self.columns[col].clone(),
+                d1,
+            )
+            .interpolate()
+        });
+        let mut blinders = self.blinders.into_iter();
+        let comm = self.comm.map(|commitment| BlindedCommitment {
+            commitment,
+            blinders: blinders.next().expect("as many blinders as commitments"),
+        });
+
+        Ok(ProverWitness {
+            length: self.length,
+            columns: self.columns,
+            non_hiding: self.non_hiding,
+            comm,
+            polys,
+            used: false,
+        })
+    }
+}
@@ -0,0 +1,107 @@
+use crate::{
+    circuits::{
+        constraints::ConstraintSystem,
+        gate::CircuitGate,
+        polynomials::generic::testing::{create_circuit, fill_in_witness},
+        wires::COLUMNS,
+    },
+    error::SetupError,
+    proof::ProverProof,
+    prover_index::ProverIndex,
+    verifier::verify,
+    verifier_index_json::VerifierIndexJson,
+};
+use ark_ff::{One, Zero};
+use ark_poly::EvaluationDomain;
+use commitment_dlog::{
+    commitment::CommitmentCurve,
+    srs::{endos, SRS},
+};
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Pallas, Vesta, VestaParameters};
+use oracle::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use std::{array, sync::Arc};
+
+type BaseSponge = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
+type ScalarSponge = DefaultFrSponge<Fp, PlonkSpongeConstantsKimchi>;
+
+/// Creates the prover index of `gates`, with the application constants `app_constants`.
+fn index(
+    gates: Vec<CircuitGate<Fp>>,
+    public: usize,
+    app_constants: &[(&str, Fp)],
+) -> ProverIndex<Vesta> {
+    let mut builder = ConstraintSystem::create(gates).public(public);
+    for (name, value) in app_constants {
+        builder = builder.app_constant(*name, *value);
+    }
+    let cs = builder.build().unwrap();
+
+    let mut srs = SRS::<Vesta>::create(cs.domain.d1.size());
+    srs.add_lagrange_basis(cs.domain.d1);
+    let (endo_q, _endo_r) = endos::<Pallas>();
+    ProverIndex::create(cs, endo_q, Arc::new(srs))
+}
+
+#[test]
+fn test_app_constants_digest() {
+    let gates = create_circuit(0, 0);
+    let version = ("version", Fp::from(3u64));
+    let chain_id = ("chain_id", Fp::from(42u64));
+
+    let digest = |app_constants: &[(&str, Fp)]| {
+        index(gates.clone(), 0, app_constants)
+            .verifier_index()
+            .digest::<BaseSponge>()
+    };
+    let plain = digest(&[]);
+    let bound = digest(&[version, chain_id]);
+
+    // the constants, their names and their order are all bound to the digest
+    assert_ne!(bound, plain);
+    assert_ne!(bound, digest(&[version, ("chain_id", Fp::from(43u64))]));
+    assert_ne!(bound, digest(&[version, ("network_id", chain_id.1)]));
+    assert_ne!(bound, digest(&[chain_id, version]));
+    assert_eq!(bound, digest(&[version, chain_id]));
+
+    // and a name can only be given once
+    let err = ConstraintSystem::create(gates)
+        .app_constant("version", Fp::one())
+        .app_constant("version", Fp::zero())
+        .build()
+        .unwrap_err();
+    assert!(matches!(err, SetupError::ConstraintSystem(_)));
+}
+
+#[test]
+fn test_app_constants_proof() {
+    let public = vec![Fp::from(3u8); 5];
+    let gates = create_circuit(0, public.len());
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
+    fill_in_witness(0, &mut witness, &public);
+
+    let index = index(gates, public.len(), &[("chain_id", Fp::from(42u64))]);
+
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let proof =
+        ProverProof::create::<BaseSponge, ScalarSponge>(&group_map, witness, &[], &index).unwrap();
+
+    // the proof verifies against the index of the same constants,
+    // including once exported and imported
+    let verifier_index = index.verifier_index();
+    verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &verifier_index, &proof).unwrap();
+    let imported = VerifierIndexJson::export::<_, BaseSponge>(&verifier_index, None)
+        .unwrap()
+        .import::<Vesta, BaseSponge>(index.srs.clone())
+        .unwrap();
+    assert_eq!(imported.app_constants, verifier_index.app_constants);
+    verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &imported, &proof).unwrap();
+
+    // but not against an index of another chain
+    let mut other_chain = index.verifier_index();
+    other_chain.app_constants[0].1 = Fp::from(43u64);
+    verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &other_chain, &proof).unwrap_err();
+}
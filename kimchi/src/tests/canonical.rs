@@ -0,0 +1,86 @@
+use super::framework::TestFramework;
+use crate::circuits::{
+    canonical::canonicalize,
+    gate::{CircuitGate, Connect},
+    polynomials::generic::testing::{create_circuit, fill_in_witness},
+    wires::{Wire, COLUMNS},
+};
+use ark_ff::Zero;
+use mina_curves::pasta::Fp;
+use std::array;
+
+/// Moves each row of `gates` and `witness` to the row given by `rows`, updating the wiring.
+fn permute(
+    gates: &[CircuitGate<Fp>],
+    witness: &[Vec<Fp>; COLUMNS],
+    rows: &[usize],
+) -> (Vec<CircuitGate<Fp>>, [Vec<Fp>; COLUMNS]) {
+    let mut permuted = gates.to_vec();
+    for (row, gate) in gates.iter().enumerate() {
+        permuted[rows[row]] = CircuitGate {
+            typ: gate.typ,
+            wires: gate.wires.map(|wire| Wire {
+                row: rows[wire.row],
+                col: wire.col,
+            }),
+            coeffs: gate.coeffs.clone(),
+        };
+    }
+    let witness = array::from_fn(|col| {
+        let mut values = witness[col].clone();
+        for (row, value) in witness[col].iter().enumerate() {
+            values[rows[row]] = *value;
+        }
+        values
+    });
+    (permuted, witness)
+}
+
+#[test]
+fn test_canonicalize_generic_circuit() {
+    let public = vec![Fp::from(11u32), Fp::from(5u32)];
+    let mut gates = create_circuit(0, public.len());
+    let n = gates.len();
+    gates.connect_cell_pair((0, 0), (2, 0));
+    gates.connect_cell_pair((1, 0), (n - 1, 3));
+    gates.connect_cell_pair((3, 1), (4, 1));
+    gates.connect_cell_pair((4, 1), (5, 4));
+
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); n]);
+    fill_in_witness(0, &mut witness, &public);
+
+    // the same circuit, with its rows in reverse order past the public input rows
+    let reversed: Vec<_> = (0..n)
+        .map(|row| {
+            if row < public.len() {
+                row
+            } else {
+                public.len() + n - 1 - row
+            }
+        })
+        .collect();
+    let (shuffled_gates, shuffled_witness) = permute(&gates, &witness, &reversed);
+
+    // both give the same canonical circuit
+    let canonical = canonicalize(&gates, public.len());
+    let shuffled = canonicalize(&shuffled_gates, public.len());
+    assert_eq!(canonical.rows[..2], [0, 1]);
+    assert_eq!(
+        serde_json::to_string(&canonical.gates).unwrap(),
+        serde_json::to_string(&shuffled.gates).unwrap()
+    );
+    let witness = canonical.witness(&witness);
+    assert_eq!(witness, shuffled.witness(&shuffled_witness));
+
+    // which is left as it is by the pass
+    let again = canonicalize(&canonical.gates, public.len());
+    assert!(again.rows.iter().enumerate().all(|(i, row)| i == *row));
+
+    // and can be proven with the moved rows of the witness
+    TestFramework::default()
+        .gates(canonical.gates)
+        .witness(witness)
+        .public_inputs(public)
+        .setup()
+        .prove_and_verify();
+}
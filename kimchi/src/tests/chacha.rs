@@ -1,11 +1,16 @@
 use super::framework::TestFramework;
 use crate::{
     circuits::{
+        constraints::ConstraintSystem,
         gate::{CircuitGate, GateType},
-        lookup::tables::{LookupTable, XOR_TABLE_ID},
+        lookup::{
+            index::{LookupError, LookupTableKind},
+            tables::{LookupTable, XOR_TABLE_ID},
+        },
         polynomials::chacha,
         wires::{Wire, COLUMNS},
     },
+    error::{ProverError, SetupError, WitnessError},
     proof::ProverProof,
     prover_index::testing::new_index_for_test,
     verifier::verify,
@@ -92,7 +97,7 @@ fn chacha_prover() {
     println!("{}{:?}", "Verifier index time: ".yellow(), start.elapsed());
 
     let start = Instant::now();
-    match verify::<Vesta, BaseSponge, ScalarSponge>(&group_map, &verifier_index, &proof) {
+    match verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &verifier_index, &proof) {
         Err(error) => panic!("Failure verifying the prover's proofs in batch: {}", error),
         Ok(_) => {
             println!("{}{:?}", "Verifier time: ".yellow(), start.elapsed());
@@ -100,7 +105,15 @@ fn chacha_prover() {
     }
 }
 
-fn chacha_setup_bad_lookup(table_id: i32) {
+/// Returns a circuit with one 'real' ChaCha0 gate and one 'fake' one, its witness,
+/// and a lookup table with ID `table_id` holding the fake lookups.
+fn chacha_bad_lookup(
+    table_id: i64,
+) -> (
+    Vec<CircuitGate<Fp>>,
+    [Vec<Fp>; COLUMNS],
+    Vec<LookupTable<Fp>>,
+) {
     // circuit gates: one 'real' ChaCha0 and one 'fake' one.
     let gates = vec![
         GateType::ChaCha0,
@@ -199,16 +212,12 @@ fn chacha_setup_bad_lookup(table_id: i32) {
     // .. and one fake witness.
     push_rows(true);
 
-    let lookup_tables = vec![
-        LookupTable {
-            id: 0,
-            data: vec![vec![Fp::from(0); 10]],
-        },
-        LookupTable {
-            id: table_id,
-            data: fakes,
-        },
-    ];
+    // no dummy table with ID 0 is given: the XOR table of the ChaCha gates is the table with ID 0,
+    // and already has the entry of all zeros, so that another table with ID 0 would collide with it
+    let lookup_tables = vec![LookupTable {
+        id: table_id,
+        data: fakes,
+    }];
 
     let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![]);
     for r in rows.into_iter() {
@@ -217,25 +226,48 @@ fn chacha_setup_bad_lookup(table_id: i32) {
         }
     }
 
-    TestFramework::default()
-        .gates(gates)
-        .witness(witness)
-        .lookup_tables(lookup_tables)
-        .setup()
-        .prove_and_verify();
+    (gates, witness, lookup_tables)
 }
 
 // Test lookup domain separation: if a different table ID is used, we shouldn't be able to use a
 // value from that table.
 #[test]
-#[should_panic]
 fn chacha_prover_fake_lookup_in_different_table_fails() {
-    chacha_setup_bad_lookup(XOR_TABLE_ID + 1)
+    let (gates, witness, lookup_tables) = chacha_bad_lookup(XOR_TABLE_ID + 1);
+    let mut runner = TestFramework::default()
+        .gates(gates)
+        .witness(witness)
+        .lookup_tables(lookup_tables)
+        .setup();
+
+    // the lookups of the fake gate are not in the XOR table
+    assert!(matches!(
+        runner.prove(),
+        Err(ProverError::WitnessNotSatisfied {
+            row: 2,
+            gate: GateType::ChaCha0,
+            cause: WitnessError::Lookup(_),
+        })
+    ));
 }
 
-// Test lookup domain collisions: if the same table ID is used, we should be able to inject and use
-// a value when it wasn't previously in the table.
+// Test lookup domain collisions: if the same table ID is used, the tables are rejected at setup,
+// as we would otherwise be able to inject and use a value when it wasn't previously in the table.
 #[test]
 fn chacha_prover_fake_lookup_in_same_table() {
-    chacha_setup_bad_lookup(XOR_TABLE_ID)
+    let (gates, _, lookup_tables) = chacha_bad_lookup(XOR_TABLE_ID);
+    let expected = LookupError::TableIDCollision {
+        id: XOR_TABLE_ID,
+        namespace: 0,
+        local: 0,
+        first: LookupTableKind::Gate,
+        second: LookupTableKind::Fixed,
+    };
+    match ConstraintSystem::create(gates)
+        .lookup(lookup_tables)
+        .build()
+    {
+        Err(SetupError::ConstraintSystem(err)) => assert_eq!(err, expected.to_string()),
+        other => panic!("expected a table ID collision, got {:?}", other.map(|_| ())),
+    }
 }
@@ -0,0 +1,123 @@
+use crate::{
+    circuits::{
+        gate::{CircuitGate, GateType},
+        lookup::tables::LookupTable,
+        polynomials::generic::testing::{create_circuit, fill_in_witness},
+        wires::{CellRef, Wire, COLUMNS},
+    },
+    error::{ProverError, WitnessError},
+    proof::ProverProof,
+    prover_index::{
+        testing::{new_index_for_test, new_index_for_test_with_lookups},
+        ProverIndex,
+    },
+};
+use ark_ff::{One, Zero};
+use commitment_dlog::commitment::CommitmentCurve;
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use oracle::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use std::array;
+
+type BaseSponge = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
+type ScalarSponge = DefaultFrSponge<Fp, PlonkSpongeConstantsKimchi>;
+
+/// Creates a proof of `witness` against `index`, checking the witness first.
+fn prove(
+    mut index: ProverIndex<Vesta>,
+    witness: [Vec<Fp>; COLUMNS],
+) -> Result<ProverProof<Vesta>, ProverError> {
+    index.check_witness = true;
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    ProverProof::create::<BaseSponge, ScalarSponge>(&group_map, witness, &[], &index)
+}
+
+/// The generic circuit of [`create_circuit`] and its witness
+fn generic_circuit() -> (Vec<CircuitGate<Fp>>, [Vec<Fp>; COLUMNS]) {
+    let gates = create_circuit(0, 0);
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
+    fill_in_witness(0, &mut witness, &[]);
+    (gates, witness)
+}
+
+#[test]
+fn test_check_witness_gate() {
+    let (gates, mut witness) = generic_circuit();
+    assert!(prove(new_index_for_test(gates.clone(), 0), witness.clone()).is_ok());
+
+    // the output of an addition is incorrect
+    witness[2][5] += Fp::one();
+    let err = prove(new_index_for_test(gates, 0), witness).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "the witness does not satisfy the circuit at row 5 (a Generic gate): the gate does not hold"
+    );
+    assert!(matches!(
+        err,
+        ProverError::WitnessNotSatisfied {
+            row: 5,
+            gate: GateType::Generic,
+            cause: WitnessError::Gate,
+        }
+    ));
+}
+
+#[test]
+fn test_check_witness_wiring() {
+    // the left input of an addition is wired to a constant, which it is not equal to
+    let (mut gates, witness) = generic_circuit();
+    gates[2].wires[0] = Wire { row: 12, col: 0 };
+    gates[12].wires[0] = Wire { row: 2, col: 0 };
+
+    let err = prove(new_index_for_test(gates, 0), witness).unwrap_err();
+    assert!(matches!(
+        err,
+        ProverError::WitnessNotSatisfied {
+            row: 2,
+            gate: GateType::Generic,
+            cause: WitnessError::Wiring(0, cell),
+        } if cell == CellRef::new(12, 0)
+    ));
+}
+
+#[test]
+fn test_check_witness_lookup() {
+    // a range table, each row looking up three values in it
+    let range_table = LookupTable {
+        id: 1,
+        data: vec![(0..16u64).map(Into::into).collect()],
+    };
+    let num_rows = 20;
+    let gates: Vec<_> = (0..num_rows)
+        .map(|row| CircuitGate {
+            typ: GateType::MultiTableLookup,
+            wires: Wire::new(row),
+            coeffs: vec![],
+        })
+        .collect();
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); num_rows]);
+    for row in 0..num_rows {
+        for i in 0..3 {
+            witness[3 * i][row] = Fp::one();
+            witness[3 * i + 1][row] = ((row + i) as u64 % 16).into();
+        }
+    }
+    let index =
+        |gates| new_index_for_test_with_lookups(gates, 0, 0, vec![range_table.clone()], None, None);
+    assert!(prove(index(gates.clone()), witness.clone()).is_ok());
+
+    // the third lookup of a row is out of range
+    witness[7][13] = 16u64.into();
+    let err = prove(index(gates), witness).unwrap_err();
+    assert!(matches!(
+        err,
+        ProverError::WitnessNotSatisfied {
+            row: 13,
+            gate: GateType::MultiTableLookup,
+            cause: WitnessError::Lookup(2),
+        }
+    ));
+}
@@ -0,0 +1,155 @@
+use crate::{
+    circuits::{
+        polynomials::generic::testing::{create_circuit, fill_in_witness},
+        wires::COLUMNS,
+    },
+    codec::{
+        decode_proof, decode_verifier_index, encode_proof, encode_verifier_index, MAGIC,
+        PROOF_VERSION,
+    },
+    error::{CodecError, VerifierIndexError},
+    migration::LayoutVersion,
+    proof::ProverProof,
+    prover_index::{testing::new_index_for_test, ProverIndex},
+    verifier::verify,
+};
+use ark_ff::{One, Zero};
+use commitment_dlog::{commitment::CommitmentCurve, srs::SRS};
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Fq, Vesta, VestaParameters};
+use oracle::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use std::{array, sync::Arc};
+
+type BaseSponge = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
+type ScalarSponge = DefaultFrSponge<Fp, PlonkSpongeConstantsKimchi>;
+
+/// Creates the index of a generic circuit, and a proof against it.
+fn index_and_proof() -> (ProverIndex<Vesta>, ProverProof<Vesta>) {
+    let public = vec![Fp::from(3u8); 5];
+    let gates = create_circuit(0, public.len());
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
+    fill_in_witness(0, &mut witness, &public);
+
+    let index = new_index_for_test(gates, public.len());
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let proof =
+        ProverProof::create::<BaseSponge, ScalarSponge>(&group_map, witness, &[], &index).unwrap();
+    (index, proof)
+}
+
+/// Returns a fresh SRS for the verifier index of `index`, as an independent verifier would.
+fn verifier_srs(index: &ProverIndex<Vesta>) -> Arc<SRS<Vesta>> {
+    let mut srs = SRS::<Vesta>::create(index.max_poly_size);
+    srs.add_lagrange_basis(index.cs.domain.d1);
+    Arc::new(srs)
+}
+
+#[test]
+fn test_codec_roundtrip() {
+    let (index, proof) = index_and_proof();
+    let verifier_index = index.verifier_index();
+
+    let proof_bytes = encode_proof(&proof);
+    assert_eq!(proof_bytes[..4], MAGIC);
+    assert_eq!(proof_bytes[4..6], [1, PROOF_VERSION]);
+    let index_bytes = encode_verifier_index(&verifier_index);
+    assert_eq!(index_bytes[..6], [b'K', b'I', b'M', b'C', 2, 1]);
+
+    // the decoded proof verifies against the decoded index, which has the expected digest
+    let expected_digest = verifier_index.digest::<BaseSponge>();
+    let decode_index = |expected_digest| {
+        decode_verifier_index::<Vesta, BaseSponge>(
+            &index_bytes,
+            verifier_srs(&index),
+            None,
+            expected_digest,
+        )
+    };
+    let decoded = decode_index(Some(expected_digest)).unwrap();
+    assert_eq!(decoded.from, LayoutVersion::V1);
+    assert_eq!(decoded.digest, expected_digest);
+    let proof = decode_proof::<Vesta>(&proof_bytes).unwrap();
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &decoded.index, &proof).unwrap();
+
+    // and both encode to the same bytes again
+    assert_eq!(encode_proof(&proof), proof_bytes);
+    assert_eq!(encode_verifier_index(&decoded.index), index_bytes);
+
+    // an index other than the expected one is rejected
+    assert!(matches!(
+        decode_index(Some(expected_digest + Fq::one())),
+        Err(CodecError::VerifierIndex(
+            VerifierIndexError::DigestMismatch
+        ))
+    ));
+}
+
+#[test]
+fn test_codec_invalid_encodings() {
+    let (index, proof) = index_and_proof();
+    let proof_bytes = encode_proof(&proof);
+    let index_bytes = encode_verifier_index(&index.verifier_index());
+    let decode_index = |bytes: &[u8]| {
+        decode_verifier_index::<Vesta, BaseSponge>(bytes, verifier_srs(&index), None, None)
+    };
+
+    // no header
+    assert!(matches!(
+        decode_proof::<Vesta>(&proof_bytes[6..]),
+        Err(CodecError::MissingHeader)
+    ));
+    assert!(matches!(
+        decode_proof::<Vesta>(&proof_bytes[..5]),
+        Err(CodecError::MissingHeader)
+    ));
+
+    // a verifier index is not a proof, and conversely
+    assert!(matches!(
+        decode_proof::<Vesta>(&index_bytes),
+        Err(CodecError::UnexpectedKind("proof", "verifier index"))
+    ));
+    assert!(matches!(
+        decode_index(&proof_bytes),
+        Err(CodecError::UnexpectedKind("verifier index", "proof"))
+    ));
+
+    // unknown versions
+    let mut bytes = proof_bytes.clone();
+    bytes[5] = PROOF_VERSION + 1;
+    assert!(matches!(
+        decode_proof::<Vesta>(&bytes),
+        Err(CodecError::UnsupportedVersion("proof", _))
+    ));
+    let mut bytes = index_bytes.clone();
+    bytes[5] = 2;
+    assert!(matches!(
+        decode_index(&bytes),
+        Err(CodecError::UnsupportedVersion("verifier index", 2))
+    ));
+
+    // trailing data
+    let mut bytes = proof_bytes;
+    bytes.push(0);
+    assert!(matches!(
+        decode_proof::<Vesta>(&bytes),
+        Err(CodecError::Proof(_))
+    ));
+    let mut bytes = index_bytes.clone();
+    bytes.push(0);
+    assert!(matches!(
+        decode_index(&bytes),
+        Err(CodecError::VerifierIndex(_))
+    ));
+
+    // a verifier index of the current layout is not one of the older layout
+    let mut bytes = index_bytes;
+    bytes[5] = 0;
+    assert!(matches!(
+        decode_index(&bytes),
+        Err(CodecError::VerifierIndex(_))
+    ));
+}
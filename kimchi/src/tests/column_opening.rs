@@ -0,0 +1,149 @@
+use crate::{
+    circuits::{
+        polynomials::generic::testing::{create_circuit, fill_in_witness},
+        wires::{CellRef, COLUMNS},
+    },
+    column_opening::{CellDisclosure, ColumnOpening},
+    error::{ProverError, VerifyError},
+    proof::ProverProof,
+    prover_index::testing::new_index_for_test,
+    prover_witness::ProverWitness,
+};
+use ark_ff::{One, Zero};
+use ark_poly::EvaluationDomain;
+use commitment_dlog::commitment::CommitmentCurve;
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use oracle::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use std::array;
+
+type BaseSponge = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
+type ScalarSponge = DefaultFrSponge<Fp, PlonkSpongeConstantsKimchi>;
+
+#[test]
+fn test_column_opening() {
+    let public = vec![Fp::from(3u8); 5];
+    let gates = create_circuit(0, public.len());
+    let len = gates.len();
+    let index = new_index_for_test(gates, public.len());
+    let verifier_index = index.verifier_index();
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); len]);
+    fill_in_witness(0, &mut witness, &public);
+    let cells = witness.clone();
+    let mut prover_witness = ProverWitness::create(&index, witness).unwrap();
+    let proof = ProverProof::create_incremental::<BaseSponge, ScalarSponge>(
+        &group_map,
+        &mut prover_witness,
+        &[],
+        &index,
+        vec![],
+    )
+    .unwrap();
+
+    // opening a column at a point of the domain reveals a cell
+    let row = public.len() + 1;
+    let points = [index.cs.domain.d1.element(row), Fp::from(42u8)];
+    let opening =
+        ColumnOpening::create::<BaseSponge>(&group_map, &index, &prover_witness, &[0, 2], &points)
+            .unwrap();
+    assert_eq!(opening.evals[0][0], vec![cells[0][row]]);
+    assert_eq!(opening.evals[1][0], vec![cells[2][row]]);
+    opening
+        .verify::<BaseSponge>(&group_map, &verifier_index, &proof)
+        .unwrap();
+
+    // a wrong evaluation is rejected
+    let mut wrong = opening.clone();
+    wrong.evals[0][0][0] += Fp::one();
+    assert!(matches!(
+        wrong.verify::<BaseSponge>(&group_map, &verifier_index, &proof),
+        Err(VerifyError::OpenProof)
+    ));
+
+    // as well as an opening against the commitments of another proof
+    let other = ProverProof::create_incremental::<BaseSponge, ScalarSponge>(
+        &group_map,
+        &mut prover_witness,
+        &[],
+        &index,
+        vec![],
+    )
+    .unwrap();
+    assert!(opening
+        .verify::<BaseSponge>(&group_map, &verifier_index, &other)
+        .is_err());
+
+    // columns that do not exist cannot be opened
+    assert!(matches!(
+        ColumnOpening::create::<BaseSponge>(
+            &group_map,
+            &index,
+            &prover_witness,
+            &[COLUMNS],
+            &points
+        ),
+        Err(ProverError::InvalidColumn(COLUMNS))
+    ));
+}
+
+#[test]
+fn test_cell_disclosure() {
+    let public = vec![Fp::from(3u8); 5];
+    let gates = create_circuit(0, public.len());
+    let len = gates.len();
+    let index = new_index_for_test(gates, public.len());
+    let verifier_index = index.verifier_index();
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); len]);
+    fill_in_witness(0, &mut witness, &public);
+    let values = witness.clone();
+    let mut prover_witness = ProverWitness::create(&index, witness).unwrap();
+    let proof = ProverProof::create_incremental::<BaseSponge, ScalarSponge>(
+        &group_map,
+        &mut prover_witness,
+        &[],
+        &index,
+        vec![],
+    )
+    .unwrap();
+
+    // the cells are disclosed once, sorted by column and row
+    let cells = [
+        CellRef::new(6, 2),
+        CellRef::new(1, 0),
+        CellRef::new(6, 0),
+        CellRef::new(1, 0),
+    ];
+    let disclosure =
+        CellDisclosure::create::<BaseSponge>(&group_map, &index, &prover_witness, &cells).unwrap();
+    let disclosed = disclosure
+        .verify::<BaseSponge>(&group_map, &verifier_index, &proof)
+        .unwrap();
+    let expected: Vec<_> = [(1, 0), (6, 0), (6, 2)]
+        .into_iter()
+        .map(|(row, col)| (CellRef::new(row, col), values[col][row]))
+        .collect();
+    assert_eq!(disclosed, expected);
+
+    // a wrong value is rejected
+    let mut wrong = disclosure.clone();
+    wrong.evals[2][0] += Fp::one();
+    assert!(matches!(
+        wrong.verify::<BaseSponge>(&group_map, &verifier_index, &proof),
+        Err(VerifyError::OpenProof)
+    ));
+
+    // as well as a cell disclosed twice
+    let mut twice = disclosure.clone();
+    twice.cells[1] = twice.cells[0];
+    assert!(matches!(
+        twice.verify::<BaseSponge>(&group_map, &verifier_index, &proof),
+        Err(VerifyError::IncorrectEvaluationsLength(_))
+    ));
+}
@@ -0,0 +1,98 @@
+use super::framework::TestFramework;
+use crate::circuits::{
+    gate::CircuitGate,
+    polynomials::generic::GenericGateSpec,
+    wires::{Wire, COLUMNS, PERMUTS},
+};
+use ark_ff::{UniformRand, Zero};
+use mina_curves::pasta::{Fp, Vesta};
+use std::array;
+
+/// Creates a circuit where a copy gate wires the cells 7 and 8 of the next row
+/// to a generic gate checking that they hold the constants 5 and 6.
+fn circuit() -> Vec<CircuitGate<Fp>> {
+    let mut copy = CircuitGate::create_copy_cells(0);
+    copy.wires[0] = Wire { row: 2, col: 0 };
+    copy.wires[1] = Wire { row: 2, col: 3 };
+
+    let mut constants = CircuitGate::create_generic_gadget(
+        Wire::new(2),
+        GenericGateSpec::Const(Fp::from(5u64)),
+        Some(GenericGateSpec::Const(Fp::from(6u64))),
+    );
+    constants.wires[0] = Wire { row: 0, col: 0 };
+    constants.wires[3] = Wire { row: 0, col: 1 };
+
+    vec![copy, CircuitGate::zero(Wire::new(1)), constants]
+}
+
+/// Creates the witness of [`circuit`], with the cells 7 and 8 of the second row set to `values`.
+fn witness(values: [Fp; 2]) -> [Vec<Fp>; COLUMNS] {
+    let rng = &mut rand::rngs::OsRng;
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); 3]);
+
+    // the non-permuted cells of the second row, copied into the first row
+    for col in &mut witness[PERMUTS..] {
+        col[1] = Fp::rand(rng);
+    }
+    witness[PERMUTS][1] = values[0];
+    witness[PERMUTS + 1][1] = values[1];
+    CircuitGate::create_copy_cells(0)
+        .generate_witness::<Vesta>(0, &mut witness, &[])
+        .unwrap();
+
+    // the constants, wired to the copies
+    witness[0][2] = Fp::from(5u64);
+    witness[3][2] = Fp::from(6u64);
+    witness
+}
+
+#[test]
+fn test_copy_cells() {
+    let values = [Fp::from(5u64), Fp::from(6u64)];
+    TestFramework::default()
+        .gates(circuit())
+        .witness(witness(values))
+        .setup()
+        .prove_and_verify();
+}
+
+#[test]
+fn test_copy_cells_selector() {
+    let runner = TestFramework::default().gates(circuit()).setup();
+    let index = runner.prover_index();
+    assert!(index.cs.copy_cells_selector_poly.is_some());
+    assert!(index.verifier_index().copy_cells_comm.is_some());
+
+    // the circuits without copy gates do not commit to the selector
+    let mut gates = circuit();
+    gates[0] = CircuitGate::zero(Wire::new(0));
+    gates[2].wires = Wire::new(2);
+    let runner = TestFramework::default().gates(gates).setup();
+    assert!(runner.prover_index().cs.copy_cells_selector_poly.is_none());
+    assert!(runner
+        .prover_index()
+        .verifier_index()
+        .copy_cells_comm
+        .is_none());
+}
+
+#[test]
+fn test_copy_cells_wrong_copy() {
+    let runner = TestFramework::default().gates(circuit()).setup();
+    let cs = &runner.prover_index().cs;
+
+    // a cell of the next row that is not the copy of the wired cell
+    let mut bad = witness([Fp::from(5u64), Fp::from(6u64)]);
+    bad[PERMUTS + 1][1] = Fp::from(7u64);
+    assert!(cs.gates[0].verify_copy_cells(0, &bad).is_err());
+    assert!(cs.gates[0]
+        .verify_witness::<Vesta>(0, &bad, cs, &[])
+        .is_err());
+    assert!(cs.verify::<Vesta>(&bad, &[]).is_err());
+
+    // and the wired cells must still hold the constants
+    let bad = witness([Fp::from(5u64), Fp::from(7u64)]);
+    assert!(cs.gates[0].verify_copy_cells(0, &bad).is_ok());
+    assert!(cs.verify::<Vesta>(&bad, &[]).is_err());
+}
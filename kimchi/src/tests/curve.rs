@@ -0,0 +1,58 @@
+use crate::curve::{
+    base_to_scalar_checked, from_limbs_checked, scalar_to_base_checked, to_other_field_limbs,
+};
+use ark_ff::{Field, One, UniformRand, Zero};
+use mina_curves::pasta::{Fp, Fq, Pallas, Vesta};
+use num_bigint::BigUint;
+use o1_utils::{field_helpers::FieldHelpers, foreign_field::LIMB_BITS};
+use rand::{rngs::StdRng, SeedableRng};
+use std::array;
+
+#[test]
+fn test_field_conversions() {
+    let rng = &mut StdRng::from_seed([0u8; 32]);
+
+    // the base field of Vesta is larger than its scalar field
+    for _ in 0..10 {
+        let x = Fp::rand(rng);
+        let y = scalar_to_base_checked::<Vesta>(x).unwrap();
+        assert_eq!(base_to_scalar_checked::<Vesta>(y), Some(x));
+        assert_eq!(base_to_scalar_checked::<Pallas>(x), Some(y));
+    }
+
+    // so that not all of its elements can be converted
+    let x = Fq::from(Fp::modulus_biguint());
+    assert_eq!(base_to_scalar_checked::<Vesta>(x), None);
+    assert_eq!(
+        base_to_scalar_checked::<Vesta>(x - Fq::one()),
+        Some(-Fp::one())
+    );
+}
+
+#[test]
+fn test_limbs() {
+    let rng = &mut StdRng::from_seed([0u8; 32]);
+
+    for x in [Fq::zero(), -Fq::one(), Fq::rand(rng), Fq::rand(rng)] {
+        let limbs = to_other_field_limbs::<Vesta>(x);
+        assert!(limbs
+            .iter()
+            .all(|limb| Into::<BigUint>::into(*limb).bits() <= LIMB_BITS as u64));
+        assert_eq!(from_limbs_checked::<Vesta>(&limbs), Some(x));
+    }
+    for x in [Fp::zero(), -Fp::one(), Fp::rand(rng)] {
+        let limbs = to_other_field_limbs::<Pallas>(x);
+        assert_eq!(from_limbs_checked::<Pallas>(&limbs), Some(x));
+    }
+
+    // limbs that are too large
+    let mut limbs = to_other_field_limbs::<Vesta>(Fq::one());
+    limbs[0] += Fp::from(2u8).pow([LIMB_BITS as u64]);
+    assert_eq!(from_limbs_checked::<Vesta>(&limbs), None);
+
+    // values that are too large
+    let modulus = Fq::modulus_biguint();
+    let mask = (BigUint::from(1u8) << LIMB_BITS) - 1u8;
+    let limbs = array::from_fn(|i| Fp::from((&modulus >> (i * LIMB_BITS)) & &mask));
+    assert_eq!(from_limbs_checked::<Vesta>(&limbs), None);
+}
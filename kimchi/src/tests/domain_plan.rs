@@ -0,0 +1,76 @@
+use crate::circuits::{
+    constraints::ConstraintSystem,
+    domain_plan::{DomainLimit, DomainPlan},
+    gate::{CircuitGate, GateType},
+    lookup::tables::LookupTable,
+    polynomials::permutation::ZK_ROWS,
+    wires::Wire,
+};
+use ark_poly::EvaluationDomain;
+use mina_curves::pasta::Fp;
+
+const ZK: usize = ZK_ROWS as usize;
+
+#[test]
+fn test_domain_plan_sizes() {
+    // limited by the rows
+    let plan = DomainPlan::new(10, 0, ZK);
+    assert_eq!(plan.d1_size, 16);
+    assert_eq!(plan.limit, DomainLimit::Rows);
+    assert_eq!(plan.padding(), 6);
+    assert_eq!(plan.d8_size(), 128);
+    assert_eq!(plan.srs_size(), 16);
+
+    // the zero-knowledge rows can double the domain
+    assert_eq!(DomainPlan::new(16, 0, ZK).d1_size, 32);
+
+    // limited by the lookup tables
+    let plan = DomainPlan::new(10, 4096, ZK);
+    assert_eq!(plan.d1_size, 8192);
+    assert_eq!(plan.limit, DomainLimit::LookupTables);
+    assert_eq!(plan.padding(), 8182);
+
+    // small tables do not change the domain
+    let plan = DomainPlan::new(100, 10, ZK);
+    assert_eq!(plan.d1_size, 128);
+    assert_eq!(plan.limit, DomainLimit::Rows);
+
+    let report = DomainPlan::new(10, 4096, ZK).to_string();
+    assert!(report.contains("d1 = 8192"));
+    assert!(report.contains("limited by the lookup tables"));
+}
+
+#[test]
+fn test_domain_plan_of_builder() {
+    let table_len = 100;
+    let table = LookupTable {
+        id: 0,
+        data: vec![(0..table_len as u64).map(Fp::from).collect()],
+    };
+    let gates: Vec<_> = (0..10)
+        .map(|row| CircuitGate {
+            typ: GateType::Lookup,
+            wires: Wire::new(row),
+            coeffs: vec![],
+        })
+        .collect();
+
+    // without lookup gates, the tables are not counted
+    let zero_gates: Vec<_> = (0..10)
+        .map(|row| CircuitGate::zero(Wire::new(row)))
+        .collect();
+    let builder = ConstraintSystem::create(zero_gates).lookup(vec![table.clone()]);
+    assert_eq!(builder.domain_plan().lookup_entries, 0);
+    assert_eq!(builder.domain_plan().d1_size, 16);
+
+    // with lookup gates, the tables determine the domain
+    let builder = ConstraintSystem::create(gates).lookup(vec![table]);
+    let plan = builder.domain_plan();
+    assert_eq!(plan.lookup_entries, table_len);
+    assert_eq!(plan.limit, DomainLimit::LookupTables);
+    assert_eq!(plan.d1_size, 128);
+
+    let cs = builder.build().unwrap();
+    assert_eq!(cs.domain.d1.size(), plan.d1_size);
+    assert_eq!(cs.gates.len(), plan.d1_size);
+}
@@ -985,7 +985,7 @@ fn test_prover_ffadd() {
     let verifier_index = prover_index.verifier_index();
 
     // Verify proof
-    let res = verify::<Vesta, BaseSponge, ScalarSponge>(&group_map, &verifier_index, &proof);
+    let res = verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &verifier_index, &proof);
 
     assert!(!res.is_err());
 }
@@ -9,6 +9,7 @@ use crate::{
         },
         wires::COLUMNS,
     },
+    error::{ProverError, VerifyError},
     proof::{ProverProof, RecursionChallenge},
     prover_index::{testing::new_index_for_test_with_lookups, ProverIndex},
     verifier::verify,
@@ -141,6 +142,31 @@ impl TestRunner {
         self.0.prover_index.as_ref().unwrap()
     }
 
+    /// Create a proof, checking the witness first
+    pub(crate) fn prove(&mut self) -> Result<ProverProof<Vesta>, ProverError> {
+        let prover = self.0.prover_index.as_mut().unwrap();
+        prover.check_witness = true;
+        let group_map = <Vesta as CommitmentCurve>::Map::setup();
+        ProverProof::create_recursive::<BaseSponge, ScalarSponge>(
+            &group_map,
+            self.0.witness.clone().unwrap(),
+            &self.0.runtime_tables,
+            prover,
+            self.0.recursion.clone(),
+            None,
+        )
+    }
+
+    /// Verify a proof
+    pub(crate) fn verify(&self, proof: &ProverProof<Vesta>) -> Result<(), VerifyError> {
+        let group_map = <Vesta as CommitmentCurve>::Map::setup();
+        verify::<Vesta, BaseSponge, ScalarSponge, _>(
+            &group_map,
+            self.0.verifier_index.as_ref().unwrap(),
+            proof,
+        )
+    }
+
     /// Create and verify a proof
     pub(crate) fn prove_and_verify(self) {
         let prover = self.0.prover_index.unwrap();
@@ -170,7 +196,7 @@ impl TestRunner {
 
         // verify the proof
         let start = Instant::now();
-        verify::<Vesta, BaseSponge, ScalarSponge>(
+        verify::<Vesta, BaseSponge, ScalarSponge, _>(
             &group_map,
             &self.0.verifier_index.unwrap(),
             &proof,
@@ -0,0 +1,83 @@
+use crate::circuits::{
+    argument::Argument,
+    expr::{Column, Variable},
+    gate::{CurrOrNext, GateType},
+    gate_info::GateInfo,
+    lookup::lookups::LookupPattern,
+    polynomials::{complete_add::CompleteAdd, poseidon::Poseidon, varbasemul::VarbaseMul},
+    wires::COLUMNS,
+};
+use mina_curves::pasta::Fp;
+
+#[test]
+fn test_gate_info() {
+    let all = GateInfo::all();
+    assert!(all.iter().any(|info| info.typ == GateType::Zero));
+    assert!(all
+        .iter()
+        .any(|info| info.typ == GateType::MultiTableLookup));
+
+    // the number of constraints is the one of the implementation of the gates
+    for (typ, constraints) in [
+        (GateType::Poseidon, Poseidon::<Fp>::CONSTRAINTS),
+        (GateType::CompleteAdd, CompleteAdd::<Fp>::CONSTRAINTS),
+        (GateType::VarBaseMul, VarbaseMul::<Fp>::CONSTRAINTS),
+        (GateType::Generic, 2),
+        (GateType::TripleGeneric, 3),
+        (GateType::Zero, 0),
+    ] {
+        assert_eq!(typ.info().constraints, constraints, "{typ:?}");
+    }
+
+    // the cells read by the constraints
+    let complete_add = GateType::CompleteAdd.info();
+    assert_eq!(
+        complete_add.witness_columns(CurrOrNext::Curr),
+        (0..11).collect::<Vec<_>>()
+    );
+    assert!(!complete_add.uses_next_row());
+    assert!(complete_add.coefficients.is_empty());
+
+    let poseidon = GateType::Poseidon.info();
+    assert_eq!(
+        poseidon.witness_columns(CurrOrNext::Curr),
+        (0..COLUMNS).collect::<Vec<_>>()
+    );
+    assert_eq!(poseidon.witness_columns(CurrOrNext::Next), vec![0, 1, 2]);
+    assert_eq!(poseidon.coefficients.len(), COLUMNS);
+    assert_eq!(poseidon.coefficients[4], "round constant 1 of the round 1");
+
+    let generic = GateType::Generic.info();
+    assert_eq!(generic.coefficients.len(), 10);
+    assert_eq!(
+        generic.coefficients[8],
+        "mul selector of the generic gate 2"
+    );
+    assert_eq!(
+        generic.witness_columns(CurrOrNext::Curr),
+        (0..6).collect::<Vec<_>>()
+    );
+
+    // the triple generic gate reads three more registers, and five more coefficients
+    let triple_generic = GateType::TripleGeneric.info();
+    assert_eq!(triple_generic.coefficients.len(), COLUMNS);
+    assert_eq!(
+        triple_generic.witness_columns(CurrOrNext::Curr),
+        (0..9).collect::<Vec<_>>()
+    );
+    assert!(triple_generic.cells.contains(&Variable {
+        col: Column::Coefficient(14),
+        row: CurrOrNext::Curr,
+    }));
+
+    // the lookups
+    assert_eq!(
+        GateType::Lookup.info().lookups,
+        vec![(CurrOrNext::Curr, LookupPattern::LookupGate)]
+    );
+    assert!(GateType::Generic.info().lookups.is_empty());
+
+    // and the metadata can be exported
+    let json = serde_json::to_value(&all).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), all.len());
+}
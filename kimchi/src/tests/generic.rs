@@ -1,7 +1,13 @@
 use super::framework::TestFramework;
+use crate::circuits::constraints::ConstraintSystem;
+use crate::circuits::gate::{CircuitGate, CircuitGateError, GateType};
 use crate::circuits::polynomials::generic::testing::{create_circuit, fill_in_witness};
-use crate::circuits::wires::COLUMNS;
-use ark_ff::Zero;
+use crate::circuits::polynomials::generic::GenericGateSpec;
+use crate::circuits::wires::{Wire, COLUMNS};
+use crate::error::ProverError;
+use crate::prover::check_constraint;
+use ark_ff::{One, Zero};
+use ark_poly::{EvaluationDomain, Evaluations};
 use mina_curves::pasta::Fp;
 use std::array;
 
@@ -74,3 +80,124 @@ fn test_generic_gate_pub_empty() {
         .setup()
         .prove_and_verify();
 }
+
+#[test]
+fn test_triple_generic_gate() {
+    // each row computes a * b, 2 * c * d and e + f, followed by double generic gates
+    let mut gates: Vec<_> = (0..10)
+        .map(|row| {
+            CircuitGate::create_triple_generic_gadget(
+                Wire::new(row),
+                GenericGateSpec::Mul {
+                    output_coeff: None,
+                    mul_coeff: None,
+                },
+                GenericGateSpec::Mul {
+                    output_coeff: None,
+                    mul_coeff: Some(2u32.into()),
+                },
+                GenericGateSpec::Add {
+                    left_coeff: None,
+                    right_coeff: None,
+                    output_coeff: None,
+                },
+            )
+            .unwrap()
+        })
+        .collect();
+    gates.extend(create_circuit(gates.len(), 0));
+
+    // create witness
+    let row = |row: usize| {
+        let [a, b, c, d, e, f] = array::from_fn(|i| Fp::from((row + i) as u64));
+        let mut cells = [Fp::zero(); COLUMNS];
+        cells[..9].copy_from_slice(&[a, b, a * b, c, d, c * d * Fp::from(2u32), e, f, e + f]);
+        cells
+    };
+    let rows: Vec<_> = (0..10).map(row).collect();
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|col| {
+        let mut column: Vec<_> = rows.iter().map(|r| r[col]).collect();
+        column.resize(gates.len(), Fp::zero());
+        column
+    });
+    fill_in_witness(10, &mut witness, &[]);
+
+    // the third gate is enforced on its own registers
+    let mut bad_witness = witness.clone();
+    bad_witness[8][3] += Fp::one();
+    assert!(gates[3].verify_generic(3, &bad_witness, &[]).is_err());
+    assert!(gates[3].verify_generic(3, &witness, &[]).is_ok());
+
+    // create and verify proof based on the witness
+    let runner = TestFramework::default()
+        .gates(gates)
+        .witness(witness)
+        .setup();
+    assert!(runner.prover_index().cs.triple_generic);
+    assert!(runner.prover_index().verifier_index().triple_generic);
+    runner.prove_and_verify();
+}
+
+#[test]
+fn test_triple_generic_gate_opt_in() {
+    // the circuits without triple generic gates do not have the third constraint
+    let cs = ConstraintSystem::fp_for_testing(create_circuit(0, 0));
+    assert!(!cs.triple_generic);
+
+    // and the public input can only be added to the first generic gate
+    let gate = |gate2, gate3| {
+        CircuitGate::<Fp>::create_triple_generic_gadget(
+            Wire::new(0),
+            GenericGateSpec::Pub,
+            gate2,
+            gate3,
+        )
+    };
+    assert!(gate(
+        GenericGateSpec::Const(Fp::one()),
+        GenericGateSpec::Const(Fp::one())
+    )
+    .is_ok());
+    assert_eq!(
+        gate(GenericGateSpec::Pub, GenericGateSpec::Const(Fp::one())).unwrap_err(),
+        CircuitGateError::PublicInputGate(GateType::TripleGeneric)
+    );
+    assert_eq!(
+        gate(GenericGateSpec::Const(Fp::one()), GenericGateSpec::Pub).unwrap_err(),
+        CircuitGateError::PublicInputGate(GateType::TripleGeneric)
+    );
+}
+
+#[test]
+fn test_check_constraint() {
+    let gates = create_circuit(0, 0);
+    let cs = ConstraintSystem::fp_for_testing(gates);
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); cs.gates.len()]);
+    fill_in_witness(0, &mut witness, &[]);
+
+    let generic_constraint = |witness: &[Vec<Fp>; COLUMNS]| {
+        let witness_d4 = array::from_fn(|col| {
+            let mut w = witness[col].clone();
+            w.resize(cs.domain.d1.size(), Fp::zero());
+            Evaluations::from_vec_and_domain(w, cs.domain.d1)
+                .interpolate()
+                .evaluate_over_domain(cs.domain.d4)
+        });
+        cs.gnrc_quot(std::iter::repeat(Fp::one()), &witness_d4)
+    };
+    check_constraint(&cs, "generic", &generic_constraint(&witness)).unwrap();
+
+    // the rows where the constraint does not hold are pinpointed
+    witness[2][5] += Fp::one();
+    witness[0][7] += Fp::one();
+    let err = check_constraint(&cs, "generic", &generic_constraint(&witness)).unwrap_err();
+    assert!(matches!(
+        err,
+        ProverError::ConstraintNotSatisfied {
+            constraint: "generic",
+            row: 5,
+            gate: GateType::Generic,
+            others: 1,
+        }
+    ));
+}
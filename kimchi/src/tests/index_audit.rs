@@ -0,0 +1,52 @@
+use crate::{
+    circuits::polynomials::generic::testing::create_circuit, index_audit::IndexAuditLog,
+    prover_index::testing::new_index_for_test,
+};
+use ark_ff::One;
+use mina_curves::pasta::{Fp, VestaParameters};
+use oracle::{constants::PlonkSpongeConstantsKimchi, sponge::DefaultFqSponge};
+
+type BaseSponge = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
+
+#[test]
+fn test_index_audit_log() {
+    let gates = create_circuit(0, 0);
+    let log = new_index_for_test(gates.clone(), 0)
+        .audit_log::<BaseSponge>()
+        .unwrap();
+
+    // the log is deterministic
+    let same = new_index_for_test(gates.clone(), 0)
+        .audit_log::<BaseSponge>()
+        .unwrap();
+    assert_eq!(log, same);
+    assert!(log.diff(&same).is_empty());
+
+    // and it serializes an entry per line
+    let mut jsonl = vec![];
+    log.write_jsonl(&mut jsonl).unwrap();
+    let lines: Vec<_> = std::str::from_utf8(&jsonl).unwrap().lines().collect();
+    assert_eq!(lines.len(), log.entries.len());
+    assert!(lines[0].starts_with(r#"{"path":"features""#));
+
+    // a gate with another coefficient is pointed to, along with the commitments it changes
+    let mut other_gates = gates;
+    let row = other_gates.len() - 1;
+    other_gates[row].coeffs[0] += Fp::one();
+    let other = new_index_for_test(other_gates, 0)
+        .audit_log::<BaseSponge>()
+        .unwrap();
+    let paths: Vec<_> = log.diff(&other).iter().map(|d| d.path).collect();
+    assert!(paths.contains(&format!("cs.gates.{row}").as_str()));
+    assert!(paths.contains(&"verifier_index.digest"));
+    assert!(paths
+        .iter()
+        .any(|path| path.starts_with("verifier_index.coefficients")));
+    assert!(!paths.iter().any(|path| path.starts_with("srs.")));
+
+    // the log of a verifier index alone is the tail of the log of its prover index
+    let index = new_index_for_test(create_circuit(0, 0), 0);
+    let verifier_log =
+        IndexAuditLog::verifier_index::<_, BaseSponge>(&index.verifier_index(), None).unwrap();
+    assert!(log.entries.ends_with(&verifier_log.entries));
+}
@@ -0,0 +1,85 @@
+use crate::{
+    circuits::{
+        constraints::ConstraintSystem,
+        polynomials::generic::testing::{create_circuit, fill_in_witness},
+        wires::COLUMNS,
+    },
+    keystore::KeyStore,
+    proof::ProverProof,
+    verifier::verify,
+};
+use ark_ff::Zero;
+use ark_poly::EvaluationDomain;
+use commitment_dlog::{
+    commitment::CommitmentCurve,
+    srs::{endos, SRS},
+};
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Pallas, Vesta, VestaParameters};
+use oracle::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use std::{array, fs, sync::Arc};
+
+type BaseSponge = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
+type ScalarSponge = DefaultFrSponge<Fp, PlonkSpongeConstantsKimchi>;
+
+#[test]
+fn test_keystore() {
+    let dir = std::env::temp_dir().join(format!("kimchi-keystore-{}", std::process::id()));
+    let keystore = KeyStore::new(&dir).unwrap();
+
+    let public = vec![Fp::from(3u8); 5];
+    let gates = create_circuit(0, public.len());
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
+    fill_in_witness(0, &mut witness, &public);
+    let circuit = || ConstraintSystem::create(gates.clone()).public(public.len());
+
+    let domain = circuit().build().unwrap().domain.d1;
+    let mut srs = SRS::<Vesta>::create(domain.size());
+    srs.add_lagrange_basis(domain);
+    let srs = Arc::new(srs);
+    let (endo_q, _endo_r) = endos::<Pallas>();
+
+    // the indexes are created and stored the first time
+    assert!(!keystore.contains(&circuit(), &srs));
+    let created = keystore
+        .prover_index(circuit(), endo_q, Arc::clone(&srs))
+        .unwrap();
+    assert!(keystore.contains(&circuit(), &srs));
+
+    // and read back afterwards
+    let index = keystore
+        .prover_index(circuit(), endo_q, Arc::clone(&srs))
+        .unwrap();
+    let verifier_index = index.verifier_index();
+    assert_eq!(
+        verifier_index.digest::<BaseSponge>(),
+        created.verifier_index().digest::<BaseSponge>()
+    );
+
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let proof =
+        ProverProof::create::<BaseSponge, ScalarSponge>(&group_map, witness, &[], &index).unwrap();
+    verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &verifier_index, &proof).unwrap();
+
+    // another circuit is stored separately
+    let other = || ConstraintSystem::create(gates.clone()).public(public.len() - 1);
+    assert_ne!(other().digest(), circuit().digest());
+    assert!(!keystore.contains(&other(), &srs));
+
+    // and a file that cannot be read back is overwritten
+    let path = keystore.path(&circuit(), &srs);
+    fs::write(&path, b"not an index").unwrap();
+    let index = keystore
+        .prover_index(circuit(), endo_q, Arc::clone(&srs))
+        .unwrap();
+    assert_eq!(
+        index.verifier_index().digest::<BaseSponge>(),
+        verifier_index.digest::<BaseSponge>()
+    );
+    assert_ne!(fs::read(&path).unwrap(), b"not an index");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
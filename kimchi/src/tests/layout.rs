@@ -0,0 +1,66 @@
+use crate::circuits::{
+    constraints::ConstraintSystem,
+    gate::{CircuitGate, GateType},
+    layout::WitnessLayout,
+    polynomials::generic::GenericGateSpec,
+    wires::{CellRef, Wire, COLUMNS},
+};
+use ark_ff::{One, Zero};
+use mina_curves::pasta::{Fp, Vesta};
+use std::array;
+
+#[test]
+fn test_witness_layout() {
+    // a chain of additions, the output of each row being the left input of the next one
+    let rows = 10;
+    let gates: Vec<_> = (0..rows)
+        .map(|row| {
+            let add = GenericGateSpec::Add {
+                left_coeff: None,
+                right_coeff: None,
+                output_coeff: None,
+            };
+            CircuitGate::create_generic_gadget(Wire::new(row), add, None)
+        })
+        .collect();
+    let cs = (1..rows)
+        .fold(ConstraintSystem::create(gates), |builder, row| {
+            builder.connect((row - 1, 2), (row, 0))
+        })
+        .build()
+        .unwrap();
+
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); rows]);
+    let mut acc = Fp::one();
+    for row in 0..rows {
+        witness[0][row] = acc;
+        witness[1][row] = Fp::one();
+        acc += Fp::one();
+        witness[2][row] = acc;
+    }
+
+    let layout = WitnessLayout::new::<Vesta>(&cs, &witness, &[]);
+    assert_eq!(layout.rows.len(), rows);
+    assert_eq!(layout.failing_rows().count(), 0);
+    assert!(layout.rows.iter().all(|r| r.gate == GateType::Generic));
+    assert!(layout.rows[0].cells[0].wire.is_none());
+    assert_eq!(layout.rows[3].cells[2].wire, Some(CellRef::new(4, 0)));
+
+    // break the copy constraint between (3, 2) and (4, 0), and the gate of row 4
+    witness[0][4] += Fp::one();
+    let layout = WitnessLayout::new::<Vesta>(&cs, &witness, &[]);
+    assert!(!layout.rows[3].cells[2].copy_ok);
+    assert!(!layout.rows[4].cells[0].copy_ok);
+    assert!(layout.rows[4].error.is_some());
+    assert_eq!(layout.failing_rows().collect::<Vec<_>>(), vec![3, 4]);
+
+    // the exports annotate the broken cells
+    let csv = layout.to_csv();
+    assert_eq!(csv.lines().count(), rows + 1);
+    assert!(csv.starts_with("row,gate,w0,"));
+    assert!(csv.contains(" -> (4, 0) !"));
+
+    let html = layout.to_html();
+    assert!(html.contains("class=\"wired mismatch\""));
+    assert!(html.contains("wired to (3, 2), copy constraint failed"));
+}
@@ -0,0 +1,62 @@
+use crate::{
+    circuits::{
+        constraints::ConstraintSystem, gate::CircuitGate,
+        polynomials::generic::testing::create_circuit, wires::Wire,
+    },
+    error::SetupError,
+};
+use mina_curves::pasta::Fp;
+
+#[test]
+fn test_linearization_snapshot() {
+    let gates = create_circuit(0, 0);
+    let cs = ConstraintSystem::create(gates.clone()).build().unwrap();
+
+    // the snapshot is stable, and lists the terms of each index column
+    let snapshot = cs.linearization_snapshot();
+    assert_eq!(snapshot, cs.linearization_snapshot());
+    assert!(snapshot.starts_with("constant_term\n"));
+    assert!(snapshot.contains("\nindex_term CompleteAdd\n"));
+    assert!(snapshot.contains("\nindex_term c[0]\n"));
+
+    // building against the snapshot succeeds, whatever the line endings
+    ConstraintSystem::create(gates.clone())
+        .linearization_snapshot(snapshot.clone())
+        .build()
+        .unwrap();
+    ConstraintSystem::create(gates.clone())
+        .linearization_snapshot(snapshot.replace('\n', "\r\n"))
+        .build()
+        .unwrap();
+
+    // a changed term is pointed to
+    let mut lines: Vec<_> = snapshot.lines().collect();
+    lines[1] = "  2 * alpha";
+    let err = ConstraintSystem::create(gates.clone())
+        .linearization_snapshot(lines.join("\n"))
+        .build()
+        .unwrap_err();
+    match err {
+        SetupError::LinearizationSnapshot(msg) => assert!(msg.starts_with("line 2 ")),
+        err => panic!("unexpected error {err}"),
+    }
+
+    // as is a missing term
+    let truncated = snapshot.lines().take(3).collect::<Vec<_>>().join("\n");
+    let err = ConstraintSystem::create(gates.clone())
+        .linearization_snapshot(truncated)
+        .build()
+        .unwrap_err();
+    assert!(matches!(err, SetupError::LinearizationSnapshot(_)));
+
+    // the optional gates change the snapshot
+    let mut with_copy = gates;
+    with_copy.push(CircuitGate::create_copy_cells(with_copy.len()));
+    with_copy.push(CircuitGate::zero(Wire::new(with_copy.len())));
+    let other = ConstraintSystem::create(with_copy)
+        .build()
+        .unwrap()
+        .linearization_snapshot();
+    assert!(other.contains("\nindex_term CopyCells\n"));
+    assert!(!snapshot.contains("CopyCells"));
+}
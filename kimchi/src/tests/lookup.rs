@@ -1,16 +1,43 @@
 use super::framework::{print_witness, TestFramework};
 use crate::circuits::{
+    constraints::ConstraintSystem,
     gate::{CircuitGate, GateType},
     lookup::{
+        index::LookupTableSpec,
         runtime_tables::{RuntimeTable, RuntimeTableCfg, RuntimeTableSpec},
-        tables::LookupTable,
+        tables::{
+            get_table, namespaced_table_id, table_id_namespace, GateLookupTable, LookupTable,
+            NOT_TABLE_ID,
+        },
     },
     polynomial::COLUMNS,
-    wires::Wire,
+    wires::{Wire, PERMUTS},
 };
-use ark_ff::Zero;
-use mina_curves::pasta::Fp;
-use std::array;
+use crate::{
+    error::{ProverError, RuntimeTableError, VerifierIndexError, VerifyError, WitnessError},
+    proof::ProverProof,
+    prover::commit_chunked,
+    prover_index::ProverIndex,
+    verifier::{batch_verify_strict, verify},
+};
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{One, UniformRand, Zero};
+use ark_poly::{EvaluationDomain, Evaluations};
+use commitment_dlog::{
+    commitment::CommitmentCurve,
+    srs::{endos, SRS},
+};
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Pallas, Vesta, VestaParameters};
+use o1_utils::field_helpers::i64_to_field;
+use oracle::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use std::{array, sync::Arc};
+
+type BaseSponge = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
+type ScalarSponge = DefaultFrSponge<Fp, PlonkSpongeConstantsKimchi>;
 
 fn setup_lookup_proof(use_values_from_table: bool, num_lookups: usize, table_sizes: Vec<usize>) {
     let lookup_table_values: Vec<Vec<_>> = table_sizes
@@ -25,7 +52,7 @@ fn setup_lookup_proof(use_values_from_table: bool, num_lookups: usize, table_siz
                 .map(Into::into)
                 .collect();
             LookupTable {
-                id: id as i32,
+                id: id as i64,
                 data: vec![index_column, lookup_table_values.clone()],
             }
         })
@@ -120,18 +147,80 @@ fn lookup_gate_rejects_bad_lookups_multiple_tables() {
     setup_lookup_proof(false, 500, vec![100, 50, 50, 2, 2])
 }
 
+fn setup_multi_table_lookup_proof(use_values_from_table: bool) {
+    // a range table with a single column, and an opcode table
+    let range_table = LookupTable {
+        id: 1,
+        data: vec![(0..16u64).map(Into::into).collect()],
+    };
+    let opcodes: Vec<Fp> = (0..8).map(|_| rand::random()).collect();
+    let opcode_table = LookupTable {
+        id: 2,
+        data: vec![(0..8u64).map(Into::into).collect(), opcodes.clone()],
+    };
+
+    // each row checks an opcode and two operands, the last lookup being unused
+    let num_rows = 20;
+    let gates = (0..num_rows)
+        .map(|row| CircuitGate {
+            typ: GateType::MultiTableLookup,
+            wires: Wire::new(row),
+            coeffs: vec![],
+        })
+        .collect();
+
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); num_rows]);
+    for row in 0..num_rows {
+        let opcode = rand::random::<usize>() % opcodes.len();
+        let lookups = [
+            (1u64, rand::random::<u64>() % 16, Fp::zero()),
+            (2, opcode as u64, opcodes[opcode]),
+            (1, rand::random::<u64>() % 16, Fp::zero()),
+            (0, 0, Fp::zero()),
+        ];
+        for (i, (table_id, index, value)) in lookups.into_iter().enumerate() {
+            witness[3 * i][row] = table_id.into();
+            witness[3 * i + 1][row] = index.into();
+            witness[3 * i + 2][row] = value;
+        }
+    }
+    if !use_values_from_table {
+        // an operand out of range
+        witness[7][num_rows / 2] = 16u64.into();
+    }
+
+    TestFramework::default()
+        .gates(gates)
+        .witness(witness)
+        .lookup_tables(vec![range_table, opcode_table])
+        .setup()
+        .prove_and_verify();
+}
+
+#[test]
+fn multi_table_lookup_gate_proving_works() {
+    setup_multi_table_lookup_proof(true)
+}
+
+#[test]
+#[should_panic]
+fn multi_table_lookup_gate_rejects_bad_lookups() {
+    setup_multi_table_lookup_proof(false)
+}
+
 fn runtime_table(num: usize, indexed: bool) {
     // runtime
     let mut runtime_tables_setup = vec![];
     for table_id in 0..num {
         let cfg = if indexed {
             RuntimeTableCfg::Indexed(RuntimeTableSpec {
-                id: table_id as i32,
+                id: table_id as i64,
                 len: 5,
+                variable_len: false,
             })
         } else {
             RuntimeTableCfg::Custom {
-                id: table_id as i32,
+                id: table_id as i64,
                 first_column: [8u32, 9, 8, 7, 1].into_iter().map(Into::into).collect(),
             }
         };
@@ -200,4 +289,485 @@ fn test_custom_runtime_table() {
     runtime_table(5, false);
 }
 
+#[test]
+fn test_variable_length_runtime_table() {
+    // a runtime table of variable length, followed by one of fixed length
+    let variable = RuntimeTableSpec {
+        id: 0,
+        len: 10,
+        variable_len: true,
+    };
+    let fixed = RuntimeTableSpec {
+        id: 1,
+        len: 5,
+        variable_len: false,
+    };
+    assert!(variable.accepts_len(4) && variable.accepts_len(10) && !variable.accepts_len(11));
+    assert!(fixed.accepts_len(5) && !fixed.accepts_len(4));
+
+    // only 4 entries of the first table are used
+    let runtime_tables = vec![
+        RuntimeTable {
+            id: 0,
+            data: [0u32, 2, 3, 4].into_iter().map(Into::into).collect(),
+        },
+        RuntimeTable {
+            id: 1,
+            data: [0u32, 2, 3, 4, 5].into_iter().map(Into::into).collect(),
+        },
+    ];
+
+    // a circuit looking up `query` in the first table, among valid queries
+    let runner = |query: (u32, u32)| {
+        let gates: Vec<_> = (0..20)
+            .map(|row| CircuitGate {
+                typ: GateType::Lookup,
+                wires: Wire::new(row),
+                coeffs: vec![],
+            })
+            .collect();
+
+        let mut cols: [_; COLUMNS] = array::from_fn(|_col| vec![Fp::zero(); gates.len()]);
+
+        // only the first 7 registers are used in the lookup gate
+        let (lookup_cols, _rest) = cols.split_at_mut(7);
+
+        for row in 0..20 {
+            // the first register is the table id
+            let table_id = row % 2;
+            lookup_cols[0][row] = (table_id as u32).into();
+
+            // the second table starts after all the entries of the first one
+            let queries: [(u32, u32); 3] = if table_id == 0 {
+                [(1, 2), (3, 4), query]
+            } else {
+                [(0, 0), (2, 3), (4, 5)]
+            };
+            let lookup_cols = &mut lookup_cols[1..];
+            for (chunk, (index, value)) in lookup_cols.chunks_mut(2).zip(queries) {
+                chunk[0][row] = index.into();
+                chunk[1][row] = value.into();
+            }
+        }
+
+        TestFramework::default()
+            .gates(gates)
+            .witness(cols)
+            .runtime_tables_setup(vec![
+                RuntimeTableCfg::Indexed(variable.clone()),
+                RuntimeTableCfg::Indexed(fixed.clone()),
+            ])
+            .setup()
+            .runtime_tables(runtime_tables.clone())
+    };
+
+    let mut valid = runner((2, 3));
+    let mut proof = valid.prove().unwrap();
+    valid.verify(&proof).unwrap();
+
+    // the number of entries used is bound to the proof
+    proof.commitments.lookup.as_mut().unwrap().runtime_lens = vec![10];
+    assert!(valid.verify(&proof).is_err());
+    proof.commitments.lookup.as_mut().unwrap().runtime_lens = vec![11];
+    assert!(matches!(
+        valid.verify(&proof),
+        Err(VerifyError::IncorrectRuntimeProof)
+    ));
+
+    // the unused entries cannot be looked up, though the runtime table would be zero there
+    let err = runner((7, 0)).prove().unwrap_err();
+    assert!(matches!(
+        err,
+        ProverError::WitnessNotSatisfied {
+            row: 0,
+            gate: GateType::Lookup,
+            cause: WitnessError::Lookup(2),
+        }
+    ));
+}
+
+#[test]
+fn test_runtime_table_builder() {
+    let specs = [
+        RuntimeTableSpec {
+            id: 0,
+            len: 4,
+            variable_len: true,
+        },
+        RuntimeTableSpec {
+            id: 1,
+            len: 2,
+            variable_len: false,
+        },
+    ];
+    let data = |n: u32| -> Vec<Fp> { (1..=n).map(Into::into).collect() };
+
+    // tables are given in any order, and built in the configured order
+    let tables = RuntimeTable::builder(&specs)
+        .table(1, data(2))
+        .table(0, data(3))
+        .build()
+        .unwrap();
+    assert_eq!(tables.iter().map(|t| t.id).collect::<Vec<_>>(), [0, 1]);
+    assert_eq!(tables[0].data, [1u32, 2, 3].map(Fp::from));
+    assert_eq!(RuntimeTable::check(&specs, &tables), Ok(()));
+
+    // the first mismatch is reported
+    let build = |given: &[(i64, u32)]| {
+        given
+            .iter()
+            .fold(RuntimeTable::builder(&specs), |builder, (id, n)| {
+                builder.table(*id, data(*n))
+            })
+            .build()
+            .map(|_| ())
+    };
+    assert_eq!(build(&[(0, 3)]), Err(RuntimeTableError::Missing(1)));
+    assert_eq!(build(&[(2, 1), (0, 5)]), Err(RuntimeTableError::Unknown(2)));
+    assert_eq!(
+        build(&[(1, 2), (1, 2), (0, 1)]),
+        Err(RuntimeTableError::Duplicate(1))
+    );
+    assert_eq!(
+        build(&[(0, 5), (1, 2)]),
+        Err(RuntimeTableError::Length {
+            id: 0,
+            len: 5,
+            max: 4
+        })
+    );
+    assert_eq!(
+        build(&[(0, 4), (1, 1)]),
+        Err(RuntimeTableError::Length {
+            id: 1,
+            len: 1,
+            max: 2
+        })
+    );
+
+    // the prover expects the configured order
+    let swapped = [tables[1].clone(), tables[0].clone()];
+    assert_eq!(
+        RuntimeTable::check(&specs, &swapped),
+        Err(RuntimeTableError::Order {
+            expected: 0,
+            got: 1
+        })
+    );
+    assert_eq!(
+        RuntimeTable::check(&specs, &tables[..1]),
+        Err(RuntimeTableError::Missing(1))
+    );
+}
+
+#[test]
+fn test_table_id_collisions() {
+    let gates: Vec<_> = (0..20)
+        .map(|row| CircuitGate {
+            typ: GateType::Lookup,
+            wires: Wire::new(row),
+            coeffs: vec![],
+        })
+        .collect();
+    let table = |id, value: u64| LookupTable {
+        id,
+        data: vec![vec![Fp::zero(), Fp::from(value)]],
+    };
+    let build = |lookup_tables, runtime_tables| {
+        ConstraintSystem::create(gates.clone())
+            .lookup(lookup_tables)
+            .runtime(runtime_tables)
+            .build()
+    };
+
+    // tables of different namespaces don't collide
+    let id = namespaced_table_id(1, 7);
+    assert_eq!(table_id_namespace(id), (1, 7));
+    let other_id = namespaced_table_id(u32::MAX, 7);
+    assert_eq!(table_id_namespace(other_id), (u32::MAX, 7));
+    assert!(build(vec![table(0, 1), table(id, 2), table(other_id, 3)], None).is_ok());
+
+    // the same table can be given twice, but not two different ones
+    assert!(build(vec![table(0, 1), table(id, 2), table(id, 2)], None).is_ok());
+    assert!(build(vec![table(0, 1), table(id, 2), table(id, 3)], None).is_err());
+
+    // nor a runtime table with the ID of another table
+    let runtime = |id| {
+        Some(vec![RuntimeTableCfg::Indexed(RuntimeTableSpec {
+            id,
+            len: 5,
+            variable_len: false,
+        })])
+    };
+    assert!(build(vec![table(0, 1)], runtime(id)).is_ok());
+    assert!(build(vec![table(0, 1), table(id, 2)], runtime(id)).is_err());
+}
+
+#[test]
+fn test_bitwise_tables() {
+    let contains = |table: &LookupTable<Fp>, entry: [u64; 2]| {
+        (0..table.len()).any(|row| {
+            table.data[0][row] == entry[0].into() && table.data[1][row] == entry[1].into()
+        })
+    };
+
+    let and = get_table::<Fp>(GateLookupTable::And);
+    let or = get_table::<Fp>(GateLookupTable::Or);
+    let not = get_table::<Fp>(GateLookupTable::Not);
+    assert_eq!((and.len(), or.len(), not.len()), (1 << 16, 1 << 16, 1 << 8));
+
+    // binary operations are indexed by 256 x + y
+    assert!(contains(&and, [256 * 0b1100 + 0b1010, 0b1000]));
+    assert!(contains(&or, [256 * 0b1100 + 0b1010, 0b1110]));
+    assert!(contains(&not, [0b1100, 0b1111_0011]));
+    assert!(!contains(&not, [0b1100, 0b1100]));
+}
+
+#[test]
+fn test_not_table_lookups() {
+    // enough rows for the table
+    let num_lookups = 300;
+    let gates = (0..num_lookups)
+        .map(|i| CircuitGate {
+            typ: GateType::Lookup,
+            coeffs: vec![],
+            wires: Wire::new(i),
+        })
+        .collect();
+
+    let mut witness: [_; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); num_lookups]);
+    for row in 0..num_lookups {
+        witness[0][row] = i64_to_field(NOT_TABLE_ID);
+        for i in 0..3 {
+            let x = rand::random::<u8>();
+            witness[1 + 2 * i][row] = x.into();
+            witness[2 + 2 * i][row] = (!x).into();
+        }
+    }
+
+    TestFramework::default()
+        .gates(gates)
+        .witness(witness)
+        .lookup_tables(vec![get_table(GateLookupTable::Not)])
+        .setup()
+        .prove_and_verify();
+}
+
 // TODO: add a test with a runtime table with ID 0 (it should panic)
+
+#[test]
+fn test_lookup_commitments_in_chunks() {
+    let gates: Vec<_> = (0..20)
+        .map(|row| CircuitGate {
+            typ: GateType::Zero,
+            wires: Wire::new(row),
+            coeffs: vec![],
+        })
+        .collect();
+    let cs = ConstraintSystem::create(gates).build().unwrap();
+    let d1 = cs.domain.d1;
+
+    // an SRS of half the size of the domain
+    let srs = SRS::<Vesta>::create(d1.size() / 2);
+    let (endo_q, _endo_r) = endos::<Pallas>();
+    let index = ProverIndex::<Vesta>::create(cs, endo_q, Arc::new(srs));
+
+    let rng = &mut rand::rngs::OsRng;
+    let evals =
+        Evaluations::from_vec_and_domain((0..d1.size()).map(|_| Fp::rand(rng)).collect(), d1);
+    let coeffs = evals.interpolate_by_ref();
+    let comm = commit_chunked(&index, &evals, &coeffs, rng);
+    assert_eq!(comm.commitment.unshifted.len(), 2);
+
+    // each chunk is blinded separately
+    let non_hiding = index.srs.commit_non_hiding(&coeffs, None);
+    for ((chunk, blinder), expected) in comm
+        .commitment
+        .unshifted
+        .iter()
+        .zip(&comm.blinders.unshifted)
+        .zip(&non_hiding.unshifted)
+    {
+        assert_eq!(
+            chunk.into_projective() - index.srs.h.mul(*blinder),
+            expected.into_projective()
+        );
+    }
+}
+
+#[test]
+fn test_lookup_proof_in_chunks() {
+    // a runtime table of variable length, followed by one of fixed length
+    let specs = vec![
+        RuntimeTableCfg::Indexed(RuntimeTableSpec {
+            id: 0,
+            len: 10,
+            variable_len: true,
+        }),
+        RuntimeTableCfg::Indexed(RuntimeTableSpec {
+            id: 1,
+            len: 5,
+            variable_len: false,
+        }),
+    ];
+    let runtime_tables = vec![
+        RuntimeTable {
+            id: 0,
+            data: [0u32, 2, 3, 4].into_iter().map(Into::into).collect(),
+        },
+        RuntimeTable {
+            id: 1,
+            data: [0u32, 2, 3, 4, 5].into_iter().map(Into::into).collect(),
+        },
+    ];
+
+    // each row looks up three entries of one of the tables
+    let gates: Vec<_> = (0..20)
+        .map(|row| CircuitGate {
+            typ: GateType::Lookup,
+            wires: Wire::new(row),
+            coeffs: vec![],
+        })
+        .collect();
+    let mut witness: [_; COLUMNS] = array::from_fn(|_col| vec![Fp::zero(); gates.len()]);
+    for row in 0..gates.len() {
+        let table_id = row % 2;
+        witness[0][row] = (table_id as u32).into();
+        let queries: [(u32, u32); 3] = if table_id == 0 {
+            [(1, 2), (3, 4), (2, 3)]
+        } else {
+            [(0, 0), (2, 3), (4, 5)]
+        };
+        for (i, (index, value)) in queries.into_iter().enumerate() {
+            witness[1 + 2 * i][row] = index.into();
+            witness[2 + 2 * i][row] = value.into();
+        }
+    }
+
+    // an SRS of a quarter of the size of the domain
+    let cs = ConstraintSystem::create(gates)
+        .runtime(Some(specs))
+        .build()
+        .unwrap();
+    let srs = SRS::<Vesta>::create(cs.domain.d1.size() / 4);
+    let (endo_q, _endo_r) = endos::<Pallas>();
+    let mut index = ProverIndex::<Vesta>::create(cs, endo_q, Arc::new(srs));
+    index.check_witness = true;
+    let verifier_index = index.verifier_index();
+    assert_eq!(verifier_index.chunks(), 4);
+
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let proof = ProverProof::create::<BaseSponge, ScalarSponge>(
+        &group_map,
+        witness,
+        &runtime_tables,
+        &index,
+    )
+    .unwrap();
+
+    // the lookup polynomials are committed and evaluated in chunks
+    let lookup = proof.commitments.lookup.as_ref().unwrap();
+    assert_eq!(lookup.aggreg.unshifted.len(), 4);
+    assert!(lookup.sorted.iter().all(|comm| comm.unshifted.len() == 4));
+    assert!(proof
+        .evals
+        .iter()
+        .all(|e| e.lookup.as_ref().unwrap().aggreg.len() == 4));
+    assert_eq!(proof.commitments.t_comm.unshifted.len(), 4 * PERMUTS);
+
+    let check = |proof: &ProverProof<Vesta>| {
+        verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &verifier_index, proof)
+    };
+    check(&proof).unwrap();
+    batch_verify_strict::<Vesta, BaseSponge, ScalarSponge, _>(
+        &group_map,
+        &[(&verifier_index, &proof)],
+    )
+    .unwrap();
+
+    // an evaluation missing a chunk is rejected
+    let mut bad = proof.clone();
+    bad.evals[1].lookup.as_mut().unwrap().aggreg.pop();
+    assert!(matches!(
+        check(&bad),
+        Err(VerifyError::EvaluationChunksMismatch(
+            "lookup aggregation",
+            4,
+            3
+        ))
+    ));
+
+    // and so is a commitment missing a chunk
+    let mut bad = proof;
+    bad.commitments.lookup.as_mut().unwrap().sorted[0]
+        .unshifted
+        .pop();
+    assert!(matches!(
+        check(&bad),
+        Err(VerifyError::IncorrectCommitmentChunks("sorted", 4, 3))
+    ));
+}
+
+#[test]
+fn test_verify_table_commitments() {
+    let gates: Vec<_> = (0..20)
+        .map(|row| CircuitGate {
+            typ: GateType::Lookup,
+            wires: Wire::new(row),
+            coeffs: vec![],
+        })
+        .collect();
+    let table = |id, value: u64| LookupTable {
+        id,
+        data: vec![
+            vec![Fp::zero(), Fp::from(value)],
+            vec![Fp::zero(), Fp::one()],
+        ],
+    };
+    let runtime_tables = Some(vec![RuntimeTableCfg::Indexed(RuntimeTableSpec {
+        id: 3,
+        len: 5,
+        variable_len: false,
+    })]);
+
+    let verifier_index = |cs: ConstraintSystem<Fp>| {
+        let srs = SRS::<Vesta>::create(cs.domain.d1.size());
+        let (endo_q, _endo_r) = endos::<Pallas>();
+        ProverIndex::<Vesta>::create(cs, endo_q, Arc::new(srs)).verifier_index()
+    };
+    let tables = vec![table(0, 1), table(1, 2)];
+    let cs = ConstraintSystem::create(gates.clone())
+        .lookup(tables.clone())
+        .runtime(runtime_tables.clone())
+        .build()
+        .unwrap();
+    let lookup_index = verifier_index(cs);
+
+    // the commitments are the ones of the specified tables
+    let spec = LookupTableSpec::create(&gates, tables, runtime_tables.clone());
+    lookup_index.verify_table_commitments(&spec).unwrap();
+
+    // but not of other data, nor of other table IDs
+    let mismatch = |tables| {
+        let spec = LookupTableSpec::create(&gates, tables, runtime_tables.clone());
+        match lookup_index.verify_table_commitments(&spec) {
+            Err(VerifierIndexError::TableMismatch(what)) => what,
+            res => panic!("unexpected result {res:?}"),
+        }
+    };
+    assert_eq!(mismatch(vec![table(0, 1), table(1, 3)]), "lookup table");
+    assert_eq!(mismatch(vec![table(0, 1), table(2, 2)]), "table IDs");
+    assert_eq!(mismatch(vec![table(0, 1)]), "lookup table");
+
+    // an index without lookups has no tables to verify
+    let gates: Vec<_> = gates
+        .iter()
+        .map(|gate| CircuitGate {
+            typ: GateType::Zero,
+            ..gate.clone()
+        })
+        .collect();
+    let cs = ConstraintSystem::create(gates.clone()).build().unwrap();
+    let spec = LookupTableSpec::create(&gates, vec![table(0, 1)], None);
+    assert!(verifier_index(cs).verify_table_commitments(&spec).is_err());
+}
@@ -0,0 +1,227 @@
+use crate::{
+    circuits::{
+        gate::CircuitGate,
+        polynomials::generic::testing::{create_circuit, fill_in_witness},
+        wires::COLUMNS,
+    },
+    error::VerifierIndexError,
+    migration::{LayoutVersion, ProverIndexV0, VerifierIndexV0},
+    proof::ProverProof,
+    prover_index::{testing::new_index_for_test, ProverIndex},
+    verifier::verify,
+    verifier_index::{zero_comm, VerifierIndex},
+};
+use ark_ff::{One, Zero};
+use commitment_dlog::{commitment::CommitmentCurve, srs::SRS};
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Fq, Vesta, VestaParameters};
+use num_bigint::BigUint;
+use oracle::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use serde::Serialize;
+use serde_with::serde_as;
+use std::{array, sync::Arc};
+
+type BaseSponge = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
+type ScalarSponge = DefaultFrSponge<Fp, PlonkSpongeConstantsKimchi>;
+
+/// The constraint system of the layout V0, with placeholders for the polynomials it held.
+#[derive(Clone, Serialize)]
+struct LegacyConstraintSystem {
+    public: usize,
+    prev_challenges: usize,
+    domain: (),
+    gates: Vec<CircuitGate<Fp>>,
+    sigmam: (),
+    coefficients8: (),
+    genericm: (),
+    psm: (),
+    generic4: (),
+    sigmal1: (),
+    sigmal8: (),
+    sid: (),
+    ps8: (),
+    complete_addl4: (),
+    mull8: (),
+    emull: (),
+    chacha8: (),
+    endomul_scalar8: (),
+    range_check_selector_polys: Option<()>,
+    foreign_field_modulus: Option<BigUint>,
+    foreign_field_add_selector_poly: Option<()>,
+    shift: (),
+    endo: (),
+    lookup_constraint_system: Option<()>,
+}
+
+/// The prover index of the layout V0.
+#[serde_as]
+#[derive(Clone, Serialize)]
+struct LegacyProverIndex {
+    cs: LegacyConstraintSystem,
+    max_poly_size: usize,
+    max_quot_size: usize,
+    #[serde_as(as = "Option<o1_utils::serialization::SerdeAs>")]
+    verifier_index_digest: Option<Fq>,
+}
+
+/// Returns a prover index, a proof made with it, and the encoding of the index in the layout V0.
+fn legacy_prover_index() -> (ProverIndex<Vesta>, ProverProof<Vesta>, LegacyProverIndex) {
+    let public = vec![Fp::from(3u8); 5];
+    let gates = create_circuit(0, public.len());
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
+    fill_in_witness(0, &mut witness, &public);
+
+    let index = new_index_for_test(gates, public.len());
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let proof =
+        ProverProof::create::<BaseSponge, ScalarSponge>(&group_map, witness, &[], &index).unwrap();
+
+    let legacy = LegacyProverIndex {
+        cs: LegacyConstraintSystem {
+            public: index.cs.public,
+            prev_challenges: index.cs.prev_challenges,
+            domain: (),
+            gates: index.cs.gates.clone(),
+            sigmam: (),
+            coefficients8: (),
+            genericm: (),
+            psm: (),
+            generic4: (),
+            sigmal1: (),
+            sigmal8: (),
+            sid: (),
+            ps8: (),
+            complete_addl4: (),
+            mull8: (),
+            emull: (),
+            chacha8: (),
+            endomul_scalar8: (),
+            range_check_selector_polys: None,
+            foreign_field_modulus: None,
+            foreign_field_add_selector_poly: None,
+            shift: (),
+            endo: (),
+            lookup_constraint_system: None,
+        },
+        max_poly_size: index.max_poly_size,
+        max_quot_size: index.max_quot_size,
+        verifier_index_digest: Some(index.verifier_index_digest::<BaseSponge>()),
+    };
+    (index, proof, legacy)
+}
+
+#[test]
+fn test_migrate_verifier_index() {
+    let (index, proof, _) = legacy_prover_index();
+    let verifier_index = index.verifier_index();
+    let chunks = verifier_index.chunks();
+    let zero = || zero_comm(chunks);
+    let expected_digest = verifier_index.digest::<BaseSponge>();
+
+    // the verifier index, as serialized in the layout V0
+    let legacy = VerifierIndexV0::<Vesta> {
+        domain: verifier_index.domain,
+        max_poly_size: verifier_index.max_poly_size,
+        max_quot_size: verifier_index.max_quot_size,
+        public: verifier_index.public,
+        prev_challenges: verifier_index.prev_challenges,
+        sigma_comm: verifier_index.sigma_comm.clone(),
+        coefficients_comm: verifier_index
+            .coefficients_comm
+            .iter()
+            .map(|comm| comm.clone().unwrap_or_else(zero))
+            .collect(),
+        generic_comm: verifier_index.generic_comm.clone(),
+        psm_comm: verifier_index.psm_comm.clone(),
+        complete_add_comm: verifier_index
+            .complete_add_comm
+            .clone()
+            .unwrap_or_else(zero),
+        mul_comm: verifier_index.mul_comm.clone().unwrap_or_else(zero),
+        emul_comm: verifier_index.emul_comm.clone().unwrap_or_else(zero),
+        endomul_scalar_comm: verifier_index
+            .endomul_scalar_comm
+            .clone()
+            .unwrap_or_else(zero),
+        chacha_comm: None,
+        range_check_comm: None,
+        foreign_field_modulus: None,
+        foreign_field_add_comm: None,
+        shift: verifier_index.shift,
+        lookup_index: None,
+    };
+    let bytes = rmp_serde::to_vec(&legacy).unwrap();
+
+    // the current layout can not read it
+    assert!(rmp_serde::from_slice::<VerifierIndex<Vesta>>(&bytes).is_err());
+
+    // but its migration has the same digest, and verifies the proofs
+    let legacy = VerifierIndexV0::<Vesta>::from_bytes(&bytes).unwrap();
+    assert_eq!(legacy.digest::<BaseSponge>(), expected_digest);
+    let migration = legacy
+        .migrate::<BaseSponge>(index.srs.clone(), None)
+        .unwrap();
+    assert_eq!(migration.from, LayoutVersion::V0);
+    assert_eq!(migration.digest, expected_digest);
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &migration.index, &proof).unwrap();
+
+    // the index only migrates against its SRS
+    let legacy = VerifierIndexV0::<Vesta>::from_bytes(&bytes).unwrap();
+    let srs = Arc::new(SRS::<Vesta>::create(2 * legacy.max_poly_size));
+    assert!(matches!(
+        legacy.migrate::<BaseSponge>(srs, None),
+        Err(VerifierIndexError::IncorrectLayout(LayoutVersion::V0, _))
+    ));
+
+    // trailing data is rejected
+    let mut trailing = bytes;
+    trailing.push(0);
+    assert!(matches!(
+        VerifierIndexV0::<Vesta>::from_bytes(&trailing),
+        Err(VerifierIndexError::IncorrectLayout(LayoutVersion::V0, _))
+    ));
+}
+
+#[test]
+fn test_migrate_prover_index() {
+    let (index, proof, mut legacy_index) = legacy_prover_index();
+    let bytes = rmp_serde::to_vec(&legacy_index).unwrap();
+    let expected_digest = index.verifier_index_digest::<BaseSponge>();
+
+    let legacy = ProverIndexV0::<Vesta>::from_bytes(&bytes).unwrap();
+    assert!(!legacy.uses_lookups());
+    let cs = legacy.constraint_system().build().unwrap();
+    assert_eq!(cs.domain.d1, index.cs.domain.d1);
+    let migration = legacy
+        .migrate::<BaseSponge>(cs, index.cs.endo, index.srs.clone())
+        .unwrap();
+    assert_eq!(migration.digest, expected_digest);
+
+    // the migrated index proves against the verifier index of the original one
+    let public = vec![Fp::from(3u8); 5];
+    let mut witness: [Vec<Fp>; COLUMNS] =
+        array::from_fn(|_| vec![Fp::zero(); migration.index.cs.gates.len()]);
+    fill_in_witness(0, &mut witness, &public);
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let migrated_proof =
+        ProverProof::create::<BaseSponge, ScalarSponge>(&group_map, witness, &[], &migration.index)
+            .unwrap();
+    let verifier_index = index.verifier_index();
+    for proof in [&proof, &migrated_proof] {
+        verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &verifier_index, proof).unwrap();
+    }
+
+    // the cached digest must be the one of the migrated index
+    legacy_index.verifier_index_digest = Some(expected_digest + Fq::one());
+    let bytes = rmp_serde::to_vec(&legacy_index).unwrap();
+    let legacy = ProverIndexV0::<Vesta>::from_bytes(&bytes).unwrap();
+    let cs = legacy.constraint_system().build().unwrap();
+    assert!(matches!(
+        legacy.migrate::<BaseSponge>(cs, index.cs.endo, index.srs.clone()),
+        Err(VerifierIndexError::DigestMismatch)
+    ));
+}
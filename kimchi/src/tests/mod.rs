@@ -1,14 +1,53 @@
+mod app_constants;
+mod canonical;
+#[cfg(feature = "chacha")]
 mod chacha;
+mod check_witness;
+mod codec;
+mod column_opening;
+mod copy_cells;
+mod curve;
+mod domain_plan;
 mod ec;
 mod endomul;
 mod endomul_scalar;
+#[cfg(feature = "foreign_field")]
 mod foreign_field_add;
 mod framework;
+mod gate_info;
 mod generic;
+#[cfg(feature = "index_audit")]
+mod index_audit;
+#[cfg(feature = "keccak")]
+mod keccak;
+mod keystore;
+mod layout;
+mod linearization;
 mod lookup;
+mod migration;
+mod optimizer;
+mod permutation;
+mod plonk_sponge;
 mod poseidon;
+mod precomputations;
+mod proof_link;
+mod prover_stream;
+mod prover_task;
+mod prover_witness;
+#[cfg(feature = "range_check")]
 mod range_check;
 mod recursion;
+mod run_length;
+mod scheme;
 mod serde;
+mod trace;
+#[cfg(feature = "cairo")]
 mod turshi;
+mod validate;
 mod varbasemul;
+mod verifier;
+mod verifier_index;
+mod verifier_index_json;
+mod verifier_math;
+mod witness;
+mod witness_audit;
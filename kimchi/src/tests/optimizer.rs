@@ -0,0 +1,80 @@
+use super::framework::TestFramework;
+use crate::circuits::{
+    gate::{CircuitGate, Connect},
+    optimizer::optimize,
+    polynomials::generic::{
+        testing::{create_circuit, fill_in_witness},
+        GenericGateSpec,
+    },
+    wires::{Wire, COLUMNS},
+};
+use ark_ff::Zero;
+use mina_curves::pasta::Fp;
+use std::array;
+
+#[test]
+fn test_optimize_generic_circuit() {
+    let public = vec![Fp::from(11u32), Fp::from(5u32)];
+    let mut gates = create_circuit(0, public.len());
+    let n = gates.len();
+
+    // a zero gate, through which the first public input is wired to an addition
+    gates.push(CircuitGate::zero(Wire::new(n)));
+    gates.connect_cell_pair((0, 0), (n, 0));
+    gates.connect_cell_pair((n, 0), (2, 0));
+
+    // a dead addition
+    gates.push(CircuitGate::create_generic_gadget(
+        Wire::new(n + 1),
+        GenericGateSpec::Add {
+            left_coeff: None,
+            right_coeff: None,
+            output_coeff: None,
+        },
+        None,
+    ));
+
+    // a generic gate without coefficients, through which two constants are wired
+    gates.push(CircuitGate::create_generic(
+        Wire::new(n + 2),
+        [Fp::zero(); 10],
+    ));
+    gates.connect_cell_pair((n - 1, 3), (n + 2, 3));
+    gates.connect_cell_pair((n + 2, 3), (n - 2, 3));
+
+    // a constant, which is not wired but must be kept
+    gates.push(CircuitGate::create_generic_gadget(
+        Wire::new(n + 3),
+        GenericGateSpec::Const(3u32.into()),
+        None,
+    ));
+
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
+    fill_in_witness(0, &mut witness, &public);
+    witness[0][n] = public[0];
+    witness[3][n + 2] = 5u32.into();
+    witness[0][n + 3] = 3u32.into();
+
+    // the additions and multiplications of the circuit are dead too, except the first one
+    let optimized = optimize(&gates, public.len());
+    assert_eq!(optimized.gates.len(), 14);
+    assert_eq!(optimized.rows[..4], [Some(0), Some(1), Some(2), None]);
+    assert_eq!(optimized.rows[n - 1], Some(12));
+    assert_eq!(optimized.rows[n..], [None, None, None, Some(13)]);
+
+    // the zero gate and the gate without coefficients are skipped in the wiring
+    assert_eq!(optimized.gates[0].wires[0], Wire { row: 2, col: 0 });
+    assert_eq!(optimized.gates[2].wires[0], Wire { row: 0, col: 0 });
+    assert_eq!(optimized.gates[12].wires[3], Wire { row: 11, col: 3 });
+    assert_eq!(optimized.gates[11].wires[3], Wire { row: 12, col: 3 });
+
+    // the optimized circuit can be proven with the remaining rows of the witness
+    let witness = optimized.witness(&witness);
+    assert_eq!(witness[0].len(), 14);
+    TestFramework::default()
+        .gates(optimized.gates)
+        .witness(witness)
+        .public_inputs(public)
+        .setup()
+        .prove_and_verify();
+}
@@ -0,0 +1,324 @@
+use crate::{
+    circuits::{
+        constraints::ConstraintSystem,
+        gate::{CircuitGate, Connect, GateType},
+        polynomials::{
+            generic::GenericGateSpec,
+            permutation::{check_shifts, derive_shifts, prefix_product},
+        },
+        wires::{CellRef, Wire, COLUMNS, PERMUTS},
+    },
+    error::{ProverError, SetupError, VerifyError, WitnessError},
+    proof::ProverProof,
+    prover_index::ProverIndex,
+    verifier::verify,
+    verifier_index_json::VerifierIndexJson,
+};
+use ark_ff::{Field, One, UniformRand, Zero};
+use ark_poly::EvaluationDomain;
+use commitment_dlog::{
+    commitment::CommitmentCurve,
+    srs::{endos, SRS},
+};
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Pallas, Vesta, VestaParameters};
+use oracle::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use std::{array, sync::Arc};
+
+type BaseSponge = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
+type ScalarSponge = DefaultFrSponge<Fp, PlonkSpongeConstantsKimchi>;
+
+/// Returns a chain of additions, the output of each row being the left input of the next one
+fn create_chain(rows: usize) -> Vec<CircuitGate<Fp>> {
+    let mut gates: Vec<_> = (0..rows)
+        .map(|row| {
+            let add = GenericGateSpec::Add {
+                left_coeff: None,
+                right_coeff: None,
+                output_coeff: None,
+            };
+            CircuitGate::create_generic_gadget(Wire::new(row), add, None)
+        })
+        .collect();
+    for row in 1..rows {
+        gates.connect_cell_pair((row - 1, 2), (row, 0));
+    }
+    gates
+}
+
+/// Returns the witness of [`create_chain`], starting from 1 and adding 1 at each row
+fn create_chain_witness(rows: usize) -> [Vec<Fp>; COLUMNS] {
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); rows]);
+    let mut acc = Fp::one();
+    for row in 0..rows {
+        witness[0][row] = acc;
+        witness[1][row] = Fp::one();
+        acc += Fp::one();
+        witness[2][row] = acc;
+    }
+    witness
+}
+
+fn create_index(cs: ConstraintSystem<Fp>) -> ProverIndex<Vesta> {
+    let mut srs = SRS::<Vesta>::create(cs.domain.d1.size());
+    srs.add_lagrange_basis(cs.domain.d1);
+    let (endo_q, _endo_r) = endos::<Pallas>();
+    ProverIndex::<Vesta>::create(cs, endo_q, Arc::new(srs))
+}
+
+#[test]
+fn test_permutation_over_first_columns() {
+    let rows = 20;
+    let cs = ConstraintSystem::create(create_chain(rows))
+        .permuted_columns(3)
+        .build()
+        .unwrap();
+    assert_eq!(cs.permuted_columns(), 3);
+    assert_eq!(cs.sigmam.len(), 3);
+
+    let mut index = create_index(cs);
+    index.check_witness = true;
+    let verifier_index = index.verifier_index();
+    assert_eq!(verifier_index.sigma_comm.len(), 3);
+
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let witness = create_chain_witness(rows);
+    index.cs.verify::<Vesta>(&witness, &[]).unwrap();
+    let proof =
+        ProverProof::create::<BaseSponge, ScalarSponge>(&group_map, witness.clone(), &[], &index)
+            .unwrap();
+    assert!(proof.evals.iter().all(|e| e.s.len() == 2));
+    verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &verifier_index, &proof).unwrap();
+
+    // an index committing to no permutation polynomial is rejected, rather than underflowing
+    let mut empty_index = verifier_index.clone();
+    empty_index.sigma_comm.clear();
+    assert!(matches!(
+        verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &empty_index, &proof),
+        Err(VerifyError::IncorrectCommitmentLength("sigma"))
+    ));
+
+    // the copy constraints of the permuted columns are enforced
+    let mut bad_witness = witness;
+    bad_witness[0][5] += Fp::one();
+    bad_witness[2][5] += Fp::one();
+    assert!(index.cs.verify::<Vesta>(&bad_witness, &[]).is_err());
+    assert!(matches!(
+        ProverProof::create::<BaseSponge, ScalarSponge>(&group_map, bad_witness, &[], &index),
+        Err(ProverError::WitnessNotSatisfied {
+            cause: WitnessError::Wiring(..),
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_permutation_wiring_outside_permuted_columns() {
+    // the chain wires the third column
+    assert!(ConstraintSystem::create(create_chain(4))
+        .permuted_columns(2)
+        .build()
+        .is_err());
+
+    // some columns must take part in the permutation
+    assert!(ConstraintSystem::create(create_chain(4))
+        .permuted_columns(0)
+        .build()
+        .is_err());
+}
+
+#[test]
+fn test_connect_cells() {
+    let rows = 10;
+    let gates: Vec<_> = (0..rows)
+        .map(|row| {
+            let add = GenericGateSpec::Add {
+                left_coeff: None,
+                right_coeff: None,
+                output_coeff: None,
+            };
+            CircuitGate::create_generic_gadget(Wire::new(row), add, None)
+        })
+        .collect();
+
+    // the same chain as `create_chain`, through the builder
+    let builder = (1..rows).fold(ConstraintSystem::create(gates.clone()), |builder, row| {
+        builder.connect((row - 1, 2), CellRef::new(row, 0))
+    });
+    let cs = builder.build().unwrap();
+    let chain = ConstraintSystem::create(create_chain(rows))
+        .build()
+        .unwrap();
+    assert!(cs
+        .gates
+        .iter()
+        .zip(&chain.gates)
+        .all(|(gate, expected)| gate.wires == expected.wires));
+    cs.verify::<Vesta>(&create_chain_witness(rows), &[])
+        .unwrap();
+
+    // a cell out of the circuit is reported
+    let err = ConstraintSystem::create(gates.clone())
+        .connect((0, 2), (rows, 0))
+        .build()
+        .unwrap_err();
+    assert!(matches!(err, SetupError::Connection(_, CellRef { row, col: 0 }, _, _) if row == rows));
+
+    // and so is a pair of cells already connected, along with where they were connected
+    let err = ConstraintSystem::create(gates)
+        .connect((0, 2), (1, 0))
+        .connect((1, 0), (0, 2))
+        .build()
+        .unwrap_err();
+    match err {
+        SetupError::Connection(a, b, _, location) => {
+            assert_eq!((a, b), (CellRef::new(1, 0), CellRef::new(0, 2)));
+            assert!(location.contains("permutation.rs"));
+        }
+        err => panic!("unexpected error: {err}"),
+    }
+}
+
+#[test]
+fn test_constants_pool() {
+    let rows = 10;
+
+    // the right inputs of the chain are all one, and so is its first left input
+    let builder = (0..rows).fold(
+        ConstraintSystem::create(create_chain(rows)).constant(Fp::one(), (0, 0)),
+        |builder, row| builder.constant(Fp::one(), (row, 1)),
+    );
+    let cs = builder
+        .constant(Fp::from(5u32), (3, 2))
+        .constant(Fp::from(7u32), (5, 2))
+        .constant(Fp::from(11u32), (rows - 1, 2))
+        .build()
+        .unwrap();
+
+    // the four distinct constants take two rows
+    assert_eq!(cs.constants.len(), 4);
+    assert_eq!(cs.constants[0].cell, CellRef::new(rows, 0));
+    assert_eq!(cs.constants[1].cell, CellRef::new(rows, 3));
+    assert_eq!(cs.constants[3].cell, CellRef::new(rows + 1, 3));
+    assert!(cs.gates[rows + 2..]
+        .iter()
+        .all(|gate| gate.typ == GateType::Zero));
+
+    let mut witness = create_chain_witness(rows);
+    cs.fill_constants(&mut witness);
+    assert_eq!(witness[0].len(), rows + 2);
+    cs.verify::<Vesta>(&witness, &[]).unwrap();
+
+    let index = create_index(cs);
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let proof =
+        ProverProof::create::<BaseSponge, ScalarSponge>(&group_map, witness.clone(), &[], &index)
+            .unwrap();
+    verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &index.verifier_index(), &proof)
+        .unwrap();
+
+    // the pinned cells cannot hold another value
+    let mut bad_witness = witness;
+    bad_witness[1][4] += Fp::one();
+    assert!(index.cs.verify::<Vesta>(&bad_witness, &[]).is_err());
+
+    // a cell cannot be pinned twice, nor be out of the circuit
+    let err = ConstraintSystem::create(create_chain(rows))
+        .constant(Fp::one(), (0, 1))
+        .constant(Fp::zero(), (0, 1))
+        .build()
+        .unwrap_err();
+    assert!(matches!(err, SetupError::Constant(cell, _, _) if cell == CellRef::new(0, 1)));
+    let err = ConstraintSystem::create(create_chain(rows))
+        .constant(Fp::one(), (rows, 0))
+        .build()
+        .unwrap_err();
+    assert!(matches!(err, SetupError::Constant(cell, _, _) if cell == CellRef::new(rows, 0)));
+}
+
+#[test]
+fn test_prefix_product() {
+    let rng = &mut rand::rngs::OsRng;
+
+    // lengths below, at, and above the number of chunks
+    for len in [0, 1, 2, 7, rayon::current_num_threads() + 1, 1000] {
+        let values: Vec<_> = (0..len).map(|_| Fp::rand(rng)).collect();
+        let expected: Vec<_> = values
+            .iter()
+            .scan(Fp::one(), |acc, value| {
+                *acc *= value;
+                Some(*acc)
+            })
+            .collect();
+
+        let mut products = values;
+        prefix_product(&mut products);
+        assert_eq!(products, expected);
+    }
+}
+
+#[test]
+fn test_custom_shifts() {
+    let rows = 20;
+    let default_index = create_index(
+        ConstraintSystem::create(create_chain(rows))
+            .build()
+            .unwrap(),
+    );
+    let domain = default_index.cs.domain.d1;
+
+    // the shifts of more columns extend the shifts of the circuits
+    let derived = derive_shifts(&domain, 2 * PERMUTS);
+    assert_eq!(derived[..PERMUTS], default_index.cs.shift);
+    check_shifts(&domain, &derived).unwrap();
+
+    // the shifts must be non-zero and in distinct cosets of the domain
+    assert!(check_shifts(&domain, &[Fp::one(), Fp::zero()]).is_err());
+    assert!(check_shifts(&domain, &[derived[1], derived[1] * domain.group_gen]).is_err());
+    let mut invalid: [Fp; PERMUTS] = array::from_fn(|i| derived[i]);
+    invalid[3] = invalid[2] * domain.group_gen.square();
+    assert!(matches!(
+        ConstraintSystem::create(create_chain(rows))
+            .shifts(invalid)
+            .build(),
+        Err(SetupError::Shifts(_))
+    ));
+
+    // valid custom shifts are used by the prover and the verifier
+    let custom: [Fp; PERMUTS] = array::from_fn(|i| derived[PERMUTS + i]);
+    let cs = ConstraintSystem::create(create_chain(rows))
+        .shifts(custom)
+        .build()
+        .unwrap();
+    assert_eq!(cs.shift, custom);
+    let index = create_index(cs);
+    let verifier_index = index.verifier_index();
+
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let proof = ProverProof::create::<BaseSponge, ScalarSponge>(
+        &group_map,
+        create_chain_witness(rows),
+        &[],
+        &index,
+    )
+    .unwrap();
+    verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &verifier_index, &proof).unwrap();
+
+    // they are bound to the digest, including once exported and imported
+    let digest = verifier_index.digest::<BaseSponge>();
+    assert_ne!(digest, default_index.verifier_index_digest::<BaseSponge>());
+    let imported = VerifierIndexJson::export::<_, BaseSponge>(&verifier_index, None)
+        .unwrap()
+        .import::<Vesta, BaseSponge>(index.srs.clone())
+        .unwrap();
+    assert_eq!(imported.shift, custom);
+    assert_eq!(imported.digest::<BaseSponge>(), digest);
+
+    // and the proof does not verify with the derived shifts
+    let mut other_shifts = verifier_index;
+    other_shifts.shift = default_index.cs.shift;
+    verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &other_shifts, &proof).unwrap_err();
+}
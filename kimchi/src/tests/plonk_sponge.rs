@@ -0,0 +1,142 @@
+use crate::{
+    circuits::{
+        polynomials::generic::testing::{create_circuit, fill_in_witness},
+        wires::COLUMNS,
+    },
+    curve::KimchiCurve,
+    plonk_sponge::FrSponge,
+    proof::ProverProof,
+    prover_index::testing::new_index_for_test,
+    verifier::{capture_oracles, verify},
+};
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{BigInteger, PrimeField, UniformRand, Zero};
+use commitment_dlog::commitment::{CommitmentCurve, PolyComm};
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Fq, Pallas, Vesta, VestaParameters};
+use oracle::{
+    constants::{PlonkSpongeConstantsKimchi as SC, SpongeConstants},
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use rand::{rngs::StdRng, SeedableRng};
+use std::array;
+
+fn random_point<G: AffineCurve>(rng: &mut StdRng) -> G {
+    G::prime_subgroup_generator()
+        .mul(G::ScalarField::rand(rng))
+        .into_affine()
+}
+
+#[test]
+fn test_absorb_commitment() {
+    let rng = &mut StdRng::from_seed([0u8; 32]);
+
+    // the base field of Vesta is larger than its scalar field,
+    // so that coordinates are absorbed as their high bits and low bit
+    let comm = PolyComm {
+        unshifted: vec![random_point::<Vesta>(rng), Vesta::zero()],
+        shifted: Some(random_point::<Vesta>(rng)),
+    };
+    let mut sponge = DefaultFrSponge::<Fp, SC>::new(Vesta::sponge_params());
+    sponge.absorb_commitment(&comm);
+
+    let mut expected = DefaultFrSponge::<Fp, SC>::new(Vesta::sponge_params());
+    for g in [comm.unshifted[0], comm.unshifted[1], comm.shifted.unwrap()] {
+        let coordinates = if g.infinity {
+            [Fq::zero(), Fq::zero()]
+        } else {
+            [g.x, g.y]
+        };
+        for c in coordinates {
+            let mut high = c.into_repr();
+            let low = Fp::from(u64::from(high.is_odd()));
+            high.div2();
+            expected.absorb_multiple(&[Fp::from_repr(high).unwrap(), low]);
+        }
+    }
+    assert_eq!(sponge.digest(), expected.digest());
+
+    // the base field of Pallas is smaller than its scalar field,
+    // so that coordinates are absorbed as they are
+    let comm = PolyComm {
+        unshifted: vec![random_point::<Pallas>(rng), random_point::<Pallas>(rng)],
+        shifted: None,
+    };
+    let mut sponge = DefaultFrSponge::<Fq, SC>::new(Pallas::sponge_params());
+    sponge.absorb_commitment(&comm);
+
+    let mut expected = DefaultFrSponge::<Fq, SC>::new(Pallas::sponge_params());
+    for g in &comm.unshifted {
+        for c in [g.x, g.y] {
+            expected.absorb(&Fq::from_repr(c.into_repr()).unwrap());
+        }
+    }
+    assert_eq!(sponge.digest(), expected.digest());
+}
+
+#[test]
+fn test_squeeze_n() {
+    let mut sponge = DefaultFrSponge::<Fp, SC>::new(Vesta::sponge_params());
+    sponge.absorb(&Fp::from(42u64));
+    let mut expected = DefaultFrSponge::<Fp, SC>::new(Vesta::sponge_params());
+    expected.absorb(&Fp::from(42u64));
+
+    // a leftover limb is used by the first challenge
+    assert_eq!(sponge.squeeze(1), expected.squeeze(1));
+    let challenges = sponge.squeeze_n(4);
+    let sequential: Vec<_> = (0..4).map(|_| expected.challenge()).collect();
+    assert_eq!(challenges, sequential);
+    assert_eq!(sponge.last_squeezed, expected.last_squeezed);
+    assert_eq!(sponge.digest(), expected.digest());
+}
+
+/// The Kimchi constants, with full-width challenges.
+struct FullWidthConstants;
+
+impl SpongeConstants for FullWidthConstants {
+    const PERM_ROUNDS_FULL: usize = SC::PERM_ROUNDS_FULL;
+    const PERM_ROUNDS_PARTIAL: usize = SC::PERM_ROUNDS_PARTIAL;
+    const PERM_HALF_ROUNDS_FULL: usize = SC::PERM_HALF_ROUNDS_FULL;
+    const PERM_SBOX: u32 = SC::PERM_SBOX;
+    const PERM_FULL_MDS: bool = SC::PERM_FULL_MDS;
+    const PERM_INITIAL_ARK: bool = SC::PERM_INITIAL_ARK;
+    const CHALLENGE_LENGTH_IN_LIMBS: usize = 4;
+}
+
+#[test]
+fn test_full_width_challenges() {
+    type BaseSponge = DefaultFqSponge<VestaParameters, FullWidthConstants>;
+    type ScalarSponge = DefaultFrSponge<Fp, FullWidthConstants>;
+
+    let gates = create_circuit(0, 0);
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
+    fill_in_witness(0, &mut witness, &[]);
+    let index = new_index_for_test(gates, 0);
+    let verifier_index = index.verifier_index();
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+
+    // the challenges of the proof, and of its opening, are derived over 256 bits
+    let proof =
+        ProverProof::create::<BaseSponge, ScalarSponge>(&group_map, witness, &[], &index).unwrap();
+    verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &verifier_index, &proof).unwrap();
+    let oracles = capture_oracles::<Vesta, BaseSponge, ScalarSponge, _>(&verifier_index, &proof)
+        .unwrap()
+        .oracles;
+    assert!(oracles.zeta_chal.0.into_repr().num_bits() > 128);
+    assert_eq!(
+        oracles.zeta,
+        oracles
+            .zeta_chal
+            .to_field_with_length(256, &Vesta::endos().1)
+    );
+
+    // which the default sponges do not reproduce
+    assert!(
+        verify::<Vesta, DefaultFqSponge<VestaParameters, SC>, DefaultFrSponge<Fp, SC>, _>(
+            &group_map,
+            &verifier_index,
+            &proof
+        )
+        .is_err()
+    );
+}
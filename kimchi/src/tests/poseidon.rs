@@ -2,16 +2,20 @@ use crate::{
     circuits::{
         gate::CircuitGate,
         polynomials,
-        polynomials::poseidon::ROUNDS_PER_ROW,
+        polynomials::poseidon::{rows_per_hash, ROUNDS_PER_ROW},
         wires::{Wire, COLUMNS},
     },
     curve::KimchiCurve,
+    error::SetupError,
     tests::framework::TestFramework,
 };
 use ark_ff::Zero;
 use mina_curves::pasta::{Fp, Vesta};
 use o1_utils::math;
-use oracle::constants::{PlonkSpongeConstantsKimchi, SpongeConstants};
+use oracle::{
+    constants::{PlonkSpongeConstantsKimchi, SpongeConfig, SpongeConstants},
+    permutation::poseidon_block_cipher_with_config,
+};
 use std::array;
 
 // aliases
@@ -82,3 +86,66 @@ fn test_poseidon() {
         .setup()
         .prove_and_verify();
 }
+
+#[test]
+fn test_poseidon_with_config() {
+    // a research variant, with fewer rounds than the kimchi sponge
+    let config = SpongeConfig {
+        rounds_full: 20,
+        ..SpongeConfig::KIMCHI
+    };
+    let rows = rows_per_hash(&config).unwrap();
+    assert_eq!(rows, 4);
+
+    let params = Vesta::sponge_params();
+    let (gates, last_row) = CircuitGate::<Fp>::create_poseidon_gadget_with_config(
+        0,
+        [Wire::new(0), Wire::new(rows)],
+        &config,
+        &params.round_constants,
+    )
+    .unwrap();
+    assert_eq!(last_row, rows);
+
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); rows + 1]);
+    let input = [Fp::from(1u32), Fp::from(2u32), Fp::from(3u32)];
+    polynomials::poseidon::generate_witness_with_config(0, &config, params, &mut witness, input);
+
+    // the output row holds the permutation of the input
+    let mut output = input;
+    poseidon_block_cipher_with_config(&config, params, &mut output);
+    assert_eq!(output, [0, 1, 2].map(|col| witness[col][rows]));
+
+    TestFramework::default()
+        .gates(gates)
+        .witness(witness)
+        .setup()
+        .prove_and_verify();
+
+    // the gate only constrains full rounds of the kimchi S-box, 5 at a time
+    for config in [
+        SpongeConfig::LEGACY,
+        SpongeConfig {
+            rounds_full: 21,
+            ..SpongeConfig::KIMCHI
+        },
+        SpongeConfig {
+            rate: 3,
+            ..SpongeConfig::KIMCHI
+        },
+    ] {
+        assert!(matches!(
+            rows_per_hash(&config),
+            Err(SetupError::ConstraintSystem(_))
+        ));
+    }
+    assert!(matches!(
+        CircuitGate::<Fp>::create_poseidon_gadget_with_config(
+            0,
+            [Wire::new(0), Wire::new(rows)],
+            &config,
+            &params.round_constants[..10],
+        ),
+        Err(SetupError::ConstraintSystem(_))
+    ));
+}
@@ -0,0 +1,65 @@
+use crate::circuits::{
+    constraints::ConstraintSystem, domain_constant_evaluation::DomainConstantEvaluations,
+    domains::EvaluationDomains, expr::l0_1, gate::CircuitGate, wires::Wire,
+};
+use ark_ff::{PrimeField, SquareRootField};
+use mina_curves::pasta::{Fp, Fq};
+use std::sync::Arc;
+
+fn circuit<F: PrimeField + SquareRootField>(rows: usize) -> ConstraintSystem<F> {
+    let gates = (0..rows)
+        .map(|row| CircuitGate::zero(Wire::new(row)))
+        .collect();
+    ConstraintSystem::create(gates).build().unwrap()
+}
+
+#[test]
+fn test_precomputations_are_shared() {
+    // circuits of different sizes, but over the same domain, share their precomputations
+    let cs1 = circuit::<Fp>(20);
+    let cs2 = circuit::<Fp>(25);
+    assert_eq!(cs1.domain.d1.size, cs2.domain.d1.size);
+    assert!(Arc::ptr_eq(cs1.precomputations(), cs2.precomputations()));
+
+    // but not across domain sizes
+    let cs3 = circuit::<Fp>(100);
+    assert_ne!(cs1.domain.d1.size, cs3.domain.d1.size);
+    assert!(!Arc::ptr_eq(cs1.precomputations(), cs3.precomputations()));
+    assert_eq!(cs3.precomputations().l0_1, l0_1(cs3.domain.d1));
+
+    // nor across fields
+    let cs4 = circuit::<Fq>(20);
+    assert_eq!(cs1.domain.d1.size, cs4.domain.d1.size);
+    assert_eq!(cs4.precomputations().l0_1, l0_1(cs4.domain.d1));
+
+    // the cached precomputations match freshly created ones
+    let domain = EvaluationDomains::<Fp>::create(20).unwrap();
+    let cached = DomainConstantEvaluations::cached(domain).unwrap();
+    let created = DomainConstantEvaluations::create(domain).unwrap();
+    assert_eq!(cached.zkpm, created.zkpm);
+    assert_eq!(cached.l0_1, created.l0_1);
+    assert_eq!(
+        cached.vanishes_on_last_4_rows,
+        created.vanishes_on_last_4_rows
+    );
+}
+
+#[test]
+fn test_precomputations_are_freed() {
+    // a domain no other test uses
+    let domain = EvaluationDomains::<Fq>::create(3000).unwrap();
+    let cached = DomainConstantEvaluations::cached(domain).unwrap();
+    assert!(Arc::ptr_eq(
+        &cached,
+        &DomainConstantEvaluations::cached(domain).unwrap()
+    ));
+
+    // the precomputations are not kept once unused
+    let weak = Arc::downgrade(&cached);
+    drop(cached);
+    assert!(weak.upgrade().is_none());
+
+    // and are created again when needed
+    let cached = DomainConstantEvaluations::cached(domain).unwrap();
+    assert_eq!(cached.l0_1, l0_1(domain.d1));
+}
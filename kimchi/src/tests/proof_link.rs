@@ -0,0 +1,107 @@
+use crate::{
+    circuits::{
+        polynomials::generic::testing::{create_circuit, fill_in_witness},
+        wires::{CellRef, COLUMNS},
+    },
+    error::{ProverError, VerifyError},
+    proof::ProverProof,
+    proof_link::ProofLink,
+    prover_index::{testing::new_index_for_test, ProverIndex},
+    prover_witness::ProverWitness,
+};
+use ark_ff::Zero;
+use commitment_dlog::commitment::CommitmentCurve;
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use oracle::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use std::array;
+
+type BaseSponge = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
+type ScalarSponge = DefaultFrSponge<Fp, PlonkSpongeConstantsKimchi>;
+
+/// Creates the index of a generic circuit with `public` inputs, and a proof of it.
+fn prove(
+    group_map: &<Vesta as CommitmentCurve>::Map,
+    public: &[Fp],
+) -> (ProverIndex<Vesta>, ProverWitness<Vesta>, ProverProof<Vesta>) {
+    let gates = create_circuit(0, public.len());
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
+    fill_in_witness(0, &mut witness, public);
+    let index = new_index_for_test(gates, public.len());
+    let mut prover_witness = ProverWitness::create(&index, witness).unwrap();
+    let proof = ProverProof::create_incremental::<BaseSponge, ScalarSponge>(
+        group_map,
+        &mut prover_witness,
+        public,
+        &index,
+        vec![],
+    )
+    .unwrap();
+    (index, prover_witness, proof)
+}
+
+#[test]
+fn test_proof_link() {
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let (left_index, left_witness, left_proof) = prove(&group_map, &[Fp::from(3u8); 5]);
+    let (right_index, right_witness, right_proof) =
+        prove(&group_map, &[Fp::from(11u8), Fp::from(5u8)]);
+    let left_verifier = left_index.verifier_index();
+    let right_verifier = right_index.verifier_index();
+
+    // the public inputs of the right circuit are computed by the left one, and conversely
+    let links = [
+        (CellRef::new(5, 0), CellRef::new(0, 0)),
+        (CellRef::new(15, 3), CellRef::new(1, 0)),
+        (CellRef::new(0, 0), CellRef::new(12, 0)),
+    ];
+    let link = ProofLink::create::<BaseSponge>(
+        &group_map,
+        (&left_index, &left_witness),
+        (&right_index, &right_witness),
+        &links,
+    )
+    .unwrap();
+    link.verify::<BaseSponge>(
+        &group_map,
+        (&left_verifier, &left_proof),
+        (&right_verifier, &right_proof),
+    )
+    .unwrap();
+
+    // the proofs cannot be swapped
+    assert!(link
+        .verify::<BaseSponge>(
+            &group_map,
+            (&right_verifier, &right_proof),
+            (&left_verifier, &left_proof),
+        )
+        .is_err());
+
+    // nor can the shared commitments
+    let mut wrong = link.clone();
+    wrong.commitments.swap(0, 1);
+    assert!(matches!(
+        wrong.verify::<BaseSponge>(
+            &group_map,
+            (&left_verifier, &left_proof),
+            (&right_verifier, &right_proof),
+        ),
+        Err(VerifyError::OpenProof)
+    ));
+
+    // cells holding different values cannot be linked
+    let unequal = (CellRef::new(5, 1), CellRef::new(0, 0));
+    assert!(matches!(
+        ProofLink::create::<BaseSponge>(
+            &group_map,
+            (&left_index, &left_witness),
+            (&right_index, &right_witness),
+            &[unequal],
+        ),
+        Err(ProverError::UnequalLink(l, r)) if (l, r) == unequal
+    ));
+}
@@ -0,0 +1,103 @@
+use crate::{
+    circuits::{
+        polynomials::generic::testing::{create_circuit, fill_in_witness},
+        wires::COLUMNS,
+    },
+    proof::ProverProof,
+    prover_index::testing::new_index_for_test,
+    prover_stream::ProvingJob,
+    prover_task::CancellationToken,
+    verifier::verify,
+};
+use ark_ff::Zero;
+use commitment_dlog::commitment::CommitmentCurve;
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use oracle::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use std::{
+    array,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+type BaseSponge = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
+type ScalarSponge = DefaultFrSponge<Fp, PlonkSpongeConstantsKimchi>;
+
+#[test]
+fn test_create_stream() {
+    let public = vec![Fp::from(3u8); 5];
+    let gates = create_circuit(0, public.len());
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
+    fill_in_witness(0, &mut witness, &public);
+    let index = Arc::new(new_index_for_test(gates, public.len()));
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+
+    // the witnesses are produced on demand,
+    // the third one being invalid for the first add gate (after the public inputs)
+    let row = public.len();
+    let taken = Arc::new(AtomicUsize::new(0));
+    let jobs = {
+        let taken = taken.clone();
+        (0..5).map(move |i| {
+            taken.fetch_add(1, Ordering::Relaxed);
+            let mut witness = witness.clone();
+            if i == 2 {
+                witness[2][row] += Fp::from(1u8);
+            }
+            ProvingJob::new(witness)
+        })
+    };
+    let stream = ProverProof::create_stream::<BaseSponge, ScalarSponge, _>(
+        &group_map,
+        jobs,
+        index.clone(),
+        2,
+        CancellationToken::new(),
+    );
+
+    // every job gives its proof, tagged with its position
+    let mut proofs: Vec<_> = stream.collect();
+    assert_eq!(taken.load(Ordering::Relaxed), 5);
+    proofs.sort_by_key(|(position, _)| *position);
+    assert_eq!(
+        proofs
+            .iter()
+            .map(|(position, _)| *position)
+            .collect::<Vec<_>>(),
+        vec![0, 1, 2, 3, 4]
+    );
+    let verifier_index = index.verifier_index();
+    for (position, proof) in proofs {
+        match proof {
+            Ok(proof) => {
+                assert_ne!(position, 2);
+                verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &verifier_index, &proof)
+                    .unwrap();
+            }
+            Err(_) => assert_eq!(position, 2),
+        }
+    }
+
+    // a cancelled stream takes no job
+    let token = CancellationToken::new();
+    token.cancel();
+    let taken = Arc::new(AtomicUsize::new(0));
+    let jobs = {
+        let taken = taken.clone();
+        let witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![]);
+        std::iter::repeat_with(move || {
+            taken.fetch_add(1, Ordering::Relaxed);
+            ProvingJob::new(witness.clone())
+        })
+    };
+    let mut stream = ProverProof::create_stream::<BaseSponge, ScalarSponge, _>(
+        &group_map, jobs, index, 2, token,
+    );
+    assert!(stream.next().is_none());
+    assert_eq!(taken.load(Ordering::Relaxed), 0);
+}
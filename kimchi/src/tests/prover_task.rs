@@ -0,0 +1,110 @@
+use crate::{
+    circuits::{
+        polynomials::generic::testing::{create_circuit, fill_in_witness},
+        wires::COLUMNS,
+    },
+    error::ProverError,
+    proof::ProverProof,
+    prover_index::{testing::new_index_for_test, ProverIndex},
+    prover_task::{CancellationToken, ProvingPhase, ProvingTask},
+    verifier::verify,
+};
+use ark_ff::Zero;
+use commitment_dlog::commitment::CommitmentCurve;
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use oracle::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use std::{
+    array,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll, Wake},
+    thread::{self, Thread},
+};
+
+type BaseSponge = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
+type ScalarSponge = DefaultFrSponge<Fp, PlonkSpongeConstantsKimchi>;
+
+/// Wakes a thread waiting for a future
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Waits for the proof of `task`, as an async runtime would.
+fn block_on(task: &mut ProvingTask<Vesta>) -> Result<ProverProof<Vesta>, ProverError> {
+    let waker = Arc::new(ThreadWaker(thread::current())).into();
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match Pin::new(&mut *task).poll(&mut cx) {
+            Poll::Ready(result) => return result,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+/// Starts proving a generic circuit.
+fn start(
+    group_map: &<Vesta as CommitmentCurve>::Map,
+    token: CancellationToken,
+) -> (Arc<ProverIndex<Vesta>>, ProvingTask<Vesta>) {
+    let public = vec![Fp::from(3u8); 5];
+    let gates = create_circuit(0, public.len());
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
+    fill_in_witness(0, &mut witness, &public);
+    let index = Arc::new(new_index_for_test(gates, public.len()));
+    let task = ProverProof::create_async::<BaseSponge, ScalarSponge>(
+        group_map,
+        witness,
+        vec![],
+        index.clone(),
+        vec![],
+        token,
+    );
+    (index, task)
+}
+
+#[test]
+fn test_create_async() {
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let (index, mut task) = start(&group_map, CancellationToken::new());
+    let proof = block_on(&mut task).unwrap();
+    verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &index.verifier_index(), &proof)
+        .unwrap();
+
+    // every phase was reported in order, and then the end of the proof
+    let events: Vec<_> = task.events().collect();
+    let phases: Vec<_> = events.iter().map(|event| event.phase).collect();
+    assert_eq!(
+        phases,
+        [
+            Some(ProvingPhase::Witness),
+            Some(ProvingPhase::Lookup),
+            Some(ProvingPhase::Permutation),
+            Some(ProvingPhase::Quotient),
+            Some(ProvingPhase::Evaluations),
+            Some(ProvingPhase::Opening),
+            None,
+        ]
+    );
+    assert!(events.windows(2).all(|w| w[0].percent < w[1].percent));
+    assert_eq!(events[0].eta, None);
+    assert_eq!(events[6].percent, 100);
+}
+
+#[test]
+fn test_create_async_cancelled() {
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let token = CancellationToken::new();
+    token.cancel();
+    let (_, mut task) = start(&group_map, token);
+    assert!(matches!(block_on(&mut task), Err(ProverError::Cancelled)));
+    assert_eq!(task.events().count(), 0);
+}
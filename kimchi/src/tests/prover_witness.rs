@@ -0,0 +1,141 @@
+use crate::{
+    circuits::{
+        gate::CircuitGate,
+        polynomials::generic::testing::{create_circuit, fill_in_witness},
+        wires::{Wire, COLUMNS},
+    },
+    error::ProverError,
+    proof::ProverProof,
+    prover_index::{testing::new_index_for_test, ProverIndex},
+    prover_witness::ProverWitness,
+    verifier::verify,
+};
+use ark_ff::Zero;
+use ark_poly::{EvaluationDomain, Evaluations};
+use commitment_dlog::commitment::CommitmentCurve;
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use oracle::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use std::array;
+
+type BaseSponge = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
+type ScalarSponge = DefaultFrSponge<Fp, PlonkSpongeConstantsKimchi>;
+
+/// Returns the witness of the generic test circuit for `public`.
+fn witness(len: usize, public: &[Fp]) -> [Vec<Fp>; COLUMNS] {
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); len]);
+    fill_in_witness(0, &mut witness, public);
+    witness
+}
+
+/// Creates a proof from `witness` and verifies it.
+fn prove_and_verify(
+    index: &ProverIndex<Vesta>,
+    witness: &mut ProverWitness<Vesta>,
+) -> ProverProof<Vesta> {
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let proof = ProverProof::create_incremental::<BaseSponge, ScalarSponge>(
+        &group_map,
+        witness,
+        &[],
+        index,
+        vec![],
+    )
+    .unwrap();
+    verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &index.verifier_index(), &proof)
+        .unwrap();
+    proof
+}
+
+#[test]
+fn test_incremental_proof() {
+    let public = vec![Fp::from(3u8); 5];
+    let gates = create_circuit(0, public.len());
+    let len = gates.len();
+    let index = new_index_for_test(gates, public.len());
+
+    let mut prover_witness = ProverWitness::create(&index, witness(len, &public)).unwrap();
+    let first = prove_and_verify(&index, &mut prover_witness);
+
+    // proving the same witness again uses fresh randomness
+    let second = prove_and_verify(&index, &mut prover_witness);
+    assert_ne!(
+        first.commitments.w_comm[0].unshifted,
+        second.commitments.w_comm[0].unshifted
+    );
+
+    // changed rows give the same polynomials and commitments as a full recomputation,
+    // whether they are updated incrementally (a single row) or not (a whole column)
+    for changed in [1, public.len()] {
+        let public: Vec<_> = (0..public.len())
+            .map(|i| Fp::from(3u8 + u8::from(i < changed)))
+            .collect();
+        let new_witness = witness(len, &public);
+        prover_witness.update(&index, &new_witness).unwrap();
+        assert_eq!(prover_witness.witness(), new_witness);
+
+        let d1 = index.cs.domain.d1;
+        for col in 0..COLUMNS {
+            let evals = Evaluations::from_vec_and_domain(prover_witness.columns[col].clone(), d1);
+            assert_eq!(evals.evals.len(), d1.size());
+            assert_eq!(
+                index
+                    .srs
+                    .commit_evaluations_non_hiding(d1, &evals, None)
+                    .unshifted,
+                prover_witness.non_hiding[col].unshifted
+            );
+            assert_eq!(evals.interpolate(), prover_witness.polys[col]);
+        }
+
+        let proof = prove_and_verify(&index, &mut prover_witness);
+        assert_eq!(proof.public, public);
+    }
+
+    // the witness must keep its length
+    let mut short_witness = prover_witness.witness();
+    for col in &mut short_witness {
+        col.pop();
+    }
+    let err = prover_witness.update(&index, &short_witness);
+    assert!(err.is_err());
+}
+
+#[test]
+fn test_committed_witness_proof() {
+    let public = vec![Fp::from(3u8); 5];
+    let gates = create_circuit(0, public.len());
+    let len = gates.len();
+    let index = new_index_for_test(gates.clone(), public.len());
+
+    // the witness is committed to first
+    let committed = ProverWitness::commit(&index, witness(len, &public)).unwrap();
+    let w_comm = committed.commitments().clone();
+
+    // and then interpolated, to create the proof of the committed witness
+    let mut prover_witness = committed.interpolate(&index).unwrap();
+    assert_eq!(prover_witness.witness(), witness(len, &public));
+    let proof = prove_and_verify(&index, &mut prover_witness);
+    for (proof_comm, comm) in proof.commitments.w_comm.iter().zip(&w_comm) {
+        assert_eq!(proof_comm.unshifted, comm.unshifted);
+    }
+
+    // a later proof of the same witness does not reuse its randomness
+    let proof = prove_and_verify(&index, &mut prover_witness);
+    for (proof_comm, comm) in proof.commitments.w_comm.iter().zip(&w_comm) {
+        assert_ne!(proof_comm.unshifted, comm.unshifted);
+    }
+
+    // the committed witness only interpolates against an index of its domain
+    let mut larger = gates;
+    larger.extend((len..4 * len).map(|row| CircuitGate::zero(Wire::new(row))));
+    let larger = new_index_for_test(larger, public.len());
+    let committed = ProverWitness::commit(&index, witness(len, &public)).unwrap();
+    assert!(matches!(
+        committed.interpolate(&larger),
+        Err(ProverError::WitnessCsInconsistent)
+    ));
+}
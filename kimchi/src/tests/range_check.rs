@@ -5,16 +5,17 @@ use crate::{
         polynomial::COLUMNS,
         polynomials::{
             generic::GenericGateSpec,
-            range_check::{self},
+            range_check::{self, gadget::RangeCheckStrategy},
         },
         wires::Wire,
     },
     proof::ProverProof,
     prover_index::testing::new_index_for_test_with_lookups,
+    tests::framework::TestFramework,
 };
 
 use ark_ec::AffineCurve;
-use ark_ff::{Field, One, Zero};
+use ark_ff::{Field, One, PrimeField, Zero};
 use mina_curves::pasta::{Fp, Pallas, Vesta, VestaParameters};
 use o1_utils::FieldHelpers;
 
@@ -1117,7 +1118,105 @@ fn verify_range_check_valid_proof1() {
     let verifier_index = prover_index.verifier_index();
 
     // Verify proof
-    let res = verify::<Vesta, BaseSponge, ScalarSponge>(&group_map, &verifier_index, &proof);
+    let res = verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &verifier_index, &proof);
 
     assert!(!res.is_err());
 }
+
+#[test]
+fn verify_range_check_bits_strategy() {
+    // small widths are cheaper to decompose into bits
+    for bits in 1..=5 {
+        assert_eq!(RangeCheckStrategy::choose(bits), RangeCheckStrategy::Gates);
+    }
+    for bits in [6, 64, 88, 89, 176, 177, 255] {
+        assert_eq!(RangeCheckStrategy::choose(bits), RangeCheckStrategy::Lookup);
+    }
+
+    assert_eq!(RangeCheckStrategy::Lookup.rows(64), 5);
+    assert_eq!(RangeCheckStrategy::Lookup.rows(88), 5);
+    assert_eq!(RangeCheckStrategy::Lookup.rows(176), 5);
+    assert_eq!(RangeCheckStrategy::Lookup.rows(255), 10);
+    assert_eq!(
+        CircuitGate::<Fp>::create_range_check_bits(3, 255).0,
+        3 + RangeCheckStrategy::Lookup.rows(255)
+    );
+    assert_eq!(CircuitGate::<Fp>::create_range_check_bits(3, 4).0, 3 + 4);
+}
+
+#[test]
+fn verify_range_check_bits() {
+    let widths = [1, 5, 6, 64, 88, 100, 176, 200, 255];
+
+    // Test circuit layout
+    //    Row Gate        Cells       Description
+    //      0 GenericPub  v <-,       Public value, wired to the first range check
+    //      1 ...         v <-'       A range check gadget for each width
+    let mut gates = vec![CircuitGate::<Fp>::create_generic_gadget(
+        Wire::new(0),
+        GenericGateSpec::Pub,
+        None,
+    )];
+    let mut starts = vec![];
+    let mut next_row = 1;
+    for bits in widths {
+        starts.push(next_row);
+        let (subsequent_row, mut range_check_gates) =
+            CircuitGate::create_range_check_bits(next_row, bits);
+        gates.append(&mut range_check_gates);
+        next_row = subsequent_row;
+    }
+    gates[0].wires[0] = Wire { row: 1, col: 0 };
+    gates[1].wires[0] = Wire { row: 0, col: 0 };
+
+    // Temporary workaround for lookup-table/domain-size issue
+    for _ in 0..(1 << 13) {
+        gates.push(CircuitGate::zero(Wire::new(next_row)));
+        next_row += 1;
+    }
+
+    let cs = ConstraintSystem::create(gates.clone())
+        .public(1)
+        .build()
+        .unwrap();
+
+    let witness = |values: &[Fp]| {
+        let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero()]);
+        witness[0][0] = values[0];
+        for (value, bits) in values.iter().zip(widths) {
+            let mut range_check_witness = range_check::witness::create_bits_witness(*value, bits);
+            for col in 0..COLUMNS {
+                witness[col].append(&mut range_check_witness[col]);
+            }
+        }
+        witness
+    };
+
+    // the largest values of each width are in range
+    let max_values: Vec<_> = widths
+        .iter()
+        .map(|bits| Fp::from(2u64).pow([*bits as u64]) - Fp::one())
+        .collect();
+    let valid_witness = witness(&max_values);
+    assert_eq!(valid_witness[0].len(), starts.last().unwrap() + 10);
+    cs.verify::<Vesta>(&valid_witness, &max_values[..1])
+        .unwrap();
+
+    // but not the next ones (when they are not reduced modulo the field)
+    for (i, bits) in widths.iter().enumerate() {
+        if *bits >= Fp::size_in_bits() {
+            continue;
+        }
+        let mut values = max_values.clone();
+        values[i] = Fp::from(2u64).pow([*bits as u64]);
+        assert!(cs.verify::<Vesta>(&witness(&values), &values[..1]).is_err());
+    }
+
+    TestFramework::default()
+        .gates(gates)
+        .witness(valid_witness)
+        .public_inputs(max_values[..1].to_vec())
+        .lookup_tables(vec![range_check::gadget::lookup_table()])
+        .setup()
+        .prove_and_verify();
+}
@@ -0,0 +1,75 @@
+use crate::{
+    circuits::{
+        polynomials::generic::testing::create_circuit, run_length::RunLengthColumn, wires::COLUMNS,
+    },
+    prover_index::testing::new_index_for_test,
+    verifier_index::zero_comm,
+};
+use ark_ff::Zero;
+use ark_poly::{EvaluationDomain, Evaluations};
+use mina_curves::pasta::Fp;
+
+#[test]
+fn test_run_length_column() {
+    let values = [1u8, 1, 0, 0, 0, 2, 2, 2, 0, 0].map(Fp::from);
+    let column = RunLengthColumn::new(values);
+
+    // the trailing zeros are left out
+    assert_eq!(
+        column.runs(),
+        &[(Fp::from(1u8), 2), (Fp::zero(), 3), (Fp::from(2u8), 3)]
+    );
+    assert_eq!(column.len(), 8);
+    assert_eq!(
+        column.nonzero_runs().collect::<Vec<_>>(),
+        vec![(0..2, Fp::from(1u8)), (5..8, Fp::from(2u8))]
+    );
+    for (row, value) in values.iter().enumerate() {
+        assert_eq!(column.get(row), *value);
+    }
+    assert_eq!(column.get(100), Fp::zero());
+    assert!(RunLengthColumn::new(vec![Fp::zero(); 4]).is_empty());
+}
+
+#[test]
+fn test_coefficients() {
+    let gates = create_circuit::<Fp>(0, 0);
+    let index = new_index_for_test(gates.clone(), 0);
+    let cs = &index.cs;
+    let verifier_index = index.verifier_index();
+    let chunks = verifier_index.chunks();
+    let zero = || zero_comm(chunks);
+
+    for col in 0..COLUMNS {
+        // the columns hold the coefficients of the gates
+        let dense: Vec<_> = (0..cs.domain.d1.size())
+            .map(|row| {
+                gates
+                    .get(row)
+                    .and_then(|gate| gate.coeffs.get(col).copied())
+                    .unwrap_or_else(Fp::zero)
+            })
+            .collect();
+        assert_eq!(cs.coefficients[col].evaluations(cs.domain.d1).evals, dense);
+
+        // the evaluations over d8 and the commitments are the ones of the dense columns
+        let dense = Evaluations::from_vec_and_domain(dense, cs.domain.d1);
+        assert_eq!(
+            cs.coefficients8()[col].evals,
+            dense
+                .interpolate_by_ref()
+                .evaluate_over_domain(cs.domain.d8)
+                .evals
+        );
+        assert_eq!(
+            verifier_index.coefficients_comm[col]
+                .clone()
+                .unwrap_or_else(zero)
+                .unshifted,
+            index
+                .srs
+                .commit_evaluations_non_hiding(cs.domain.d1, &dense, None)
+                .unshifted
+        );
+    }
+}
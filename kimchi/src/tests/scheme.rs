@@ -0,0 +1,172 @@
+use crate::{
+    circuits::{
+        constraints::ConstraintSystem,
+        polynomials::generic::testing::{create_circuit, fill_in_witness},
+        wires::COLUMNS,
+    },
+    proof::ProverProof,
+    prover_index::ProverIndex,
+    verifier::{batch_verify_with_diagnostics, verify},
+};
+use ark_ff::Zero;
+use ark_poly::{univariate::DensePolynomial, Evaluations, Radix2EvaluationDomain as D};
+use commitment_dlog::{
+    commitment::{BatchEvaluationProof, BlindedCommitment, CommitmentCurve, PolyComm},
+    error::CommitmentError,
+    evaluation_proof::OpeningProof,
+    scheme::{OpeningPolynomial, PolynomialCommitment},
+    srs::{endos, SRS},
+};
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Fq, Pallas, Vesta, VestaParameters};
+use oracle::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+    FqSponge,
+};
+use rand::{CryptoRng, RngCore};
+use std::{
+    array,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+type BaseSponge = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
+type ScalarSponge = DefaultFrSponge<Fp, PlonkSpongeConstantsKimchi>;
+
+/// The inner product argument as a scheme of its own,
+/// counting the openings and the verifications it goes through
+#[derive(Clone, Debug)]
+struct CountingScheme {
+    srs: SRS<Vesta>,
+    opened: Arc<AtomicUsize>,
+    verified: Arc<AtomicUsize>,
+}
+
+impl PolynomialCommitment<Vesta> for CountingScheme {
+    type OpeningProof = OpeningProof<Vesta>;
+
+    fn create(depth: usize) -> Self {
+        CountingScheme {
+            srs: SRS::create(depth),
+            opened: Arc::default(),
+            verified: Arc::default(),
+        }
+    }
+
+    fn add_lagrange_basis(&mut self, domain: D<Fp>) {
+        self.srs.add_lagrange_basis(domain)
+    }
+
+    fn lagrange_basis(&self, domain: D<Fp>) -> Option<&[Vesta]> {
+        PolynomialCommitment::lagrange_basis(&self.srs, domain)
+    }
+
+    fn max_poly_size(&self) -> usize {
+        self.srs.max_poly_size()
+    }
+
+    fn blinding_commitment(&self) -> Vesta {
+        self.srs.blinding_commitment()
+    }
+
+    fn mask_custom(
+        &self,
+        comm: PolyComm<Vesta>,
+        blinders: &PolyComm<Fp>,
+    ) -> Result<BlindedCommitment<Vesta>, CommitmentError> {
+        self.srs.mask_custom(comm, blinders)
+    }
+
+    fn commit(
+        &self,
+        plnm: &DensePolynomial<Fp>,
+        max: Option<usize>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> BlindedCommitment<Vesta> {
+        self.srs.commit(plnm, max, rng)
+    }
+
+    fn commit_non_hiding(&self, plnm: &DensePolynomial<Fp>, max: Option<usize>) -> PolyComm<Vesta> {
+        self.srs.commit_non_hiding(plnm, max)
+    }
+
+    fn commit_evaluations_non_hiding(
+        &self,
+        domain: D<Fp>,
+        plnm: &Evaluations<Fp, D<Fp>>,
+        max: Option<usize>,
+    ) -> PolyComm<Vesta> {
+        self.srs.commit_evaluations_non_hiding(domain, plnm, max)
+    }
+
+    fn open<EFqSponge, RNG>(
+        &self,
+        group_map: &<Vesta as CommitmentCurve>::Map,
+        plnms: &[OpeningPolynomial<Fp>],
+        elm: &[Fp],
+        polyscale: Fp,
+        evalscale: Fp,
+        sponge: EFqSponge,
+        rng: &mut RNG,
+    ) -> OpeningProof<Vesta>
+    where
+        EFqSponge: Clone + FqSponge<Fq, Vesta, Fp>,
+        RNG: RngCore + CryptoRng,
+    {
+        self.opened.fetch_add(1, Ordering::SeqCst);
+        self.srs
+            .open(group_map, plnms, elm, polyscale, evalscale, sponge, rng)
+    }
+
+    fn batch_verify<EFqSponge, RNG>(
+        &self,
+        group_map: &<Vesta as CommitmentCurve>::Map,
+        batch: &mut [BatchEvaluationProof<Vesta, EFqSponge>],
+        rng: &mut RNG,
+    ) -> bool
+    where
+        EFqSponge: FqSponge<Fq, Vesta, Fp>,
+        RNG: RngCore + CryptoRng,
+    {
+        self.verified.fetch_add(1, Ordering::SeqCst);
+        self.srs.verify(group_map, batch, rng)
+    }
+}
+
+#[test]
+fn test_generic_commitment_scheme() {
+    let gates = create_circuit(0, 0);
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
+    fill_in_witness(0, &mut witness, &[]);
+
+    let cs = ConstraintSystem::create(gates).build().unwrap();
+    let mut scheme = CountingScheme::create(cs.domain.d1.size());
+    scheme.add_lagrange_basis(cs.domain.d1);
+    let (endo_q, _) = endos::<Pallas>();
+    let index = ProverIndex::<Vesta, CountingScheme>::create(cs, endo_q, Arc::new(scheme));
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+
+    // the prover opens through the scheme of its index
+    let proof =
+        ProverProof::create::<BaseSponge, ScalarSponge>(&group_map, witness, &[], &index).unwrap();
+    assert_eq!(index.srs.opened.load(Ordering::SeqCst), 1);
+
+    // and the verifiers verify through it, the verifier index sharing the scheme of the prover index
+    let verifier_index = index.verifier_index();
+    verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &verifier_index, &proof).unwrap();
+    assert_eq!(index.srs.verified.load(Ordering::SeqCst), 1);
+    batch_verify_with_diagnostics::<Vesta, BaseSponge, ScalarSponge, _>(
+        &group_map,
+        &[(&verifier_index, &proof)],
+    )
+    .unwrap();
+    assert_eq!(index.srs.verified.load(Ordering::SeqCst), 2);
+
+    // the proof carries the opening proof of the scheme
+    let bytes = rmp_serde::to_vec(&proof).unwrap();
+    let decoded: ProverProof<Vesta, CountingScheme> = rmp_serde::from_slice(&bytes).unwrap();
+    verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &verifier_index, &decoded).unwrap();
+}
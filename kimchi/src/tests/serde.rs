@@ -4,16 +4,22 @@ use crate::{
         polynomials::generic::testing::{create_circuit, fill_in_witness},
         wires::COLUMNS,
     },
-    proof::ProverProof,
+    error::VerifyError,
+    proof::{ProverProof, RecursionChallenge, MAX_PREV_CHALLENGES, MAX_PUBLIC},
     prover_index::testing::new_index_for_test,
-    verifier::verify,
+    verifier::{decode_canonical_proof, verify, verify_canonical},
     verifier_index::VerifierIndex,
 };
-use ark_ec::short_weierstrass_jacobian::GroupAffine;
+use ark_ec::{short_weierstrass_jacobian::GroupAffine, AffineCurve};
 use ark_ff::Zero;
-use commitment_dlog::{commitment::CommitmentCurve, srs::SRS};
+use commitment_dlog::{
+    commitment::{CommitmentCurve, PolyComm, MAX_CHUNKS},
+    srs::SRS,
+};
 use groupmap::GroupMap;
 use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use num_bigint::BigUint;
+use o1_utils::{serialization::LengthLimitExceeded, FieldHelpers};
 use oracle::{
     constants::PlonkSpongeConstantsKimchi,
     sponge::{DefaultFqSponge, DefaultFrSponge},
@@ -84,8 +90,138 @@ mod tests {
 
         // verify the proof
         let start = Instant::now();
-        verify::<Vesta, BaseSponge, ScalarSponge>(&group_map, &verifier_index_deserialize, &proof)
-            .unwrap();
+        verify::<Vesta, BaseSponge, ScalarSponge, _>(
+            &group_map,
+            &verifier_index_deserialize,
+            &proof,
+        )
+        .unwrap();
         println!("- time to verify: {}ms", start.elapsed().as_millis());
     }
+
+    #[test]
+    fn test_deserialization_limits() {
+        let ctx = BenchmarkCtx::new(1 << 4);
+        let proof = ctx.create_proof();
+
+        let deserialize = |proof: &ProverProof<Vesta>| {
+            let bytes = rmp_serde::to_vec(proof).unwrap();
+            decode_canonical_proof::<Vesta, SRS<Vesta>>(&bytes)
+        };
+        let exceeds = |proof: &ProverProof<Vesta>, len, max| {
+            matches!(
+                deserialize(proof),
+                Err(VerifyError::LengthLimitExceeded(err)) if err == LengthLimitExceeded { len, max }
+            )
+        };
+        assert!(deserialize(&proof).is_ok());
+
+        // too many chunks in a witness commitment
+        let mut bad_proof = proof.clone();
+        bad_proof.commitments.w_comm[0].unshifted =
+            vec![Vesta::prime_subgroup_generator(); MAX_CHUNKS + 1];
+        assert!(exceeds(&bad_proof, MAX_CHUNKS + 1, MAX_CHUNKS));
+
+        // too many chunks in an evaluation
+        let mut bad_proof = proof.clone();
+        bad_proof.evals[0].z = vec![Fp::zero(); MAX_CHUNKS + 1];
+        assert!(exceeds(&bad_proof, MAX_CHUNKS + 1, MAX_CHUNKS));
+
+        // too many public inputs
+        let mut bad_proof = proof.clone();
+        bad_proof.public = vec![Fp::zero(); MAX_PUBLIC + 1];
+        assert!(exceeds(&bad_proof, MAX_PUBLIC + 1, MAX_PUBLIC));
+
+        // too many previous challenges
+        let mut bad_proof = proof;
+        let challenge = RecursionChallenge {
+            chals: vec![Fp::zero()],
+            comm: PolyComm {
+                unshifted: vec![Vesta::prime_subgroup_generator()],
+                shifted: None,
+            },
+        };
+        bad_proof.prev_challenges = vec![challenge.clone(); MAX_PREV_CHALLENGES];
+        assert!(deserialize(&bad_proof).is_ok());
+        bad_proof.prev_challenges.push(challenge);
+        assert!(exceeds(
+            &bad_proof,
+            MAX_PREV_CHALLENGES + 1,
+            MAX_PREV_CHALLENGES
+        ));
+    }
+
+    #[test]
+    fn test_canonical_proof() {
+        let public = vec![Fp::from(3u8); 5];
+        let gates = create_circuit(0, public.len());
+        let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
+        fill_in_witness(0, &mut witness, &public);
+
+        let index = new_index_for_test(gates, public.len());
+        let verifier_index = index.verifier_index();
+        let group_map = <Vesta as CommitmentCurve>::Map::setup();
+        let proof =
+            ProverProof::create::<BaseSponge, ScalarSponge>(&group_map, witness, &[], &index)
+                .unwrap();
+        let bytes = rmp_serde::to_vec(&proof).unwrap();
+
+        let verify_bytes = |bytes: &[u8]| {
+            verify_canonical::<Vesta, BaseSponge, ScalarSponge, _>(
+                &group_map,
+                &verifier_index,
+                bytes,
+            )
+        };
+        let non_canonical = |bytes: &[u8]| match verify_bytes(bytes) {
+            Err(VerifyError::NonCanonicalProof(reason)) => reason,
+            _ => panic!("the encoding should be rejected"),
+        };
+
+        // the canonical encoding verifies
+        let decoded = verify_bytes(&bytes).unwrap();
+        assert_eq!(rmp_serde::to_vec(&decoded).unwrap(), bytes);
+
+        // trailing data
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(non_canonical(&trailing), "trailing data");
+
+        // the proof encoded as a map rather than an array, which decodes to the same proof
+        let named = rmp_serde::to_vec_named(&proof).unwrap();
+        let same: ProverProof<Vesta> = rmp_serde::from_slice(&named).unwrap();
+        verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &verifier_index, &same).unwrap();
+        assert_eq!(non_canonical(&named), "non-canonical encoding");
+
+        // the bytes of `ft_eval1`, preceded by their MessagePack header
+        let ft_eval1 = proof.ft_eval1.to_bytes();
+        let start = bytes
+            .windows(ft_eval1.len())
+            .position(|window| window == ft_eval1)
+            .unwrap();
+        assert_eq!(bytes[start - 2..start], [0xc4, 32]);
+        let replace = |encoding: &[u8]| {
+            let mut replaced = bytes[..start - 2].to_vec();
+            replaced.extend(encoding);
+            replaced.extend(&bytes[start + ft_eval1.len()..]);
+            replaced
+        };
+
+        // a byte trailing the field element, which its decoding ignores
+        let mut padded = vec![0xc4, 33];
+        padded.extend(&ft_eval1);
+        padded.push(0);
+        let padded = replace(&padded);
+        let same: ProverProof<Vesta> = rmp_serde::from_slice(&padded).unwrap();
+        assert_eq!(same.ft_eval1, proof.ft_eval1);
+        assert_eq!(non_canonical(&padded), "non-canonical encoding");
+
+        // the field element not reduced modulo the modulus
+        let unreduced: BigUint = Into::<BigUint>::into(proof.ft_eval1) + Fp::modulus_biguint();
+        let mut unreduced = unreduced.to_bytes_le();
+        unreduced.resize(32, 0);
+        let mut encoding = vec![0xc4, 32];
+        encoding.extend(unreduced);
+        assert_eq!(non_canonical(&replace(&encoding)), "malformed encoding");
+    }
 }
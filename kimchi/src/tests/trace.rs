@@ -0,0 +1,112 @@
+use super::framework::TestFramework;
+use crate::circuits::{
+    gate::CircuitGate,
+    polynomials::generic::GenericGateSpec,
+    trace::{StepLayout, TraceToWitness},
+    wires::{Wire, COLUMNS},
+};
+use ark_ff::{One, Zero};
+use mina_curves::pasta::Fp;
+
+/// A step of the execution of a Fibonacci sequence
+struct Fibonacci {
+    a: Fp,
+    b: Fp,
+}
+
+/// Runs the Fibonacci sequence for `steps` steps.
+fn trace(steps: usize) -> Vec<Fibonacci> {
+    let mut trace = vec![Fibonacci {
+        a: Fp::zero(),
+        b: Fp::one(),
+    }];
+    while trace.len() < steps {
+        let last = trace.last().unwrap();
+        let (a, b) = (last.b, last.a + last.b);
+        trace.push(Fibonacci { a, b });
+    }
+    trace
+}
+
+/// Lays out a step as a row `[a, b, a + b]`,
+/// and the transition to the next step as a row `[a + b, b']`.
+fn layout() -> StepLayout<Fibonacci, Fp> {
+    StepLayout::default()
+        .row(|step| {
+            let mut row = [Fp::zero(); COLUMNS];
+            row[..3].copy_from_slice(&[step.a, step.b, step.a + step.b]);
+            row
+        })
+        .transition(|step, next| {
+            let mut row = [Fp::zero(); COLUMNS];
+            row[..2].copy_from_slice(&[step.a + step.b, next.b]);
+            row
+        })
+}
+
+/// The gates of the layout: an addition per step, and an equality per transition.
+fn circuit(steps: usize) -> Vec<CircuitGate<Fp>> {
+    let mut gates = vec![];
+    for step in 0..steps {
+        gates.push(CircuitGate::create_generic_gadget(
+            Wire::new(gates.len()),
+            GenericGateSpec::Add {
+                left_coeff: None,
+                right_coeff: None,
+                output_coeff: None,
+            },
+            None,
+        ));
+        if step + 1 < steps {
+            gates.push(CircuitGate::create_generic_gadget(
+                Wire::new(gates.len()),
+                GenericGateSpec::Add {
+                    left_coeff: None,
+                    right_coeff: Some(-Fp::one()),
+                    output_coeff: Some(Fp::zero()),
+                },
+                None,
+            ));
+        }
+    }
+    gates
+}
+
+#[test]
+fn test_step_layout_rows() {
+    let layout = layout();
+    assert_eq!(layout.num_rows(0), 0);
+    assert_eq!(layout.num_rows(1), 1);
+    assert_eq!(layout.num_rows(4), 7);
+    assert!(layout.rows(&[]).is_empty());
+
+    let trace = trace(4);
+    let rows = layout.rows(&trace);
+    assert_eq!(rows.len(), layout.num_rows(4));
+
+    // the rows of the steps alternate with the rows of the transitions
+    for (i, step) in trace.iter().enumerate() {
+        assert_eq!(rows[2 * i][..3], [step.a, step.b, step.a + step.b]);
+    }
+    assert_eq!(rows[1][..2], [Fp::one(), Fp::one()]);
+    assert_eq!(rows[5][..2], [Fp::from(3u64), Fp::from(3u64)]);
+
+    let witness = layout.witness(&trace);
+    for (col, column) in witness.iter().enumerate() {
+        assert!(column
+            .iter()
+            .zip(&rows)
+            .all(|(cell, row)| *cell == row[col]));
+    }
+}
+
+#[test]
+fn test_step_layout_prove() {
+    let steps = 10;
+    let witness = layout().witness(&trace(steps));
+    TestFramework::default()
+        .gates(circuit(steps))
+        .witness(witness)
+        .setup()
+        .prove_and_verify();
+}
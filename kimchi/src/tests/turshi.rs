@@ -73,6 +73,8 @@ fn test_cairo_gate() {
     let ninstr = prog.trace().len();
     let inirow = 0;
     let (circuit, _) = CircuitGate::<F>::create_cairo_gadget(inirow, ninstr);
+    assert_eq!(witness[0].len(), circuit.len());
+    assert_eq!(cairo_layout::<F>().num_rows(ninstr) + 1, circuit.len());
 
     // Verify each gate
     let mut row = 0;
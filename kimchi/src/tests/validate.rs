@@ -0,0 +1,104 @@
+use crate::{
+    circuits::{
+        constraints::ConstraintSystem,
+        gate::{CircuitGate, CircuitGateError, GateType},
+        polynomials::{generic::testing::create_circuit, poseidon::POS_ROWS_PER_HASH},
+        wires::Wire,
+    },
+    curve::KimchiCurve,
+    error::SetupError,
+};
+use mina_curves::pasta::{Fp, Vesta};
+
+/// Returns the error of building a circuit of `gates`, padded with generic gates.
+fn build(mut gates: Vec<CircuitGate<Fp>>) -> Result<(), SetupError> {
+    let row = gates.len();
+    gates.extend(create_circuit(row, 0));
+    ConstraintSystem::create(gates).build().map(|_| ())
+}
+
+#[test]
+fn test_validate_poseidon() {
+    let round_constants = &Vesta::sponge_params().round_constants;
+    let (gates, _) = CircuitGate::<Fp>::create_poseidon_gadget(
+        0,
+        [Wire::new(0), Wire::new(POS_ROWS_PER_HASH)],
+        round_constants,
+    );
+    build(gates.clone()).unwrap();
+
+    // a permutation spans all its rows
+    let mut truncated = gates.clone();
+    truncated.remove(POS_ROWS_PER_HASH - 1);
+    truncated[POS_ROWS_PER_HASH - 1] = CircuitGate::zero(Wire::new(POS_ROWS_PER_HASH - 1));
+    assert!(matches!(
+        build(truncated),
+        Err(SetupError::Gate(
+            0,
+            CircuitGateError::BlockLength(GateType::Poseidon, rows)
+        )) if rows == POS_ROWS_PER_HASH - 1
+    ));
+
+    // and is followed by its output row
+    let mut unterminated = gates.clone();
+    unterminated.pop();
+    assert!(matches!(
+        build(unterminated),
+        Err(SetupError::Gate(
+            row,
+            CircuitGateError::NextGate(GateType::Poseidon, GateType::Generic)
+        )) if row == POS_ROWS_PER_HASH - 1
+    ));
+
+    // each row holds the round constants of its rounds
+    let mut missing_constants = gates;
+    missing_constants[3].coeffs.pop();
+    assert!(matches!(
+        build(missing_constants),
+        Err(SetupError::Gate(
+            3,
+            CircuitGateError::CoefficientCount(GateType::Poseidon, _)
+        ))
+    ));
+}
+
+#[test]
+fn test_validate() {
+    let gate = |typ, row| CircuitGate {
+        typ,
+        wires: Wire::new(row),
+        coeffs: vec![],
+    };
+    build(vec![gate(GateType::VarBaseMul, 0), gate(GateType::Zero, 1)]).unwrap();
+
+    // the scalar multiplication spans two rows
+    assert!(matches!(
+        build(vec![gate(GateType::VarBaseMul, 0)]),
+        Err(SetupError::Gate(
+            0,
+            CircuitGateError::NextGate(GateType::VarBaseMul, GateType::Generic)
+        ))
+    ));
+
+    // only the generic and poseidon gates have coefficients
+    let mut add = gate(GateType::CompleteAdd, 0);
+    add.coeffs.push(Fp::from(1u8));
+    assert!(matches!(
+        build(vec![add]),
+        Err(SetupError::Gate(
+            0,
+            CircuitGateError::CoefficientCount(GateType::CompleteAdd, 1)
+        ))
+    ));
+
+    // the wires stay within the circuit
+    let mut add = gate(GateType::CompleteAdd, 0);
+    add.wires[2].row = 1 << 20;
+    assert!(matches!(
+        build(vec![add]),
+        Err(SetupError::Gate(
+            0,
+            CircuitGateError::WireRow(GateType::CompleteAdd, 2, row)
+        )) if row == 1 << 20
+    ));
+}
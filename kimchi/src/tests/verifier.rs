@@ -0,0 +1,442 @@
+use crate::{
+    circuits::{
+        constraints::ConstraintSystem,
+        expr::Column,
+        polynomials::generic::testing::{create_circuit, fill_in_witness},
+        wires::COLUMNS,
+    },
+    error::{CycleVerifyError, EvaluationPoint, OpenedPolynomial, ProverError, VerifyError},
+    proof::{LookupCommitments, ProverProof},
+    prover_index::{testing::new_index_for_test, ProverIndex},
+    prover_witness::ProverWitness,
+    verifier::{
+        batch_verify, batch_verify_strict, batch_verify_with_context,
+        batch_verify_with_diagnostics, capture_oracles, public_commitments, public_evaluations,
+        replay_oracles, verify, verify_with_context,
+    },
+};
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{One, UniformRand, Zero};
+use ark_poly::{EvaluationDomain, Evaluations, Polynomial, Radix2EvaluationDomain as D};
+use commitment_dlog::{
+    commitment::CommitmentCurve,
+    error::OpeningError,
+    srs::{endos, SRS},
+};
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Fq, Pallas, PallasParameters, Vesta, VestaParameters};
+use oracle::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use rand::{rngs::StdRng, SeedableRng};
+use std::{array, sync::Arc};
+
+type BaseSponge = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
+type ScalarSponge = DefaultFrSponge<Fp, PlonkSpongeConstantsKimchi>;
+type PallasBaseSponge = DefaultFqSponge<PallasParameters, PlonkSpongeConstantsKimchi>;
+type PallasScalarSponge = DefaultFrSponge<Fq, PlonkSpongeConstantsKimchi>;
+
+#[test]
+fn test_public_evaluations() {
+    let rng = &mut StdRng::from_seed([0u8; 32]);
+    let domain = D::<Fp>::new(16).unwrap();
+    let public: Vec<_> = (0..5).map(|_| Fp::rand(rng)).collect();
+    let public_elements: Vec<_> = domain.elements().take(public.len()).collect();
+
+    // the negated public input polynomial, interpolated over the domain
+    let mut values: Vec<_> = public.iter().map(|p| -*p).collect();
+    values.resize(domain.size(), Fp::zero());
+    let public_poly = Evaluations::from_vec_and_domain(values, domain).interpolate();
+
+    let zeta = Fp::rand(rng);
+    let [eval_zeta, eval_zetaw] = public_evaluations(domain, &public_elements, &public, zeta);
+    assert_eq!(eval_zeta, vec![public_poly.evaluate(&zeta)]);
+    assert_eq!(
+        eval_zetaw,
+        vec![public_poly.evaluate(&(zeta * domain.group_gen))]
+    );
+
+    // without public input
+    let [eval_zeta, eval_zetaw] = public_evaluations(domain, &[], &[], zeta);
+    assert_eq!(eval_zeta, vec![Fp::zero()]);
+    assert_eq!(eval_zetaw, vec![Fp::zero()]);
+}
+
+#[test]
+fn test_public_commitments() {
+    let rng = &mut StdRng::from_seed([0u8; 32]);
+    let prover_index = new_index_for_test(create_circuit(0, 5), 5);
+    let index = prover_index.verifier_index();
+    let other_index = new_index_for_test(create_circuit(0, 0), 0).verifier_index();
+
+    // the commitments of a batch are those of each public input
+    let publics: Vec<Vec<Fp>> = (0..3)
+        .map(|_| (0..5).map(|_| Fp::rand(rng)).collect())
+        .collect();
+    let mut batch: Vec<_> = publics.iter().map(|p| (&index, &p[..])).collect();
+    batch.insert(1, (&other_index, &[][..]));
+    let comms = public_commitments(&batch).unwrap();
+    assert_eq!(comms.len(), batch.len());
+    for ((index, public), comm) in batch.iter().zip(&comms) {
+        let srs = index.srs();
+        let lgr_comm = &srs.lagrange_bases[&index.domain.size()];
+        let expected = public
+            .iter()
+            .zip(lgr_comm)
+            .fold(srs.h.into_projective(), |acc, (p, l)| acc + l.mul(-*p));
+        assert_eq!(comm.unshifted, vec![expected.into_affine()]);
+        assert!(comm.shifted.is_none());
+    }
+
+    // a public input of the wrong length is rejected
+    let err = public_commitments(&[(&index, &publics[0][..4])]).unwrap_err();
+    assert!(matches!(err, VerifyError::IncorrectPubicInputLength(5)));
+
+    // and proofs with distinct public inputs verify together
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let proofs: Vec<_> = publics
+        .iter()
+        .map(|public| {
+            let mut witness: [Vec<Fp>; COLUMNS] =
+                array::from_fn(|_| vec![Fp::zero(); prover_index.cs.gates.len()]);
+            fill_in_witness(0, &mut witness, public);
+            ProverProof::create::<BaseSponge, ScalarSponge>(&group_map, witness, &[], &prover_index)
+                .unwrap()
+        })
+        .collect();
+    let batch: Vec<_> = proofs.iter().map(|proof| (&index, proof)).collect();
+    batch_verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &batch).unwrap();
+}
+
+#[test]
+fn test_replay_oracles() {
+    let public = vec![Fp::from(3u8); 5];
+    let gates = create_circuit(0, public.len());
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
+    fill_in_witness(0, &mut witness, &public);
+
+    let index = new_index_for_test(gates, public.len());
+    let verifier_index = index.verifier_index();
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let proof =
+        ProverProof::create::<BaseSponge, ScalarSponge>(&group_map, witness, &[], &index).unwrap();
+
+    // capture the transcript, and pin it through a serialization round trip
+    let transcript =
+        capture_oracles::<Vesta, BaseSponge, ScalarSponge, _>(&verifier_index, &proof).unwrap();
+    let transcript = serde_json::from_str(&serde_json::to_string(&transcript).unwrap()).unwrap();
+    replay_oracles::<Vesta, BaseSponge, ScalarSponge, _>(&verifier_index, &proof, &transcript)
+        .unwrap();
+
+    // a transcript with different challenges is rejected
+    let mut bad_transcript = transcript.clone();
+    bad_transcript.oracles.zeta += Fp::one();
+    let err = replay_oracles::<Vesta, BaseSponge, ScalarSponge, _>(
+        &verifier_index,
+        &proof,
+        &bad_transcript,
+    )
+    .unwrap_err();
+    assert!(matches!(err, VerifyError::OraclesMismatch("oracles")));
+
+    // so is a proof with a different evaluation
+    let mut bad_proof = proof;
+    bad_proof.ft_eval1 += Fp::one();
+    let err = replay_oracles::<Vesta, BaseSponge, ScalarSponge, _>(
+        &verifier_index,
+        &bad_proof,
+        &transcript,
+    )
+    .unwrap_err();
+    assert!(matches!(err, VerifyError::OraclesMismatch("oracles")));
+}
+
+#[test]
+fn test_zero_columns() {
+    // the generic gates only use the first columns
+    let public = vec![Fp::from(3u8); 5];
+    let gates = create_circuit(0, public.len());
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
+    fill_in_witness(0, &mut witness, &public);
+
+    let cs = ConstraintSystem::create(gates)
+        .public(public.len())
+        .zero_columns((7..COLUMNS).collect())
+        .build()
+        .unwrap();
+    let mut srs = SRS::<Vesta>::create(cs.domain.d1.size());
+    srs.add_lagrange_basis(cs.domain.d1);
+    let (endo_q, _endo_r) = endos::<Pallas>();
+    let index = ProverIndex::<Vesta>::create(cs, endo_q, Arc::new(srs));
+    let verifier_index = index.verifier_index();
+
+    // the zero columns are neither committed to nor evaluated
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let proof =
+        ProverProof::create::<BaseSponge, ScalarSponge>(&group_map, witness.clone(), &[], &index)
+            .unwrap();
+    for col in 0..COLUMNS {
+        let zero = col >= 7;
+        assert_eq!(proof.commitments.w_comm[col].unshifted.is_empty(), zero);
+        assert!(proof.evals.iter().all(|e| e.w[col].is_empty() == zero));
+    }
+    verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &verifier_index, &proof).unwrap();
+
+    // the zero columns are bound by the digest of the index
+    let digest = verifier_index.digest::<BaseSponge>();
+    for col in [0, 7] {
+        let mut flipped = verifier_index.clone();
+        flipped.zero_columns[col] = !flipped.zero_columns[col];
+        assert_ne!(flipped.digest::<BaseSponge>(), digest);
+    }
+
+    // a proof committing to a zero column is rejected
+    let mut bad_proof = proof;
+    bad_proof.commitments.w_comm[7] = bad_proof.commitments.w_comm[0].clone();
+    let err = verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &verifier_index, &bad_proof)
+        .unwrap_err();
+    assert!(matches!(err, VerifyError::IncorrectCommitmentLength("w")));
+
+    // and so is a witness using a zero column
+    witness[7][0] = Fp::one();
+    assert!(index.cs.verify::<Vesta>(&witness, &public).is_err());
+    assert!(matches!(
+        ProverWitness::create(&index, witness),
+        Err(ProverError::NonZeroColumn(7))
+    ));
+}
+
+#[test]
+fn test_unexpected_lookup_commitment() {
+    let public = vec![Fp::from(3u8); 5];
+    let gates = create_circuit(0, public.len());
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
+    fill_in_witness(0, &mut witness, &public);
+
+    let index = new_index_for_test(gates, public.len());
+    let verifier_index = index.verifier_index();
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let proof =
+        ProverProof::create::<BaseSponge, ScalarSponge>(&group_map, witness, &[], &index).unwrap();
+    assert!(proof.commitments.lookup.is_none());
+
+    // the verifier only absorbs the lookup commitments of a circuit using lookups
+    let mut bad_proof = proof;
+    bad_proof.commitments.lookup = Some(LookupCommitments {
+        sorted: vec![bad_proof.commitments.z_comm.clone()],
+        aggreg: bad_proof.commitments.z_comm.clone(),
+        runtime: None,
+        runtime_lens: vec![],
+    });
+    let err = verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &verifier_index, &bad_proof)
+        .unwrap_err();
+    assert!(matches!(err, VerifyError::UnexpectedLookupCommitment));
+}
+
+#[test]
+fn test_strict_verification() {
+    let public = vec![Fp::from(3u8); 5];
+    let gates = create_circuit(0, public.len());
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
+    fill_in_witness(0, &mut witness, &public);
+
+    let index = new_index_for_test(gates, public.len());
+    let verifier_index = index.verifier_index();
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let proof =
+        ProverProof::create::<BaseSponge, ScalarSponge>(&group_map, witness, &[], &index).unwrap();
+    proof.validate(&verifier_index).unwrap();
+    batch_verify_strict::<Vesta, BaseSponge, ScalarSponge, _>(
+        &group_map,
+        &[(&verifier_index, &proof)],
+    )
+    .unwrap();
+
+    // a quotient commitment missing a chunk is rejected before any verification work
+    let mut bad_proof = proof.clone();
+    bad_proof.commitments.t_comm.unshifted.pop();
+    let err = batch_verify_strict::<Vesta, BaseSponge, ScalarSponge, _>(
+        &group_map,
+        &[(&verifier_index, &bad_proof)],
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err,
+        VerifyError::IncorrectCommitmentChunks("t", _, _)
+    ));
+
+    // so is an opening proof with an extra round
+    let mut bad_proof = proof;
+    let round = bad_proof.proof.lr[0];
+    bad_proof.proof.lr.push(round);
+    let err = bad_proof.validate(&verifier_index).unwrap_err();
+    assert!(matches!(err, VerifyError::IncorrectOpeningRounds(_, _)));
+}
+
+#[test]
+fn test_verify_with_diagnostics() {
+    let public = vec![Fp::from(3u8); 5];
+    let gates = create_circuit(0, public.len());
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
+    fill_in_witness(0, &mut witness, &public);
+
+    let index = new_index_for_test(gates, public.len());
+    let verifier_index = index.verifier_index();
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let proof =
+        ProverProof::create::<BaseSponge, ScalarSponge>(&group_map, witness, &[], &index).unwrap();
+    let diagnose = |bad_proof: &ProverProof<Vesta>| {
+        batch_verify_with_diagnostics::<Vesta, BaseSponge, ScalarSponge, _>(
+            &group_map,
+            &[(&verifier_index, &proof), (&verifier_index, bad_proof)],
+        )
+    };
+    diagnose(&proof).unwrap();
+
+    // a malformed evaluation is named by its polynomial and point
+    let mut bad_proof = proof.clone();
+    let chunk = bad_proof.evals[1].w[3][0];
+    bad_proof.evals[1].w[3].push(chunk);
+    assert!(matches!(
+        diagnose(&bad_proof),
+        Err(VerifyError::EvaluationChunks {
+            proof: 1,
+            polynomial: OpenedPolynomial::Column(Column::Witness(3)),
+            point: EvaluationPoint::ZetaOmega,
+            chunks: 2,
+            expected: 1,
+        })
+    ));
+
+    // while a wrong evaluation can only be attributed to its proof
+    let mut bad_proof = proof.clone();
+    bad_proof.evals[0].z[0] += Fp::one();
+    assert!(matches!(
+        diagnose(&bad_proof),
+        Err(VerifyError::OpenProofDiagnostics(OpeningError::Opening {
+            proof: 1
+        }))
+    ));
+}
+
+#[test]
+fn test_batch_verify_cycle() {
+    let public = vec![Fp::from(3u8); 5];
+    let gates = create_circuit(0, public.len());
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
+    fill_in_witness(0, &mut witness, &public);
+    let index = new_index_for_test(gates, public.len());
+    let verifier_index = index.verifier_index();
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let proof =
+        ProverProof::create::<BaseSponge, ScalarSponge>(&group_map, witness, &[], &index).unwrap();
+
+    // the same circuit, on the other curve
+    let other_public = vec![Fq::from(3u8); 5];
+    let gates = create_circuit(0, other_public.len());
+    let mut witness: [Vec<Fq>; COLUMNS] = array::from_fn(|_| vec![Fq::zero(); gates.len()]);
+    fill_in_witness(0, &mut witness, &other_public);
+    let cs = ConstraintSystem::create(gates)
+        .public(other_public.len())
+        .build()
+        .unwrap();
+    let mut srs = SRS::<Pallas>::create(cs.domain.d1.size());
+    srs.add_lagrange_basis(cs.domain.d1);
+    let (endo_q, _endo_r) = endos::<Vesta>();
+    let other_index = ProverIndex::<Pallas>::create(cs, endo_q, Arc::new(srs));
+    let other_verifier_index = other_index.verifier_index();
+    let other_group_map = <Pallas as CommitmentCurve>::Map::setup();
+    let other_proof = ProverProof::create::<PallasBaseSponge, PallasScalarSponge>(
+        &other_group_map,
+        witness,
+        &[],
+        &other_index,
+    )
+    .unwrap();
+
+    let verify_cycle = |proof: &ProverProof<Vesta>, other_proof: &ProverProof<Pallas>| {
+        batch_verify_cycle::<
+            Vesta,
+            BaseSponge,
+            ScalarSponge,
+            PallasBaseSponge,
+            PallasScalarSponge,
+            _,
+            _,
+        >(
+            (&group_map, &other_group_map),
+            &[(&verifier_index, proof)],
+            &[(&other_verifier_index, other_proof)],
+        )
+    };
+    verify_cycle(&proof, &other_proof).unwrap();
+
+    // a failure on one curve is reported along with the outcome of the other curve
+    let mut bad_proof = other_proof.clone();
+    bad_proof.ft_eval1 += Fq::one();
+    let err = verify_cycle(&proof, &bad_proof).unwrap_err();
+    assert!(matches!(
+        err,
+        CycleVerifyError {
+            first: None,
+            other: Some(_)
+        }
+    ));
+}
+
+#[test]
+fn test_proof_context() {
+    let public = vec![Fp::from(3u8); 5];
+    let gates = create_circuit(0, public.len());
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
+    fill_in_witness(0, &mut witness, &public);
+    let index = new_index_for_test(gates, public.len());
+    let verifier_index = index.verifier_index();
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+
+    let prove = |context: &[u8]| {
+        ProverProof::create_with_context::<BaseSponge, ScalarSponge>(
+            &group_map,
+            witness.clone(),
+            &[],
+            &index,
+            vec![],
+            context,
+        )
+        .unwrap()
+    };
+    let verify_in = |proof: &ProverProof<Vesta>, context: &[u8]| {
+        verify_with_context::<Vesta, BaseSponge, ScalarSponge, _>(
+            &group_map,
+            &verifier_index,
+            proof,
+            context,
+        )
+    };
+
+    // a proof bound to a context only verifies in that context
+    let bound = prove(b"app-a");
+    verify_in(&bound, b"app-a").unwrap();
+    verify_in(&bound, b"app-b").unwrap_err();
+    verify_in(&bound, b"").unwrap_err();
+    verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &verifier_index, &bound).unwrap_err();
+
+    // while an empty context is the same as no context
+    let unbound = prove(b"");
+    verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &verifier_index, &unbound).unwrap();
+    verify_in(&unbound, b"app-a").unwrap_err();
+
+    // and a batch binds each proof to its own context
+    let batch = [
+        (&verifier_index, &bound, &b"app-a"[..]),
+        (&verifier_index, &unbound, &b""[..]),
+    ];
+    batch_verify_with_context::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &batch).unwrap();
+    let swapped = [
+        (&verifier_index, &bound, &b""[..]),
+        (&verifier_index, &unbound, &b"app-a"[..]),
+    ];
+    batch_verify_with_context::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &swapped)
+        .unwrap_err();
+}
@@ -0,0 +1,299 @@
+use crate::{
+    circuits::{
+        constraints::ConstraintSystem,
+        gate::{CircuitGate, GateType},
+        lookup::tables::LookupTable,
+        polynomials::generic::testing::{create_circuit, fill_in_witness},
+        wires::{Wire, COLUMNS},
+    },
+    error::VerifierIndexError,
+    proof::ProverProof,
+    prover_index::{
+        testing::{new_index_for_test, new_index_for_test_with_lookups},
+        ProverIndex,
+    },
+    verifier::verify,
+    verifier_index::{zero_comm, VerifierIndex, VerifierIndexBuilder},
+};
+use ark_ff::{One, Zero};
+use ark_poly::EvaluationDomain;
+use commitment_dlog::{
+    commitment::CommitmentCurve,
+    srs::{endos, SRS},
+};
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Fq, Pallas, Vesta, VestaParameters};
+use oracle::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use std::{array, sync::Arc};
+
+type BaseSponge = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
+type ScalarSponge = DefaultFrSponge<Fp, PlonkSpongeConstantsKimchi>;
+
+/// Returns a builder filled with the commitments of `index`.
+fn builder(index: &ProverIndex<Vesta>) -> VerifierIndexBuilder<Vesta> {
+    let verifier_index: VerifierIndex<Vesta> = index.verifier_index();
+    let chunks = verifier_index.chunks();
+    let zero = || zero_comm(chunks);
+    let mut builder = VerifierIndexBuilder::new(index.srs.clone(), index.cs.domain.d1.size())
+        .unwrap()
+        .public(verifier_index.public)
+        .prev_challenges(verifier_index.prev_challenges)
+        .zero_columns(
+            (0..COLUMNS)
+                .filter(|col| verifier_index.zero_columns[*col])
+                .collect(),
+        )
+        .sigma(verifier_index.sigma_comm.to_vec())
+        .coefficients(
+            verifier_index
+                .coefficients_comm
+                .map(|comm| comm.unwrap_or_else(zero))
+                .to_vec(),
+        )
+        .selector(GateType::Generic, verifier_index.generic_comm)
+        .selector(GateType::Poseidon, verifier_index.psm_comm)
+        .selector(
+            GateType::CompleteAdd,
+            verifier_index.complete_add_comm.unwrap_or_else(zero),
+        )
+        .selector(
+            GateType::VarBaseMul,
+            verifier_index.mul_comm.unwrap_or_else(zero),
+        )
+        .selector(
+            GateType::EndoMul,
+            verifier_index.emul_comm.unwrap_or_else(zero),
+        )
+        .selector(
+            GateType::EndoMulScalar,
+            verifier_index.endomul_scalar_comm.unwrap_or_else(zero),
+        );
+    if let (Some(lookup_cs), Some(lookup_index)) = (
+        index.cs.lookup_constraint_system.as_ref(),
+        verifier_index.lookup_index,
+    ) {
+        builder = builder.lookup(lookup_cs.configuration.clone(), lookup_index);
+    }
+    builder
+}
+
+#[test]
+fn test_verifier_index_builder() {
+    let public = vec![Fp::from(3u8); 5];
+    let gates = create_circuit(0, public.len());
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
+    fill_in_witness(0, &mut witness, &public);
+
+    let index = new_index_for_test(gates, public.len());
+    let expected_digest = index.verifier_index().digest::<BaseSponge>();
+
+    // the built index is the one of the prover index
+    let (verifier_index, digest) = builder(&index)
+        .expected_digest(expected_digest)
+        .build::<BaseSponge>()
+        .unwrap();
+    assert_eq!(digest, expected_digest);
+
+    // and verifies its proofs
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let proof =
+        ProverProof::create::<BaseSponge, ScalarSponge>(&group_map, witness, &[], &index).unwrap();
+    verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &verifier_index, &proof).unwrap();
+}
+
+#[test]
+fn test_verifier_index_zero_commitments() {
+    let public = vec![Fp::from(3u8); 5];
+    let gates = create_circuit(0, public.len());
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
+    fill_in_witness(0, &mut witness, &public);
+
+    let index = new_index_for_test(gates, public.len());
+    let verifier_index = index.verifier_index();
+    let chunks = verifier_index.chunks();
+    let zero = || zero_comm(chunks);
+
+    // the generic circuit only uses some of the coefficients, and none of the omitted selectors
+    let used = index
+        .cs
+        .coefficients
+        .iter()
+        .filter(|column| column.nonzero_runs().next().is_some())
+        .count();
+    assert!(used < COLUMNS);
+    assert_eq!(
+        verifier_index
+            .coefficients_comm
+            .iter()
+            .filter(|comm| comm.is_some())
+            .count(),
+        used
+    );
+    assert!(verifier_index.complete_add_comm.is_none());
+    assert!(verifier_index.mul_comm.is_none());
+    assert!(verifier_index.emul_comm.is_none());
+    assert!(verifier_index.endomul_scalar_comm.is_none());
+
+    // which shrinks the index, without changing its digest
+    let mut expanded = verifier_index.clone();
+    for comm in expanded.coefficients_comm.iter_mut().chain([
+        &mut expanded.complete_add_comm,
+        &mut expanded.mul_comm,
+        &mut expanded.emul_comm,
+        &mut expanded.endomul_scalar_comm,
+    ]) {
+        comm.get_or_insert_with(zero);
+    }
+    assert!(
+        rmp_serde::to_vec(&verifier_index).unwrap().len()
+            < rmp_serde::to_vec(&expanded).unwrap().len()
+    );
+    assert_eq!(
+        verifier_index.digest::<BaseSponge>(),
+        expanded.digest::<BaseSponge>()
+    );
+
+    // and the proofs verify against both
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let proof =
+        ProverProof::create::<BaseSponge, ScalarSponge>(&group_map, witness, &[], &index).unwrap();
+    for verifier_index in [&verifier_index, &expanded] {
+        verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, verifier_index, &proof).unwrap();
+    }
+}
+
+#[test]
+fn test_verifier_index_zero_commitments_in_chunks() {
+    // an SRS of half the size of the domain, committing in 2 chunks
+    let cs = ConstraintSystem::create(create_circuit(0, 0))
+        .build()
+        .unwrap();
+    let srs = SRS::<Vesta>::create(cs.domain.d1.size() / 2);
+    let (endo_q, _endo_r) = endos::<Pallas>();
+    let index = ProverIndex::<Vesta>::create(cs, endo_q, Arc::new(srs));
+    let verifier_index = index.verifier_index();
+    assert_eq!(verifier_index.chunks(), 2);
+    assert_eq!(verifier_index.generic_comm.unshifted.len(), 2);
+
+    // the omitted selectors stand for the zero commitments in as many chunks in the digest
+    assert!(verifier_index.complete_add_comm.is_none());
+    let digest = verifier_index.digest::<BaseSponge>();
+    let expanded = |chunks| {
+        let mut expanded = verifier_index.clone();
+        expanded.complete_add_comm = Some(zero_comm(chunks));
+        expanded.digest::<BaseSponge>()
+    };
+    assert_eq!(expanded(2), digest);
+    assert_ne!(expanded(1), digest);
+}
+
+#[test]
+fn test_verifier_index_builder_lookup() {
+    let lookup_table = LookupTable {
+        id: 0,
+        data: vec![(0..16u64).map(Into::into).collect()],
+    };
+    let gates = (0..20)
+        .map(|i| CircuitGate {
+            typ: GateType::Lookup,
+            coeffs: vec![],
+            wires: Wire::new(i),
+        })
+        .collect();
+    let index = new_index_for_test_with_lookups(gates, 0, 0, vec![lookup_table], None, None);
+    let expected_digest = index.verifier_index().digest::<BaseSponge>();
+
+    let (_, digest) = builder(&index).build::<BaseSponge>().unwrap();
+    assert_eq!(digest, expected_digest);
+
+    // the lookup commitments must match the configuration
+    let verifier_index = index.verifier_index();
+    let mut configuration = index
+        .cs
+        .lookup_constraint_system
+        .as_ref()
+        .unwrap()
+        .configuration
+        .clone();
+    configuration.lookup_info.max_joint_size += 1;
+    let err = builder(&index)
+        .lookup(configuration, verifier_index.lookup_index.unwrap())
+        .build::<BaseSponge>()
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        VerifierIndexError::InconsistentLookup("configuration")
+    ));
+}
+
+#[test]
+fn test_verifier_index_builder_errors() {
+    let gates = create_circuit(0, 0);
+    let index = new_index_for_test(gates, 0);
+    let verifier_index = index.verifier_index();
+
+    // missing selector
+    #[cfg(feature = "chacha")]
+    {
+        let err = builder(&index)
+            .selector(GateType::ChaCha0, verifier_index.generic_comm.clone())
+            .build::<BaseSponge>()
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            VerifierIndexError::SelectorMissing(GateType::ChaCha1)
+        ));
+    }
+
+    // unexpected selector
+    let err = builder(&index)
+        .selector(GateType::Lookup, verifier_index.generic_comm.clone())
+        .build::<BaseSponge>()
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        VerifierIndexError::UnexpectedSelector(GateType::Lookup)
+    ));
+
+    // incorrect number of commitments
+    let mut sigma_comm = verifier_index.sigma_comm.clone();
+    sigma_comm.push(sigma_comm[0].clone());
+    let err = builder(&index)
+        .sigma(sigma_comm)
+        .build::<BaseSponge>()
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        VerifierIndexError::IncorrectCommitmentCount("sigma", 7, 8)
+    ));
+    let err = builder(&index)
+        .sigma(vec![])
+        .build::<BaseSponge>()
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        VerifierIndexError::IncorrectCommitmentCount("sigma", 7, 0)
+    ));
+
+    // incorrect commitment size
+    let mut chunked = verifier_index.generic_comm.clone();
+    chunked.unshifted.push(chunked.unshifted[0]);
+    let err = builder(&index)
+        .selector(GateType::Generic, chunked)
+        .build::<BaseSponge>()
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        VerifierIndexError::IncorrectCommitmentSize("selector", 1)
+    ));
+
+    // unexpected digest
+    let err = builder(&index)
+        .expected_digest(verifier_index.digest::<BaseSponge>() + Fq::one())
+        .build::<BaseSponge>()
+        .unwrap_err();
+    assert!(matches!(err, VerifierIndexError::DigestMismatch));
+}
@@ -0,0 +1,121 @@
+use crate::{
+    circuits::{
+        gate::{CircuitGate, GateType},
+        lookup::tables::LookupTable,
+        polynomials::generic::testing::{create_circuit, fill_in_witness},
+        wires::{Wire, COLUMNS},
+    },
+    error::VerifierIndexError,
+    proof::ProverProof,
+    prover_index::testing::{new_index_for_test, new_index_for_test_with_lookups},
+    verifier::verify,
+    verifier_index_json::VerifierIndexJson,
+};
+use ark_ff::Zero;
+use commitment_dlog::commitment::CommitmentCurve;
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use oracle::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use std::array;
+
+type BaseSponge = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
+type ScalarSponge = DefaultFrSponge<Fp, PlonkSpongeConstantsKimchi>;
+
+#[test]
+fn test_verifier_index_json() {
+    let public = vec![Fp::from(3u8); 5];
+    let gates = create_circuit(0, public.len());
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
+    fill_in_witness(0, &mut witness, &public);
+
+    let index = new_index_for_test(gates, public.len());
+    let verifier_index = index.verifier_index();
+    let export = VerifierIndexJson::export::<_, BaseSponge>(&verifier_index, None).unwrap();
+    assert_eq!(export.public, public.len());
+    assert_eq!(export.selectors[0].gate, GateType::Generic);
+
+    // the export round-trips through JSON
+    let json = export.to_json();
+    let imported = VerifierIndexJson::from_json(&json).unwrap();
+    assert_eq!(imported, export);
+
+    // and imports back to the index, which verifies its proofs
+    let verifier_index = imported.import::<_, BaseSponge>(index.srs.clone()).unwrap();
+    assert_eq!(
+        verifier_index.digest::<BaseSponge>(),
+        index.verifier_index().digest::<BaseSponge>()
+    );
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let proof =
+        ProverProof::create::<BaseSponge, ScalarSponge>(&group_map, witness, &[], &index).unwrap();
+    verify::<Vesta, BaseSponge, ScalarSponge, _>(&group_map, &verifier_index, &proof).unwrap();
+
+    // an export that was tampered with is rejected
+    let mut tampered = export.clone();
+    tampered.sigma.swap(0, 1);
+    assert!(matches!(
+        tampered.import::<_, BaseSponge>(index.srs.clone()),
+        Err(VerifierIndexError::DigestMismatch)
+    ));
+    let mut tampered = export.clone();
+    tampered.linearization_digest = "00".to_string();
+    assert!(matches!(
+        tampered.import::<_, BaseSponge>(index.srs.clone()),
+        Err(VerifierIndexError::LinearizationMismatch)
+    ));
+    let mut tampered = export;
+    tampered.shifts[0] = "zz".to_string();
+    assert!(matches!(
+        tampered.import::<_, BaseSponge>(index.srs.clone()),
+        Err(VerifierIndexError::InvalidExport("shifts"))
+    ));
+    assert!(matches!(
+        VerifierIndexJson::from_json("{}"),
+        Err(VerifierIndexError::Json(_))
+    ));
+}
+
+#[test]
+fn test_verifier_index_json_lookup() {
+    let lookup_table = LookupTable {
+        id: 0,
+        data: vec![(0..16u64).map(Into::into).collect()],
+    };
+    let gates = (0..20)
+        .map(|i| CircuitGate {
+            typ: GateType::Lookup,
+            coeffs: vec![],
+            wires: Wire::new(i),
+        })
+        .collect();
+    let index = new_index_for_test_with_lookups(gates, 0, 0, vec![lookup_table], None, None);
+    let verifier_index = index.verifier_index();
+    let configuration = &index
+        .cs
+        .lookup_constraint_system
+        .as_ref()
+        .unwrap()
+        .configuration;
+
+    // the lookup configuration is required to export an index with lookups
+    assert!(matches!(
+        VerifierIndexJson::export::<_, BaseSponge>(&verifier_index, None),
+        Err(VerifierIndexError::InconsistentLookup("configuration"))
+    ));
+
+    let export =
+        VerifierIndexJson::export::<_, BaseSponge>(&verifier_index, Some(configuration)).unwrap();
+    assert!(export.lookup.is_some());
+    let json = export.to_json();
+    let imported = VerifierIndexJson::from_json(&json)
+        .unwrap()
+        .import::<_, BaseSponge>(index.srs.clone())
+        .unwrap();
+    assert_eq!(
+        imported.digest::<BaseSponge>(),
+        verifier_index.digest::<BaseSponge>()
+    );
+}
@@ -0,0 +1,57 @@
+use crate::{
+    circuits::polynomials::generic::testing::create_circuit,
+    prover_index::testing::new_index_for_test,
+    verifier_math::{unnormalized_lagrange_basis, vanishes_on_last_4_rows, vanishing, zk_w, zkpm},
+};
+use ark_ff::{Field, One, UniformRand, Zero};
+use ark_poly::{EvaluationDomain, Polynomial, Radix2EvaluationDomain as D};
+use mina_curves::pasta::Fp;
+
+/// Returns the product of `x - w^i` over the rows `i` of `rows` of `domain`.
+fn vanishing_on(domain: D<Fp>, rows: impl Iterator<Item = usize>, x: Fp) -> Fp {
+    rows.map(|i| x - domain.element(i)).product()
+}
+
+#[test]
+fn test_verifier_math_definitions() {
+    let rng = &mut rand::rngs::OsRng;
+    let domain = D::<Fp>::new(1 << 4).unwrap();
+    let n = domain.size();
+    let x = Fp::rand(rng);
+
+    assert_eq!(vanishing(domain, x), x.pow([n as u64]) - Fp::one());
+    assert_eq!(vanishing(domain, x), vanishing_on(domain, 0..n, x));
+
+    assert_eq!(zk_w(domain), domain.element(n - 3));
+    assert_eq!(zkpm(domain, x), vanishing_on(domain, n - 3..n, x));
+    assert_eq!(
+        vanishes_on_last_4_rows(domain, x),
+        vanishing_on(domain, n - 4..n, x)
+    );
+
+    // the unnormalized Lagrange polynomials, a negative index counting from the end
+    for i in [0, 1, 5] {
+        let expected = vanishing_on(domain, (0..n).filter(|j| *j != i), x);
+        assert_eq!(unnormalized_lagrange_basis(&domain, i as i32, &x), expected);
+        assert_eq!(
+            unnormalized_lagrange_basis(&domain, i as i32 - n as i32, &x),
+            expected
+        );
+    }
+
+    // which vanish on the other rows of the domain
+    assert!(unnormalized_lagrange_basis(&domain, 1, &domain.element(2)).is_zero());
+    assert!(zkpm(domain, domain.element(n - 1)).is_zero());
+    assert!(!zkpm(domain, domain.element(n - 4)).is_zero());
+}
+
+#[test]
+fn test_verifier_math_index() {
+    let rng = &mut rand::rngs::OsRng;
+    let index = new_index_for_test(create_circuit(0, 0), 0).verifier_index();
+    let x = Fp::rand(rng);
+
+    // the helpers of the verifier index are the same polynomials
+    assert_eq!(index.zkpm().evaluate(&x), zkpm(index.domain, x));
+    assert_eq!(*index.w(), zk_w(index.domain));
+}
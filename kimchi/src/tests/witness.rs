@@ -0,0 +1,235 @@
+use crate::{
+    circuits::{
+        gate::{CircuitGate, CircuitGateError, CircuitGateResult, GateType},
+        polynomials::{
+            generic::GenericGateSpec,
+            poseidon::{self, round_to_cols, POS_ROWS_PER_HASH},
+        },
+        wires::{Wire, COLUMNS},
+        witness::WitnessTable,
+    },
+    curve::KimchiCurve,
+    tests::framework::TestFramework,
+};
+use ark_ec::AffineCurve;
+use ark_ff::{One, Zero};
+use mina_curves::pasta::{Fp, Pallas as Other, Vesta};
+use std::array;
+
+/// A circuit using each gate with a built-in witness fill-in,
+/// with the inputs of its gates.
+fn circuit() -> (Vec<CircuitGate<Fp>>, Vec<Vec<Fp>>) {
+    let mut gates = vec![];
+    let mut inputs: Vec<Vec<Fp>> = vec![];
+
+    // generic gates
+    gates.push(CircuitGate::create_generic_gadget(
+        Wire::new(0),
+        GenericGateSpec::Add {
+            left_coeff: None,
+            right_coeff: Some(3u32.into()),
+            output_coeff: None,
+        },
+        Some(GenericGateSpec::Mul {
+            output_coeff: None,
+            mul_coeff: Some(2u32.into()),
+        }),
+    ));
+    inputs.push([11u32, 23, 11, 23].map(Into::into).to_vec());
+    gates.push(
+        CircuitGate::create_triple_generic_gadget(
+            Wire::new(1),
+            GenericGateSpec::Mul {
+                output_coeff: None,
+                mul_coeff: None,
+            },
+            GenericGateSpec::Mul {
+                output_coeff: None,
+                mul_coeff: None,
+            },
+            GenericGateSpec::Add {
+                left_coeff: None,
+                right_coeff: None,
+                output_coeff: None,
+            },
+        )
+        .unwrap(),
+    );
+    inputs.push([2u32, 3, 4, 5, 6, 20].map(Into::into).to_vec());
+
+    // poseidon (the inputs of the rows after the first one are read from the witness)
+    let row = gates.len();
+    let (poseidon, last_row) = CircuitGate::create_poseidon_gadget(
+        row,
+        [Wire::new(row), Wire::new(row + POS_ROWS_PER_HASH)],
+        &Vesta::sponge_params().round_constants,
+    );
+    gates.extend(poseidon);
+    inputs.push([1u32, 2, 3].map(Into::into).to_vec());
+    inputs.resize(last_row + 1, vec![]);
+
+    // elliptic curve gates
+    let g = Other::prime_subgroup_generator();
+    let g2 = g + g;
+    let g3 = g2 + g;
+    let row = gates.len();
+    gates.push(CircuitGate {
+        typ: GateType::CompleteAdd,
+        wires: Wire::new(row),
+        coeffs: vec![],
+    });
+    inputs.push(vec![g.x, g.y, g2.x, g2.y]);
+
+    let row = gates.len();
+    gates.push(CircuitGate {
+        typ: GateType::EndoMulScalar,
+        wires: Wire::new(row),
+        coeffs: vec![],
+    });
+    inputs.push(
+        [3u32, 2, 2, 0, 1, 2, 3, 3, 2, 1, 0]
+            .map(Into::into)
+            .to_vec(),
+    );
+
+    let row = gates.len();
+    gates.push(CircuitGate {
+        typ: GateType::VarBaseMul,
+        wires: Wire::new(row),
+        coeffs: vec![],
+    });
+    gates.push(CircuitGate::zero(Wire::new(row + 1)));
+    let bits = [1u32, 0, 1, 1, 0].map(Fp::from);
+    inputs.push([[g.x, g.y, g2.x, g2.y, 5u32.into()].as_slice(), &bits].concat());
+    inputs.push(vec![]);
+
+    let row = gates.len();
+    gates.push(CircuitGate {
+        typ: GateType::EndoMul,
+        wires: Wire::new(row),
+        coeffs: vec![],
+    });
+    gates.push(CircuitGate::zero(Wire::new(row + 1)));
+    let bits = [1u32, 0, 0, 1].map(Fp::from);
+    inputs.push([[g.x, g.y, g3.x, g3.y, 7u32.into()].as_slice(), &bits].concat());
+    inputs.push(vec![]);
+
+    (gates, inputs)
+}
+
+#[test]
+fn test_generate_witness() {
+    let (gates, inputs) = circuit();
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
+
+    for (row, (gate, inputs)) in gates.iter().zip(&inputs).enumerate() {
+        let inputs = match gate.typ {
+            GateType::Poseidon if inputs.is_empty() => {
+                round_to_cols(0).map(|col| witness[col][row]).collect()
+            }
+            _ => inputs.clone(),
+        };
+        gate.generate_witness::<Vesta>(row, &mut witness, &inputs)
+            .unwrap();
+    }
+
+    // the generic outputs
+    assert_eq!(witness[2][0], Fp::from(11u32 + 3 * 23));
+    assert_eq!(witness[5][0], Fp::from(2 * 11u32 * 23));
+    assert_eq!(witness[5][1], Fp::from(4u32 * 5));
+    assert_eq!(witness[8][1], Fp::from(6u32 + 20));
+
+    // poseidon matches the witness generation of the whole hash
+    let mut expected = witness.clone();
+    poseidon::generate_witness(
+        2,
+        Vesta::sponge_params(),
+        &mut expected,
+        [1u32, 2, 3].map(Into::into),
+    );
+    assert_eq!(expected, witness);
+
+    // the gates are satisfied
+    TestFramework::default()
+        .gates(gates)
+        .witness(witness)
+        .setup()
+        .prove_and_verify();
+}
+
+#[test]
+fn test_generate_witness_errors() {
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); 2]);
+    let gate = |typ| CircuitGate {
+        typ,
+        wires: Wire::new(0),
+        coeffs: vec![],
+    };
+
+    // no built-in fill-in
+    let err = gate(GateType::Lookup)
+        .generate_witness::<Vesta>(0, &mut witness, &[])
+        .unwrap_err();
+    assert_eq!(
+        err,
+        CircuitGateError::MissingWitnessFiller(GateType::Lookup)
+    );
+
+    // invalid inputs
+    let err = gate(GateType::Generic)
+        .generate_witness::<Vesta>(0, &mut witness, &[Fp::one(); 3])
+        .unwrap_err();
+    assert_eq!(
+        err,
+        CircuitGateError::WitnessInputCount(GateType::Generic, 4, 3)
+    );
+    let err = gate(GateType::EndoMul)
+        .generate_witness::<Vesta>(0, &mut witness, &[Fp::from(2u32); 9])
+        .unwrap_err();
+    assert_eq!(
+        err,
+        CircuitGateError::InvalidWitnessInputs(GateType::EndoMul)
+    );
+
+    // missing rows
+    let err = gate(GateType::Poseidon)
+        .generate_witness::<Vesta>(1, &mut witness, &[Fp::one(); 3])
+        .unwrap_err();
+    assert_eq!(
+        err,
+        CircuitGateError::FailedToGetWitnessForRow(GateType::Poseidon, 1)
+    );
+}
+
+#[test]
+fn test_witness_table_register() {
+    fn fill_lookup(
+        _: &CircuitGate<Fp>,
+        row: usize,
+        witness: &mut [Vec<Fp>; COLUMNS],
+        inputs: &[Fp],
+    ) -> CircuitGateResult<()> {
+        for (col, input) in inputs.iter().enumerate() {
+            witness[col][row] = *input;
+        }
+        Ok(())
+    }
+
+    let table = WitnessTable::<Vesta>::default().register(GateType::Lookup, fill_lookup);
+    let gate = CircuitGate {
+        typ: GateType::Lookup,
+        wires: Wire::new(0),
+        coeffs: vec![],
+    };
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); 1]);
+    table
+        .fill_witness(&gate, 0, &mut witness, &[Fp::one(); 2])
+        .unwrap();
+    assert_eq!(witness[1][0], Fp::one());
+
+    // the built-in fill-ins are still available
+    assert!(table.get(GateType::Generic).is_some());
+    assert!(WitnessTable::<Vesta>::default()
+        .get(GateType::Lookup)
+        .is_none());
+}
@@ -0,0 +1,67 @@
+use crate::circuits::{
+    gate::{CircuitGate, CurrOrNext, GateType},
+    polynomials::generic::testing::{create_circuit, fill_in_witness},
+    wires::{Wire, COLUMNS},
+    witness_audit::{CellRange, RangeViolation, WitnessAudit},
+};
+use ark_ff::Zero;
+use mina_curves::pasta::Fp;
+use std::array;
+
+#[test]
+fn test_witness_audit() {
+    // a complete addition followed by generic gates
+    let mut gates = vec![CircuitGate {
+        typ: GateType::CompleteAdd,
+        wires: Wire::new(0),
+        coeffs: vec![],
+    }];
+    gates.extend(create_circuit(1, 0));
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
+    fill_in_witness(1, &mut witness, &[]);
+
+    // the witness respects the ranges of the gates
+    let audit = WitnessAudit::new();
+    assert!(audit.run(&gates, &witness).is_empty());
+
+    // but not once the flag `same_x` is not a boolean
+    witness[7][0] = Fp::from(2u8);
+    let violations = audit.run(&gates, &witness);
+    assert_eq!(
+        violations,
+        vec![RangeViolation {
+            row: 0,
+            col: 7,
+            typ: Some(GateType::CompleteAdd),
+            range: CellRange::Boolean,
+            value: Fp::from(2u8),
+        }]
+    );
+    let message = violations[0].to_string();
+    assert!(message.starts_with("row 0, column 7 (CompleteAdd gate): "));
+    assert!(message.ends_with(" is not a boolean"));
+    witness[7][0] = Fp::zero();
+
+    // the ranges declared for a gate type apply to all its rows, the ones of a cell to the cell
+    let audit = WitnessAudit::new()
+        .gate_range(
+            GateType::Generic,
+            CurrOrNext::Curr,
+            0..2,
+            CellRange::Bits(4),
+        )
+        .cell_range(1, 5, CellRange::Bits(8));
+    let violations = audit.run(&gates, &witness);
+    // the add and mul rows hold 23 in column 1 and 11 * 23 * 2 in column 5
+    assert_eq!(violations.len(), 10 + 1);
+    assert!(violations
+        .iter()
+        .all(|violation| violation.typ == Some(GateType::Generic)));
+    assert_eq!((violations[0].row, violations[0].col), (1, 1));
+    assert_eq!((violations[1].row, violations[1].col), (1, 5));
+    assert_eq!(violations[1].range, CellRange::Bits(8));
+
+    // cells beyond the witness are not audited
+    let audit = WitnessAudit::new().cell_range(gates.len(), 0, CellRange::Boolean);
+    assert!(audit.run(&gates, &witness).is_empty());
+}
@@ -0,0 +1,130 @@
+//! This module implements [`TranscriptAbsorb`], the absorption of the messages of a proof
+//! into the Fiat-Shamir transcript.
+//!
+//! The prover and the verifier both absorb the commitments and evaluations of a proof
+//! through the implementations of this module, on the same types
+//! ([`ProverCommitments`] and the evaluations of [`ProofEvaluations`]),
+//! so that both sides absorb the same data in the same order by construction.
+//!
+//! The messages are absorbed in [`Round`]s, each of them followed by the challenges
+//! that depend on it. The prover absorbs its commitments as it creates them:
+//! the parts of a message sent in later rounds are still empty at that point.
+
+use crate::plonk_sponge::FrSponge;
+use crate::proof::{LookupCommitments, ProofEvaluations, ProverCommitments};
+use ark_ec::AffineCurve;
+use ark_ff::Field;
+use oracle::FqSponge;
+
+/// The rounds of the Fiat-Shamir argument, in order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Round {
+    /// The witness commitments, and the runtime table commitment
+    /// with the number of entries used in the runtime tables of variable length,
+    /// followed by the joint combiner
+    Witness,
+    /// The commitments to the sorted lookup polynomials, followed by `beta` and `gamma`
+    Sorted,
+    /// The commitments to the lookup and permutation aggregation polynomials, followed by `alpha`
+    Permutation,
+    /// The commitment to the quotient polynomial, followed by `zeta`
+    Quotient,
+    /// The evaluations of the polynomials at `zeta` and `zeta * omega`, followed by `v` and `u`
+    Evaluations,
+}
+
+/// A message of a proof, absorbed into the sponge `Sponge`.
+pub trait TranscriptAbsorb<Sponge> {
+    /// Absorbs the parts of the message sent in `round` into `sponge`.
+    fn absorb(&self, round: Round, sponge: &mut Sponge);
+}
+
+impl<G, EFqSponge> TranscriptAbsorb<EFqSponge> for LookupCommitments<G>
+where
+    G: AffineCurve,
+    EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
+{
+    fn absorb(&self, round: Round, sponge: &mut EFqSponge) {
+        match round {
+            Round::Witness => {
+                if let Some(runtime) = &self.runtime {
+                    sponge.absorb_g(&runtime.unshifted);
+                }
+                if !self.runtime_lens.is_empty() {
+                    let runtime_lens: Vec<_> = self
+                        .runtime_lens
+                        .iter()
+                        .map(|len| G::BaseField::from(*len as u64))
+                        .collect();
+                    sponge.absorb_fq(&runtime_lens);
+                }
+            }
+            Round::Sorted => {
+                for sorted in &self.sorted {
+                    sponge.absorb_g(&sorted.unshifted);
+                }
+            }
+            Round::Permutation => sponge.absorb_g(&self.aggreg.unshifted),
+            Round::Quotient | Round::Evaluations => (),
+        }
+    }
+}
+
+impl<G, EFqSponge> TranscriptAbsorb<EFqSponge> for ProverCommitments<G>
+where
+    G: AffineCurve,
+    EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
+{
+    fn absorb(&self, round: Round, sponge: &mut EFqSponge) {
+        match round {
+            Round::Witness => {
+                for w in &self.w_comm {
+                    sponge.absorb_g(&w.unshifted);
+                }
+            }
+            Round::Quotient => sponge.absorb_g(&self.t_comm.unshifted),
+            Round::Sorted | Round::Permutation | Round::Evaluations => (),
+        }
+
+        if let Some(lookup) = &self.lookup {
+            lookup.absorb(round, sponge);
+        }
+
+        // the permutation aggregation comes after the lookup aggregation
+        if round == Round::Permutation {
+            sponge.absorb_g(&self.z_comm.unshifted);
+        }
+    }
+}
+
+/// The evaluations at all points are absorbed polynomial by polynomial.
+impl<F, EFrSponge, const N: usize> TranscriptAbsorb<EFrSponge> for [ProofEvaluations<Vec<F>>; N]
+where
+    F: Field,
+    EFrSponge: FrSponge<F>,
+{
+    fn absorb(&self, round: Round, sponge: &mut EFrSponge) {
+        if round != Round::Evaluations {
+            return;
+        }
+
+        let e = ProofEvaluations::transpose(std::array::from_fn(|i| &self[i]));
+
+        let mut points = vec![&e.z, &e.generic_selector, &e.poseidon_selector];
+        points.extend(e.w.iter());
+        points.extend(e.s.iter());
+
+        if let Some(l) = e.lookup.as_ref() {
+            points.push(&l.aggreg);
+            points.push(&l.table);
+            points.extend(l.sorted.iter());
+            points.extend(l.runtime.iter());
+        }
+
+        for p in points {
+            for x in p {
+                sponge.absorb_multiple(x);
+            }
+        }
+    }
+}
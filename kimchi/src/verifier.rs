@@ -1,36 +1,51 @@
 //! This module implements zk-proof batch verifier functionality.
 
 use crate::{
+    alphas::Alphas,
     circuits::{
         argument::ArgumentType,
         constraints::ConstraintSystem,
         expr::{Column, Constants, PolishToken},
         gate::GateType,
-        lookup::{lookups::LookupsUsed, tables::combine_table},
+        lookup::{
+            lookups::{LookupPattern, LookupsUsed},
+            runtime_tables::UnusedEntry,
+            tables::combine_table,
+        },
         polynomials::{generic, permutation},
         scalars::RandomOracles,
         wires::{COLUMNS, PERMUTS},
     },
     curve::KimchiCurve,
-    error::VerifyError,
-    oracles::OraclesResult,
-    plonk_sponge::FrSponge,
-    proof::{ProverProof, RecursionChallenge},
-    verifier_index::VerifierIndex,
+    error::{CycleVerifyError, EvaluationPoint, OpenedPolynomial, VerifyError},
+    oracles::{OraclesResult, OraclesTranscript},
+    plonk_sponge::{absorb_context, FrSponge},
+    proof::{LookupCommitments, ProofEvaluations, ProverProof, RecursionChallenge},
+    transcript::{Round, TranscriptAbsorb},
+    verifier_index::{zero_comm, VerifierIndex},
+    verifier_math,
 };
-use ark_ff::{Field, One, PrimeField, Zero};
-use ark_poly::{EvaluationDomain, Polynomial};
-use commitment_dlog::commitment::{
-    combined_inner_product, BatchEvaluationProof, Evaluation, PolyComm,
+use ark_ec::msm::VariableBaseMSM;
+use ark_ff::{FftField, Field, One, PrimeField, Zero};
+use ark_poly::{EvaluationDomain, Radix2EvaluationDomain as D};
+use commitment_dlog::{
+    commitment::{
+        combined_inner_product, BatchEvaluationProof, CommitmentCurve, Evaluation, PolyComm,
+    },
+    error::OpeningError,
+    scheme::PolynomialCommitment,
 };
 use itertools::izip;
+use o1_utils::{curve_helpers::batch_normalize, serialization::LengthLimitExceeded};
 use oracle::{sponge::ScalarChallenge, FqSponge};
 use rand::thread_rng;
+use rayon::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 /// The result of a proof verification.
 pub type Result<T> = std::result::Result<T, VerifyError>;
 
-impl<G: KimchiCurve> ProverProof<G>
+impl<G: KimchiCurve, S: PolynomialCommitment<G>> ProverProof<G, S>
 where
     G::BaseField: PrimeField,
 {
@@ -48,20 +63,48 @@ where
         EFrSponge: FrSponge<G::ScalarField>,
     >(
         &self,
-        index: &VerifierIndex<G>,
+        index: &VerifierIndex<G, S>,
+        public_comm: &PolyComm<G>,
+    ) -> Result<OraclesResult<G, EFqSponge>> {
+        self.oracles_with_context::<EFqSponge, EFrSponge>(index, public_comm, &[])
+    }
+
+    /// This function runs the random oracle argument like [`ProverProof::oracles`],
+    /// for a proof bound to the application `context` (see [`ProverProof::create_with_context`]).
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `commitment(s)` are invalid(missing or wrong length), or `proof` is verified as invalid.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `PolishToken` evaluation is invalid.
+    pub fn oracles_with_context<
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+        EFrSponge: FrSponge<G::ScalarField>,
+    >(
+        &self,
+        index: &VerifierIndex<G, S>,
         public_comm: &PolyComm<G>,
+        context: &[u8],
     ) -> Result<OraclesResult<G, EFqSponge>> {
         //~
         //~ #### Fiat-Shamir argument
         //~
         //~ We run the following algorithm:
         //~
+        //~ 1. Enforce that the index commits to the permutation of at least one and at most `PERMUTS` columns.
+        check_sigma_comm(index)?;
+
         let n = index.domain.size;
         let (_, endo_r) = G::endos();
 
         //~ 1. Setup the Fq-Sponge.
         let mut fq_sponge = EFqSponge::new(G::OtherCurve::sponge_params());
 
+        //~ 1. If the proof is bound to an application context, absorb the digest of the context.
+        absorb_context::<G, _>(&mut fq_sponge, context);
+
         //~ 1. Absorb the digest of the VerifierIndex.
         let verifier_index_digest = index.digest::<EFqSponge>();
         fq_sponge.absorb_fq(&[verifier_index_digest]);
@@ -74,29 +117,29 @@ where
         //~ 1. Absorb the commitment of the public input polynomial with the Fq-Sponge.
         fq_sponge.absorb_g(&public_comm.unshifted);
 
-        //~ 1. Absorb the commitments to the registers / witness columns with the Fq-Sponge.
-        self.commitments
-            .w_comm
-            .iter()
-            .for_each(|c| fq_sponge.absorb_g(&c.unshifted));
+        //~ 1. Enforce that the proof contains the lookup commitments if and only if lookup is used,
+        //~    and the commitment to the runtime table if and only if runtime tables are used.
+        match (&index.lookup_index, &self.commitments.lookup) {
+            (None, None) => (),
+            (None, Some(_)) => return Err(VerifyError::UnexpectedLookupCommitment),
+            (Some(_), None) => return Err(VerifyError::LookupCommitmentMissing),
+            (Some(l), Some(lookup_commits)) => {
+                if l.runtime_tables_selector.is_some() != lookup_commits.runtime.is_some() {
+                    return Err(VerifyError::IncorrectRuntimeProof);
+                }
+                //~    Enforce as well that the commitments to the lookup polynomials, and their evaluations,
+                //~    have one chunk per chunk of the domain (see `max_poly_size`).
+                check_lookup_chunks(index, lookup_commits, &self.evals)?;
+            }
+        }
+
+        //~ 1. Absorb the commitments to the registers / witness columns with the Fq-Sponge,
+        //~    followed by the commitment to the runtime table if runtime tables are used,
+        //~    and by the number of entries used in each runtime table of variable length, if any.
+        self.commitments.absorb(Round::Witness, &mut fq_sponge);
 
         //~ 1. If lookup is used:
         let joint_combiner = if let Some(l) = &index.lookup_index {
-            let lookup_commits = self
-                .commitments
-                .lookup
-                .as_ref()
-                .ok_or(VerifyError::LookupCommitmentMissing)?;
-
-            // if runtime is used, absorb the commitment
-            if l.runtime_tables_selector.is_some() {
-                let runtime_commit = lookup_commits
-                    .runtime
-                    .as_ref()
-                    .ok_or(VerifyError::IncorrectRuntimeProof)?;
-                fq_sponge.absorb_g(&runtime_commit.unshifted);
-            }
-
             //~~ - If it involves queries to a multiple-column lookup table,
             //~~   then squeeze the Fq-Sponge to obtain the joint combiner challenge $j'$,
             //~~   otherwise set the joint combiner challenge $j'$ to $0$.
@@ -110,52 +153,72 @@ where
             //~~ - Derive the scalar joint combiner challenge $j$ from $j'$ using the endomorphism.
             //~~   (TODO: specify endomorphism)
             let joint_combiner = ScalarChallenge(joint_combiner);
-            let joint_combiner_field = joint_combiner.to_field(endo_r);
+            let joint_combiner_field =
+                joint_combiner.to_field_with_limbs(EFqSponge::CHALLENGE_LENGTH_IN_LIMBS, endo_r);
             let joint_combiner = (joint_combiner, joint_combiner_field);
 
-            //~~ - absorb the commitments to the sorted polynomials.
-            for com in &lookup_commits.sorted {
-                fq_sponge.absorb_g(&com.unshifted);
-            }
-
             Some(joint_combiner)
         } else {
             None
         };
 
+        //~ 1. If lookup is used, absorb the commitments to the sorted polynomials.
+        self.commitments.absorb(Round::Sorted, &mut fq_sponge);
+
         //~ 1. Sample $\beta$ with the Fq-Sponge.
         let beta = fq_sponge.challenge();
 
         //~ 1. Sample $\gamma$ with the Fq-Sponge.
         let gamma = fq_sponge.challenge();
 
-        //~ 1. If using lookup, absorb the commitment to the aggregation lookup polynomial.
-        self.commitments.lookup.iter().for_each(|l| {
-            fq_sponge.absorb_g(&l.aggreg.unshifted);
-        });
-
-        //~ 1. Absorb the commitment to the permutation trace with the Fq-Sponge.
-        fq_sponge.absorb_g(&self.commitments.z_comm.unshifted);
+        //~ 1. If using lookup, absorb the commitment to the aggregation lookup polynomial,
+        //~    then absorb the commitment to the permutation trace with the Fq-Sponge.
+        self.commitments.absorb(Round::Permutation, &mut fq_sponge);
 
         //~ 1. Sample $\alpha'$ with the Fq-Sponge.
         let alpha_chal = ScalarChallenge(fq_sponge.challenge());
 
         //~ 1. Derive $\alpha$ from $\alpha'$ using the endomorphism (TODO: details).
-        let alpha = alpha_chal.to_field(endo_r);
+        let alpha = alpha_chal.to_field_with_limbs(EFqSponge::CHALLENGE_LENGTH_IN_LIMBS, endo_r);
 
-        //~ 1. Enforce that the length of the $t$ commitment is of size `PERMUTS`.
-        if self.commitments.t_comm.unshifted.len() != PERMUTS {
+        //~ 1. Enforce that the length of the $t$ commitment is of size `PERMUTS`
+        //~    per chunk of the domain (see `max_poly_size`).
+        if self.commitments.t_comm.unshifted.len() != PERMUTS * index.chunks() {
             return Err(VerifyError::IncorrectCommitmentLength("t"));
         }
 
+        //~ 1. Enforce that the proof evaluates all the permutation polynomials but the last one.
+        if self
+            .evals
+            .iter()
+            .any(|e| e.s.len() != index.sigma_comm.len() - 1)
+        {
+            return Err(VerifyError::IncorrectEvaluationsLength("s"));
+        }
+
+        //~ 1. Enforce that the columns declared zero by the index are neither committed to nor evaluated.
+        for (col, zero) in index.zero_columns.iter().enumerate() {
+            if !zero {
+                continue;
+            }
+            if !self.commitments.w_comm[col].unshifted.is_empty()
+                || self.commitments.w_comm[col].shifted.is_some()
+            {
+                return Err(VerifyError::IncorrectCommitmentLength("w"));
+            }
+            if self.evals.iter().any(|e| !e.w[col].is_empty()) {
+                return Err(VerifyError::IncorrectEvaluationsLength("w"));
+            }
+        }
+
         //~ 1. Absorb the commitment to the quotient polynomial $t$ into the argument.
-        fq_sponge.absorb_g(&self.commitments.t_comm.unshifted);
+        self.commitments.absorb(Round::Quotient, &mut fq_sponge);
 
         //~ 1. Sample $\zeta'$ with the Fq-Sponge.
         let zeta_chal = ScalarChallenge(fq_sponge.challenge());
 
         //~ 1. Derive $\zeta$ from $\zeta'$ using the endomorphism (TODO: specify).
-        let zeta = zeta_chal.to_field(endo_r);
+        let zeta = zeta_chal.to_field_with_limbs(EFqSponge::CHALLENGE_LENGTH_IN_LIMBS, endo_r);
 
         //~ 1. Setup the Fr-Sponge.
         let digest = fq_sponge.clone().digest();
@@ -204,48 +267,11 @@ where
         let mut all_alphas = index.powers_of_alpha.clone();
         all_alphas.instantiate(alpha);
 
-        // compute Lagrange base evaluation denominators
-        let w: Vec<_> = index.domain.elements().take(self.public.len()).collect();
-
-        let mut zeta_minus_x: Vec<_> = w.iter().map(|w| zeta - w).collect();
-
-        w.iter()
-            .take(self.public.len())
-            .for_each(|w| zeta_minus_x.push(zetaw - w));
-
-        ark_ff::fields::batch_inversion::<G::ScalarField>(&mut zeta_minus_x);
-
         //~ 1. Evaluate the negated public polynomial (if present) at $\zeta$ and $\zeta\omega$.
         //~
         //~    NOTE: this works only in the case when the poly segment size is not smaller than that of the domain.
-        let public_evals = if self.public.is_empty() {
-            [vec![G::ScalarField::zero()], vec![G::ScalarField::zero()]]
-        } else {
-            [
-                vec![
-                    (self
-                        .public
-                        .iter()
-                        .zip(zeta_minus_x.iter())
-                        .zip(index.domain.elements())
-                        .map(|((p, l), w)| -*l * p * w)
-                        .fold(G::ScalarField::zero(), |x, y| x + y))
-                        * (zeta1 - G::ScalarField::one())
-                        * index.domain.size_inv,
-                ],
-                vec![
-                    (self
-                        .public
-                        .iter()
-                        .zip(zeta_minus_x[self.public.len()..].iter())
-                        .zip(index.domain.elements())
-                        .map(|((p, l), w)| -*l * p * w)
-                        .fold(G::ScalarField::zero(), |x, y| x + y))
-                        * index.domain.size_inv
-                        * (zetaw.pow(&[n as u64]) - G::ScalarField::one()),
-                ],
-            ]
-        };
+        let public_evals =
+            public_evaluations(index.domain, index.public_elements(), &self.public, zeta);
 
         //~ 1. Absorb the unique evaluation of ft: $ft(\zeta\omega)$.
         fr_sponge.absorb(&self.ft_eval1);
@@ -256,22 +282,22 @@ where
         //~~ - generic selector
         //~~ - poseidon selector
         //~~ - the 15 register/witness
-        //~~ - 6 sigmas evaluations (the last one is not evaluated)
+        //~~ - the sigmas evaluations (the last one is not evaluated)
         fr_sponge.absorb_multiple(&public_evals[0]);
         fr_sponge.absorb_multiple(&public_evals[1]);
-        fr_sponge.absorb_evaluations([&self.evals[0], &self.evals[1]]);
+        self.evals.absorb(Round::Evaluations, &mut fr_sponge);
 
         //~ 1. Sample $v'$ with the Fr-Sponge.
         let v_chal = fr_sponge.challenge();
 
         //~ 1. Derive $v$ from $v'$ using the endomorphism (TODO: specify).
-        let v = v_chal.to_field(endo_r);
+        let v = v_chal.to_field_with_limbs(EFrSponge::CHALLENGE_LENGTH_IN_LIMBS, endo_r);
 
         //~ 1. Sample $u'$ with the Fr-Sponge.
         let u_chal = fr_sponge.challenge();
 
         //~ 1. Derive $u$ from $u'$ using the endomorphism (TODO: specify).
-        let u = u_chal.to_field(endo_r);
+        let u = u_chal.to_field_with_limbs(EFrSponge::CHALLENGE_LENGTH_IN_LIMBS, endo_r);
 
         //~ 1. Create a list of all polynomials that have an evaluation proof.
 
@@ -280,131 +306,6 @@ where
             self.evals[1].combine(powers_of_eval_points_for_chunks[1]),
         ];
 
-        //~ 1. Compute the evaluation of $ft(\zeta)$.
-        let ft_eval0 = {
-            let zkp = index.zkpm().evaluate(&zeta);
-            let zeta1m1 = zeta1 - G::ScalarField::one();
-
-            let mut alpha_powers =
-                all_alphas.get_alphas(ArgumentType::Permutation, permutation::CONSTRAINTS);
-            let alpha0 = alpha_powers
-                .next()
-                .expect("missing power of alpha for permutation");
-            let alpha1 = alpha_powers
-                .next()
-                .expect("missing power of alpha for permutation");
-            let alpha2 = alpha_powers
-                .next()
-                .expect("missing power of alpha for permutation");
-
-            let init = (evals[0].w[PERMUTS - 1] + gamma) * evals[1].z * alpha0 * zkp;
-            let mut ft_eval0 = evals[0]
-                .w
-                .iter()
-                .zip(evals[0].s.iter())
-                .map(|(w, s)| (beta * s) + w + gamma)
-                .fold(init, |x, y| x * y);
-
-            ft_eval0 -= if public_evals[0].is_empty() {
-                G::ScalarField::zero()
-            } else {
-                public_evals[0][0]
-            };
-
-            ft_eval0 -= evals[0]
-                .w
-                .iter()
-                .zip(index.shift.iter())
-                .map(|(w, s)| gamma + (beta * zeta * s) + w)
-                .fold(alpha0 * zkp * evals[0].z, |x, y| x * y);
-
-            let numerator = ((zeta1m1 * alpha1 * (zeta - index.w()))
-                + (zeta1m1 * alpha2 * (zeta - G::ScalarField::one())))
-                * (G::ScalarField::one() - evals[0].z);
-
-            let denominator = (zeta - index.w()) * (zeta - G::ScalarField::one());
-            let denominator = denominator.inverse().expect("negligible probability");
-
-            ft_eval0 += numerator * denominator;
-
-            let cs = Constants {
-                alpha,
-                beta,
-                gamma,
-                joint_combiner: joint_combiner.as_ref().map(|j| j.1),
-                endo_coefficient: index.endo,
-                mds: &G::sponge_params().mds,
-                foreign_field_modulus: index.foreign_field_modulus.clone(),
-            };
-            ft_eval0 -= PolishToken::evaluate(
-                &index.linearization.constant_term,
-                index.domain,
-                zeta,
-                &evals,
-                &cs,
-            )
-            .unwrap();
-
-            ft_eval0
-        };
-
-        let combined_inner_product = {
-            let ft_eval0 = vec![ft_eval0];
-            let ft_eval1 = vec![self.ft_eval1];
-
-            #[allow(clippy::type_complexity)]
-            let mut es: Vec<(Vec<Vec<G::ScalarField>>, Option<usize>)> =
-                polys.iter().map(|(_, e)| (e.clone(), None)).collect();
-            es.push((public_evals.to_vec(), None));
-            es.push((vec![ft_eval0, ft_eval1], None));
-            es.push((
-                self.evals.iter().map(|e| e.z.clone()).collect::<Vec<_>>(),
-                None,
-            ));
-            es.push((
-                self.evals
-                    .iter()
-                    .map(|e| e.generic_selector.clone())
-                    .collect::<Vec<_>>(),
-                None,
-            ));
-            es.push((
-                self.evals
-                    .iter()
-                    .map(|e| e.poseidon_selector.clone())
-                    .collect::<Vec<_>>(),
-                None,
-            ));
-            es.extend(
-                (0..COLUMNS)
-                    .map(|c| {
-                        (
-                            self.evals
-                                .iter()
-                                .map(|e| e.w[c].clone())
-                                .collect::<Vec<_>>(),
-                            None,
-                        )
-                    })
-                    .collect::<Vec<_>>(),
-            );
-            es.extend(
-                (0..PERMUTS - 1)
-                    .map(|c| {
-                        (
-                            self.evals
-                                .iter()
-                                .map(|e| e.s[c].clone())
-                                .collect::<Vec<_>>(),
-                            None,
-                        )
-                    })
-                    .collect::<Vec<_>>(),
-            );
-
-            combined_inner_product(&evaluation_points, &v, &u, &es, index.srs().g.len())
-        };
-
         let oracles = RandomOracles {
             joint_combiner,
             beta,
@@ -419,6 +320,21 @@ where
             u_chal,
         };
 
+        //~ 1. Compute the evaluation of $ft(\zeta)$.
+        let ft_eval0 = ft_eval0(index, &oracles, &all_alphas, &evals, &public_evals[0]);
+
+        let combined_inner_product = {
+            let ft_evals = [ft_eval0, self.ft_eval1];
+            let es = combined_inner_product_evaluations(
+                polys.iter().map(|(_, e)| &e[..]),
+                &public_evals,
+                &ft_evals,
+                &self.evals,
+                &index.zero_columns,
+            );
+            combined_inner_product(&evaluation_points, &v, &u, &es, index.srs().max_poly_size())
+        };
+
         Ok(OraclesResult {
             fq_sponge,
             digest,
@@ -432,15 +348,598 @@ where
             combined_inner_product,
         })
     }
+
+    /// Checks the shape of the proof against `index`, before any transcript work:
+    /// the number of chunks of its commitments and evaluations,
+    /// the number of its sorted lookup polynomials and of the rounds of its opening proof,
+    /// and the presence of its optional parts.
+    ///
+    /// This is the strict mode of [`batch_verify_strict`].
+    /// The other verification functions reject such proofs as well,
+    /// but only when running into them, with less precise errors.
+    ///
+    /// # Errors
+    ///
+    /// Will give error on the first part of the proof that is not of the expected shape.
+    pub fn validate(&self, index: &VerifierIndex<G, S>) -> Result<()> {
+        check_sigma_comm(index)?;
+        let chunks = index.chunks();
+
+        // commitments have no shifted part, and at most one chunk per segment of the domain,
+        // exactly when they are computed from evaluations over the domain
+        let check_comm = |name, comm: &PolyComm<G>, exact: bool| {
+            if comm.shifted.is_some() {
+                return Err(VerifyError::UnexpectedShiftedCommitment(name));
+            }
+            let len = comm.unshifted.len();
+            if len == 0 || len > chunks || (exact && len != chunks) {
+                return Err(VerifyError::IncorrectCommitmentChunks(name, chunks, len));
+            }
+            Ok(())
+        };
+        // public input and previous challenges
+        if self.public.len() != index.public {
+            return Err(VerifyError::IncorrectPubicInputLength(index.public));
+        }
+        if self.prev_challenges.len() != index.prev_challenges {
+            return Err(VerifyError::IncorrectPrevChallengesLength(
+                index.prev_challenges,
+                self.prev_challenges.len(),
+            ));
+        }
+
+        // witness, without the columns declared zero
+        for col in 0..COLUMNS {
+            if index.zero_columns[col] {
+                let comm = &self.commitments.w_comm[col];
+                if !comm.unshifted.is_empty() || comm.shifted.is_some() {
+                    return Err(VerifyError::IncorrectCommitmentLength("w"));
+                }
+                if self.evals.iter().any(|e| !e.w[col].is_empty()) {
+                    return Err(VerifyError::IncorrectEvaluationsLength("w"));
+                }
+            } else {
+                check_comm("w", &self.commitments.w_comm[col], true)?;
+                check_evals("w", chunks, self.evals.iter().map(|e| &e.w[col]))?;
+            }
+        }
+
+        // permutation and quotient
+        check_comm("z", &self.commitments.z_comm, false)?;
+        check_evals("z", chunks, self.evals.iter().map(|e| &e.z))?;
+        if self.commitments.t_comm.shifted.is_some() {
+            return Err(VerifyError::UnexpectedShiftedCommitment("t"));
+        }
+        if self.commitments.t_comm.unshifted.len() != PERMUTS * chunks {
+            return Err(VerifyError::IncorrectCommitmentChunks(
+                "t",
+                PERMUTS * chunks,
+                self.commitments.t_comm.unshifted.len(),
+            ));
+        }
+        let sigmas = index.sigma_comm.len() - 1;
+        if self.evals.iter().any(|e| e.s.len() != sigmas) {
+            return Err(VerifyError::IncorrectEvaluationsLength("s"));
+        }
+        for i in 0..sigmas {
+            check_evals("s", chunks, self.evals.iter().map(|e| &e.s[i]))?;
+        }
+        check_evals(
+            "generic selector",
+            chunks,
+            self.evals.iter().map(|e| &e.generic_selector),
+        )?;
+        check_evals(
+            "poseidon selector",
+            chunks,
+            self.evals.iter().map(|e| &e.poseidon_selector),
+        )?;
+
+        // lookup
+        match (&index.lookup_index, &self.commitments.lookup) {
+            (None, None) => {
+                if self.evals.iter().any(|e| e.lookup.is_some()) {
+                    return Err(VerifyError::ProofInconsistentLookup);
+                }
+            }
+            (None, Some(_)) => return Err(VerifyError::UnexpectedLookupCommitment),
+            (Some(_), None) => return Err(VerifyError::LookupCommitmentMissing),
+            (Some(l), Some(lookup)) => {
+                use strum::IntoEnumIterator;
+
+                // one more sorted polynomial than lookups per row
+                let sorted = LookupPattern::iter()
+                    .filter(|p| l.lookup_selectors[*p].is_some())
+                    .map(|p| p.max_lookups_per_row() + 1)
+                    .max()
+                    .unwrap_or(1);
+                if lookup.sorted.len() != sorted {
+                    return Err(VerifyError::IncorrectSortedLength(
+                        sorted,
+                        lookup.sorted.len(),
+                    ));
+                }
+                for comm in &lookup.sorted {
+                    check_comm("sorted", comm, true)?;
+                }
+                check_comm("lookup aggregation", &lookup.aggreg, true)?;
+
+                let runtime = l.runtime_tables_selector.is_some();
+                match &lookup.runtime {
+                    Some(comm) if runtime => check_comm("runtime table", comm, false)?,
+                    None if !runtime => (),
+                    _ => return Err(VerifyError::IncorrectRuntimeProof),
+                }
+                // the number of entries used in each runtime table of variable length
+                if UnusedEntry::all(&l.variable_runtime_tables, &lookup.runtime_lens).is_none() {
+                    return Err(VerifyError::IncorrectRuntimeProof);
+                }
+
+                for e in &self.evals {
+                    let evals = e.lookup.as_ref().ok_or(VerifyError::LookupEvalsMissing)?;
+                    if evals.sorted.len() != sorted {
+                        return Err(VerifyError::IncorrectSortedLength(
+                            sorted,
+                            evals.sorted.len(),
+                        ));
+                    }
+                    if evals.runtime.is_some() != runtime {
+                        return Err(VerifyError::IncorrectRuntimeProof);
+                    }
+                }
+                let lookup_evals = || self.evals.iter().map(|e| e.lookup.as_ref().unwrap());
+                check_evals(
+                    "lookup aggregation",
+                    chunks,
+                    lookup_evals().map(|l| &l.aggreg),
+                )?;
+                check_evals("lookup table", chunks, lookup_evals().map(|l| &l.table))?;
+                for i in 0..sorted {
+                    check_evals("sorted", chunks, lookup_evals().map(|l| &l.sorted[i]))?;
+                }
+                if runtime {
+                    let runtime_evals = lookup_evals().map(|l| l.runtime.as_ref().unwrap());
+                    check_evals("runtime table", chunks, runtime_evals)?;
+                }
+            }
+        }
+
+        // opening proof, with one round per halving of the SRS for the inner product argument
+        if let Some((rounds, expected)) = index.srs().opening_rounds(&self.proof) {
+            if rounds != expected {
+                return Err(VerifyError::IncorrectOpeningRounds(expected, rounds));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks that the evaluations of the polynomial `name` at each point have at least one chunk,
+/// and at most `chunks`.
+fn check_evals<'a, F: 'a>(
+    name: &'static str,
+    chunks: usize,
+    evals: impl Iterator<Item = &'a Vec<F>>,
+) -> Result<()> {
+    for e in evals {
+        if e.is_empty() || e.len() > chunks {
+            return Err(VerifyError::IncorrectEvaluationChunks(
+                name,
+                chunks,
+                e.len(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the commitments to the sorted and aggregation lookup polynomials of a proof
+/// have one chunk per chunk of the domain of `index`, and the commitment to its runtime table at most as many,
+/// and that their evaluations (and those of the lookup table) have as many chunks as their commitments,
+/// so that each chunk of an evaluation opens the corresponding chunk of its commitment.
+fn check_lookup_chunks<G: KimchiCurve, S: PolynomialCommitment<G>>(
+    index: &VerifierIndex<G, S>,
+    lookup: &LookupCommitments<G>,
+    evals: &[ProofEvaluations<Vec<G::ScalarField>>; 2],
+) -> Result<()> {
+    let chunks = index.chunks();
+    let comms = lookup
+        .sorted
+        .iter()
+        .map(|comm| ("sorted", comm))
+        .chain(std::iter::once(("lookup aggregation", &lookup.aggreg)));
+    for (name, comm) in comms {
+        if comm.unshifted.len() != chunks {
+            return Err(VerifyError::IncorrectCommitmentChunks(
+                name,
+                chunks,
+                comm.unshifted.len(),
+            ));
+        }
+    }
+    if let Some(runtime) = &lookup.runtime {
+        let len = runtime.unshifted.len();
+        if len == 0 || len > chunks {
+            return Err(VerifyError::IncorrectCommitmentChunks(
+                "runtime table",
+                chunks,
+                len,
+            ));
+        }
+    }
+
+    for e in evals {
+        let l = e.lookup.as_ref().ok_or(VerifyError::LookupEvalsMissing)?;
+        if l.sorted.len() != lookup.sorted.len() {
+            return Err(VerifyError::IncorrectSortedLength(
+                lookup.sorted.len(),
+                l.sorted.len(),
+            ));
+        }
+        let evals = l
+            .sorted
+            .iter()
+            .map(|e| ("sorted", e, chunks))
+            .chain([
+                ("lookup aggregation", &l.aggreg, chunks),
+                ("lookup table", &l.table, chunks),
+            ])
+            .chain(
+                l.runtime
+                    .iter()
+                    .zip(&lookup.runtime)
+                    .map(|(e, comm)| ("runtime table", e, comm.unshifted.len())),
+            );
+        for (name, e, expected) in evals {
+            if e.len() != expected {
+                return Err(VerifyError::EvaluationChunksMismatch(
+                    name,
+                    expected,
+                    e.len(),
+                ));
+            }
+        }
+        if l.runtime.is_some() != lookup.runtime.is_some() {
+            return Err(VerifyError::IncorrectRuntimeProof);
+        }
+    }
+    Ok(())
+}
+
+/// Checks that `index` commits to the permutation polynomials of at least one
+/// and at most [`PERMUTS`] columns, which a deserialized index may not do.
+fn check_sigma_comm<G: KimchiCurve, S>(index: &VerifierIndex<G, S>) -> Result<()> {
+    if index.sigma_comm.is_empty() || index.sigma_comm.len() > PERMUTS {
+        return Err(VerifyError::IncorrectCommitmentLength("sigma"));
+    }
+    Ok(())
+}
+
+/// Evaluates the negated public input polynomial at $\zeta$ and $\zeta\omega$,
+/// given the elements of the domain of the public input rows
+/// (see [VerifierIndex::public_elements]) and the public input.
+///
+/// Note: this only works when the poly segment size is not smaller than the domain size.
+pub fn public_evaluations<F: FftField>(
+    domain: D<F>,
+    public_elements: &[F],
+    public: &[F],
+    zeta: F,
+) -> [Vec<F>; 2] {
+    if public.is_empty() {
+        return [vec![F::zero()], vec![F::zero()]];
+    }
+
+    let zetaw = zeta * domain.group_gen;
+
+    // compute Lagrange base evaluation denominators
+    let mut zeta_minus_x: Vec<_> = public_elements.iter().map(|w| zeta - w).collect();
+    public_elements
+        .iter()
+        .for_each(|w| zeta_minus_x.push(zetaw - w));
+    ark_ff::fields::batch_inversion::<F>(&mut zeta_minus_x);
+    let (zeta_minus_x, zetaw_minus_x) = zeta_minus_x.split_at(public_elements.len());
+
+    let eval = |inverses: &[F]| {
+        public
+            .iter()
+            .zip(inverses)
+            .zip(public_elements)
+            .map(|((p, l), w)| -*l * p * w)
+            .fold(F::zero(), |x, y| x + y)
+    };
+    [
+        vec![eval(zeta_minus_x) * verifier_math::vanishing(domain, zeta) * domain.size_inv],
+        vec![eval(zetaw_minus_x) * domain.size_inv * verifier_math::vanishing(domain, zetaw)],
+    ]
+}
+
+/// Computes the evaluation of $ft$ at $\zeta$, given the challenges of the protocol,
+/// the evaluations of the proof at $\zeta$ and $\zeta\omega$ (combined across chunks),
+/// and the evaluation of the public input polynomial at $\zeta$.
+///
+/// # Panics
+///
+/// Will panic if `PolishToken` evaluation is invalid.
+pub fn ft_eval0<G: KimchiCurve, S>(
+    index: &VerifierIndex<G, S>,
+    oracles: &RandomOracles<G::ScalarField>,
+    all_alphas: &Alphas<G::ScalarField>,
+    evals: &[ProofEvaluations<G::ScalarField>],
+    public_eval: &[G::ScalarField],
+) -> G::ScalarField {
+    let RandomOracles {
+        alpha,
+        beta,
+        gamma,
+        zeta,
+        ..
+    } = *oracles;
+    let zkp = verifier_math::zkpm(index.domain, zeta);
+    let zeta1m1 = verifier_math::vanishing(index.domain, zeta);
+
+    let mut alpha_powers =
+        all_alphas.get_alphas(ArgumentType::Permutation, permutation::CONSTRAINTS);
+    let alpha0 = alpha_powers
+        .next()
+        .expect("missing power of alpha for permutation");
+    let alpha1 = alpha_powers
+        .next()
+        .expect("missing power of alpha for permutation");
+    let alpha2 = alpha_powers
+        .next()
+        .expect("missing power of alpha for permutation");
+
+    // only the first columns take part in the permutation,
+    // the last of them having its sigma in commitment form only
+    let permuted_columns = index.sigma_comm.len();
+    let init = (evals[0].w[permuted_columns - 1] + gamma) * evals[1].z * alpha0 * zkp;
+    let mut ft_eval0 = evals[0]
+        .w
+        .iter()
+        .zip(evals[0].s.iter())
+        .map(|(w, s)| (beta * s) + w + gamma)
+        .fold(init, |x, y| x * y);
+
+    ft_eval0 -= public_eval
+        .first()
+        .copied()
+        .unwrap_or_else(G::ScalarField::zero);
+
+    ft_eval0 -= evals[0]
+        .w
+        .iter()
+        .zip(index.shift.iter())
+        .take(permuted_columns)
+        .map(|(w, s)| gamma + (beta * zeta * s) + w)
+        .fold(alpha0 * zkp * evals[0].z, |x, y| x * y);
+
+    let numerator = ((zeta1m1 * alpha1 * (zeta - index.w()))
+        + (zeta1m1 * alpha2 * (zeta - G::ScalarField::one())))
+        * (G::ScalarField::one() - evals[0].z);
+
+    let denominator = (zeta - index.w()) * (zeta - G::ScalarField::one());
+    let denominator = denominator.inverse().expect("negligible probability");
+
+    ft_eval0 += numerator * denominator;
+
+    let cs = Constants {
+        alpha,
+        beta,
+        gamma,
+        joint_combiner: oracles.joint_combiner.as_ref().map(|j| j.1),
+        endo_coefficient: index.endo,
+        mds: &G::sponge_params().mds,
+        foreign_field_modulus: index.foreign_field_modulus.clone(),
+    };
+    ft_eval0 -= PolishToken::evaluate(
+        &index.linearization.constant_term,
+        index.domain,
+        zeta,
+        evals,
+        &cs,
+    )
+    .unwrap();
+
+    ft_eval0
+}
+
+/// Lists the evaluations at $\zeta$ and $\zeta\omega$ that are combined
+/// into the combined inner product, in the order in which they are opened:
+/// the evaluations of the previous recursion challenges,
+/// of the public input polynomial, of $ft$, and of the proof
+/// (but the ones of the `zero_columns`, which are not opened).
+#[allow(clippy::type_complexity)]
+pub fn combined_inner_product_evaluations<'a, F: Field>(
+    prev_evals: impl IntoIterator<Item = &'a [Vec<F>]>,
+    public_evals: &'a [Vec<F>; 2],
+    ft_evals: &'a [F; 2],
+    evals: &'a [ProofEvaluations<Vec<F>>; 2],
+    zero_columns: &[bool; COLUMNS],
+) -> Vec<(Vec<&'a [F]>, Option<usize>)> {
+    let mut es: Vec<_> = prev_evals
+        .into_iter()
+        .map(|e| (e.iter().map(|e| &e[..]).collect(), None))
+        .collect();
+    es.push((public_evals.iter().map(|e| &e[..]).collect(), None));
+    es.push((ft_evals.iter().map(std::slice::from_ref).collect(), None));
+    es.push((evals.iter().map(|e| &e.z[..]).collect(), None));
+    es.push((
+        evals.iter().map(|e| &e.generic_selector[..]).collect(),
+        None,
+    ));
+    es.push((
+        evals.iter().map(|e| &e.poseidon_selector[..]).collect(),
+        None,
+    ));
+    es.extend(
+        (0..COLUMNS)
+            .filter(|c| !zero_columns[*c])
+            .map(|c| (evals.iter().map(|e| &e.w[c][..]).collect(), None)),
+    );
+    es.extend((0..evals[0].s.len()).map(|c| (evals.iter().map(|e| &e.s[c][..]).collect(), None)));
+    es
+}
+
+/// Commits to the negated public input polynomial of a proof.
+fn public_commitment<G, S>(
+    index: &VerifierIndex<G, S>,
+    public: &[G::ScalarField],
+) -> Result<PolyComm<G>>
+where
+    G: KimchiCurve,
+    G::BaseField: PrimeField,
+    S: PolynomialCommitment<G>,
+{
+    let mut comms = public_commitments(&[(index, public)])?;
+    Ok(comms.pop().expect("one commitment per public input"))
+}
+
+/// Commits to the negated public input polynomials of a batch of proofs, given with their index.
+/// The Lagrange bases of an index are gathered once for all its proofs,
+/// and the commitments are converted to affine form together, with a single inversion.
+pub(crate) fn public_commitments<G, S>(
+    publics: &[(&VerifierIndex<G, S>, &[G::ScalarField])],
+) -> Result<Vec<PolyComm<G>>>
+where
+    G: KimchiCurve,
+    G::BaseField: PrimeField,
+    S: PolynomialCommitment<G>,
+{
+    // the bases of the public input of each distinct index, followed by the blinding generator
+    let mut bases: Vec<(*const VerifierIndex<G, S>, Vec<G>)> = vec![];
+    for (index, public) in publics {
+        if public.len() != index.public {
+            return Err(VerifyError::IncorrectPubicInputLength(index.public));
+        }
+        if !bases.iter().any(|(i, _)| std::ptr::eq(*i, *index)) {
+            let srs = index.srs();
+            // without public input, the Lagrange bases (which the domain may exceed) are not needed
+            let mut index_bases: Vec<_> = if index.public == 0 {
+                vec![]
+            } else {
+                let lgr_comm = srs
+                    .lagrange_basis(index.domain)
+                    .expect("pre-computed committed lagrange bases not found");
+                lgr_comm.iter().take(index.public).copied().collect()
+            };
+            index_bases.push(srs.blinding_commitment());
+            bases.push((*index, index_bases));
+        }
+    }
+
+    let msms: Vec<_> = publics
+        .iter()
+        .map(|(index, public)| {
+            let (_, bases) = bases
+                .iter()
+                .find(|(i, _)| std::ptr::eq(*i, *index))
+                .expect("the bases of every index are gathered");
+            let scalars: Vec<_> = public
+                .iter()
+                .map(|s| (-*s).into_repr())
+                .chain(std::iter::once(G::ScalarField::one().into_repr()))
+                .collect();
+            (&bases[..], scalars)
+        })
+        .collect();
+    let comms: Vec<_> = msms
+        .par_iter()
+        .map(|(bases, scalars)| VariableBaseMSM::multi_scalar_mul(bases, scalars))
+        .collect();
+
+    Ok(batch_normalize(&comms)
+        .into_iter()
+        .map(|comm| PolyComm {
+            unshifted: vec![comm],
+            shifted: None,
+        })
+        .collect())
+}
+
+/// Runs the oracle protocol (see [ProverProof::oracles]) on a proof,
+/// and captures the scalars it produces.
+///
+/// # Errors
+///
+/// Will give error if the oracle protocol fails on the proof.
+pub fn capture_oracles<G, EFqSponge, EFrSponge, S>(
+    index: &VerifierIndex<G, S>,
+    proof: &ProverProof<G, S>,
+) -> Result<OraclesTranscript<G::ScalarField>>
+where
+    G: KimchiCurve,
+    G::BaseField: PrimeField,
+    S: PolynomialCommitment<G>,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    EFrSponge: FrSponge<G::ScalarField>,
+{
+    let public_comm = public_commitment(index, &proof.public)?;
+    let result = proof.oracles::<EFqSponge, EFrSponge>(index, &public_comm)?;
+    Ok(result.transcript())
+}
+
+/// Re-runs the oracle protocol on a proof, and checks that it produces
+/// exactly the scalars of a captured transcript (see [capture_oracles]).
+///
+/// # Errors
+///
+/// Will give error if the oracle protocol fails on the proof,
+/// or if one of the scalars does not match the transcript.
+pub fn replay_oracles<G, EFqSponge, EFrSponge, S>(
+    index: &VerifierIndex<G, S>,
+    proof: &ProverProof<G, S>,
+    transcript: &OraclesTranscript<G::ScalarField>,
+) -> Result<()>
+where
+    G: KimchiCurve,
+    G::BaseField: PrimeField,
+    S: PolynomialCommitment<G>,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    EFrSponge: FrSponge<G::ScalarField>,
+{
+    let replayed = capture_oracles::<G, EFqSponge, EFrSponge, S>(index, proof)?;
+
+    let mismatch = if replayed.digest != transcript.digest {
+        Some("digest")
+    } else if replayed.oracles != transcript.oracles {
+        Some("oracles")
+    } else if replayed.public_evals != transcript.public_evals {
+        Some("public_evals")
+    } else if replayed.powers_of_eval_points_for_chunks
+        != transcript.powers_of_eval_points_for_chunks
+    {
+        Some("powers_of_eval_points_for_chunks")
+    } else if replayed.zeta1 != transcript.zeta1 {
+        Some("zeta1")
+    } else if replayed.ft_eval0 != transcript.ft_eval0 {
+        Some("ft_eval0")
+    } else if replayed.combined_inner_product != transcript.combined_inner_product {
+        Some("combined_inner_product")
+    } else {
+        None
+    };
+
+    match mismatch {
+        Some(field) => Err(VerifyError::OraclesMismatch(field)),
+        None => Ok(()),
+    }
 }
 
-fn to_batch<'a, G, EFqSponge, EFrSponge>(
-    index: &VerifierIndex<G>,
-    proof: &'a ProverProof<G>,
-) -> Result<BatchEvaluationProof<'a, G, EFqSponge>>
+fn to_batch<'a, G, EFqSponge, EFrSponge, S>(
+    index: &VerifierIndex<G, S>,
+    proof: &'a ProverProof<G, S>,
+    public_comm: PolyComm<G>,
+    context: &[u8],
+) -> Result<(
+    BatchEvaluationProof<'a, G, EFqSponge, S::OpeningProof>,
+    Vec<OpenedPolynomial>,
+)>
 where
     G: KimchiCurve,
     G::BaseField: PrimeField,
+    S: PolynomialCommitment<G>,
     EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
     EFrSponge: FrSponge<G::ScalarField>,
 {
@@ -459,39 +958,8 @@ where
         ));
     }
 
-    //~ 1. Commit to the negated public input polynomial.
-    let lgr_comm = index
-        .srs()
-        .lagrange_bases
-        .get(&index.domain.size())
-        .expect("pre-computed committed lagrange bases not found");
-    let com: Vec<_> = lgr_comm
-        .iter()
-        .map(|c| PolyComm {
-            unshifted: vec![*c],
-            shifted: None,
-        })
-        .take(index.public)
-        .collect();
-    let com_ref: Vec<_> = com.iter().collect();
-    if proof.public.len() != index.public {
-        return Err(VerifyError::IncorrectPubicInputLength(index.public));
-    }
-    let elm: Vec<_> = proof.public.iter().map(|s| -*s).collect();
-    let public_comm = PolyComm::<G>::multi_scalar_mul(&com_ref, &elm);
-    let public_comm = {
-        index
-            .srs()
-            .mask_custom(
-                public_comm,
-                &PolyComm {
-                    unshifted: vec![G::ScalarField::one(); 1],
-                    shifted: None,
-                },
-            )
-            .unwrap()
-            .commitment
-    };
+    //~ 1. Commit to the negated public input polynomial
+    //~    (for all the proofs of the batch at once, see [batch verification](#batch-verification-of-proofs)).
 
     //~ 1. Run the [Fiat-Shamir argument](#fiat-shamir-argument).
     let OraclesResult {
@@ -504,7 +972,7 @@ where
         zeta1: zeta_to_domain_size,
         ft_eval0,
         ..
-    } = proof.oracles::<EFqSponge, EFrSponge>(index, &public_comm)?;
+    } = proof.oracles_with_context::<EFqSponge, EFrSponge>(index, &public_comm, context)?;
 
     //~ 1. Combine the chunked polynomials' evaluations
     //~    (TODO: most likely only the quotient polynomial is chunked)
@@ -514,6 +982,30 @@ where
         proof.evals[1].combine(powers_of_eval_points_for_chunks[1]),
     ];
 
+    //~ 1. List the entries left unused in the runtime tables of variable length,
+    //~    from the number of entries used in each of them given by the proof,
+    //~    and extend the commitment to the runtime selector to their rows
+    //~    with the commitments to their Lagrange polynomials.
+    let unused_entries = match (&index.lookup_index, &proof.commitments.lookup) {
+        (Some(li), Some(lookup)) => {
+            UnusedEntry::all(&li.variable_runtime_tables, &lookup.runtime_lens)
+                .ok_or(VerifyError::IncorrectRuntimeProof)?
+        }
+        _ => vec![],
+    };
+    let runtime_selector_comm = index
+        .lookup_index
+        .as_ref()
+        .and_then(|li| li.runtime_tables_selector.as_ref())
+        .map(|comm| {
+            if unused_entries.is_empty() {
+                comm.clone()
+            } else {
+                let runs = UnusedEntry::mask_runs(&unused_entries, |_| G::ScalarField::one());
+                comm + &index.srs().commit_runs_non_hiding(index.domain, &runs)
+            }
+        });
+
     //~ 4. Compute the commitment to the linearized polynomial $f$.
     //~    To do this, add the constraints of all of the gates, of the permutation,
     //~    and optionally of the lookup.
@@ -522,13 +1014,17 @@ where
     //~    contained in the verifier index or in the proof,
     //~    unless a polynomial has its evaluation provided by the proof
     //~    in which case the evaluation should be used in place of the commitment.
+    //~    The coefficient and selector commitments omitted from the verifier index
+    //~    are the commitment to the zero polynomial.
     let f_comm = {
+        let zero = zero_comm(index.chunks());
+
         // the permutation is written manually (not using the expr framework)
-        let zkp = index.zkpm().evaluate(&oracles.zeta);
+        let zkp = verifier_math::zkpm(index.domain, oracles.zeta);
 
         let alphas = all_alphas.get_alphas(ArgumentType::Permutation, permutation::CONSTRAINTS);
 
-        let mut commitments = vec![&index.sigma_comm[PERMUTS - 1]];
+        let mut commitments = vec![&index.sigma_comm[index.sigma_comm.len() - 1]];
         let mut scalars = vec![ConstraintSystem::<G::ScalarField>::perm_scalars(
             &evals,
             oracles.beta,
@@ -539,16 +1035,23 @@ where
 
         // generic is written manually (not using the expr framework)
         {
-            let alphas =
-                all_alphas.get_alphas(ArgumentType::Gate(GateType::Generic), generic::CONSTRAINTS);
+            let alphas = all_alphas.get_alphas(
+                ArgumentType::Gate(GateType::Generic),
+                generic::constraints(index.triple_generic),
+            );
 
             let generic_scalars = &ConstraintSystem::<G::ScalarField>::gnrc_scalars(
                 alphas,
                 &evals[0].w,
                 evals[0].generic_selector,
+                index.triple_generic,
             );
 
-            let generic_com = index.coefficients_comm.iter().take(generic_scalars.len());
+            let generic_com = index
+                .coefficients_comm
+                .iter()
+                .take(generic_scalars.len())
+                .map(|comm| comm.as_ref().unwrap_or(&zero));
 
             assert_eq!(generic_scalars.len(), generic_com.len());
 
@@ -582,7 +1085,7 @@ where
                     }
                     Coefficient(i) => {
                         scalars.push(scalar);
-                        commitments.push(&index.coefficients_comm[*i]);
+                        commitments.push(index.coefficients_comm[*i].as_ref().unwrap_or(&zero));
                     }
                     Z => {
                         scalars.push(scalar);
@@ -625,7 +1128,7 @@ where
                         None => {
                             panic!("Attempted to use {:?}, but no lookup index was given", col)
                         }
-                        Some(lindex) => match &lindex.runtime_tables_selector {
+                        Some(_) => match &runtime_selector_comm {
                             None => panic!("No runtime selector was given"),
                             Some(comm) => {
                                 scalars.push(scalar);
@@ -639,24 +1142,37 @@ where
                     Index(t) => {
                         use GateType::*;
                         let c = match t {
-                            Zero | Generic | Lookup => {
+                            Zero | Generic | TripleGeneric | Lookup | MultiTableLookup => {
                                 panic!("Selector for {:?} not defined", t)
                             }
-                            CompleteAdd => &index.complete_add_comm,
-                            VarBaseMul => &index.mul_comm,
-                            EndoMul => &index.emul_comm,
-                            EndoMulScalar => &index.endomul_scalar_comm,
+                            CompleteAdd => index.complete_add_comm.as_ref().unwrap_or(&zero),
+                            VarBaseMul => index.mul_comm.as_ref().unwrap_or(&zero),
+                            EndoMul => index.emul_comm.as_ref().unwrap_or(&zero),
+                            EndoMulScalar => index.endomul_scalar_comm.as_ref().unwrap_or(&zero),
                             Poseidon => &index.psm_comm,
+                            #[cfg(feature = "chacha")]
                             ChaCha0 => &index.chacha_comm.as_ref().unwrap()[0],
+                            #[cfg(feature = "chacha")]
                             ChaCha1 => &index.chacha_comm.as_ref().unwrap()[1],
+                            #[cfg(feature = "chacha")]
                             ChaCha2 => &index.chacha_comm.as_ref().unwrap()[2],
+                            #[cfg(feature = "chacha")]
                             ChaChaFinal => &index.chacha_comm.as_ref().unwrap()[3],
+                            #[cfg(feature = "cairo")]
                             CairoClaim | CairoInstruction | CairoFlags | CairoTransition => {
                                 unimplemented!()
                             }
+                            #[cfg(feature = "range_check")]
                             RangeCheck0 => &index.range_check_comm.as_ref().unwrap()[0],
+                            #[cfg(feature = "range_check")]
                             RangeCheck1 => &index.range_check_comm.as_ref().unwrap()[1],
+                            #[cfg(feature = "foreign_field")]
                             ForeignFieldAdd => index.foreign_field_add_comm.as_ref().unwrap(),
+                            CopyCells => index.copy_cells_comm.as_ref().unwrap(),
+                            #[cfg(feature = "keccak")]
+                            Xor16 => &index.keccak_comm.as_ref().unwrap()[0],
+                            #[cfg(feature = "keccak")]
+                            Rot64 => &index.keccak_comm.as_ref().unwrap()[1],
                         };
                         scalars.push(scalar);
                         commitments.push(c);
@@ -680,9 +1196,12 @@ where
 
     //~ 1. List the polynomial commitments, and their associated evaluations,
     //~    that are associated to the aggregated evaluation proof in the proof:
+    // (along with the names of the polynomials, for diagnostics)
     let mut evaluations = vec![];
+    let mut polynomials = vec![];
 
     //~~ - recursion
+    polynomials.extend((0..polys.len()).map(OpenedPolynomial::PrevChallenges));
     evaluations.extend(polys.into_iter().map(|(c, e)| Evaluation {
         commitment: c,
         evaluations: e,
@@ -690,6 +1209,7 @@ where
     }));
 
     //~~ - public input commitment
+    polynomials.push(OpenedPolynomial::Public);
     evaluations.push(Evaluation {
         commitment: public_comm,
         evaluations: public_evals.to_vec(),
@@ -697,6 +1217,7 @@ where
     });
 
     //~~ - ft commitment (chunks of it)
+    polynomials.push(OpenedPolynomial::Ft);
     evaluations.push(Evaluation {
         commitment: ft_comm,
         evaluations: vec![vec![ft_eval0], vec![proof.ft_eval1]],
@@ -704,6 +1225,7 @@ where
     });
 
     //~~ - permutation commitment
+    polynomials.push(OpenedPolynomial::Column(Column::Z));
     evaluations.push(Evaluation {
         commitment: proof.commitments.z_comm.clone(),
         evaluations: proof.evals.iter().map(|e| e.z.clone()).collect(),
@@ -711,6 +1233,10 @@ where
     });
 
     //~~ - index commitments that use the coefficients
+    polynomials.extend(
+        [GateType::Generic, GateType::Poseidon]
+            .map(|typ| OpenedPolynomial::Column(Column::Index(typ))),
+    );
     evaluations.push(Evaluation {
         commitment: index.generic_comm.clone(),
         evaluations: proof
@@ -730,14 +1256,23 @@ where
         degree_bound: None,
     });
 
-    //~~ - witness commitments
+    //~~ - witness commitments, but the ones of the columns declared zero
+    polynomials.extend(
+        (0..COLUMNS)
+            .filter(|i| !index.zero_columns[*i])
+            .map(|i| OpenedPolynomial::Column(Column::Witness(i))),
+    );
     evaluations.extend(
         proof
             .commitments
             .w_comm
             .iter()
+            .zip(index.zero_columns)
+            .filter(|(_, zero)| !zero)
+            .map(|(c, _)| c)
             .zip(
                 (0..COLUMNS)
+                    .filter(|i| !index.zero_columns[*i])
                     .map(|i| {
                         proof
                             .evals
@@ -755,12 +1290,13 @@ where
     );
 
     //~~ - sigma commitments
+    polynomials.extend((0..index.sigma_comm.len() - 1).map(OpenedPolynomial::Sigma));
     evaluations.extend(
         index
             .sigma_comm
             .iter()
             .zip(
-                (0..PERMUTS - 1)
+                (0..index.sigma_comm.len() - 1)
                     .map(|i| {
                         proof
                             .evals
@@ -800,11 +1336,14 @@ where
         }
 
         // add evaluations of sorted polynomials
-        for (comm, evals0, evals1) in izip!(
+        for (i, (comm, evals0, evals1)) in izip!(
             &lookup_comms.sorted,
             lookup_eval0.sorted.clone(),
             lookup_eval1.sorted.clone()
-        ) {
+        )
+        .enumerate()
+        {
+            polynomials.push(OpenedPolynomial::Column(Column::LookupSorted(i)));
             evaluations.push(Evaluation {
                 commitment: comm.clone(),
                 evaluations: vec![evals0, evals1],
@@ -813,6 +1352,7 @@ where
         }
 
         // add evaluations of the aggreg polynomial
+        polynomials.push(OpenedPolynomial::Column(Column::LookupAggreg));
         evaluations.push(Evaluation {
             commitment: lookup_comms.aggreg.clone(),
             evaluations: vec![lookup_eval0.aggreg.clone(), lookup_eval1.aggreg.clone()],
@@ -828,16 +1368,27 @@ where
             let lookup_table: Vec<_> = li.lookup_table.iter().collect();
             let runtime = lookup_comms.runtime.as_ref();
 
-            combine_table(
+            let table_comm = combine_table(
                 &lookup_table,
                 joint_combiner.1,
                 table_id_combiner,
                 li.table_ids.as_ref(),
                 runtime,
-            )
+            );
+
+            // the unused entries of the runtime tables of variable length are masked
+            if unused_entries.is_empty() {
+                table_comm
+            } else {
+                let runs = UnusedEntry::mask_runs(&unused_entries, |entry| {
+                    entry.fixed_part(table_id_combiner)
+                });
+                &table_comm - &index.srs().commit_runs_non_hiding(index.domain, &runs)
+            }
         };
 
         // add evaluation of the table polynomial
+        polynomials.push(OpenedPolynomial::Column(Column::LookupTable));
         evaluations.push(Evaluation {
             commitment: table_comm,
             evaluations: vec![lookup_eval0.table.clone(), lookup_eval1.table.clone()],
@@ -861,6 +1412,7 @@ where
                 .cloned()
                 .ok_or(VerifyError::IncorrectRuntimeProof)?;
 
+            polynomials.push(OpenedPolynomial::Column(Column::LookupRuntimeTable));
             evaluations.push(Evaluation {
                 commitment: runtime.clone(),
                 evaluations: vec![runtime_eval0, runtime_eval1],
@@ -871,14 +1423,50 @@ where
 
     // prepare for the opening proof verification
     let evaluation_points = vec![oracles.zeta, oracles.zeta * index.domain.group_gen];
-    Ok(BatchEvaluationProof {
+    let batch = BatchEvaluationProof {
         sponge: fq_sponge,
         evaluations,
         evaluation_points,
         polyscale: oracles.v,
         evalscale: oracles.u,
         opening: &proof.proof,
-    })
+    };
+    Ok((batch, polynomials))
+}
+
+/// Names the polynomial and the point of a malformed evaluation reported by the diagnostics
+/// of the opening proofs, given the names of the polynomials opened by each proof of the batch.
+fn diagnose(polynomials: &[Vec<OpenedPolynomial>], err: OpeningError) -> VerifyError {
+    match err {
+        OpeningError::EvaluationPoints {
+            proof,
+            evaluation,
+            rows,
+            ..
+        } => VerifyError::EvaluationPoints {
+            proof,
+            polynomial: polynomials[proof][evaluation],
+            rows,
+        },
+        OpeningError::EvaluationChunks {
+            proof,
+            evaluation,
+            point,
+            chunks,
+            expected,
+        } => VerifyError::EvaluationChunks {
+            proof,
+            polynomial: polynomials[proof][evaluation],
+            point: if point == 0 {
+                EvaluationPoint::Zeta
+            } else {
+                EvaluationPoint::ZetaOmega
+            },
+            chunks,
+            expected,
+        },
+        err => VerifyError::OpenProofDiagnostics(err),
+    }
 }
 
 /// Verify a proof [`ProverProof`] using a [`VerifierIndex`] and a `group_map`.
@@ -886,19 +1474,43 @@ where
 /// # Errors
 ///
 /// Will give error if `proof(s)` are not verified as valid.
-pub fn verify<G, EFqSponge, EFrSponge>(
+pub fn verify<G, EFqSponge, EFrSponge, S>(
     group_map: &G::Map,
-    verifier_index: &VerifierIndex<G>,
-    proof: &ProverProof<G>,
+    verifier_index: &VerifierIndex<G, S>,
+    proof: &ProverProof<G, S>,
 ) -> Result<()>
 where
     G: KimchiCurve,
     G::BaseField: PrimeField,
+    S: PolynomialCommitment<G>,
     EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
     EFrSponge: FrSponge<G::ScalarField>,
 {
     let proofs = vec![(verifier_index, proof)];
-    batch_verify::<G, EFqSponge, EFrSponge>(group_map, &proofs)
+    batch_verify::<G, EFqSponge, EFrSponge, S>(group_map, &proofs)
+}
+
+/// Verify a proof [`ProverProof`] bound to the application `context`
+/// (see [`ProverProof::create_with_context`]) using a [`VerifierIndex`] and a `group_map`.
+///
+/// # Errors
+///
+/// Will give error if `proof(s)` are not verified as valid, including if they were created in another context.
+pub fn verify_with_context<G, EFqSponge, EFrSponge, S>(
+    group_map: &G::Map,
+    verifier_index: &VerifierIndex<G, S>,
+    proof: &ProverProof<G, S>,
+    context: &[u8],
+) -> Result<()>
+where
+    G: KimchiCurve,
+    G::BaseField: PrimeField,
+    S: PolynomialCommitment<G>,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    EFrSponge: FrSponge<G::ScalarField>,
+{
+    let proofs = vec![(verifier_index, proof, context)];
+    batch_verify_with_context::<G, EFqSponge, EFrSponge, S>(group_map, &proofs)
 }
 
 /// This function verifies the batch of zk-proofs
@@ -909,13 +1521,241 @@ where
 /// # Errors
 ///
 /// Will give error if `srs` of `proof` is invalid or `verify` process fails.
-pub fn batch_verify<G, EFqSponge, EFrSponge>(
+pub fn batch_verify<G, EFqSponge, EFrSponge, S>(
     group_map: &G::Map,
-    proofs: &[(&VerifierIndex<G>, &ProverProof<G>)],
+    proofs: &[(&VerifierIndex<G, S>, &ProverProof<G, S>)],
 ) -> Result<()>
 where
     G: KimchiCurve,
     G::BaseField: PrimeField,
+    S: PolynomialCommitment<G>,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    EFrSponge: FrSponge<G::ScalarField>,
+{
+    batch_verify_inner::<G, EFqSponge, EFrSponge, S>(
+        group_map,
+        &without_context(proofs),
+        false,
+        false,
+    )
+}
+
+/// This function verifies the batch of zk-proofs like [`batch_verify`],
+/// each proof being bound to its application context (see [`ProverProof::create_with_context`]).
+///
+/// # Errors
+///
+/// Will give error if `srs` of `proof` is invalid or `verify` process fails.
+pub fn batch_verify_with_context<G, EFqSponge, EFrSponge, S>(
+    group_map: &G::Map,
+    proofs: &[(&VerifierIndex<G, S>, &ProverProof<G, S>, &[u8])],
+) -> Result<()>
+where
+    G: KimchiCurve,
+    G::BaseField: PrimeField,
+    S: PolynomialCommitment<G>,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    EFrSponge: FrSponge<G::ScalarField>,
+{
+    batch_verify_inner::<G, EFqSponge, EFrSponge, S>(group_map, proofs, false, false)
+}
+
+/// This function verifies the batch of zk-proofs like [`batch_verify`],
+/// but (more slowly) reports which proof fails the opening proof verification,
+/// or which of its evaluations is malformed, naming the polynomial and the point of the evaluation.
+/// As the opening proof only checks a random combination of the evaluations of a proof,
+/// a wrong (but well-formed) evaluation can only be attributed to its proof.
+///
+/// # Errors
+///
+/// Will give error if `srs` of `proof` is invalid or `verify` process fails,
+/// with [`VerifyError::EvaluationPoints`] or [`VerifyError::EvaluationChunks`] on a malformed evaluation,
+/// and [`VerifyError::OpenProofDiagnostics`] instead of [`VerifyError::OpenProof`].
+pub fn batch_verify_with_diagnostics<G, EFqSponge, EFrSponge, S>(
+    group_map: &G::Map,
+    proofs: &[(&VerifierIndex<G, S>, &ProverProof<G, S>)],
+) -> Result<()>
+where
+    G: KimchiCurve,
+    G::BaseField: PrimeField,
+    S: PolynomialCommitment<G>,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    EFrSponge: FrSponge<G::ScalarField>,
+{
+    batch_verify_inner::<G, EFqSponge, EFrSponge, S>(
+        group_map,
+        &without_context(proofs),
+        true,
+        false,
+    )
+}
+
+/// This function verifies the batch of zk-proofs like [`batch_verify`],
+/// but first checks the shape of each proof with [`ProverProof::validate`],
+/// so that malformed proofs are rejected with precise errors before any transcript work.
+///
+/// # Errors
+///
+/// Will give error if a proof is malformed, if `srs` of `proof` is invalid or `verify` process fails.
+pub fn batch_verify_strict<G, EFqSponge, EFrSponge, S>(
+    group_map: &G::Map,
+    proofs: &[(&VerifierIndex<G, S>, &ProverProof<G, S>)],
+) -> Result<()>
+where
+    G: KimchiCurve,
+    G::BaseField: PrimeField,
+    S: PolynomialCommitment<G>,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    EFrSponge: FrSponge<G::ScalarField>,
+{
+    batch_verify_inner::<G, EFqSponge, EFrSponge, S>(
+        group_map,
+        &without_context(proofs),
+        false,
+        true,
+    )
+}
+
+/// Decodes a proof from its MessagePack encoding (as given by `rmp_serde::to_vec`),
+/// only accepting the canonical encoding of the proof:
+/// the bytes must hold a single proof without trailing data,
+/// and be exactly the encoding of the decoded proof.
+///
+/// Decoding already rejects field elements that are not reduced and points that are not on the curve.
+/// Comparing the bytes with the encoding of the decoded proof rejects the remaining freedom:
+/// bytes trailing the encoding of a field element or a point,
+/// unused bits (e.g. the coordinates of the point at infinity),
+/// and the alternative MessagePack encodings of integers, lengths and structures.
+///
+/// # Errors
+///
+/// Will give error if `bytes` do not encode a proof, or not canonically,
+/// with [`VerifyError::LengthLimitExceeded`] if a part of the proof is longer than its limit
+/// (see [`crate::proof::MAX_PUBLIC`] for instance).
+pub fn decode_canonical_proof<G, S>(bytes: &[u8]) -> Result<ProverProof<G, S>>
+where
+    G: KimchiCurve,
+    S: PolynomialCommitment<G>,
+    ProverProof<G, S>: Serialize + DeserializeOwned,
+{
+    let mut reader = bytes;
+    let proof = ProverProof::<G, S>::deserialize(&mut rmp_serde::Deserializer::new(&mut reader))
+        .map_err(|err| match LengthLimitExceeded::find(&err) {
+            Some(err) => VerifyError::LengthLimitExceeded(err),
+            None => VerifyError::NonCanonicalProof("malformed encoding"),
+        })?;
+    if !reader.is_empty() {
+        return Err(VerifyError::NonCanonicalProof("trailing data"));
+    }
+    let encoding = rmp_serde::to_vec(&proof)
+        .map_err(|_| VerifyError::NonCanonicalProof("malformed encoding"))?;
+    if encoding != bytes {
+        return Err(VerifyError::NonCanonicalProof("non-canonical encoding"));
+    }
+    Ok(proof)
+}
+
+/// Verifies the proof encoded by `bytes` like [`verify`], in an audit mode
+/// for systems that use the bytes of proofs as unique identifiers:
+/// the proof must also be canonically encoded (see [`decode_canonical_proof`])
+/// and of the expected shape (see [`ProverProof::validate`]),
+/// so that a valid proof has a single accepted encoding.
+/// Returns the decoded proof.
+///
+/// # Errors
+///
+/// Will give error if the proof is not canonically encoded, is malformed, or is not verified as valid.
+pub fn verify_canonical<G, EFqSponge, EFrSponge, S>(
+    group_map: &G::Map,
+    verifier_index: &VerifierIndex<G, S>,
+    bytes: &[u8],
+) -> Result<ProverProof<G, S>>
+where
+    G: KimchiCurve,
+    G::BaseField: PrimeField,
+    S: PolynomialCommitment<G>,
+    ProverProof<G, S>: Serialize + DeserializeOwned,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    EFrSponge: FrSponge<G::ScalarField>,
+{
+    let proof = decode_canonical_proof::<G, S>(bytes)?;
+    batch_verify_strict::<G, EFqSponge, EFrSponge, S>(group_map, &[(verifier_index, &proof)])?;
+    Ok(proof)
+}
+
+/// This function verifies two batches of zk-proofs, one on each curve of a cycle,
+/// as carried by recursive systems.
+/// The two batches are verified concurrently on the same rayon thread pool,
+/// so that the parallel work of each batch fills the idle threads of the other.
+///
+/// # Errors
+///
+/// Will give error if either batch fails [`batch_verify`], reporting the outcome of both batches.
+#[allow(clippy::type_complexity)]
+pub fn batch_verify_cycle<G, EFqSponge, EFrSponge, EFqSpongeOther, EFrSpongeOther, S, SOther>(
+    group_maps: (&G::Map, &<G::OtherCurve as CommitmentCurve>::Map),
+    proofs: &[(&VerifierIndex<G, S>, &ProverProof<G, S>)],
+    other_proofs: &[(
+        &VerifierIndex<G::OtherCurve, SOther>,
+        &ProverProof<G::OtherCurve, SOther>,
+    )],
+) -> std::result::Result<(), CycleVerifyError>
+where
+    G: KimchiCurve,
+    G::BaseField: PrimeField,
+    G::Map: Sync,
+    <G::OtherCurve as CommitmentCurve>::Map: Sync,
+    S: PolynomialCommitment<G>,
+    SOther: PolynomialCommitment<G::OtherCurve>,
+    VerifierIndex<G, S>: Sync,
+    VerifierIndex<G::OtherCurve, SOther>: Sync,
+    ProverProof<G, S>: Sync,
+    ProverProof<G::OtherCurve, SOther>: Sync,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    EFrSponge: FrSponge<G::ScalarField>,
+    EFqSpongeOther: Clone + FqSponge<G::ScalarField, G::OtherCurve, G::BaseField>,
+    EFrSpongeOther: FrSponge<G::BaseField>,
+{
+    let (group_map, other_group_map) = group_maps;
+    let (first, other) = rayon::join(
+        || batch_verify::<G, EFqSponge, EFrSponge, S>(group_map, proofs),
+        || {
+            batch_verify::<G::OtherCurve, EFqSpongeOther, EFrSpongeOther, SOther>(
+                other_group_map,
+                other_proofs,
+            )
+        },
+    );
+
+    match (first, other) {
+        (Ok(()), Ok(())) => Ok(()),
+        (first, other) => Err(CycleVerifyError {
+            first: first.err(),
+            other: other.err(),
+        }),
+    }
+}
+
+/// Returns the proofs of a batch, bound to no application context.
+fn without_context<'a, G: KimchiCurve, S: PolynomialCommitment<G>>(
+    proofs: &[(&'a VerifierIndex<G, S>, &'a ProverProof<G, S>)],
+) -> Vec<(&'a VerifierIndex<G, S>, &'a ProverProof<G, S>, &'a [u8])> {
+    proofs
+        .iter()
+        .map(|(index, proof)| (*index, *proof, &[][..]))
+        .collect()
+}
+
+fn batch_verify_inner<G, EFqSponge, EFrSponge, S>(
+    group_map: &G::Map,
+    proofs: &[(&VerifierIndex<G, S>, &ProverProof<G, S>, &[u8])],
+    diagnostics: bool,
+    strict: bool,
+) -> Result<()>
+where
+    G: KimchiCurve,
+    G::BaseField: PrimeField,
+    S: PolynomialCommitment<G>,
     EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
     EFrSponge: FrSponge<G::ScalarField>,
 {
@@ -934,25 +1774,48 @@ where
     //~ 1. Ensure that all the proof's verifier index have a URS of the same length. (TODO: do they have to be the same URS though? should we check for that?)
     // TODO: Account for the different SRS lengths
     let srs = &proofs[0].0.srs();
-    for (index, _) in proofs.iter() {
-        if index.srs().g.len() != srs.g.len() {
+    for (index, _, _) in proofs.iter() {
+        if index.srs().max_poly_size() != srs.max_poly_size() {
             return Err(VerifyError::DifferentSRS);
         }
 
-        // also make sure that the SRS is not smaller than the domain size
-        if index.srs().max_degree() < index.domain.size() {
+        // also make sure that the SRS is not smaller than the domain size,
+        // when the public input is committed with its Lagrange bases
+        // (the other polynomials are committed in chunks)
+        if index.public > 0 && index.srs().max_poly_size() < index.domain.size() {
             return Err(VerifyError::SRSTooSmall);
         }
     }
 
+    // in strict mode, check the shape of all the proofs first
+    if strict {
+        for (index, proof, _) in proofs {
+            proof.validate(index)?;
+        }
+    }
+
+    //~ 1. Commit to the negated public input polynomials of all the proofs.
+    let publics: Vec<_> = proofs
+        .iter()
+        .map(|(index, proof, _)| (*index, &proof.public[..]))
+        .collect();
+    let public_comms = public_commitments(&publics)?;
+
     //~ 1. Validate each proof separately following the [partial verification](#partial-verification) steps.
     let mut batch = vec![];
-    for (index, proof) in proofs {
-        batch.push(to_batch::<G, EFqSponge, EFrSponge>(index, proof)?);
+    let mut polynomials = vec![];
+    for ((index, proof, context), public_comm) in proofs.iter().zip(public_comms) {
+        let (evaluations, opened) =
+            to_batch::<G, EFqSponge, EFrSponge, S>(index, proof, public_comm, context)?;
+        batch.push(evaluations);
+        polynomials.push(opened);
     }
 
     //~ 1. Use the [`PolyCom.verify`](#polynomial-commitments) to verify the partially evaluated proofs.
-    if srs.verify::<EFqSponge, _>(group_map, &mut batch, &mut thread_rng()) {
+    if diagnostics {
+        srs.verify_with_diagnostics::<EFqSponge, _>(group_map, &mut batch, &mut thread_rng())
+            .map_err(|err| diagnose(&polynomials, err))
+    } else if srs.batch_verify::<EFqSponge, _>(group_map, &mut batch, &mut thread_rng()) {
         Ok(())
     } else {
         Err(VerifyError::OpenProof)
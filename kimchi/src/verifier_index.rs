@@ -1,34 +1,50 @@
 //! This module implements the verifier index as [`VerifierIndex`].
 //! You can derive this struct from the [`ProverIndex`] struct.
 
+#[cfg(feature = "foreign_field")]
+use crate::circuits::polynomials::foreign_field_add;
+#[cfg(feature = "keccak")]
+use crate::circuits::polynomials::keccak;
+#[cfg(feature = "range_check")]
+use crate::circuits::polynomials::range_check;
 use crate::{
     alphas::Alphas,
     circuits::{
         expr::{Linearization, PolishToken},
-        lookup::{index::LookupSelectors, lookups::LookupsUsed},
-        polynomials::{
-            permutation::{zk_polynomial, zk_w3},
-            range_check,
+        gate::GateType,
+        lookup::{
+            constraints::LookupConfiguration,
+            index::{LookupSelectors, LookupTableSpec},
+            lookups::{LookupPattern, LookupsUsed},
+            runtime_tables::VariableRuntimeTable,
         },
+        polynomials::permutation::{check_shifts, zk_polynomial, zk_w3, Shifts},
         wires::{COLUMNS, PERMUTS},
     },
     curve::KimchiCurve,
     error::VerifierIndexError,
+    linearization::expr_linearization,
     prover_index::ProverIndex,
 };
-use ark_ff::{One, PrimeField};
-use ark_poly::{univariate::DensePolynomial, Radix2EvaluationDomain as D};
+use ark_ff::{One, PrimeField, Zero};
+use ark_poly::{
+    univariate::DensePolynomial, EvaluationDomain, Evaluations, Radix2EvaluationDomain as D,
+};
+use blake2::{Blake2b512, Digest};
 use commitment_dlog::{
     commitment::{CommitmentCurve, PolyComm},
+    scheme::PolynomialCommitment,
     srs::SRS,
 };
 use num_bigint::BigUint;
+use o1_utils::field_helpers::i64_to_field;
 use once_cell::sync::OnceCell;
 use oracle::FqSponge;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_with::serde_as;
 use std::array;
 use std::{
+    collections::HashMap,
     fs::{File, OpenOptions},
     io::{BufReader, BufWriter, Seek, SeekFrom::Start},
     path::Path,
@@ -56,11 +72,16 @@ pub struct LookupVerifierIndex<G: CommitmentCurve> {
     /// An optional selector polynomial for runtime tables
     #[serde(bound = "PolyComm<G>: Serialize + DeserializeOwned")]
     pub runtime_tables_selector: Option<PolyComm<G>>,
+
+    /// The runtime tables of variable length, whose unused entries are masked
+    #[serde(default)]
+    pub variable_runtime_tables: Vec<VariableRuntimeTable>,
 }
 
+/// The polynomial commitment scheme of the index is `S` (by default, the inner product argument of the [`SRS`]).
 #[serde_as]
 #[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct VerifierIndex<G: KimchiCurve> {
+pub struct VerifierIndex<G: KimchiCurve, S = SRS<G>> {
     /// evaluation domain
     #[serde_as(as = "o1_utils::serialization::SerdeAs")]
     pub domain: D<G::ScalarField>,
@@ -70,22 +91,29 @@ pub struct VerifierIndex<G: KimchiCurve> {
     pub max_quot_size: usize,
     /// polynomial commitment keys
     #[serde(skip)]
-    pub srs: OnceCell<Arc<SRS<G>>>,
+    pub srs: OnceCell<Arc<S>>,
     /// number of public inputs
     pub public: usize,
     /// number of previous evaluation challenges, for recursive proving
     pub prev_challenges: usize,
+    /// witness columns declared identically zero,
+    /// which are neither committed to nor evaluated in proofs
+    pub zero_columns: [bool; COLUMNS],
 
     // index polynomial commitments
-    /// permutation commitment array
+    /// permutation commitment array, one for each column taking part in the permutation
     #[serde(bound = "PolyComm<G>: Serialize + DeserializeOwned")]
-    pub sigma_comm: [PolyComm<G>; PERMUTS],
-    /// coefficient commitment array
+    pub sigma_comm: Vec<PolyComm<G>>,
+    /// coefficient commitment array, `None` for the columns without coefficients
+    /// (see [`zero_comm`])
     #[serde(bound = "PolyComm<G>: Serialize + DeserializeOwned")]
-    pub coefficients_comm: [PolyComm<G>; COLUMNS],
+    pub coefficients_comm: [Option<PolyComm<G>>; COLUMNS],
     /// coefficient commitment array
     #[serde(bound = "PolyComm<G>: Serialize + DeserializeOwned")]
     pub generic_comm: PolyComm<G>,
+    /// whether the circuit uses the triple generic gate,
+    /// whose third generic gate adds a constraint to the rows of all the generic gates
+    pub triple_generic: bool,
 
     // poseidon polynomial commitments
     /// poseidon constraint selector polynomial commitment
@@ -93,33 +121,53 @@ pub struct VerifierIndex<G: KimchiCurve> {
     pub psm_comm: PolyComm<G>,
 
     // ECC arithmetic polynomial commitments
-    /// EC addition selector polynomial commitment
+    /// EC addition selector polynomial commitment,
+    /// `None` if the circuit does not use the gate
     #[serde(bound = "PolyComm<G>: Serialize + DeserializeOwned")]
-    pub complete_add_comm: PolyComm<G>,
-    /// EC variable base scalar multiplication selector polynomial commitment
+    pub complete_add_comm: Option<PolyComm<G>>,
+    /// EC variable base scalar multiplication selector polynomial commitment,
+    /// `None` if the circuit does not use the gate
     #[serde(bound = "PolyComm<G>: Serialize + DeserializeOwned")]
-    pub mul_comm: PolyComm<G>,
-    /// endoscalar multiplication selector polynomial commitment
+    pub mul_comm: Option<PolyComm<G>>,
+    /// endoscalar multiplication selector polynomial commitment,
+    /// `None` if the circuit does not use the gate
     #[serde(bound = "PolyComm<G>: Serialize + DeserializeOwned")]
-    pub emul_comm: PolyComm<G>,
-    /// endoscalar multiplication scalar computation selector polynomial commitment
+    pub emul_comm: Option<PolyComm<G>>,
+    /// endoscalar multiplication scalar computation selector polynomial commitment,
+    /// `None` if the circuit does not use the gate
     #[serde(bound = "PolyComm<G>: Serialize + DeserializeOwned")]
-    pub endomul_scalar_comm: PolyComm<G>,
+    pub endomul_scalar_comm: Option<PolyComm<G>>,
 
     /// Chacha polynomial commitments
+    #[cfg(feature = "chacha")]
     #[serde(bound = "PolyComm<G>: Serialize + DeserializeOwned")]
     pub chacha_comm: Option<[PolyComm<G>; 4]>,
 
+    #[cfg(feature = "range_check")]
     #[serde(bound = "PolyComm<G>: Serialize + DeserializeOwned")]
     pub range_check_comm: Option<[PolyComm<G>; range_check::gadget::GATE_COUNT]>,
 
     // Foreign field modulus
     pub foreign_field_modulus: Option<BigUint>,
 
+    /// named constants of the application, absorbed in the digest of the index
+    #[serde_as(as = "Vec<(_, o1_utils::serialization::SerdeAs)>")]
+    pub app_constants: Vec<(String, G::ScalarField)>,
+
     // Foreign field addition gates polynomial commitments
+    #[cfg(feature = "foreign_field")]
     #[serde(bound = "Option<PolyComm<G>>: Serialize + DeserializeOwned")]
     pub foreign_field_add_comm: Option<PolyComm<G>>,
 
+    /// copy gate selector polynomial commitment, `None` if the circuit does not use the gate
+    #[serde(bound = "Option<PolyComm<G>>: Serialize + DeserializeOwned")]
+    pub copy_cells_comm: Option<PolyComm<G>>,
+
+    /// Keccak gates selector polynomial commitments
+    #[cfg(feature = "keccak")]
+    #[serde(bound = "PolyComm<G>: Serialize + DeserializeOwned")]
+    pub keccak_comm: Option<[PolyComm<G>; keccak::gadget::GATE_COUNT]>,
+
     /// wire coordinate shifts
     #[serde_as(as = "[o1_utils::serialization::SerdeAs; PERMUTS]")]
     pub shift: [G::ScalarField; PERMUTS],
@@ -130,6 +178,9 @@ pub struct VerifierIndex<G: KimchiCurve> {
     /// domain offset for zero-knowledge
     #[serde(skip)]
     pub w: OnceCell<G::ScalarField>,
+    /// the elements of the domain of the public input rows
+    #[serde(skip)]
+    pub public_elements: OnceCell<Vec<G::ScalarField>>,
     /// endoscalar coefficient
     #[serde(skip)]
     pub endo: G::ScalarField,
@@ -145,13 +196,13 @@ pub struct VerifierIndex<G: KimchiCurve> {
 }
 //~spec:endcode
 
-impl<G: KimchiCurve> ProverIndex<G> {
+impl<G: KimchiCurve, S: PolynomialCommitment<G>> ProverIndex<G, S> {
     /// Produces the [`VerifierIndex`] from the prover's [`ProverIndex`].
     ///
     /// # Panics
     ///
     /// Will panic if `srs` cannot be in `cell`.
-    pub fn verifier_index(&self) -> VerifierIndex<G> {
+    pub fn verifier_index(&self) -> VerifierIndex<G, S> {
         if let Some(verifier_index) = &self.verifier_index {
             return verifier_index.clone();
         }
@@ -190,6 +241,7 @@ impl<G: KimchiCurve> ProverIndex<G> {
                         .runtime_selector
                         .as_ref()
                         .map(|e| self.srs.commit_evaluations_non_hiding(domain, e, None)),
+                    variable_runtime_tables: cs.variable_runtime_tables(),
                 })
         };
 
@@ -201,43 +253,58 @@ impl<G: KimchiCurve> ProverIndex<G> {
             powers_of_alpha: self.powers_of_alpha.clone(),
             public: self.cs.public,
             prev_challenges: self.cs.prev_challenges,
+            zero_columns: self.cs.zero_columns,
             srs: {
                 let cell = OnceCell::new();
                 cell.set(Arc::clone(&self.srs)).unwrap();
                 cell
             },
 
-            sigma_comm: array::from_fn(|i| self.srs.commit_non_hiding(&self.cs.sigmam[i], None)),
+            sigma_comm: self
+                .cs
+                .sigmal1
+                .iter()
+                .map(|sigma| self.srs.commit_evaluations_non_hiding(domain, sigma, None))
+                .collect(),
             coefficients_comm: array::from_fn(|i| {
-                self.srs
-                    .commit_evaluations_non_hiding(domain, &self.cs.coefficients8[i], None)
+                non_zero(
+                    self.srs
+                        .commit_runs_non_hiding(domain, self.cs.coefficients[i].runs()),
+                )
             }),
             generic_comm: mask_fixed(self.srs.commit_non_hiding(&self.cs.genericm, None)),
+            triple_generic: self.cs.triple_generic,
 
             psm_comm: mask_fixed(self.srs.commit_non_hiding(&self.cs.psm, None)),
 
-            complete_add_comm: self.srs.commit_evaluations_non_hiding(
+            complete_add_comm: non_zero(self.srs.commit_evaluations_non_hiding(
                 domain,
                 &self.cs.complete_addl4,
                 None,
-            ),
-            mul_comm: self
-                .srs
-                .commit_evaluations_non_hiding(domain, &self.cs.mull8, None),
-            emul_comm: self
-                .srs
-                .commit_evaluations_non_hiding(domain, &self.cs.emull, None),
+            )),
+            mul_comm: non_zero(self.srs.commit_evaluations_non_hiding(
+                domain,
+                &self.cs.mull8,
+                None,
+            )),
+            emul_comm: non_zero(self.srs.commit_evaluations_non_hiding(
+                domain,
+                &self.cs.emull,
+                None,
+            )),
 
-            endomul_scalar_comm: self.srs.commit_evaluations_non_hiding(
+            endomul_scalar_comm: non_zero(self.srs.commit_evaluations_non_hiding(
                 domain,
                 &self.cs.endomul_scalar8,
                 None,
-            ),
+            )),
 
+            #[cfg(feature = "chacha")]
             chacha_comm: self.cs.chacha8.as_ref().map(|c| {
                 array::from_fn(|i| self.srs.commit_evaluations_non_hiding(domain, &c[i], None))
             }),
 
+            #[cfg(feature = "range_check")]
             range_check_comm: self.cs.range_check_selector_polys.as_ref().map(|poly| {
                 array::from_fn(|i| {
                     self.srs
@@ -245,6 +312,7 @@ impl<G: KimchiCurve> ProverIndex<G> {
                 })
             }),
 
+            #[cfg(feature = "foreign_field")]
             foreign_field_add_comm: self
                 .cs
                 .foreign_field_add_selector_poly
@@ -254,6 +322,19 @@ impl<G: KimchiCurve> ProverIndex<G> {
                         .commit_evaluations_non_hiding(domain, &poly.eval8, None)
                 }),
 
+            copy_cells_comm: self.cs.copy_cells_selector_poly.as_ref().map(|poly| {
+                self.srs
+                    .commit_evaluations_non_hiding(domain, &poly.eval8, None)
+            }),
+
+            #[cfg(feature = "keccak")]
+            keccak_comm: self.cs.keccak_selector_polys.as_ref().map(|poly| {
+                array::from_fn(|i| {
+                    self.srs
+                        .commit_evaluations_non_hiding(domain, &poly[i].eval8, None)
+                })
+            }),
+
             shift: self.cs.shift,
             zkpm: {
                 let cell = OnceCell::new();
@@ -265,49 +346,121 @@ impl<G: KimchiCurve> ProverIndex<G> {
                 cell.set(zk_w3(self.cs.domain.d1)).unwrap();
                 cell
             },
+            public_elements: OnceCell::new(),
             endo: self.cs.endo,
             lookup_index,
             linearization: self.linearization.clone(),
             foreign_field_modulus: self.cs.foreign_field_modulus.clone(),
+            app_constants: self.cs.app_constants.clone(),
         }
     }
 }
 
-impl<G: KimchiCurve> VerifierIndex<G> {
+impl<G: KimchiCurve, S: PolynomialCommitment<G>> VerifierIndex<G, S> {
     /// Gets srs from [`VerifierIndex`] lazily
-    pub fn srs(&self) -> &Arc<SRS<G>>
+    pub fn srs(&self) -> &Arc<S>
     where
         G::BaseField: PrimeField,
     {
         self.srs.get_or_init(|| {
-            let mut srs = SRS::<G>::create(self.max_poly_size);
-            srs.add_lagrange_basis(self.domain);
+            let mut srs = S::create(self.max_poly_size);
+            // the Lagrange bases span a single chunk, and are only needed when the domain fits in it
+            if self.domain.size() <= self.max_poly_size {
+                srs.add_lagrange_basis(self.domain);
+            }
             Arc::new(srs)
         })
     }
 
-    /// Gets zkpm from [`VerifierIndex`] lazily
+    /// Checks that the commitments to the concatenated lookup table of the index,
+    /// and to its table IDs, are the ones of the tables of `spec`,
+    /// so that auditors can confirm what data the circuit looks up.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the index does not use lookups, if `spec` is not valid,
+    /// or if the commitments do not match the tables of `spec`.
+    pub fn verify_table_commitments(
+        &self,
+        spec: &LookupTableSpec<G::ScalarField>,
+    ) -> Result<(), VerifierIndexError>
+    where
+        G::BaseField: PrimeField,
+    {
+        let lookup_index = self
+            .lookup_index
+            .as_ref()
+            .ok_or(VerifierIndexError::TableMismatch("lookup tables"))?;
+        let (columns, table_ids) = spec
+            .concatenate(self.domain.size())
+            .map_err(|e| VerifierIndexError::InvalidTableSpec(e.to_string()))?;
+
+        let srs = self.srs();
+        let matches = |mut evals: Vec<G::ScalarField>, comm: &PolyComm<G>| {
+            evals.resize(self.domain.size(), G::ScalarField::zero());
+            let evals = Evaluations::from_vec_and_domain(evals, self.domain);
+            let expected = srs.commit_evaluations_non_hiding(self.domain, &evals, None);
+            expected.unshifted == comm.unshifted && expected.shifted == comm.shifted
+        };
+
+        if columns.len() != lookup_index.lookup_table.len()
+            || !columns
+                .into_iter()
+                .zip(&lookup_index.lookup_table)
+                .all(|(column, comm)| matches(column, comm))
+        {
+            return Err(VerifierIndexError::TableMismatch("lookup table"));
+        }
+        let table_ids_match = match (table_ids, &lookup_index.table_ids) {
+            (Some(table_ids), Some(comm)) => matches(table_ids, comm),
+            (None, None) => true,
+            _ => false,
+        };
+        if !table_ids_match {
+            return Err(VerifierIndexError::TableMismatch("table IDs"));
+        }
+        Ok(())
+    }
+
+    /// Returns the number of chunks of the commitments to the polynomials over the domain,
+    /// each chunk committing to `max_poly_size` of their evaluations.
+    pub fn chunks(&self) -> usize {
+        (self.domain.size() + self.max_poly_size - 1) / self.max_poly_size
+    }
+
+    /// Gets zkpm from [`VerifierIndex`] lazily,
+    /// the polynomial evaluated by [`verifier_math::zkpm`](crate::verifier_math::zkpm)
     pub fn zkpm(&self) -> &DensePolynomial<G::ScalarField> {
         self.zkpm.get_or_init(|| zk_polynomial(self.domain))
     }
 
-    /// Gets w from [`VerifierIndex`] lazily
+    /// Gets w from [`VerifierIndex`] lazily,
+    /// as given by [`verifier_math::zk_w`](crate::verifier_math::zk_w)
     pub fn w(&self) -> &G::ScalarField {
         self.w.get_or_init(|| zk_w3(self.domain))
     }
 
+    /// Gets the elements of the domain of the public input rows from [`VerifierIndex`] lazily
+    pub fn public_elements(&self) -> &[G::ScalarField] {
+        self.public_elements
+            .get_or_init(|| self.domain.elements().take(self.public).collect())
+    }
+
     /// Deserializes a [`VerifierIndex`] from a file, given a pointer to an SRS and an optional offset in the file.
     ///
     /// # Errors
     ///
     /// Will give error if it fails to deserialize from file or unable to set `srs` in `verifier_index`.
     pub fn from_file(
-        srs: Option<Arc<SRS<G>>>,
+        srs: Option<Arc<S>>,
         path: &Path,
         offset: Option<u64>,
         // TODO: we shouldn't have to pass these
         endo: G::ScalarField,
-    ) -> Result<Self, String> {
+    ) -> Result<Self, String>
+    where
+        Self: DeserializeOwned,
+    {
         // open file
         let file = File::open(path).map_err(|e| e.to_string())?;
 
@@ -356,6 +509,64 @@ impl<G: KimchiCurve> VerifierIndex<G> {
             .map_err(|e| e.to_string())
     }
 
+    /// Returns the commitments to the selectors of the gates of the index,
+    /// the selectors omitted from the index being the zero commitments they stand for
+    /// (the selectors of the optional gates that the circuit does not use are left out).
+    pub fn selectors(&self) -> Vec<(GateType, PolyComm<G>)> {
+        let zero = zero_comm(self.chunks());
+        let mut selectors = vec![
+            (GateType::Generic, self.generic_comm.clone()),
+            (GateType::Poseidon, self.psm_comm.clone()),
+        ];
+        selectors.extend(
+            [
+                (GateType::CompleteAdd, &self.complete_add_comm),
+                (GateType::VarBaseMul, &self.mul_comm),
+                (GateType::EndoMul, &self.emul_comm),
+                (GateType::EndoMulScalar, &self.endomul_scalar_comm),
+            ]
+            .map(|(typ, comm)| (typ, comm.as_ref().unwrap_or(&zero).clone())),
+        );
+        #[cfg(feature = "chacha")]
+        if let Some(chacha_comm) = &self.chacha_comm {
+            let gates = [
+                GateType::ChaCha0,
+                GateType::ChaCha1,
+                GateType::ChaCha2,
+                GateType::ChaChaFinal,
+            ];
+            selectors.extend(gates.into_iter().zip(chacha_comm.iter().cloned()));
+        }
+        #[cfg(feature = "range_check")]
+        if let Some(range_check_comm) = &self.range_check_comm {
+            selectors.extend(
+                range_check::gadget::circuit_gates()
+                    .into_iter()
+                    .zip(range_check_comm.iter().cloned()),
+            );
+        }
+        #[cfg(feature = "foreign_field")]
+        if let Some(foreign_field_add_comm) = &self.foreign_field_add_comm {
+            selectors.extend(
+                foreign_field_add::gadget::circuit_gates()
+                    .into_iter()
+                    .zip([foreign_field_add_comm.clone()]),
+            );
+        }
+        if let Some(copy_cells_comm) = &self.copy_cells_comm {
+            selectors.push((GateType::CopyCells, copy_cells_comm.clone()));
+        }
+        #[cfg(feature = "keccak")]
+        if let Some(keccak_comm) = &self.keccak_comm {
+            selectors.extend(
+                keccak::gadget::circuit_gates()
+                    .into_iter()
+                    .zip(keccak_comm.iter().cloned()),
+            );
+        }
+        selectors
+    }
+
     /// Compute the digest of the [`VerifierIndex`], which can be used for the Fiat-Shamir
     /// transformation while proving / verifying.
     pub fn digest<EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>>(
@@ -364,12 +575,13 @@ impl<G: KimchiCurve> VerifierIndex<G> {
         let mut fq_sponge = EFqSponge::new(G::OtherCurve::sponge_params());
         // We fully expand this to make the compiler check that we aren't missing any commitments
         let VerifierIndex {
-            domain: _,
+            domain,
             max_poly_size: _,
             max_quot_size: _,
             srs: _,
             public: _,
             prev_challenges: _,
+            zero_columns,
 
             // Always present
             sigma_comm,
@@ -382,17 +594,28 @@ impl<G: KimchiCurve> VerifierIndex<G> {
             endomul_scalar_comm,
 
             // Optional gates
+            triple_generic,
+            #[cfg(feature = "chacha")]
             chacha_comm,
+            #[cfg(feature = "range_check")]
             range_check_comm,
+            #[cfg(feature = "foreign_field")]
             foreign_field_add_comm,
+            copy_cells_comm,
+            #[cfg(feature = "keccak")]
+            keccak_comm,
             foreign_field_modulus: _,
 
+            // Application constants; optional
+            app_constants,
+
             // Lookup index; optional
             lookup_index,
 
-            shift: _,
+            shift,
             zkpm: _,
             w: _,
+            public_elements: _,
             endo: _,
 
             linearization: _,
@@ -404,31 +627,45 @@ impl<G: KimchiCurve> VerifierIndex<G> {
         for comm in sigma_comm.iter() {
             fq_sponge.absorb_g(&comm.unshifted);
         }
+        // the omitted commitments are absorbed as the zero commitments they stand for,
+        // in as many chunks as the other commitments
+        let zero = zero_comm(self.chunks());
         for comm in coefficients_comm.iter() {
-            fq_sponge.absorb_g(&comm.unshifted);
+            fq_sponge.absorb_g(&comm.as_ref().unwrap_or(&zero).unshifted);
         }
         fq_sponge.absorb_g(&generic_comm.unshifted);
         fq_sponge.absorb_g(&psm_comm.unshifted);
-        fq_sponge.absorb_g(&complete_add_comm.unshifted);
-        fq_sponge.absorb_g(&mul_comm.unshifted);
-        fq_sponge.absorb_g(&emul_comm.unshifted);
-        fq_sponge.absorb_g(&endomul_scalar_comm.unshifted);
+        for comm in [complete_add_comm, mul_comm, emul_comm, endomul_scalar_comm] {
+            fq_sponge.absorb_g(&comm.as_ref().unwrap_or(&zero).unshifted);
+        }
 
         // Optional gates
 
+        #[cfg(feature = "chacha")]
         if let Some(chacha_comm) = chacha_comm {
             for chacha_comm in chacha_comm {
                 fq_sponge.absorb_g(&chacha_comm.unshifted);
             }
         }
+        #[cfg(feature = "range_check")]
         if let Some(range_check_comm) = range_check_comm {
             for range_check_comm in range_check_comm {
                 fq_sponge.absorb_g(&range_check_comm.unshifted);
             }
         }
+        #[cfg(feature = "foreign_field")]
         if let Some(foreign_field_add_comm) = foreign_field_add_comm {
             fq_sponge.absorb_g(&foreign_field_add_comm.unshifted);
         }
+        if let Some(copy_cells_comm) = copy_cells_comm {
+            fq_sponge.absorb_g(&copy_cells_comm.unshifted);
+        }
+        #[cfg(feature = "keccak")]
+        if let Some(keccak_comm) = keccak_comm {
+            for keccak_comm in keccak_comm {
+                fq_sponge.absorb_g(&keccak_comm.unshifted);
+            }
+        }
 
         // Lookup index; optional
 
@@ -437,13 +674,18 @@ impl<G: KimchiCurve> VerifierIndex<G> {
             lookup_table,
             table_ids,
             runtime_tables_selector,
+            variable_runtime_tables,
 
             lookup_selectors:
                 LookupSelectors {
+                    #[cfg(feature = "chacha")]
                     chacha,
+                    #[cfg(feature = "chacha")]
                     chacha_final,
                     lookup_gate,
+                    #[cfg(feature = "range_check")]
                     range_check_gate,
+                    multi_table_lookup_gate,
                 },
 
             max_joint_size: _,
@@ -459,19 +701,463 @@ impl<G: KimchiCurve> VerifierIndex<G> {
                 fq_sponge.absorb_g(&runtime_tables_selector.unshifted);
             }
 
+            #[cfg(feature = "chacha")]
             if let Some(chacha) = chacha {
                 fq_sponge.absorb_g(&chacha.unshifted);
             }
+            #[cfg(feature = "chacha")]
             if let Some(chacha_final) = chacha_final {
                 fq_sponge.absorb_g(&chacha_final.unshifted);
             }
             if let Some(lookup_gate) = lookup_gate {
                 fq_sponge.absorb_g(&lookup_gate.unshifted);
             }
+            #[cfg(feature = "range_check")]
             if let Some(range_check_gate) = range_check_gate {
                 fq_sponge.absorb_g(&range_check_gate.unshifted);
             }
+            if let Some(multi_table_lookup_gate) = multi_table_lookup_gate {
+                fq_sponge.absorb_g(&multi_table_lookup_gate.unshifted);
+            }
+
+            // (absorbing nothing without runtime tables of variable length)
+            for table in variable_runtime_tables {
+                fq_sponge.absorb_fr(&[
+                    i64_to_field(table.id),
+                    G::ScalarField::from(table.offset as u64),
+                    G::ScalarField::from(table.len as u64),
+                ]);
+            }
+        }
+
+        // Triple generic gate; optional
+        // (absorbing nothing without it, so that the digest of other indexes is unchanged)
+
+        if *triple_generic {
+            fq_sponge.absorb_fr(&[G::ScalarField::from(GateType::TripleGeneric as u64)]);
+        }
+
+        // Application constants; optional
+        // (absorbing nothing without them, so that the digest of other indexes is unchanged)
+
+        for (name, value) in app_constants {
+            let name =
+                G::ScalarField::from_le_bytes_mod_order(&Blake2b512::digest(name.as_bytes()));
+            fq_sponge.absorb_fr(&[name, *value]);
+        }
+
+        // Zero columns; optional
+        // (absorbing nothing without them, so that the digest of other indexes is unchanged)
+
+        let zero_mask = zero_columns
+            .iter()
+            .rev()
+            .fold(0u64, |mask, zero| (mask << 1) | u64::from(*zero));
+        if zero_mask != 0 {
+            fq_sponge.absorb_fq(&[G::BaseField::from(zero_mask)]);
+        }
+
+        // Custom shifts; optional
+        // (absorbing nothing with the derived shifts, so that the digest of other indexes is unchanged)
+
+        if *shift != Shifts::derived(domain) {
+            fq_sponge.absorb_fr(shift);
         }
         fq_sponge.digest_fq()
     }
 }
+
+/// A builder assembling a [`VerifierIndex`] from its individual commitments,
+/// for instance when they are retrieved from on-chain data rather than derived from a [`ProverIndex`].
+/// The linearization and the powers of alpha are derived from the commitments given,
+/// and so are the wire shifts unless custom ones are given.
+///
+/// How to use it:
+/// 1. Create the builder with `VerifierIndexBuilder::new(srs, domain_size)`
+/// 2. Set the commitments with `sigma(), coefficients(), selector(), lookup()`,
+///    and optionally `public(), prev_challenges(), zero_columns(), triple_generic(), foreign_field_modulus(), app_constant(), shifts(), expected_digest()`
+/// 3. Finally call the `build()` method to validate the commitments,
+///    and obtain the `VerifierIndex` along with its digest
+pub struct VerifierIndexBuilder<G: KimchiCurve> {
+    srs: Arc<SRS<G>>,
+    domain: D<G::ScalarField>,
+    public: usize,
+    prev_challenges: usize,
+    zero_columns: Vec<usize>,
+    triple_generic: bool,
+    sigma_comm: Vec<PolyComm<G>>,
+    coefficients_comm: Vec<PolyComm<G>>,
+    selectors: HashMap<GateType, PolyComm<G>>,
+    foreign_field_modulus: Option<BigUint>,
+    app_constants: Vec<(String, G::ScalarField)>,
+    shifts: Option<[G::ScalarField; PERMUTS]>,
+    lookup: Option<(LookupConfiguration<G::ScalarField>, LookupVerifierIndex<G>)>,
+    expected_digest: Option<G::BaseField>,
+}
+
+impl<G: KimchiCurve> VerifierIndexBuilder<G>
+where
+    G::BaseField: PrimeField,
+{
+    /// Creates a builder for a circuit over a domain of `domain_size` rows.
+    /// The `srs` must contain the Lagrange basis of that domain.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the domain cannot be created, or is not supported by the `srs`.
+    pub fn new(srs: Arc<SRS<G>>, domain_size: usize) -> Result<Self, VerifierIndexError> {
+        let domain = D::new(domain_size).ok_or(VerifierIndexError::DomainCreation)?;
+        if !srs.lagrange_bases.contains_key(&domain.size()) {
+            return Err(VerifierIndexError::LagrangeBasisMissing(domain.size()));
+        }
+        Ok(Self {
+            srs,
+            domain,
+            public: 0,
+            prev_challenges: 0,
+            zero_columns: vec![],
+            triple_generic: false,
+            sigma_comm: vec![],
+            coefficients_comm: vec![],
+            selectors: HashMap::new(),
+            foreign_field_modulus: None,
+            app_constants: vec![],
+            shifts: None,
+            lookup: None,
+            expected_digest: None,
+        })
+    }
+
+    /// Sets the number of public inputs.
+    pub fn public(mut self, public: usize) -> Self {
+        self.public = public;
+        self
+    }
+
+    /// Sets the number of previous evaluation challenges, for recursive proving.
+    pub fn prev_challenges(mut self, prev_challenges: usize) -> Self {
+        self.prev_challenges = prev_challenges;
+        self
+    }
+
+    /// Sets the witness columns declared identically zero.
+    pub fn zero_columns(mut self, zero_columns: Vec<usize>) -> Self {
+        self.zero_columns = zero_columns;
+        self
+    }
+
+    /// Sets whether the circuit uses the triple generic gate.
+    pub fn triple_generic(mut self, triple_generic: bool) -> Self {
+        self.triple_generic = triple_generic;
+        self
+    }
+
+    /// Sets the commitments to the permutation polynomials,
+    /// one for each of the first columns taking part in the permutation.
+    pub fn sigma(mut self, sigma_comm: Vec<PolyComm<G>>) -> Self {
+        self.sigma_comm = sigma_comm;
+        self
+    }
+
+    /// Sets the commitments to the coefficient polynomials.
+    pub fn coefficients(mut self, coefficients_comm: Vec<PolyComm<G>>) -> Self {
+        self.coefficients_comm = coefficients_comm;
+        self
+    }
+
+    /// Sets the commitment to the selector polynomial of a gate type.
+    /// For the generic and the poseidon gates, this is the commitment included in the index
+    /// (which is masked).
+    pub fn selector(mut self, typ: GateType, comm: PolyComm<G>) -> Self {
+        self.selectors.insert(typ, comm);
+        self
+    }
+
+    /// Sets the foreign field modulus.
+    pub fn foreign_field_modulus(mut self, foreign_field_modulus: BigUint) -> Self {
+        self.foreign_field_modulus = Some(foreign_field_modulus);
+        self
+    }
+
+    /// Adds a named constant of the application, as [`crate::circuits::constraints::Builder::app_constant`] does.
+    pub fn app_constant(mut self, name: impl Into<String>, value: G::ScalarField) -> Self {
+        self.app_constants.push((name.into(), value));
+        self
+    }
+
+    /// Sets the custom shifts of the permutation of the circuit, if it does not use the derived ones.
+    pub fn shifts(mut self, shifts: [G::ScalarField; PERMUTS]) -> Self {
+        self.shifts = Some(shifts);
+        self
+    }
+
+    /// Sets the lookup configuration, and the commitments of the lookup argument.
+    pub fn lookup(
+        mut self,
+        configuration: LookupConfiguration<G::ScalarField>,
+        lookup_index: LookupVerifierIndex<G>,
+    ) -> Self {
+        self.lookup = Some((configuration, lookup_index));
+        self
+    }
+
+    /// Sets the digest that the index is expected to have.
+    pub fn expected_digest(mut self, digest: G::BaseField) -> Self {
+        self.expected_digest = Some(digest);
+        self
+    }
+
+    /// Validates the commitments, and builds the [`VerifierIndex`] along with its digest.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if a commitment is missing, unexpected, or of an unexpected size,
+    /// if a column declared zero does not exist,
+    /// if the lookup commitments are inconsistent with the lookup configuration,
+    /// if the custom shifts are invalid, or if the digest is not the expected one.
+    pub fn build<EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>>(
+        mut self,
+    ) -> Result<(VerifierIndex<G>, G::BaseField), VerifierIndexError> {
+        let max_poly_size = self.srs.g.len();
+        let chunks = (self.domain.size() + max_poly_size - 1) / max_poly_size;
+        let check_chunks = |name, comm: &PolyComm<G>| {
+            if comm.unshifted.len() == chunks && comm.shifted.is_none() {
+                Ok(())
+            } else {
+                Err(VerifierIndexError::IncorrectCommitmentSize(name, chunks))
+            }
+        };
+
+        // zero columns
+        let mut zero_columns = [false; COLUMNS];
+        for col in self.zero_columns {
+            *zero_columns
+                .get_mut(col)
+                .ok_or(VerifierIndexError::ColumnMissing(col))? = true;
+        }
+
+        // permutation and coefficients
+        let sigma_comm = self.sigma_comm;
+        if sigma_comm.is_empty() || sigma_comm.len() > PERMUTS {
+            return Err(VerifierIndexError::IncorrectCommitmentCount(
+                "sigma",
+                PERMUTS,
+                sigma_comm.len(),
+            ));
+        }
+        let coefficients_comm: [PolyComm<G>; COLUMNS] =
+            into_array("coefficients", self.coefficients_comm)?;
+        for comm in &sigma_comm {
+            check_chunks("sigma", comm)?;
+        }
+        for comm in &coefficients_comm {
+            check_chunks("coefficients", comm)?;
+        }
+        let coefficients_comm = coefficients_comm.map(non_zero);
+
+        // selectors
+        let mut selector = |typ: GateType| {
+            let comm = self.selectors.remove(&typ);
+            if let Some(comm) = &comm {
+                check_chunks("selector", comm)?;
+            }
+            Ok(comm)
+        };
+        let mut required = |typ| selector(typ)?.ok_or(VerifierIndexError::SelectorMissing(typ));
+        let generic_comm = required(GateType::Generic)?;
+        let psm_comm = required(GateType::Poseidon)?;
+        let complete_add_comm = non_zero(required(GateType::CompleteAdd)?);
+        let mul_comm = non_zero(required(GateType::VarBaseMul)?);
+        let emul_comm = non_zero(required(GateType::EndoMul)?);
+        let endomul_scalar_comm = non_zero(required(GateType::EndoMulScalar)?);
+
+        let mut optional = |gates: &[GateType]| {
+            let comms = gates
+                .iter()
+                .map(|typ| selector(*typ))
+                .collect::<Result<Vec<_>, _>>()?;
+            if comms.iter().all(Option::is_none) {
+                return Ok(None);
+            }
+            gates
+                .iter()
+                .zip(comms)
+                .map(|(typ, comm)| comm.ok_or(VerifierIndexError::SelectorMissing(*typ)))
+                .collect::<Result<Vec<_>, _>>()
+                .map(Some)
+        };
+        #[cfg(feature = "chacha")]
+        let chacha_comm = optional(&[
+            GateType::ChaCha0,
+            GateType::ChaCha1,
+            GateType::ChaCha2,
+            GateType::ChaChaFinal,
+        ])?
+        .map(|comms| into_array("chacha", comms))
+        .transpose()?;
+        #[cfg(feature = "range_check")]
+        let range_check_comm = optional(&range_check::gadget::circuit_gates())?
+            .map(|comms| into_array("range check", comms))
+            .transpose()?;
+        #[cfg(feature = "foreign_field")]
+        let foreign_field_add_comm = optional(&foreign_field_add::gadget::circuit_gates())?
+            .and_then(|comms| comms.into_iter().next());
+        let copy_cells_comm =
+            optional(&[GateType::CopyCells])?.and_then(|comms| comms.into_iter().next());
+        #[cfg(feature = "keccak")]
+        let keccak_comm = optional(&keccak::gadget::circuit_gates())?
+            .map(|comms| into_array("keccak", comms))
+            .transpose()?;
+
+        if let Some(typ) = self.selectors.keys().next() {
+            return Err(VerifierIndexError::UnexpectedSelector(*typ));
+        }
+
+        // lookup
+        let (lookup_configuration, lookup_index) = match self.lookup {
+            Some((configuration, lookup_index)) => {
+                check_lookup(&configuration, &lookup_index)?;
+                (Some(configuration), Some(lookup_index))
+            }
+            None => (None, None),
+        };
+
+        #[cfg(feature = "chacha")]
+        let chacha = chacha_comm.is_some();
+        #[cfg(not(feature = "chacha"))]
+        let chacha = false;
+        #[cfg(feature = "range_check")]
+        let range_check = range_check_comm.is_some();
+        #[cfg(not(feature = "range_check"))]
+        let range_check = false;
+        #[cfg(feature = "foreign_field")]
+        let foreign_field_add = foreign_field_add_comm.is_some();
+        #[cfg(not(feature = "foreign_field"))]
+        let foreign_field_add = false;
+        #[cfg(feature = "keccak")]
+        let keccak = keccak_comm.is_some();
+        #[cfg(not(feature = "keccak"))]
+        let keccak = false;
+        let (linearization, powers_of_alpha) = expr_linearization(
+            chacha,
+            range_check,
+            lookup_configuration.as_ref(),
+            foreign_field_add,
+            copy_cells_comm.is_some(),
+            keccak,
+        );
+
+        let shift = match self.shifts {
+            None => Shifts::derived(&self.domain),
+            Some(shifts) => {
+                check_shifts(&self.domain, &shifts).map_err(VerifierIndexError::InvalidShifts)?;
+                shifts
+            }
+        };
+
+        let verifier_index = VerifierIndex {
+            domain: self.domain,
+            max_poly_size,
+            max_quot_size: PERMUTS * self.domain.size(),
+            srs: {
+                let cell = OnceCell::new();
+                cell.set(self.srs).unwrap();
+                cell
+            },
+            public: self.public,
+            prev_challenges: self.prev_challenges,
+            zero_columns,
+            sigma_comm,
+            coefficients_comm,
+            generic_comm,
+            triple_generic: self.triple_generic,
+            psm_comm,
+            complete_add_comm,
+            mul_comm,
+            emul_comm,
+            endomul_scalar_comm,
+            #[cfg(feature = "chacha")]
+            chacha_comm,
+            #[cfg(feature = "range_check")]
+            range_check_comm,
+            foreign_field_modulus: self.foreign_field_modulus,
+            app_constants: self.app_constants,
+            #[cfg(feature = "foreign_field")]
+            foreign_field_add_comm,
+            copy_cells_comm,
+            #[cfg(feature = "keccak")]
+            keccak_comm,
+            shift,
+            zkpm: OnceCell::new(),
+            w: OnceCell::new(),
+            public_elements: OnceCell::new(),
+            endo: G::OtherCurve::endos().0,
+            lookup_index,
+            linearization,
+            powers_of_alpha,
+        };
+
+        let digest = verifier_index.digest::<EFqSponge>();
+        match self.expected_digest {
+            Some(expected) if expected != digest => Err(VerifierIndexError::DigestMismatch),
+            _ => Ok((verifier_index, digest)),
+        }
+    }
+}
+
+/// Returns the commitment to the zero polynomial in `chunks` chunks,
+/// which the [`VerifierIndex`] omits for the coefficient columns and the selectors of the gates
+/// that the circuit does not use (see [`VerifierIndex::chunks`]).
+pub fn zero_comm<G: CommitmentCurve>(chunks: usize) -> PolyComm<G> {
+    PolyComm {
+        unshifted: vec![G::zero(); chunks],
+        shifted: None,
+    }
+}
+
+/// Returns `comm`, unless it is the commitment to the zero polynomial.
+fn non_zero<G: CommitmentCurve>(comm: PolyComm<G>) -> Option<PolyComm<G>> {
+    if comm.unshifted.iter().all(|g| g.is_zero()) && comm.shifted.is_none() {
+        None
+    } else {
+        Some(comm)
+    }
+}
+
+/// Converts a vector of commitments into an array, checking its length.
+fn into_array<G: CommitmentCurve, const N: usize>(
+    name: &'static str,
+    comms: Vec<PolyComm<G>>,
+) -> Result<[PolyComm<G>; N], VerifierIndexError> {
+    let len = comms.len();
+    comms
+        .try_into()
+        .map_err(|_| VerifierIndexError::IncorrectCommitmentCount(name, N, len))
+}
+
+/// Checks that the commitments of the lookup argument match its configuration.
+fn check_lookup<G: KimchiCurve>(
+    configuration: &LookupConfiguration<G::ScalarField>,
+    lookup_index: &LookupVerifierIndex<G>,
+) -> Result<(), VerifierIndexError> {
+    let info = &configuration.lookup_info;
+    if lookup_index.lookup_used != configuration.lookup_used
+        || lookup_index.max_joint_size != info.max_joint_size
+    {
+        return Err(VerifierIndexError::InconsistentLookup("configuration"));
+    }
+    if lookup_index.lookup_table.is_empty() {
+        return Err(VerifierIndexError::InconsistentLookup("lookup table"));
+    }
+    if lookup_index.runtime_tables_selector.is_some() != info.uses_runtime_tables {
+        return Err(VerifierIndexError::InconsistentLookup("runtime tables"));
+    }
+    use strum::IntoEnumIterator;
+
+    for pattern in LookupPattern::iter() {
+        if lookup_index.lookup_selectors[pattern].is_some() != info.kinds.contains(&pattern) {
+            return Err(VerifierIndexError::InconsistentLookup("lookup selectors"));
+        }
+    }
+    Ok(())
+}
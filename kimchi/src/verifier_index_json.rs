@@ -0,0 +1,514 @@
+//! This module implements [`VerifierIndexJson`], an export of a [`VerifierIndex`]
+//! into plain JSON, for verifiers implemented outside of this crate (in Go, TypeScript, ...).
+//!
+//! The export only contains strings, integers and booleans:
+//!
+//! - field elements are hex strings of their little-endian canonical encoding
+//!   (32 bytes for the pasta fields), as given by [`FieldHelpers::to_hex`],
+//! - curve points are objects `{ "x": <hex>, "y": <hex> }` of their affine coordinates,
+//!   or `null` for the point at infinity,
+//! - commitments are arrays of points, one for each chunk of the committed polynomial
+//!   (none of the commitments of a verifier index are shifted),
+//! - gate types and lookup patterns are the names of their Rust variants.
+//!
+//! For instance, a circuit of 8 rows without lookups exports as
+//!
+//! ```text
+//! {
+//!   "version": 1,
+//!   "domain": { "log_size": 3, "size": 8, "generator": "..." },
+//!   "max_poly_size": 32,
+//!   "public": 1,
+//!   "prev_challenges": 0,
+//!   "zero_columns": [],
+//!   "triple_generic": false,
+//!   "shifts": ["0100...", "..."],
+//!   "sigma": [[{ "x": "...", "y": "..." }], ...],
+//!   "coefficients": [[...], ...],
+//!   "selectors": [{ "gate": "Generic", "commitment": [...] }, ...],
+//!   "foreign_field_modulus": null,
+//!   "app_constants": [{ "name": "chain_id", "value": "..." }],
+//!   "lookup": null,
+//!   "linearization_digest": "...",
+//!   "digest": "..."
+//! }
+//! ```
+//!
+//! The `digest` is the digest of the index (see [`VerifierIndex::digest`]),
+//! absorbed by the verifier at the start of the Fiat-Shamir transcript.
+//! The `linearization_digest` fingerprints the linearization of the index,
+//! so that an external verifier can check that the linearization it implements
+//! is the one of the circuit (see [`linearization_digest`]).
+//!
+//! [`VerifierIndexJson::import`] rebuilds a [`VerifierIndex`] from its export,
+//! recomputing the parts that are not exported, and checking them against both digests.
+
+use crate::{
+    circuits::{
+        expr::{Linearization, PolishToken},
+        gate::GateType,
+        lookup::{
+            constraints::LookupConfiguration,
+            index::LookupSelectors,
+            lookups::{JointLookup, LookupInfo, LookupPattern, LookupsUsed},
+            runtime_tables::VariableRuntimeTable,
+        },
+        wires::COLUMNS,
+    },
+    curve::KimchiCurve,
+    error::VerifierIndexError,
+    verifier_index::{zero_comm, LookupVerifierIndex, VerifierIndex, VerifierIndexBuilder},
+};
+use ark_ff::{Field, PrimeField, Zero};
+use blake2::{Blake2b512, Digest};
+use commitment_dlog::{
+    commitment::{CommitmentCurve, PolyComm},
+    srs::SRS,
+};
+use num_bigint::BigUint;
+use o1_utils::FieldHelpers;
+use oracle::FqSponge;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use strum::IntoEnumIterator;
+
+/// The version of the format of [`VerifierIndexJson`].
+pub const FORMAT_VERSION: u32 = 1;
+
+/// The affine coordinates of a curve point, in hex.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PointJson {
+    pub x: String,
+    pub y: String,
+}
+
+/// A commitment, as the points of its chunks (`None` for the point at infinity).
+pub type CommitmentJson = Vec<Option<PointJson>>;
+
+/// The evaluation domain of a circuit.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DomainJson {
+    /// The base-2 logarithm of the size of the domain
+    pub log_size: u32,
+    /// The size of the domain
+    pub size: u64,
+    /// The generator of the domain, in hex
+    pub generator: String,
+}
+
+/// The commitment to the selector polynomial of a gate type.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SelectorJson {
+    pub gate: GateType,
+    pub commitment: CommitmentJson,
+}
+
+/// A named constant of the application.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppConstantJson {
+    pub name: String,
+    /// The value of the constant, in hex
+    pub value: String,
+}
+
+/// The commitment to the selector polynomial of a lookup pattern.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LookupSelectorJson {
+    pub pattern: LookupPattern,
+    pub commitment: CommitmentJson,
+}
+
+/// The lookup configuration of a circuit, and the commitments of its lookup argument.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LookupJson {
+    /// Whether the lookups are single or joint
+    pub lookup_used: LookupsUsed,
+    /// The lookup patterns used by the circuit
+    pub kinds: Vec<LookupPattern>,
+    /// The maximum number of lookups per row
+    pub max_per_row: usize,
+    /// The maximum number of columns of a joint lookup
+    pub max_joint_size: u32,
+    /// Whether runtime tables are used
+    pub uses_runtime_tables: bool,
+    /// The table id of the lookup padding the rows with fewer lookups, in hex
+    pub dummy_table_id: String,
+    /// The entry of the lookup padding the rows with fewer lookups, in hex
+    pub dummy_entry: Vec<String>,
+    /// The commitments to the columns of the lookup table
+    pub table: Vec<CommitmentJson>,
+    /// The commitments to the selectors of the lookup patterns used
+    pub selectors: Vec<LookupSelectorJson>,
+    /// The commitment to the table ids, if several tables are used
+    pub table_ids: Option<CommitmentJson>,
+    /// The commitment to the selector of the runtime tables, if used
+    pub runtime_tables_selector: Option<CommitmentJson>,
+    /// The runtime tables of variable length, with the rows where they start
+    #[serde(default)]
+    pub variable_runtime_tables: Vec<VariableRuntimeTable>,
+}
+
+/// A [`VerifierIndex`], exported to plain JSON (see the [module documentation](self)).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifierIndexJson {
+    /// The version of the format, [`FORMAT_VERSION`]
+    pub version: u32,
+    /// The evaluation domain
+    pub domain: DomainJson,
+    /// The maximum size of the committed polynomials, the size of the SRS
+    pub max_poly_size: usize,
+    /// The number of public inputs
+    pub public: usize,
+    /// The number of previous evaluation challenges, for recursive proving
+    pub prev_challenges: usize,
+    /// The witness columns that are identically zero
+    pub zero_columns: Vec<usize>,
+    /// Whether the circuit uses the triple generic gate
+    /// (absent from the exports made before it was introduced)
+    #[serde(default)]
+    pub triple_generic: bool,
+    /// The wire shifts of the permutation, in hex
+    pub shifts: Vec<String>,
+    /// The commitments to the permutation polynomials
+    pub sigma: Vec<CommitmentJson>,
+    /// The commitments to the coefficient polynomials
+    pub coefficients: Vec<CommitmentJson>,
+    /// The commitments to the selector polynomials of the gates used
+    pub selectors: Vec<SelectorJson>,
+    /// The foreign field modulus, in big-endian hex
+    pub foreign_field_modulus: Option<String>,
+    /// The named constants of the application, absorbed in the digest
+    /// (absent from the exports made before they were introduced)
+    #[serde(default)]
+    pub app_constants: Vec<AppConstantJson>,
+    /// The lookup argument, if used
+    pub lookup: Option<LookupJson>,
+    /// The digest of the linearization, in hex
+    pub linearization_digest: String,
+    /// The digest of the index, in hex
+    pub digest: String,
+}
+
+/// Returns the Blake2b-512 digest of a linearization, in hex.
+/// It is a fingerprint of its expression rather than a documented encoding:
+/// external verifiers should compare it with the digest of the linearization they implement,
+/// as computed by this function.
+pub fn linearization_digest<F: Field>(
+    linearization: &Linearization<Vec<PolishToken<F>>>,
+) -> String {
+    let mut h = Blake2b512::new();
+    h.update(format!("{:?}", linearization.constant_term));
+    for (column, tokens) in &linearization.index_terms {
+        h.update(format!("{column:?}: {tokens:?}"));
+    }
+    hex::encode(h.finalize())
+}
+
+/// Exports a commitment.
+fn commitment_json<G: CommitmentCurve>(comm: &PolyComm<G>) -> CommitmentJson {
+    comm.unshifted
+        .iter()
+        .map(|g| {
+            g.to_coordinates().map(|(x, y)| PointJson {
+                x: x.to_hex(),
+                y: y.to_hex(),
+            })
+        })
+        .collect()
+}
+
+/// Imports a field element, `name` being the part of the export it belongs to.
+fn field<F: Field>(name: &'static str, hex: &str) -> Result<F, VerifierIndexError> {
+    F::from_hex(hex).map_err(|_| VerifierIndexError::InvalidExport(name))
+}
+
+/// Imports a commitment, `name` being the part of the export it belongs to.
+fn commitment<G: CommitmentCurve>(
+    name: &'static str,
+    comm: &CommitmentJson,
+) -> Result<PolyComm<G>, VerifierIndexError> {
+    let unshifted = comm
+        .iter()
+        .map(|point| match point {
+            None => Ok(G::zero()),
+            Some(PointJson { x, y }) => Ok(G::of_coordinates(field(name, x)?, field(name, y)?)),
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(PolyComm {
+        unshifted,
+        shifted: None,
+    })
+}
+
+/// Imports commitments, `name` being the part of the export they belong to.
+fn commitments<G: CommitmentCurve>(
+    name: &'static str,
+    comms: &[CommitmentJson],
+) -> Result<Vec<PolyComm<G>>, VerifierIndexError> {
+    comms.iter().map(|comm| commitment(name, comm)).collect()
+}
+
+impl VerifierIndexJson {
+    /// Exports `index`, given the lookup configuration of its circuit if it uses lookups.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the lookup configuration is missing or not expected.
+    pub fn export<G, EFqSponge>(
+        index: &VerifierIndex<G>,
+        lookup_configuration: Option<&LookupConfiguration<G::ScalarField>>,
+    ) -> Result<Self, VerifierIndexError>
+    where
+        G: KimchiCurve,
+        G::BaseField: PrimeField,
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    {
+        // the commitments omitted from the index are exported as zero commitments
+        let zero = zero_comm(index.chunks());
+
+        let lookup = match (&index.lookup_index, lookup_configuration) {
+            (None, None) => None,
+            (Some(lookup_index), Some(configuration)) => Some(LookupJson {
+                lookup_used: configuration.lookup_used,
+                kinds: configuration.lookup_info.kinds.clone(),
+                max_per_row: configuration.lookup_info.max_per_row,
+                max_joint_size: configuration.lookup_info.max_joint_size,
+                uses_runtime_tables: configuration.lookup_info.uses_runtime_tables,
+                dummy_table_id: configuration.dummy_lookup.table_id.to_hex(),
+                dummy_entry: configuration
+                    .dummy_lookup
+                    .entry
+                    .iter()
+                    .map(|x| x.to_hex())
+                    .collect(),
+                table: lookup_index
+                    .lookup_table
+                    .iter()
+                    .map(commitment_json)
+                    .collect(),
+                selectors: LookupPattern::iter()
+                    .filter_map(|pattern| {
+                        lookup_index.lookup_selectors[pattern].as_ref().map(|comm| {
+                            LookupSelectorJson {
+                                pattern,
+                                commitment: commitment_json(comm),
+                            }
+                        })
+                    })
+                    .collect(),
+                table_ids: lookup_index.table_ids.as_ref().map(commitment_json),
+                runtime_tables_selector: lookup_index
+                    .runtime_tables_selector
+                    .as_ref()
+                    .map(commitment_json),
+                variable_runtime_tables: lookup_index.variable_runtime_tables.clone(),
+            }),
+            _ => return Err(VerifierIndexError::InconsistentLookup("configuration")),
+        };
+
+        Ok(Self {
+            version: FORMAT_VERSION,
+            domain: DomainJson {
+                log_size: index.domain.log_size_of_group,
+                size: index.domain.size,
+                generator: index.domain.group_gen.to_hex(),
+            },
+            max_poly_size: index.max_poly_size,
+            public: index.public,
+            prev_challenges: index.prev_challenges,
+            zero_columns: (0..COLUMNS)
+                .filter(|col| index.zero_columns[*col])
+                .collect(),
+            triple_generic: index.triple_generic,
+            shifts: index.shift.iter().map(|s| s.to_hex()).collect(),
+            sigma: index.sigma_comm.iter().map(commitment_json).collect(),
+            coefficients: index
+                .coefficients_comm
+                .iter()
+                .map(|comm| commitment_json(comm.as_ref().unwrap_or(&zero)))
+                .collect(),
+            selectors: index
+                .selectors()
+                .into_iter()
+                .map(|(gate, comm)| SelectorJson {
+                    gate,
+                    commitment: commitment_json(&comm),
+                })
+                .collect(),
+            foreign_field_modulus: index
+                .foreign_field_modulus
+                .as_ref()
+                .map(|modulus| modulus.to_str_radix(16)),
+            app_constants: index
+                .app_constants
+                .iter()
+                .map(|(name, value)| AppConstantJson {
+                    name: name.clone(),
+                    value: value.to_hex(),
+                })
+                .collect(),
+            lookup,
+            linearization_digest: linearization_digest(&index.linearization),
+            digest: index.digest::<EFqSponge>().to_hex(),
+        })
+    }
+
+    /// Rebuilds the exported [`VerifierIndex`] against `srs`,
+    /// which must contain the Lagrange basis of its domain.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the export is malformed, does not match `srs`,
+    /// or if the rebuilt index does not have the exported digests.
+    pub fn import<G, EFqSponge>(
+        &self,
+        srs: Arc<SRS<G>>,
+    ) -> Result<VerifierIndex<G>, VerifierIndexError>
+    where
+        G: KimchiCurve,
+        G::BaseField: PrimeField,
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    {
+        if self.version != FORMAT_VERSION {
+            return Err(VerifierIndexError::InvalidExport("version"));
+        }
+        if self.max_poly_size != srs.g.len() {
+            return Err(VerifierIndexError::InvalidExport("max_poly_size"));
+        }
+        if self.domain.size != 1 << self.domain.log_size {
+            return Err(VerifierIndexError::InvalidExport("domain"));
+        }
+
+        // the shifts are imported, as the circuit may use custom ones
+        let shifts = self
+            .shifts
+            .iter()
+            .map(|s| field("shifts", s))
+            .collect::<Result<Vec<G::ScalarField>, _>>()?
+            .try_into()
+            .map_err(|_| VerifierIndexError::InvalidExport("shifts"))?;
+
+        let mut builder = VerifierIndexBuilder::new(srs, self.domain.size as usize)?
+            .public(self.public)
+            .prev_challenges(self.prev_challenges)
+            .zero_columns(self.zero_columns.clone())
+            .triple_generic(self.triple_generic)
+            .sigma(commitments("sigma", &self.sigma)?)
+            .coefficients(commitments("coefficients", &self.coefficients)?)
+            .shifts(shifts)
+            .expected_digest(field("digest", &self.digest)?);
+        for selector in &self.selectors {
+            builder = builder.selector(
+                selector.gate,
+                commitment("selectors", &selector.commitment)?,
+            );
+        }
+        if let Some(modulus) = &self.foreign_field_modulus {
+            let modulus = BigUint::parse_bytes(modulus.as_bytes(), 16)
+                .ok_or(VerifierIndexError::InvalidExport("foreign_field_modulus"))?;
+            builder = builder.foreign_field_modulus(modulus);
+        }
+        for constant in &self.app_constants {
+            builder = builder.app_constant(
+                constant.name.clone(),
+                field("app_constants", &constant.value)?,
+            );
+        }
+        if let Some(lookup) = &self.lookup {
+            let (configuration, lookup_index) = lookup.import()?;
+            builder = builder.lookup(configuration, lookup_index);
+        }
+
+        let (index, _) = builder.build::<EFqSponge>()?;
+
+        // the parts of the index that are derived rather than exported must match too
+        let generator: G::ScalarField = field("domain", &self.domain.generator)?;
+        if generator != index.domain.group_gen {
+            return Err(VerifierIndexError::InvalidExport("domain"));
+        }
+        if linearization_digest(&index.linearization) != self.linearization_digest {
+            return Err(VerifierIndexError::LinearizationMismatch);
+        }
+        Ok(index)
+    }
+
+    /// Serializes the export to pretty-printed JSON.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if serialization fails, which it does not for this type.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("the export serializes to JSON")
+    }
+
+    /// Deserializes an export from JSON.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `json` is not an export of a verifier index.
+    pub fn from_json(json: &str) -> Result<Self, VerifierIndexError> {
+        serde_json::from_str(json).map_err(|e| VerifierIndexError::Json(e.to_string()))
+    }
+}
+
+impl LookupJson {
+    /// Returns the lookup configuration and the lookup commitments of the export.
+    fn import<G: KimchiCurve>(
+        &self,
+    ) -> Result<(LookupConfiguration<G::ScalarField>, LookupVerifierIndex<G>), VerifierIndexError>
+    {
+        let configuration = LookupConfiguration {
+            lookup_used: self.lookup_used,
+            lookup_info: LookupInfo {
+                kinds: self.kinds.clone(),
+                max_per_row: self.max_per_row,
+                max_joint_size: self.max_joint_size,
+                uses_runtime_tables: self.uses_runtime_tables,
+            },
+            dummy_lookup: JointLookup {
+                table_id: field("lookup", &self.dummy_table_id)?,
+                entry: self
+                    .dummy_entry
+                    .iter()
+                    .map(|x| field("lookup", x))
+                    .collect::<Result<_, _>>()?,
+            },
+        };
+
+        let selector = |pattern| {
+            self.selectors
+                .iter()
+                .find(|selector| selector.pattern == pattern)
+                .map(|selector| commitment("lookup", &selector.commitment))
+                .transpose()
+        };
+        let lookup_selectors = LookupSelectors {
+            #[cfg(feature = "chacha")]
+            chacha: selector(LookupPattern::ChaCha)?,
+            #[cfg(feature = "chacha")]
+            chacha_final: selector(LookupPattern::ChaChaFinal)?,
+            lookup_gate: selector(LookupPattern::LookupGate)?,
+            #[cfg(feature = "range_check")]
+            range_check_gate: selector(LookupPattern::RangeCheckGate)?,
+            multi_table_lookup_gate: selector(LookupPattern::MultiTableLookupGate)?,
+        };
+        let lookup_index = LookupVerifierIndex {
+            lookup_used: self.lookup_used,
+            lookup_table: commitments("lookup", &self.table)?,
+            lookup_selectors,
+            table_ids: self
+                .table_ids
+                .as_ref()
+                .map(|comm| commitment("lookup", comm))
+                .transpose()?,
+            max_joint_size: self.max_joint_size,
+            runtime_tables_selector: self
+                .runtime_tables_selector
+                .as_ref()
+                .map(|comm| commitment("lookup", comm))
+                .transpose()?,
+            variable_runtime_tables: self.variable_runtime_tables.clone(),
+        };
+        Ok((configuration, lookup_index))
+    }
+}
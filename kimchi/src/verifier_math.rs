@@ -0,0 +1,62 @@
+//! This module implements the evaluations of the polynomials fixed by the domain of a circuit,
+//! which a verifier computes itself rather than reading them from the proof.
+//! An independent implementation of the verifier, or a verifier circuit,
+//! must compute them exactly as defined here, for a domain of size `n` and generator `w`:
+//!
+//! - [`vanishing`] evaluates the vanishing polynomial of the domain, `x^n - 1`,
+//! - [`zk_w`] is `w^(n - 3)`, the first of the zero-knowledge rows
+//!   (see [`VerifierIndex::w`](crate::verifier_index::VerifierIndex::w)),
+//! - [`zkpm`] evaluates the polynomial vanishing on the zero-knowledge rows,
+//!   `(x - w^(n - 3)) (x - w^(n - 2)) (x - w^(n - 1))`
+//!   (see [`VerifierIndex::zkpm`](crate::verifier_index::VerifierIndex::zkpm)),
+//! - [`vanishes_on_last_4_rows`] evaluates the polynomial vanishing on the last 4 rows,
+//!   `(x - w^(n - 4)) (x - w^(n - 3)) (x - w^(n - 2)) (x - w^(n - 1))`,
+//! - [`unnormalized_lagrange_basis`] evaluates the `i`-th Lagrange polynomial of the domain,
+//!   up to a constant factor, `(x^n - 1) / (x - w^i)`.
+//!
+//! The evaluations of the public input polynomial, which build on them,
+//! are given by [`public_evaluations`](crate::verifier::public_evaluations).
+
+use crate::circuits::polynomials::permutation::{
+    eval_vanishes_on_last_4_rows, eval_zk_polynomial, zk_w3,
+};
+use ark_ff::{FftField, Field};
+use ark_poly::{EvaluationDomain, Radix2EvaluationDomain as D};
+
+/// Evaluates the vanishing polynomial of `domain` at `x`: `x^n - 1`.
+pub fn vanishing<F: FftField>(domain: D<F>, x: F) -> F {
+    domain.evaluate_vanishing_polynomial(x)
+}
+
+/// Returns the element `w^(n - 3)` of `domain`,
+/// the first of the rows used for zero-knowledge.
+pub fn zk_w<F: FftField>(domain: D<F>) -> F {
+    zk_w3(domain)
+}
+
+/// Evaluates at `x` the polynomial vanishing on the rows of `domain` used for zero-knowledge:
+/// `(x - w^(n - 3)) (x - w^(n - 2)) (x - w^(n - 1))`.
+pub fn zkpm<F: FftField>(domain: D<F>, x: F) -> F {
+    eval_zk_polynomial(domain, x)
+}
+
+/// Evaluates at `x` the polynomial vanishing on the last 4 rows of `domain`:
+/// `(x - w^(n - 4)) (x - w^(n - 3)) (x - w^(n - 2)) (x - w^(n - 1))`.
+pub fn vanishes_on_last_4_rows<F: FftField>(domain: D<F>, x: F) -> F {
+    eval_vanishes_on_last_4_rows(domain, x)
+}
+
+/// Evaluates at `x` the `i`-th Lagrange polynomial of `domain`, without its normalization:
+/// `(x^n - 1) / (x - w^i)`, a negative `i` counting from the end of the domain.
+///
+/// # Panics
+///
+/// Will panic if `x` is `w^i`.
+pub fn unnormalized_lagrange_basis<F: FftField>(domain: &D<F>, i: i32, x: &F) -> F {
+    let omega_i = if i < 0 {
+        domain.group_gen.pow(&[-i as u64]).inverse().unwrap()
+    } else {
+        domain.group_gen.pow(&[i as u64])
+    };
+    domain.evaluate_vanishing_polynomial(*x) / (*x - omega_i)
+}
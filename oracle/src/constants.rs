@@ -1,3 +1,8 @@
+use crate::poseidon::ArithmeticSpongeParams;
+use ark_ff::{Field, PrimeField};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
 pub trait SpongeConstants {
     const SPONGE_CAPACITY: usize = 1;
     const SPONGE_WIDTH: usize = 3;
@@ -8,6 +13,10 @@ pub trait SpongeConstants {
     const PERM_SBOX: u32;
     const PERM_FULL_MDS: bool;
     const PERM_INITIAL_ARK: bool;
+    /// The number of limbs of 64 bits of the challenges squeezed from the sponge.
+    /// Challenges at least as long as the field they are squeezed into are full-width,
+    /// and reduced modulo its order.
+    const CHALLENGE_LENGTH_IN_LIMBS: usize = crate::sponge::CHALLENGE_LENGTH_IN_LIMBS;
 }
 
 #[derive(Clone)]
@@ -39,3 +48,246 @@ impl SpongeConstants for PlonkSpongeConstantsKimchi {
     const PERM_FULL_MDS: bool = true;
     const PERM_INITIAL_ARK: bool = false;
 }
+
+/// The number of full rounds added to the bounds of [`SpongeConfig::validate`] as a security margin
+pub const FULL_ROUNDS_MARGIN: usize = 2;
+
+/// The factor applied to the partial rounds required by [`SpongeConfig::validate`],
+/// in thousandths, as a security margin (7.5% more partial rounds)
+pub const PARTIAL_ROUNDS_MARGIN: usize = 1075;
+
+/// A set of sponge constants chosen at runtime, rather than with a [`SpongeConstants`] type,
+/// so that variants of the permutation (e.g. with fewer rounds, or a wider state)
+/// can be hashed with without recompiling.
+///
+/// With partial rounds, the permutation applies half of the full rounds,
+/// then the partial rounds, then the other half of the full rounds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpongeConfig {
+    /// the number of field elements of the state that are not absorbed into
+    pub capacity: usize,
+    /// the number of field elements absorbed or squeezed per permutation
+    pub rate: usize,
+    /// the number of full rounds
+    pub rounds_full: usize,
+    /// the number of partial rounds
+    pub rounds_partial: usize,
+    /// the exponent of the S-box
+    pub sbox: u32,
+    /// whether the MDS matrix of the parameters is used
+    /// (instead of a fixed matrix of a state of 3 field elements)
+    pub full_mds: bool,
+    /// whether round constants are added to the state before the first round
+    pub initial_ark: bool,
+}
+
+/// Errors of a [`SpongeConfig`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SpongeConfigError {
+    /// the capacity or the rate is zero
+    EmptyState,
+    /// the S-box is not a permutation of the field
+    InvalidSbox(u32),
+    /// the fixed MDS matrix is used with a state that is not of 3 field elements
+    UnsupportedMds(usize),
+    /// partial rounds are used with an odd number of full rounds
+    OddFullRounds(usize),
+    /// there are fewer rounds than the security margins require
+    InsufficientRounds {
+        /// which bound is not met
+        bound: &'static str,
+        /// the number of rounds the bound requires
+        required: usize,
+        /// the number of rounds of the configuration, once the security margins are taken off
+        actual: usize,
+    },
+    /// the parameters of the sponge do not match the configuration
+    Params(String),
+}
+
+impl fmt::Display for SpongeConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyState => write!(f, "the capacity and the rate must not be zero"),
+            Self::InvalidSbox(sbox) => write!(f, "x^{sbox} is not a permutation of the field"),
+            Self::UnsupportedMds(width) => write!(
+                f,
+                "the fixed MDS matrix is for a state of 3 field elements, not {width}"
+            ),
+            Self::OddFullRounds(rounds) => write!(
+                f,
+                "partial rounds require an even number of full rounds, not {rounds}"
+            ),
+            Self::InsufficientRounds {
+                bound,
+                required,
+                actual,
+            } => write!(
+                f,
+                "the {bound} bound requires {required} rounds, the configuration has {actual} once the margins are taken off"
+            ),
+            Self::Params(msg) => write!(f, "the sponge parameters do not match: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for SpongeConfigError {}
+
+impl SpongeConfig {
+    /// The configuration of the Kimchi sponge, see [`PlonkSpongeConstantsKimchi`]
+    pub const KIMCHI: Self = Self::of::<PlonkSpongeConstantsKimchi>();
+
+    /// The configuration of the legacy sponge, see [`PlonkSpongeConstantsLegacy`]
+    pub const LEGACY: Self = Self::of::<PlonkSpongeConstantsLegacy>();
+
+    /// Returns the configuration of the constants `SC`.
+    pub const fn of<SC: SpongeConstants>() -> Self {
+        let rounds_full = if SC::PERM_HALF_ROUNDS_FULL == 0 {
+            SC::PERM_ROUNDS_FULL
+        } else {
+            2 * SC::PERM_HALF_ROUNDS_FULL
+        };
+        Self {
+            capacity: SC::SPONGE_CAPACITY,
+            rate: SC::SPONGE_RATE,
+            rounds_full,
+            rounds_partial: SC::PERM_ROUNDS_PARTIAL,
+            sbox: SC::PERM_SBOX,
+            full_mds: SC::PERM_FULL_MDS,
+            initial_ark: SC::PERM_INITIAL_ARK,
+        }
+    }
+
+    /// Returns the number of field elements of the state.
+    pub fn width(&self) -> usize {
+        self.capacity + self.rate
+    }
+
+    /// Returns the number of rounds of the permutation.
+    pub fn rounds(&self) -> usize {
+        self.rounds_full + self.rounds_partial
+    }
+
+    /// Returns the number of round constants (each of [`SpongeConfig::width`] field elements)
+    /// the permutation uses.
+    pub fn round_constants(&self) -> usize {
+        self.rounds() + usize::from(self.initial_ark)
+    }
+
+    /// Checks that the configuration is well-formed over the field `F`,
+    /// and that it has enough rounds for `security_bits` bits of security.
+    ///
+    /// The rounds must meet the statistical and interpolation bounds of the Poseidon paper,
+    /// with [`FULL_ROUNDS_MARGIN`] more full rounds and [`PARTIAL_ROUNDS_MARGIN`] more partial rounds.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the configuration is malformed, or has too few rounds.
+    pub fn validate<F: PrimeField>(&self, security_bits: u32) -> Result<(), SpongeConfigError> {
+        if self.capacity == 0 || self.rate == 0 {
+            return Err(SpongeConfigError::EmptyState);
+        }
+        if !self.full_mds && self.width() != 3 {
+            return Err(SpongeConfigError::UnsupportedMds(self.width()));
+        }
+        if self.rounds_partial > 0 && self.rounds_full % 2 != 0 {
+            return Err(SpongeConfigError::OddFullRounds(self.rounds_full));
+        }
+
+        // x^a is a permutation of the field iff gcd(a, p - 1) = 1
+        if self.sbox < 3 {
+            return Err(SpongeConfigError::InvalidSbox(self.sbox));
+        }
+        let sbox = u64::from(self.sbox);
+        let p_minus_one = (mod_u64(F::characteristic(), sbox) + sbox - 1) % sbox;
+        if gcd(sbox, p_minus_one) != 1 {
+            return Err(SpongeConfigError::InvalidSbox(self.sbox));
+        }
+
+        let field_bits = f64::from(F::size_in_bits() as u32);
+        let security_bits = f64::from(security_bits);
+        let alpha = f64::from(self.sbox);
+        let width = self.width() as f64;
+
+        // statistical attacks
+        let required = if security_bits <= (field_bits - (alpha - 1.).log2()) * (width + 1.) {
+            6
+        } else {
+            10
+        };
+        let actual = self.rounds_full.saturating_sub(FULL_ROUNDS_MARGIN);
+        if actual < required {
+            return Err(SpongeConfigError::InsufficientRounds {
+                bound: "statistical",
+                required,
+                actual,
+            });
+        }
+
+        // interpolation attacks
+        let required = ((security_bits.min(field_bits) / alpha.log2()).ceil()
+            + width.log(alpha).ceil()) as usize;
+        let actual = self.rounds_full - FULL_ROUNDS_MARGIN
+            + self.rounds_partial * 1000 / PARTIAL_ROUNDS_MARGIN;
+        if actual < required {
+            return Err(SpongeConfigError::InsufficientRounds {
+                bound: "interpolation",
+                required,
+                actual,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Checks that `params` holds the round constants and the MDS matrix the configuration uses.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if there are too few round constants, or if they or the MDS matrix
+    /// do not have the width of the state.
+    pub fn check_params<F: Field>(
+        &self,
+        params: &ArithmeticSpongeParams<F>,
+    ) -> Result<(), SpongeConfigError> {
+        let width = self.width();
+        if params.round_constants.len() < self.round_constants() {
+            return Err(SpongeConfigError::Params(format!(
+                "{} round constants are required, {} are given",
+                self.round_constants(),
+                params.round_constants.len()
+            )));
+        }
+        if let Some(i) = params.round_constants[..self.round_constants()]
+            .iter()
+            .position(|rc| rc.len() != width)
+        {
+            return Err(SpongeConfigError::Params(format!(
+                "the round constant {i} is not of {width} field elements"
+            )));
+        }
+        if self.full_mds
+            && (params.mds.len() != width || params.mds.iter().any(|row| row.len() != width))
+        {
+            return Err(SpongeConfigError::Params(format!(
+                "the MDS matrix is not of {width} by {width} field elements"
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Returns `n mod m`, for `n` given as little-endian limbs.
+fn mod_u64(n: &[u64], m: u64) -> u64 {
+    n.iter().rev().fold(0, |acc, &limb| {
+        ((u128::from(acc) << 64 | u128::from(limb)) % u128::from(m)) as u64
+    })
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
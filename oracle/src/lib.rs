@@ -7,16 +7,45 @@ pub mod sponge;
 #[cfg(test)]
 mod tests;
 
+use ark_ec::ProjectiveCurve;
 use ark_ff::Field;
 
 pub trait FqSponge<Fq: Field, G, Fr> {
+    /// The number of limbs of 64 bits of the challenges squeezed from the sponge,
+    /// which the endomorphism map of a [`ScalarChallenge`](sponge::ScalarChallenge) runs over
+    /// (see [`ScalarChallenge::to_field_with_limbs`](sponge::ScalarChallenge::to_field_with_limbs)).
+    const CHALLENGE_LENGTH_IN_LIMBS: usize = sponge::CHALLENGE_LENGTH_IN_LIMBS;
+
     fn new(p: &'static poseidon::ArithmeticSpongeParams<Fq>) -> Self;
     fn absorb_g(&mut self, g: &[G]);
+
+    /// Absorbs points given in projective coordinates, as [`FqSponge::absorb_g`] would absorb
+    /// their affine forms, which are computed all at once (with a single field inversion).
+    /// Returns the affine forms, for the caller to reuse.
+    fn absorb_g_batch<P>(&mut self, g: &[P]) -> Vec<G>
+    where
+        P: ProjectiveCurve<Affine = G>,
+    {
+        let affine = P::batch_normalization_into_affine(g);
+        self.absorb_g(&affine);
+        affine
+    }
     fn absorb_fq(&mut self, x: &[Fq]);
     fn absorb_fr(&mut self, x: &[Fr]);
     fn challenge(&mut self) -> Fr;
     fn challenge_fq(&mut self) -> Fq;
 
+    /// Creates `n` challenges from the current state of the sponge,
+    /// the same as `n` successive calls to [`FqSponge::challenge`].
+    fn squeeze_n(&mut self, n: usize) -> Vec<Fr> {
+        (0..n).map(|_| self.challenge()).collect()
+    }
+
+    /// Squeezes `num_limbs` limbs of 64 bits, the limbs challenges are packed from.
+    /// The limbs left over from a squeeze are used by the next challenge,
+    /// until something is absorbed.
+    fn challenge_fq_limbs(&mut self, num_limbs: usize) -> Vec<u64>;
+
     fn digest(self) -> Fr;
     fn digest_fq(self) -> Fq;
 }
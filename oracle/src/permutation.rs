@@ -1,6 +1,6 @@
 //! The permutation module contains the function implementing the permutation used in Poseidon
 
-use crate::constants::SpongeConstants;
+use crate::constants::{SpongeConfig, SpongeConstants};
 use crate::poseidon::{sbox, ArithmeticSpongeParams};
 use ark_ff::Field;
 
@@ -103,3 +103,84 @@ pub fn poseidon_block_cipher<F: Field, SC: SpongeConstants>(
         half_rounds::<F, SC>(params, state);
     }
 }
+
+fn apply_mds_matrix_with_config<F: Field>(
+    config: &SpongeConfig,
+    params: &ArithmeticSpongeParams<F>,
+    state: &mut [F],
+) {
+    let new_state: Vec<F> = if config.full_mds {
+        params
+            .mds
+            .iter()
+            .map(|m| {
+                state
+                    .iter()
+                    .zip(m.iter())
+                    .fold(F::zero(), |x, (s, &m)| m * s + x)
+            })
+            .collect()
+    } else {
+        vec![
+            state[0] + state[2],
+            state[0] + state[1],
+            state[1] + state[2],
+        ]
+    };
+    state.copy_from_slice(&new_state);
+}
+
+/// Applies the round `r` of the permutation configured by `config` to `state`:
+/// the S-box (to the first element only if `partial`), the MDS matrix, then the round constant `r`.
+pub fn round_with_config<F: Field>(
+    config: &SpongeConfig,
+    params: &ArithmeticSpongeParams<F>,
+    state: &mut [F],
+    r: usize,
+    partial: bool,
+) {
+    let sbox = [u64::from(config.sbox)];
+    if partial {
+        state[0] = state[0].pow(sbox);
+    } else {
+        for state_i in state.iter_mut() {
+            *state_i = state_i.pow(sbox);
+        }
+    }
+    apply_mds_matrix_with_config(config, params, state);
+    for (state_i, x) in state.iter_mut().zip(params.round_constants[r].iter()) {
+        state_i.add_assign(x);
+    }
+}
+
+/// The permutation of Poseidon, with the constants of `config` rather than of a [`SpongeConstants`] type.
+/// It matches [`poseidon_block_cipher`] for the configuration [`SpongeConfig::of`] the constants.
+///
+/// # Panics
+///
+/// Will panic if `params` or `state` do not match `config` (see [`SpongeConfig::check_params`]).
+pub fn poseidon_block_cipher_with_config<F: Field>(
+    config: &SpongeConfig,
+    params: &ArithmeticSpongeParams<F>,
+    state: &mut [F],
+) {
+    assert_eq!(state.len(), config.width(), "the state has the wrong width");
+
+    let mut r = 0;
+    if config.initial_ark {
+        for (state_i, x) in state.iter_mut().zip(params.round_constants[0].iter()) {
+            state_i.add_assign(x);
+        }
+        r += 1;
+    }
+
+    let half_rounds = config.rounds_full / 2;
+    for round in 0..config.rounds() {
+        // without partial rounds, all the rounds are full
+        let partial = config.rounds_partial > 0
+            && round >= half_rounds
+            && round < half_rounds + config.rounds_partial;
+        round_with_config(config, params, state, r, partial);
+        r += 1;
+    }
+}
@@ -1,7 +1,7 @@
 //! This module implements Poseidon Hash Function primitive
 
-use crate::constants::SpongeConstants;
-use crate::permutation::{full_round, poseidon_block_cipher};
+use crate::constants::{SpongeConfig, SpongeConfigError, SpongeConstants};
+use crate::permutation::{full_round, poseidon_block_cipher, poseidon_block_cipher_with_config};
 use ark_ff::Field;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
@@ -127,3 +127,91 @@ impl<F: Field, SC: SpongeConstants> Sponge<F, F> for ArithmeticSponge<F, SC> {
         self.sponge_state = SpongeState::Absorbed(0);
     }
 }
+
+/// An arithmetic sponge whose constants are chosen at runtime, see [`SpongeConfig`].
+/// It absorbs and squeezes as [`ArithmeticSponge`] does.
+#[derive(Clone)]
+pub struct ConfiguredSponge<F: Field> {
+    pub config: SpongeConfig,
+    pub sponge_state: SpongeState,
+    pub state: Vec<F>,
+    params: ArithmeticSpongeParams<F>,
+}
+
+impl<F: Field> ConfiguredSponge<F> {
+    /// Creates a sponge of the configuration `config`, with the parameters `params`.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `params` does not match `config` (see [`SpongeConfig::check_params`]).
+    pub fn new(
+        config: SpongeConfig,
+        params: ArithmeticSpongeParams<F>,
+    ) -> Result<Self, SpongeConfigError> {
+        config.check_params(&params)?;
+        Ok(Self {
+            config,
+            sponge_state: SpongeState::Absorbed(0),
+            state: vec![F::zero(); config.width()],
+            params,
+        })
+    }
+
+    /// Returns the parameters of the sponge.
+    pub fn params(&self) -> &ArithmeticSpongeParams<F> {
+        &self.params
+    }
+
+    fn poseidon_block_cipher(&mut self) {
+        poseidon_block_cipher_with_config(&self.config, &self.params, &mut self.state);
+    }
+
+    /// Absorbs the field elements `x`.
+    pub fn absorb(&mut self, x: &[F]) {
+        for x in x.iter() {
+            match self.sponge_state {
+                SpongeState::Absorbed(n) => {
+                    if n == self.config.rate {
+                        self.poseidon_block_cipher();
+                        self.sponge_state = SpongeState::Absorbed(1);
+                        self.state[0].add_assign(x);
+                    } else {
+                        self.sponge_state = SpongeState::Absorbed(n + 1);
+                        self.state[n].add_assign(x);
+                    }
+                }
+                SpongeState::Squeezed(_n) => {
+                    self.state[0].add_assign(x);
+                    self.sponge_state = SpongeState::Absorbed(1);
+                }
+            }
+        }
+    }
+
+    /// Squeezes a field element.
+    pub fn squeeze(&mut self) -> F {
+        match self.sponge_state {
+            SpongeState::Squeezed(n) => {
+                if n == self.config.rate {
+                    self.poseidon_block_cipher();
+                    self.sponge_state = SpongeState::Squeezed(1);
+                    self.state[0]
+                } else {
+                    self.sponge_state = SpongeState::Squeezed(n + 1);
+                    self.state[n]
+                }
+            }
+            SpongeState::Absorbed(_n) => {
+                self.poseidon_block_cipher();
+                self.sponge_state = SpongeState::Squeezed(1);
+                self.state[0]
+            }
+        }
+    }
+
+    /// Resets the sponge back to its initial state.
+    pub fn reset(&mut self) {
+        self.state = vec![F::zero(); self.state.len()];
+        self.sponge_state = SpongeState::Absorbed(0);
+    }
+}
@@ -2,6 +2,8 @@ use crate::constants::SpongeConstants;
 use crate::poseidon::{ArithmeticSponge, ArithmeticSpongeParams, Sponge};
 use ark_ec::{short_weierstrass_jacobian::GroupAffine, SWModelParameters};
 use ark_ff::{BigInteger, Field, FpParameters, One, PrimeField, Zero};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
 
 pub use crate::FqSponge;
 
@@ -11,8 +13,10 @@ const HIGH_ENTROPY_LIMBS: usize = 2;
 
 // TODO: move to a different file / module
 /// A challenge which is used as a scalar on a group element in the verifier
-#[derive(Clone, Debug)]
-pub struct ScalarChallenge<F>(pub F);
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "F: Field")]
+pub struct ScalarChallenge<F>(#[serde_as(as = "o1_utils::serialization::SerdeAs")] pub F);
 
 pub fn endo_coefficient<F: PrimeField>() -> F {
     let p_minus_1_over_3 = (F::zero() - F::one()) / F::from(3u64);
@@ -60,6 +64,25 @@ impl<F: PrimeField> ScalarChallenge<F> {
         let length_in_bits = 64 * CHALLENGE_LENGTH_IN_LIMBS;
         self.to_field_with_length(length_in_bits, endo_coeff)
     }
+
+    /// Like [`ScalarChallenge::to_field`], for a challenge of `length_in_limbs` limbs of 64 bits
+    /// (see [`FqSponge::CHALLENGE_LENGTH_IN_LIMBS`](crate::FqSponge::CHALLENGE_LENGTH_IN_LIMBS)).
+    pub fn to_field_with_limbs(&self, length_in_limbs: usize, endo_coeff: &F) -> F {
+        self.to_field_with_length(challenge_length_in_bits::<F>(length_in_limbs), endo_coeff)
+    }
+
+    /// Like [`ScalarChallenge::to_field`], for a challenge squeezed from a sponge with constants `SC`.
+    pub fn to_field_with_constants<SC: SpongeConstants>(&self, endo_coeff: &F) -> F {
+        self.to_field_with_limbs(SC::CHALLENGE_LENGTH_IN_LIMBS, endo_coeff)
+    }
+}
+
+/// Returns the length in bits of the challenges of `length_in_limbs` limbs squeezed into `F`.
+/// Full-width challenges are as long as the field, rounded up to an even length
+/// for the endomorphism map.
+pub fn challenge_length_in_bits<F: PrimeField>(length_in_limbs: usize) -> usize {
+    let size = F::size_in_bits();
+    (64 * length_in_limbs).min(size + size % 2)
 }
 
 #[derive(Clone)]
@@ -82,20 +105,31 @@ fn pack<B: BigInteger>(limbs_lsb: &[u64]) -> B {
     res
 }
 
+/// Packs the limbs of a challenge into a field element,
+/// reducing full-width challenges modulo the order of the field.
+fn challenge_from_limbs<F: PrimeField>(limbs_lsb: &[u64]) -> F {
+    if 64 * limbs_lsb.len() < F::size_in_bits() {
+        F::from_repr(pack(limbs_lsb))
+            .expect("internal representation was not a valid field element")
+    } else {
+        let bytes: Vec<u8> = limbs_lsb.iter().flat_map(|x| x.to_le_bytes()).collect();
+        F::from_le_bytes_mod_order(&bytes)
+    }
+}
+
 impl<Fr: PrimeField, SC: SpongeConstants> DefaultFrSponge<Fr, SC> {
-    pub fn squeeze(&mut self, num_limbs: usize) -> Fr {
-        if self.last_squeezed.len() >= num_limbs {
-            let last_squeezed = self.last_squeezed.clone();
-            let (limbs, remaining) = last_squeezed.split_at(num_limbs);
-            self.last_squeezed = remaining.to_vec();
-            Fr::from_repr(pack::<Fr::BigInt>(limbs))
-                .expect("internal representation was not a valid field element")
-        } else {
+    pub fn squeeze_limbs(&mut self, num_limbs: usize) -> Vec<u64> {
+        while self.last_squeezed.len() < num_limbs {
             let x = self.sponge.squeeze().into_repr();
             self.last_squeezed
                 .extend(&x.as_ref()[0..HIGH_ENTROPY_LIMBS]);
-            self.squeeze(num_limbs)
         }
+        let remaining = self.last_squeezed.split_off(num_limbs);
+        std::mem::replace(&mut self.last_squeezed, remaining)
+    }
+
+    pub fn squeeze(&mut self, num_limbs: usize) -> Fr {
+        challenge_from_limbs(&self.squeeze_limbs(num_limbs))
     }
 }
 
@@ -105,17 +139,13 @@ where
     <P::BaseField as PrimeField>::BigInt: Into<<P::ScalarField as PrimeField>::BigInt>,
 {
     pub fn squeeze_limbs(&mut self, num_limbs: usize) -> Vec<u64> {
-        if self.last_squeezed.len() >= num_limbs {
-            let last_squeezed = self.last_squeezed.clone();
-            let (limbs, remaining) = last_squeezed.split_at(num_limbs);
-            self.last_squeezed = remaining.to_vec();
-            limbs.to_vec()
-        } else {
+        while self.last_squeezed.len() < num_limbs {
             let x = self.sponge.squeeze().into_repr();
             self.last_squeezed
                 .extend(&x.as_ref()[0..HIGH_ENTROPY_LIMBS]);
-            self.squeeze_limbs(num_limbs)
         }
+        let remaining = self.last_squeezed.split_off(num_limbs);
+        std::mem::replace(&mut self.last_squeezed, remaining)
     }
 
     pub fn squeeze_field(&mut self) -> P::BaseField {
@@ -124,8 +154,7 @@ where
     }
 
     pub fn squeeze(&mut self, num_limbs: usize) -> P::ScalarField {
-        P::ScalarField::from_repr(pack(&self.squeeze_limbs(num_limbs)))
-            .expect("internal representation was not a valid field element")
+        challenge_from_limbs(&self.squeeze_limbs(num_limbs))
     }
 }
 
@@ -135,6 +164,8 @@ where
     P::BaseField: PrimeField,
     <P::BaseField as PrimeField>::BigInt: Into<<P::ScalarField as PrimeField>::BigInt>,
 {
+    const CHALLENGE_LENGTH_IN_LIMBS: usize = SC::CHALLENGE_LENGTH_IN_LIMBS;
+
     fn new(params: &'static ArithmeticSpongeParams<P::BaseField>) -> DefaultFqSponge<P, SC> {
         DefaultFqSponge {
             sponge: ArithmeticSponge::new(params),
@@ -144,16 +175,14 @@ where
 
     fn absorb_g(&mut self, g: &[GroupAffine<P>]) {
         self.last_squeezed = vec![];
-        for g in g.iter() {
-            if g.infinity {
-                // absorb a fake point (0, 0)
-                let zero = P::BaseField::zero();
-                self.sponge.absorb(&[zero, zero]);
-            } else {
-                self.sponge.absorb(&[g.x]);
-                self.sponge.absorb(&[g.y]);
-            }
-        }
+        // the coordinates of the points are absorbed at once,
+        // the point at infinity as a fake point (0, 0)
+        let zero = P::BaseField::zero();
+        let coordinates: Vec<_> = g
+            .iter()
+            .flat_map(|g| if g.infinity { [zero, zero] } else { [g.x, g.y] })
+            .collect();
+        self.sponge.absorb(&coordinates);
     }
 
     fn absorb_fq(&mut self, x: &[P::BaseField]) {
@@ -210,12 +239,24 @@ where
     }
 
     fn challenge(&mut self) -> P::ScalarField {
-        self.squeeze(CHALLENGE_LENGTH_IN_LIMBS)
+        self.squeeze(SC::CHALLENGE_LENGTH_IN_LIMBS)
     }
 
     fn challenge_fq(&mut self) -> P::BaseField {
         self.squeeze_field()
     }
+
+    fn squeeze_n(&mut self, n: usize) -> Vec<P::ScalarField> {
+        // the limbs of all the challenges are squeezed at once
+        self.squeeze_limbs(n * SC::CHALLENGE_LENGTH_IN_LIMBS)
+            .chunks(SC::CHALLENGE_LENGTH_IN_LIMBS)
+            .map(challenge_from_limbs)
+            .collect()
+    }
+
+    fn challenge_fq_limbs(&mut self, num_limbs: usize) -> Vec<u64> {
+        self.squeeze_limbs(num_limbs)
+    }
 }
 
 //
@@ -0,0 +1,106 @@
+use crate::{
+    constants::{
+        PlonkSpongeConstantsKimchi, PlonkSpongeConstantsLegacy, SpongeConfig, SpongeConfigError,
+    },
+    pasta::{fp_kimchi, fp_legacy},
+    poseidon::{ArithmeticSponge, ConfiguredSponge, Sponge as _},
+};
+use mina_curves::pasta::Fp;
+
+#[test]
+fn test_configured_sponge_matches_constants() {
+    let input: Vec<_> = (0..5u64).map(Fp::from).collect();
+
+    let mut sponge =
+        ArithmeticSponge::<Fp, PlonkSpongeConstantsKimchi>::new(fp_kimchi::static_params());
+    sponge.absorb(&input);
+    let mut configured = ConfiguredSponge::new(SpongeConfig::KIMCHI, fp_kimchi::params()).unwrap();
+    configured.absorb(&input);
+    assert_eq!(configured.squeeze(), sponge.squeeze());
+    assert_eq!(configured.squeeze(), sponge.squeeze());
+
+    let mut sponge =
+        ArithmeticSponge::<Fp, PlonkSpongeConstantsLegacy>::new(fp_legacy::static_params());
+    sponge.absorb(&input);
+    let mut configured =
+        ConfiguredSponge::new(SpongeConfig::LEGACY, fp_legacy::static_params().clone()).unwrap();
+    configured.absorb(&input);
+    assert_eq!(configured.squeeze(), sponge.squeeze());
+}
+
+#[test]
+fn test_sponge_config_validation() {
+    SpongeConfig::KIMCHI.validate::<Fp>(128).unwrap();
+    SpongeConfig::LEGACY.validate::<Fp>(128).unwrap();
+
+    // fewer rounds, even if they hash
+    let reduced = SpongeConfig {
+        rounds_full: 20,
+        ..SpongeConfig::KIMCHI
+    };
+    assert!(matches!(
+        reduced.validate::<Fp>(128),
+        Err(SpongeConfigError::InsufficientRounds {
+            bound: "interpolation",
+            ..
+        })
+    ));
+    let mut sponge = ConfiguredSponge::new(reduced, fp_kimchi::params()).unwrap();
+    sponge.absorb(&[Fp::from(1u64)]);
+    sponge.squeeze();
+
+    // partial rounds
+    let partial = SpongeConfig {
+        rounds_full: 8,
+        rounds_partial: 56,
+        ..SpongeConfig::KIMCHI
+    };
+    partial.validate::<Fp>(128).unwrap();
+    assert!(matches!(
+        ConfiguredSponge::new(partial, fp_kimchi::params()),
+        Err(SpongeConfigError::Params(_))
+    ));
+    assert_eq!(
+        SpongeConfig {
+            rounds_full: 9,
+            ..partial
+        }
+        .validate::<Fp>(128),
+        Err(SpongeConfigError::OddFullRounds(9))
+    );
+    assert!(matches!(
+        SpongeConfig {
+            rounds_full: 6,
+            rounds_partial: 80,
+            ..partial
+        }
+        .validate::<Fp>(128),
+        Err(SpongeConfigError::InsufficientRounds {
+            bound: "statistical",
+            ..
+        })
+    ));
+
+    // S-boxes that are not permutations
+    for sbox in [1, 2, 4] {
+        assert_eq!(
+            SpongeConfig {
+                sbox,
+                ..SpongeConfig::KIMCHI
+            }
+            .validate::<Fp>(128),
+            Err(SpongeConfigError::InvalidSbox(sbox))
+        );
+    }
+
+    // the fixed MDS matrix is of width 3
+    assert_eq!(
+        SpongeConfig {
+            rate: 3,
+            full_mds: false,
+            ..SpongeConfig::KIMCHI
+        }
+        .validate::<Fp>(128),
+        Err(SpongeConfigError::UnsupportedMds(4))
+    );
+}
@@ -1 +1,3 @@
+mod config_tests;
 mod poseidon_tests;
+mod sponge_tests;
@@ -0,0 +1,107 @@
+use crate::{
+    constants::{PlonkSpongeConstantsKimchi as SC, SpongeConstants},
+    pasta::fq_kimchi,
+    sponge::{challenge_length_in_bits, endo_coefficient, DefaultFqSponge, ScalarChallenge},
+    FqSponge,
+};
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{BigInteger, PrimeField, Zero};
+use mina_curves::pasta::{Fp, Fq, Vesta, VestaParameters};
+
+type Sponge = DefaultFqSponge<VestaParameters, SC>;
+
+#[test]
+fn test_squeeze_n() {
+    let mut sponge = Sponge::new(fq_kimchi::static_params());
+    sponge.absorb_fq(&[Fq::from(42u64)]);
+    let mut expected = sponge.clone();
+
+    // a leftover limb is used by the first challenge, as it would be by `challenge`
+    assert_eq!(sponge.challenge_fq_limbs(1), expected.challenge_fq_limbs(1));
+    let challenges = sponge.squeeze_n(5);
+    let sequential: Vec<Fp> = (0..5).map(|_| expected.challenge()).collect();
+    assert_eq!(challenges, sequential);
+    assert_eq!(sponge.last_squeezed, expected.last_squeezed);
+    assert_eq!(sponge.challenge(), expected.challenge());
+
+    // absorbing drops the leftover limbs
+    sponge.absorb_fq(&[Fq::from(1u64)]);
+    expected.absorb_fq(&[Fq::from(1u64)]);
+    assert!(sponge.last_squeezed.is_empty());
+    assert_eq!(
+        sponge.squeeze_n(3),
+        (0..3).map(|_| expected.challenge()).collect::<Vec<_>>()
+    );
+    assert!(sponge.squeeze_n(0).is_empty());
+}
+
+#[test]
+fn test_absorb_g_batch() {
+    let g = Vesta::prime_subgroup_generator().into_projective();
+    // including the point at infinity
+    let points = [
+        g,
+        g.double(),
+        <Vesta as AffineCurve>::Projective::zero(),
+        g.double() + g,
+    ];
+
+    // the batch is absorbed as the affine points would be
+    let mut sponge = Sponge::new(fq_kimchi::static_params());
+    let affine = sponge.absorb_g_batch(&points);
+    let mut expected = Sponge::new(fq_kimchi::static_params());
+    expected.absorb_g(&points.map(|p| p.into_affine()));
+    assert_eq!(affine, points.map(|p| p.into_affine()));
+    assert_eq!(sponge.challenge(), expected.challenge());
+
+    assert!(sponge
+        .absorb_g_batch::<<Vesta as AffineCurve>::Projective>(&[])
+        .is_empty());
+}
+
+/// The Kimchi constants, with full-width challenges.
+struct FullWidthConstants;
+
+impl SpongeConstants for FullWidthConstants {
+    const PERM_ROUNDS_FULL: usize = SC::PERM_ROUNDS_FULL;
+    const PERM_ROUNDS_PARTIAL: usize = SC::PERM_ROUNDS_PARTIAL;
+    const PERM_HALF_ROUNDS_FULL: usize = SC::PERM_HALF_ROUNDS_FULL;
+    const PERM_SBOX: u32 = SC::PERM_SBOX;
+    const PERM_FULL_MDS: bool = SC::PERM_FULL_MDS;
+    const PERM_INITIAL_ARK: bool = SC::PERM_INITIAL_ARK;
+    const CHALLENGE_LENGTH_IN_LIMBS: usize = 4;
+}
+
+#[test]
+fn test_challenge_length() {
+    let mut sponge =
+        DefaultFqSponge::<VestaParameters, FullWidthConstants>::new(fq_kimchi::static_params());
+    let mut expected = Sponge::new(fq_kimchi::static_params());
+    sponge.absorb_fq(&[Fq::from(42u64)]);
+    expected.absorb_fq(&[Fq::from(42u64)]);
+
+    // a full-width challenge is made of the limbs of two default challenges, reduced
+    let limbs = expected.challenge_fq_limbs(4);
+    let bytes: Vec<u8> = limbs.iter().flat_map(|x| x.to_le_bytes()).collect();
+    let challenge = sponge.challenge();
+    assert_eq!(challenge, Fp::from_le_bytes_mod_order(&bytes));
+    assert!(challenge.into_repr().num_bits() > 128);
+
+    // the endomorphism map runs over the whole challenge
+    assert_eq!(challenge_length_in_bits::<Fp>(4), 256);
+    assert_eq!(challenge_length_in_bits::<Fp>(2), 128);
+    assert_eq!(
+        <DefaultFqSponge<VestaParameters, FullWidthConstants> as FqSponge<_, _, _>>::CHALLENGE_LENGTH_IN_LIMBS,
+        4
+    );
+    let endo = endo_coefficient::<Fp>();
+    let chal = ScalarChallenge(challenge);
+    assert_eq!(
+        chal.to_field_with_constants::<FullWidthConstants>(&endo),
+        chal.to_field_with_length(256, &endo)
+    );
+    assert_eq!(
+        chal.to_field_with_constants::<SC>(&endo),
+        chal.to_field(&endo)
+    );
+}
@@ -0,0 +1,167 @@
+//! This module implements a standalone check of IPA accumulators, for light clients.
+//!
+//! The verification of an opening proof (see [`SRS::verify`]) has a succinct part,
+//! checking the proof against its evaluations with a few scalar operations,
+//! and an expensive part, checking that the point `sg` of the proof commits to
+//! the polynomial `b(X) = (1 + chal[-1] X)(1 + chal[-2] X^2)...` of its challenges
+//! (see [`b_poly`](crate::commitment::b_poly)), with an MSM of the size of the SRS.
+//! The pair `(chals, sg)` is the [`IpaAccumulator`] of the proof.
+//!
+//! A client that trusts the succinct part, for example because a recursive proof verified it,
+//! only needs to check the accumulators.
+//! An [`AccumulatorCheck`] folds them one at a time with random scalars,
+//! so that a whole batch is checked with a single MSM by [`AccumulatorCheck::verify`].
+//! Its state is serializable, and checks started independently can be combined
+//! with [`AccumulatorCheck::merge`], so that stateless clients can split the work
+//! across messages or blocks.
+
+use crate::{
+    commitment::{b_poly_coefficients_into, CommitmentCurve},
+    error::AccumulatorError,
+    evaluation_proof::MAX_ROUNDS,
+    srs::SRS,
+};
+use ark_ec::{msm::VariableBaseMSM, AffineCurve, ProjectiveCurve};
+use ark_ff::{PrimeField, UniformRand, Zero};
+use o1_utils::{
+    math,
+    serialization::{Bounded, SerdeAs},
+};
+use rand_core::{CryptoRng, RngCore};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+
+/// The accumulator of an opening proof: its challenges,
+/// and the commitment `sg` to the polynomial of its challenges.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IpaAccumulator<G: CommitmentCurve> {
+    /// The challenges of the opening proof
+    #[serde_as(as = "Bounded<SerdeAs, MAX_ROUNDS>")]
+    pub chals: Vec<G::ScalarField>,
+    /// The claimed commitment to the polynomial of the challenges
+    #[serde_as(as = "SerdeAs")]
+    pub sg: G,
+}
+
+/// The check of a batch of [`IpaAccumulator`], against an SRS.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccumulatorCheck<G: CommitmentCurve> {
+    /// The number of rounds of the opening proofs of the SRS
+    rounds: usize,
+    /// The random combination of the coefficients of the polynomials of the accumulators
+    #[serde_as(as = "Vec<SerdeAs>")]
+    scalars: Vec<G::ScalarField>,
+    /// The same random combination of the commitments of the accumulators
+    #[serde_as(as = "SerdeAs")]
+    sg: G,
+    /// The number of accumulators added so far
+    count: usize,
+}
+
+impl<G: CommitmentCurve> AccumulatorCheck<G> {
+    /// Starts an empty check against `srs`.
+    pub fn new(srs: &SRS<G>) -> Self {
+        let rounds = math::ceil_log2(srs.g.len());
+        Self {
+            rounds,
+            scalars: vec![G::ScalarField::zero(); 1 << rounds],
+            sg: G::zero(),
+            count: 0,
+        }
+    }
+
+    /// Returns the number of accumulators added to the check.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Returns `true` if no accumulator was added to the check.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Adds `accumulator` to the check, scaled by a fresh random scalar.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the accumulator has more challenges than the SRS supports.
+    pub fn add(
+        &mut self,
+        accumulator: &IpaAccumulator<G>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(), AccumulatorError> {
+        let rounds = accumulator.chals.len();
+        if rounds > self.rounds {
+            return Err(AccumulatorError::Rounds {
+                accumulator: self.count,
+                rounds,
+                max: self.rounds,
+            });
+        }
+
+        // like in the opening proof, fewer challenges only use the first generators
+        let mut s = vec![G::ScalarField::zero(); 1 << rounds];
+        b_poly_coefficients_into(&accumulator.chals, &mut s);
+
+        let r = G::ScalarField::rand(rng);
+        self.scalars
+            .par_iter_mut()
+            .zip(s.par_iter())
+            .for_each(|(scalar, s)| *scalar += r * s);
+        self.sg = (self.sg.into_projective() + accumulator.sg.mul(r)).into_affine();
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Combines the check with `other`, which must be against the same SRS.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the checks are not for the same number of rounds.
+    pub fn merge(&mut self, other: &Self) -> Result<(), AccumulatorError> {
+        if self.rounds != other.rounds || self.scalars.len() != other.scalars.len() {
+            return Err(AccumulatorError::Merge {
+                rounds: self.rounds,
+                other: other.rounds,
+            });
+        }
+
+        self.scalars
+            .par_iter_mut()
+            .zip(other.scalars.par_iter())
+            .for_each(|(scalar, other)| *scalar += other);
+        self.sg = (self.sg.into_projective() + other.sg.into_projective()).into_affine();
+        self.count += other.count;
+        Ok(())
+    }
+
+    /// Checks all the accumulators added so far against `srs`, with a single MSM.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the check was started against another SRS,
+    /// or if an accumulator does not commit to the polynomial of its challenges.
+    pub fn verify(&self, srs: &SRS<G>) -> Result<(), AccumulatorError> {
+        let srs_rounds = math::ceil_log2(srs.g.len());
+        if srs_rounds != self.rounds || self.scalars.len() != 1 << srs_rounds {
+            return Err(AccumulatorError::Srs {
+                rounds: self.rounds,
+                srs_rounds,
+            });
+        }
+
+        // the SRS is padded with zero generators up to a power of two
+        let scalars: Vec<_> = self.scalars[..srs.g.len()]
+            .iter()
+            .map(|s| s.into_repr())
+            .collect();
+        let expected = VariableBaseMSM::multi_scalar_mul(&srs.g, &scalars);
+        if expected != self.sg.into_projective() {
+            return Err(AccumulatorError::Commitment);
+        }
+        Ok(())
+    }
+}
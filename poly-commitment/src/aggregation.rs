@@ -0,0 +1,222 @@
+//! This module implements the aggregation of opening claims against the same SRS
+//! into a single opening proof.
+//!
+//! Each opening claim is a batch of polynomials, all opened at all the points of the claim,
+//! as [`SRS::open`] would prove it.
+//! Proving `N` claims separately ships `N` opening proofs, each with its own folding challenges.
+//! An aggregator instead absorbs all the claims (their points, commitments and evaluations)
+//! in a sponge, derives the scaling factors once, and proves all the claims with a single
+//! multi-point opening (see [`crate::multi_point`]): each claim is a set of points
+//! of the combined claim, so that the proof holds a single commitment, an evaluation per claim,
+//! and a single opening proof.
+//!
+//! The protocol allows it when the claims are against the same SRS, and do not use degree bounds.
+//! The points of a claim must be distinct, but claims can share points.
+
+use crate::commitment::*;
+use crate::error::CommitmentError;
+use crate::multi_point::{
+    MultiPointEvaluation, MultiPointEvaluationProof, MultiPointOpeningProof, MultiPointPolynomial,
+};
+use crate::srs::SRS;
+use ark_ec::AffineCurve;
+use ark_ff::{Field, PrimeField};
+use ark_poly::{univariate::DensePolynomial, Polynomial, UVPolynomial};
+use oracle::FqSponge;
+use rand_core::{CryptoRng, RngCore};
+
+/// An opening claim to aggregate: polynomials, with their commitments, all opened at all the points.
+pub struct OpeningClaim<'a, G: CommitmentCurve> {
+    /// The polynomials, with their commitments and the blinding factors of the commitments
+    pub polys: Vec<(
+        &'a DensePolynomial<G::ScalarField>,
+        &'a BlindedCommitment<G>,
+    )>,
+    /// The evaluation points, which must be distinct
+    pub points: Vec<G::ScalarField>,
+}
+
+/// The evaluations of an opening claim: of all its commitments at all its points.
+pub struct ClaimEvaluations<G: AffineCurve> {
+    /// The commitments and their evaluations, with a row per point
+    pub evaluations: Vec<Evaluation<G>>,
+    /// The evaluation points
+    pub points: Vec<G::ScalarField>,
+}
+
+/// An aggregated opening proof, with the claims it proves
+pub struct AggregatedEvaluationProof<'a, G, EFqSponge>
+where
+    G: AffineCurve,
+    EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
+{
+    /// The sponge the claims are absorbed in
+    pub sponge: EFqSponge,
+    /// The claims
+    pub claims: Vec<ClaimEvaluations<G>>,
+    /// The opening proof of all the claims
+    pub opening: &'a MultiPointOpeningProof<G>,
+}
+
+/// Returns the evaluations at `x` of the `chunks` chunks of `n` coefficients of `poly`.
+fn chunk_evaluations<F: Field>(poly: &DensePolynomial<F>, n: usize, chunks: usize, x: F) -> Vec<F> {
+    let len = poly.coeffs.len();
+    (0..chunks)
+        .map(|j| {
+            let chunk = &poly.coeffs[std::cmp::min(j * n, len)..std::cmp::min((j + 1) * n, len)];
+            DensePolynomial::from_coefficients_slice(chunk).evaluate(&x)
+        })
+        .collect()
+}
+
+/// Returns whether the `points` are distinct.
+fn distinct<F: Field>(points: &[F]) -> bool {
+    points
+        .iter()
+        .enumerate()
+        .all(|(i, x)| !points[..i].contains(x))
+}
+
+/// Absorbs the shape and the points of a claim.
+fn absorb_claim_header<G, EFqSponge>(
+    sponge: &mut EFqSponge,
+    polys: usize,
+    points: &[G::ScalarField],
+) where
+    G: AffineCurve,
+    EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
+{
+    sponge.absorb_fr(&[
+        G::ScalarField::from(polys as u64),
+        G::ScalarField::from(points.len() as u64),
+    ]);
+    sponge.absorb_fr(points);
+}
+
+impl<G: CommitmentCurve> SRS<G> {
+    /// This function proves all the opening `claims` with a single opening proof
+    /// (see the module documentation).
+    ///
+    /// # Errors
+    ///
+    /// Will give error if a claim has no points or repeats a point, or if a commitment has a degree bound.
+    pub fn open_aggregated<EFqSponge, RNG>(
+        &self,
+        group_map: &G::Map,
+        claims: &[OpeningClaim<G>],
+        mut sponge: EFqSponge,
+        rng: &mut RNG,
+    ) -> Result<MultiPointOpeningProof<G>, CommitmentError>
+    where
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+        RNG: RngCore + CryptoRng,
+        G::BaseField: PrimeField,
+    {
+        let n = self.g.len();
+
+        // the points of each claim are a set of points of the combined claim
+        let mut elm = vec![];
+        let mut plnms = vec![];
+        for (i, claim) in claims.iter().enumerate() {
+            if claim.points.is_empty() {
+                return Err(CommitmentError::ClaimWithoutPoints(i));
+            }
+            if !distinct(&claim.points) {
+                return Err(CommitmentError::ClaimRepeatsPoint(i));
+            }
+            let points: Vec<_> = (elm.len()..elm.len() + claim.points.len()).collect();
+
+            absorb_claim_header::<G, _>(&mut sponge, claim.polys.len(), &claim.points);
+            for (poly, comm) in &claim.polys {
+                if comm.commitment.shifted.is_some() {
+                    return Err(CommitmentError::ClaimWithDegreeBound(i));
+                }
+                sponge.absorb_g(&comm.commitment.unshifted);
+                let chunks = comm.commitment.unshifted.len();
+                for x in &claim.points {
+                    sponge.absorb_fr(&chunk_evaluations(poly, n, chunks, *x));
+                }
+
+                plnms.push(MultiPointPolynomial {
+                    poly,
+                    blinders: comm.blinders.clone(),
+                    points: points.clone(),
+                });
+            }
+            elm.extend_from_slice(&claim.points);
+        }
+
+        let polyscale = sponge.challenge();
+        let evalscale = sponge.challenge();
+        Ok(self.open_multi_point(group_map, &plnms, &elm, polyscale, evalscale, sponge, rng))
+    }
+
+    /// This function verifies a batch of aggregated opening proofs
+    ///     batch: batch of aggregated opening proofs, with their claims
+    ///     randomness source context
+    ///     RETURN: verification status
+    pub fn verify_aggregated<EFqSponge, RNG>(
+        &self,
+        group_map: &G::Map,
+        batch: &mut [AggregatedEvaluationProof<G, EFqSponge>],
+        rng: &mut RNG,
+    ) -> bool
+    where
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+        RNG: RngCore + CryptoRng,
+        G::BaseField: PrimeField,
+    {
+        let mut reduced = vec![];
+        for AggregatedEvaluationProof {
+            sponge,
+            claims,
+            opening,
+        } in batch.iter_mut()
+        {
+            let mut elm = vec![];
+            let mut evaluations = vec![];
+            for claim in claims.iter() {
+                if claim.points.is_empty() || !distinct(&claim.points) {
+                    return false;
+                }
+                let points: Vec<_> = (elm.len()..elm.len() + claim.points.len()).collect();
+
+                absorb_claim_header::<G, _>(sponge, claim.evaluations.len(), &claim.points);
+                for e in &claim.evaluations {
+                    let chunks = e.commitment.unshifted.len();
+                    if e.degree_bound.is_some()
+                        || e.commitment.shifted.is_some()
+                        || e.evaluations.len() != claim.points.len()
+                        || e.evaluations.iter().any(|evals| evals.len() != chunks)
+                    {
+                        return false;
+                    }
+                    sponge.absorb_g(&e.commitment.unshifted);
+                    for evals in &e.evaluations {
+                        sponge.absorb_fr(evals);
+                    }
+
+                    evaluations.push(MultiPointEvaluation {
+                        commitment: e.commitment.clone(),
+                        points: points.clone(),
+                        evaluations: e.evaluations.clone(),
+                    });
+                }
+                elm.extend_from_slice(&claim.points);
+            }
+
+            let polyscale = sponge.challenge();
+            let evalscale = sponge.challenge();
+            reduced.push(MultiPointEvaluationProof {
+                sponge: sponge.clone(),
+                evaluations,
+                evaluation_points: elm,
+                polyscale,
+                evalscale,
+                opening: *opening,
+            });
+        }
+
+        self.verify_multi_point(group_map, &mut reduced, rng)
+    }
+}
@@ -0,0 +1,304 @@
+//! This module implements an updatable ceremony creating a structured reference string
+//! made of the powers `[G, tau G, tau^2 G, ...]` of a secret `tau`,
+//! as used by KZG commitments, with `G` the generator of the curve.
+//!
+//! The [`Accumulator`] of a ceremony starts with `tau = 1`.
+//! Each participant multiplies `tau` by a secret `s` of their own with [`Accumulator::contribute`],
+//! and publishes the resulting [`Contribution`], which anyone can check with [`Accumulator::update`].
+//! `tau` stays unknown as long as a single participant discarded their secret.
+//!
+//! A contribution proves that its powers `y_i` are the powers `x_i` of the accumulator
+//! re-randomized by `s`, that is `y_i = s^i x_i`, without pairings.
+//! For a challenge `r`, it gives the partial sums `H_j = sum_{i >= j} r^i s^(i - j) x_i`,
+//! and proves that `H_j - r^j x_j = s H_(j + 1)` for all `j`
+//! (batched with the powers of another challenge)
+//! with a proof of discrete logarithm equality against its public key `s G`.
+//! As the last partial sum is `r^(n - 1) x_(n - 1)`, the first one is then `sum_i r^i s^i x_i`,
+//! which must be `sum_i r^i y_i`.
+//!
+//! [`Accumulator::verify_transcript`] replays a whole ceremony from its contributions,
+//! and [`Accumulator::into_srs`] extracts the resulting [`SRS`].
+
+use crate::commitment::CommitmentCurve;
+use crate::error::CeremonyError;
+use crate::srs::SRS;
+use ark_ec::{msm::VariableBaseMSM, AffineCurve, ProjectiveCurve};
+use ark_ff::{Field, PrimeField, UniformRand, Zero};
+use groupmap::GroupMap;
+use oracle::FqSponge;
+use rand_core::{CryptoRng, RngCore};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+
+/// The state of a ceremony: the powers of the secret `tau`,
+/// and the public keys of the contributions so far.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Accumulator<G: CommitmentCurve> {
+    /// The powers `tau^i G` of the secret
+    #[serde_as(as = "Vec<o1_utils::serialization::SerdeAs>")]
+    pub powers: Vec<G>,
+    /// The public keys of the contributions, in order
+    #[serde_as(as = "Vec<o1_utils::serialization::SerdeAs>")]
+    pub public_keys: Vec<G>,
+}
+
+/// A contribution to a ceremony, re-randomizing the powers of an [`Accumulator`]
+/// by the secret `s` of a participant.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Contribution<G: CommitmentCurve> {
+    /// The re-randomized powers `s^i tau^i G`
+    #[serde_as(as = "Vec<o1_utils::serialization::SerdeAs>")]
+    pub powers: Vec<G>,
+    /// The public key `s G` of the participant
+    #[serde_as(as = "o1_utils::serialization::SerdeAs")]
+    pub public_key: G,
+    /// The partial sums `H_j` of the proof of re-randomization
+    #[serde_as(as = "Vec<o1_utils::serialization::SerdeAs>")]
+    pub partial_sums: Vec<G>,
+    /// The commitment `k G` of the proof of discrete logarithm equality
+    #[serde_as(as = "o1_utils::serialization::SerdeAs")]
+    pub commitment_g: G,
+    /// The commitment `k A` of the proof of discrete logarithm equality,
+    /// with `A` the batched right-hand sides of the partial sum equalities
+    #[serde_as(as = "o1_utils::serialization::SerdeAs")]
+    pub commitment_a: G,
+    /// The response `k + c s` of the proof of discrete logarithm equality
+    #[serde_as(as = "o1_utils::serialization::SerdeAs")]
+    pub response: G::ScalarField,
+}
+
+/// Returns `[1, x, ..., x^(n - 1)]`.
+fn powers_of<F: Field>(x: F, n: usize) -> Vec<F> {
+    std::iter::successors(Some(F::one()), |p| Some(*p * x))
+        .take(n)
+        .collect()
+}
+
+fn msm<G: CommitmentCurve>(bases: &[G], scalars: &[G::ScalarField]) -> G::Projective {
+    let scalars: Vec<_> = scalars.iter().map(|s| s.into_repr()).collect();
+    VariableBaseMSM::multi_scalar_mul(bases, &scalars)
+}
+
+/// Absorbs the powers before and after a contribution, and its public key,
+/// and returns the challenge `r` of the partial sums.
+fn partial_sums_challenge<G, EFqSponge>(
+    sponge: &mut EFqSponge,
+    before: &[G],
+    after: &[G],
+    public_key: G,
+) -> G::ScalarField
+where
+    G: CommitmentCurve,
+    EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
+{
+    sponge.absorb_g(before);
+    sponge.absorb_g(after);
+    sponge.absorb_g(&[public_key]);
+    sponge.challenge()
+}
+
+/// Batches the equalities `H_j - r^j x_j = s H_(j + 1)` with the powers of `rho`,
+/// and returns their sides without `s`:
+/// `(sum_j rho^j H_(j + 1), sum_j rho^j (H_j - r^j x_j))`.
+fn batch_equalities<G: CommitmentCurve>(
+    powers: &[G],
+    partial_sums: &[G],
+    r: G::ScalarField,
+    rho: G::ScalarField,
+) -> (G::Projective, G::Projective) {
+    let n = powers.len();
+    let rhos = powers_of(rho, n - 1);
+    let left = msm(&partial_sums[1..], &rhos);
+    let right =
+        msm(&partial_sums[..n - 1], &rhos) - msm(&powers[..n - 1], &powers_of(rho * r, n - 1));
+    (left, right)
+}
+
+impl<G: CommitmentCurve> Accumulator<G> {
+    /// Starts a ceremony for an SRS of `depth` generators.
+    pub fn new(depth: usize) -> Self {
+        assert!(depth > 0, "a ceremony needs at least one power");
+        Self {
+            powers: vec![G::prime_subgroup_generator(); depth],
+            public_keys: vec![],
+        }
+    }
+
+    /// Re-randomizes the powers of the accumulator with a fresh secret,
+    /// and returns the resulting contribution along with its proof.
+    /// The secret is dropped on return.
+    pub fn contribute<EFqSponge>(
+        &self,
+        mut sponge: EFqSponge,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Contribution<G>
+    where
+        EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
+    {
+        let n = self.powers.len();
+        let generator = G::prime_subgroup_generator();
+
+        let s = loop {
+            let s = G::ScalarField::rand(rng);
+            if !s.is_zero() {
+                break s;
+            }
+        };
+        let public_key = generator.mul(s).into_affine();
+
+        // y_i = s^i x_i
+        let powers: Vec<_> = self
+            .powers
+            .par_iter()
+            .zip(powers_of(s, n))
+            .map(|(x, e)| x.mul(e))
+            .collect();
+        let powers = G::Projective::batch_normalization_into_affine(&powers);
+
+        // H_j = s^-j sum_{i >= j} r^i y_i
+        let r = partial_sums_challenge(&mut sponge, &self.powers, &powers, public_key);
+        let mut sums: Vec<_> = powers
+            .par_iter()
+            .zip(powers_of(r, n))
+            .map(|(y, e)| y.mul(e))
+            .collect();
+        for j in (0..n - 1).rev() {
+            let next = sums[j + 1];
+            sums[j] += next;
+        }
+        let s_inv = s.inverse().expect("the secret is not zero");
+        sums.par_iter_mut()
+            .zip(powers_of(s_inv, n))
+            .for_each(|(sum, e)| *sum = sum.mul(e.into_repr()));
+        let partial_sums = G::Projective::batch_normalization_into_affine(&sums);
+
+        sponge.absorb_g(&partial_sums);
+        let rho = sponge.challenge();
+        let (left, _) = batch_equalities(&self.powers, &partial_sums, r, rho);
+
+        // proof that log_G(s G) = log_A(B) = s, for the sides (A, B) of the batched equalities
+        let k = G::ScalarField::rand(rng);
+        let commitment_g = generator.mul(k).into_affine();
+        let commitment_a = left.mul(k.into_repr()).into_affine();
+        sponge.absorb_g(&[commitment_g, commitment_a]);
+        let c = sponge.challenge();
+
+        Contribution {
+            powers,
+            public_key,
+            partial_sums,
+            commitment_g,
+            commitment_a,
+            response: k + c * s,
+        }
+    }
+
+    /// Checks the proof of `contribution`, and applies it to the accumulator.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the contribution is malformed or its proof is invalid,
+    /// in which case the accumulator is left unchanged.
+    pub fn update<EFqSponge>(
+        &mut self,
+        contribution: Contribution<G>,
+        mut sponge: EFqSponge,
+    ) -> Result<(), CeremonyError>
+    where
+        EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
+    {
+        let index = self.public_keys.len();
+        let n = self.powers.len();
+
+        if contribution.powers.len() != n {
+            return Err(CeremonyError::Powers {
+                contribution: index,
+                powers: contribution.powers.len(),
+                expected: n,
+            });
+        }
+        if contribution.partial_sums.len() != n {
+            return Err(CeremonyError::PartialSums {
+                contribution: index,
+                sums: contribution.partial_sums.len(),
+                expected: n,
+            });
+        }
+        if contribution.powers[0] != self.powers[0] {
+            return Err(CeremonyError::FirstPower {
+                contribution: index,
+            });
+        }
+        if contribution.public_key.is_zero() {
+            return Err(CeremonyError::PublicKey {
+                contribution: index,
+            });
+        }
+
+        let r = partial_sums_challenge(
+            &mut sponge,
+            &self.powers,
+            &contribution.powers,
+            contribution.public_key,
+        );
+        sponge.absorb_g(&contribution.partial_sums);
+        let rho = sponge.challenge();
+        sponge.absorb_g(&[contribution.commitment_g, contribution.commitment_a]);
+        let c = sponge.challenge();
+
+        // the partial sums end with r^(n - 1) x_(n - 1), and start with sum_i r^i y_i
+        let rs = powers_of(r, n);
+        let last = self.powers[n - 1].mul(rs[n - 1]);
+        let first = msm(&contribution.powers, &rs);
+        let bounds = contribution.partial_sums[n - 1].into_projective() == last
+            && contribution.partial_sums[0].into_projective() == first;
+
+        // and follow each other by a factor s
+        let (left, right) = batch_equalities(&self.powers, &contribution.partial_sums, r, rho);
+        let z = contribution.response;
+        let dleq = G::prime_subgroup_generator().mul(z)
+            == contribution.commitment_g.into_projective() + contribution.public_key.mul(c)
+            && left.mul(z.into_repr())
+                == contribution.commitment_a.into_projective() + right.mul(c.into_repr());
+
+        if !(bounds && dleq) {
+            return Err(CeremonyError::Rerandomization {
+                contribution: index,
+            });
+        }
+
+        self.powers = contribution.powers;
+        self.public_keys.push(contribution.public_key);
+        Ok(())
+    }
+
+    /// Replays a ceremony for an SRS of `depth` generators from its `contributions`,
+    /// and returns the resulting accumulator.
+    ///
+    /// # Errors
+    ///
+    /// Will give error on the first contribution rejected by [`Accumulator::update`].
+    pub fn verify_transcript<EFqSponge>(
+        depth: usize,
+        contributions: impl IntoIterator<Item = Contribution<G>>,
+        sponge: EFqSponge,
+    ) -> Result<Self, CeremonyError>
+    where
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    {
+        let mut accumulator = Self::new(depth);
+        for contribution in contributions {
+            accumulator.update(contribution, sponge.clone())?;
+        }
+        Ok(accumulator)
+    }
+
+    /// Returns the SRS made of the powers of the accumulator,
+    /// along with the same blinding generator as [`SRS::create`].
+    pub fn into_srs(self) -> SRS<G> {
+        SRS::with_generators(&G::Map::setup(), self.powers)
+    }
+}
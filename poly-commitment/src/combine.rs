@@ -21,6 +21,7 @@ use ark_ec::{
 };
 use ark_ff::{BitIteratorBE, Field, One, PrimeField, Zero};
 use itertools::Itertools;
+use o1_utils::curve_helpers::batch_normalize;
 use oracle::sponge::ScalarChallenge;
 use rayon::prelude::*;
 
@@ -286,12 +287,13 @@ fn batch_negate_in_place<P: SWModelParameters>(ps: &mut [SWJAffine<P>]) {
 }
 
 /// Uses a batch version of Algorithm 1 of https://eprint.iacr.org/2019/1021.pdf (on page 19) to
-/// compute `g1 + g2.scale(chal.to_field(endo_coeff))`
+/// compute `g1 + g2.scale(chal.to_field_with_length(length_in_bits, endo_coeff))`
 fn affine_window_combine_one_endo_base<P: SWModelParameters>(
     endo_coeff: P::BaseField,
     g1: &[SWJAffine<P>],
     g2: &[SWJAffine<P>],
     chal: ScalarChallenge<P::ScalarField>,
+    length_in_bits: usize,
 ) -> Vec<SWJAffine<P>> {
     fn assign<A: Copy>(dst: &mut [A], src: &[A]) {
         let n = dst.len();
@@ -316,7 +318,7 @@ fn affine_window_combine_one_endo_base<P: SWModelParameters>(
 
     let mut tmp_s = g2.to_vec();
     let mut tmp_acc = g2.to_vec();
-    for i in (0..(128 / 2)).rev() {
+    for i in (0..(length_in_bits as u64 / 2)).rev() {
         // s = g2
         assign(&mut tmp_s, g2);
         // tmp = acc
@@ -428,7 +430,8 @@ pub fn affine_window_combine<P: SWModelParameters>(
 }
 
 /// Given vectors of curve points `g1` and `g2`, compute a vector whose ith entry is
-/// `g1[i] + g2[i].scale(chal.to_field(endo_coeff))`
+/// `g1[i] + g2[i].scale(chal.to_field_with_length(length_in_bits, endo_coeff))`
+/// (see [`challenge_length_in_bits`](oracle::sponge::challenge_length_in_bits)).
 ///
 /// Internally, it uses the curve endomorphism to speed up this operation.
 pub fn affine_window_combine_one_endo<P: SWModelParameters>(
@@ -436,12 +439,15 @@ pub fn affine_window_combine_one_endo<P: SWModelParameters>(
     g1: &[SWJAffine<P>],
     g2: &[SWJAffine<P>],
     chal: ScalarChallenge<P::ScalarField>,
+    length_in_bits: usize,
 ) -> Vec<SWJAffine<P>> {
     const CHUNK_SIZE: usize = 4096;
     let b: Vec<_> = g1.chunks(CHUNK_SIZE).zip(g2.chunks(CHUNK_SIZE)).collect();
     let v: Vec<_> = b
         .into_par_iter()
-        .map(|(v1, v2)| affine_window_combine_one_endo_base(endo_coeff, v1, v2, chal.clone()))
+        .map(|(v1, v2)| {
+            affine_window_combine_one_endo_base(endo_coeff, v1, v2, chal.clone(), length_in_bits)
+        })
         .collect();
     v.concat()
 }
@@ -465,15 +471,14 @@ pub fn window_combine<G: AffineCurve>(
     x_lo: G::ScalarField,
     x_hi: G::ScalarField,
 ) -> Vec<G> {
-    let mut g_proj: Vec<G::Projective> = {
+    let g_proj: Vec<G::Projective> = {
         let pairs: Vec<_> = g_lo.iter().zip(g_hi).collect();
         pairs
             .into_par_iter()
             .map(|(lo, hi)| window_shamir::<G>(x_lo, *lo, x_hi, *hi))
             .collect()
     };
-    G::Projective::batch_normalization(g_proj.as_mut_slice());
-    g_proj.par_iter().map(|g| g.into_affine()).collect()
+    batch_normalize(&g_proj)
 }
 
 pub fn affine_shamir_window_table<P: SWModelParameters>(
@@ -700,12 +705,11 @@ pub fn shamir_window_table<G: AffineCurve>(g1: G, g2: G) -> [G; 16] {
         g
     };
 
-    let mut v = vec![
+    let v = [
         g00_00, g01_00, g10_00, g11_00, g00_01, g01_01, g10_01, g11_01, g00_10, g01_10, g10_10,
         g11_10, g00_11, g01_11, g10_11, g11_11,
     ];
-    G::Projective::batch_normalization(v.as_mut_slice());
-    let v: Vec<_> = v.iter().map(|x| x.into_affine()).collect();
+    let v = batch_normalize(&v);
     [
         v[0], v[1], v[2], v[3], v[4], v[5], v[6], v[7], v[8], v[9], v[10], v[11], v[12], v[13],
         v[14], v[15],
@@ -6,7 +6,11 @@
 //!     producing the batched opening proof
 //! 3. Verify batch of batched opening proofs
 
-use crate::{error::CommitmentError, srs::SRS};
+use crate::{
+    error::CommitmentError,
+    msm::{self, ScalarRepr},
+    srs::SRS,
+};
 use ark_ec::{
     models::short_weierstrass_jacobian::GroupAffine as SWJAffine, msm::VariableBaseMSM,
     AffineCurve, ProjectiveCurve, SWModelParameters,
@@ -16,21 +20,31 @@ use ark_ff::{
 };
 use ark_poly::{
     univariate::DensePolynomial, EvaluationDomain, Evaluations, Radix2EvaluationDomain as D,
+    UVPolynomial,
 };
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use core::ops::{Add, Sub};
 use groupmap::{BWParameters, GroupMap};
+use o1_utils::curve_helpers::batch_normalize;
 use o1_utils::math;
+use o1_utils::serialization::{Bounded, SerdeAs};
 use o1_utils::ExtendedDensePolynomial as _;
-use oracle::{sponge::ScalarChallenge, FqSponge};
+use oracle::{
+    sponge::{challenge_length_in_bits, ScalarChallenge},
+    FqSponge,
+};
 use rand_core::{CryptoRng, RngCore};
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
+use std::collections::HashMap;
 use std::iter::Iterator;
 
 use super::evaluation_proof::*;
 
+/// The maximum number of chunks of a polynomial commitment accepted when deserializing
+pub const MAX_CHUNKS: usize = 1 << 10;
+
 /// A polynomial commitment.
 #[serde_as]
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -38,7 +52,7 @@ pub struct PolyComm<C>
 where
     C: CanonicalDeserialize + CanonicalSerialize,
 {
-    #[serde_as(as = "Vec<o1_utils::serialization::SerdeAs>")]
+    #[serde_as(as = "Bounded<SerdeAs, MAX_CHUNKS>")]
     pub unshifted: Vec<C>,
     #[serde_as(as = "Option<o1_utils::serialization::SerdeAs>")]
     pub shifted: Option<C>,
@@ -188,12 +202,12 @@ impl<'a, 'b, C: AffineCurve> Sub<&'a PolyComm<C>> for &'b PolyComm<C> {
             } else if i < n1 {
                 self.unshifted[i]
             } else {
-                other.unshifted[i]
+                -other.unshifted[i]
             };
             unshifted.push(pt);
         }
         let shifted = match (self.shifted, other.shifted) {
-            (None, _) => other.shifted,
+            (None, _) => other.shifted.map(|p| -p),
             (_, None) => self.shifted,
             (Some(p1), Some(p2)) => Some(p1 + (-p2)),
         };
@@ -202,55 +216,56 @@ impl<'a, 'b, C: AffineCurve> Sub<&'a PolyComm<C>> for &'b PolyComm<C> {
 }
 
 impl<C: AffineCurve> PolyComm<C> {
+    /// Returns the commitment of the chunks `unshifted` and `shifted` in projective form,
+    /// all normalized at once (see [`batch_normalize`]).
+    pub fn from_projective(unshifted: &[C::Projective], shifted: Option<C::Projective>) -> Self {
+        let mut points = unshifted.to_vec();
+        points.extend(shifted);
+        let mut unshifted = batch_normalize(&points);
+        let shifted = shifted.map(|_| unshifted.pop().unwrap());
+        PolyComm { unshifted, shifted }
+    }
+
     pub fn scale(&self, c: C::ScalarField) -> PolyComm<C> {
-        PolyComm {
-            unshifted: self
-                .unshifted
-                .iter()
-                .map(|g| g.mul(c).into_affine())
-                .collect(),
-            shifted: self.shifted.map(|g| g.mul(c).into_affine()),
-        }
+        let unshifted: Vec<_> = self.unshifted.iter().map(|g| g.mul(c)).collect();
+        PolyComm::from_projective(&unshifted, self.shifted.map(|g| g.mul(c)))
     }
 
     pub fn multi_scalar_mul(com: &[&PolyComm<C>], elm: &[C::ScalarField]) -> Self {
         assert_eq!(com.len(), elm.len());
-        PolyComm::<C> {
-            shifted: {
-                let pairs = com
-                    .iter()
-                    .zip(elm.iter())
-                    .filter_map(|(c, s)| c.shifted.map(|c| (c, s)))
-                    .collect::<Vec<_>>();
-                if pairs.is_empty() {
-                    None
-                } else {
-                    let points = pairs.iter().map(|(c, _)| *c).collect::<Vec<_>>();
-                    let scalars = pairs.iter().map(|(_, s)| s.into_repr()).collect::<Vec<_>>();
-                    Some(VariableBaseMSM::multi_scalar_mul(&points, &scalars).into_affine())
-                }
-            },
-            unshifted: {
-                if com.is_empty() || elm.is_empty() {
-                    vec![C::zero()]
-                } else {
-                    let n = Iterator::max(com.iter().map(|c| c.unshifted.len())).unwrap();
-                    (0..n)
-                        .map(|i| {
-                            let mut points = Vec::new();
-                            let mut scalars = Vec::new();
-                            com.iter().zip(elm.iter()).for_each(|(p, s)| {
-                                if i < p.unshifted.len() {
-                                    points.push(p.unshifted[i]);
-                                    scalars.push(s.into_repr())
-                                }
-                            });
-                            VariableBaseMSM::multi_scalar_mul(&points, &scalars).into_affine()
-                        })
-                        .collect::<Vec<_>>()
-                }
-            },
-        }
+        let shifted = {
+            let pairs = com
+                .iter()
+                .zip(elm.iter())
+                .filter_map(|(c, s)| c.shifted.map(|c| (c, s)))
+                .collect::<Vec<_>>();
+            if pairs.is_empty() {
+                None
+            } else {
+                let points = pairs.iter().map(|(c, _)| *c).collect::<Vec<_>>();
+                let scalars = pairs.iter().map(|(_, s)| s.into_repr()).collect::<Vec<_>>();
+                Some(msm::multi_scalar_mul(&points, &scalars))
+            }
+        };
+        let unshifted = if com.is_empty() || elm.is_empty() {
+            vec![C::Projective::zero()]
+        } else {
+            let n = Iterator::max(com.iter().map(|c| c.unshifted.len())).unwrap();
+            (0..n)
+                .map(|i| {
+                    let mut points = Vec::new();
+                    let mut scalars = Vec::new();
+                    com.iter().zip(elm.iter()).for_each(|(p, s)| {
+                        if i < p.unshifted.len() {
+                            points.push(p.unshifted[i]);
+                            scalars.push(s.into_repr())
+                        }
+                    });
+                    msm::multi_scalar_mul(&points, &scalars)
+                })
+                .collect::<Vec<_>>()
+        };
+        PolyComm::from_projective(&unshifted, shifted)
     }
 }
 
@@ -267,31 +282,39 @@ pub fn product<F: Field>(xs: impl Iterator<Item = F>) -> F {
 /// It's "step 8: Define the univariate polynomial" of
 /// appendix A.2 of <https://eprint.iacr.org/2020/499>
 pub fn b_poly<F: Field>(chals: &[F], x: F) -> F {
-    let k = chals.len();
-
-    let mut pow_twos = vec![x];
-
-    for i in 1..k {
-        pow_twos.push(pow_twos[i - 1].square());
+    let mut res = F::one();
+    // x^(2^i), for the i-th challenge from the end
+    let mut pow_two = x;
+    for chal in chals.iter().rev() {
+        res *= F::one() + (*chal * pow_two);
+        pow_two.square_in_place();
     }
-
-    product((0..k).map(|i| (F::one() + (chals[i] * pow_twos[k - 1 - i]))))
+    res
 }
 
 pub fn b_poly_coefficients<F: Field>(chals: &[F]) -> Vec<F> {
-    let rounds = chals.len();
-    let s_length = 1 << rounds;
-    let mut s = vec![F::one(); s_length];
-    let mut k: usize = 0;
-    let mut pow: usize = 1;
-    for i in 1..s_length {
-        k += if i == pow { 1 } else { 0 };
-        pow <<= if i == pow { 1 } else { 0 };
-        s[i] = s[i - (pow >> 1)] * chals[rounds - 1 - (k - 1)];
-    }
+    let mut s = vec![F::zero(); 1 << chals.len()];
+    b_poly_coefficients_into(chals, &mut s);
     s
 }
 
+/// Writes the coefficients of [`b_poly`] into `s`, whose length must be `2^chals.len()`,
+/// so that the same buffer can be reused across openings.
+pub fn b_poly_coefficients_into<F: Field>(chals: &[F], s: &mut [F]) {
+    assert_eq!(s.len(), 1 << chals.len());
+    s[0] = F::one();
+    // s[2^j + i] = s[i] * chal[-1 - j]
+    let mut len = 1;
+    for chal in chals.iter().rev() {
+        let (lo, hi) = s.split_at_mut(len);
+        hi[..len]
+            .par_iter_mut()
+            .zip(lo.par_iter())
+            .for_each(|(h, l)| *h = *l * chal);
+        len *= 2;
+    }
+}
+
 /// `pows(d, x)` returns a vector containing the first `d` powers of the field element `x` (from `1` to `x^(d-1)`).
 pub fn pows<F: Field>(d: usize, x: F) -> Vec<F> {
     let mut acc = F::one();
@@ -318,13 +341,13 @@ pub fn squeeze_challenge<
     endo_r: &Fr,
     sponge: &mut EFqSponge,
 ) -> Fr {
-    squeeze_prechallenge(sponge).to_field(endo_r)
+    squeeze_prechallenge(sponge).to_field_with_limbs(EFqSponge::CHALLENGE_LENGTH_IN_LIMBS, endo_r)
 }
 
 pub trait CommitmentCurve: AffineCurve<BaseField = Self::CommitmentField> {
     type CommitmentField: PrimeField;
     type Params: SWModelParameters;
-    type Map: GroupMap<Self::BaseField>;
+    type Map: GroupMap<Self::BaseField> + Sync;
 
     fn to_coordinates(&self) -> Option<(Self::BaseField, Self::BaseField)>;
     fn of_coordinates(x: Self::BaseField, y: Self::BaseField) -> Self;
@@ -334,15 +357,18 @@ pub trait CommitmentCurve: AffineCurve<BaseField = Self::CommitmentField> {
         crate::combine::window_combine(g1, g2, Self::ScalarField::one(), x2)
     }
 
-    /// Combine where x1 = one
+    /// Combine where x1 = one, and x2 is a challenge of `length_in_limbs` limbs
+    /// (see [`ScalarChallenge::to_field_with_limbs`])
     fn combine_one_endo(
         endo_r: Self::ScalarField,
         _endo_q: Self::BaseField,
         g1: &[Self],
         g2: &[Self],
         x2: ScalarChallenge<Self::ScalarField>,
+        length_in_limbs: usize,
     ) -> Vec<Self> {
-        crate::combine::window_combine(g1, g2, Self::ScalarField::one(), x2.to_field(&endo_r))
+        let x2 = x2.to_field_with_limbs(length_in_limbs, &endo_r);
+        crate::combine::window_combine(g1, g2, Self::ScalarField::one(), x2)
     }
 
     fn combine(
@@ -385,8 +411,10 @@ where
         g1: &[Self],
         g2: &[Self],
         x2: ScalarChallenge<Self::ScalarField>,
+        length_in_limbs: usize,
     ) -> Vec<Self> {
-        crate::combine::affine_window_combine_one_endo(endo_q, g1, g2, x2)
+        let length_in_bits = challenge_length_in_bits::<Self::ScalarField>(length_in_limbs);
+        crate::combine::affine_window_combine_one_endo(endo_q, g1, g2, x2, length_in_bits)
     }
 
     fn combine(
@@ -409,50 +437,68 @@ pub fn to_group<G: CommitmentCurve>(m: &G::Map, t: <G as AffineCurve>::BaseField
 /// and the columns represent potential segments (if a polynomial was split in several parts).
 /// Note that if one of the polynomial comes specified with a degree bound,
 /// the evaluation for the last segment is potentially shifted to meet the proof.
+/// The polynomials are combined in parallel.
 #[allow(clippy::type_complexity)]
 pub fn combined_inner_product<F: PrimeField>(
     evaluation_points: &[F],
     polyscale: &F,
     evalscale: &F,
     // TODO(mimoo): needs a type that can get you evaluations or segments
-    polys: &[(Vec<Vec<F>>, Option<usize>)],
+    polys: &[(Vec<&[F]>, Option<usize>)],
     srs_length: usize,
 ) -> F {
-    let mut res = F::zero();
-    let mut xi_i = F::one();
-
-    for (evals_tr, shifted) in polys.iter().filter(|(evals_tr, _)| !evals_tr[0].is_empty()) {
-        // transpose the evaluations
-        let evals = (0..evals_tr[0].len())
-            .map(|i| evals_tr.iter().map(|v| v[i]).collect::<Vec<_>>())
-            .collect::<Vec<_>>();
-
-        // iterating over the polynomial segments
-        for eval in &evals {
-            let term = DensePolynomial::<F>::eval_polynomial(eval, *evalscale);
-
-            res += &(xi_i * term);
-            xi_i *= polyscale;
-        }
-
-        if let Some(m) = shifted {
-            // polyscale^i sum_j evalscale^j elm_j^{N - m} f(elm_j)
-            let last_evals = if *m >= evals.len() * srs_length {
-                vec![F::zero(); evaluation_points.len()]
-            } else {
-                evals[evals.len() - 1].clone()
-            };
-            let shifted_evals: Vec<_> = evaluation_points
-                .iter()
-                .zip(&last_evals)
-                .map(|(elm, f_elm)| elm.pow(&[(srs_length - (*m) % srs_length) as u64]) * f_elm)
-                .collect();
+    let polys: Vec<_> = polys
+        .iter()
+        .filter(|(evals, _)| !evals[0].is_empty())
+        .collect();
+
+    // each segment is scaled by the next power of polyscale, as well as each shifted segment
+    let mut powers = 0;
+    let offsets: Vec<_> = polys
+        .iter()
+        .map(|(evals, shifted)| {
+            let offset = powers;
+            powers += evals[0].len() + usize::from(shifted.is_some());
+            offset
+        })
+        .collect();
+    let xi: Vec<_> = std::iter::successors(Some(F::one()), |x| Some(*x * polyscale))
+        .take(powers)
+        .collect();
+
+    polys
+        .par_iter()
+        .zip(offsets.par_iter())
+        .map(|((evals, shifted), offset)| {
+            let segments = evals[0].len();
+            let mut res = F::zero();
+
+            // iterating over the polynomial segments
+            for i in 0..segments {
+                let eval: Vec<_> = evals.iter().map(|e| e[i]).collect();
+                let term = DensePolynomial::<F>::eval_polynomial(&eval, *evalscale);
+                res += xi[offset + i] * term;
+            }
 
-            res += &(xi_i * DensePolynomial::<F>::eval_polynomial(&shifted_evals, *evalscale));
-            xi_i *= polyscale;
-        }
-    }
-    res
+            if let Some(m) = shifted {
+                // polyscale^i sum_j evalscale^j elm_j^{N - m} f(elm_j)
+                let shifted_evals: Vec<_> = if *m >= segments * srs_length {
+                    vec![F::zero(); evaluation_points.len()]
+                } else {
+                    evaluation_points
+                        .iter()
+                        .zip(evals.iter())
+                        .map(|(elm, e)| {
+                            elm.pow(&[(srs_length - (*m) % srs_length) as u64]) * e[segments - 1]
+                        })
+                        .collect()
+                };
+                let term = DensePolynomial::<F>::eval_polynomial(&shifted_evals, *evalscale);
+                res += xi[offset + segments] * term;
+            }
+            res
+        })
+        .reduce(F::zero, |a, b| a + b)
 }
 
 /// Contains the evaluation of a polynomial commitment at a set of points.
@@ -470,9 +516,10 @@ where
     pub degree_bound: Option<usize>,
 }
 
-/// Contains the batch evaluation
+/// Contains the batch evaluation,
+/// along with its opening proof (by default, that of the inner product argument of the [`SRS`])
 // TODO: I think we should really change this name to something more correct
-pub struct BatchEvaluationProof<'a, G, EFqSponge>
+pub struct BatchEvaluationProof<'a, G, EFqSponge, Opening = OpeningProof<G>>
 where
     G: AffineCurve,
     EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
@@ -486,7 +533,7 @@ where
     /// scaling factor for polynomials
     pub evalscale: G::ScalarField,
     /// batched opening proof
-    pub opening: &'a OpeningProof<G>,
+    pub opening: &'a Opening,
 }
 
 impl<G: CommitmentCurve> SRS<G> {
@@ -516,14 +563,16 @@ impl<G: CommitmentCurve> SRS<G> {
         com: PolyComm<G>,
         blinders: &PolyComm<G::ScalarField>,
     ) -> Result<BlindedCommitment<G>, CommitmentError> {
-        let commitment = com
+        let pairs = com
             .zip(blinders)
-            .ok_or_else(|| CommitmentError::BlindersDontMatch(blinders.len(), com.len()))?
-            .map(|(g, b)| {
-                let mut g_masked = self.h.mul(b);
-                g_masked.add_assign_mixed(&g);
-                g_masked.into_affine()
-            });
+            .ok_or_else(|| CommitmentError::BlindersDontMatch(blinders.len(), com.len()))?;
+        let mask = |(g, b): (G, G::ScalarField)| {
+            let mut g_masked = self.h.mul(b);
+            g_masked.add_assign_mixed(&g);
+            g_masked
+        };
+        let unshifted: Vec<_> = pairs.unshifted.into_iter().map(mask).collect();
+        let commitment = PolyComm::from_projective(&unshifted, pairs.shifted.map(mask));
         Ok(BlindedCommitment {
             commitment,
             blinders: blinders.clone(),
@@ -541,7 +590,16 @@ impl<G: CommitmentCurve> SRS<G> {
         plnm: &DensePolynomial<G::ScalarField>,
         max: Option<usize>,
     ) -> PolyComm<G> {
-        Self::commit_helper(&plnm.coeffs[..], &self.g[..], None, plnm.is_zero(), max)
+        match &self.msm_table {
+            Some(table) => Self::commit_chunks(
+                &plnm.coeffs[..],
+                table.len(),
+                plnm.is_zero(),
+                max,
+                |offset, scalars| table.multi_scalar_mul(offset, scalars),
+            ),
+            None => Self::commit_helper(&plnm.coeffs[..], &self.g[..], None, plnm.is_zero(), max),
+        }
     }
 
     pub fn commit_helper(
@@ -555,22 +613,35 @@ impl<G: CommitmentCurve> SRS<G> {
             Some(n) => n,
             None => basis.len(),
         };
+        Self::commit_chunks(scalars, n, is_zero, max, |offset, scalars| {
+            msm::multi_scalar_mul(&basis[offset..], scalars)
+        })
+    }
+
+    /// Commits to the chunks of `scalars` of size `n`, as [`SRS::commit_helper`],
+    /// with `multi_scalar_mul` returning the MSM of the scalars it is given with the basis from the point `offset`.
+    fn commit_chunks(
+        scalars: &[G::ScalarField],
+        n: usize,
+        is_zero: bool,
+        max: Option<usize>,
+        multi_scalar_mul: impl Fn(usize, &[ScalarRepr<G>]) -> G::Projective,
+    ) -> PolyComm<G> {
         let p = scalars.len();
 
         // committing all the segments without shifting
-        let unshifted = if is_zero {
-            vec![G::zero()]
+        let unshifted: Vec<_> = if is_zero {
+            vec![G::Projective::zero()]
         } else {
             (0..p / n + if p % n != 0 { 1 } else { 0 })
                 .map(|i| {
-                    VariableBaseMSM::multi_scalar_mul(
-                        basis,
+                    multi_scalar_mul(
+                        0,
                         &scalars[i * n..p]
                             .iter()
                             .map(|s| s.into_repr())
                             .collect::<Vec<_>>(),
                     )
-                    .into_affine()
                 })
                 .collect()
         };
@@ -581,27 +652,28 @@ impl<G: CommitmentCurve> SRS<G> {
             Some(max) => {
                 let start = max - (max % n);
                 if is_zero || start >= p {
-                    Some(G::zero())
+                    Some(G::Projective::zero())
                 } else if max % n == 0 {
                     None
                 } else {
-                    Some(
-                        VariableBaseMSM::multi_scalar_mul(
-                            &basis[n - (max % n)..],
-                            &scalars[start..p]
-                                .iter()
-                                .map(|s| s.into_repr())
-                                .collect::<Vec<_>>(),
-                        )
-                        .into_affine(),
-                    )
+                    Some(multi_scalar_mul(
+                        n - (max % n),
+                        &scalars[start..p]
+                            .iter()
+                            .map(|s| s.into_repr())
+                            .collect::<Vec<_>>(),
+                    ))
                 }
             }
         };
 
-        PolyComm::<G> { unshifted, shifted }
+        PolyComm::from_projective(&unshifted, shifted)
     }
 
+    /// Commits to the polynomial of evaluations `plnm` over `domain`,
+    /// directly from the Lagrange bases of `domain` when the SRS has them (see [`SRS::add_lagrange_basis`]),
+    /// and otherwise by interpolating the evaluations first.
+    /// Evaluations over a larger domain are subsampled to `domain`.
     pub fn commit_evaluations_non_hiding(
         &self,
         domain: D<G::ScalarField>,
@@ -609,22 +681,85 @@ impl<G: CommitmentCurve> SRS<G> {
         max: Option<usize>,
     ) -> PolyComm<G> {
         let is_zero = plnm.evals.par_iter().all(|x| x.is_zero());
-        let basis = match self.lagrange_bases.get(&domain.size()) {
-            None => panic!("lagrange bases for size {} not found", domain.size()),
-            Some(v) => &v[..],
-        };
-        match domain.size.cmp(&plnm.domain().size) {
+        let subsampled: Vec<_>;
+        let evals = match domain.size.cmp(&plnm.domain().size) {
             std::cmp::Ordering::Less => {
                 let s = (plnm.domain().size / domain.size) as usize;
-                let v: Vec<_> = (0..(domain.size())).map(|i| plnm.evals[s * i]).collect();
-                Self::commit_helper(&v[..], basis, None, is_zero, max)
-            }
-            std::cmp::Ordering::Equal => {
-                Self::commit_helper(&plnm.evals[..], basis, None, is_zero, max)
+                subsampled = (0..(domain.size())).map(|i| plnm.evals[s * i]).collect();
+                &subsampled[..]
             }
+            std::cmp::Ordering::Equal => &plnm.evals[..],
             std::cmp::Ordering::Greater => {
                 panic!("desired commitment domain size greater than evaluations' domain size")
             }
+        };
+        match self.lagrange_bases.get(&domain.size()) {
+            Some(basis) => Self::commit_helper(evals, basis, None, is_zero, max),
+            None => {
+                let coeffs = DensePolynomial::from_coefficients_vec(domain.ifft(evals));
+                self.commit_non_hiding(&coeffs, max)
+            }
+        }
+    }
+
+    /// Commits to the polynomial of evaluations over `domain` given as `runs`,
+    /// a value and the number of consecutive rows holding it, the rows past the runs being zero.
+    /// With the Lagrange bases of `domain`, the bases of each run are summed
+    /// before a single MSM over the runs of nonzero values,
+    /// so that the cost depends on the runs rather than on the size of the domain.
+    /// The commitment is the one of [`SRS::commit_evaluations_non_hiding`].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the runs cover more rows than `domain`.
+    pub fn commit_runs_non_hiding(
+        &self,
+        domain: D<G::ScalarField>,
+        runs: &[(G::ScalarField, usize)],
+    ) -> PolyComm<G> {
+        let starts: Vec<_> = runs
+            .iter()
+            .scan(0, |start, (_, length)| {
+                let run = *start;
+                *start += length;
+                Some(run)
+            })
+            .collect();
+        let rows: usize = runs.iter().map(|(_, length)| length).sum();
+        assert!(rows <= domain.size());
+
+        let basis = match self.lagrange_bases.get(&domain.size()) {
+            Some(basis) => basis,
+            None => {
+                let mut evals = vec![G::ScalarField::zero(); domain.size()];
+                for (start, (value, length)) in starts.iter().zip(runs) {
+                    evals[*start..start + length].fill(*value);
+                }
+                let evals = Evaluations::from_vec_and_domain(evals, domain);
+                return self.commit_evaluations_non_hiding(domain, &evals, None);
+            }
+        };
+
+        let (bases, scalars): (Vec<_>, Vec<_>) = starts
+            .par_iter()
+            .zip(runs)
+            .filter(|(_, (value, _))| !value.is_zero())
+            .map(|(start, (value, length))| {
+                let sum = basis[*start..start + length]
+                    .iter()
+                    .fold(G::Projective::zero(), |sum, g| sum.add_mixed(g));
+                (sum, value.into_repr())
+            })
+            .unzip();
+        let unshifted = if bases.is_empty() {
+            G::zero()
+        } else {
+            let bases = G::Projective::batch_normalization_into_affine(&bases);
+            msm::multi_scalar_mul(&bases, &scalars).into_affine()
+        };
+        PolyComm {
+            unshifted: vec![unshifted],
+            shifted: None,
         }
     }
 
@@ -694,6 +829,11 @@ impl<G: CommitmentCurve> SRS<G> {
         let mut scalars = vec![G::ScalarField::zero(); padded_length + 1];
         assert_eq!(scalars.len(), points.len());
 
+        // the folded challenge products of the openings, shared by the openings with the same
+        // challenges (as when several proofs of a batch carry the same accumulator)
+        let mut folded: HashMap<Vec<G::ScalarField>, FoldedChallenges<G::ScalarField>> =
+            HashMap::new();
+
         // sample randomiser to scale the proofs with
         let rand_base = G::ScalarField::rand(rng);
         let sg_rand_base = G::ScalarField::rand(rng);
@@ -701,6 +841,11 @@ impl<G: CommitmentCurve> SRS<G> {
         let mut rand_base_i = G::ScalarField::one();
         let mut sg_rand_base_i = G::ScalarField::one();
 
+        // the points U_i are mapped to the curve all at once, after the loop:
+        // their terms use zero points until then
+        let mut us = vec![];
+        let mut u_terms = vec![];
+
         for BatchEvaluationProof {
             sponge,
             evaluation_points,
@@ -729,7 +874,7 @@ impl<G: CommitmentCurve> SRS<G> {
                                     Some(b)
                                 }
                             })();
-                            (evaluations.clone(), bound)
+                            (evaluations.iter().map(|e| &e[..]).collect(), bound)
                         },
                     )
                     .collect();
@@ -738,14 +883,19 @@ impl<G: CommitmentCurve> SRS<G> {
 
             sponge.absorb_fr(&[shift_scalar::<G>(combined_inner_product0)]);
 
-            let t = sponge.challenge_fq();
-            let u: G = to_group(group_map, t);
+            us.push(sponge.challenge_fq());
 
             let Challenges { chal, chal_inv } =
                 opening.challenges::<EFqSponge>(&self.endo_r, sponge);
+            if chal.len() > max_rounds {
+                return false;
+            }
 
             sponge.absorb_g(&[opening.delta]);
-            let c = ScalarChallenge(sponge.challenge()).to_field(&self.endo_r);
+            let c = ScalarChallenge(sponge.challenge())
+                .to_field_with_limbs(EFqSponge::CHALLENGE_LENGTH_IN_LIMBS, &self.endo_r);
+
+            let products = folded.entry(chal.clone()).or_default();
 
             // < s, sum_i evalscale^i pows(evaluation_point[i]) >
             // ==
@@ -754,15 +904,16 @@ impl<G: CommitmentCurve> SRS<G> {
                 let mut scale = G::ScalarField::one();
                 let mut res = G::ScalarField::zero();
                 for &e in evaluation_points.iter() {
-                    let term = b_poly(&chal, e);
+                    let term = *products
+                        .evaluations
+                        .entry(e)
+                        .or_insert_with(|| b_poly(&chal, e));
                     res += &(scale * term);
                     scale *= *evalscale;
                 }
                 res
             };
 
-            let s = b_poly_coefficients(&chal);
-
             let neg_rand_base_i = -rand_base_i;
 
             // TERM
@@ -777,14 +928,9 @@ impl<G: CommitmentCurve> SRS<G> {
             // =
             // < sg_rand_base_i s, self.g >
             //
-            // to check correctness of the sg component.
-            {
-                let terms: Vec<_> = s.par_iter().map(|s| sg_rand_base_i * s).collect();
-
-                for (i, term) in terms.iter().enumerate() {
-                    scalars[i + 1] += term;
-                }
-            }
+            // to check correctness of the sg component,
+            // once for all the openings with the same challenges (after the loop).
+            products.sg_scale += sg_rand_base_i;
 
             // TERM
             // - rand_base_i * z2 * H
@@ -793,7 +939,8 @@ impl<G: CommitmentCurve> SRS<G> {
             // TERM
             // -rand_base_i * (z1 * b0 * U)
             scalars.push(neg_rand_base_i * (opening.z1 * b0));
-            points.push(u);
+            u_terms.push(points.len());
+            points.push(G::zero());
 
             // TERM
             // rand_base_i c_i Q_i
@@ -847,7 +994,8 @@ impl<G: CommitmentCurve> SRS<G> {
             };
 
             scalars.push(rand_base_i_c_i * combined_inner_product0);
-            points.push(u);
+            u_terms.push(points.len());
+            points.push(G::zero());
 
             scalars.push(rand_base_i);
             points.push(opening.delta);
@@ -856,12 +1004,39 @@ impl<G: CommitmentCurve> SRS<G> {
             sg_rand_base_i *= &sg_rand_base;
         }
 
+        // the terms < sg_scale s, self.g > of the distinct challenges,
+        // computing their b_poly coefficients in the same scratch space
+        let mut s = vec![G::ScalarField::zero(); padded_length];
+        for (chal, FoldedChallenges { sg_scale, .. }) in &folded {
+            let s = &mut s[..1 << chal.len()];
+            b_poly_coefficients_into(chal, s);
+            scalars[1..]
+                .par_iter_mut()
+                .zip(s.par_iter())
+                .for_each(|(scalar, s)| *scalar += *sg_scale * s);
+        }
+
+        // each proof has two terms in U_i
+        let us = group_map.batch_to_group(&us);
+        for (term, (x, y)) in u_terms.iter().zip(us.iter().flat_map(|u| [u, u])) {
+            points[*term] = G::of_coordinates(*x, *y);
+        }
+
         // verify the equation
         let scalars: Vec<_> = scalars.iter().map(|x| x.into_repr()).collect();
         VariableBaseMSM::multi_scalar_mul(&points, &scalars) == G::Projective::zero()
     }
 }
 
+/// The folded challenge products of the openings of a batch with the same challenges:
+/// the evaluations of their [`b_poly`] at the evaluation points,
+/// and the sum of the scales of its coefficients in the check of the `sg` commitments.
+#[derive(Default)]
+struct FoldedChallenges<F> {
+    evaluations: HashMap<F, F>,
+    sg_scale: F,
+}
+
 pub fn inner_prod<F: Field>(xs: &[F], ys: &[F]) -> F {
     let mut res = F::zero();
     for (&x, y) in xs.iter().zip(ys) {
@@ -915,6 +1090,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_b_poly() {
+        let mut rng = StdRng::from_seed([0u8; 32]);
+        let chals: Vec<_> = (0..7).map(|_| Fp::rand(&mut rng)).collect();
+        let x = Fp::rand(&mut rng);
+
+        let s = b_poly_coefficients(&chals);
+        let p = DensePolynomial::from_coefficients_vec(s.clone());
+        assert_eq!(p.evaluate(&x), b_poly(&chals, x));
+
+        // a reused buffer is fully overwritten
+        let mut buffer = vec![Fp::rand(&mut rng); s.len()];
+        b_poly_coefficients_into(&chals, &mut buffer);
+        assert_eq!(buffer, s);
+    }
+
     #[test]
     fn test_opening_proof() {
         // create two polynomials
@@ -0,0 +1,172 @@
+//! This module implements proofs that two commitments are to the same polynomial,
+//! under different blinders, as when a commitment is re-randomized
+//! (see [`SRS::rerandomize`]) before being handed to another aggregation layer.
+//!
+//! The chunks of two commitments to the same polynomial differ by `(r_i - r'_i) H`,
+//! with `r_i` and `r'_i` the blinders of their `i`-th chunks and `H` the blinding generator of the SRS.
+//! For a challenge `rho`, the differences of the chunks are batched into
+//! `D = sum_i rho^i (C_i - C'_i)`, and the proof is a proof of knowledge of the discrete logarithm
+//! `d = sum_i rho^i (r_i - r'_i)` of `D` in base `H`:
+//! the commitment `K = k H` of a random `k`, and the response `z = k + c d` to a challenge `c`,
+//! checked as `z H = K + c D`.
+//! As the prover does not know the discrete logarithms of the generators of the SRS in base `H`,
+//! it can only know `d` if the committed polynomials are the same.
+//!
+//! The shifted chunks of the commitments, if any, are batched last.
+//! The challenges are derived from the sponge, after absorbing both commitments,
+//! so that the sponge given to [`SRS::prove_equality`] and [`SRS::verify_equality`]
+//! only needs to be in the same state.
+
+use crate::commitment::{BlindedCommitment, CommitmentCurve, PolyComm};
+use crate::error::CommitmentError;
+use crate::srs::SRS;
+use ark_ec::{msm::VariableBaseMSM, AffineCurve, ProjectiveCurve};
+use ark_ff::{One, PrimeField, UniformRand};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use oracle::FqSponge;
+use rand_core::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+
+/// A proof that two commitments are to the same polynomial (see the [module documentation](self))
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EqualityProof<G: CommitmentCurve> {
+    /// The commitment `k H` of the proof of knowledge
+    #[serde_as(as = "o1_utils::serialization::SerdeAs")]
+    pub commitment: G,
+    /// The response `k + c d` of the proof of knowledge
+    #[serde_as(as = "o1_utils::serialization::SerdeAs")]
+    pub response: G::ScalarField,
+}
+
+/// Returns the chunks of `comm`, its shifted chunk last.
+fn chunks<A>(comm: &PolyComm<A>) -> Vec<A>
+where
+    A: Copy + CanonicalDeserialize + CanonicalSerialize,
+{
+    comm.unshifted.iter().copied().chain(comm.shifted).collect()
+}
+
+/// Absorbs both commitments, and returns the powers of the challenge `rho`
+/// batching the differences of their chunks.
+/// Returns `None` if the commitments do not have the same number of chunks.
+fn batching_powers<G, EFqSponge>(
+    sponge: &mut EFqSponge,
+    left: &PolyComm<G>,
+    right: &PolyComm<G>,
+) -> Option<Vec<G::ScalarField>>
+where
+    G: CommitmentCurve,
+    EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
+{
+    left.zip(right)?;
+    let left = chunks(left);
+    sponge.absorb_g(&left);
+    sponge.absorb_g(&chunks(right));
+    let rho: G::ScalarField = sponge.challenge();
+    Some(
+        std::iter::successors(Some(G::ScalarField::one()), |p| Some(*p * rho))
+            .take(left.len())
+            .collect(),
+    )
+}
+
+impl<G: CommitmentCurve> SRS<G> {
+    /// Re-randomizes a commitment with fresh blinders,
+    /// returning a commitment to the same polynomial that cannot be linked to `comm`.
+    pub fn rerandomize(
+        &self,
+        comm: &BlindedCommitment<G>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> BlindedCommitment<G> {
+        let fresh = comm.blinders.map(|_| G::ScalarField::rand(rng));
+        let rerandomized = self
+            .mask_custom(comm.commitment.clone(), &fresh)
+            .expect("the blinders have the shape of the commitment");
+        BlindedCommitment {
+            commitment: rerandomized.commitment,
+            blinders: comm
+                .blinders
+                .zip(&fresh)
+                .expect("the blinders have the same shape")
+                .map(|(old, fresh)| old + fresh),
+        }
+    }
+
+    /// Proves that two commitments are to the same polynomial, from their blinders.
+    /// The proof is only valid if the commitments are indeed to the same polynomial.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the commitments, or a commitment and its blinders,
+    /// do not have the same number of chunks.
+    pub fn prove_equality<EFqSponge>(
+        &self,
+        left: &BlindedCommitment<G>,
+        right: &BlindedCommitment<G>,
+        mut sponge: EFqSponge,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<EqualityProof<G>, CommitmentError>
+    where
+        EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
+    {
+        for comm in [left, right] {
+            if comm.commitment.zip(&comm.blinders).is_none() {
+                return Err(CommitmentError::BlindersDontMatch(
+                    comm.blinders.len(),
+                    comm.commitment.len(),
+                ));
+            }
+        }
+        let rhos = batching_powers(&mut sponge, &left.commitment, &right.commitment).ok_or(
+            CommitmentError::ChunksDontMatch(left.commitment.len(), right.commitment.len()),
+        )?;
+
+        // d = sum_i rho^i (r_i - r'_i)
+        let d = chunks(&left.blinders)
+            .into_iter()
+            .zip(chunks(&right.blinders))
+            .zip(&rhos)
+            .map(|((l, r), rho)| (l - r) * rho)
+            .sum::<G::ScalarField>();
+
+        let k = G::ScalarField::rand(rng);
+        let commitment = self.h.mul(k).into_affine();
+        sponge.absorb_g(&[commitment]);
+        let c = sponge.challenge();
+
+        Ok(EqualityProof {
+            commitment,
+            response: k + c * d,
+        })
+    }
+
+    /// Verifies a proof that two commitments are to the same polynomial,
+    /// as returned by [`SRS::prove_equality`].
+    pub fn verify_equality<EFqSponge>(
+        &self,
+        left: &PolyComm<G>,
+        right: &PolyComm<G>,
+        proof: &EqualityProof<G>,
+        mut sponge: EFqSponge,
+    ) -> bool
+    where
+        EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
+    {
+        let rhos = match batching_powers(&mut sponge, left, right) {
+            Some(rhos) => rhos,
+            None => return false,
+        };
+        sponge.absorb_g(&[proof.commitment]);
+        let c: G::ScalarField = sponge.challenge();
+
+        // D = sum_i rho^i (C_i - C'_i)
+        let rhos: Vec<_> = rhos.iter().map(|rho| rho.into_repr()).collect();
+        let batch = |comm: &PolyComm<G>| VariableBaseMSM::multi_scalar_mul(&chunks(comm), &rhos);
+        let difference = batch(left) - batch(right);
+
+        self.h.mul(proof.response)
+            == proof.commitment.into_projective() + difference.mul(c.into_repr())
+    }
+}
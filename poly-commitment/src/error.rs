@@ -6,4 +6,101 @@ pub enum CommitmentError {
         "the length of the given blinders ({0}) don't match the length of the commitment ({1})"
     )]
     BlindersDontMatch(usize, usize),
+
+    #[error("the commitments do not have the same number of chunks ({0} and {1})")]
+    ChunksDontMatch(usize, usize),
+
+    #[error("the opening claim {0} has no evaluation point")]
+    ClaimWithoutPoints(usize),
+
+    #[error("the opening claim {0} repeats an evaluation point")]
+    ClaimRepeatsPoint(usize),
+
+    #[error("the opening claim {0} has a commitment with a degree bound, which is not supported")]
+    ClaimWithDegreeBound(usize),
+}
+
+/// Reasons for a batch of opening proofs to fail verification,
+/// as reported by [`crate::scheme::PolynomialCommitment::verify_with_diagnostics`].
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpeningError {
+    #[error("proof {proof}: evaluation {evaluation} is given at {rows} points, but there are {points} evaluation points")]
+    EvaluationPoints {
+        proof: usize,
+        evaluation: usize,
+        rows: usize,
+        points: usize,
+    },
+
+    #[error("proof {proof}: evaluation {evaluation} has {chunks} chunks at point {point}, but its commitment has {expected}")]
+    EvaluationChunks {
+        proof: usize,
+        evaluation: usize,
+        point: usize,
+        chunks: usize,
+        expected: usize,
+    },
+
+    #[error(
+        "proof {proof}: the opening proof has {rounds} rounds, but the SRS supports at most {max}"
+    )]
+    Rounds {
+        proof: usize,
+        rounds: usize,
+        max: usize,
+    },
+
+    #[error("proof {proof}: the opening proof does not match the evaluations")]
+    Opening { proof: usize },
+}
+
+/// Reasons for a contribution to a ceremony to be rejected,
+/// as reported by [`crate::ceremony::Accumulator::update`].
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CeremonyError {
+    #[error("contribution {contribution}: {powers} powers are given, but the accumulator has {expected}")]
+    Powers {
+        contribution: usize,
+        powers: usize,
+        expected: usize,
+    },
+
+    #[error("contribution {contribution}: {sums} partial sums are given, but the accumulator has {expected} powers")]
+    PartialSums {
+        contribution: usize,
+        sums: usize,
+        expected: usize,
+    },
+
+    #[error("contribution {contribution}: the first power is not the generator")]
+    FirstPower { contribution: usize },
+
+    #[error("contribution {contribution}: the public key is zero")]
+    PublicKey { contribution: usize },
+
+    #[error(
+        "contribution {contribution}: the powers are not a re-randomization of the accumulator"
+    )]
+    Rerandomization { contribution: usize },
+}
+
+/// Reasons for a check of IPA accumulators to fail,
+/// as reported by [`crate::accumulator::AccumulatorCheck`].
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccumulatorError {
+    #[error("accumulator {accumulator}: {rounds} challenges are given, but the SRS supports at most {max}")]
+    Rounds {
+        accumulator: usize,
+        rounds: usize,
+        max: usize,
+    },
+
+    #[error("cannot merge a check for {other} rounds into a check for {rounds} rounds")]
+    Merge { rounds: usize, other: usize },
+
+    #[error("the check is for {rounds} rounds, but the SRS has {srs_rounds}")]
+    Srs { rounds: usize, srs_rounds: usize },
+
+    #[error("an accumulator does not commit to the polynomial of its challenges")]
+    Commitment,
 }
@@ -203,8 +203,7 @@ impl<G: CommitmentCurve> SRS<G> {
                     .iter()
                     .map(|x| x.into_repr())
                     .collect::<Vec<_>>(),
-            )
-            .into_affine();
+            );
 
             let r = VariableBaseMSM::multi_scalar_mul(
                 &[&g[n..], &[self.h, u]].concat(),
@@ -213,17 +212,15 @@ impl<G: CommitmentCurve> SRS<G> {
                     .iter()
                     .map(|x| x.into_repr())
                     .collect::<Vec<_>>(),
-            )
-            .into_affine();
+            );
 
-            lr.push((l, r));
+            // l and r are absorbed as a single batch, which normalizes them at once
+            let lr_affine = sponge.absorb_g_batch(&[l, r]);
+            lr.push((lr_affine[0], lr_affine[1]));
             blinders.push((rand_l, rand_r));
 
-            sponge.absorb_g(&[l]);
-            sponge.absorb_g(&[r]);
-
             let u_pre = squeeze_prechallenge(&mut sponge);
-            let u = u_pre.to_field(&self.endo_r);
+            let u = u_pre.to_field_with_limbs(EFqSponge::CHALLENGE_LENGTH_IN_LIMBS, &self.endo_r);
             let u_inv = u.inverse().unwrap();
 
             chals.push(u);
@@ -253,7 +250,14 @@ impl<G: CommitmentCurve> SRS<G> {
                 })
                 .collect();
 
-            g = G::combine_one_endo(self.endo_r, self.endo_q, &g_lo, &g_hi, u_pre);
+            g = G::combine_one_endo(
+                self.endo_r,
+                self.endo_q,
+                &g_lo,
+                &g_hi,
+                u_pre,
+                EFqSponge::CHALLENGE_LENGTH_IN_LIMBS,
+            );
         }
 
         assert!(g.len() == 1);
@@ -275,7 +279,8 @@ impl<G: CommitmentCurve> SRS<G> {
         .into_affine();
 
         sponge.absorb_g(&[delta]);
-        let c = ScalarChallenge(sponge.challenge()).to_field(&self.endo_r);
+        let c = ScalarChallenge(sponge.challenge())
+            .to_field_with_limbs(EFqSponge::CHALLENGE_LENGTH_IN_LIMBS, &self.endo_r);
 
         let z1 = a0 * c + d;
         let z2 = c * r_prime + r_delta;
@@ -290,12 +295,17 @@ impl<G: CommitmentCurve> SRS<G> {
     }
 }
 
+/// The maximum number of rounds of an opening proof accepted when deserializing
+pub const MAX_ROUNDS: usize = 64;
+
 #[serde_as]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(bound = "G: ark_serialize::CanonicalDeserialize + ark_serialize::CanonicalSerialize")]
 pub struct OpeningProof<G: AffineCurve> {
     /// vector of rounds of L & R commitments
-    #[serde_as(as = "Vec<(o1_utils::serialization::SerdeAs, o1_utils::serialization::SerdeAs)>")]
+    #[serde_as(
+        as = "o1_utils::serialization::Bounded<(o1_utils::serialization::SerdeAs, o1_utils::serialization::SerdeAs), MAX_ROUNDS>"
+    )]
     pub lr: Vec<(G, G)>,
     #[serde_as(as = "o1_utils::serialization::SerdeAs")]
     pub delta: G,
@@ -1,9 +1,17 @@
+pub mod accumulator;
+pub mod aggregation;
+pub mod ceremony;
 pub mod chunked;
 mod combine;
 pub mod commitment;
+pub mod equality;
 pub mod error;
 pub mod evaluation_proof;
+pub mod msm;
+pub mod multi_point;
+pub mod scheme;
 pub mod srs;
+pub mod vector_commitment;
 
 #[cfg(test)]
 mod tests;
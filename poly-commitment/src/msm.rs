@@ -0,0 +1,222 @@
+//! This module implements the multi-scalar multiplications (MSMs) of the commitments,
+//! with a backend selected at runtime from the number of points:
+//!
+//! - [`MsmBackend::Straus`] for a few points (e.g. the MSMs of a verifier, over tens of commitments):
+//!   the small multiples of every point are tabulated,
+//!   and the windows of all the scalars share a single chain of doublings,
+//! - [`MsmBackend::Pippenger`] for many points (e.g. the commitments of polynomials to an SRS),
+//!   with the bucket method of arkworks,
+//! - [`MsmBackend::Precomputed`] for many points that are fixed and known in advance
+//!   (e.g. the generators of an SRS, see [`SRS::add_msm_table`](crate::srs::SRS::add_msm_table)):
+//!   an [`MsmTable`] holds the points multiplied by the powers of the size of a window,
+//!   so that all the windows of all the scalars go to a same set of buckets, without doublings.
+
+use ark_ec::{msm::VariableBaseMSM, AffineCurve, ProjectiveCurve};
+use ark_ff::{BigInteger, PrimeField, Zero};
+use o1_utils::curve_helpers::batch_normalize;
+use rayon::prelude::*;
+
+/// The scalars of an MSM over the points `G`
+pub type ScalarRepr<G> = <<G as AffineCurve>::ScalarField as PrimeField>::BigInt;
+
+/// The largest number of points for which [`MsmBackend::Straus`] is selected
+pub const STRAUS_MAX_SIZE: usize = 32;
+
+/// The smallest number of points for which [`MsmBackend::Precomputed`] is selected, given a table
+pub const PRECOMPUTED_MIN_SIZE: usize = 1 << 8;
+
+/// The number of bits of a window of [`MsmBackend::Straus`]
+const STRAUS_WINDOW: usize = 4;
+
+/// The bounds of the number of bits of a window of an [`MsmTable`]
+const TABLE_WINDOW: (usize, usize) = (4, 16);
+
+/// An algorithm of multi-scalar multiplication (see the [module documentation](self))
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MsmBackend {
+    /// Straus' algorithm, with a shared chain of doublings
+    Straus,
+    /// Pippenger's bucket method
+    Pippenger,
+    /// the bucket method over precomputed multiples of fixed points
+    Precomputed,
+}
+
+impl MsmBackend {
+    /// Selects the backend of an MSM over `size` points,
+    /// given whether an [`MsmTable`] of the points is available.
+    pub fn select(size: usize, precomputed: bool) -> Self {
+        if size <= STRAUS_MAX_SIZE {
+            MsmBackend::Straus
+        } else if precomputed && size >= PRECOMPUTED_MIN_SIZE {
+            MsmBackend::Precomputed
+        } else {
+            MsmBackend::Pippenger
+        }
+    }
+}
+
+/// Returns the `width` bits of `scalar` from the bit `start`.
+fn digit<B: BigInteger>(scalar: &B, start: usize, width: usize) -> usize {
+    let limbs = scalar.as_ref();
+    let (limb, shift) = (start / 64, start % 64);
+    if limb >= limbs.len() {
+        return 0;
+    }
+    let mut bits = limbs[limb] >> shift;
+    if shift + width > 64 && limb + 1 < limbs.len() {
+        bits |= limbs[limb + 1] << (64 - shift);
+    }
+    (bits & ((1 << width) - 1)) as usize
+}
+
+/// Returns the sum of the `scalars` multiples of the `bases`, with the backend selected
+/// from the number of bases (see [`MsmBackend::select`]).
+/// Like [`VariableBaseMSM::multi_scalar_mul`], the extra bases or scalars are ignored.
+pub fn multi_scalar_mul<G: AffineCurve>(bases: &[G], scalars: &[ScalarRepr<G>]) -> G::Projective {
+    let size = std::cmp::min(bases.len(), scalars.len());
+    match MsmBackend::select(size, false) {
+        MsmBackend::Straus => straus(bases, scalars),
+        MsmBackend::Pippenger | MsmBackend::Precomputed => {
+            VariableBaseMSM::multi_scalar_mul(bases, scalars)
+        }
+    }
+}
+
+/// Returns the sum of the `scalars` multiples of the `bases`, with Straus' algorithm.
+/// The extra bases or scalars are ignored.
+pub fn straus<G: AffineCurve>(bases: &[G], scalars: &[ScalarRepr<G>]) -> G::Projective {
+    let size = std::cmp::min(bases.len(), scalars.len());
+    let count = (1 << STRAUS_WINDOW) - 1;
+
+    // the multiples 1, ..., 2^w - 1 of every base
+    let mut table = Vec::with_capacity(size * count);
+    for g in &bases[..size] {
+        let g = g.into_projective();
+        let mut multiple = g;
+        table.push(multiple);
+        for _ in 1..count {
+            multiple += &g;
+            table.push(multiple);
+        }
+    }
+    let table = G::Projective::batch_normalization_into_affine(&table);
+
+    let bits = <G::ScalarField as PrimeField>::size_in_bits();
+    let windows = (bits + STRAUS_WINDOW - 1) / STRAUS_WINDOW;
+    let mut res = G::Projective::zero();
+    for window in (0..windows).rev() {
+        for _ in 0..STRAUS_WINDOW {
+            res.double_in_place();
+        }
+        for (multiples, scalar) in table.chunks(count).zip(scalars) {
+            let d = digit(scalar, window * STRAUS_WINDOW, STRAUS_WINDOW);
+            if d != 0 {
+                res.add_assign_mixed(&multiples[d - 1]);
+            }
+        }
+    }
+    res
+}
+
+/// The multiples of fixed points by the powers of `2^c`, for a window of `c` bits,
+/// to compute MSMs over these points with [`MsmBackend::Precomputed`]
+#[derive(Clone, Debug)]
+pub struct MsmTable<G> {
+    /// the number of bits of a window
+    window: usize,
+    /// the points multiplied by `2^(c k)`, for every window `k`
+    multiples: Vec<Vec<G>>,
+}
+
+impl<G: AffineCurve> MsmTable<G> {
+    /// Creates the table of `bases`, with a window of about the logarithm of their number.
+    /// The table holds a copy of the bases per window of a scalar.
+    pub fn new(bases: &[G]) -> Self {
+        let log = (usize::BITS - bases.len().leading_zeros()) as usize;
+        let window = log.clamp(TABLE_WINDOW.0, TABLE_WINDOW.1);
+        let bits = <G::ScalarField as PrimeField>::size_in_bits();
+        let windows = (bits + window - 1) / window;
+
+        let mut multiples = Vec::with_capacity(windows);
+        multiples.push(bases.to_vec());
+        for _ in 1..windows {
+            let last = multiples.last().unwrap();
+            let next: Vec<_> = last
+                .par_iter()
+                .map(|g: &G| {
+                    let mut g = g.into_projective();
+                    for _ in 0..window {
+                        g.double_in_place();
+                    }
+                    g
+                })
+                .collect();
+            multiples.push(batch_normalize(&next));
+        }
+        MsmTable { window, multiples }
+    }
+
+    /// Returns the number of points of the table.
+    pub fn len(&self) -> usize {
+        self.multiples[0].len()
+    }
+
+    /// Returns whether the table has no points.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the points of the table.
+    pub fn bases(&self) -> &[G] {
+        &self.multiples[0]
+    }
+
+    /// Returns the sum of the `scalars` multiples of the points of the table from the point `offset`,
+    /// with the backend selected from their number (see [`MsmBackend::select`]).
+    /// The extra points or scalars are ignored.
+    pub fn multi_scalar_mul(&self, offset: usize, scalars: &[ScalarRepr<G>]) -> G::Projective {
+        let bases = &self.bases()[offset..];
+        let size = std::cmp::min(bases.len(), scalars.len());
+        match MsmBackend::select(size, true) {
+            MsmBackend::Straus => straus(bases, scalars),
+            MsmBackend::Pippenger => VariableBaseMSM::multi_scalar_mul(bases, scalars),
+            MsmBackend::Precomputed => self.precomputed(offset, &scalars[..size]),
+        }
+    }
+
+    /// Returns the MSM of [`MsmTable::multi_scalar_mul`] with [`MsmBackend::Precomputed`].
+    pub fn precomputed(&self, offset: usize, scalars: &[ScalarRepr<G>]) -> G::Projective {
+        let size = std::cmp::min(self.len() - offset, scalars.len());
+        let scalars = &scalars[..size];
+
+        // the windows are split among the threads, each filling its own buckets
+        let threads = rayon::current_num_threads();
+        let windows_per_thread = (self.multiples.len() + threads - 1) / threads;
+        self.multiples
+            .par_chunks(windows_per_thread)
+            .enumerate()
+            .map(|(chunk, multiples)| {
+                let mut buckets = vec![G::Projective::zero(); (1 << self.window) - 1];
+                for (i, bases) in multiples.iter().enumerate() {
+                    let start = (chunk * windows_per_thread + i) * self.window;
+                    for (g, scalar) in bases[offset..].iter().zip(scalars) {
+                        let d = digit(scalar, start, self.window);
+                        if d != 0 {
+                            buckets[d - 1].add_assign_mixed(g);
+                        }
+                    }
+                }
+
+                // the sum of the buckets, each multiplied by its digit
+                let mut res = G::Projective::zero();
+                let mut running_sum = G::Projective::zero();
+                for bucket in buckets.into_iter().rev() {
+                    running_sum += &bucket;
+                    res += &running_sum;
+                }
+                res
+            })
+            .reduce(G::Projective::zero, |a, b| a + b)
+    }
+}
@@ -0,0 +1,356 @@
+//! This module implements openings of polynomial commitments at a different set of
+//! evaluation points per polynomial.
+//!
+//! [`SRS::open`] proves the evaluations of *all* the polynomials of a batch at *all* the
+//! evaluation points, as the inner product argument works on a single combination of the
+//! polynomials. When each polynomial is only evaluated at some of the points, the openings
+//! are first reduced to a single point (as in the multi-point opening of Halo 2):
+//!
+//! 1. the (chunks of the) polynomials evaluated at the same set of points `S` are combined
+//!    with powers of `polyscale` into `q_S`, whose evaluations on `S` follow from the
+//!    evaluations of the polynomials;
+//! 2. the prover commits to `f = sum_S evalscale^S (q_S - r_S) / Z_S`, where `r_S` interpolates
+//!    the evaluations of `q_S` on `S` and `Z_S` vanishes on `S`
+//!    (so that `f` is a polynomial only if the evaluations are correct);
+//! 3. the sponge derives a point `x`, at which the prover gives the evaluations of the `q_S`;
+//! 4. the sponge derives a new `polyscale`, with which the `q_S` and `f` are opened at `x`
+//!    with [`SRS::open`], the verifier computing `f(x)` from the evaluations of the `q_S`.
+//!
+//! The proof contains a single commitment, one evaluation per distinct set of points
+//! and a single opening proof, whatever the number of points.
+//! Degree bounds are not supported.
+
+use crate::commitment::*;
+use crate::evaluation_proof::OpeningProof;
+use crate::srs::SRS;
+use ark_ec::{msm::VariableBaseMSM, AffineCurve, ProjectiveCurve};
+use ark_ff::{Field, One, PrimeField, Zero};
+use ark_poly::{univariate::DensePolynomial, Polynomial, UVPolynomial};
+use o1_utils::ExtendedDensePolynomial as _;
+use oracle::FqSponge;
+use rand_core::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use std::collections::BTreeMap;
+
+/// A polynomial to open at some of the evaluation points.
+pub struct MultiPointPolynomial<'a, F: Field> {
+    /// The polynomial
+    pub poly: &'a DensePolynomial<F>,
+    /// The blinding factors of the (chunks of the) commitment
+    pub blinders: PolyComm<F>,
+    /// The indices of the evaluation points, in increasing order
+    pub points: Vec<usize>,
+}
+
+/// Contains the evaluations of a polynomial commitment at some of the evaluation points.
+pub struct MultiPointEvaluation<G>
+where
+    G: AffineCurve,
+{
+    /// The commitment of the polynomial being evaluated
+    pub commitment: PolyComm<G>,
+
+    /// The indices of the evaluation points, in increasing order
+    pub points: Vec<usize>,
+
+    /// Contains an evaluation table, with a row per point of `points`
+    pub evaluations: Vec<Vec<G::ScalarField>>,
+}
+
+/// Contains the batch evaluation at a different set of points per polynomial
+pub struct MultiPointEvaluationProof<'a, G, EFqSponge>
+where
+    G: AffineCurve,
+    EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
+{
+    pub sponge: EFqSponge,
+    pub evaluations: Vec<MultiPointEvaluation<G>>,
+    /// vector of evaluation points
+    pub evaluation_points: Vec<G::ScalarField>,
+    /// scaling factor for polynomials
+    pub polyscale: G::ScalarField,
+    /// scaling factor for the sets of evaluation points
+    pub evalscale: G::ScalarField,
+    /// batched opening proof
+    pub opening: &'a MultiPointOpeningProof<G>,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "G: ark_serialize::CanonicalDeserialize + ark_serialize::CanonicalSerialize")]
+pub struct MultiPointOpeningProof<G: AffineCurve> {
+    /// commitment to the combined quotient `f`
+    pub quotient: PolyComm<G>,
+    /// evaluations of the combined polynomials `q_S` at the opening point
+    #[serde_as(as = "Vec<o1_utils::serialization::SerdeAs>")]
+    pub evaluations: Vec<G::ScalarField>,
+    /// opening proof at a single point
+    pub proof: OpeningProof<G>,
+}
+
+/// Groups the polynomials by their set of evaluation points,
+/// returning the indices of the polynomials evaluated at each set.
+fn point_sets<'a>(
+    points: impl Iterator<Item = &'a [usize]>,
+    num_points: usize,
+) -> BTreeMap<&'a [usize], Vec<usize>> {
+    let mut sets = BTreeMap::<_, Vec<_>>::new();
+    for (i, set) in points.enumerate() {
+        assert!(
+            !set.is_empty(),
+            "a polynomial must be evaluated at some point"
+        );
+        assert!(set.windows(2).all(|w| w[0] < w[1]), "unsorted points");
+        assert!(set[set.len() - 1] < num_points, "unknown point");
+        sets.entry(set).or_default().push(i);
+    }
+    sets
+}
+
+/// Returns the polynomial of degree less than `points.len()`
+/// which takes the given `values` at the given `points`.
+fn interpolate<F: Field>(points: &[F], values: &[F]) -> DensePolynomial<F> {
+    let mut res = DensePolynomial::zero();
+    for (i, (xi, yi)) in points.iter().zip(values).enumerate() {
+        // l_i = prod_{j != i} (X - x_j) / (x_i - x_j)
+        let mut l = vec![F::one()];
+        let mut denominator = F::one();
+        for (_, xj) in points.iter().enumerate().filter(|(j, _)| *j != i) {
+            // multiplying by X - x_j
+            l.insert(0, F::zero());
+            for k in 0..l.len() - 1 {
+                let c = l[k + 1];
+                l[k] -= *xj * c;
+            }
+            denominator *= *xi - xj;
+        }
+        let scale = *yi * denominator.inverse().expect("duplicated points");
+        res += (scale, &DensePolynomial::from_coefficients_vec(l));
+    }
+    res
+}
+
+impl<G: CommitmentCurve> SRS<G> {
+    /// This function opens polynomial commitments in batch, each at its own set of points
+    ///     plnms: batch of polynomials to open, with the blinders of their commitments
+    ///         and the indices of their evaluation points
+    ///     elm: evaluation point vector to open the commitments at
+    ///     polyscale: polynomial scaling factor for opening commitments in batch
+    ///     evalscale: scaling factor for the sets of evaluation points
+    ///     oracle_params: parameters for the random oracle argument
+    ///     RETURN: commitment opening proof
+    #[allow(clippy::too_many_arguments)]
+    pub fn open_multi_point<EFqSponge, RNG>(
+        &self,
+        group_map: &G::Map,
+        plnms: &[MultiPointPolynomial<G::ScalarField>],
+        elm: &[G::ScalarField],
+        polyscale: G::ScalarField,
+        evalscale: G::ScalarField,
+        mut sponge: EFqSponge,
+        rng: &mut RNG,
+    ) -> MultiPointOpeningProof<G>
+    where
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+        RNG: RngCore + CryptoRng,
+        G::BaseField: PrimeField,
+    {
+        let n = self.g.len();
+        let sets = point_sets(plnms.iter().map(|p| &p.points[..]), elm.len());
+
+        // q_S and the blinding factor of its commitment, for each set of points S
+        let mut combined = vec![];
+        // f = sum_S evalscale^S (q_S - r_S) / Z_S
+        let mut quotient = DensePolynomial::zero();
+        let mut evalscale_i = G::ScalarField::one();
+        for (set, polys) in &sets {
+            let mut q = DensePolynomial::zero();
+            let mut blinder = G::ScalarField::zero();
+            let mut polyscale_i = G::ScalarField::one();
+            for p in polys.iter().map(|i| &plnms[*i]) {
+                // iterating over the chunks of the polynomial
+                let len = p.poly.coeffs.len();
+                for (j, b) in p.blinders.unshifted.iter().enumerate() {
+                    let chunk =
+                        &p.poly.coeffs[std::cmp::min(j * n, len)..std::cmp::min((j + 1) * n, len)];
+                    q += (
+                        polyscale_i,
+                        &DensePolynomial::from_coefficients_slice(chunk),
+                    );
+                    blinder += polyscale_i * b;
+                    polyscale_i *= polyscale;
+                }
+            }
+
+            let points: Vec<_> = set.iter().map(|k| elm[*k]).collect();
+            let values: Vec<_> = points.iter().map(|x| q.evaluate(x)).collect();
+            let mut f = &q - &interpolate(&points, &values);
+            for x in &points {
+                f = f.divide_by_linear(*x).0;
+            }
+            quotient += (evalscale_i, &f);
+            evalscale_i *= evalscale;
+
+            combined.push((q, blinder));
+        }
+
+        let BlindedCommitment {
+            commitment,
+            blinders,
+        } = self.commit(&quotient, None, rng);
+        sponge.absorb_g(&commitment.unshifted);
+        let x = sponge.challenge();
+
+        let evaluations: Vec<_> = combined.iter().map(|(q, _)| q.evaluate(&x)).collect();
+        sponge.absorb_fr(&evaluations);
+        let polyscale = sponge.challenge();
+
+        let mut polys: Vec<_> = combined
+            .iter()
+            .map(|(q, blinder)| {
+                let blinders = PolyComm {
+                    unshifted: vec![*blinder],
+                    shifted: None,
+                };
+                (q, None, blinders)
+            })
+            .collect();
+        polys.push((&quotient, None, blinders));
+
+        let proof = self.open(
+            group_map,
+            &polys,
+            &[x],
+            polyscale,
+            G::ScalarField::one(),
+            sponge,
+            rng,
+        );
+
+        MultiPointOpeningProof {
+            quotient: commitment,
+            evaluations,
+            proof,
+        }
+    }
+
+    /// This function verifies batch of batched opening proofs at a different set of points per polynomial
+    ///     batch: batch of batched polynomial commitment opening proofs
+    ///     randomness source context
+    ///     RETURN: verification status
+    pub fn verify_multi_point<EFqSponge, RNG>(
+        &self,
+        group_map: &G::Map,
+        batch: &mut [MultiPointEvaluationProof<G, EFqSponge>],
+        rng: &mut RNG,
+    ) -> bool
+    where
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+        RNG: RngCore + CryptoRng,
+        G::BaseField: PrimeField,
+    {
+        let mut reduced = vec![];
+        for MultiPointEvaluationProof {
+            sponge,
+            evaluations,
+            evaluation_points,
+            polyscale,
+            evalscale,
+            opening,
+        } in batch.iter_mut()
+        {
+            let sets = point_sets(
+                evaluations.iter().map(|e| &e.points[..]),
+                evaluation_points.len(),
+            );
+            if opening.evaluations.len() != sets.len()
+                || opening.quotient.unshifted.len() != 1
+                || opening.quotient.shifted.is_some()
+            {
+                return false;
+            }
+
+            // the commitment of q_S and its evaluations on S, for each set of points S
+            let mut combined = vec![];
+            for (set, polys) in &sets {
+                let mut points = vec![];
+                let mut scalars = vec![];
+                let mut values = vec![G::ScalarField::zero(); set.len()];
+                let mut polyscale_i = G::ScalarField::one();
+                for e in polys.iter().map(|i| &evaluations[*i]) {
+                    let chunks = e.commitment.unshifted.len();
+                    if e.commitment.shifted.is_some()
+                        || e.evaluations.len() != set.len()
+                        || e.evaluations.iter().any(|evals| evals.len() != chunks)
+                    {
+                        return false;
+                    }
+
+                    // iterating over the chunks of the polynomial
+                    for (j, comm_ch) in e.commitment.unshifted.iter().enumerate() {
+                        points.push(*comm_ch);
+                        scalars.push(polyscale_i.into_repr());
+                        for (value, evals) in values.iter_mut().zip(&e.evaluations) {
+                            *value += polyscale_i * evals[j];
+                        }
+                        polyscale_i *= *polyscale;
+                    }
+                }
+
+                let commitment = PolyComm {
+                    unshifted: vec![
+                        VariableBaseMSM::multi_scalar_mul(&points, &scalars).into_affine()
+                    ],
+                    shifted: None,
+                };
+                let points: Vec<_> = set.iter().map(|k| evaluation_points[*k]).collect();
+                combined.push((commitment, points, values));
+            }
+
+            sponge.absorb_g(&opening.quotient.unshifted);
+            let x = sponge.challenge();
+            sponge.absorb_fr(&opening.evaluations);
+            let polyscale = sponge.challenge();
+
+            // f(x) = sum_S evalscale^S (q_S(x) - r_S(x)) / Z_S(x)
+            let mut quotient_eval = G::ScalarField::zero();
+            let mut evalscale_i = G::ScalarField::one();
+            for ((_, points, values), q_eval) in combined.iter().zip(&opening.evaluations) {
+                let r_eval = interpolate(points, values).evaluate(&x);
+                let z_eval = product(points.iter().map(|p| x - p));
+                match z_eval.inverse() {
+                    Some(z_inv) => quotient_eval += evalscale_i * (*q_eval - r_eval) * z_inv,
+                    None => return false,
+                }
+                evalscale_i *= *evalscale;
+            }
+
+            let mut evaluations: Vec<_> = combined
+                .into_iter()
+                .zip(&opening.evaluations)
+                .map(|((commitment, _, _), q_eval)| Evaluation {
+                    commitment,
+                    evaluations: vec![vec![*q_eval]],
+                    degree_bound: None,
+                })
+                .collect();
+            evaluations.push(Evaluation {
+                commitment: opening.quotient.clone(),
+                evaluations: vec![vec![quotient_eval]],
+                degree_bound: None,
+            });
+
+            reduced.push(BatchEvaluationProof {
+                sponge: sponge.clone(),
+                evaluations,
+                evaluation_points: vec![x],
+                polyscale,
+                evalscale: G::ScalarField::one(),
+                opening: &opening.proof,
+            });
+        }
+
+        self.verify(group_map, &mut reduced, rng)
+    }
+}
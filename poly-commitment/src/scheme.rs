@@ -0,0 +1,342 @@
+//! This module implements the [`PolynomialCommitment`] trait, the interface of a polynomial
+//! commitment scheme as used by a proof system:
+//!
+//! 1. commit to polynomials, in coefficient or evaluation form, chunked by the size of the scheme,
+//! 2. open a batch of committed polynomials at a few evaluation points, into a single opening proof,
+//! 3. verify batches of such opening proofs at once.
+//!
+//! The commitments are [`PolyComm`]s of curve points, whatever the scheme,
+//! so that the transcripts and the linearization of a proof system stay unchanged across schemes:
+//! a scheme only brings its own setup (its [`SRS`] or equivalent) and its own opening proofs.
+//! The inner product argument of the [`SRS`] implements the trait,
+//! and a pairing-based scheme (e.g. KZG over the first group of BN254 or BLS12-381)
+//! would implement it over the points of its first group.
+
+use crate::{
+    commitment::{BatchEvaluationProof, BlindedCommitment, CommitmentCurve, PolyComm},
+    error::{CommitmentError, OpeningError},
+    evaluation_proof::OpeningProof,
+    srs::SRS,
+};
+use ark_ff::{PrimeField, UniformRand, Zero};
+use ark_poly::{
+    univariate::DensePolynomial, EvaluationDomain, Evaluations, Radix2EvaluationDomain as D,
+};
+use o1_utils::math;
+use oracle::FqSponge;
+use rand_core::{CryptoRng, RngCore};
+use std::fmt::Debug;
+
+/// A polynomial to open in a batch, with its optional degree bound and the blinders of its commitment
+pub type OpeningPolynomial<'a, F> = (&'a DensePolynomial<F>, Option<usize>, PolyComm<F>);
+
+/// A polynomial commitment scheme over the points of `G` (see the [module documentation](self)),
+/// implemented by its setup, which the indexes of a proof system share
+pub trait PolynomialCommitment<G: CommitmentCurve>: Clone + Debug + Send + Sync {
+    /// The proof of a batched opening
+    type OpeningProof: Clone + Debug + Send + Sync;
+
+    /// Creates a setup for polynomials of up to `depth` coefficients per chunk.
+    fn create(depth: usize) -> Self
+    where
+        Self: Sized;
+
+    /// Precomputes the commitments to the Lagrange polynomials of `domain`,
+    /// with which polynomials in evaluation form over `domain` are committed.
+    fn add_lagrange_basis(&mut self, domain: D<G::ScalarField>);
+
+    /// Returns the commitments to the Lagrange polynomials of `domain`, if precomputed.
+    fn lagrange_basis(&self, domain: D<G::ScalarField>) -> Option<&[G]>;
+
+    /// Returns the maximum number of coefficients of a chunk of a committed polynomial.
+    fn max_poly_size(&self) -> usize;
+
+    /// Returns the point blinding the commitments, a blinder `w` adding `w` times the point to a chunk.
+    fn blinding_commitment(&self) -> G;
+
+    /// Blinds each chunk of `comm` with the corresponding blinder of `blinders`.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `blinders` do not have the shape of `comm`.
+    fn mask_custom(
+        &self,
+        comm: PolyComm<G>,
+        blinders: &PolyComm<G::ScalarField>,
+    ) -> Result<BlindedCommitment<G>, CommitmentError>;
+
+    /// Blinds each chunk of `comm` with a random blinder.
+    fn mask(
+        &self,
+        comm: PolyComm<G>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> BlindedCommitment<G> {
+        let blinders = comm.map(|_| G::ScalarField::rand(rng));
+        self.mask_custom(comm, &blinders)
+            .expect("the blinders have the shape of the commitment")
+    }
+
+    /// Commits to `plnm`, chunked by [`PolynomialCommitment::max_poly_size`],
+    /// with the degree bound `max` if any, and blinds the commitment.
+    fn commit(
+        &self,
+        plnm: &DensePolynomial<G::ScalarField>,
+        max: Option<usize>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> BlindedCommitment<G>;
+
+    /// Commits to `plnm` like [`PolynomialCommitment::commit`], without blinding the commitment.
+    fn commit_non_hiding(
+        &self,
+        plnm: &DensePolynomial<G::ScalarField>,
+        max: Option<usize>,
+    ) -> PolyComm<G>;
+
+    /// Commits to the polynomial of the evaluations `plnm` over `domain`, without blinding the commitment.
+    fn commit_evaluations_non_hiding(
+        &self,
+        domain: D<G::ScalarField>,
+        plnm: &Evaluations<G::ScalarField, D<G::ScalarField>>,
+        max: Option<usize>,
+    ) -> PolyComm<G>;
+
+    /// Commits to the polynomial of the evaluations `plnm` over `domain`, and blinds the commitment.
+    fn commit_evaluations(
+        &self,
+        domain: D<G::ScalarField>,
+        plnm: &Evaluations<G::ScalarField, D<G::ScalarField>>,
+        max: Option<usize>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> BlindedCommitment<G> {
+        self.mask(self.commit_evaluations_non_hiding(domain, plnm, max), rng)
+    }
+
+    /// Commits to the polynomial whose evaluations over `domain` are given as runs,
+    /// a value and the number of consecutive rows holding it, the rows past the runs being zero,
+    /// without blinding the commitment.
+    /// The commitment is the one of [`PolynomialCommitment::commit_evaluations_non_hiding`].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the runs cover more rows than `domain`.
+    fn commit_runs_non_hiding(
+        &self,
+        domain: D<G::ScalarField>,
+        runs: &[(G::ScalarField, usize)],
+    ) -> PolyComm<G> {
+        let mut evals = Vec::with_capacity(domain.size());
+        for (value, length) in runs {
+            evals.extend(std::iter::repeat(*value).take(*length));
+        }
+        assert!(evals.len() <= domain.size());
+        evals.resize(domain.size(), G::ScalarField::zero());
+        let evals = Evaluations::from_vec_and_domain(evals, domain);
+        self.commit_evaluations_non_hiding(domain, &evals, None)
+    }
+
+    /// Opens the polynomials `plnms` at the evaluation points `elm`,
+    /// combined with the powers of `polyscale` (across polynomials) and of `evalscale` (across points),
+    /// continuing the transcript of `sponge`.
+    #[allow(clippy::too_many_arguments)]
+    fn open<EFqSponge, RNG>(
+        &self,
+        group_map: &G::Map,
+        plnms: &[OpeningPolynomial<G::ScalarField>],
+        elm: &[G::ScalarField],
+        polyscale: G::ScalarField,
+        evalscale: G::ScalarField,
+        sponge: EFqSponge,
+        rng: &mut RNG,
+    ) -> Self::OpeningProof
+    where
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+        RNG: RngCore + CryptoRng,
+        G::BaseField: PrimeField;
+
+    /// Verifies the opening proofs of `batch` at once.
+    fn batch_verify<EFqSponge, RNG>(
+        &self,
+        group_map: &G::Map,
+        batch: &mut [BatchEvaluationProof<G, EFqSponge, Self::OpeningProof>],
+        rng: &mut RNG,
+    ) -> bool
+    where
+        EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
+        RNG: RngCore + CryptoRng,
+        G::BaseField: PrimeField;
+
+    /// Returns the number of rounds of the opening proof `proof`, and the number of rounds
+    /// of the opening proofs of the setup, for a scheme whose opening proofs run in rounds
+    /// (the inner product argument halving the setup at each round).
+    /// A scheme whose opening proofs have a constant size returns `None`.
+    fn opening_rounds(&self, _proof: &Self::OpeningProof) -> Option<(usize, usize)> {
+        None
+    }
+
+    /// Verifies the opening proofs of `batch` like [`PolynomialCommitment::batch_verify`],
+    /// reporting why it fails, which is slower:
+    /// the evaluations (and the rounds of the opening proofs) are checked to be well-formed,
+    /// and the proofs are then verified one by one.
+    /// Note that an opening proof only checks a random combination of the evaluations of a proof,
+    /// so that a wrong (but well-formed) evaluation can only be attributed to its proof.
+    ///
+    /// # Errors
+    ///
+    /// Will give error on the first malformed evaluation or invalid proof of the batch.
+    fn verify_with_diagnostics<EFqSponge, RNG>(
+        &self,
+        group_map: &G::Map,
+        batch: &mut [BatchEvaluationProof<G, EFqSponge, Self::OpeningProof>],
+        rng: &mut RNG,
+    ) -> Result<(), OpeningError>
+    where
+        EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
+        RNG: RngCore + CryptoRng,
+        G::BaseField: PrimeField,
+    {
+        for (proof, p) in batch.iter().enumerate() {
+            for (evaluation, e) in p.evaluations.iter().enumerate() {
+                if e.evaluations.len() != p.evaluation_points.len() {
+                    return Err(OpeningError::EvaluationPoints {
+                        proof,
+                        evaluation,
+                        rows: e.evaluations.len(),
+                        points: p.evaluation_points.len(),
+                    });
+                }
+                let expected = e.commitment.unshifted.len();
+                for (point, evals) in e.evaluations.iter().enumerate() {
+                    if evals.len() != expected {
+                        return Err(OpeningError::EvaluationChunks {
+                            proof,
+                            evaluation,
+                            point,
+                            chunks: evals.len(),
+                            expected,
+                        });
+                    }
+                }
+            }
+
+            if let Some((rounds, max)) = self.opening_rounds(p.opening) {
+                if rounds > max {
+                    return Err(OpeningError::Rounds { proof, rounds, max });
+                }
+            }
+        }
+
+        for (proof, p) in batch.iter_mut().enumerate() {
+            if !self.batch_verify(group_map, std::slice::from_mut(p), rng) {
+                return Err(OpeningError::Opening { proof });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The inner product argument
+impl<G: CommitmentCurve> PolynomialCommitment<G> for SRS<G> {
+    type OpeningProof = OpeningProof<G>;
+
+    fn create(depth: usize) -> Self {
+        SRS::create(depth)
+    }
+
+    fn add_lagrange_basis(&mut self, domain: D<G::ScalarField>) {
+        SRS::add_lagrange_basis(self, domain)
+    }
+
+    fn lagrange_basis(&self, domain: D<G::ScalarField>) -> Option<&[G]> {
+        self.lagrange_bases
+            .get(&domain.size())
+            .map(|basis| &basis[..])
+    }
+
+    fn max_poly_size(&self) -> usize {
+        self.max_degree()
+    }
+
+    fn blinding_commitment(&self) -> G {
+        self.h
+    }
+
+    fn mask_custom(
+        &self,
+        comm: PolyComm<G>,
+        blinders: &PolyComm<G::ScalarField>,
+    ) -> Result<BlindedCommitment<G>, CommitmentError> {
+        SRS::mask_custom(self, comm, blinders)
+    }
+
+    fn commit(
+        &self,
+        plnm: &DensePolynomial<G::ScalarField>,
+        max: Option<usize>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> BlindedCommitment<G> {
+        SRS::commit(self, plnm, max, rng)
+    }
+
+    fn commit_non_hiding(
+        &self,
+        plnm: &DensePolynomial<G::ScalarField>,
+        max: Option<usize>,
+    ) -> PolyComm<G> {
+        SRS::commit_non_hiding(self, plnm, max)
+    }
+
+    fn commit_evaluations_non_hiding(
+        &self,
+        domain: D<G::ScalarField>,
+        plnm: &Evaluations<G::ScalarField, D<G::ScalarField>>,
+        max: Option<usize>,
+    ) -> PolyComm<G> {
+        SRS::commit_evaluations_non_hiding(self, domain, plnm, max)
+    }
+
+    fn commit_runs_non_hiding(
+        &self,
+        domain: D<G::ScalarField>,
+        runs: &[(G::ScalarField, usize)],
+    ) -> PolyComm<G> {
+        SRS::commit_runs_non_hiding(self, domain, runs)
+    }
+
+    fn open<EFqSponge, RNG>(
+        &self,
+        group_map: &G::Map,
+        plnms: &[OpeningPolynomial<G::ScalarField>],
+        elm: &[G::ScalarField],
+        polyscale: G::ScalarField,
+        evalscale: G::ScalarField,
+        sponge: EFqSponge,
+        rng: &mut RNG,
+    ) -> OpeningProof<G>
+    where
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+        RNG: RngCore + CryptoRng,
+        G::BaseField: PrimeField,
+    {
+        SRS::open(
+            self, group_map, plnms, elm, polyscale, evalscale, sponge, rng,
+        )
+    }
+
+    fn batch_verify<EFqSponge, RNG>(
+        &self,
+        group_map: &G::Map,
+        batch: &mut [BatchEvaluationProof<G, EFqSponge>],
+        rng: &mut RNG,
+    ) -> bool
+    where
+        EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
+        RNG: RngCore + CryptoRng,
+        G::BaseField: PrimeField,
+    {
+        SRS::verify(self, group_map, batch, rng)
+    }
+
+    fn opening_rounds(&self, proof: &OpeningProof<G>) -> Option<(usize, usize)> {
+        Some((proof.lr.len(), math::ceil_log2(self.g.len())))
+    }
+}
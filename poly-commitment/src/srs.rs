@@ -1,15 +1,25 @@
 //! This module implements the Marlin structured reference string primitive
 
-use crate::commitment::CommitmentCurve;
-use ark_ec::{AffineCurve, ProjectiveCurve};
+use crate::{commitment::CommitmentCurve, msm::MsmTable};
+use ark_ec::AffineCurve;
 use ark_ff::{BigInteger, PrimeField};
 use ark_poly::{EvaluationDomain, Radix2EvaluationDomain as D};
 use blake2::{Blake2b512, Digest};
 use groupmap::GroupMap;
+use o1_utils::curve_helpers::batch_normalize;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use std::array;
 use std::collections::HashMap;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+/// The number of group elements handled at once by a thread
+/// when creating the SRS.
+const GENERATORS_PER_TASK: usize = 1 << 10;
 
 #[serde_as]
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -25,6 +35,9 @@ pub struct SRS<G: CommitmentCurve> {
     /// Commitments to Lagrange bases, per domain size
     #[serde(skip)]
     pub lagrange_bases: HashMap<usize, Vec<G>>,
+    /// The precomputed multiples of `g` for the MSMs of the commitments, if any
+    #[serde(skip)]
+    pub msm_table: Option<Arc<MsmTable<G>>>,
     /// Coefficient for the curve endomorphism
     #[serde(skip)]
     pub endo_r: G::ScalarField,
@@ -49,7 +62,9 @@ pub fn endos<G: CommitmentCurve>() -> (G::BaseField, G::ScalarField) {
     (endo_q, endo_r)
 }
 
-fn point_of_random_bytes<G: CommitmentCurve>(map: &G::Map, random_bytes: &[u8]) -> G {
+/// Maps random bytes (only the first 31 are used) to a point of the curve, with the group map.
+/// The resulting point has an unknown discrete logarithm.
+pub fn point_of_random_bytes<G: CommitmentCurve>(map: &G::Map, random_bytes: &[u8]) -> G {
     // packing in bit-representation
     const N: usize = 31;
     let mut bits = [false; 8 * N];
@@ -65,6 +80,13 @@ fn point_of_random_bytes<G: CommitmentCurve>(map: &G::Map, random_bytes: &[u8])
     G::of_coordinates(x, y)
 }
 
+/// Returns the `i`-th generator of the SRS.
+fn generator<G: CommitmentCurve>(map: &G::Map, i: usize) -> G {
+    let mut h = Blake2b512::new();
+    h.update((i as u32).to_be_bytes());
+    point_of_random_bytes(map, &h.finalize())
+}
+
 impl<G: CommitmentCurve> SRS<G> {
     pub fn max_degree(&self) -> usize {
         self.g.len()
@@ -145,23 +167,46 @@ impl<G: CommitmentCurve> SRS<G> {
             self.g[0..n].iter().map(|g| g.into_projective()).collect();
         domain.ifft_in_place(&mut lg);
 
-        <G as AffineCurve>::Projective::batch_normalization(lg.as_mut_slice());
-        self.lagrange_bases
-            .insert(n, lg.iter().map(|g| g.into_affine()).collect());
+        self.lagrange_bases.insert(n, batch_normalize(&lg));
+    }
+
+    /// Precomputes the multiples of the generators `g` used by the MSMs of the commitments
+    /// to polynomials in coefficient form (see [`MsmTable`]).
+    /// The table holds a copy of the generators per window of a scalar (e.g. 16 copies for 2^16 generators),
+    /// and must be added again if the generators change.
+    pub fn add_msm_table(&mut self) {
+        self.msm_table = Some(Arc::new(MsmTable::new(&self.g)));
     }
 
     /// This function creates SRS instance for circuits with number of rows up to `depth`.
     pub fn create(depth: usize) -> Self {
+        Self::create_with_progress(depth, |_, _| {})
+    }
+
+    /// Same as [`SRS::create`], but calls `progress` with the number of generators created
+    /// so far and `depth`, every time a batch of generators is done.
+    /// As the generators are created in parallel, `progress` can be called from several threads.
+    pub fn create_with_progress(depth: usize, progress: impl Fn(usize, usize) + Sync) -> Self {
         let m = G::Map::setup();
 
-        let g: Vec<_> = (0..depth)
-            .map(|i| {
-                let mut h = Blake2b512::new();
-                h.update(&(i as u32).to_be_bytes());
-                point_of_random_bytes(&m, &h.finalize())
-            })
-            .collect();
+        let created = AtomicUsize::new(0);
+        let mut g = vec![G::zero(); depth];
+        g.par_chunks_mut(GENERATORS_PER_TASK)
+            .enumerate()
+            .for_each(|(i, chunk)| {
+                for (j, g) in chunk.iter_mut().enumerate() {
+                    *g = generator(&m, i * GENERATORS_PER_TASK + j);
+                }
+                let done = created.fetch_add(chunk.len(), Ordering::Relaxed) + chunk.len();
+                progress(done, depth);
+            });
+
+        Self::with_generators(&m, g)
+    }
 
+    /// Returns the SRS made of the generators `g`,
+    /// along with the same blinding generator as [`SRS::create`].
+    pub(crate) fn with_generators(m: &G::Map, g: Vec<G>) -> Self {
         let (endo_q, endo_r) = endos::<G>();
 
         const MISC: usize = 1;
@@ -169,13 +214,14 @@ impl<G: CommitmentCurve> SRS<G> {
             let mut h = Blake2b512::new();
             h.update("srs_misc".as_bytes());
             h.update(&(i as u32).to_be_bytes());
-            point_of_random_bytes(&m, &h.finalize())
+            point_of_random_bytes(m, &h.finalize())
         });
 
         SRS {
             g,
             h,
             lagrange_bases: HashMap::new(),
+            msm_table: None,
             endo_r,
             endo_q,
         }
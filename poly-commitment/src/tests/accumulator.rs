@@ -0,0 +1,77 @@
+use crate::{
+    accumulator::{AccumulatorCheck, IpaAccumulator},
+    commitment::b_poly_coefficients,
+    error::AccumulatorError,
+    srs::SRS,
+};
+use ark_ff::{One, UniformRand};
+use ark_poly::{univariate::DensePolynomial, UVPolynomial};
+use mina_curves::pasta::{Fp, Vesta};
+use rand::SeedableRng;
+
+/// Returns the accumulator of random challenges, committed with `srs`
+fn accumulator(srs: &SRS<Vesta>, rounds: usize, rng: &mut impl rand::Rng) -> IpaAccumulator<Vesta> {
+    let chals: Vec<_> = (0..rounds).map(|_| Fp::rand(rng)).collect();
+    let b = DensePolynomial::from_coefficients_vec(b_poly_coefficients(&chals));
+    let sg = srs.commit_non_hiding(&b, None).unshifted[0];
+    IpaAccumulator { chals, sg }
+}
+
+#[test]
+fn test_accumulator_check() {
+    let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(0);
+    let srs = SRS::<Vesta>::create(1 << 6);
+
+    // the accumulators are split across two checks, as two messages would
+    let mut check = AccumulatorCheck::new(&srs);
+    let mut other = AccumulatorCheck::new(&srs);
+    for _ in 0..3 {
+        check
+            .add(&accumulator(&srs, 6, &mut rng), &mut rng)
+            .unwrap();
+        other
+            .add(&accumulator(&srs, 6, &mut rng), &mut rng)
+            .unwrap();
+    }
+    // fewer challenges only use the first generators
+    other
+        .add(&accumulator(&srs, 4, &mut rng), &mut rng)
+        .unwrap();
+    check.verify(&srs).unwrap();
+    other.verify(&srs).unwrap();
+
+    check.merge(&other).unwrap();
+    assert_eq!(check.len(), 7);
+    check.verify(&srs).unwrap();
+
+    // a wrong commitment is caught by the single MSM
+    let mut bad = accumulator(&srs, 6, &mut rng);
+    bad.chals[0] += Fp::one();
+    check.add(&bad, &mut rng).unwrap();
+    assert_eq!(check.verify(&srs), Err(AccumulatorError::Commitment));
+
+    // so are accumulators or checks of another SRS
+    let mut check = AccumulatorCheck::new(&srs);
+    let err = check.add(&accumulator(&srs, 7, &mut rng), &mut rng);
+    assert!(matches!(
+        err,
+        Err(AccumulatorError::Rounds { rounds: 7, .. })
+    ));
+    let small = SRS::<Vesta>::create(1 << 5);
+    assert!(check.merge(&AccumulatorCheck::new(&small)).is_err());
+    assert!(check.verify(&small).is_err());
+}
+
+#[test]
+fn test_accumulator_non_power_of_two_srs() {
+    let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(1);
+    let srs = SRS::<Vesta>::create(48);
+
+    let mut check = AccumulatorCheck::new(&srs);
+    assert!(check.is_empty());
+    check.verify(&srs).unwrap();
+    check
+        .add(&accumulator(&srs, 5, &mut rng), &mut rng)
+        .unwrap();
+    check.verify(&srs).unwrap();
+}
@@ -0,0 +1,161 @@
+use crate::{
+    aggregation::{AggregatedEvaluationProof, ClaimEvaluations, OpeningClaim},
+    commitment::{BlindedCommitment, CommitmentCurve, Evaluation},
+    error::CommitmentError,
+    srs::SRS,
+};
+use ark_ff::{One, UniformRand};
+use ark_poly::{univariate::DensePolynomial, UVPolynomial};
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use o1_utils::ExtendedDensePolynomial as _;
+use oracle::constants::PlonkSpongeConstantsKimchi as SC;
+use oracle::sponge::DefaultFqSponge;
+use oracle::FqSponge as _;
+use rand::{Rng, SeedableRng};
+
+type Sponge = DefaultFqSponge<VestaParameters, SC>;
+
+/// The polynomials of a claim, with their commitments, and their evaluations at the points of the claim
+struct Claim {
+    polys: Vec<(DensePolynomial<Fp>, BlindedCommitment<Vesta>)>,
+    points: Vec<Fp>,
+}
+
+impl Claim {
+    /// Creates a claim of 4 polynomials of random degree (possibly chunked), at 2 random points
+    fn rand(srs: &SRS<Vesta>, rng: &mut rand_chacha::ChaCha20Rng) -> Self {
+        let polys = (0..4)
+            .map(|_| {
+                let poly = DensePolynomial::<Fp>::rand(rng.gen_range(1..300), rng);
+                let comm = srs.commit(&poly, None, rng);
+                (poly, comm)
+            })
+            .collect();
+        let points = (0..2).map(|_| Fp::rand(rng)).collect();
+        Self { polys, points }
+    }
+
+    fn opening_claim(&self) -> OpeningClaim<'_, Vesta> {
+        OpeningClaim {
+            polys: self.polys.iter().map(|(poly, comm)| (poly, comm)).collect(),
+            points: self.points.clone(),
+        }
+    }
+
+    fn evaluations(&self, srs: &SRS<Vesta>) -> ClaimEvaluations<Vesta> {
+        ClaimEvaluations {
+            evaluations: self
+                .polys
+                .iter()
+                .map(|(poly, comm)| Evaluation {
+                    commitment: comm.commitment.clone(),
+                    evaluations: self
+                        .points
+                        .iter()
+                        .map(|x| poly.to_chunked_polynomial(srs.g.len()).evaluate_chunks(*x))
+                        .collect(),
+                    degree_bound: None,
+                })
+                .collect(),
+            points: self.points.clone(),
+        }
+    }
+}
+
+#[test]
+fn test_aggregated_opening() {
+    let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(0);
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let sponge = Sponge::new(oracle::pasta::fq_kimchi::static_params());
+    let srs = SRS::<Vesta>::create(1 << 7);
+
+    let claims: Vec<_> = (0..5).map(|_| Claim::rand(&srs, &mut rng)).collect();
+    let opening_claims: Vec<_> = claims.iter().map(Claim::opening_claim).collect();
+    let proof = srs
+        .open_aggregated(&group_map, &opening_claims, sponge.clone(), &mut rng)
+        .unwrap();
+
+    let batch = |claims: Vec<ClaimEvaluations<Vesta>>| {
+        vec![AggregatedEvaluationProof {
+            sponge: sponge.clone(),
+            claims,
+            opening: &proof,
+        }]
+    };
+    let evaluations = || {
+        claims
+            .iter()
+            .map(|c| c.evaluations(&srs))
+            .collect::<Vec<_>>()
+    };
+    assert!(srs.verify_aggregated(&group_map, &mut batch(evaluations()), &mut rng));
+
+    // the aggregated proof ships a single opening proof, instead of one per claim
+    let separate: Vec<_> = claims
+        .iter()
+        .map(|claim| {
+            let polys: Vec<_> = claim
+                .polys
+                .iter()
+                .map(|(poly, comm)| (poly, None, comm.blinders.clone()))
+                .collect();
+            srs.open(
+                &group_map,
+                &polys,
+                &claim.points,
+                Fp::rand(&mut rng),
+                Fp::rand(&mut rng),
+                sponge.clone(),
+                &mut rng,
+            )
+        })
+        .collect();
+    let separate_points: usize = separate.iter().map(|p| 2 * p.lr.len() + 2).sum();
+    let aggregated_points = 2 * proof.proof.lr.len() + 2 + proof.quotient.unshifted.len();
+    assert!(aggregated_points * 4 < separate_points);
+    assert_eq!(proof.evaluations.len(), claims.len());
+
+    // a wrong evaluation is rejected
+    let mut wrong = evaluations();
+    wrong[3].evaluations[1].evaluations[0][0] += Fp::one();
+    assert!(!srs.verify_aggregated(&group_map, &mut batch(wrong), &mut rng));
+
+    // as are claims in another order, or a missing claim
+    let mut swapped = evaluations();
+    swapped.swap(0, 1);
+    assert!(!srs.verify_aggregated(&group_map, &mut batch(swapped), &mut rng));
+    let mut missing = evaluations();
+    missing.pop();
+    assert!(!srs.verify_aggregated(&group_map, &mut batch(missing), &mut rng));
+
+    // and claims with a repeated point, without panicking
+    let mut repeated = evaluations();
+    repeated[0].points[1] = repeated[0].points[0];
+    assert!(!srs.verify_aggregated(&group_map, &mut batch(repeated), &mut rng));
+
+    // nor can such claims be proven
+    let mut opening_claims: Vec<_> = claims.iter().map(Claim::opening_claim).collect();
+    opening_claims[2].points[1] = opening_claims[2].points[0];
+    assert!(matches!(
+        srs.open_aggregated(&group_map, &opening_claims, sponge.clone(), &mut rng),
+        Err(CommitmentError::ClaimRepeatsPoint(2))
+    ));
+    opening_claims[2].points.clear();
+    assert!(matches!(
+        srs.open_aggregated(&group_map, &opening_claims, sponge.clone(), &mut rng),
+        Err(CommitmentError::ClaimWithoutPoints(2))
+    ));
+
+    // nor claims on commitments with a degree bound
+    let poly = DensePolynomial::<Fp>::rand(20, &mut rng);
+    let bounded = srs.commit(&poly, Some(30), &mut rng);
+    let bounded_claim = OpeningClaim {
+        polys: vec![(&poly, &bounded)],
+        points: vec![Fp::rand(&mut rng)],
+    };
+    assert!(matches!(
+        srs.open_aggregated(&group_map, &[bounded_claim], sponge, &mut rng),
+        Err(CommitmentError::ClaimWithDegreeBound(0))
+    ));
+}
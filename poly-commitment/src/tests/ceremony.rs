@@ -0,0 +1,125 @@
+use crate::{
+    ceremony::{Accumulator, Contribution},
+    commitment::CommitmentCurve,
+    error::CeremonyError,
+};
+use ark_ec::AffineCurve;
+use ark_ff::{UniformRand, Zero};
+use ark_poly::{EvaluationDomain, Radix2EvaluationDomain as D};
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use oracle::constants::PlonkSpongeConstantsKimchi as SC;
+use oracle::sponge::DefaultFqSponge;
+use oracle::FqSponge as _;
+use rand::SeedableRng;
+
+type Sponge = DefaultFqSponge<VestaParameters, SC>;
+
+fn sponge() -> Sponge {
+    Sponge::new(oracle::pasta::fq_kimchi::static_params())
+}
+
+#[test]
+fn test_ceremony() {
+    let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(0);
+    let depth = 1 << 5;
+
+    let mut accumulator = Accumulator::<Vesta>::new(depth);
+    let mut contributions = vec![];
+    for _ in 0..3 {
+        let contribution = accumulator.contribute(sponge(), &mut rng);
+        contributions.push(contribution.clone());
+        accumulator.update(contribution, sponge()).unwrap();
+    }
+    assert_eq!(accumulator.public_keys.len(), 3);
+    assert_eq!(accumulator.powers[0], Vesta::prime_subgroup_generator());
+    assert_ne!(accumulator.powers[1], accumulator.powers[0]);
+
+    // the transcript leads to the same accumulator
+    let replayed = Accumulator::verify_transcript(depth, contributions, sponge()).unwrap();
+    assert_eq!(replayed.powers, accumulator.powers);
+    assert_eq!(replayed.public_keys, accumulator.public_keys);
+
+    // and the extracted SRS can open commitments
+    let mut srs = accumulator.into_srs();
+    assert_eq!(srs.g.len(), depth);
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let domain = D::<Fp>::new(depth).unwrap();
+    srs.add_lagrange_basis(domain);
+    let values: Vec<_> = (0..depth).map(|_| Fp::rand(&mut rng)).collect();
+    let commitment = srs.commit_vector(domain, &values, &mut rng);
+    let proof = srs.open_vector(
+        &group_map,
+        domain,
+        &values,
+        &commitment,
+        &[3],
+        sponge(),
+        &mut rng,
+    );
+    assert!(srs.verify_vector(
+        &group_map,
+        domain,
+        &commitment.commitment,
+        &[(3, values[3])],
+        &proof,
+        sponge(),
+        &mut rng,
+    ));
+}
+
+#[test]
+fn test_ceremony_bad_contributions() {
+    let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(0);
+    let depth = 10;
+
+    let mut accumulator = Accumulator::<Vesta>::new(depth);
+    let first = accumulator.contribute(sponge(), &mut rng);
+    accumulator.update(first, sponge()).unwrap();
+    let contribution = accumulator.contribute(sponge(), &mut rng);
+
+    let check = |tamper: &dyn Fn(&mut Contribution<Vesta>)| {
+        let mut contribution = contribution.clone();
+        tamper(&mut contribution);
+        let mut accumulator = accumulator.clone();
+        let res = accumulator.update(contribution, sponge());
+        assert_eq!(accumulator.public_keys.len(), 1);
+        res.unwrap_err()
+    };
+
+    // a power that is not re-randomized
+    let err = check(&|c| c.powers[4] = c.powers[3]);
+    assert_eq!(err, CeremonyError::Rerandomization { contribution: 1 });
+
+    // a contribution to another accumulator
+    let err = check(&|c| c.powers.swap(1, 2));
+    assert_eq!(err, CeremonyError::Rerandomization { contribution: 1 });
+
+    // a wrong partial sum
+    let err = check(&|c| c.partial_sums[5] = c.partial_sums[6]);
+    assert_eq!(err, CeremonyError::Rerandomization { contribution: 1 });
+
+    // a wrong proof of discrete logarithm equality
+    let err = check(&|c| c.response += Fp::from(1u64));
+    assert_eq!(err, CeremonyError::Rerandomization { contribution: 1 });
+
+    // malformed contributions
+    let err = check(&|c| {
+        c.powers.pop();
+    });
+    assert_eq!(
+        err,
+        CeremonyError::Powers {
+            contribution: 1,
+            powers: depth - 1,
+            expected: depth
+        }
+    );
+    let err = check(&|c| c.powers[0] = c.powers[1]);
+    assert_eq!(err, CeremonyError::FirstPower { contribution: 1 });
+    let err = check(&|c| c.public_key = Vesta::zero());
+    assert_eq!(err, CeremonyError::PublicKey { contribution: 1 });
+
+    // the untampered contribution is accepted
+    accumulator.update(contribution, sponge()).unwrap();
+}
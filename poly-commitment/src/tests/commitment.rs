@@ -1,9 +1,14 @@
 use crate::{
-    commitment::{BatchEvaluationProof, BlindedCommitment, CommitmentCurve, Evaluation, PolyComm},
+    commitment::{
+        combined_inner_product, BatchEvaluationProof, BlindedCommitment, CommitmentCurve,
+        Evaluation, PolyComm,
+    },
+    error::OpeningError,
     evaluation_proof::OpeningProof,
+    scheme::PolynomialCommitment as _,
     srs::SRS,
 };
-use ark_ff::{UniformRand, Zero};
+use ark_ff::{Field, One, UniformRand, Zero};
 use ark_poly::{univariate::DensePolynomial, UVPolynomial};
 use colored::Colorize;
 use groupmap::GroupMap;
@@ -218,6 +223,64 @@ fn test_randomised<RNG: Rng + CryptoRng>(mut rng: &mut RNG) {
         "batch verification time:".green(),
         timer.elapsed()
     );
+
+    // the openings with the same challenges share their folded challenge products
+    let mut batch: Vec<_> = proofs
+        .iter()
+        .chain(&proofs[..3])
+        .map(|p| p.verify_type())
+        .collect();
+    assert!(srs.verify::<DefaultFqSponge<VestaParameters, SC>, _>(&group_map, &mut batch, &mut rng));
+    let mut batch: Vec<_> = proofs
+        .iter()
+        .chain(&proofs[..3])
+        .map(|p| p.verify_type())
+        .collect();
+    batch[8].opening = &proofs[2].proof;
+    assert!(
+        !srs.verify::<DefaultFqSponge<VestaParameters, SC>, _>(&group_map, &mut batch, &mut rng)
+    );
+
+    // the diagnostic mode accepts the same batch
+    let mut batch: Vec<_> = proofs.iter().map(|p| p.verify_type()).collect();
+    assert_eq!(
+        srs.verify_with_diagnostics(&group_map, &mut batch, &mut rng),
+        Ok(())
+    );
+
+    // and reports a wrong evaluation
+    let mut batch: Vec<_> = proofs.iter().map(|p| p.verify_type()).collect();
+    batch[3].evaluations[5].evaluations[2][0] += Fp::from(1u64);
+    assert_eq!(
+        srs.verify_with_diagnostics(&group_map, &mut batch, &mut rng),
+        Err(OpeningError::Opening { proof: 3 })
+    );
+
+    // or a malformed one
+    let mut batch: Vec<_> = proofs.iter().map(|p| p.verify_type()).collect();
+    let chunks = batch[4].evaluations[1].evaluations[6].len();
+    batch[4].evaluations[1].evaluations[6].push(Fp::zero());
+    assert_eq!(
+        srs.verify_with_diagnostics(&group_map, &mut batch, &mut rng),
+        Err(OpeningError::EvaluationChunks {
+            proof: 4,
+            evaluation: 1,
+            point: 6,
+            chunks: chunks + 1,
+            expected: chunks,
+        })
+    );
+    let mut batch: Vec<_> = proofs.iter().map(|p| p.verify_type()).collect();
+    batch[2].evaluations[0].evaluations.pop();
+    assert_eq!(
+        srs.verify_with_diagnostics(&group_map, &mut batch, &mut rng),
+        Err(OpeningError::EvaluationPoints {
+            proof: 2,
+            evaluation: 0,
+            rows: 6,
+            points: 7,
+        })
+    );
 }
 
 #[test]
@@ -248,3 +311,70 @@ where
     let mut rng = <rand_chacha::ChaCha20Rng as SeedableRng>::from_seed(seed);
     test_randomised(&mut rng)
 }
+
+#[test]
+fn test_combined_inner_product() {
+    let rng = &mut rand::thread_rng();
+    let srs_length = 8;
+    let points = [Fp::rand(rng), Fp::rand(rng)];
+    let (polyscale, evalscale) = (Fp::rand(rng), Fp::rand(rng));
+
+    // evaluations at each point of each segment
+    let mut evals =
+        || -> Vec<Vec<Fp>> { (0..2).map(|_| vec![Fp::rand(rng), Fp::rand(rng)]).collect() };
+    let (a, b, c) = (evals(), evals(), evals());
+    let polys: Vec<(Vec<&[Fp]>, Option<usize>)> = vec![
+        (a.iter().map(|e| &e[..]).collect(), None),
+        (vec![&[][..], &[][..]], None),
+        (b.iter().map(|e| &e[..]).collect(), Some(11)),
+        (c.iter().map(|e| &e[..]).collect(), Some(16)),
+    ];
+
+    // each segment, then the shifted last segment, scaled by the successive powers of polyscale
+    let combine = |e: &[Vec<Fp>], segment: usize, shift: Option<u64>| {
+        e.iter()
+            .zip(&points)
+            .enumerate()
+            .map(|(j, (e, x))| {
+                let shifted = shift.map_or(Fp::one(), |s| x.pow([s]));
+                evalscale.pow([j as u64]) * shifted * e[segment]
+            })
+            .fold(Fp::zero(), |acc, t| acc + t)
+    };
+    let terms = [
+        combine(&a, 0, None),
+        combine(&a, 1, None),
+        combine(&b, 0, None),
+        combine(&b, 1, None),
+        combine(&b, 1, Some(5)),
+        combine(&c, 0, None),
+        combine(&c, 1, None),
+        // the bound covers all the segments, so that nothing is shifted
+        Fp::zero(),
+    ];
+    let expected = terms.iter().enumerate().fold(Fp::zero(), |acc, (i, t)| {
+        acc + polyscale.pow([i as u64]) * t
+    });
+
+    assert_eq!(
+        combined_inner_product(&points, &polyscale, &evalscale, &polys, srs_length),
+        expected
+    );
+}
+
+#[test]
+fn test_commitment_difference_in_chunks() {
+    let rng = &mut rand::thread_rng();
+    let srs = SRS::<Vesta>::create(8);
+
+    // the difference of commitments is the commitment to the difference of the polynomials,
+    // whatever their number of chunks
+    let short = DensePolynomial::<Fp>::rand(5, rng);
+    let long = DensePolynomial::<Fp>::rand(20, rng);
+    let diff = &srs.commit_non_hiding(&short, None) - &srs.commit_non_hiding(&long, None);
+    assert_eq!(diff.unshifted.len(), 3);
+    assert_eq!(
+        diff.unshifted,
+        srs.commit_non_hiding(&(&short - &long), None).unshifted
+    );
+}
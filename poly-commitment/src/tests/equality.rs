@@ -0,0 +1,85 @@
+use crate::{error::CommitmentError, srs::SRS};
+use ark_ff::{One, UniformRand};
+use ark_poly::{univariate::DensePolynomial, UVPolynomial};
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use oracle::constants::PlonkSpongeConstantsKimchi as SC;
+use oracle::sponge::DefaultFqSponge;
+use oracle::FqSponge as _;
+use rand::SeedableRng;
+
+type Sponge = DefaultFqSponge<VestaParameters, SC>;
+
+#[test]
+fn test_equality_proof() {
+    let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(0);
+    let sponge = Sponge::new(oracle::pasta::fq_kimchi::static_params());
+    let srs = SRS::<Vesta>::create(1 << 5);
+
+    // a polynomial in 3 chunks, with a degree bound
+    let poly = DensePolynomial::<Fp>::rand(80, &mut rng);
+    let comm = srs.commit(&poly, Some(90), &mut rng);
+    assert_eq!(comm.commitment.unshifted.len(), 3);
+
+    let rerandomized = srs.rerandomize(&comm, &mut rng);
+    assert_ne!(rerandomized.commitment.unshifted, comm.commitment.unshifted);
+    assert_ne!(rerandomized.commitment.shifted, comm.commitment.shifted);
+    let proof = srs
+        .prove_equality(&comm, &rerandomized, sponge.clone(), &mut rng)
+        .unwrap();
+    assert!(srs.verify_equality(
+        &comm.commitment,
+        &rerandomized.commitment,
+        &proof,
+        sponge.clone()
+    ));
+
+    // the proof is bound to the commitments, in order
+    assert!(!srs.verify_equality(
+        &rerandomized.commitment,
+        &comm.commitment,
+        &proof,
+        sponge.clone()
+    ));
+    let other = srs.commit(&poly, Some(90), &mut rng);
+    assert!(!srs.verify_equality(&comm.commitment, &other.commitment, &proof, sponge.clone()));
+
+    // a commitment to another polynomial cannot be proven equal
+    let mut other_poly = poly.clone();
+    other_poly.coeffs[0] += Fp::one();
+    let other = srs.commit(&other_poly, Some(90), &mut rng);
+    let proof = srs
+        .prove_equality(&comm, &other, sponge.clone(), &mut rng)
+        .unwrap();
+    assert!(!srs.verify_equality(&comm.commitment, &other.commitment, &proof, sponge.clone()));
+
+    // nor a commitment with another number of chunks
+    let truncated = srs.commit(
+        &DensePolynomial::from_coefficients_slice(&poly.coeffs[..40]),
+        Some(90),
+        &mut rng,
+    );
+    assert!(matches!(
+        srs.prove_equality(&comm, &truncated, sponge.clone(), &mut rng),
+        Err(CommitmentError::ChunksDontMatch(3, 2))
+    ));
+    let proof = srs
+        .prove_equality(&comm, &rerandomized, sponge.clone(), &mut rng)
+        .unwrap();
+    assert!(!srs.verify_equality(&comm.commitment, &truncated.commitment, &proof, sponge));
+}
+
+#[test]
+fn test_equality_proof_wrong_response() {
+    let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(0);
+    let sponge = Sponge::new(oracle::pasta::fq_kimchi::static_params());
+    let srs = SRS::<Vesta>::create(1 << 5);
+
+    let poly = DensePolynomial::<Fp>::rand(20, &mut rng);
+    let comm = srs.commit(&poly, None, &mut rng);
+    let rerandomized = srs.rerandomize(&comm, &mut rng);
+    let mut proof = srs
+        .prove_equality(&comm, &rerandomized, sponge.clone(), &mut rng)
+        .unwrap();
+    proof.response = Fp::rand(&mut rng);
+    assert!(!srs.verify_equality(&comm.commitment, &rerandomized.commitment, &proof, sponge));
+}
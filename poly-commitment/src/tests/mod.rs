@@ -1,2 +1,11 @@
+mod accumulator;
+mod aggregation;
 mod batch_15_wires;
+mod ceremony;
 mod commitment;
+mod equality;
+mod msm;
+mod multi_point;
+mod scheme;
+mod srs;
+mod vector_commitment;
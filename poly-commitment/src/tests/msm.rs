@@ -0,0 +1,87 @@
+use crate::{
+    msm::{multi_scalar_mul, straus, MsmBackend, MsmTable, PRECOMPUTED_MIN_SIZE, STRAUS_MAX_SIZE},
+    srs::SRS,
+};
+use ark_ec::{msm::VariableBaseMSM, AffineCurve, ProjectiveCurve};
+use ark_ff::{PrimeField, UniformRand, Zero};
+use ark_poly::{univariate::DensePolynomial, UVPolynomial};
+use mina_curves::pasta::{Fp, Vesta};
+use rand::{rngs::StdRng, SeedableRng};
+
+#[test]
+fn test_msm_backend_selection() {
+    assert_eq!(MsmBackend::select(0, false), MsmBackend::Straus);
+    assert_eq!(
+        MsmBackend::select(STRAUS_MAX_SIZE, true),
+        MsmBackend::Straus
+    );
+    assert_eq!(
+        MsmBackend::select(STRAUS_MAX_SIZE + 1, true),
+        MsmBackend::Pippenger
+    );
+    assert_eq!(
+        MsmBackend::select(PRECOMPUTED_MIN_SIZE, false),
+        MsmBackend::Pippenger
+    );
+    assert_eq!(
+        MsmBackend::select(PRECOMPUTED_MIN_SIZE, true),
+        MsmBackend::Precomputed
+    );
+}
+
+#[test]
+fn test_msm_backends() {
+    let rng = &mut StdRng::from_seed([0u8; 32]);
+    let srs = SRS::<Vesta>::create(PRECOMPUTED_MIN_SIZE + 50);
+    let table = MsmTable::new(&srs.g);
+
+    for size in [0, 1, 7, STRAUS_MAX_SIZE, STRAUS_MAX_SIZE + 1, srs.g.len()] {
+        let mut scalars: Vec<_> = (0..size).map(|_| Fp::rand(rng)).collect();
+        // the extreme scalars
+        if size >= 2 {
+            scalars[0] = Fp::zero();
+            scalars[1] = -Fp::from(1u64);
+        }
+        let scalars: Vec<_> = scalars.iter().map(|s| s.into_repr()).collect();
+        let expected = VariableBaseMSM::multi_scalar_mul(&srs.g, &scalars);
+
+        assert_eq!(straus(&srs.g, &scalars), expected);
+        assert_eq!(multi_scalar_mul(&srs.g, &scalars), expected);
+        assert_eq!(table.multi_scalar_mul(0, &scalars), expected);
+        assert_eq!(table.precomputed(0, &scalars), expected);
+
+        // from an offset of the table
+        let expected = VariableBaseMSM::multi_scalar_mul(&srs.g[3..], &scalars);
+        assert_eq!(table.precomputed(3, &scalars), expected);
+    }
+}
+
+#[test]
+fn test_commit_with_msm_table() {
+    let rng = &mut StdRng::from_seed([0u8; 32]);
+    let mut srs = SRS::<Vesta>::create(PRECOMPUTED_MIN_SIZE * 2);
+    let n = srs.g.len();
+
+    // two chunks, the last one shifted
+    let plnm = DensePolynomial::<Fp>::rand(n + 300, rng);
+    let bound = Some(n + 301);
+    let expected = srs.commit_non_hiding(&plnm, bound);
+    assert_eq!(expected.unshifted.len(), 2);
+    assert!(expected.shifted.is_some());
+
+    srs.add_msm_table();
+    assert_eq!(srs.commit_non_hiding(&plnm, bound), expected);
+
+    // a commitment is the sum of the scaled generators
+    let sum = srs.g[..10]
+        .iter()
+        .zip(&plnm.coeffs)
+        .fold(<Vesta as AffineCurve>::Projective::zero(), |sum, (g, c)| {
+            sum + g.mul(*c)
+        });
+    let plnm = DensePolynomial::from_coefficients_slice(&plnm.coeffs[..10]);
+    assert_eq!(
+        srs.commit_non_hiding(&plnm, None).unshifted,
+        vec![sum.into_affine()]
+    );
+}
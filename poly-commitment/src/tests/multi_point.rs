@@ -0,0 +1,142 @@
+use crate::{
+    commitment::{BlindedCommitment, CommitmentCurve},
+    multi_point::{
+        MultiPointEvaluation, MultiPointEvaluationProof, MultiPointOpeningProof,
+        MultiPointPolynomial,
+    },
+    srs::SRS,
+};
+use ark_ff::{UniformRand, Zero};
+use ark_poly::{univariate::DensePolynomial, UVPolynomial};
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use o1_utils::ExtendedDensePolynomial as _;
+use oracle::constants::PlonkSpongeConstantsKimchi as SC;
+use oracle::sponge::DefaultFqSponge;
+use oracle::FqSponge as _;
+use rand::{Rng, SeedableRng};
+
+type Sponge = DefaultFqSponge<VestaParameters, SC>;
+
+/// A proof, with the evaluations of the commitments it opens
+struct Opening {
+    eval_points: Vec<Fp>,
+    evaluations: Vec<MultiPointEvaluation<Vesta>>,
+    polyscale: Fp,
+    evalscale: Fp,
+    proof: MultiPointOpeningProof<Vesta>,
+}
+
+impl Opening {
+    fn verify_type(&self, sponge: &Sponge) -> MultiPointEvaluationProof<'_, Vesta, Sponge> {
+        MultiPointEvaluationProof {
+            sponge: sponge.clone(),
+            evaluations: self
+                .evaluations
+                .iter()
+                .map(|e| MultiPointEvaluation {
+                    commitment: e.commitment.clone(),
+                    points: e.points.clone(),
+                    evaluations: e.evaluations.clone(),
+                })
+                .collect(),
+            evaluation_points: self.eval_points.clone(),
+            polyscale: self.polyscale,
+            evalscale: self.evalscale,
+            opening: &self.proof,
+        }
+    }
+}
+
+/// Opens 9 polynomials of random degree (possibly chunked),
+/// each at a random non-empty subset of 5 random points
+fn open(srs: &SRS<Vesta>, sponge: &Sponge, rng: &mut rand_chacha::ChaCha20Rng) -> Opening {
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let eval_points: Vec<Fp> = (0..5).map(|_| Fp::rand(rng)).collect();
+
+    let mut polys = vec![];
+    let mut evaluations = vec![];
+    for _ in 0..9 {
+        let len = rng.gen_range(0..300);
+        let poly = if len == 0 {
+            DensePolynomial::<Fp>::zero()
+        } else {
+            DensePolynomial::<Fp>::rand(len, rng)
+        };
+        let points: Vec<_> = loop {
+            let points: Vec<_> = (0..eval_points.len()).filter(|_| rng.gen()).collect();
+            if !points.is_empty() {
+                break points;
+            }
+        };
+
+        let BlindedCommitment {
+            commitment,
+            blinders,
+        } = srs.commit(&poly, None, rng);
+        evaluations.push(MultiPointEvaluation {
+            commitment,
+            points: points.clone(),
+            evaluations: points
+                .iter()
+                .map(|i| {
+                    poly.to_chunked_polynomial(srs.g.len())
+                        .evaluate_chunks(eval_points[*i])
+                })
+                .collect(),
+        });
+        polys.push((poly, blinders, points));
+    }
+
+    let plnms: Vec<_> = polys
+        .iter()
+        .map(|(poly, blinders, points)| MultiPointPolynomial {
+            poly,
+            blinders: blinders.clone(),
+            points: points.clone(),
+        })
+        .collect();
+    let polyscale = Fp::rand(rng);
+    let evalscale = Fp::rand(rng);
+    let proof = srs.open_multi_point(
+        &group_map,
+        &plnms,
+        &eval_points,
+        polyscale,
+        evalscale,
+        sponge.clone(),
+        rng,
+    );
+
+    Opening {
+        eval_points,
+        evaluations,
+        polyscale,
+        evalscale,
+        proof,
+    }
+}
+
+#[test]
+fn test_multi_point_opening() {
+    let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(0);
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let sponge = Sponge::new(oracle::pasta::fq_kimchi::static_params());
+    let srs = SRS::<Vesta>::create(1 << 7);
+
+    // batch verify several proofs
+    let openings: Vec<_> = (0..3).map(|_| open(&srs, &sponge, &mut rng)).collect();
+    let mut batch: Vec<_> = openings.iter().map(|o| o.verify_type(&sponge)).collect();
+    assert!(srs.verify_multi_point(&group_map, &mut batch, &mut rng));
+
+    // a wrong evaluation is rejected
+    let mut batch = vec![openings[0].verify_type(&sponge)];
+    batch[0].evaluations[0].evaluations[0][0] += Fp::from(1u64);
+    assert!(!srs.verify_multi_point(&group_map, &mut batch, &mut rng));
+
+    // as is an evaluation at another point
+    let mut batch = vec![openings[0].verify_type(&sponge)];
+    let point = openings[0].evaluations[0].points[0];
+    batch[0].evaluation_points[point] += Fp::from(1u64);
+    assert!(!srs.verify_multi_point(&group_map, &mut batch, &mut rng));
+}
@@ -0,0 +1,88 @@
+use crate::{
+    commitment::{BatchEvaluationProof, CommitmentCurve, Evaluation},
+    scheme::PolynomialCommitment,
+    srs::SRS,
+};
+use ark_ff::UniformRand;
+use ark_poly::{univariate::DensePolynomial, UVPolynomial};
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use o1_utils::ExtendedDensePolynomial as _;
+use oracle::constants::PlonkSpongeConstantsKimchi as SC;
+use oracle::sponge::DefaultFqSponge;
+use oracle::FqSponge as _;
+use rand::SeedableRng;
+
+type Sponge = DefaultFqSponge<VestaParameters, SC>;
+
+/// Commits to two polynomials with `scheme`, opens them at two points and verifies the opening,
+/// with the evaluations of the first polynomial shifted by `error`.
+fn commit_open_verify<S: PolynomialCommitment<Vesta>>(scheme: &S, error: Fp) -> bool {
+    let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(0);
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let sponge = Sponge::new(oracle::pasta::fq_kimchi::static_params());
+
+    // the second polynomial spans two chunks
+    let size = scheme.max_poly_size();
+    let polys: Vec<_> = [size / 2, size + 3]
+        .into_iter()
+        .map(|len| DensePolynomial::<Fp>::rand(len - 1, &mut rng))
+        .collect();
+    let comms: Vec<_> = polys
+        .iter()
+        .map(|poly| scheme.commit(poly, None, &mut rng))
+        .collect();
+
+    let points = [Fp::rand(&mut rng), Fp::rand(&mut rng)];
+    let (polyscale, evalscale) = (Fp::rand(&mut rng), Fp::rand(&mut rng));
+    let plnms: Vec<_> = polys
+        .iter()
+        .zip(&comms)
+        .map(|(poly, comm)| (poly, None, comm.blinders.clone()))
+        .collect();
+    let proof = scheme.open(
+        &group_map,
+        &plnms,
+        &points,
+        polyscale,
+        evalscale,
+        sponge.clone(),
+        &mut rng,
+    );
+
+    let evaluations = polys
+        .iter()
+        .zip(comms)
+        .enumerate()
+        .map(|(i, (poly, comm))| Evaluation {
+            commitment: comm.commitment,
+            evaluations: points
+                .iter()
+                .map(|point| {
+                    let mut chunks = poly.to_chunked_polynomial(size).evaluate_chunks(*point);
+                    if i == 0 {
+                        chunks[0] += error;
+                    }
+                    chunks
+                })
+                .collect(),
+            degree_bound: None,
+        })
+        .collect();
+    let mut batch = [BatchEvaluationProof {
+        sponge,
+        evaluations,
+        evaluation_points: points.to_vec(),
+        polyscale,
+        evalscale,
+        opening: &proof,
+    }];
+    scheme.batch_verify(&group_map, &mut batch, &mut rng)
+}
+
+#[test]
+fn test_polynomial_commitment() {
+    let srs = SRS::<Vesta>::create(1 << 5);
+    assert!(commit_open_verify(&srs, Fp::from(0u64)));
+    assert!(!commit_open_verify(&srs, Fp::from(1u64)));
+}
@@ -0,0 +1,146 @@
+use crate::{
+    commitment::CommitmentCurve,
+    srs::{point_of_random_bytes, SRS},
+};
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{One, UniformRand, Zero};
+use ark_poly::{EvaluationDomain, Evaluations, Radix2EvaluationDomain as D};
+use blake2::{Blake2b512, Digest};
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Vesta};
+use rand::{rngs::StdRng, SeedableRng};
+use std::sync::Mutex;
+
+#[test]
+fn test_create_with_progress() {
+    // not a multiple of the number of generators per task
+    let depth = (1 << 11) + 5;
+    let reports = Mutex::new(vec![]);
+    let srs = SRS::<Vesta>::create_with_progress(depth, |done, total| {
+        assert_eq!(total, depth);
+        reports.lock().unwrap().push(done);
+    });
+    assert_eq!(srs.g.len(), depth);
+
+    // one report per batch of generators, the last one being complete
+    let reports = reports.into_inner().unwrap();
+    assert_eq!(reports.len(), 3);
+    assert_eq!(reports.iter().max(), Some(&depth));
+
+    // the generators are the same as when created one by one
+    let map = <Vesta as CommitmentCurve>::Map::setup();
+    for i in [0, 1, 1 << 10, depth - 1] {
+        let mut h = Blake2b512::new();
+        h.update((i as u32).to_be_bytes());
+        assert_eq!(
+            srs.g[i],
+            point_of_random_bytes::<Vesta>(&map, &h.finalize())
+        );
+    }
+}
+
+#[test]
+fn test_lagrange_basis() {
+    let n = 1 << 11;
+    let domain = D::<Fp>::new(n).unwrap();
+    let mut srs = SRS::<Vesta>::create(n);
+    srs.add_lagrange_basis(domain);
+
+    // the Lagrange basis sums to the commitment to 1
+    let sum = srs.lagrange_bases[&n]
+        .iter()
+        .fold(<Vesta as AffineCurve>::Projective::zero(), |acc, g| {
+            acc + g.into_projective()
+        });
+    assert_eq!(sum.into_affine(), srs.g[0].mul(Fp::one()).into_affine());
+}
+
+#[test]
+fn test_commit_evaluations() {
+    let rng = &mut StdRng::from_seed([0u8; 32]);
+    let n = 1 << 8;
+    let domain = D::<Fp>::new(n).unwrap();
+    let d4 = D::<Fp>::new(4 * n).unwrap();
+    let evals: Vec<_> = (0..n).map(|_| Fp::rand(rng)).collect();
+    let evals = Evaluations::from_vec_and_domain(evals, domain);
+    let evals4 = evals.interpolate_by_ref().evaluate_over_domain(d4);
+
+    // without Lagrange bases, the evaluations are interpolated
+    let mut srs = SRS::<Vesta>::create(n);
+    let expected = srs
+        .commit_non_hiding(&evals.interpolate_by_ref(), None)
+        .unshifted;
+    assert_eq!(
+        srs.commit_evaluations_non_hiding(domain, &evals, None)
+            .unshifted,
+        expected
+    );
+    assert_eq!(
+        srs.commit_evaluations_non_hiding(domain, &evals4, None)
+            .unshifted,
+        expected
+    );
+
+    // with them, the evaluations are committed to directly
+    srs.add_lagrange_basis(domain);
+    assert_eq!(
+        srs.commit_evaluations_non_hiding(domain, &evals, None)
+            .unshifted,
+        expected
+    );
+    assert_eq!(
+        srs.commit_evaluations_non_hiding(domain, &evals4, None)
+            .unshifted,
+        expected
+    );
+
+    // as well as evaluations covering only the first rows
+    let public = Evaluations::from_vec_and_domain(evals.evals[..3].to_vec(), domain);
+    assert_eq!(
+        srs.commit_evaluations_non_hiding(domain, &public, None)
+            .unshifted,
+        srs.commit_non_hiding(&public.interpolate_by_ref(), None)
+            .unshifted
+    );
+}
+
+#[test]
+fn test_commit_runs() {
+    let n = 1 << 8;
+    let domain = D::<Fp>::new(n).unwrap();
+    let runs = [
+        (Fp::from(5u8), 3),
+        (Fp::zero(), 10),
+        (Fp::from(7u8), 1),
+        (Fp::from(5u8), 20),
+    ];
+    let mut evals = vec![];
+    for (value, length) in runs {
+        evals.extend(std::iter::repeat(value).take(length));
+    }
+    evals.resize(n, Fp::zero());
+    let evals = Evaluations::from_vec_and_domain(evals, domain);
+
+    // the commitment is the one of the evaluations, with or without Lagrange bases
+    let mut srs = SRS::<Vesta>::create(n);
+    let expected = srs
+        .commit_evaluations_non_hiding(domain, &evals, None)
+        .unshifted;
+    assert_eq!(
+        srs.commit_runs_non_hiding(domain, &runs).unshifted,
+        expected
+    );
+    srs.add_lagrange_basis(domain);
+    assert_eq!(
+        srs.commit_runs_non_hiding(domain, &runs).unshifted,
+        expected
+    );
+
+    // including for a zero column
+    let zero = Evaluations::from_vec_and_domain(vec![Fp::zero(); n], domain);
+    assert_eq!(
+        srs.commit_runs_non_hiding(domain, &[]).unshifted,
+        srs.commit_evaluations_non_hiding(domain, &zero, None)
+            .unshifted
+    );
+}
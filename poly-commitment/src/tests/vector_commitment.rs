@@ -0,0 +1,66 @@
+use crate::{commitment::CommitmentCurve, srs::SRS};
+use ark_ff::{UniformRand, Zero};
+use ark_poly::{EvaluationDomain, Radix2EvaluationDomain as D};
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use oracle::constants::PlonkSpongeConstantsKimchi as SC;
+use oracle::sponge::DefaultFqSponge;
+use oracle::FqSponge as _;
+use rand::SeedableRng;
+
+type Sponge = DefaultFqSponge<VestaParameters, SC>;
+
+#[test]
+fn test_vector_commitment() {
+    let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(0);
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let sponge = Sponge::new(oracle::pasta::fq_kimchi::static_params());
+
+    let domain = D::<Fp>::new(1 << 7).unwrap();
+    let mut srs = SRS::<Vesta>::create(domain.size());
+    srs.add_lagrange_basis(domain);
+
+    // a vector shorter than the domain is padded with zeros
+    let values: Vec<_> = (0..100).map(|_| Fp::rand(&mut rng)).collect();
+    let commitment = srs.commit_vector(domain, &values, &mut rng);
+
+    let positions = [0, 5, 5, 99, 120];
+    let proof = srs.open_vector(
+        &group_map,
+        domain,
+        &values,
+        &commitment,
+        &positions,
+        sponge.clone(),
+        &mut rng,
+    );
+
+    let opened: Vec<_> = positions
+        .iter()
+        .map(|i| (*i, values.get(*i).copied().unwrap_or_else(Fp::zero)))
+        .collect();
+    let verify = |opened: &[(usize, Fp)], rng: &mut rand_chacha::ChaCha20Rng| {
+        srs.verify_vector(
+            &group_map,
+            domain,
+            &commitment.commitment,
+            opened,
+            &proof,
+            sponge.clone(),
+            rng,
+        )
+    };
+    assert!(verify(&opened, &mut rng));
+
+    // a wrong value is rejected
+    let mut wrong = opened.clone();
+    wrong[1].1 += Fp::from(1u64);
+    assert!(!verify(&wrong, &mut rng));
+
+    // as is a value at another position
+    let mut wrong = opened.clone();
+    wrong[3].0 = 98;
+    assert!(!verify(&wrong, &mut rng));
+    wrong[3].0 = domain.size();
+    assert!(!verify(&wrong, &mut rng));
+}
@@ -0,0 +1,149 @@
+//! This module implements vector commitments over the Lagrange basis.
+//!
+//! A vector `v` of at most `n` values (for a domain of size `n`, with generator `omega`)
+//! is committed to as the polynomial `p` such that `p(omega^i) = v_i`, directly from the
+//! commitments to the Lagrange basis of the domain (see [`SRS::add_lagrange_basis`]),
+//! so that committing requires no interpolation.
+//! Opening the vector at some positions is opening `p` at the corresponding powers of `omega`,
+//! with a single inner product argument whatever the number of positions.
+//!
+//! The scaling factor of the evaluation points is derived from the sponge, after absorbing
+//! the commitment and the opened positions and values, so that the sponge given to
+//! [`SRS::open_vector`] and [`SRS::verify_vector`] only needs to be in the same state.
+
+use crate::commitment::*;
+use crate::evaluation_proof::OpeningProof;
+use crate::srs::SRS;
+use ark_ff::{One, PrimeField};
+use ark_poly::{EvaluationDomain, Evaluations, Radix2EvaluationDomain as D};
+use oracle::FqSponge;
+use rand_core::{CryptoRng, RngCore};
+
+/// Pads `values` with zeros to the size of the domain.
+fn vector_evaluations<F: PrimeField>(domain: D<F>, values: &[F]) -> Evaluations<F, D<F>> {
+    assert!(
+        values.len() <= domain.size(),
+        "vector of {} values larger than the domain",
+        values.len()
+    );
+    let mut evals = values.to_vec();
+    evals.resize(domain.size(), F::zero());
+    Evaluations::from_vec_and_domain(evals, domain)
+}
+
+/// Absorbs the commitment and the opened values at the given points,
+/// and returns the scaling factor of the evaluation points.
+fn vector_evalscale<G, EFqSponge>(
+    sponge: &mut EFqSponge,
+    commitment: &PolyComm<G>,
+    points: &[G::ScalarField],
+    values: &[G::ScalarField],
+) -> G::ScalarField
+where
+    G: CommitmentCurve,
+    EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
+{
+    sponge.absorb_g(&commitment.unshifted);
+    sponge.absorb_fr(points);
+    sponge.absorb_fr(values);
+    sponge.challenge()
+}
+
+impl<G: CommitmentCurve> SRS<G> {
+    /// Commits to a vector of at most `domain.size()` values (padded with zeros),
+    /// using the Lagrange basis of the domain, which must have been added to the SRS.
+    pub fn commit_vector(
+        &self,
+        domain: D<G::ScalarField>,
+        values: &[G::ScalarField],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> BlindedCommitment<G> {
+        self.commit_evaluations(domain, &vector_evaluations(domain, values), None, rng)
+    }
+
+    /// This function opens a vector commitment at some positions
+    ///     domain: domain of the vector commitment
+    ///     values: committed vector
+    ///     commitment: commitment to the vector, as returned by [`SRS::commit_vector`]
+    ///     positions: positions to open the vector at
+    ///     oracle_params: parameters for the random oracle argument
+    ///     RETURN: commitment opening proof
+    #[allow(clippy::too_many_arguments)]
+    pub fn open_vector<EFqSponge, RNG>(
+        &self,
+        group_map: &G::Map,
+        domain: D<G::ScalarField>,
+        values: &[G::ScalarField],
+        commitment: &BlindedCommitment<G>,
+        positions: &[usize],
+        mut sponge: EFqSponge,
+        rng: &mut RNG,
+    ) -> OpeningProof<G>
+    where
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+        RNG: RngCore + CryptoRng,
+        G::BaseField: PrimeField,
+    {
+        let evals = vector_evaluations(domain, values);
+        let points: Vec<_> = positions.iter().map(|i| domain.element(*i)).collect();
+        let opened: Vec<_> = positions.iter().map(|i| evals.evals[*i]).collect();
+        let evalscale = vector_evalscale(&mut sponge, &commitment.commitment, &points, &opened);
+
+        let poly = evals.interpolate();
+        self.open(
+            group_map,
+            &[(&poly, None, commitment.blinders.clone())],
+            &points,
+            G::ScalarField::one(),
+            evalscale,
+            sponge,
+            rng,
+        )
+    }
+
+    /// This function verifies an opening of a vector commitment
+    ///     domain: domain of the vector commitment
+    ///     commitment: commitment to the vector
+    ///     opened: opened positions, with the values of the vector at these positions
+    ///     proof: opening proof, as returned by [`SRS::open_vector`]
+    ///     oracle_params: parameters for the random oracle argument
+    ///     randomness source context
+    ///     RETURN: verification status
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify_vector<EFqSponge, RNG>(
+        &self,
+        group_map: &G::Map,
+        domain: D<G::ScalarField>,
+        commitment: &PolyComm<G>,
+        opened: &[(usize, G::ScalarField)],
+        proof: &OpeningProof<G>,
+        mut sponge: EFqSponge,
+        rng: &mut RNG,
+    ) -> bool
+    where
+        EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
+        RNG: RngCore + CryptoRng,
+        G::BaseField: PrimeField,
+    {
+        if opened.iter().any(|(i, _)| *i >= domain.size()) {
+            return false;
+        }
+        let points: Vec<_> = opened.iter().map(|(i, _)| domain.element(*i)).collect();
+        let values: Vec<_> = opened.iter().map(|(_, v)| *v).collect();
+        let evalscale = vector_evalscale(&mut sponge, commitment, &points, &values);
+
+        let mut batch = vec![BatchEvaluationProof {
+            sponge,
+            evaluations: vec![Evaluation {
+                commitment: commitment.clone(),
+                evaluations: values.into_iter().map(|v| vec![v]).collect(),
+                degree_bound: None,
+            }],
+            evaluation_points: points,
+            polyscale: G::ScalarField::one(),
+            evalscale,
+            opening: proof,
+        }];
+        self.verify(group_map, &mut batch, rng)
+    }
+}
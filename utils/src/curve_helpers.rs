@@ -0,0 +1,44 @@
+//! Useful helper methods for the points of [ark_ec] curves.
+
+use ark_ec::ProjectiveCurve;
+use rayon::prelude::*;
+
+/// The number of points normalized at once by a thread,
+/// sharing a single field inversion
+const POINTS_PER_TASK: usize = 1 << 10;
+
+/// Converts projective points to their affine form,
+/// with a single field inversion per batch of points rather than one per point,
+/// and the batches spread over threads.
+pub fn batch_normalize<C: ProjectiveCurve>(points: &[C]) -> Vec<C::Affine> {
+    let mut points = points.to_vec();
+    points
+        .par_chunks_mut(POINTS_PER_TASK)
+        .for_each(C::batch_normalization);
+    points.par_iter().map(|p| p.into_affine()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ec::AffineCurve;
+    use ark_ff::Zero;
+    use mina_curves::pasta::{Fq, Pallas, ProjectivePallas};
+
+    #[test]
+    fn test_batch_normalize() {
+        // more points than a batch, and the point at infinity
+        let g = Pallas::prime_subgroup_generator();
+        let mut points: Vec<_> = (0..POINTS_PER_TASK as u64 + 5)
+            .map(|i| g.mul(Fq::from(i + 1)))
+            .collect();
+        points[3] = ProjectivePallas::zero();
+
+        let affine = batch_normalize(&points);
+        assert_eq!(affine.len(), points.len());
+        for (p, a) in points.iter().zip(&affine) {
+            assert_eq!(p.into_affine(), *a);
+        }
+        assert!(batch_normalize::<ProjectivePallas>(&[]).is_empty());
+    }
+}
@@ -1,7 +1,7 @@
 //! This adds a few utility functions for the [DensePolynomial] arkworks type.
 
-use ark_ff::Field;
-use ark_poly::{univariate::DensePolynomial, UVPolynomial};
+use ark_ff::{Field, Zero};
+use ark_poly::{univariate::DensePolynomial, EvaluationDomain, UVPolynomial};
 use rayon::prelude::*;
 
 use crate::chunked_polynomial::ChunkedPolynomial;
@@ -24,6 +24,29 @@ pub trait ExtendedDensePolynomial<F: Field> {
     /// Convert a polynomial into chunks.
     /// Implementors must ensure that the result contains at least 1 chunk.
     fn to_chunked_polynomial(&self, size: usize) -> ChunkedPolynomial<F>;
+
+    /// Divides the polynomial by `X - a` (synthetic division),
+    /// returning the quotient and the remainder, which is the evaluation at `a`.
+    fn divide_by_linear(&self, a: F) -> (Self, F)
+    where
+        Self: Sized;
+
+    /// Divides the polynomial by `X^n - c`, returning the quotient and the remainder.
+    /// This takes linear time, instead of the quasi-linear time of a generic division.
+    fn divide_by_binomial(&self, n: usize, c: F) -> (Self, Self)
+    where
+        Self: Sized;
+
+    /// Divides the polynomial by the vanishing polynomial `X^n - offset^n`
+    /// of the coset `offset * domain` (the subgroup `domain` itself if `offset` is one),
+    /// returning the quotient and the remainder.
+    fn divide_by_vanishing_coset<D: EvaluationDomain<F>>(
+        &self,
+        domain: D,
+        offset: F,
+    ) -> (Self, Self)
+    where
+        Self: Sized;
 }
 
 impl<F: Field> ExtendedDensePolynomial<F> for DensePolynomial<F> {
@@ -71,6 +94,57 @@ impl<F: Field> ExtendedDensePolynomial<F> for DensePolynomial<F> {
             size: chunk_size,
         }
     }
+
+    fn divide_by_linear(&self, a: F) -> (Self, F) {
+        let mut quotient = vec![F::zero(); self.coeffs.len().saturating_sub(1)];
+        let mut carry = F::zero();
+        for i in (1..self.coeffs.len()).rev() {
+            carry = self.coeffs[i] + carry * a;
+            quotient[i - 1] = carry;
+        }
+        let remainder = self.coeffs.first().map_or(F::zero(), |c0| *c0 + carry * a);
+        (DensePolynomial::from_coefficients_vec(quotient), remainder)
+    }
+
+    fn divide_by_binomial(&self, n: usize, c: F) -> (Self, Self) {
+        assert!(n > 0);
+        if self.coeffs.len() <= n {
+            return (DensePolynomial::zero(), self.clone());
+        }
+
+        // q_i = p_{i + n} + c q_{i + n}, computed a block of n coefficients at a time,
+        // from the highest block down
+        let mut quotient = self.coeffs[n..].to_vec();
+        let blocks = (quotient.len() + n - 1) / n;
+        for block in (0..blocks - 1).rev() {
+            let (low, high) = quotient.split_at_mut((block + 1) * n);
+            low[block * n..]
+                .par_iter_mut()
+                .zip(high.par_iter())
+                .for_each(|(q, q_high)| *q += c * q_high);
+        }
+
+        // r_i = p_i + c q_i for i < n
+        let mut remainder = self.coeffs[..n].to_vec();
+        remainder
+            .par_iter_mut()
+            .zip(quotient.par_iter())
+            .for_each(|(r, q)| *r += c * q);
+
+        (
+            DensePolynomial::from_coefficients_vec(quotient),
+            DensePolynomial::from_coefficients_vec(remainder),
+        )
+    }
+
+    fn divide_by_vanishing_coset<D: EvaluationDomain<F>>(
+        &self,
+        domain: D,
+        offset: F,
+    ) -> (Self, Self) {
+        let n = domain.size();
+        self.divide_by_binomial(n, offset.pow([n as u64]))
+    }
 }
 
 //
@@ -81,9 +155,48 @@ impl<F: Field> ExtendedDensePolynomial<F> for DensePolynomial<F> {
 mod tests {
     use super::*;
     use ark_ff::One;
-    use ark_poly::{univariate::DensePolynomial, UVPolynomial};
+    use ark_poly::{
+        univariate::DensePolynomial, EvaluationDomain, Polynomial, Radix2EvaluationDomain,
+        UVPolynomial,
+    };
     use mina_curves::pasta::Fp;
 
+    #[test]
+    fn test_divisions() {
+        let polynomial = |degree: u64, seed: u64| {
+            let coeffs = (0..=degree).map(|i| Fp::from(seed + i * i)).collect();
+            DensePolynomial::from_coefficients_vec(coeffs)
+        };
+        let p = polynomial(37, 3);
+
+        let a = Fp::from(42u64);
+        let (q, r) = p.divide_by_linear(a);
+        let x_minus_a = DensePolynomial::from_coefficients_slice(&[-a, Fp::one()]);
+        assert_eq!(
+            &(&q * &x_minus_a) + &DensePolynomial::from_coefficients_vec(vec![r]),
+            p
+        );
+        assert_eq!(r, p.evaluate(&a));
+
+        for n in [1, 5, 8, 37, 40] {
+            let c = Fp::from(n as u64 + 2);
+            let (q, r) = p.divide_by_binomial(n, c);
+            let mut binomial = vec![Fp::zero(); n + 1];
+            binomial[0] = -c;
+            binomial[n] = Fp::one();
+            let binomial = DensePolynomial::from_coefficients_vec(binomial);
+            assert!(r.degree() < n);
+            assert_eq!(&(&q * &binomial) + &r, p);
+        }
+
+        let domain = Radix2EvaluationDomain::<Fp>::new(8).unwrap();
+        let z = polynomial(20, 5);
+        let multiple = z.mul_by_vanishing_poly(domain);
+        let (q, r) = multiple.divide_by_vanishing_coset(domain, Fp::one());
+        assert_eq!(q, z);
+        assert!(r.is_zero());
+    }
+
     #[test]
     fn test_chunk() {
         let one = Fp::one();
@@ -1,8 +1,9 @@
 //! This adds a few utility functions for the [Evaluations] arkworks type.
 
 use ark_ff::FftField;
-use ark_poly::{Evaluations, Radix2EvaluationDomain};
+use ark_poly::{EvaluationDomain, Evaluations, Radix2EvaluationDomain};
 use rayon::prelude::*;
+use std::borrow::Cow;
 
 /// An extension for the [Evaluations] type.
 pub trait ExtendedEvaluations<F: FftField> {
@@ -18,6 +19,68 @@ pub trait ExtendedEvaluations<F: FftField> {
 
     /// Utility function for shifting poly along domain coordinate
     fn shift(&self, len: usize) -> Self;
+
+    /// Evaluates the interpolated polynomial over `domain`, which should be at least as large
+    /// as the domain of the evaluations (the evaluations are returned as is on the same domain).
+    fn lift(&self, domain: Radix2EvaluationDomain<F>) -> Self;
+
+    /// Adds the evaluations pointwise, over the larger of both domains
+    /// (the evaluations over the smaller domain are [lifted](ExtendedEvaluations::lift)).
+    fn add_lifted(&self, other: &Self) -> Self;
+
+    /// Subtracts the evaluations pointwise, over the larger of both domains.
+    fn sub_lifted(&self, other: &Self) -> Self;
+
+    /// Multiplies the evaluations pointwise, over the larger of both domains.
+    /// The domain should be large enough for the degree of the product.
+    fn mul_lifted(&self, other: &Self) -> Self;
+
+    /// Computes `self * other + addend` pointwise in a single pass,
+    /// over the largest of the three domains.
+    fn mul_add(&self, other: &Self, addend: &Self) -> Self;
+
+    /// Inverts each evaluation, with a single field inversion for all of them.
+    /// The evaluations equal to zero are left as zero.
+    fn batch_inverse(&self) -> Self;
+}
+
+/// Lifts `evals` to `domain` only if it is over another domain, to avoid a copy otherwise.
+fn lifted<'a, F: FftField>(
+    evals: &'a Evaluations<F, Radix2EvaluationDomain<F>>,
+    domain: Radix2EvaluationDomain<F>,
+) -> Cow<'a, [F]> {
+    if evals.domain().size() == domain.size() {
+        Cow::Borrowed(&evals.evals)
+    } else {
+        Cow::Owned(evals.lift(domain).evals)
+    }
+}
+
+/// Returns the largest of the domains of `evals`.
+fn largest_domain<F: FftField>(
+    evals: &[&Evaluations<F, Radix2EvaluationDomain<F>>],
+) -> Radix2EvaluationDomain<F> {
+    evals
+        .iter()
+        .map(|e| e.domain())
+        .max_by_key(|d| d.size())
+        .expect("at least one evaluation")
+}
+
+/// Combines `a` and `b` pointwise with `f`, over the larger of their domains.
+fn zip_lifted<F: FftField>(
+    a: &Evaluations<F, Radix2EvaluationDomain<F>>,
+    b: &Evaluations<F, Radix2EvaluationDomain<F>>,
+    f: impl Fn(F, F) -> F + Sync,
+) -> Evaluations<F, Radix2EvaluationDomain<F>> {
+    let domain = largest_domain(&[a, b]);
+    let (a, b) = (lifted(a, domain), lifted(b, domain));
+    let evals = a
+        .par_iter()
+        .zip(b.par_iter())
+        .map(|(a, b)| f(*a, *b))
+        .collect();
+    Evaluations::from_vec_and_domain(evals, domain)
 }
 
 impl<F: FftField> ExtendedEvaluations<F> for Evaluations<F, Radix2EvaluationDomain<F>> {
@@ -53,4 +116,86 @@ impl<F: FftField> ExtendedEvaluations<F> for Evaluations<F, Radix2EvaluationDoma
         result.evals.append(&mut tail);
         result
     }
+
+    fn lift(&self, domain: Radix2EvaluationDomain<F>) -> Self {
+        assert!(domain.size() >= self.domain().size());
+        if domain.size() == self.domain().size() {
+            return self.clone();
+        }
+        self.interpolate_by_ref().evaluate_over_domain(domain)
+    }
+
+    fn add_lifted(&self, other: &Self) -> Self {
+        zip_lifted(self, other, |a, b| a + b)
+    }
+
+    fn sub_lifted(&self, other: &Self) -> Self {
+        zip_lifted(self, other, |a, b| a - b)
+    }
+
+    fn mul_lifted(&self, other: &Self) -> Self {
+        zip_lifted(self, other, |a, b| a * b)
+    }
+
+    fn mul_add(&self, other: &Self, addend: &Self) -> Self {
+        let domain = largest_domain(&[self, other, addend]);
+        let (a, b, c) = (
+            lifted(self, domain),
+            lifted(other, domain),
+            lifted(addend, domain),
+        );
+        let evals = a
+            .par_iter()
+            .zip(b.par_iter())
+            .zip(c.par_iter())
+            .map(|((a, b), c)| *a * b + c)
+            .collect();
+        Evaluations::from_vec_and_domain(evals, domain)
+    }
+
+    fn batch_inverse(&self) -> Self {
+        let mut result = self.clone();
+        ark_ff::batch_inversion(&mut result.evals);
+        result
+    }
+}
+
+//
+// Tests
+//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::{Field, One, Zero};
+    use ark_poly::{univariate::DensePolynomial, UVPolynomial};
+    use mina_curves::pasta::Fp;
+
+    #[test]
+    fn test_lifted_arithmetic() {
+        let d1 = Radix2EvaluationDomain::<Fp>::new(8).unwrap();
+        let d4 = Radix2EvaluationDomain::<Fp>::new(32).unwrap();
+        let poly = |seed: u64| {
+            let coeffs = (0..8).map(|i| Fp::from(seed + i * i)).collect();
+            DensePolynomial::from_coefficients_vec(coeffs)
+        };
+        let (p, q, r) = (poly(1), poly(2), poly(3));
+        let p1 = p.evaluate_over_domain_by_ref(d1);
+        let q4 = q.evaluate_over_domain_by_ref(d4);
+        let r1 = r.evaluate_over_domain_by_ref(d1);
+
+        assert_eq!(p1.lift(d4), p.evaluate_over_domain_by_ref(d4));
+        assert_eq!(p1.add_lifted(&q4).interpolate(), &p + &q);
+        assert_eq!(q4.sub_lifted(&p1).interpolate(), &q - &p);
+        assert_eq!(p1.mul_lifted(&q4).interpolate(), &p * &q);
+        assert_eq!(p1.mul_add(&q4, &r1).interpolate(), &(&p * &q) + &r);
+
+        let inverses = p1.batch_inverse();
+        for (e, inv) in p1.evals.iter().zip(&inverses.evals) {
+            assert_eq!(*inv, e.inverse().unwrap());
+        }
+        let d = Radix2EvaluationDomain::<Fp>::new(2).unwrap();
+        let zero = Evaluations::from_vec_and_domain(vec![Fp::zero(), Fp::one()], d);
+        assert_eq!(zero.batch_inverse().evals, vec![Fp::zero(), Fp::one()]);
+    }
 }
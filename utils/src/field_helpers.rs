@@ -126,6 +126,15 @@ pub fn i32_to_field<F: From<u64> + Neg<Output = F>>(i: i32) -> F {
     }
 }
 
+/// Converts an [i64] into a [Field]
+pub fn i64_to_field<F: From<u64> + Neg<Output = F>>(i: i64) -> F {
+    if i >= 0 {
+        F::from(i as u64)
+    } else {
+        -F::from(i.unsigned_abs())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -284,4 +293,19 @@ mod tests {
             BaseField::from_biguint(big_zero_1).expect("Failed")
         );
     }
+
+    #[test]
+    fn field_from_i64() {
+        assert_eq!(i64_to_field::<BaseField>(0), BaseField::from(0u64));
+        assert_eq!(i64_to_field::<BaseField>(-5), -BaseField::from(5u64));
+        assert_eq!(
+            i64_to_field::<BaseField>(i64::MIN),
+            -BaseField::from(1u64 << 63)
+        );
+        assert_eq!(
+            i64_to_field::<BaseField>(i64::MAX),
+            BaseField::from(i64::MAX as u64)
+        );
+        assert_eq!(i64_to_field::<BaseField>(-7), i32_to_field::<BaseField>(-7));
+    }
 }
@@ -28,6 +28,29 @@ pub const SECP256K1_MOD: &[u8] = &[
     0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFE, 0xFF, 0xFF, 0xFC, 0x2F,
 ];
 
+/// The order of the group of secp256k1 points (in big endian)
+/// FFFFFFFF FFFFFFFF FFFFFFFF FFFFFFFE BAAEDCE6 AF48A03B BFD25E8C D0364141
+pub const SECP256K1_ORDER: &[u8] = &[
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFE,
+    0xBA, 0xAE, 0xDC, 0xE6, 0xAF, 0x48, 0xA0, 0x3B, 0xBF, 0xD2, 0x5E, 0x8C, 0xD0, 0x36, 0x41, 0x41,
+];
+
+/// The foreign field modulus of P-256 (a.k.a. secp256r1) is the prime number (in big endian)
+/// FFFFFFFF 00000001 00000000 00000000 00000000 FFFFFFFF FFFFFFFF FFFFFFFF
+/// given by the computation 2^256 - 2^224 + 2^192 + 2^96 - 1
+/// more information here <https://www.secg.org/sec2-v2.pdf>
+pub const P256_MOD: &[u8] = &[
+    0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+];
+
+/// The order of the group of P-256 points (in big endian)
+/// FFFFFFFF 00000000 FFFFFFFF FFFFFFFF BCE6FAAD A7179E84 F3B9CAC2 FC632551
+pub const P256_ORDER: &[u8] = &[
+    0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+    0xBC, 0xE6, 0xFA, 0xAD, 0xA7, 0x17, 0x9E, 0x84, 0xF3, 0xB9, 0xCA, 0xC2, 0xFC, 0x63, 0x25, 0x51,
+];
+
 /// Bit length of the foreign field modulus
 pub const FOREIGN_BITS: usize = 8 * SECP256K1_MOD.len(); // 256 bits
 
@@ -181,6 +204,14 @@ mod tests {
         assert_eq!(fe.to_big(), big);
     }
 
+    #[test]
+    fn test_p256_mod() {
+        let two = BigUint::from(2u32);
+        let expected = two.pow(256) - two.pow(224) + two.pow(192) + two.pow(96) - 1u32;
+        assert_eq!(BigUint::from_bytes_be(P256_MOD), expected);
+        assert!(BigUint::from_bytes_be(P256_ORDER) < expected);
+    }
+
     #[test]
     fn test_from_biguint() {
         let one = ForeignElement::<BaseField, 3>::from_be(&[0x01]);
@@ -4,6 +4,7 @@
 
 pub mod adjacent_pairs;
 pub mod chunked_polynomial;
+pub mod curve_helpers;
 pub mod dense_polynomial;
 pub mod evaluations;
 pub mod field_helpers;
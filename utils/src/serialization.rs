@@ -2,7 +2,9 @@
 //! [arkworks](http://arkworks.rs/) types that implement [CanonicalSerialize] and [CanonicalDeserialize].
 
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-use serde_with::Bytes;
+use serde::de::{Error, SeqAccess, Visitor};
+use serde_with::{de::DeserializeAsWrap, Bytes};
+use std::{fmt, marker::PhantomData};
 
 //
 // Serialization with serde
@@ -79,3 +81,101 @@ where
         T::deserialize(&mut &bytes[..]).map_err(serde::de::Error::custom)
     }
 }
+
+/// The error of a sequence deserialized with [Bounded] that is longer than its limit.
+/// As serde errors only carry the messages of the errors they are built from,
+/// it is recovered from a deserialization error with [LengthLimitExceeded::find].
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("{} {len} elements, more than the limit of {max}", PREFIX)]
+pub struct LengthLimitExceeded {
+    /// The length of the sequence (at least, if the format does not give it upfront)
+    pub len: usize,
+    /// The limit of the sequence
+    pub max: usize,
+}
+
+/// The start of the message of a [LengthLimitExceeded] error
+const PREFIX: &str = "length limit exceeded: the sequence has";
+
+impl LengthLimitExceeded {
+    /// Returns the [LengthLimitExceeded] error that `err` was built from, if any.
+    pub fn find(err: &impl fmt::Display) -> Option<Self> {
+        let message = err.to_string();
+        let (_, rest) = message.split_once(PREFIX)?;
+        let mut numbers = rest
+            .split(|c: char| !c.is_ascii_digit())
+            .filter(|n| !n.is_empty())
+            .map(str::parse);
+        Some(Self {
+            len: numbers.next()?.ok()?,
+            max: numbers.next()?.ok()?,
+        })
+    }
+}
+
+/// You can use [Bounded] with [serde_with] to deserialize a [Vec] of at most `MAX` elements,
+/// each deserialized with `T` (for example [SerdeAs]).
+/// The length is checked before the elements are deserialized (when the format provides it),
+/// so that untrusted inputs cannot trigger large allocations.
+/// A longer sequence fails with a [LengthLimitExceeded] error.
+/// Simply add annotations like `#[serde_as(as = "o1_utils::serialization::Bounded<SerdeAs, 16>")]`
+pub struct Bounded<T, const MAX: usize>(PhantomData<T>);
+
+impl<T, U, const MAX: usize> serde_with::SerializeAs<Vec<U>> for Bounded<T, MAX>
+where
+    T: serde_with::SerializeAs<U>,
+{
+    fn serialize_as<S>(val: &Vec<U>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        <Vec<T>>::serialize_as(val, serializer)
+    }
+}
+
+impl<'de, T, U, const MAX: usize> serde_with::DeserializeAs<'de, Vec<U>> for Bounded<T, MAX>
+where
+    T: serde_with::DeserializeAs<'de, U>,
+{
+    fn deserialize_as<D>(deserializer: D) -> Result<Vec<U>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct BoundedVisitor<T, U, const MAX: usize>(PhantomData<(T, U)>);
+
+        impl<'de, T, U, const MAX: usize> Visitor<'de> for BoundedVisitor<T, U, MAX>
+        where
+            T: serde_with::DeserializeAs<'de, U>,
+        {
+            type Value = Vec<U>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(formatter, "a sequence of at most {MAX} elements")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Vec<U>, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let len = seq.size_hint().unwrap_or(0);
+                if len > MAX {
+                    return Err(A::Error::custom(LengthLimitExceeded { len, max: MAX }));
+                }
+
+                let mut values = Vec::with_capacity(len);
+                while let Some(value) = seq.next_element::<DeserializeAsWrap<U, T>>()? {
+                    if values.len() == MAX {
+                        return Err(A::Error::custom(LengthLimitExceeded {
+                            len: MAX + 1,
+                            max: MAX,
+                        }));
+                    }
+                    values.push(value.into_inner());
+                }
+                Ok(values)
+            }
+        }
+
+        deserializer.deserialize_seq(BoundedVisitor::<T, U, MAX>(PhantomData))
+    }
+}